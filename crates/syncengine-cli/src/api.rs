@@ -0,0 +1,459 @@
+//! HTTP control API for `syncengine serve --api`
+//!
+//! A small JSON REST surface over the engine, for driving a headless node
+//! from other services:
+//!
+//! | Method | Path                                  | Body                  |
+//! |--------|---------------------------------------|-----------------------|
+//! | GET    | `/health`                             |                       |
+//! | GET    | `/realms`                             |                       |
+//! | POST   | `/realms`                             | `{"name"}`            |
+//! | GET    | `/realms/{realm_id}/tasks`            |                       |
+//! | POST   | `/realms/{realm_id}/tasks`            | `{"title"}`           |
+//! | POST   | `/realms/{realm_id}/tasks/{id}/toggle`|                       |
+//! | POST   | `/realms/{realm_id}/sync/pause`       |                       |
+//! | POST   | `/realms/{realm_id}/sync/resume`      |                       |
+//! | GET    | `/contacts`                           |                       |
+//! | POST   | `/messages`                           | `{"to", "content"}`   |
+//! | GET    | `/events` (WebSocket)                 |                       |
+//!
+//! `/events` streams engine activity as JSON text frames, one per event:
+//! `{"kind": "sync", "event": {...}}` for `SyncEvent`s and
+//! `{"kind": "contact", "event": {...}}` for `ContactEvent`s. Each client gets
+//! its own subscription; a client that falls behind loses the oldest events
+//! and is told so with `{"kind": "lagged", "stream": "sync", "skipped": n}`.
+//!
+//! Browsers let any page open a WebSocket to localhost, so `/events` refuses
+//! upgrades whose `Origin` header names anything but a loopback host.
+//! Non-browser clients that send no `Origin` are let through.
+//!
+//! `/health` answers 200 when the node is healthy and 503 otherwise, with the
+//! individual checks in the body either way.
+//!
+//! Errors come back as `{"error": "..."}` with a 4xx/5xx status. There is no
+//! authentication, so the API binds to localhost unless told otherwise.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use syncengine_core::types::contact::{ContactInfo, ContactStatus};
+use syncengine_core::{
+    ContactEvent, Did, HealthReport, RealmId, RealmInfo, SyncEngine, SyncError, SyncEvent, Task,
+    TaskId,
+};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, Mutex};
+
+/// Default address for `--api` without a value
+pub const DEFAULT_API_ADDR: &str = "127.0.0.1:7777";
+
+/// Engine shared between the API handlers and the serve loop
+pub type SharedEngine = Arc<Mutex<SyncEngine>>;
+
+/// Realm as returned by the API (IDs in base58)
+#[derive(Debug, Serialize)]
+pub struct RealmSummary {
+    pub id: String,
+    pub name: String,
+    pub is_shared: bool,
+    pub created_at: i64,
+    pub read_only: bool,
+}
+
+impl From<RealmInfo> for RealmSummary {
+    fn from(info: RealmInfo) -> Self {
+        Self {
+            id: info.id.to_base58(),
+            name: info.name,
+            is_shared: info.is_shared,
+            created_at: info.created_at,
+            read_only: info.read_only,
+        }
+    }
+}
+
+/// Contact as returned by the API, without topic or key material
+#[derive(Debug, Serialize)]
+pub struct ContactSummary {
+    pub did: String,
+    pub display_name: String,
+    pub status: ContactStatus,
+    pub is_favorite: bool,
+    pub last_seen: u64,
+}
+
+impl From<ContactInfo> for ContactSummary {
+    fn from(contact: ContactInfo) -> Self {
+        Self {
+            did: contact.peer_did,
+            display_name: contact.profile.display_name,
+            status: contact.status,
+            is_favorite: contact.is_favorite,
+            last_seen: contact.last_seen,
+        }
+    }
+}
+
+/// Health report as returned by the API
+#[derive(Debug, Serialize)]
+pub struct HealthSummary {
+    pub healthy: bool,
+    pub identity_initialized: bool,
+    pub networking_active: bool,
+    pub relay_reachable: Option<bool>,
+    pub syncing_realms: usize,
+    pub last_sync_age_secs: Option<u64>,
+    pub database_writable: bool,
+}
+
+impl From<HealthReport> for HealthSummary {
+    fn from(report: HealthReport) -> Self {
+        Self {
+            healthy: report.is_healthy(),
+            identity_initialized: report.identity_initialized,
+            networking_active: report.networking_active,
+            relay_reachable: report.relay_reachable,
+            syncing_realms: report.syncing_realms,
+            last_sync_age_secs: report.last_sync_age.map(|age| age.as_secs()),
+            database_writable: report.database_writable,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateRealm {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddTask {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendMessage {
+    to: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct MessageSent {
+    sequence: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RealmSyncState {
+    realm_id: String,
+    syncing: bool,
+    paused: bool,
+}
+
+/// One `/events` WebSocket frame
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum EventFrame {
+    Sync { event: SyncEvent },
+    Contact { event: ContactEvent },
+    /// The client fell behind and `skipped` events were dropped
+    Lagged { stream: &'static str, skipped: u64 },
+}
+
+/// Error response body: `{"error": "..."}`
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<SyncError> for ApiError {
+    fn from(err: SyncError) -> Self {
+        let status = match &err {
+            SyncError::RealmNotFound(_) | SyncError::TaskNotFound(_) | SyncError::ContactNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            SyncError::InvalidOperation(_)
+            | SyncError::PrivateRealmOperation(_)
+            | SyncError::InvalidDidFormat(_)
+            | SyncError::ContactKeyExchangeIncomplete { .. }
+            | SyncError::RecipientKeysMissing { .. } => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self {
+            status,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(serde_json::json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+fn parse_realm_id(s: &str) -> ApiResult<RealmId> {
+    RealmId::from_base58(s).map_err(|e| ApiError::bad_request(format!("Invalid realm ID '{}': {}", s, e)))
+}
+
+fn parse_task_id(s: &str) -> ApiResult<TaskId> {
+    TaskId::from_string(s).map_err(|e| ApiError::bad_request(format!("Invalid task ID '{}': {}", s, e)))
+}
+
+/// Build the API router over a shared engine.
+pub fn router(engine: SharedEngine) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/realms", get(list_realms).post(create_realm))
+        .route("/realms/{realm_id}/tasks", get(list_tasks).post(add_task))
+        .route("/realms/{realm_id}/tasks/{task_id}/toggle", post(toggle_task))
+        .route("/realms/{realm_id}/sync/pause", post(pause_sync))
+        .route("/realms/{realm_id}/sync/resume", post(resume_sync))
+        .route("/contacts", get(list_contacts))
+        .route("/messages", post(send_message))
+        .route("/events", get(events))
+        .with_state(engine)
+}
+
+/// Bind `addr` and serve the API until the task is dropped or aborted.
+///
+/// Returns the bound address (useful when `addr` has port 0) and the
+/// server task.
+pub async fn spawn(
+    addr: SocketAddr,
+    engine: SharedEngine,
+) -> std::io::Result<(SocketAddr, tokio::task::JoinHandle<std::io::Result<()>>)> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let bound = listener.local_addr()?;
+    let app = router(engine);
+    let task = tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok((bound, task))
+}
+
+async fn health(State(engine): State<SharedEngine>) -> (StatusCode, Json<HealthSummary>) {
+    let summary = HealthSummary::from(engine.lock().await.health());
+    let status = if summary.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(summary))
+}
+
+async fn list_realms(State(engine): State<SharedEngine>) -> ApiResult<Json<Vec<RealmSummary>>> {
+    let realms = engine.lock().await.list_realms().await?;
+    Ok(Json(realms.into_iter().map(RealmSummary::from).collect()))
+}
+
+async fn create_realm(
+    State(engine): State<SharedEngine>,
+    Json(body): Json<CreateRealm>,
+) -> ApiResult<(StatusCode, Json<RealmSummary>)> {
+    let mut engine = engine.lock().await;
+    let id = engine.create_realm(&body.name).await?;
+    let info = engine
+        .get_realm(&id)
+        .await?
+        .ok_or_else(|| SyncError::RealmNotFound(id.to_base58()))?;
+    Ok((StatusCode::CREATED, Json(info.into())))
+}
+
+async fn list_tasks(
+    State(engine): State<SharedEngine>,
+    Path(realm_id): Path<String>,
+) -> ApiResult<Json<Vec<Task>>> {
+    let realm_id = parse_realm_id(&realm_id)?;
+    let mut engine = engine.lock().await;
+    engine.open_realm(&realm_id).await?;
+    Ok(Json(engine.list_tasks(&realm_id)?))
+}
+
+async fn add_task(
+    State(engine): State<SharedEngine>,
+    Path(realm_id): Path<String>,
+    Json(body): Json<AddTask>,
+) -> ApiResult<(StatusCode, Json<Task>)> {
+    let realm_id = parse_realm_id(&realm_id)?;
+    let mut engine = engine.lock().await;
+    let task_id = engine.add_task(&realm_id, &body.title).await?;
+    let task = engine
+        .get_task(&realm_id, &task_id)?
+        .ok_or_else(|| SyncError::TaskNotFound(task_id.to_string_repr()))?;
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+async fn toggle_task(
+    State(engine): State<SharedEngine>,
+    Path((realm_id, task_id)): Path<(String, String)>,
+) -> ApiResult<Json<Task>> {
+    let realm_id = parse_realm_id(&realm_id)?;
+    let task_id = parse_task_id(&task_id)?;
+    let mut engine = engine.lock().await;
+    engine.open_realm(&realm_id).await?;
+    engine.toggle_task(&realm_id, &task_id).await?;
+    let task = engine
+        .get_task(&realm_id, &task_id)?
+        .ok_or_else(|| SyncError::TaskNotFound(task_id.to_string_repr()))?;
+    Ok(Json(task))
+}
+
+async fn pause_sync(
+    State(engine): State<SharedEngine>,
+    Path(realm_id): Path<String>,
+) -> ApiResult<Json<RealmSyncState>> {
+    let realm_id = parse_realm_id(&realm_id)?;
+    let mut engine = engine.lock().await;
+    engine.open_realm(&realm_id).await?;
+    engine.pause_sync(&realm_id)?;
+    Ok(Json(sync_state(&engine, &realm_id)))
+}
+
+async fn resume_sync(
+    State(engine): State<SharedEngine>,
+    Path(realm_id): Path<String>,
+) -> ApiResult<Json<RealmSyncState>> {
+    let realm_id = parse_realm_id(&realm_id)?;
+    let mut engine = engine.lock().await;
+    engine.open_realm(&realm_id).await?;
+    engine.resume_sync(&realm_id).await?;
+    Ok(Json(sync_state(&engine, &realm_id)))
+}
+
+fn sync_state(engine: &SyncEngine, realm_id: &RealmId) -> RealmSyncState {
+    RealmSyncState {
+        realm_id: realm_id.to_base58(),
+        syncing: engine.is_realm_syncing(realm_id),
+        paused: engine.is_sync_paused(realm_id),
+    }
+}
+
+async fn list_contacts(State(engine): State<SharedEngine>) -> ApiResult<Json<Vec<ContactSummary>>> {
+    let contacts = engine.lock().await.list_contacts(true)?;
+    Ok(Json(contacts.into_iter().map(ContactSummary::from).collect()))
+}
+
+async fn send_message(
+    State(engine): State<SharedEngine>,
+    Json(body): Json<SendMessage>,
+) -> ApiResult<Json<MessageSent>> {
+    Did::parse(&body.to).map_err(|e| ApiError::bad_request(format!("Invalid DID '{}': {}", body.to, e)))?;
+    let mut engine = engine.lock().await;
+    engine.init_profile_keys()?;
+    let sequence = engine.send_message(&body.to, &body.content).await?;
+    Ok(Json(MessageSent { sequence }))
+}
+
+async fn events(
+    State(engine): State<SharedEngine>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Some(origin) = headers.get(header::ORIGIN) {
+        if !origin.to_str().is_ok_and(is_loopback_origin) {
+            tracing::warn!(?origin, "Refused /events upgrade from non-loopback origin");
+            return ApiError {
+                status: StatusCode::FORBIDDEN,
+                message: "Origin not allowed".to_string(),
+            }
+            .into_response();
+        }
+    }
+
+    let (sync_rx, contact_rx) = {
+        let mut engine = engine.lock().await;
+        let sync_rx = engine.subscribe_events();
+        let contact_rx = match engine.subscribe_contact_events().await {
+            Ok(rx) => Some(rx),
+            Err(e) => {
+                tracing::warn!(error = %e, "Contact events unavailable on /events");
+                None
+            }
+        };
+        (sync_rx, contact_rx)
+    };
+    ws.on_upgrade(move |socket| forward_events(socket, sync_rx, contact_rx))
+}
+
+/// Whether an `Origin` header value names a loopback host, e.g.
+/// `http://localhost:3000` or `http://[::1]`. The opaque `null` origin is not.
+fn is_loopback_origin(origin: &str) -> bool {
+    let Some((_, rest)) = origin.split_once("://") else {
+        return false;
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Pump events into `socket` until either side goes away.
+async fn forward_events(
+    mut socket: WebSocket,
+    mut sync_rx: broadcast::Receiver<SyncEvent>,
+    mut contact_rx: Option<broadcast::Receiver<ContactEvent>>,
+) {
+    loop {
+        let frame = tokio::select! {
+            event = sync_rx.recv() => match event {
+                Ok(event) => EventFrame::Sync { event },
+                Err(RecvError::Lagged(skipped)) => EventFrame::Lagged { stream: "sync", skipped },
+                Err(RecvError::Closed) => break,
+            },
+            event = recv_contact(&mut contact_rx) => match event {
+                Ok(event) => EventFrame::Contact { event },
+                Err(RecvError::Lagged(skipped)) => EventFrame::Lagged { stream: "contact", skipped },
+                Err(RecvError::Closed) => {
+                    contact_rx = None;
+                    continue;
+                }
+            },
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Nothing to do with client messages; pings are answered by axum
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        let text = match serde_json::to_string(&frame) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to serialize event frame");
+                continue;
+            }
+        };
+        if socket.send(Message::Text(text.into())).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Receive from the contact stream, or wait forever if there isn't one.
+async fn recv_contact(
+    rx: &mut Option<broadcast::Receiver<ContactEvent>>,
+) -> Result<ContactEvent, RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
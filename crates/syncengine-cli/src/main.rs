@@ -8,16 +8,36 @@
 //! # Show node information
 //! syncengine info
 //!
+//! # Print this node's address and add it on another node, no discovery needed
+//! syncengine info --addr
+//! syncengine peers add <sync-addr:...>
+//!
+//! # See when a peer connected and disconnected
+//! syncengine peers status <endpoint_id> --history
+//!
 //! # Create a new realm
 //! syncengine realm create "My Tasks"
 //!
 //! # List all realms
 //! syncengine realm list
 //!
+//! # List members of a shared realm
+//! syncengine realm members <realm_id>
+//!
+//! # Leave a shared realm, keeping a read-only copy
+//! syncengine realm leave <realm_id> --keep-copy
+//!
+//! # Rotate a realm's key, locking out anyone not sent the new one
+//! syncengine realm rekey <realm_id>
+//!
+//! # Snapshot a realm and compare two snapshots
+//! syncengine realm snapshot <realm_id> "before cleanup"
+//! syncengine realm diff <realm_id> <snapshot_a> <snapshot_b>
+//!
 //! # Add a task to a realm
 //! syncengine task add <realm_id> "Buy groceries"
 //!
-//! # List tasks in a realm
+//! # List tasks in a realm (leave out the ID to pick the realm from a list)
 //! syncengine task list <realm_id>
 //!
 //! # Toggle task completion
@@ -40,16 +60,51 @@
 //!
 //! # List pending contact requests
 //! syncengine contact pending
+//!
+//! # Move contacts to another device
+//! syncengine contact export contacts.json
+//! syncengine contact import contacts.json
+//!
+//! # Run a node that also finds peers on the local network
+//! syncengine serve --lan
+//!
+//! # Run a node through a self-hosted relay
+//! syncengine serve --relay https://relay.example.org
+//!
+//! # Run a node with the HTTP control API on 127.0.0.1:7777
+//! syncengine serve --api
 //! ```
-
+//!
+//! ## Exit Codes
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Success |
+//! | 1 | Other failure |
+//! | 2 | Invalid input (bad arguments, operation not allowed) |
+//! | 4 | Not found (realm, task, snapshot, contact, peer) |
+//! | 69 | Network or gossip failure |
+//! | 77 | Identity, signature or key failure |
+
+mod api;
+mod picker;
+mod render;
+
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
 use anyhow::Result;
 use tokio::io::AsyncBufReadExt;
+
+use render::{Cell, Renderer, Tone};
 use clap::{Parser, Subcommand};
-use syncengine_core::{Did, PeerStatus, RealmId, SyncEngine, TaskId};
+use syncengine_core::{
+    ConnectionChange, ContactsBundle, Did, GossipConfig, ImageOrientation, NodeAddrBytes,
+    PeerStatus, RealmId, RealmTemplate, SnapshotId, SyncEngine, SyncError, TaskActivityKind,
+    TaskId, TranscriptFormat,
+};
 
 /// Synchronicity Engine - P2P Task Sharing
 #[derive(Parser)]
@@ -68,6 +123,11 @@ struct Cli {
     #[arg(short, long, global = true)]
     data_dir: Option<PathBuf>,
 
+    /// Show what a destructive command would change without changing it
+    /// (realm delete, identity regenerate)
+    #[arg(long, global = true)]
+    dry_run: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -75,7 +135,22 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Show node information
-    Info,
+    Info {
+        /// Print this node's full network address for sharing out of band
+        #[arg(long)]
+        addr: bool,
+
+        /// Sync all shared realms and print a JSON network snapshot for bug reports
+        #[arg(long, conflicts_with = "addr")]
+        debug: bool,
+
+        /// Seconds to wait for gossip neighbors before the snapshot (with --debug)
+        #[arg(long, default_value = "5", requires = "debug")]
+        wait: u64,
+    },
+
+    /// Check node health (exits nonzero if unhealthy)
+    Health,
 
     /// Identity management
     Identity {
@@ -137,11 +212,30 @@ enum Commands {
         action: LogsAction,
     },
 
+    /// Move the data directory somewhere else (pass --data-dir <path> afterwards)
+    Relocate {
+        /// New data directory; must be empty or not exist yet
+        #[arg(long)]
+        to: PathBuf,
+    },
+
     /// Start serving/syncing as a persistent P2P node
     Serve {
         /// Realm to sync (optional, can join/create realms via other commands)
         #[arg(short, long)]
         realm: Option<String>,
+        /// Discover peers on the local network via mDNS
+        #[arg(long)]
+        lan: bool,
+        /// Use this relay server instead of the default ones (http or https URL)
+        #[arg(long, conflicts_with = "no_relay")]
+        relay: Option<String>,
+        /// Never use a relay; only direct connections
+        #[arg(long)]
+        no_relay: bool,
+        /// Expose the HTTP control API on this address (default 127.0.0.1:7777)
+        #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = api::DEFAULT_API_ADDR)]
+        api: Option<std::net::SocketAddr>,
     },
 }
 
@@ -161,6 +255,19 @@ enum IdentityAction {
         #[arg(long)]
         force: bool,
     },
+    /// Export this identity, encrypted, to use it on another device too
+    Link,
+    /// Use an identity exported with `identity link` on this device
+    Join {
+        /// The encrypted identity bundle printed by `identity link`
+        bundle: String,
+        /// The key printed by `identity link`
+        #[arg(long)]
+        key: String,
+        /// Name for this device in the identity's device list
+        #[arg(long, default_value = "Linked device")]
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -169,6 +276,9 @@ enum RealmAction {
     Create {
         /// Name of the realm
         name: String,
+        /// Seed the realm with a template's tasks (built-in or from <data-dir>/templates)
+        #[arg(long)]
+        template: Option<String>,
     },
     /// List all realms
     List,
@@ -182,44 +292,123 @@ enum RealmAction {
         /// Realm ID (base58)
         realm_id: String,
     },
+    /// List members observed in a shared realm
+    Members {
+        /// Realm ID (base58)
+        realm_id: String,
+    },
+    /// Leave a shared realm and stop syncing it
+    Leave {
+        /// Realm ID (base58)
+        realm_id: String,
+        /// Keep a local read-only copy of the realm's tasks
+        #[arg(long)]
+        keep_copy: bool,
+    },
+    /// Stop reminders and other notifications for a realm (it keeps syncing)
+    Mute {
+        /// Realm ID (base58)
+        realm_id: String,
+    },
+    /// Turn notifications for a realm back on
+    Unmute {
+        /// Realm ID (base58)
+        realm_id: String,
+    },
+    /// Replace a realm's encryption key and send it to current members
+    Rekey {
+        /// Realm ID (base58)
+        realm_id: String,
+        /// DID of a departing member to leave off the new key (repeatable)
+        #[arg(long, value_name = "DID")]
+        exclude: Vec<String>,
+    },
+    /// Save a local snapshot of a realm's current tasks
+    Snapshot {
+        /// Realm ID (base58)
+        realm_id: String,
+        /// Label describing the snapshot
+        label: String,
+    },
+    /// List snapshots of a realm
+    Snapshots {
+        /// Realm ID (base58)
+        realm_id: String,
+    },
+    /// Show task changes between two snapshots
+    Diff {
+        /// Realm ID (base58)
+        realm_id: String,
+        /// Earlier snapshot ID
+        snapshot_a: String,
+        /// Later snapshot ID
+        snapshot_b: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum TaskAction {
     /// Add a task to a realm
+    #[command(allow_missing_positional = true)]
     Add {
-        /// Realm ID (base58)
-        realm_id: String,
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
         /// Task title
         title: String,
     },
     /// List tasks in a realm
     List {
-        /// Realm ID (base58)
-        realm_id: String,
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
     },
     /// Toggle task completion
+    #[command(allow_missing_positional = true)]
     Toggle {
-        /// Realm ID (base58)
-        realm_id: String,
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
         /// Task ID (ULID string)
         task_id: String,
     },
     /// Delete a task
+    #[command(allow_missing_positional = true)]
     Delete {
-        /// Realm ID (base58)
-        realm_id: String,
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
         /// Task ID (ULID string)
         task_id: String,
     },
+    /// Show what was done to a task and who each change claims as its author, oldest first
+    #[command(allow_missing_positional = true)]
+    History {
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
+        /// Task ID (ULID string)
+        task_id: String,
+    },
+    /// Assign a task to a realm member, or unassign it when no DID is given
+    Assign {
+        /// [REALM_ID] TASK_ID [DID]: realm ID (base58), picked from a list
+        /// when omitted in a terminal; task ID (ULID string); assignee DID
+        /// (did:sync:...)
+        #[arg(value_name = "ARGS", num_args = 1..=3, required = true)]
+        args: Vec<String>,
+    },
+    /// List tasks assigned to a realm member
+    #[command(allow_missing_positional = true)]
+    Assigned {
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
+        /// Assignee DID (did:sync:...)
+        did: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum InviteAction {
     /// Create an invite for a realm
     Create {
-        /// Realm ID (base58)
-        realm_id: String,
+        /// Realm ID (base58); pick from a list when omitted in a terminal
+        realm_id: Option<String>,
     },
     /// Join a realm via invite ticket
     Join {
@@ -230,6 +419,11 @@ enum InviteAction {
 
 #[derive(Subcommand)]
 enum PeersAction {
+    /// Add a peer from an address shared out of band
+    Add {
+        /// Node address (sync-addr:...), as printed by `info --addr`
+        addr: String,
+    },
     /// List all discovered peers
     List {
         /// Filter by status (online, offline, unknown)
@@ -240,6 +434,9 @@ enum PeersAction {
     Status {
         /// Peer endpoint ID (hex format)
         endpoint_id: String,
+        /// Also show when the peer connected and disconnected
+        #[arg(long)]
+        history: bool,
     },
     /// Manually attempt to connect to a peer
     Connect {
@@ -253,6 +450,14 @@ enum PeersAction {
         /// Nickname to set
         nickname: String,
     },
+    /// List the gossip neighbors on a realm's topic
+    Topic {
+        /// Realm ID (base58)
+        realm: String,
+        /// Seconds to wait for neighbors to show up
+        #[arg(long, default_value = "5")]
+        wait: u64,
+    },
 }
 
 #[derive(Subcommand)]
@@ -262,6 +467,12 @@ enum ContactCommands {
         /// Hours until invite expires (default: 24)
         #[arg(short, long, default_value = "24")]
         expiry_hours: u8,
+        /// Realm ID (base58) to share with whoever accepts the invite
+        #[arg(long)]
+        realm: Option<String>,
+        /// Greeting shown to whoever opens the invite (max 280 characters)
+        #[arg(long)]
+        greeting: Option<String>,
     },
 
     /// Accept a contact invitation
@@ -273,8 +484,29 @@ enum ContactCommands {
     /// List all contacts
     List,
 
+    /// Remove a contact and leave your shared topic
+    Remove {
+        /// DID of the contact to remove
+        did: String,
+        /// Keep the messages already received from them
+        #[arg(long)]
+        keep_messages: bool,
+    },
+
     /// List pending contact requests
     Pending,
+
+    /// Export contacts and their pinned profiles to a file
+    Export {
+        /// Output file (JSON)
+        path: PathBuf,
+    },
+
+    /// Import contacts from a file written by `contact export`
+    Import {
+        /// Bundle file (JSON)
+        path: PathBuf,
+    },
 }
 
 #[derive(Subcommand)]
@@ -387,6 +619,20 @@ enum ChatAction {
         /// Contact's DID
         did: String,
     },
+
+    /// Export a conversation to a transcript file
+    Export {
+        /// Contact's DID
+        did: String,
+
+        /// Output file
+        #[arg(short, long)]
+        out: PathBuf,
+
+        /// Transcript format: markdown or text (default: from the file extension)
+        #[arg(short, long)]
+        format: Option<String>,
+    },
 }
 
 /// Log management commands
@@ -409,6 +655,10 @@ enum LogsAction {
         /// Include trace-level logs (very verbose)
         #[arg(long)]
         trace: bool,
+
+        /// Add a per-realm timeline of sync events
+        #[arg(long)]
+        sync_timeline: bool,
     },
 
     /// Show a timeline view of recent logs
@@ -476,25 +726,158 @@ fn default_data_dir() -> PathBuf {
         .join("data")
 }
 
+/// Exit code for failures without a more specific category
+const EXIT_FAILURE: i32 = 1;
+/// Exit code for malformed arguments or an operation that isn't allowed
+const EXIT_INVALID_INPUT: i32 = 2;
+/// Exit code when a realm, task, snapshot, contact or peer doesn't exist
+const EXIT_NOT_FOUND: i32 = 4;
+/// Exit code when the network or gossip layer failed (sysexits EX_UNAVAILABLE)
+const EXIT_NETWORK: i32 = 69;
+/// Exit code for identity, signature and key failures (sysexits EX_NOPERM)
+const EXIT_AUTH: i32 = 77;
+
+/// Failures the CLI detects itself, rather than the engine
+#[derive(Debug)]
+enum CliError {
+    /// An argument couldn't be parsed or isn't one of the accepted values
+    InvalidInput(String),
+    /// Something named on the command line doesn't exist
+    NotFound(String),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::InvalidInput(msg) | CliError::NotFound(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Pick the process exit code for an error, so scripts can branch on the
+/// kind of failure
+fn exit_code(err: &anyhow::Error) -> i32 {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<SyncError>() {
+            return sync_error_exit_code(e);
+        }
+        if let Some(e) = cause.downcast_ref::<CliError>() {
+            return match e {
+                CliError::InvalidInput(_) => EXIT_INVALID_INPUT,
+                CliError::NotFound(_) => EXIT_NOT_FOUND,
+            };
+        }
+    }
+    EXIT_FAILURE
+}
+
+/// Exit code for each `SyncError` category
+fn sync_error_exit_code(err: &SyncError) -> i32 {
+    match err {
+        SyncError::RealmNotFound(_)
+        | SyncError::TaskNotFound(_)
+        | SyncError::SnapshotNotFound(_)
+        | SyncError::ContactNotFound(_) => EXIT_NOT_FOUND,
+
+        SyncError::InvalidInvite(_)
+        | SyncError::InvalidDidFormat(_)
+        | SyncError::InvalidOperation(_)
+        | SyncError::InvalidConfig(_)
+        | SyncError::InvalidImage(_)
+        | SyncError::IncompatibleDocument(_)
+        | SyncError::PrivateRealmOperation(_)
+        | SyncError::PayloadTooLarge { .. } => EXIT_INVALID_INPUT,
+
+        SyncError::Network(_) | SyncError::Gossip(_) | SyncError::PeerConnection(_) => {
+            EXIT_NETWORK
+        }
+
+        SyncError::Identity(_)
+        | SyncError::SignatureInvalid(_)
+        | SyncError::DecryptionFailed(_)
+        | SyncError::Crypto(_)
+        | SyncError::ContactKeyExchangeIncomplete { .. }
+        | SyncError::RecipientKeysMissing { .. } => EXIT_AUTH,
+
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// Build an invalid-input error for the user's arguments
+fn invalid_input(msg: String) -> anyhow::Error {
+    CliError::InvalidInput(msg).into()
+}
+
 /// Parse a realm ID from base58 string
 fn parse_realm_id(s: &str) -> Result<RealmId> {
-    RealmId::from_base58(s).map_err(|e| anyhow::anyhow!("Invalid realm ID '{}': {}", s, e))
+    RealmId::from_base58(s).map_err(|e| invalid_input(format!("Invalid realm ID '{}': {}", s, e)))
+}
+
+/// Use the given realm ID, or let the user pick one when it was left out
+///
+/// Picking needs a terminal; piped or scripted runs must pass the ID.
+async fn resolve_realm_id(engine: &SyncEngine, realm_id: Option<String>) -> Result<RealmId> {
+    if let Some(realm_id) = realm_id {
+        return parse_realm_id(&realm_id);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(invalid_input(
+            "No realm ID given. Pass one (see `realm list`), or run in a terminal to pick a realm."
+                .to_string(),
+        ));
+    }
+
+    let realms = engine.list_realms().await?;
+    if realms.is_empty() {
+        return Err(CliError::NotFound(
+            "No realms yet. Create one with `realm create`.".to_string(),
+        )
+        .into());
+    }
+    let picked = picker::pick_realm(&realms, &mut std::io::stdin().lock(), &mut std::io::stdout())?;
+    picked.ok_or_else(|| invalid_input("No realm selected".to_string()))
+}
+
+/// Split `task assign` arguments into realm ID, task ID and assignee DID
+///
+/// Both the realm and the DID are optional, so two arguments are a task and
+/// a DID when the second one is a DID, and a realm and a task otherwise.
+fn split_assign_args(args: Vec<String>) -> Result<(Option<String>, String, Option<String>)> {
+    let mut args = args.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(task_id), None, None) => Ok((None, task_id, None)),
+        (Some(task_id), Some(did), None) if did.starts_with("did:") => Ok((None, task_id, Some(did))),
+        (Some(realm_id), Some(task_id), did) => Ok((Some(realm_id), task_id, did)),
+        _ => Err(invalid_input("Expected [REALM_ID] TASK_ID [DID]".to_string())),
+    }
 }
 
 /// Parse a task ID from ULID string
 fn parse_task_id(s: &str) -> Result<TaskId> {
-    TaskId::from_string(s).map_err(|e| anyhow::anyhow!("Invalid task ID '{}': {}", s, e))
+    TaskId::from_string(s).map_err(|e| invalid_input(format!("Invalid task ID '{}': {}", s, e)))
+}
+
+/// Parse a snapshot ID from ULID string
+fn parse_snapshot_id(s: &str) -> Result<SnapshotId> {
+    SnapshotId::from_string(s)
+        .map_err(|e| invalid_input(format!("Invalid snapshot ID '{}': {}", s, e)))
 }
 
 /// Parse a peer endpoint ID from hex string
 fn parse_endpoint_id(s: &str) -> Result<iroh::PublicKey> {
-    let bytes = hex::decode(s).map_err(|e| anyhow::anyhow!("Invalid hex format: {}", e))?;
+    let bytes = hex::decode(s).map_err(|e| invalid_input(format!("Invalid hex format: {}", e)))?;
     if bytes.len() != 32 {
-        anyhow::bail!("Endpoint ID must be 32 bytes (got {})", bytes.len());
+        return Err(invalid_input(format!(
+            "Endpoint ID must be 32 bytes (got {})",
+            bytes.len()
+        )));
     }
     let mut array = [0u8; 32];
     array.copy_from_slice(&bytes);
-    iroh::PublicKey::from_bytes(&array).map_err(|e| anyhow::anyhow!("Invalid public key: {}", e))
+    iroh::PublicKey::from_bytes(&array)
+        .map_err(|e| invalid_input(format!("Invalid public key: {}", e)))
 }
 
 /// Parse peer status from string
@@ -503,27 +886,117 @@ fn parse_peer_status(s: &str) -> Result<PeerStatus> {
         "online" => Ok(PeerStatus::Online),
         "offline" => Ok(PeerStatus::Offline),
         "unknown" => Ok(PeerStatus::Unknown),
-        _ => anyhow::bail!(
+        _ => Err(invalid_input(format!(
             "Invalid status '{}'. Must be one of: online, offline, unknown",
             s
-        ),
+        ))),
+    }
+}
+
+/// Format a number of seconds as a short "2d 4h" / "3h 12m" / "5m" span
+fn format_remaining(secs: i64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
     }
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let cli = Cli::parse();
 
     setup_logging(cli.verbose);
 
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(exit_code(&e));
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let dry_run = cli.dry_run;
+    let supports_dry_run = matches!(
+        &cli.command,
+        Commands::Realm { action: RealmAction::Delete { .. } }
+            | Commands::Identity { action: IdentityAction::Regenerate { .. } }
+    );
+    if dry_run && !supports_dry_run {
+        return Err(invalid_input(
+            "--dry-run is only supported by: realm delete, identity regenerate".to_string(),
+        ));
+    }
+
     let data_dir = cli.data_dir.unwrap_or_else(default_data_dir);
+
+    // Relocation copies the data directory, so it must not be open
+    if let Commands::Relocate { to } = &cli.command {
+        let engine = SyncEngine::relocate(&data_dir, to).await?;
+        engine.shutdown().await?;
+        println!("Moved data directory to {}", to.display());
+        println!(
+            "Run with --data-dir {} from now on; {} can be removed",
+            to.display(),
+            data_dir.display()
+        );
+        return Ok(());
+    }
+
     let mut engine = SyncEngine::new(&data_dir).await?;
 
     // Initialize identity on startup so DID is always available
     engine.init_identity()?;
 
     match cli.command {
-        Commands::Info => {
+        Commands::Info { addr: true, .. } => {
+            engine.start_networking().await?;
+            let current_addr = || {
+                engine
+                    .node_addr()
+                    .ok_or_else(|| SyncError::Network("Networking did not start".to_string()))
+            };
+
+            // Direct addresses show up once the endpoint has probed its interfaces
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(3);
+            let mut addr = current_addr()?;
+            while addr.direct_addresses.is_empty() && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                addr = current_addr()?;
+            }
+            println!("{}", addr.encode()?);
+        }
+
+        Commands::Info { debug: true, wait, .. } => {
+            engine.start_networking().await?;
+            let mut shared = Vec::new();
+            for realm in engine.list_realms().await? {
+                if realm.is_shared {
+                    engine.open_realm(&realm.id).await?;
+                    engine.start_sync(&realm.id).await?;
+                    shared.push(realm.id);
+                }
+            }
+
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(wait);
+            let has_neighbors =
+                |engine: &SyncEngine| shared.iter().any(|id| !engine.topic_neighbors(id).is_empty());
+            while !shared.is_empty()
+                && !has_neighbors(&engine)
+                && std::time::Instant::now() < deadline
+            {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+            engine.process_pending_sync();
+
+            let snapshot = engine.network_debug_snapshot();
+            println!("{}", serde_json::to_string_pretty(&snapshot)?);
+        }
+
+        Commands::Info { .. } => {
+            engine.init_profile_keys()?;
             let info = engine.node_info().await?;
 
             println!("Synchronicity Engine v0.1.0");
@@ -545,12 +1018,52 @@ async fn main() -> Result<()> {
                 println!("  Relay: {}", relay);
             }
             println!();
+            println!("Profile:");
+            if let Some(did) = &info.profile_did {
+                println!("  DID: {}", did);
+            } else {
+                println!("  DID: (profile keys not initialized)");
+            }
+            match info.log_head {
+                Some(seq) => println!("  Log head: {}", seq),
+                None => println!("  Log head: (empty)"),
+            }
+            println!("  Outbox: {} unacknowledged", info.outbox_size);
+            println!("  Contacts: {}", info.contact_count);
+            println!("  Mirrored profiles: {}", info.mirror_count);
+            println!();
             println!("Data directory: {}", info.data_dir.display());
             println!("Realms: {}", info.realm_count);
             println!();
             println!("Status: Local mode (P2P not active)");
         }
 
+        Commands::Health => {
+            let health = engine.health();
+            let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+
+            println!("Identity initialized: {}", yes_no(health.identity_initialized));
+            println!("Networking active:    {}", yes_no(health.networking_active));
+            match health.relay_reachable {
+                Some(reachable) => println!("Relay reachable:      {}", yes_no(reachable)),
+                None => println!("Relay reachable:      n/a"),
+            }
+            println!("Syncing realms:       {}", health.syncing_realms);
+            match health.last_sync_age {
+                Some(age) => println!("Last sync:            {}s ago", age.as_secs()),
+                None => println!("Last sync:            never"),
+            }
+            println!("Database writable:    {}", yes_no(health.database_writable));
+            println!();
+
+            if health.is_healthy() {
+                println!("Status: healthy");
+            } else {
+                println!("Status: UNHEALTHY");
+                std::process::exit(1);
+            }
+        }
+
         Commands::Identity { action } => match action {
             IdentityAction::Show => {
                 if let Some(did) = engine.did() {
@@ -572,10 +1085,24 @@ async fn main() -> Result<()> {
                 if let Some(exported) = engine.export_public_key(&format) {
                     println!("{}", exported);
                 } else {
-                    anyhow::bail!("Identity not initialized");
+                    return Err(SyncError::Identity("Identity not initialized".to_string()).into());
                 }
             }
 
+            IdentityAction::Regenerate { force: _ } if dry_run => {
+                let preview = engine.regenerate_identity_preview()?;
+                println!("Dry run: identity would be regenerated.");
+                if let Some(did) = &preview.current_did {
+                    println!("  Replaced DID: {}", did);
+                }
+                if let Some(fingerprint) = &preview.current_key_fingerprint {
+                    println!("  Replaced key: {}", fingerprint);
+                }
+                println!("  Contacts who know the old DID: {}", preview.contact_count);
+                println!();
+                println!("Nothing was changed.");
+            }
+
             IdentityAction::Regenerate { force } => {
                 if !force {
                     println!("WARNING: Regenerating identity is IRREVERSIBLE!");
@@ -593,15 +1120,45 @@ async fn main() -> Result<()> {
                     println!("  New DID: {}", did);
                 }
             }
+
+            IdentityAction::Link => {
+                engine.init_profile_keys()?;
+                let link = engine.export_identity_encrypted()?;
+                println!("Bundle: {}", link.bundle);
+                println!("Key:    {}", link.link_key);
+                println!();
+                println!("On the other device, run:");
+                println!("  syncengine identity join <bundle> --key <key>");
+                println!("Anyone with both can act as you; send them over different channels.");
+            }
+
+            IdentityAction::Join { bundle, key, name } => {
+                let device = engine.import_identity_encrypted(&bundle, &key, &name)?;
+                let did = engine.did().expect("DID should exist after import");
+                println!("Joined identity as a linked device.");
+                println!("  DID: {}", did);
+                println!("  Device: {}", device);
+            }
         },
 
         Commands::Realm { action } => match action {
-            RealmAction::Create { name } => {
+            RealmAction::Create { name, template: None } => {
                 let id = engine.create_realm(&name).await?;
                 println!("Created realm: {}", name);
                 println!("  ID: {}", id.to_base58());
             }
 
+            RealmAction::Create { name, template: Some(template_name) } => {
+                let templates_dir = data_dir.join("templates");
+                let Some(template) = RealmTemplate::find(&template_name, Some(&templates_dir))? else {
+                    return Err(invalid_input(format!("Unknown template '{}'", template_name)));
+                };
+                let id = engine.create_realm_from_template(&name, &template).await?;
+                println!("Created realm: {} (from template {})", name, template.name);
+                println!("  ID: {}", id.to_base58());
+                println!("  Tasks: {}", template.tasks.len());
+            }
+
             RealmAction::List => {
                 let realms = engine.list_realms().await?;
                 if realms.is_empty() {
@@ -609,9 +1166,29 @@ async fn main() -> Result<()> {
                 } else {
                     println!("Realms ({}):", realms.len());
                     println!();
-                    for realm in realms {
-                        let shared = if realm.is_shared { " [shared]" } else { "" };
-                        println!("  {} {}{}", realm.id.to_base58(), realm.name, shared);
+                    let rows: Vec<_> = realms
+                        .iter()
+                        .map(|realm| {
+                            let mut flags = Vec::new();
+                            if realm.is_shared {
+                                flags.push(Cell::toned("[shared]", Tone::Cyan));
+                            }
+                            if realm.read_only {
+                                flags.push(Cell::toned("[read-only]", Tone::Dim));
+                            }
+                            if realm.muted {
+                                flags.push(Cell::toned("[muted]", Tone::Dim));
+                            }
+                            let mut row = vec![
+                                Cell::plain(realm.id.to_base58()),
+                                Cell::plain(realm.name.as_str()),
+                            ];
+                            row.extend(flags);
+                            row
+                        })
+                        .collect();
+                    for line in Renderer::detect().table(&rows) {
+                        println!("{}", line);
                     }
                 }
             }
@@ -623,6 +1200,12 @@ async fn main() -> Result<()> {
                         println!("Realm: {}", realm.name);
                         println!("  ID: {}", realm.id.to_base58());
                         println!("  Shared: {}", if realm.is_shared { "Yes" } else { "No" });
+                        if realm.read_only {
+                            println!("  Read-only: Yes");
+                        }
+                        if realm.muted {
+                            println!("  Muted: Yes");
+                        }
                         println!(
                             "  Created: {}",
                             chrono::DateTime::from_timestamp(realm.created_at, 0)
@@ -636,28 +1219,184 @@ async fn main() -> Result<()> {
                         println!("  Tasks: {}", tasks.len());
                     }
                     None => {
-                        anyhow::bail!("Realm not found: {}", realm_id);
+                        return Err(SyncError::RealmNotFound(realm_id.to_string()).into());
                     }
                 }
             }
 
+            RealmAction::Delete { realm_id } if dry_run => {
+                let id = parse_realm_id(&realm_id)?;
+                let preview = engine.delete_realm_preview(&id)?;
+                println!("Dry run: realm would be deleted.");
+                println!("  Realm: {} ({})", preview.name, realm_id);
+                println!("  Tasks: {}", preview.task_count);
+                println!("  Members: {}", preview.member_count);
+                println!("  Snapshots: {}", preview.snapshot_count);
+                if preview.is_shared {
+                    println!("  Shared: other members keep their copies");
+                }
+                println!();
+                println!("Nothing was changed.");
+            }
+
             RealmAction::Delete { realm_id } => {
                 let id = parse_realm_id(&realm_id)?;
                 engine.delete_realm(&id).await?;
                 println!("Deleted realm: {}", realm_id);
             }
+
+            RealmAction::Members { realm_id } => {
+                let id = parse_realm_id(&realm_id)?;
+                let members = engine.realm_members(&id)?;
+                let own_did = engine.did().map(|d| d.to_string());
+
+                if members.is_empty() {
+                    println!("No members observed yet.");
+                } else {
+                    println!("Members ({}):", members.len());
+                    println!();
+                    for member in members {
+                        let name = if own_did.as_deref() == Some(member.did.as_str()) {
+                            "(you)".to_string()
+                        } else {
+                            engine
+                                .get_pinned_profile(&member.did)?
+                                .map(|pin| pin.signed_profile.profile.display_name)
+                                .unwrap_or_else(|| "(unknown)".to_string())
+                        };
+                        let format_ts = |ts: i64| {
+                            chrono::DateTime::from_timestamp(ts, 0)
+                                .map(|dt| dt.to_rfc3339())
+                                .unwrap_or_else(|| ts.to_string())
+                        };
+                        println!("  {} {}", member.did, name);
+                        println!("    Joined: {}", format_ts(member.joined_at));
+                        println!("    Last seen: {}", format_ts(member.last_seen));
+                    }
+                }
+            }
+
+            RealmAction::Leave {
+                realm_id,
+                keep_copy,
+            } => {
+                let id = parse_realm_id(&realm_id)?;
+                engine.leave_realm(&id, keep_copy).await?;
+                if keep_copy {
+                    println!("Left realm: {} (read-only copy kept)", realm_id);
+                } else {
+                    println!("Left realm: {}", realm_id);
+                }
+            }
+
+            RealmAction::Mute { realm_id } => {
+                let id = parse_realm_id(&realm_id)?;
+                engine.set_realm_muted(&id, true)?;
+                println!("Muted realm: {}", realm_id);
+            }
+
+            RealmAction::Unmute { realm_id } => {
+                let id = parse_realm_id(&realm_id)?;
+                engine.set_realm_muted(&id, false)?;
+                println!("Unmuted realm: {}", realm_id);
+            }
+
+            RealmAction::Rekey { realm_id, exclude } => {
+                let id = parse_realm_id(&realm_id)?;
+                let exclude = exclude
+                    .iter()
+                    .map(|did| Did::parse(did))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !engine.is_networking_active() {
+                    engine.start_networking().await?;
+                }
+                let outcome = engine.rekey_realm(&id, &exclude).await?;
+                println!("Rekeyed realm: {}", realm_id);
+                println!("  Key epoch: {}", outcome.epoch);
+                println!("  New key sent to: {} member(s)", outcome.delivered.len());
+                for did in &exclude {
+                    println!("  Excluded: {}", did);
+                }
+                for did in &outcome.undelivered {
+                    println!("  Not reached: {} (re-invite to restore access)", did);
+                }
+            }
+
+            RealmAction::Snapshot { realm_id, label } => {
+                let id = parse_realm_id(&realm_id)?;
+                let snapshot_id = engine.snapshot_realm(&id, &label).await?;
+                println!("Created snapshot: {}", label);
+                println!("  ID: {}", snapshot_id.to_string_repr());
+            }
+
+            RealmAction::Snapshots { realm_id } => {
+                let id = parse_realm_id(&realm_id)?;
+                let snapshots = engine.list_realm_snapshots(&id)?;
+                if snapshots.is_empty() {
+                    println!("No snapshots found.");
+                } else {
+                    println!("Snapshots ({}):", snapshots.len());
+                    println!();
+                    for snapshot in snapshots {
+                        let created = chrono::DateTime::from_timestamp(snapshot.created_at, 0)
+                            .map(|dt| dt.to_rfc3339())
+                            .unwrap_or_else(|| snapshot.created_at.to_string());
+                        println!(
+                            "  {} {} ({})",
+                            snapshot.id.to_string_repr(),
+                            snapshot.label,
+                            created
+                        );
+                    }
+                }
+            }
+
+            RealmAction::Diff {
+                realm_id,
+                snapshot_a,
+                snapshot_b,
+            } => {
+                let id = parse_realm_id(&realm_id)?;
+                let a = parse_snapshot_id(&snapshot_a)?;
+                let b = parse_snapshot_id(&snapshot_b)?;
+                let diff = engine.diff_snapshots(&id, &a, &b)?;
+
+                if diff.is_empty() {
+                    println!("No differences.");
+                } else {
+                    for task in &diff.added {
+                        println!("+ {} {}", task.id.to_string_repr(), task.title);
+                    }
+                    for task in &diff.removed {
+                        println!("- {} {}", task.id.to_string_repr(), task.title);
+                    }
+                    for change in &diff.modified {
+                        println!("~ {} {}", change.id.to_string_repr(), change.title);
+                        for field in &change.fields {
+                            println!("    {}: {} -> {}", field.field, field.before, field.after);
+                        }
+                    }
+                    println!();
+                    println!(
+                        "{} added, {} removed, {} modified",
+                        diff.added.len(),
+                        diff.removed.len(),
+                        diff.modified.len()
+                    );
+                }
+            }
         },
 
         Commands::Task { action } => match action {
             TaskAction::Add { realm_id, title } => {
-                let id = parse_realm_id(&realm_id)?;
+                let id = resolve_realm_id(&engine, realm_id).await?;
                 let task_id = engine.add_task(&id, &title).await?;
                 println!("Added task: {}", title);
                 println!("  ID: {}", task_id.to_string_repr());
             }
 
             TaskAction::List { realm_id } => {
-                let id = parse_realm_id(&realm_id)?;
+                let id = resolve_realm_id(&engine, realm_id).await?;
                 engine.open_realm(&id).await?;
                 let tasks = engine.list_tasks(&id)?;
 
@@ -666,15 +1405,33 @@ async fn main() -> Result<()> {
                 } else {
                     println!("Tasks ({}):", tasks.len());
                     println!();
-                    for task in tasks {
-                        let status = if task.completed { "✓" } else { "○" };
-                        println!("  {} {} {}", status, task.id.to_string_repr(), task.title);
+                    let rows: Vec<_> = tasks
+                        .iter()
+                        .map(|task| {
+                            let mut row = vec![
+                                render::task_glyph(task.completed),
+                                Cell::plain(task.id.to_string_repr()),
+                                Cell::plain(task.title.as_str()),
+                            ];
+                            let assignee = match (&task.assignee_name, &task.assignee) {
+                                (Some(name), _) => Some(name.clone()),
+                                (None, Some(did)) => Some(did.to_string()),
+                                (None, None) => None,
+                            };
+                            if let Some(assignee) = assignee {
+                                row.push(Cell::toned(format!("-> {}", assignee), Tone::Cyan));
+                            }
+                            row
+                        })
+                        .collect();
+                    for line in Renderer::detect().table(&rows) {
+                        println!("{}", line);
                     }
                 }
             }
 
             TaskAction::Toggle { realm_id, task_id } => {
-                let rid = parse_realm_id(&realm_id)?;
+                let rid = resolve_realm_id(&engine, realm_id).await?;
                 let tid = parse_task_id(&task_id)?;
                 engine.toggle_task(&rid, &tid).await?;
 
@@ -690,16 +1447,79 @@ async fn main() -> Result<()> {
             }
 
             TaskAction::Delete { realm_id, task_id } => {
-                let rid = parse_realm_id(&realm_id)?;
+                let rid = resolve_realm_id(&engine, realm_id).await?;
                 let tid = parse_task_id(&task_id)?;
                 engine.delete_task(&rid, &tid).await?;
                 println!("Deleted task: {}", task_id);
             }
+
+            TaskAction::Assign { args } => {
+                let (realm_id, task_id, did) = split_assign_args(args)?;
+                let rid = resolve_realm_id(&engine, realm_id).await?;
+                let tid = parse_task_id(&task_id)?;
+                let assignee = did.as_deref().map(Did::parse).transpose()?;
+                engine.assign_task(&rid, &tid, assignee).await?;
+                match did {
+                    Some(did) => println!("Assigned task {} to {}", task_id, did),
+                    None => println!("Unassigned task {}", task_id),
+                }
+            }
+
+            TaskAction::Assigned { realm_id, did } => {
+                let rid = resolve_realm_id(&engine, realm_id).await?;
+                let assignee = Did::parse(&did)?;
+                engine.open_realm(&rid).await?;
+                let tasks = engine.list_tasks_assigned_to(&rid, &assignee)?;
+
+                if tasks.is_empty() {
+                    println!("No tasks assigned to {}.", did);
+                } else {
+                    println!("Assigned tasks ({}):", tasks.len());
+                    println!();
+                    for task in tasks {
+                        let status = if task.completed { "✓" } else { "○" };
+                        println!("  {} {} {}", status, task.id.to_string_repr(), task.title);
+                    }
+                }
+            }
+
+            TaskAction::History { realm_id, task_id } => {
+                let rid = resolve_realm_id(&engine, realm_id).await?;
+                let tid = parse_task_id(&task_id)?;
+                let history = engine.task_history(&rid, &tid).await?;
+
+                if history.is_empty() {
+                    println!("No history for task {}.", task_id);
+                } else {
+                    for activity in history {
+                        let when = activity
+                            .timestamp
+                            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                            .map(|dt| dt.to_rfc3339())
+                            .unwrap_or_else(|| "(unknown time)".to_string());
+                        let who = match activity.claimed_author.as_deref() {
+                            Some(did) => format!("claimed by {}", did),
+                            None => "(unknown author)".to_string(),
+                        };
+                        let what = match activity.kind {
+                            TaskActivityKind::Created { title } => format!("created \"{}\"", title),
+                            TaskActivityKind::Completed => "completed".to_string(),
+                            TaskActivityKind::Reopened => "reopened".to_string(),
+                            TaskActivityKind::Retitled { from, to } => {
+                                format!("retitled \"{}\" -> \"{}\"", from, to)
+                            }
+                            TaskActivityKind::Edited { fields } => format!("edited {}", fields.join(", ")),
+                            TaskActivityKind::Deleted => "deleted".to_string(),
+                        };
+                        println!("  {}  {}  {}", when, who, what);
+                    }
+                }
+            }
         },
 
         Commands::Invite { action } => match action {
             InviteAction::Create { realm_id } => {
-                let id = parse_realm_id(&realm_id)?;
+                let id = resolve_realm_id(&engine, realm_id).await?;
                 let ticket = engine.create_invite(&id).await?;
                 println!("Invite created:");
                 println!();
@@ -720,6 +1540,20 @@ async fn main() -> Result<()> {
         },
 
         Commands::Peers { action } => match action {
+            PeersAction::Add { addr } => {
+                let node_addr = NodeAddrBytes::decode(&addr)?;
+                let peer_id = engine.add_peer_node_addr(&node_addr)?;
+                let endpoint_id_hex = hex::encode(peer_id.as_bytes());
+                println!("Added peer {}", &endpoint_id_hex[..16]);
+                println!("  Full ID: {}", endpoint_id_hex);
+                if let Some(relay) = &node_addr.relay_url {
+                    println!("  Relay: {}", relay);
+                }
+                for direct in &node_addr.direct_addresses {
+                    println!("  Direct: {}", direct);
+                }
+            }
+
             PeersAction::List { status } => {
                 let peers = if let Some(status_str) = status {
                     let status = parse_peer_status(&status_str)?;
@@ -733,19 +1567,23 @@ async fn main() -> Result<()> {
                 } else {
                     println!("Discovered peers ({}):", peers.len());
                     println!();
-                    for peer in peers {
+                    let rows: Vec<_> = peers
+                        .iter()
+                        .map(|peer| {
+                            let nickname = peer.nickname.as_deref().unwrap_or("(unnamed)");
+                            vec![
+                                render::peer_glyph(peer.status),
+                                Cell::plain(&hex::encode(peer.endpoint_id)[..16]),
+                                Cell::plain(nickname),
+                                render::peer_status(peer.status),
+                                Cell::plain(format!("({} shared realms)", peer.shared_realms.len())),
+                            ]
+                        })
+                        .collect();
+                    let lines = Renderer::detect().table(&rows);
+                    for (peer, line) in peers.iter().zip(lines) {
                         let endpoint_id_hex = hex::encode(peer.endpoint_id);
-                        let nickname = peer.nickname.unwrap_or_else(|| "(unnamed)".to_string());
-                        let status = peer.status;
-                        let realms = peer.shared_realms.len();
-
-                        println!(
-                            "  {} - {} [{}] ({} shared realms)",
-                            &endpoint_id_hex[..16],
-                            nickname,
-                            status,
-                            realms
-                        );
+                        println!("{}", line);
                         println!("    Full ID: {}", endpoint_id_hex);
                         if !peer.shared_realms.is_empty() {
                             println!("    Shared realms:");
@@ -758,24 +1596,53 @@ async fn main() -> Result<()> {
                 }
             }
 
-            PeersAction::Status { endpoint_id } => {
+            PeersAction::Status {
+                endpoint_id,
+                history,
+            } => {
                 let peer_id = parse_endpoint_id(&endpoint_id)?;
                 match engine.peer_registry().get(&peer_id)? {
                     Some(peer) => {
-                        let nickname = peer.nickname.unwrap_or_else(|| "(unnamed)".to_string());
+                        let nickname = peer.nickname.clone().unwrap_or_else(|| "(unnamed)".to_string());
                         println!("Peer: {}", nickname);
                         println!("  Endpoint ID: {}", hex::encode(peer.endpoint_id));
                         println!("  Status: {}", peer.status);
                         println!("  Last seen: {} (Unix timestamp)", peer.last_seen);
+                        let backoff = peer.backoff();
+                        if backoff.consecutive_failures > 0 {
+                            println!(
+                                "  Reconnect backoff: {}s after {} failed attempt(s), next at {} (Unix timestamp)",
+                                backoff.delay_secs, backoff.consecutive_failures, backoff.next_attempt_at
+                            );
+                        }
                         println!("  Shared realms: {}", peer.shared_realms.len());
                         if !peer.shared_realms.is_empty() {
                             for realm_id in &peer.shared_realms {
                                 println!("    - {}", realm_id.to_base58());
                             }
                         }
+                        if history {
+                            let log = engine.peer_connection_log(&peer_id);
+                            println!("  Connection history ({} events):", log.len());
+                            for event in log {
+                                let at = chrono::DateTime::from_timestamp_millis(event.at_ms as i64)
+                                    .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+                                    .unwrap_or_else(|| event.at_ms.to_string());
+                                let change = match event.change {
+                                    ConnectionChange::Connected => "connected",
+                                    ConnectionChange::Disconnected => "disconnected",
+                                };
+                                let path = match event.direct {
+                                    Some(true) => " (direct)",
+                                    Some(false) => " (relay)",
+                                    None => "",
+                                };
+                                println!("    {} {}{}", at, change, path);
+                            }
+                        }
                     }
                     None => {
-                        anyhow::bail!("Peer not found: {}", endpoint_id);
+                        return Err(CliError::NotFound(format!("Peer not found: {}", endpoint_id)).into());
                     }
                 }
             }
@@ -805,7 +1672,11 @@ async fn main() -> Result<()> {
                         }
                     }
                 } else {
-                    anyhow::bail!("Peer not found in registry: {}", endpoint_id);
+                    return Err(CliError::NotFound(format!(
+                        "Peer not found in registry: {}",
+                        endpoint_id
+                    ))
+                    .into());
                 }
             }
 
@@ -823,16 +1694,64 @@ async fn main() -> Result<()> {
                     &endpoint_id[..16]
                 );
             }
+
+            PeersAction::Topic { realm, wait } => {
+                let realm_id = parse_realm_id(&realm)?;
+                engine.start_sync(&realm_id).await?;
+
+                let deadline = std::time::Instant::now() + std::time::Duration::from_secs(wait);
+                let mut neighbors = engine.topic_neighbors(&realm_id);
+                while neighbors.is_empty() && std::time::Instant::now() < deadline {
+                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                    neighbors = engine.topic_neighbors(&realm_id);
+                }
+
+                if neighbors.is_empty() {
+                    println!("No gossip neighbors on realm {}.", realm_id.to_base58());
+                } else {
+                    println!("Gossip neighbors on realm {} ({}):", realm_id.to_base58(), neighbors.len());
+                    for peer_id in &neighbors {
+                        let nickname = engine
+                            .peer_registry()
+                            .get(peer_id)?
+                            .and_then(|peer| peer.nickname)
+                            .unwrap_or_else(|| "(unnamed)".to_string());
+                        println!("  {} {}", hex::encode(peer_id.as_bytes()), nickname);
+                    }
+                }
+            }
         },
 
         Commands::Contact { cmd } => match cmd {
-            ContactCommands::GenerateInvite { expiry_hours } => {
-                let invite = engine.generate_contact_invite(expiry_hours).await?;
+            ContactCommands::GenerateInvite {
+                expiry_hours,
+                realm,
+                greeting,
+            } => {
+                let realm_id = realm.as_deref().map(parse_realm_id).transpose()?;
+                let invite = match (greeting, &realm_id) {
+                    (Some(greeting), realm_id) => {
+                        engine
+                            .generate_contact_invite_with_greeting(
+                                expiry_hours,
+                                greeting,
+                                realm_id.as_ref(),
+                            )
+                            .await?
+                    }
+                    (None, Some(id)) => {
+                        engine.generate_contact_invite_with_realm(expiry_hours, id).await?
+                    }
+                    (None, None) => engine.generate_contact_invite(expiry_hours).await?,
+                };
                 println!("Contact invitation generated:");
                 println!();
                 println!("{}", invite);
                 println!();
                 println!("Share this link to invite others to your contact list.");
+                if let Some(realm_id) = &realm {
+                    println!("Accepting it also joins realm {}.", realm_id);
+                }
                 println!();
                 println!("Expires in: {} hours", expiry_hours);
             }
@@ -846,7 +1765,10 @@ async fn main() -> Result<()> {
                         id
                     }
                     _ => {
-                        anyhow::bail!("Invalid invite code format. Expected 32-character hex string (16 bytes).");
+                        return Err(invalid_input(
+                            "Invalid invite code format. Expected 32-character hex string (16 bytes)."
+                                .to_string(),
+                        ));
                     }
                 };
 
@@ -857,7 +1779,7 @@ async fn main() -> Result<()> {
             }
 
             ContactCommands::List => {
-                let contacts = engine.list_contacts()?;
+                let contacts = engine.list_contacts(true)?;
 
                 if contacts.is_empty() {
                     println!("No contacts in your list.");
@@ -867,7 +1789,10 @@ async fn main() -> Result<()> {
                     for contact in contacts {
                         let status = contact.status;
                         let favorite = if contact.is_favorite { " ★" } else { "" };
-                        println!("  {} {}{}", contact.profile.display_name, status, favorite);
+                        println!("  {} {}{}", contact.display_name(), status, favorite);
+                        if contact.nickname.is_some() {
+                            println!("    Profile name: {}", contact.profile.display_name);
+                        }
                         println!("    DID: {}", contact.peer_did);
                         println!("    Connected: {}", contact.accepted_at);
                         println!("    Last seen: {} (Unix timestamp)", contact.last_seen);
@@ -879,9 +1804,24 @@ async fn main() -> Result<()> {
                 }
             }
 
+            ContactCommands::Remove { did, keep_messages } => {
+                engine.remove_contact(&did, keep_messages).await?;
+                println!("Removed contact {}", did);
+                if keep_messages {
+                    println!("Messages from them were kept.");
+                }
+            }
+
             ContactCommands::Pending => {
+                let now = chrono::Utc::now().timestamp();
+                let expired = engine.expire_stale_pending(now)?;
                 let (incoming, outgoing) = engine.list_pending_contacts()?;
 
+                if !expired.is_empty() {
+                    println!("Dropped {} expired request(s).", expired.len());
+                    println!();
+                }
+
                 if incoming.is_empty() && outgoing.is_empty() {
                     println!("No pending contact requests.");
                 } else {
@@ -892,6 +1832,7 @@ async fn main() -> Result<()> {
                             println!("  {} ({})", pending.profile.display_name, pending.state);
                             println!("    DID: {}", pending.peer_did);
                             println!("    Requested: {} (Unix timestamp)", pending.created_at);
+                            println!("    Expires in: {}", format_remaining(pending.remaining_secs(now)));
                             if let Some(subtitle) = &pending.profile.subtitle {
                                 println!("    {} ", subtitle);
                             }
@@ -909,14 +1850,45 @@ async fn main() -> Result<()> {
                             println!("  {} ({})", pending.profile.display_name, pending.state);
                             println!("    DID: {}", pending.peer_did);
                             println!("    Sent: {} (Unix timestamp)", pending.created_at);
+                            println!("    Expires in: {}", format_remaining(pending.remaining_secs(now)));
                             if let Some(subtitle) = &pending.profile.subtitle {
                                 println!("    {} ", subtitle);
                             }
+                            if let Some(greeting) = &pending.greeting {
+                                println!("    Greeting: \"{}\"", greeting);
+                            }
                             println!();
                         }
                     }
                 }
             }
+
+            ContactCommands::Export { path } => {
+                let bundle = engine.export_contacts()?;
+                std::fs::write(&path, bundle.to_json()?)
+                    .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+                println!("Exported {} contact(s) to {}", bundle.contacts.len(), path.display());
+            }
+
+            ContactCommands::Import { path } => {
+                let json = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+                let bundle = ContactsBundle::from_json(&json)?;
+                let report = engine.import_contacts(&bundle)?;
+
+                println!("Imported {} contact(s)", report.imported.len());
+                if !report.skipped.is_empty() {
+                    println!("Skipped {} existing contact(s)", report.skipped.len());
+                }
+                if !report.needs_key_exchange.is_empty() {
+                    println!();
+                    println!("Encryption keys could not be migrated for:");
+                    for did in &report.needs_key_exchange {
+                        println!("  {}", did);
+                    }
+                    println!("Re-exchange contact invites with them to restore E2E messaging.");
+                }
+            }
         },
 
         Commands::Profile { action } => match action {
@@ -968,16 +1940,18 @@ async fn main() -> Result<()> {
                     changed = true;
                 }
                 if let Some(avatar_path) = avatar {
-                    // Read and upload avatar
+                    // Read, crop to the golden ratio and upload avatar
                     let avatar_data = std::fs::read(&avatar_path)
                         .map_err(|e| anyhow::anyhow!("Failed to read avatar file: {}", e))?;
 
-                    let blob_id = engine.upload_avatar(avatar_data).await
+                    let avatar = engine
+                        .upload_avatar_cropped(avatar_data, ImageOrientation::Portrait)
+                        .await
                         .map_err(|e| anyhow::anyhow!("Failed to upload avatar: {}", e))?;
 
-                    profile.avatar_blob_id = Some(blob_id.clone());
+                    println!("Avatar uploaded: {}", avatar.blob_id);
+                    profile.avatar_blob_id = Some(avatar.blob_id);
                     changed = true;
-                    println!("Avatar uploaded: {}", blob_id);
                 }
 
                 if changed {
@@ -994,7 +1968,7 @@ async fn main() -> Result<()> {
             }
 
             ProfileAction::Get { did } => {
-                match engine.get_pinned_profile(&did)? {
+                match engine.get_verified_pinned_profile(&did)? {
                     Some(pin) => {
                         let p = &pin.signed_profile.profile;
                         println!("Profile for {}:", did);
@@ -1009,7 +1983,7 @@ async fn main() -> Result<()> {
                         println!();
                         println!("  Relationship: {:?}", pin.relationship);
                         println!("  Pinned At: {}", pin.pinned_at);
-                        println!("  ✓ Signature Valid: {}", pin.signed_profile.verify());
+                        println!("  ✓ Signature Valid: {}", pin.verified);
                     }
                     None => {
                         println!("No pinned profile found for DID: {}", did);
@@ -1075,7 +2049,8 @@ async fn main() -> Result<()> {
                     println!("  DID: {}", signed.did());
                     println!("  Name: {}", signed.profile.display_name);
                     println!();
-                    println!("Note: Profile will be broadcast when connected to the network.");
+                    println!("Note: Profile will be broadcast when connected to the network,");
+                    println!("and re-announced automatically whenever new peers join.");
                     println!("Use 'syncengine serve' to start the P2P network.");
                 }
             },
@@ -1149,7 +2124,7 @@ async fn main() -> Result<()> {
 
             PacketAction::Mirror { did } => {
                 let did = Did::from_str(&did)
-                    .map_err(|e| anyhow::anyhow!("Invalid DID: {}", e))?;
+                    .map_err(|e| invalid_input(format!("Invalid DID: {}", e)))?;
 
                 match engine.mirror_head(&did) {
                     Some(seq) => {
@@ -1281,24 +2256,47 @@ async fn main() -> Result<()> {
                         println!("  To: {}", did);
                         println!("  Content: {}", message);
                     }
-                    Err(e) => {
-                        // Check if this is the "recipient key lookup not implemented" error
-                        if e.to_string().contains("Recipient public key lookup not yet implemented") {
-                            println!("Cannot send message: Contact key exchange not complete.");
-                            println!();
-                            println!("To send encrypted messages, you need to:");
-                            println!("  1. Exchange contact invites with the recipient");
-                            println!("  2. Both parties must accept the contact request");
-                            println!("  3. Wait for key exchange to complete");
-                            println!();
-                            println!("Use 'syncengine contact generate-invite' to create an invite.");
-                        } else {
-                            return Err(e.into());
-                        }
+                    Err(e @ SyncError::ContactKeyExchangeIncomplete { .. }) => {
+                        println!("Cannot send message: Contact key exchange not complete.");
+                        println!();
+                        println!("To send encrypted messages, you need to:");
+                        println!("  1. Exchange contact invites with the recipient");
+                        println!("  2. Both parties must accept the contact request");
+                        println!("  3. Wait for key exchange to complete");
+                        println!();
+                        println!("Use 'syncengine contact generate-invite' to create an invite.");
+                        return Err(e.into());
                     }
+                    Err(e @ SyncError::RecipientKeysMissing { .. }) => {
+                        println!("Cannot send message: {} has no encryption keys.", did);
+                        println!();
+                        println!("This contact was added before encrypted messaging was supported.");
+                        println!("Exchange contact invites again to share encryption keys.");
+                        return Err(e.into());
+                    }
+                    Err(e) => return Err(e.into()),
                 }
             }
 
+            ChatAction::Export { did, out, format } => {
+                engine.init_profile_keys()?;
+
+                let format = match format {
+                    Some(name) => TranscriptFormat::parse(&name).ok_or_else(|| {
+                        invalid_input(format!("Unknown format '{}': use markdown or text", name))
+                    })?,
+                    None => match out.extension().and_then(|ext| ext.to_str()) {
+                        Some(ext) => TranscriptFormat::parse(ext).unwrap_or_default(),
+                        None => TranscriptFormat::default(),
+                    },
+                };
+
+                let file = std::fs::File::create(&out)?;
+                let mut writer = std::io::BufWriter::new(file);
+                let count = engine.export_conversation_to(&did, format, &mut writer)?;
+                println!("Exported {} messages to {}", count, out.display());
+            }
+
             ChatAction::Interactive { did } => {
                 engine.init_profile_keys()?;
                 engine.start_networking().await?;
@@ -1408,6 +2406,7 @@ async fn main() -> Result<()> {
                     output,
                     debug,
                     trace,
+                    sync_timeline,
                 } => {
                     let logs_dir = logs_dir.unwrap_or_else(|| default_logs_dir.clone());
                     let output_path = output.unwrap_or_else(|| PathBuf::from("LOGS.md"));
@@ -1415,6 +2414,7 @@ async fn main() -> Result<()> {
                     let options = ReportOptions {
                         include_trace: trace,
                         include_debug: debug,
+                        include_sync_timeline: sync_timeline,
                         ..Default::default()
                     };
 
@@ -1552,7 +2552,21 @@ async fn main() -> Result<()> {
             }
         }
 
-        Commands::Serve { realm } => {
+        Commands::Relocate { .. } => unreachable!("relocate runs before the engine is opened"),
+
+        Commands::Serve {
+            realm,
+            lan,
+            relay,
+            no_relay,
+            api,
+        } => {
+            // Validate the relay before doing anything else
+            let relay_url = relay
+                .as_deref()
+                .map(GossipConfig::parse_relay_url)
+                .transpose()?;
+
             println!("Starting Synchronicity Engine...");
             println!();
 
@@ -1563,6 +2577,12 @@ async fn main() -> Result<()> {
             println!();
 
             // Start gossip networking
+            engine.set_gossip_config(GossipConfig {
+                local_discovery: lan,
+                relay_url,
+                disable_relay: no_relay,
+                ..Default::default()
+            })?;
             engine.start_networking().await?;
             let info = engine.node_info().await?;
 
@@ -1572,6 +2592,11 @@ async fn main() -> Result<()> {
             }
             if let Some(relay) = &info.relay_url {
                 println!("  Relay: {}", relay);
+            } else if no_relay {
+                println!("  Relay: disabled (direct connections only)");
+            }
+            if lan {
+                println!("  LAN discovery: enabled");
             }
             println!();
 
@@ -1592,6 +2617,21 @@ async fn main() -> Result<()> {
 
             println!("Data directory: {}", info.data_dir.display());
             println!();
+
+            let engine = std::sync::Arc::new(tokio::sync::Mutex::new(engine));
+            let api_server = match api {
+                Some(addr) => {
+                    if !addr.ip().is_loopback() {
+                        println!("WARNING: the API has no authentication and is reachable beyond this machine");
+                    }
+                    let (bound, server) = api::spawn(addr, engine.clone()).await?;
+                    println!("API: http://{}", bound);
+                    println!();
+                    Some(server)
+                }
+                None => None,
+            };
+
             println!("Node is running. Press Ctrl+C to stop.");
             println!();
 
@@ -1607,11 +2647,18 @@ async fn main() -> Result<()> {
                         break;
                     }
                     _ = tokio::time::sleep(Duration::from_secs(1)) => {
+                        {
+                            let mut engine = engine.lock().await;
+                            engine.autosave();
+                            engine.fire_due_reminders();
+                        }
+
                         // Check if we should print status
                         if last_status.elapsed() >= status_interval {
                             last_status = std::time::Instant::now();
 
                             // Print status update
+                            let engine = engine.lock().await;
                             let realms = engine.list_realms().await?;
                             let syncing_count = realms.iter()
                                 .filter(|r| engine.is_realm_syncing(&r.id))
@@ -1628,6 +2675,13 @@ async fn main() -> Result<()> {
             }
 
             println!("Shutting down...");
+            if let Some(server) = api_server {
+                server.abort();
+                let _ = server.await;
+            }
+            let engine = std::sync::Arc::try_unwrap(engine)
+                .map_err(|_| anyhow::anyhow!("Engine still in use at shutdown"))?
+                .into_inner();
             engine.shutdown().await?;
             println!("Goodbye.");
         }
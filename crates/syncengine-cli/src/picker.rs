@@ -0,0 +1,84 @@
+//! Interactive realm selection
+//!
+//! Commands that act on one realm accept its base58 ID, which is tedious to
+//! type. When the ID is left out and stdin is a terminal, the realms are
+//! listed by number and the user picks one.
+
+use std::io::{self, BufRead, Write};
+
+use syncengine_core::{RealmId, RealmInfo};
+
+/// List `realms` on `output` and read a choice by number from `input`
+///
+/// Re-prompts until a number in range is entered. Returns `None` if input
+/// ends or an empty line is entered, i.e. the user backed out.
+pub fn pick_realm<R: BufRead, W: Write>(
+    realms: &[RealmInfo],
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<Option<RealmId>> {
+    writeln!(output, "Realms:")?;
+    for (index, realm) in realms.iter().enumerate() {
+        let shared = if realm.is_shared { " [shared]" } else { "" };
+        writeln!(output, "  {}) {}{}", index + 1, realm.name, shared)?;
+    }
+
+    loop {
+        write!(output, "Select a realm [1-{}]: ", realms.len())?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let choice = line.trim();
+        if choice.is_empty() {
+            return Ok(None);
+        }
+        match choice.parse::<usize>() {
+            Ok(n) if (1..=realms.len()).contains(&n) => return Ok(Some(realms[n - 1].id.clone())),
+            _ => writeln!(output, "Enter a number from 1 to {}.", realms.len())?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn realms() -> Vec<RealmInfo> {
+        ["Garden", "Workshop", "Kitchen"]
+            .into_iter()
+            .map(RealmInfo::new)
+            .collect()
+    }
+
+    #[test]
+    fn test_pick_realm_by_number() {
+        let realms = realms();
+        let mut input = io::Cursor::new("2\n");
+        let mut output = Vec::new();
+
+        let picked = pick_realm(&realms, &mut input, &mut output).unwrap();
+        assert_eq!(picked, Some(realms[1].id.clone()));
+
+        let shown = String::from_utf8(output).unwrap();
+        assert!(shown.contains("  1) Garden"));
+        assert!(shown.contains("  2) Workshop"));
+        assert!(shown.contains("Select a realm [1-3]"));
+    }
+
+    #[test]
+    fn test_pick_realm_reprompts_and_can_be_cancelled() {
+        let realms = realms();
+        let mut input = io::Cursor::new("0\nkitchen\n3\n");
+        let mut output = Vec::new();
+        let picked = pick_realm(&realms, &mut input, &mut output).unwrap();
+        assert_eq!(picked, Some(realms[2].id.clone()));
+        let shown = String::from_utf8(output).unwrap();
+        assert_eq!(shown.matches("Enter a number from 1 to 3.").count(), 2);
+
+        let mut input = io::Cursor::new("");
+        assert_eq!(pick_realm(&realms, &mut input, &mut Vec::new()).unwrap(), None);
+    }
+}
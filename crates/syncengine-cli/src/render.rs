@@ -0,0 +1,190 @@
+//! Styled, aligned output for the list commands
+//!
+//! Status glyphs use the design system's colors: moss for done or online,
+//! gold for open or unknown, cyan for people and sharing. Color is turned
+//! off when `NO_COLOR` is set (see <https://no-color.org>) or stdout isn't a
+//! terminal, so piped output stays plain text.
+
+use std::io::IsTerminal;
+
+use syncengine_core::PeerStatus;
+
+/// A design-system color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    /// `--moss-glow`: done, online
+    Moss,
+    /// `--gold`: open, unknown
+    Gold,
+    /// `--cyan`: people, sharing
+    Cyan,
+    /// Faint: secondary details
+    Dim,
+}
+
+impl Tone {
+    fn ansi(self) -> &'static str {
+        match self {
+            Tone::Moss => "\x1b[38;2;124;184;124m",
+            Tone::Gold => "\x1b[38;2;212;175;55m",
+            Tone::Cyan => "\x1b[38;2;0;212;170m",
+            Tone::Dim => "\x1b[2m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Applies color, or doesn't, consistently for one command's output
+#[derive(Debug, Clone, Copy)]
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    /// Color only if `NO_COLOR` is unset and stdout is a terminal
+    pub fn detect() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self::new(no_color, std::io::stdout().is_terminal())
+    }
+
+    /// Color only if `no_color` is false and output goes to a terminal
+    pub fn new(no_color: bool, is_tty: bool) -> Self {
+        Self {
+            color: !no_color && is_tty,
+        }
+    }
+
+    /// `text` in `tone`, or unchanged when color is off
+    pub fn paint(&self, tone: Tone, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", tone.ansi(), text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Render a table's rows, one line each, with columns aligned
+    ///
+    /// Widths are measured on the plain text, so color never shifts a
+    /// column. The last column isn't padded.
+    pub fn table(&self, rows: &[Vec<Cell>]) -> Vec<String> {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|col| {
+                rows.iter()
+                    .filter_map(|row| row.get(col))
+                    .map(|cell| cell.text.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        rows.iter()
+            .map(|row| {
+                let mut line = String::from(" ");
+                for (col, cell) in row.iter().enumerate() {
+                    line.push(' ');
+                    line.push_str(&match cell.tone {
+                        Some(tone) => self.paint(tone, &cell.text),
+                        None => cell.text.clone(),
+                    });
+                    if col + 1 < row.len() {
+                        let pad = widths[col] - cell.text.chars().count();
+                        line.extend(std::iter::repeat_n(' ', pad));
+                    }
+                }
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/// One table cell: plain text and an optional color
+#[derive(Debug, Clone)]
+pub struct Cell {
+    text: String,
+    tone: Option<Tone>,
+}
+
+impl Cell {
+    /// Uncolored text
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tone: None,
+        }
+    }
+
+    /// Text in `tone`
+    pub fn toned(text: impl Into<String>, tone: Tone) -> Self {
+        Self {
+            text: text.into(),
+            tone: Some(tone),
+        }
+    }
+}
+
+/// `✓` in moss for a completed task, `○` in gold for an open one
+pub fn task_glyph(completed: bool) -> Cell {
+    if completed {
+        Cell::toned("✓", Tone::Moss)
+    } else {
+        Cell::toned("○", Tone::Gold)
+    }
+}
+
+/// Glyph for a peer's connection status
+pub fn peer_glyph(status: PeerStatus) -> Cell {
+    let glyph = match status {
+        PeerStatus::Online => "●",
+        PeerStatus::Offline => "○",
+        PeerStatus::Unknown => "?",
+    };
+    Cell::toned(glyph, peer_tone(status))
+}
+
+/// A peer's connection status as a word, colored like its glyph
+pub fn peer_status(status: PeerStatus) -> Cell {
+    Cell::toned(status.to_string(), peer_tone(status))
+}
+
+fn peer_tone(status: PeerStatus) -> Tone {
+    match status {
+        PeerStatus::Online => Tone::Moss,
+        PeerStatus::Offline => Tone::Dim,
+        PeerStatus::Unknown => Tone::Gold,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<Cell>> {
+        vec![
+            vec![task_glyph(true), Cell::plain("01ARZ"), Cell::plain("Water")],
+            vec![task_glyph(false), Cell::plain("01B"), Cell::plain("Weed")],
+        ]
+    }
+
+    #[test]
+    fn test_no_color_has_no_ansi_codes() {
+        for renderer in [Renderer::new(true, true), Renderer::new(false, false)] {
+            let lines = renderer.table(&rows());
+            assert_eq!(lines, ["  ✓ 01ARZ Water", "  ○ 01B   Weed"]);
+            assert!(lines.iter().all(|line| !line.contains('\x1b')));
+        }
+    }
+
+    #[test]
+    fn test_tty_colors_glyphs_and_keeps_alignment() {
+        let lines = Renderer::new(false, true).table(&rows());
+        assert_eq!(
+            lines[0],
+            "  \x1b[38;2;124;184;124m✓\x1b[0m 01ARZ Water",
+            "completed tasks get a moss check"
+        );
+        assert_eq!(lines[1], "  \x1b[38;2;212;175;55m○\x1b[0m 01B   Weed");
+    }
+}
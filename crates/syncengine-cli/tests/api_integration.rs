@@ -0,0 +1,259 @@
+//! HTTP Control API Integration Tests
+//!
+//! These tests run `syncengine serve --api` as a child process and drive it
+//! over plain HTTP, checking that requests change the engine's state.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tungstenite::client::IntoClientRequest;
+
+// ============================================================================
+// Test Utilities
+// ============================================================================
+
+/// A running `syncengine serve --api` process, killed on drop
+struct ApiNode {
+    child: Child,
+    addr: SocketAddr,
+}
+
+impl ApiNode {
+    /// Start a node on an ephemeral port and wait for the API to come up
+    fn start(data_dir: &TempDir) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_syncengine"))
+            .arg("--data-dir")
+            .arg(data_dir.path())
+            .args(["serve", "--no-relay", "--api", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start syncengine serve");
+
+        let stdout = child.stdout.take().unwrap();
+        let addr = BufReader::new(stdout)
+            .lines()
+            .map_while(Result::ok)
+            .find_map(|line| line.strip_prefix("API: http://").map(|a| a.parse().unwrap()))
+            .expect("serve should print the API address");
+
+        Self { child, addr }
+    }
+
+    /// Send a request and return the status code and JSON body
+    fn request(&self, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let mut stream = TcpStream::connect(self.addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.addr,
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").expect("malformed HTTP response");
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = if body.is_empty() { Value::Null } else { serde_json::from_str(body).unwrap() };
+        (status, body)
+    }
+
+    fn get(&self, path: &str) -> (u16, Value) {
+        self.request("GET", path, None)
+    }
+
+    fn post(&self, path: &str, body: Value) -> (u16, Value) {
+        self.request("POST", path, Some(body))
+    }
+
+    /// Open the `/events` WebSocket
+    fn events(&self) -> tungstenite::WebSocket<TcpStream> {
+        self.events_from(None).expect("WebSocket handshake failed")
+    }
+
+    /// Open the `/events` WebSocket as a browser page at `origin` would
+    fn events_from(
+        &self,
+        origin: Option<&str>,
+    ) -> tungstenite::Result<tungstenite::WebSocket<TcpStream>> {
+        let stream = TcpStream::connect(self.addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        let mut request = format!("ws://{}/events", self.addr).into_client_request().unwrap();
+        if let Some(origin) = origin {
+            request.headers_mut().insert("Origin", origin.parse().unwrap());
+        }
+        tungstenite::client(request, stream)
+            .map(|(socket, _)| socket)
+            .map_err(|e| match e {
+                tungstenite::HandshakeError::Failure(e) => e,
+                tungstenite::HandshakeError::Interrupted(_) => panic!("blocking handshake interrupted"),
+            })
+    }
+}
+
+/// Read frames until one matches `pred`, panicking on timeout
+fn next_frame_matching(
+    socket: &mut tungstenite::WebSocket<TcpStream>,
+    pred: impl Fn(&Value) -> bool,
+) -> Value {
+    loop {
+        match socket.read().expect("no matching event frame") {
+            tungstenite::Message::Text(text) => {
+                let frame: Value = serde_json::from_str(&text).unwrap();
+                if pred(&frame) {
+                    return frame;
+                }
+            }
+            _ => continue,
+        }
+    }
+}
+
+impl Drop for ApiNode {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ============================================================================
+// API Tests
+// ============================================================================
+
+#[test]
+fn test_api_realm_and_task_roundtrip() {
+    let data_dir = TempDir::new().unwrap();
+    let node = ApiNode::start(&data_dir);
+
+    let (status, health) = node.get("/health");
+    assert_eq!(status, 200);
+    assert_eq!(health["healthy"], true);
+    assert_eq!(health["database_writable"], true);
+    assert_eq!(health["networking_active"], true);
+
+    let (status, realm) = node.post("/realms", json!({ "name": "Kitchen Garden" }));
+    assert_eq!(status, 201);
+    assert_eq!(realm["name"], "Kitchen Garden");
+    let realm_id = realm["id"].as_str().unwrap().to_string();
+
+    let (status, realms) = node.get("/realms");
+    assert_eq!(status, 200);
+    assert!(realms.as_array().unwrap().iter().any(|r| r["id"] == realm_id.as_str()));
+
+    let tasks_path = format!("/realms/{}/tasks", realm_id);
+    let (status, tasks) = node.get(&tasks_path);
+    assert_eq!(status, 200);
+    assert_eq!(tasks, json!([]));
+
+    let (status, task) = node.post(&tasks_path, json!({ "title": "Sow basil" }));
+    assert_eq!(status, 201);
+    assert_eq!(task["title"], "Sow basil");
+    assert_eq!(task["completed"], false);
+    let task_id = task["id"].as_str().unwrap().to_string();
+
+    let (status, tasks) = node.get(&tasks_path);
+    assert_eq!(status, 200);
+    let titles: Vec<_> = tasks.as_array().unwrap().iter().map(|t| t["title"].clone()).collect();
+    assert_eq!(titles, [json!("Sow basil")]);
+
+    let (status, task) = node.post(&format!("{}/{}/toggle", tasks_path, task_id), json!({}));
+    assert_eq!(status, 200);
+    assert_eq!(task["completed"], true);
+
+    let (_, tasks) = node.get(&tasks_path);
+    assert_eq!(tasks[0]["completed"], true);
+}
+
+#[test]
+fn test_api_pauses_and_resumes_realm_sync() {
+    let data_dir = TempDir::new().unwrap();
+    let node = ApiNode::start(&data_dir);
+
+    let (_, realm) = node.post("/realms", json!({ "name": "Seed Library" }));
+    let realm_id = realm["id"].as_str().unwrap().to_string();
+
+    let (status, state) = node.post(&format!("/realms/{}/sync/pause", realm_id), json!({}));
+    assert_eq!(status, 200);
+    assert_eq!(state["realm_id"], realm_id.as_str());
+    assert_eq!(state["paused"], true);
+
+    // Edits still land locally while paused
+    let (status, _) = node.post(&format!("/realms/{}/tasks", realm_id), json!({ "title": "Label jars" }));
+    assert_eq!(status, 201);
+
+    let (status, state) = node.post(&format!("/realms/{}/sync/resume", realm_id), json!({}));
+    assert_eq!(status, 200);
+    assert_eq!(state["paused"], false);
+
+    let (status, body) = node.post("/realms/not-a-realm/sync/pause", json!({}));
+    assert_eq!(status, 400);
+    assert!(body["error"].as_str().unwrap().contains("Invalid realm ID"));
+}
+
+#[test]
+fn test_api_reports_errors_as_json() {
+    let data_dir = TempDir::new().unwrap();
+    let node = ApiNode::start(&data_dir);
+
+    let (status, body) = node.get("/realms/not-a-realm/tasks");
+    assert_eq!(status, 400);
+    assert!(body["error"].as_str().unwrap().contains("Invalid realm ID"));
+
+    let (status, body) = node.post("/messages", json!({ "to": "nobody", "content": "hi" }));
+    assert_eq!(status, 400);
+    assert!(body["error"].as_str().unwrap().contains("Invalid DID"));
+
+    // Well-formed, but not a contact we can seal messages for
+    let (status, body) = node.post("/messages", json!({ "to": "did:sync:zStranger", "content": "hi" }));
+    assert_eq!(status, 400);
+    assert!(body["error"].is_string());
+
+    let (status, contacts) = node.get("/contacts");
+    assert_eq!(status, 200);
+    assert_eq!(contacts, json!([]));
+}
+
+#[test]
+fn test_api_event_stream_reports_task_changes_to_every_client() {
+    let data_dir = TempDir::new().unwrap();
+    let node = ApiNode::start(&data_dir);
+
+    let (_, realm) = node.post("/realms", json!({ "name": "Orchard" }));
+    let realm_id = realm["id"].as_str().unwrap().to_string();
+
+    let mut first = node.events();
+    let mut second = node.events();
+
+    let (status, task) = node.post(&format!("/realms/{}/tasks", realm_id), json!({ "title": "Prune apples" }));
+    assert_eq!(status, 201);
+    let task_id = task["id"].clone();
+
+    for socket in [&mut first, &mut second] {
+        let frame = next_frame_matching(socket, |f| f["kind"] == "sync" && f["event"]["type"] == "task_changed");
+        assert_eq!(frame["event"]["realm_id"], realm_id.as_str());
+        assert_eq!(frame["event"]["task_id"], task_id);
+    }
+}
+
+#[test]
+fn test_api_event_stream_refuses_foreign_origins() {
+    let data_dir = TempDir::new().unwrap();
+    let node = ApiNode::start(&data_dir);
+
+    match node.events_from(Some("https://evil.example")) {
+        Err(tungstenite::Error::Http(response)) => assert_eq!(response.status(), 403),
+        Err(e) => panic!("expected a 403 response, got {e}"),
+        Ok(_) => panic!("a foreign origin must not get the event stream"),
+    }
+    assert!(node.events_from(Some("null")).is_err());
+
+    for origin in ["http://localhost:3000", "http://127.0.0.1:8080", "http://[::1]"] {
+        assert!(node.events_from(Some(origin)).is_ok(), "{origin} should be allowed");
+    }
+}
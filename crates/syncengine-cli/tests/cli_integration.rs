@@ -52,7 +52,9 @@ fn test_info_command() {
         .success()
         .stdout(predicate::str::contains("Synchronicity Engine"))
         .stdout(predicate::str::contains("Identity:"))
-        .stdout(predicate::str::contains("DID:"));
+        .stdout(predicate::str::contains("DID:"))
+        .stdout(predicate::str::contains("Log head: (empty)"))
+        .stdout(predicate::str::contains("Contacts: 0"));
 }
 
 #[test]
@@ -66,6 +68,47 @@ fn test_info_shows_data_directory() {
         .stdout(predicate::str::contains("Data directory:"));
 }
 
+#[test]
+fn test_shared_addr_can_be_added_as_peer() {
+    let love_dir = TempDir::new().unwrap();
+    let joy_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&love_dir)
+        .args(["info", "--addr"])
+        .output()
+        .expect("Failed to run info --addr");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let addr = stdout
+        .lines()
+        .find(|line| line.starts_with("sync-addr:"))
+        .expect("info --addr should print a sync-addr")
+        .to_string();
+
+    let output = cli_cmd(&joy_dir)
+        .args(["peers", "add", &addr])
+        .output()
+        .expect("Failed to run peers add");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let endpoint_id = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("  Full ID: "))
+        .expect("peers add should print the full ID")
+        .to_string();
+
+    cli_cmd(&joy_dir)
+        .args(["peers", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(endpoint_id));
+
+    cli_cmd(&joy_dir)
+        .args(["peers", "add", "sync-invite:abc"])
+        .assert()
+        .failure();
+}
+
 // ============================================================================
 // Identity Command Tests
 // ============================================================================
@@ -146,6 +189,46 @@ fn test_identity_regenerate_with_force() {
     assert!(new_output.contains("DID:"));
 }
 
+#[test]
+fn test_identity_link_and_join() {
+    let desktop_dir = TempDir::new().unwrap();
+    let laptop_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&desktop_dir).args(["identity", "link"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let field = |label: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix(label))
+            .map(|value| value.trim().to_string())
+            .unwrap()
+    };
+    let (bundle, key) = (field("Bundle:"), field("Key:"));
+
+    // A wrong key doesn't open the bundle
+    cli_cmd(&laptop_dir)
+        .args(["identity", "join", &bundle, "--key", "11111111111111111111111111111111"])
+        .assert()
+        .failure();
+
+    cli_cmd(&laptop_dir)
+        .args(["identity", "join", &bundle, "--key", &key, "--name", "laptop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("linked device"));
+
+    let did = |dir: &TempDir| {
+        let output = cli_cmd(dir).args(["identity", "show"]).output().unwrap();
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find(|line| line.contains("DID:"))
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(did(&desktop_dir), did(&laptop_dir));
+}
+
 // ============================================================================
 // Realm Command Tests
 // ============================================================================
@@ -270,6 +353,248 @@ fn test_realm_delete() {
         .stdout(predicate::str::contains("No realms found"));
 }
 
+#[test]
+fn test_realm_delete_dry_run() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Keep Me"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let realm_id = extract_realm_id(&stdout).expect("Should find realm ID");
+    for title in ["Mend fence", "Feed hens"] {
+        cli_cmd(&data_dir)
+            .args(["task", "add", &realm_id, title])
+            .assert()
+            .success();
+    }
+
+    cli_cmd(&data_dir)
+        .args(["--dry-run", "realm", "delete", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Realm: Keep Me"))
+        .stdout(predicate::str::contains("Tasks: 2"))
+        .stdout(predicate::str::contains("Nothing was changed"));
+
+    // The realm and its tasks are still there
+    cli_cmd(&data_dir)
+        .args(["realm", "show", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tasks: 2"));
+
+    // Commands without a preview refuse to run rather than make changes
+    cli_cmd(&data_dir)
+        .args(["realm", "leave", &realm_id, "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--dry-run is only supported"));
+}
+
+#[test]
+fn test_realm_members_unshared() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Solo Realm"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let realm_id = extract_realm_id(&stdout).expect("Should find realm ID");
+
+    // A realm that never synced has no observed members
+    cli_cmd(&data_dir)
+        .args(["realm", "members", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No members observed yet"));
+}
+
+#[test]
+fn test_realm_leave_keep_copy() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Leaving Realm"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let realm_id = extract_realm_id(&stdout).expect("Should find realm ID");
+
+    cli_cmd(&data_dir)
+        .args(["realm", "leave", &realm_id, "--keep-copy"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("read-only copy kept"));
+
+    cli_cmd(&data_dir)
+        .args(["realm", "show", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Read-only: Yes"));
+
+    // Writes to the kept copy are refused
+    cli_cmd(&data_dir)
+        .args(["task", "add", &realm_id, "Too late"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("read-only"));
+}
+
+#[test]
+fn test_realm_mute_and_unmute() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Noisy Realm"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let realm_id = extract_realm_id(&stdout).expect("Should find realm ID");
+
+    cli_cmd(&data_dir)
+        .args(["realm", "mute", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Muted realm"));
+
+    cli_cmd(&data_dir)
+        .args(["realm", "show", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Muted: Yes"));
+
+    cli_cmd(&data_dir)
+        .args(["realm", "unmute", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unmuted realm"));
+
+    cli_cmd(&data_dir)
+        .args(["realm", "show", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Muted:").not());
+}
+
+#[test]
+fn test_serve_rejects_malformed_relay() {
+    let data_dir = TempDir::new().unwrap();
+
+    cli_cmd(&data_dir)
+        .args(["serve", "--relay", "ftp://relay.example.org"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("http or https"));
+
+    cli_cmd(&data_dir)
+        .args(["serve", "--relay", "not a url"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Malformed relay URL"));
+}
+
+#[test]
+fn test_relocate_moves_data_directory() {
+    let data_dir = TempDir::new().unwrap();
+    let target = TempDir::new().unwrap();
+    let new_dir = target.path().join("moved");
+
+    cli_cmd(&data_dir)
+        .args(["realm", "create", "My Realm"])
+        .assert()
+        .success();
+
+    cli_cmd(&data_dir)
+        .arg("relocate")
+        .arg("--to")
+        .arg(&new_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved data directory"));
+
+    // Refuses to relocate onto a directory that already has data
+    cli_cmd(&data_dir)
+        .arg("relocate")
+        .arg("--to")
+        .arg(&new_dir)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("is not empty"));
+
+    Command::cargo_bin("syncengine")
+        .unwrap()
+        .arg("--data-dir")
+        .arg(&new_dir)
+        .args(["realm", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("My Realm"));
+}
+
+#[test]
+fn test_realm_rekey() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Rekey Realm"])
+        .output()
+        .unwrap();
+    let realm_id = extract_realm_id(&String::from_utf8_lossy(&output.stdout)).unwrap();
+
+    cli_cmd(&data_dir)
+        .args(["realm", "rekey", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Key epoch: 1"));
+
+    cli_cmd(&data_dir)
+        .args(["realm", "rekey", &realm_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Key epoch: 2"));
+}
+
+#[test]
+fn test_realm_diff_snapshots() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Diff Realm"])
+        .output()
+        .unwrap();
+    let realm_id = extract_realm_id(&String::from_utf8_lossy(&output.stdout)).unwrap();
+
+    let snapshot = |label: &str| {
+        let output = cli_cmd(&data_dir)
+            .args(["realm", "snapshot", &realm_id, label])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        extract_realm_id(&String::from_utf8_lossy(&output.stdout)).unwrap()
+    };
+
+    let before = snapshot("before");
+    cli_cmd(&data_dir)
+        .args(["task", "add", &realm_id, "Sow cover crop"])
+        .assert()
+        .success();
+    let after = snapshot("after");
+
+    cli_cmd(&data_dir)
+        .args(["realm", "diff", &realm_id, &before, &after])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ "))
+        .stdout(predicate::str::contains("Sow cover crop"))
+        .stdout(predicate::str::contains("1 added, 0 removed, 0 modified"));
+}
+
 // ============================================================================
 // Task Command Tests
 // ============================================================================
@@ -319,6 +644,42 @@ fn test_task_list_empty() {
         .stdout(predicate::str::contains("No tasks in this realm"));
 }
 
+#[test]
+fn test_realm_commands_without_realm_id_need_terminal() {
+    let data_dir = TempDir::new().unwrap();
+
+    cli_cmd(&data_dir)
+        .args(["realm", "create", "Picker"])
+        .assert()
+        .success();
+
+    // stdin is piped here, so there is no picker to fall back on
+    let task_id = "01ARZ3NDEKTSV4RRFFQ69G5FAV";
+    for args in [
+        vec!["task", "list"],
+        vec!["task", "add", "Water seedlings"],
+        vec!["task", "toggle", task_id],
+        vec!["task", "delete", task_id],
+        vec!["task", "history", task_id],
+        vec!["task", "assign", task_id],
+        vec!["task", "assign", task_id, "did:sync:z6MkJoy"],
+        vec!["task", "assigned", "did:sync:z6MkJoy"],
+    ] {
+        cli_cmd(&data_dir)
+            .args(&args)
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("No realm ID given"));
+    }
+
+    cli_cmd(&data_dir)
+        .args(["invite", "create"])
+        .write_stdin("1\n")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("No realm ID given"));
+}
+
 #[test]
 fn test_task_list_with_tasks() {
     let data_dir = TempDir::new().unwrap();
@@ -353,6 +714,38 @@ fn test_task_list_with_tasks() {
         .stdout(predicate::str::contains("Task Two"));
 }
 
+#[test]
+fn test_task_list_no_color_is_plain_text() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Plain Output"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let realm_id = extract_realm_id(&stdout).expect("Should find realm ID");
+
+    cli_cmd(&data_dir)
+        .args(["task", "add", &realm_id, "Mulch the beds"])
+        .assert()
+        .success();
+
+    for args in [vec!["task", "list", &realm_id], vec!["realm", "list"]] {
+        cli_cmd(&data_dir)
+            .env("NO_COLOR", "1")
+            .args(&args)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains('\x1b').not());
+    }
+
+    cli_cmd(&data_dir)
+        .env("NO_COLOR", "1")
+        .args(["task", "list", &realm_id])
+        .assert()
+        .stdout(predicate::str::contains("○ "));
+}
+
 #[test]
 fn test_task_toggle() {
     let data_dir = TempDir::new().unwrap();
@@ -427,6 +820,60 @@ fn test_task_delete() {
         .stdout(predicate::str::contains("No tasks in this realm"));
 }
 
+#[test]
+fn test_task_assign() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["identity", "show"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let did = stdout
+        .split_whitespace()
+        .find(|word| word.starts_with("did:sync:"))
+        .expect("Should find DID")
+        .to_string();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Assign Test"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let realm_id = extract_realm_id(&stdout).expect("Should find realm ID");
+
+    let output = cli_cmd(&data_dir)
+        .args(["task", "add", &realm_id, "Fix the gate"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let task_id = extract_task_id(&stdout).expect("Should find task ID");
+
+    cli_cmd(&data_dir)
+        .args(["task", "assign", &realm_id, &task_id, &did])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Assigned task"));
+
+    cli_cmd(&data_dir)
+        .args(["task", "assigned", &realm_id, &did])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fix the gate"));
+
+    cli_cmd(&data_dir)
+        .args(["task", "assign", &realm_id, &task_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unassigned task"));
+
+    cli_cmd(&data_dir)
+        .args(["task", "assigned", &realm_id, &did])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No tasks assigned"));
+}
+
 #[test]
 fn test_task_toggle_nonexistent() {
     let data_dir = TempDir::new().unwrap();
@@ -663,6 +1110,68 @@ fn test_missing_required_args() {
     cli_cmd(&data_dir).args(["task", "add"]).assert().failure();
 }
 
+#[test]
+fn test_exit_code_not_found() {
+    let data_dir = TempDir::new().unwrap();
+
+    let output = cli_cmd(&data_dir)
+        .args(["realm", "create", "Exit Codes"])
+        .output()
+        .unwrap();
+    let realm_id = extract_realm_id(&String::from_utf8_lossy(&output.stdout))
+        .expect("Should find realm ID");
+
+    cli_cmd(&data_dir)
+        .args(["task", "toggle", &realm_id, "01ARZ3NDEKTSV4RRFFQ69G5FAV"])
+        .assert()
+        .code(4);
+
+    cli_cmd(&data_dir)
+        .args(["realm", "show", "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"])
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn test_exit_code_invalid_input() {
+    let data_dir = TempDir::new().unwrap();
+
+    cli_cmd(&data_dir)
+        .args(["realm", "show", "invalid-id"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid realm ID"));
+
+    cli_cmd(&data_dir)
+        .args(["--dry-run", "realm", "list"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_exit_code_network() {
+    let data_dir = TempDir::new().unwrap();
+
+    // Broadcasting needs profile gossip, which a one-shot command never starts
+    cli_cmd(&data_dir)
+        .args(["packet", "send-heartbeat"])
+        .assert()
+        .code(69)
+        .stderr(predicate::str::contains("Gossip error"));
+}
+
+#[test]
+fn test_exit_code_auth() {
+    let data_dir = TempDir::new().unwrap();
+
+    // No key exchange with this DID, so there is nothing to encrypt to
+    cli_cmd(&data_dir)
+        .args(["chat", "send", "did:sync:zNoSuchContact", "hello"])
+        .assert()
+        .code(77)
+        .stdout(predicate::str::contains("Contact key exchange not complete"));
+}
+
 #[test]
 fn test_help_works() {
     let data_dir = TempDir::new().unwrap();
@@ -0,0 +1,53 @@
+//! Benchmarks for pinned profile lookups
+//!
+//! Run with: cargo bench -p syncengine-core --bench profile_bench
+//!
+//! Compares resolving a contact list's pinned profiles one DID at a time
+//! against a single bulk lookup.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use syncengine_core::identity::HybridKeypair;
+use syncengine_core::types::{PinRelationship, ProfilePin, SignedProfile, UserProfile};
+use syncengine_core::Storage;
+
+/// On-disk storage with `count` pinned contact profiles, and their DIDs
+fn pinned_storage(count: usize) -> (Storage, Vec<String>, tempfile::TempDir) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let storage = Storage::new(temp_dir.path().join("bench.redb")).unwrap();
+    let keypair = HybridKeypair::generate();
+    let dids: Vec<String> = (0..count).map(|i| format!("did:sync:zContact{}", i)).collect();
+    for (i, did) in dids.iter().enumerate() {
+        let profile = UserProfile::new(format!("peer_{}", i), format!("Contact {}", i));
+        let signed = SignedProfile::sign(&profile, &keypair);
+        storage
+            .save_pinned_profile(&ProfilePin::new(did.clone(), signed, PinRelationship::Contact))
+            .unwrap();
+    }
+    (storage, dids, temp_dir)
+}
+
+fn bench_pinned_profile_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pinned_profiles");
+
+    for count in [10, 50, 100] {
+        let (storage, dids, _temp_dir) = pinned_storage(count);
+        let did_refs: Vec<&str> = dids.iter().map(String::as_str).collect();
+
+        group.bench_with_input(BenchmarkId::new("individual", count), &did_refs, |b, dids| {
+            b.iter(|| {
+                for did in dids {
+                    black_box(storage.load_pinned_profile(did).unwrap());
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("bulk", count), &did_refs, |b, dids| {
+            b.iter(|| black_box(storage.load_pinned_profiles(dids).unwrap()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(profile_benches, bench_pinned_profile_lookup);
+criterion_main!(profile_benches);
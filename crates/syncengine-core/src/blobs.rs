@@ -39,6 +39,9 @@
 use std::path::Path;
 
 use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
 use iroh::Endpoint;
 use iroh_blobs::store::fs::FsStore;
 use iroh_blobs::store::mem::MemStore;
@@ -440,6 +443,117 @@ impl BlobManager {
     }
 }
 
+/// The golden ratio, used for image proportions throughout the app
+pub const GOLDEN_RATIO: f64 = 1.618;
+
+/// Longest edge of an avatar after cropping, in pixels
+///
+/// Keeps re-encoded avatars well under [`MAX_AVATAR_SIZE`].
+pub const MAX_AVATAR_EDGE: u32 = 512;
+
+/// Longest edge of an avatar thumbnail, in pixels
+pub const MAX_AVATAR_THUMB_EDGE: u32 = 96;
+
+/// JPEG quality for re-encoded avatars
+const AVATAR_JPEG_QUALITY: u8 = 85;
+
+/// Which way a golden-ratio crop is oriented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageOrientation {
+    /// Portrait: 1:1.618 (height > width)
+    #[default]
+    Portrait,
+    /// Landscape: 1.618:1 (width > height)
+    Landscape,
+}
+
+/// Crop an image to the golden ratio
+///
+/// Keeps as much of the image as the proportion allows and centers the crop.
+pub fn crop_to_golden_ratio(img: &DynamicImage, orientation: ImageOrientation) -> DynamicImage {
+    let (width, height) = img.dimensions();
+
+    let (target_w, target_h) = match orientation {
+        ImageOrientation::Portrait => {
+            let new_h = (width as f64 * GOLDEN_RATIO) as u32;
+            if new_h <= height {
+                (width, new_h)
+            } else {
+                ((height as f64 / GOLDEN_RATIO) as u32, height)
+            }
+        }
+        ImageOrientation::Landscape => {
+            let new_w = (height as f64 * GOLDEN_RATIO) as u32;
+            if new_w <= width {
+                (new_w, height)
+            } else {
+                (width, (width as f64 / GOLDEN_RATIO) as u32)
+            }
+        }
+    };
+
+    let x = width.saturating_sub(target_w) / 2;
+    let y = height.saturating_sub(target_h) / 2;
+    img.crop_imm(x, y, target_w, target_h)
+}
+
+/// Decode an avatar, crop it to the golden ratio and re-encode it as JPEG
+///
+/// Images whose longest edge is over [`MAX_AVATAR_EDGE`] are scaled down
+/// after cropping.
+///
+/// # Errors
+///
+/// Returns `SyncError::InvalidImage` if `data` isn't a PNG, JPEG or WebP
+/// image, and `SyncError::Blob` if re-encoding fails.
+pub fn crop_avatar(data: &[u8], orientation: ImageOrientation) -> BlobResult<Vec<u8>> {
+    let img = decode_image(data)?;
+    let mut cropped = crop_to_golden_ratio(&img, orientation);
+    if cropped.width().max(cropped.height()) > MAX_AVATAR_EDGE {
+        cropped = cropped.resize(MAX_AVATAR_EDGE, MAX_AVATAR_EDGE, FilterType::Lanczos3);
+    }
+
+    let encoded = encode_avatar_jpeg(&cropped)?;
+    debug!(
+        width = cropped.width(),
+        height = cropped.height(),
+        size = encoded.len(),
+        "Cropped avatar"
+    );
+    Ok(encoded)
+}
+
+/// Scale an avatar down to a JPEG thumbnail for list views
+///
+/// The longest edge of the thumbnail is at most [`MAX_AVATAR_THUMB_EDGE`];
+/// the proportions are kept and small images are not scaled up.
+///
+/// # Errors
+///
+/// Returns `SyncError::InvalidImage` if `data` isn't a PNG, JPEG or WebP
+/// image, and `SyncError::Blob` if re-encoding fails.
+pub fn avatar_thumbnail(data: &[u8]) -> BlobResult<Vec<u8>> {
+    let img = decode_image(data)?;
+    let thumb = if img.width().max(img.height()) > MAX_AVATAR_THUMB_EDGE {
+        img.thumbnail(MAX_AVATAR_THUMB_EDGE, MAX_AVATAR_THUMB_EDGE)
+    } else {
+        img
+    };
+    encode_avatar_jpeg(&thumb)
+}
+
+fn decode_image(data: &[u8]) -> BlobResult<DynamicImage> {
+    image::load_from_memory(data).map_err(|e| SyncError::InvalidImage(e.to_string()))
+}
+
+fn encode_avatar_jpeg(img: &DynamicImage) -> BlobResult<Vec<u8>> {
+    let mut encoded = Vec::new();
+    JpegEncoder::new_with_quality(&mut encoded, AVATAR_JPEG_QUALITY)
+        .encode_image(&img.to_rgb8())
+        .map_err(|e| SyncError::Blob(format!("Failed to encode avatar: {}", e)))?;
+    Ok(encoded)
+}
+
 /// Protocol handler for blob transfers.
 ///
 /// This wraps the BlobsProtocol for integration with the iroh Router.
@@ -549,6 +663,17 @@ mod tests {
         assert_eq!(hash, parsed);
     }
 
+    #[test]
+    fn test_crop_to_golden_ratio_centers_crop() {
+        let img = DynamicImage::new_rgb8(1000, 1000);
+
+        let landscape = crop_to_golden_ratio(&img, ImageOrientation::Landscape);
+        assert_eq!(landscape.dimensions(), (1000, 618));
+
+        let portrait = crop_to_golden_ratio(&img, ImageOrientation::Portrait);
+        assert_eq!(portrait.dimensions(), (618, 1000));
+    }
+
     #[test]
     fn test_parse_invalid_hash() {
         // Invalid hex
@@ -0,0 +1,118 @@
+//! `@` mentions in chat messages
+//!
+//! A mention is `@` followed by a DID (`@did:sync:z...`) or a contact's
+//! nickname (`@joy`), at the start of the message or after whitespace.
+//! Callers decide what a handle resolves to; handles that resolve to
+//! nobody are left as plain text.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::identity::Did;
+
+/// A resolved mention within a message's content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mention {
+    /// Byte range of the mention in the content, `@` included
+    pub range: Range<usize>,
+    /// Who the mention refers to
+    pub did: Did,
+}
+
+/// A piece of message content, for rendering mentions differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageSegment<'a> {
+    /// Ordinary text
+    Text(&'a str),
+    /// A resolved mention, `@` included
+    Mention(&'a str),
+}
+
+/// Find the mentions in `content` that `resolve` maps to a DID.
+///
+/// `resolve` gets the handle without the `@`. Trailing punctuation is not
+/// part of a handle, so "thanks @joy." mentions `joy`.
+pub fn find_mentions(content: &str, resolve: impl Fn(&str) -> Option<Did>) -> Vec<Mention> {
+    let mut mentions = Vec::new();
+    let mut prev: Option<char> = None;
+    for (start, c) in content.char_indices() {
+        let at_boundary = prev.is_none_or(char::is_whitespace);
+        prev = Some(c);
+        if c != '@' || !at_boundary {
+            continue;
+        }
+
+        let rest = &content[start + 1..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+            .unwrap_or(rest.len());
+        let handle = rest[..len].trim_end_matches(['.', ':', '-']);
+        if handle.is_empty() {
+            continue;
+        }
+        if let Some(did) = resolve(handle) {
+            mentions.push(Mention {
+                range: start..start + 1 + handle.len(),
+                did,
+            });
+        }
+    }
+    mentions
+}
+
+/// Split `content` into text and the mentions at `spans`.
+///
+/// Spans must be in order and not overlap, as [`find_mentions`] returns them.
+/// Spans that don't fit the content are ignored.
+pub fn split_mentions<'a>(content: &'a str, spans: &[Range<usize>]) -> Vec<MessageSegment<'a>> {
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for span in spans {
+        if span.start < pos || content.get(span.clone()).is_none() {
+            continue;
+        }
+        if span.start > pos {
+            segments.push(MessageSegment::Text(&content[pos..span.start]));
+        }
+        segments.push(MessageSegment::Mention(&content[span.clone()]));
+        pos = span.end;
+    }
+    if pos < content.len() {
+        segments.push(MessageSegment::Text(&content[pos..]));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mentions_resolve_or_stay_text() {
+        let joy = Did::parse("did:sync:zJoy").unwrap();
+        let resolve = |handle: &str| match handle {
+            "joy" | "did:sync:zJoy" => Some(joy.clone()),
+            _ => None,
+        };
+
+        let content = "@joy see did:sync:zJoy, ask @nobody or mail a@joy. Thanks @did:sync:zJoy.";
+        let mentions = find_mentions(content, resolve);
+        assert_eq!(mentions.len(), 2);
+        assert_eq!(&content[mentions[0].range.clone()], "@joy");
+        assert_eq!(&content[mentions[1].range.clone()], "@did:sync:zJoy");
+        assert!(mentions.iter().all(|m| m.did == joy));
+
+        let spans: Vec<_> = mentions.iter().map(|m| m.range.clone()).collect();
+        let segments = split_mentions(content, &spans);
+        assert_eq!(segments.first(), Some(&MessageSegment::Mention("@joy")));
+        assert_eq!(segments.last(), Some(&MessageSegment::Text(".")));
+        assert_eq!(
+            segments
+                .iter()
+                .filter(|s| matches!(s, MessageSegment::Mention(_)))
+                .count(),
+            2
+        );
+    }
+}
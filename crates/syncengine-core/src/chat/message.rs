@@ -5,6 +5,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::mention::{find_mentions, split_mentions, Mention, MessageSegment};
+use crate::identity::Did;
+
 /// A decrypted chat message ready for display.
 ///
 /// This is a high-level abstraction over [`PacketEnvelope`] containing
@@ -22,11 +25,15 @@ use serde::{Deserialize, Serialize};
 ///     timestamp: 1705123456789,
 ///     sequence: 42,
 ///     is_mine: false,
+///     delivered: false,
+///     mentions: vec![],
+///     mention_spans: vec![],
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ChatMessage {
-    /// Unique identifier (sender_did + ":" + sequence)
+    /// Unique identifier (sender_did + ":" + sequence, with "#" + device
+    /// after the DID for messages from a linked device)
     pub id: String,
     /// Sender's DID
     pub sender_did: String,
@@ -40,6 +47,16 @@ pub struct ChatMessage {
     pub sequence: u64,
     /// Whether this message was sent by us
     pub is_mine: bool,
+    /// Whether the recipient acknowledged storing this message
+    /// (only meaningful for our own messages)
+    #[serde(default)]
+    pub delivered: bool,
+    /// People mentioned with `@`, in order of first mention
+    #[serde(default)]
+    pub mentions: Vec<Did>,
+    /// Where each mention appears in `content`
+    #[serde(default)]
+    pub mention_spans: Vec<Mention>,
 }
 
 impl ChatMessage {
@@ -70,7 +87,35 @@ impl ChatMessage {
             timestamp,
             sequence,
             is_mine,
+            delivered: false,
+            mentions: Vec::new(),
+            mention_spans: Vec::new(),
+        }
+    }
+
+    /// Fill in `mentions` using `resolve` to look up each `@` handle.
+    ///
+    /// Handles that resolve to nobody stay plain text.
+    pub fn with_mentions(mut self, resolve: impl Fn(&str) -> Option<Did>) -> Self {
+        self.mention_spans = find_mentions(&self.content, resolve);
+        self.mentions.clear();
+        for mention in &self.mention_spans {
+            if !self.mentions.contains(&mention.did) {
+                self.mentions.push(mention.did.clone());
+            }
         }
+        self
+    }
+
+    /// Whether `did` is mentioned in this message
+    pub fn is_mentioned(&self, did: &Did) -> bool {
+        self.mentions.contains(did)
+    }
+
+    /// The content split into text and mentions, for highlighting
+    pub fn segments(&self) -> Vec<MessageSegment<'_>> {
+        let spans: Vec<_> = self.mention_spans.iter().map(|m| m.range.clone()).collect();
+        split_mentions(&self.content, &spans)
     }
 
     /// Get the display name for the sender.
@@ -54,12 +54,23 @@
 //! 1. Packets arrive via contact topic subscription
 //! 2. `handle_incoming_packet()` stores in MirrorStore
 //! 3. `get_conversation()` loads and decrypts messages for display
+//! 4. A message that `@`-mentions us emits `SyncEvent::Mentioned`
+//!
+//! **Delivery:**
+//! 1. On storing a direct message, the recipient sends back a `PacketPayload::Receipt`
+//! 2. `get_conversation()` marks our messages with a matching receipt as `delivered`
 
 mod conversation;
+mod mention;
 mod message;
+mod transcript;
 
 pub use conversation::Conversation;
+pub use mention::{find_mentions, split_mentions, Mention, MessageSegment};
 pub use message::ChatMessage;
+pub use transcript::{write_transcript, TranscriptFormat};
+
+use std::collections::BTreeSet;
 
 use crate::identity::Did;
 use crate::profile::{PacketEnvelope, PacketPayload};
@@ -86,14 +97,19 @@ pub fn extract_chat_message(
     match payload {
         PacketPayload::DirectMessage { content, recipient: _ } => {
             let is_mine = envelope.sender.as_str() == my_did;
-            Some(ChatMessage::new(
+            let mut message = ChatMessage::new(
                 envelope.sender.as_str().to_string(),
                 sender_name,
                 content.clone(),
                 envelope.timestamp,
                 envelope.sequence,
                 is_mine,
-            ))
+            );
+            // Each device of the sender numbers its own log from zero
+            if !envelope.device.is_primary() {
+                message.id = format!("{}#{}:{}", envelope.sender, envelope.device, envelope.sequence);
+            }
+            Some(message)
         }
         _ => None,
     }
@@ -105,6 +121,9 @@ pub fn extract_chat_message(
 /// 1. Packets we received from the contact (stored in MirrorStore)
 /// 2. Packets we sent to the contact (stored in our ProfileLog)
 ///
+/// Sent messages are marked `delivered` when a received packet is a
+/// [`PacketPayload::Receipt`] for them.
+///
 /// # Arguments
 ///
 /// * `contact_did` - The contact's DID
@@ -113,28 +132,37 @@ pub fn extract_chat_message(
 /// * `sent_packets` - Packets we sent (from our log)
 /// * `my_did` - Our DID
 /// * `decrypt_fn` - Function to decrypt packet payloads
+/// * `resolve_mention` - Function mapping an `@` handle to the DID it mentions
 ///
 /// # Returns
 ///
 /// A Conversation containing all messages with this contact.
-pub fn build_conversation<F>(
+pub fn build_conversation<F, R>(
     contact_did: &str,
     contact_name: Option<String>,
     received_packets: Vec<PacketEnvelope>,
     sent_packets: Vec<PacketEnvelope>,
     my_did: &str,
     decrypt_fn: F,
+    resolve_mention: R,
 ) -> Conversation
 where
     F: Fn(&PacketEnvelope) -> Option<PacketPayload>,
+    R: Fn(&str) -> Option<Did>,
 {
     let mut conversation = Conversation::new(contact_did.to_string(), contact_name.clone());
 
-    // Add received messages
+    // Add received messages, noting which of ours the contact acknowledged
+    let mut acked = BTreeSet::new();
     for envelope in received_packets {
         if let Some(payload) = decrypt_fn(&envelope) {
+            if let PacketPayload::Receipt { ref original_sender, packet_seq } = payload {
+                if original_sender.as_str() == my_did {
+                    acked.insert(packet_seq);
+                }
+            }
             if let Some(msg) = extract_chat_message(&envelope, &payload, my_did, contact_name.clone()) {
-                conversation.add_message(msg);
+                conversation.add_message(msg.with_mentions(&resolve_mention));
             }
         }
     }
@@ -148,8 +176,9 @@ where
             // Check if this DirectMessage was intended for this contact
             if let PacketPayload::DirectMessage { ref recipient, .. } = payload {
                 if recipient.as_str() == contact_did {
-                    if let Some(msg) = extract_chat_message(&envelope, &payload, my_did, None) {
-                        conversation.add_message(msg);
+                    if let Some(mut msg) = extract_chat_message(&envelope, &payload, my_did, None) {
+                        msg.delivered = acked.contains(&msg.sequence);
+                        conversation.add_message(msg.with_mentions(&resolve_mention));
                     }
                 }
             }
@@ -161,16 +190,16 @@ where
 
 /// Get a display name for a contact.
 ///
-/// Looks up the peer by DID and returns their display name if available.
-/// Falls back to nickname if profile display_name is not set or empty.
+/// Prefers our local nickname for the peer, so contacts with identical
+/// profile names can be told apart, then their profile display_name.
 pub fn get_contact_display_name(peer: Option<&Peer>) -> Option<String> {
     peer.and_then(|p| {
-        // Try profile display_name first (if non-empty), then nickname
-        p.profile
-            .as_ref()
-            .map(|profile| profile.display_name.clone())
-            .filter(|name| !name.is_empty())
-            .or_else(|| p.nickname.clone())
+        p.nickname.clone().or_else(|| {
+            p.profile
+                .as_ref()
+                .map(|profile| profile.display_name.clone())
+                .filter(|name| !name.is_empty())
+        })
     })
 }
 
@@ -263,6 +292,7 @@ mod tests {
                 // Simple decrypt for global packets
                 envelope.decode_global_payload().ok()
             },
+            |_| None,
         );
 
         assert_eq!(conversation.contact_did, friend_did);
@@ -278,6 +308,46 @@ mod tests {
         assert_eq!(theirs_count, 2);
     }
 
+    #[test]
+    fn test_build_conversation_marks_acknowledged_messages_delivered() {
+        let friend_keys = ProfileKeys::generate();
+        let my_keys = ProfileKeys::generate();
+        let my_did = my_keys.did();
+        let friend_did = friend_keys.did();
+
+        let (sent1, _) = create_test_dm_envelope(&my_keys, "First", friend_did.clone(), 1);
+        let (sent2, _) = create_test_dm_envelope(&my_keys, "Second", friend_did.clone(), 2);
+        let receipt = PacketEnvelope::create_global(
+            &friend_keys,
+            &PacketPayload::Receipt {
+                original_sender: my_did.clone(),
+                packet_seq: 1,
+            },
+            1,
+            [0u8; 32],
+        )
+        .unwrap();
+
+        let conversation = build_conversation(
+            friend_did.as_str(),
+            None,
+            vec![receipt],
+            vec![sent1, sent2],
+            my_did.as_str(),
+            |envelope| envelope.decode_global_payload().ok(),
+            |_| None,
+        );
+
+        // Receipts are not shown as messages
+        assert_eq!(conversation.len(), 2);
+        let delivered: Vec<_> = conversation
+            .messages()
+            .iter()
+            .map(|m| (m.sequence, m.delivered))
+            .collect();
+        assert_eq!(delivered, [(1, true), (2, false)]);
+    }
+
     #[test]
     fn test_build_conversation_empty() {
         let conversation = build_conversation(
@@ -287,6 +357,7 @@ mod tests {
             vec![],
             "did:sync:me",
             |_| None,
+            |_| None,
         );
 
         assert!(conversation.is_empty());
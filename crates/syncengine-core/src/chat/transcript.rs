@@ -0,0 +1,96 @@
+//! Conversation transcripts for archiving
+//!
+//! A transcript is a readable copy of a [`Conversation`], oldest message
+//! first, with a timestamp and sender label on every message. It is written
+//! message by message so a long conversation never has to be rendered into
+//! one string.
+
+use std::io::{self, Write};
+
+use chrono::{TimeZone, Utc};
+
+use super::{ChatMessage, Conversation};
+
+/// How a transcript is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranscriptFormat {
+    /// A Markdown document with a heading per message
+    #[default]
+    Markdown,
+    /// One `[time] sender: text` line per message
+    PlainText,
+}
+
+impl TranscriptFormat {
+    /// Parse a format name: `markdown`/`md` or `text`/`txt`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Some(Self::Markdown),
+            "text" | "txt" | "plain" => Some(Self::PlainText),
+            _ => None,
+        }
+    }
+}
+
+/// Write `conversation` as a transcript, returning how many messages were written.
+pub fn write_transcript<W: Write>(
+    conversation: &Conversation,
+    format: TranscriptFormat,
+    writer: &mut W,
+) -> io::Result<usize> {
+    let name = conversation.display_name();
+    match format {
+        TranscriptFormat::Markdown => {
+            writeln!(writer, "# Conversation with {}", name)?;
+            writeln!(writer)?;
+            writeln!(writer, "- DID: `{}`", conversation.contact_did)?;
+            writeln!(writer, "- Messages: {}", conversation.len())?;
+        }
+        TranscriptFormat::PlainText => {
+            writeln!(writer, "Conversation with {}", name)?;
+            writeln!(writer, "DID: {}", conversation.contact_did)?;
+            writeln!(writer, "Messages: {}", conversation.len())?;
+        }
+    }
+
+    for message in conversation.messages() {
+        let sender = sender_label(message, &name);
+        let time = format_timestamp(message.timestamp);
+        match format {
+            TranscriptFormat::Markdown => {
+                writeln!(writer)?;
+                writeln!(writer, "### {} · {}", sender, time)?;
+                writeln!(writer)?;
+                writeln!(writer, "{}", message.content)?;
+            }
+            TranscriptFormat::PlainText => {
+                // Continuation lines are indented so every line stays attributable
+                let content = message.content.replace('\n', "\n    ");
+                writeln!(writer)?;
+                writeln!(writer, "[{}] {}: {}", time, sender, content)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(conversation.len())
+}
+
+/// "You" for our messages; otherwise the sender's name, falling back to
+/// the conversation's name so the label matches the heading
+fn sender_label(message: &ChatMessage, contact_name: &str) -> String {
+    if message.is_mine {
+        "You".to_string()
+    } else {
+        message
+            .sender_name
+            .clone()
+            .unwrap_or_else(|| contact_name.to_string())
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    Utc.timestamp_millis_opt(timestamp)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
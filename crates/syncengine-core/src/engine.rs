@@ -25,33 +25,54 @@
 //! let invite = engine.generate_invite(&realm_id).await?;
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use automerge::ChangeHash;
 use iroh_gossip::proto::TopicId;
 use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
+use tokio_util::task::TaskTracker;
 use tracing::{debug, info, warn};
 
-use crate::blobs::BlobManager;
+use crate::blobs::{BlobManager, ImageOrientation};
+use crate::crypto::RealmCrypto;
 use crate::error::SyncError;
-use crate::identity::{Did, HybridKeypair, HybridPublicKey};
+use crate::identity::{DeviceEntry, DeviceId, DeviceList, Did, HybridKeypair, HybridPublicKey, SignedDeviceList};
 // Indra's Network: Profile packet layer
 use crate::profile::{
-    MirrorStore, PacketAddress, PacketEnvelope, PacketPayload, ProfileKeys, ProfileLog,
-    ProfileTopicTracker,
+    LogIntegrityReport, MirrorStore, PacketAddress, PacketEnvelope, PacketPayload, ProfileKeys,
+    ProfileLog, ProfileTopicTracker,
 };
 use crate::invite::{InviteTicket, NodeAddrBytes};
-use crate::peers::{PeerInfo, PeerRegistry, PeerSource, PeerStatus};
-use crate::realm::RealmDoc;
-use crate::storage::Storage;
+use crate::peers::{
+    ConnectionChange, ConnectionEvent, PeerInfo, PeerRegistry, PeerSource, PeerStatus,
+};
+use crate::realm::{RealmDoc, RealmSnapshotView, RealmTemplate};
+use crate::storage::{InMemoryBackend, Storage, StorageBackend};
+use crate::sync::capture::{CaptureDirection, CaptureRecorder};
+#[cfg(feature = "debug-server")]
+use crate::sync::capture::{CapturedRealm, MessageCapture};
 use crate::sync::{
-    ContactEvent, ContactManager, GossipSync, NetworkDebugInfo, RelayStore, RelayWrapper,
-    SyncEnvelope, SyncEvent, SyncMessage, SyncStatus, TopicEvent, TopicReceiver, TopicSender,
+    content_hash, ContactEvent, ContactManager, ContentHash, GossipConfig, GossipSync,
+    NetworkDebugInfo, NetworkErrorRecord, PacketGate, PacketSyncMessage, PacketTrust,
+    RelayStore, RelayWrapper, SeenCache, SyncEnvelope, SyncEvent,
+    SyncMessage, SyncStatus, TopicDebugInfo, TopicEvent, TopicReceiver, TopicSender,
+};
+use crate::sync::packet_gate;
+use crate::types::contact::{
+    BundledContact, ContactImportReport, ContactInfo, ContactsBundle, HybridContactInvite,
+    PeerContactInvite, PendingContact, ProfileSnapshot, CONTACTS_BUNDLE_VERSION,
+};
+use crate::types::{
+    RealmDiff, RealmId, RealmInfo, RealmMember, RealmMetadata, RealmRole, RealmRoles,
+    RealmSnapshot, SnapshotId, RandomTaskIds, Recurrence, Task, TaskActivity, TaskId,
+    TaskIdGenerator,
 };
-use crate::types::contact::{ContactInfo, HybridContactInvite, PeerContactInvite, PendingContact, ProfileSnapshot};
-use crate::types::{RealmId, RealmInfo, Task, TaskId};
 
 /// Reserved name for the default Private realm
 const PRIVATE_REALM_NAME: &str = "Private";
@@ -80,11 +101,87 @@ const ONBOARDING_TASKS: &[(&str, &str)] = &[
     ),
 ];
 
+/// Short and full forms of a public key for debug output
+fn debug_key_ids(key: &iroh::PublicKey) -> (String, String) {
+    let full = format!("{:?}", key);
+    // Extract just the hex part, e.g., "PublicKey(abc...)" -> "abc..."
+    let short = full
+        .strip_prefix("PublicKey(")
+        .and_then(|s| s.strip_suffix(")"))
+        .map(|s| s.chars().take(8).collect::<String>())
+        .unwrap_or_else(|| "unknown".to_string());
+    (short, full)
+}
+
+/// Wrap a realm's full document for sending, marking it if compacted
+fn full_document_message(realm_id: &RealmId, doc: &mut RealmDoc) -> SyncMessage {
+    let document = doc.save();
+    if doc.is_compacted() {
+        SyncMessage::CompactedDocument {
+            realm_id: realm_id.clone(),
+            document,
+        }
+    } else {
+        SyncMessage::SyncResponse {
+            realm_id: realm_id.clone(),
+            document,
+        }
+    }
+}
+
+/// Merge a peer's full copy of a realm, archiving any history it replaces
+///
+/// Absorbing a compacted copy can swap our document for it, dropping our
+/// change history; that history is kept in storage for
+/// [`SyncEngine::task_history`] and for resyncing lagging replicas.
+fn absorb_archiving(
+    storage: &Storage,
+    realm_id: &RealmId,
+    doc: &mut RealmDoc,
+    remote: RealmDoc,
+) -> Result<(), SyncError> {
+    if doc.shares_history(&remote) {
+        return doc.absorb(remote);
+    }
+    let heads = doc.heads();
+    let before = doc.save();
+    doc.absorb(remote)?;
+    if !doc.knows(&heads) {
+        storage.archive_realm_history(realm_id, &before)?;
+    }
+    Ok(())
+}
+
 /// Check if a realm name is the reserved "Private" name (case-insensitive)
 fn is_private_realm_name(name: &str) -> bool {
     name.eq_ignore_ascii_case(PRIVATE_REALM_NAME)
 }
 
+/// Recursively copy `from` into the existing directory `to`, checking each
+/// copied file against its source. Returns the number of files copied.
+fn copy_dir_verified(from: &Path, to: &Path) -> Result<usize, SyncError> {
+    let mut copied = 0;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir(&target)?;
+            copied += copy_dir_verified(&source, &target)?;
+        } else {
+            std::fs::copy(&source, &target)?;
+            if blake3::hash(&std::fs::read(&source)?) != blake3::hash(&std::fs::read(&target)?) {
+                return Err(SyncError::Storage(format!(
+                    "Copy of {} does not match the original",
+                    source.display()
+                )));
+            }
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
 /// Internal state for an open realm
 struct RealmState {
     /// The Automerge document containing tasks
@@ -93,6 +190,14 @@ struct RealmState {
     topic_sender: Option<TopicSender>,
     /// Encryption key for the realm (32 bytes for ChaCha20-Poly1305)
     realm_key: [u8; 32],
+    /// Handle to the background gossip listener (if syncing), aborted to leave the topic
+    listener: Option<tokio::task::AbortHandle>,
+    /// Whether `doc` has changes not yet written to storage
+    dirty: bool,
+    /// Whether local changes have not reached the realm topic yet
+    unsynced: bool,
+    /// Whether sync is paused: the topic stays joined but nothing is sent or applied
+    paused: bool,
 }
 
 /// Main entry point for Synchronicity Engine
@@ -115,6 +220,18 @@ struct RealmState {
 /// Default capacity for event broadcast channel
 const EVENT_CHANNEL_CAPACITY: usize = 256;
 
+/// How long `shutdown()` waits for in-flight sends before closing the endpoint
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often online peers are probed for connection quality
+const KEEPALIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Estimated clock skew beyond which `SyncEvent::ClockSkewWarning` is sent
+const CLOCK_SKEW_WARNING_MS: i64 = 30_000;
+
+/// Sync errors kept for `network_debug_snapshot`
+const RECENT_ERROR_LIMIT: usize = 32;
+
 /// Result of startup sync operation
 ///
 /// Contains statistics about the startup sync attempt, including:
@@ -132,10 +249,115 @@ pub struct StartupSyncResult {
     pub peers_skipped_backoff: usize,
     /// Number of profile updates received
     pub profiles_updated: usize,
-    /// Jitter delay applied in milliseconds (0-30000)
+    /// Jitter delay applied in milliseconds (at most
+    /// [`GossipConfig::startup_jitter_max_ms`])
     pub jitter_delay_ms: u64,
 }
 
+impl StartupSyncResult {
+    /// Add one progress event to the totals
+    ///
+    /// Summing every event from
+    /// [`SyncEngine::start_networking_with_progress`] this way gives the
+    /// result it returns.
+    pub fn record(&mut self, progress: &StartupProgress) {
+        match progress {
+            StartupProgress::Started { jitter_delay_ms, .. } => {
+                self.jitter_delay_ms = *jitter_delay_ms;
+            }
+            StartupProgress::Attempting { .. } => self.peers_attempted += 1,
+            StartupProgress::Succeeded { .. } => self.peers_succeeded += 1,
+            StartupProgress::Failed { .. } => {}
+            StartupProgress::SkippedBackoff { .. } => self.peers_skipped_backoff += 1,
+        }
+    }
+}
+
+/// A step of startup sync, reported as it happens by
+/// [`SyncEngine::start_networking_with_progress`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartupProgress {
+    /// Jitter is over; this many known peers will be worked through
+    Started {
+        /// Known peers, contacts included
+        peer_count: usize,
+        /// Jitter delay applied in milliseconds
+        jitter_delay_ms: u64,
+    },
+    /// Dialing a peer
+    Attempting {
+        /// The peer's endpoint ID
+        peer_id: String,
+    },
+    /// Connected to a peer; contacts count as connected through their
+    /// contact topic without being dialed
+    Succeeded {
+        /// The peer's endpoint ID
+        peer_id: String,
+    },
+    /// Dialing a peer failed or timed out
+    Failed {
+        /// The peer's endpoint ID
+        peer_id: String,
+    },
+    /// Not dialed because the peer's backoff hasn't elapsed
+    SkippedBackoff {
+        /// The peer's endpoint ID
+        peer_id: String,
+    },
+}
+
+/// Result of accepting a contact invite
+///
+/// Reports the contact request that was sent and, for invites that bundle a
+/// realm, whether that realm was joined.
+#[derive(Debug, Clone)]
+pub struct ContactInviteAcceptance {
+    /// DID of the inviter (the contact is finalized once they auto-accept)
+    pub inviter_did: String,
+    /// Display name carried in the invite
+    pub inviter_name: String,
+    /// Realm joined from the bundled ticket
+    pub joined_realm: Option<RealmId>,
+    /// Why a bundled realm was not joined (e.g. the ticket expired)
+    pub realm_skipped: Option<String>,
+}
+
+/// A decoded invite URI
+///
+/// Returned by [`SyncEngine::parse_invite_uri`], which tells the two invite
+/// schemes apart: `sync-invite:` for realms and `sync-contact:` for contacts.
+#[derive(Debug, Clone)]
+pub enum InviteKind {
+    /// A realm invite ticket
+    Realm(InviteTicket),
+    /// A validated contact invite
+    Contact(Box<HybridContactInvite>),
+}
+
+/// Result of accepting an invite URI with [`SyncEngine::accept_invite_uri`]
+#[derive(Debug, Clone)]
+pub enum AcceptedInvite {
+    /// The realm that was joined
+    Realm(RealmId),
+    /// The contact request that was sent
+    Contact(ContactInviteAcceptance),
+}
+
+/// Result of rekeying a realm
+///
+/// Lists which members were sent the new key. Members that could not be
+/// reached must be re-invited before they can follow the realm again.
+#[derive(Debug, Clone)]
+pub struct RealmRekeyOutcome {
+    /// Key generation now in use for the realm
+    pub epoch: u64,
+    /// Members the new key was sent to over their contact topic
+    pub delivered: Vec<String>,
+    /// Members the new key could not be sent to (e.g. not a contact)
+    pub undelivered: Vec<String>,
+}
+
 /// Incoming sync data from background listener tasks
 /// Internal messages for sync coordination between listener tasks and main engine
 enum SyncChannelMessage {
@@ -146,7 +368,11 @@ enum SyncChannelMessage {
         envelope_bytes: Vec<u8>,
     },
     /// Request to broadcast our full document to peers (triggered on NeighborUp)
-    BroadcastRequest { realm_id: RealmId },
+    BroadcastRequest {
+        realm_id: RealmId,
+        /// The neighbor that just joined the realm topic
+        neighbor: iroh::PublicKey,
+    },
 }
 
 pub struct SyncEngine {
@@ -168,6 +394,8 @@ pub struct SyncEngine {
     identity: Option<HybridKeypair>,
     /// Per-realm sync status tracking (Arc<Mutex> for thread-safe access from listener tasks)
     sync_status: Arc<Mutex<HashMap<RealmId, SyncStatus>>>,
+    /// Current gossip neighbors on each syncing realm's topic, kept by the listener tasks
+    topic_neighbors: Arc<Mutex<HashMap<RealmId, HashSet<iroh::PublicKey>>>>,
     /// Event broadcast channel for notifying listeners of realm changes
     event_tx: broadcast::Sender<SyncEvent>,
     /// Contact event broadcast channel for contact exchange events
@@ -207,6 +435,10 @@ pub struct SyncEngine {
     /// Initialized when profile_keys are initialized.
     profile_log: Option<ProfileLog>,
 
+    /// Which of our identity's devices this node is.
+    /// [`DeviceId::PRIMARY`] unless the identity was imported from another device.
+    device_id: DeviceId,
+
     /// Mirror store for other profiles' packet logs.
     /// Stores packets from contacts for offline sync and relay.
     mirror_store: Option<MirrorStore>,
@@ -236,6 +468,59 @@ pub struct SyncEngine {
     /// When a contact is offline and a mutual peer receives a relay request,
     /// the encrypted payload is stored here until the recipient comes online.
     relay_store: Arc<std::sync::Mutex<RelayStore>>,
+
+    /// Last packet sequence scanned for realm rekeys, per sender.
+    /// Keeps `apply_realm_rekeys` from decrypting the same packets repeatedly.
+    rekey_scan_heads: HashMap<Did, u64>,
+
+    /// Endpoint options applied when networking starts
+    gossip_config: GossipConfig,
+
+    /// Whether the background reconnection scheduler has been spawned
+    reconnect_scheduler_started: bool,
+
+    /// Incoming packets addressed to us that failed to decrypt, per sender DID
+    packet_decryption_failures: HashMap<String, u64>,
+
+    /// Size limit, rate limit and trust ledger applied to incoming packets
+    packet_gate: Arc<PacketGate>,
+
+    /// Hashes of realm envelopes and changes already applied, to drop repeats
+    seen_changes: SeenCache,
+
+    /// Incoming realm changes dropped as repeats
+    duplicate_changes: u64,
+
+    /// Most recent sync errors, for `network_debug_snapshot`
+    recent_errors: VecDeque<NetworkErrorRecord>,
+
+    /// Document heads each realm member last announced, sorted
+    member_heads: HashMap<RealmId, HashMap<String, Vec<ChangeHash>>>,
+
+    /// Realm gossip recording, while a capture is running
+    message_capture: parking_lot::Mutex<Option<CaptureRecorder>>,
+
+    /// Realm envelopes broadcast since startup
+    realm_messages_sent: AtomicU64,
+
+    /// Realm envelopes received since startup
+    realm_messages_received: AtomicU64,
+
+    /// When a peer's realm changes were last applied successfully
+    last_sync_at: Option<Instant>,
+
+    /// When dirty realms were last written out by autosave
+    last_autosave: Instant,
+
+    /// Fire-and-forget sends (document broadcasts, role tables, receipts)
+    /// that shutdown waits on before closing the endpoint
+    in_flight: TaskTracker,
+
+    /// Reminders already sent, keyed by task and the due date they were for
+    reminded: HashSet<(TaskId, i64)>,
+
+    /// Where IDs for new tasks come from (random ULIDs unless a test swaps it)
+    task_ids: Box<dyn TaskIdGenerator>,
 }
 
 impl SyncEngine {
@@ -251,6 +536,24 @@ impl SyncEngine {
     /// Returns `SyncError::Io` if the directory cannot be created.
     /// Returns `SyncError::Database` if storage initialization fails.
     pub async fn new(data_dir: impl AsRef<Path>) -> Result<Self, SyncError> {
+        Self::new_with_onboarding(data_dir, Self::default_onboarding_tasks()).await
+    }
+
+    /// Create a SyncEngine that seeds a new Private realm with custom tasks
+    ///
+    /// Like [`new`](Self::new), but when the Private realm has to be
+    /// created it gets `tasks` (title, description) instead of the default
+    /// onboarding tasks. Pass an empty list for an empty Private realm. An
+    /// existing Private realm is left as it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Io` if the directory cannot be created.
+    /// Returns `SyncError::Database` if storage initialization fails.
+    pub async fn new_with_onboarding(
+        data_dir: impl AsRef<Path>,
+        tasks: Vec<(String, String)>,
+    ) -> Result<Self, SyncError> {
         let data_dir = data_dir.as_ref().to_path_buf();
         info!(?data_dir, "Initializing SyncEngine");
 
@@ -259,25 +562,148 @@ impl SyncEngine {
         let db_path = data_dir.join("syncengine.redb");
         let storage = Storage::new(&db_path)?;
 
-        // Initialize peer registry using the same database connection
-        let peer_registry = Arc::new(PeerRegistry::new(storage.db_handle())?);
-
         // Initialize blob manager with persistent FsStore
         let blob_path = data_dir.join("blobs");
         let blob_manager = BlobManager::new_persistent(&blob_path).await?;
         info!(?blob_path, "Blob manager initialized with persistent storage");
 
+        Self::with_parts(data_dir, storage, blob_manager, &tasks).await
+    }
+
+    /// The tasks (title, description) a new Private realm starts with
+    pub fn default_onboarding_tasks() -> Vec<(String, String)> {
+        ONBOARDING_TASKS
+            .iter()
+            .map(|(title, description)| (title.to_string(), description.to_string()))
+            .collect()
+    }
+
+    /// Create a SyncEngine whose database lives on a custom storage backend
+    ///
+    /// Nothing touches the filesystem: every table is kept by `backend`
+    /// and image blobs are kept in memory. With an [`InMemoryBackend`] this
+    /// gives a throwaway engine, which is much faster to set up in tests
+    /// than one on disk. [`data_dir`](Self::data_dir) is empty.
+    ///
+    /// [`InMemoryBackend`]: crate::storage::InMemoryBackend
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the engine's initial state cannot be written.
+    pub async fn new_with_storage(backend: impl StorageBackend) -> Result<Self, SyncError> {
+        info!("Initializing SyncEngine on a custom storage backend");
+        let storage = Storage::with_backend(backend);
+        Self::with_parts(
+            PathBuf::new(),
+            storage,
+            BlobManager::new_memory(),
+            &Self::default_onboarding_tasks(),
+        )
+        .await
+    }
+
+    /// Create a throwaway SyncEngine held entirely in memory
+    ///
+    /// Behaves like [`new`](Self::new), Private realm included, but writes
+    /// nothing to disk and everything is gone once the engine is dropped.
+    /// Meant for tests and short-lived nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Database` if storage initialization fails.
+    pub async fn new_ephemeral() -> Result<Self, SyncError> {
+        Self::new_with_storage(InMemoryBackend::new()).await
+    }
+
+    /// Move a data directory to `new_dir` and open the engine there
+    ///
+    /// Copies the database and blob store from `old_dir`, checks every
+    /// copied file against its original, then opens the copy. `old_dir` is
+    /// left untouched and can be removed once the returned engine is in
+    /// use. No engine may have `old_dir` open while this runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidOperation` if `old_dir` holds no
+    /// database, if `new_dir` exists and is not an empty directory, or if
+    /// `new_dir` is inside `old_dir`.
+    /// Returns `SyncError::Storage` if a copied file doesn't match its
+    /// original; the partial copy is removed.
+    /// Returns `SyncError::Io` if copying fails.
+    pub async fn relocate(
+        old_dir: impl AsRef<Path>,
+        new_dir: impl AsRef<Path>,
+    ) -> Result<Self, SyncError> {
+        let old_dir = old_dir.as_ref();
+        let new_dir = new_dir.as_ref();
+        info!(?old_dir, ?new_dir, "Relocating data directory");
+
+        if !old_dir.join("syncengine.redb").is_file() {
+            return Err(SyncError::InvalidOperation(format!(
+                "{} is not a data directory",
+                old_dir.display()
+            )));
+        }
+        let created = !new_dir.exists();
+        if created {
+            std::fs::create_dir_all(new_dir)?;
+        } else if !new_dir.is_dir() || std::fs::read_dir(new_dir)?.next().is_some() {
+            return Err(SyncError::InvalidOperation(format!(
+                "{} is not empty",
+                new_dir.display()
+            )));
+        }
+        if new_dir.canonicalize()?.starts_with(old_dir.canonicalize()?) {
+            if created {
+                std::fs::remove_dir(new_dir)?;
+            }
+            return Err(SyncError::InvalidOperation(format!(
+                "{} is inside {}",
+                new_dir.display(),
+                old_dir.display()
+            )));
+        }
+
+        let copied = match copy_dir_verified(old_dir, new_dir) {
+            Ok(copied) => copied,
+            Err(e) => {
+                warn!(?new_dir, error = ?e, "Relocation failed, removing partial copy");
+                let _ = std::fs::remove_dir_all(new_dir);
+                if !created {
+                    let _ = std::fs::create_dir(new_dir);
+                }
+                return Err(e);
+            }
+        };
+        info!(copied, ?new_dir, "Data directory copied and verified");
+
+        Self::new(new_dir).await
+    }
+
+    /// Finish construction once storage and blobs are set up
+    async fn with_parts(
+        data_dir: PathBuf,
+        storage: Storage,
+        blob_manager: BlobManager,
+        onboarding: &[(String, String)],
+    ) -> Result<Self, SyncError> {
+        // Initialize peer registry using the same database connection
+        let peer_registry = Arc::new(PeerRegistry::new(storage.backend()));
+
         let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
         let (contact_event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
         let (sync_tx, sync_rx) = tokio::sync::mpsc::unbounded_channel();
 
         // Initialize mirror store for profile packet storage
-        let mirror_store = MirrorStore::new(storage.db_handle())?;
+        let mirror_store = MirrorStore::new(storage.backend());
 
         // Initialize packet event buffer for UI visualization
         let packet_event_buffer = crate::sync::PacketEventBuffer::with_defaults();
 
+        // Shared with the background listeners that also receive packets
+        let packet_gate = Arc::new(PacketGate::new(&GossipConfig::default(), event_tx.clone()));
+
         let mut engine = Self {
             storage,
             peer_registry,
@@ -288,6 +714,7 @@ impl SyncEngine {
             data_dir,
             identity: None,
             sync_status: Arc::new(Mutex::new(HashMap::new())),
+            topic_neighbors: Arc::new(Mutex::new(HashMap::new())),
             event_tx,
             contact_event_tx,
             sync_rx,
@@ -299,15 +726,33 @@ impl SyncEngine {
             // Indra's Network packet layer
             profile_keys: None,
             profile_log: None, // Initialized when profile_keys are initialized
+            device_id: DeviceId::PRIMARY, // Loaded with the profile keys
             mirror_store: Some(mirror_store),
             profile_topic_tracker: ProfileTopicTracker::new(),
             networking_requested: false,
             packet_event_buffer,
             relay_store: Arc::new(std::sync::Mutex::new(RelayStore::new())),
+            rekey_scan_heads: HashMap::new(),
+            gossip_config: GossipConfig::default(),
+            reconnect_scheduler_started: false,
+            packet_decryption_failures: HashMap::new(),
+            packet_gate,
+            seen_changes: SeenCache::new(GossipConfig::DEFAULT_DEDUP_CACHE_SIZE),
+            duplicate_changes: 0,
+            recent_errors: VecDeque::new(),
+            member_heads: HashMap::new(),
+            message_capture: parking_lot::Mutex::new(None),
+            realm_messages_sent: AtomicU64::new(0),
+            realm_messages_received: AtomicU64::new(0),
+            last_sync_at: None,
+            last_autosave: Instant::now(),
+            reminded: HashSet::new(),
+            in_flight: TaskTracker::new(),
+            task_ids: Box::new(RandomTaskIds::default()),
         };
 
         // Initialize the Private realm if it doesn't exist
-        engine.ensure_private_realm().await?;
+        engine.ensure_private_realm(onboarding).await?;
 
         Ok(engine)
     }
@@ -315,9 +760,12 @@ impl SyncEngine {
     /// Ensure the Private realm exists, creating it if necessary
     ///
     /// This is called automatically during engine initialization.
-    /// The Private realm is a special, non-shareable realm that contains
-    /// sacred onboarding tasks to guide new users.
-    async fn ensure_private_realm(&mut self) -> Result<(), SyncError> {
+    /// The Private realm is a special, non-shareable realm that starts with
+    /// the given onboarding tasks to guide new users.
+    async fn ensure_private_realm(
+        &mut self,
+        onboarding: &[(String, String)],
+    ) -> Result<(), SyncError> {
         // Check if Private realm already exists
         let existing_realms = self.storage.list_realms()?;
         if existing_realms
@@ -340,7 +788,7 @@ impl SyncEngine {
 
         // Create document with onboarding tasks
         let mut doc = RealmDoc::new();
-        for (title, description) in ONBOARDING_TASKS {
+        for (title, description) in onboarding {
             doc.add_quest(title, None, description)?;
         }
 
@@ -349,7 +797,7 @@ impl SyncEngine {
         self.storage.save_realm_key(&realm_id, &realm_key)?;
         self.storage.save_document(&realm_id, &doc.save())?;
 
-        debug!(%realm_id, "Private realm created with {} onboarding tasks", ONBOARDING_TASKS.len());
+        debug!(%realm_id, "Private realm created with {} onboarding tasks", onboarding.len());
         Ok(())
     }
 
@@ -438,12 +886,27 @@ impl SyncEngine {
     pub fn regenerate_identity(&mut self) -> Result<(), SyncError> {
         warn!("Regenerating identity - this is irreversible!");
         let keypair = HybridKeypair::generate();
+        if let Some(did) = self.did() {
+            self.storage.delete_device_list(did.as_str())?;
+        }
         self.storage.save_identity(&keypair)?;
+        self.storage.save_device_id(&DeviceId::PRIMARY)?;
         self.identity = Some(keypair);
         info!("New identity generated");
         Ok(())
     }
 
+    /// What [`Self::regenerate_identity`] would replace, without replacing it
+    pub fn regenerate_identity_preview(&self) -> Result<IdentityRegeneratePreview, SyncError> {
+        Ok(IdentityRegeneratePreview {
+            current_did: self.did(),
+            current_key_fingerprint: self
+                .public_key()
+                .map(|pk| hex::encode(&pk.to_bytes()[..8])),
+            contact_count: self.storage.list_contacts()?.len(),
+        })
+    }
+
     /// Export the public key in different formats.
     ///
     /// # Arguments
@@ -504,6 +967,248 @@ impl SyncEngine {
         public_key.verify(data, signature)
     }
 
+    // ═══════════════════════════════════════════════════════════════════════
+    // Linked Devices
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Which of our identity's devices this node is.
+    ///
+    /// [`DeviceId::PRIMARY`] unless the identity was imported with
+    /// [`import_identity_encrypted`](Self::import_identity_encrypted).
+    pub fn device_id(&self) -> DeviceId {
+        self.device_id
+    }
+
+    /// Get the signed device list of `did`, if it uses several devices.
+    pub fn device_list(&self, did: &Did) -> Result<Option<SignedDeviceList>, SyncError> {
+        self.storage.load_device_list(did.as_str())
+    }
+
+    /// Check whether the node at `endpoint` is one of `did`'s devices.
+    ///
+    /// Uses the identity's signed device list, or for an identity without
+    /// one, the endpoint we exchanged contacts with.
+    pub fn is_device_of(&self, did: &Did, endpoint: &iroh::PublicKey) -> Result<bool, SyncError> {
+        if let Some(signed) = self.device_list(did)? {
+            return Ok(signed.list.device_at(endpoint.as_bytes()).is_some());
+        }
+        Ok(self
+            .storage
+            .load_contact(did.as_str())?
+            .is_some_and(|contact| contact.peer_endpoint_id == *endpoint.as_bytes()))
+    }
+
+    /// Export our identity so another device can use it too.
+    ///
+    /// The identity and profile keys, our device list and our contacts are
+    /// encrypted with a fresh key. Pass both halves of the returned
+    /// [`IdentityLink`] to [`import_identity_encrypted`](Self::import_identity_encrypted)
+    /// on the new device, ideally over different channels: the key alone
+    /// reveals nothing, but together they are the identity.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Identity` if identity or profile keys are not initialized.
+    pub fn export_identity_encrypted(&self) -> Result<IdentityLink, SyncError> {
+        let keys = self.profile_keys.as_ref().ok_or_else(|| {
+            SyncError::Identity("Profile keys not initialized. Call init_profile_keys() first.".to_string())
+        })?;
+        let identity = self
+            .identity
+            .as_ref()
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?;
+
+        // The list must name this device before anyone else joins it
+        let entry = DeviceEntry {
+            device: self.device_id,
+            endpoint_id: self.endpoint_id().map(|id| *id.as_bytes()),
+            name: "Original device".to_string(),
+            added_at: chrono::Utc::now().timestamp(),
+        };
+        let devices = match self.storage.load_device_list(keys.did().as_str())? {
+            Some(signed) if signed.list.device(&self.device_id).is_some() => signed,
+            stored => {
+                let mut list = stored.map_or_else(|| DeviceList::new(keys.did()), |s| s.list);
+                list.upsert(entry);
+                list.version += 1;
+                let signed = SignedDeviceList::sign(list, identity);
+                self.storage.save_device_list(&signed)?;
+                signed
+            }
+        };
+
+        let bundle = IdentityLinkBundle {
+            version: IDENTITY_LINK_VERSION,
+            identity: identity.to_bytes(),
+            profile_keys: keys.to_bytes(),
+            profile: Some(self.get_own_profile()?),
+            devices,
+            contacts: self.export_contacts()?,
+        };
+        let plaintext =
+            postcard::to_allocvec(&bundle).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let link_key = RealmCrypto::generate_key();
+        let ciphertext = RealmCrypto::new(&link_key).encrypt(&plaintext)?;
+
+        info!(did = %keys.did(), contacts = bundle.contacts.contacts.len(), "Exported identity for linking a device");
+        Ok(IdentityLink {
+            bundle: bs58::encode(ciphertext).into_string(),
+            link_key: bs58::encode(link_key).into_string(),
+        })
+    }
+
+    /// Take on an identity exported from another device.
+    ///
+    /// This node becomes a new device of that identity: it signs as the same
+    /// DID, writes its own packet log, and is added to the identity's device
+    /// list, which contacts receive once we network. Contacts from the
+    /// export are imported with working keys.
+    ///
+    /// Must be called on a node that hasn't started networking or made
+    /// contacts, since its current identity is replaced.
+    ///
+    /// # Returns
+    ///
+    /// The ID this node was given among the identity's devices.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidOperation` if networking has started or we
+    /// have contacts, `SyncError::Crypto` if the key doesn't open the bundle,
+    /// and `SyncError::SignatureInvalid` if the bundle's keys and device list
+    /// don't belong to one identity.
+    pub fn import_identity_encrypted(
+        &mut self,
+        bundle: &str,
+        link_key: &str,
+        device_name: &str,
+    ) -> Result<DeviceId, SyncError> {
+        if self.is_networking_active() {
+            return Err(SyncError::InvalidOperation(
+                "Import an identity before starting networking".to_string(),
+            ));
+        }
+        if !self.storage.list_contacts()?.is_empty() {
+            return Err(SyncError::InvalidOperation(
+                "Cannot replace an identity that has contacts".to_string(),
+            ));
+        }
+
+        let link_key: [u8; 32] = bs58::decode(link_key.trim())
+            .into_vec()
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| SyncError::Crypto("Invalid identity link key".to_string()))?;
+        let ciphertext = bs58::decode(bundle.trim())
+            .into_vec()
+            .map_err(|e| SyncError::Serialization(format!("Invalid identity bundle: {}", e)))?;
+        let plaintext = RealmCrypto::new(&link_key).decrypt(&ciphertext)?;
+        let bundle: IdentityLinkBundle =
+            postcard::from_bytes(&plaintext).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        if bundle.version != IDENTITY_LINK_VERSION {
+            return Err(SyncError::InvalidOperation(format!(
+                "Unsupported identity bundle version {}",
+                bundle.version
+            )));
+        }
+
+        let keypair = HybridKeypair::from_bytes(&bundle.identity)?;
+        let keys = ProfileKeys::from_bytes(&bundle.profile_keys)?;
+        let did = Did::from_public_key(&keypair.public_key());
+        if keys.did() != did || bundle.devices.list.did != did || !bundle.devices.verify() {
+            return Err(SyncError::SignatureInvalid(
+                "Identity bundle keys and device list don't match".to_string(),
+            ));
+        }
+
+        // Drop what the replaced identity had published about itself
+        if let Some(old_did) = self.did().filter(|old| *old != did) {
+            self.storage.delete_pinned_profile(old_did.as_str())?;
+            self.storage.delete_profile(old_did.as_str())?;
+            self.storage.delete_device_list(old_did.as_str())?;
+        }
+
+        let device = DeviceId::generate();
+        let mut list = bundle.devices.list;
+        list.upsert(DeviceEntry {
+            device,
+            endpoint_id: None,
+            name: device_name.to_string(),
+            added_at: chrono::Utc::now().timestamp(),
+        });
+        list.version += 1;
+
+        self.storage.save_identity(&keypair)?;
+        self.storage.save_profile_keys(&keys)?;
+        self.storage.save_device_id(&device)?;
+        self.storage.save_device_list(&SignedDeviceList::sign(list, &keypair))?;
+        if let Some(profile) = &bundle.profile {
+            self.storage.save_profile(profile)?;
+        }
+
+        self.identity = Some(keypair);
+        self.profile_keys = None;
+        self.profile_log = None;
+        self.init_profile_keys()?;
+        let report = self.import_contacts(&bundle.contacts)?;
+
+        info!(%did, %device, contacts = report.imported.len(), "Imported identity as a linked device");
+        Ok(device)
+    }
+
+    /// Send our signed device list to every contact.
+    ///
+    /// Contacts also receive it whenever their topic with us comes up, so
+    /// this is only needed to spread a change right away.
+    ///
+    /// # Returns
+    ///
+    /// The number of contact topics it was sent on; 0 if our identity is
+    /// only used on this device.
+    pub async fn announce_devices(&self) -> Result<usize, SyncError> {
+        let did = self
+            .did()
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?;
+        let Some(signed) = self.storage.load_device_list(did.as_str())? else {
+            return Ok(0);
+        };
+        let manager = self
+            .contact_manager
+            .as_ref()
+            .ok_or_else(|| SyncError::NotReady("Contact manager not initialized".to_string()))?;
+        let bytes = crate::sync::ProfileGossipMessage::devices(signed).to_bytes()?;
+        Ok(manager.broadcast_profile_to_contacts(&bytes).await)
+    }
+
+    /// Record this node's endpoint in our device list, if we keep one.
+    ///
+    /// A linked device only learns its endpoint once it networks, and
+    /// contacts need it to tell the device apart from an impersonator.
+    fn refresh_own_device_endpoint(&self) -> Result<(), SyncError> {
+        let (Some(identity), Some(endpoint)) = (self.identity.as_ref(), self.endpoint_id()) else {
+            return Ok(());
+        };
+        let did = Did::from_public_key(&identity.public_key());
+        let Some(signed) = self.storage.load_device_list(did.as_str())? else {
+            return Ok(());
+        };
+        let Some(entry) = signed.list.device(&self.device_id) else {
+            return Ok(());
+        };
+        if entry.endpoint_id == Some(*endpoint.as_bytes()) {
+            return Ok(());
+        }
+
+        let mut list = signed.list.clone();
+        let mut entry = entry.clone();
+        entry.endpoint_id = Some(*endpoint.as_bytes());
+        list.upsert(entry);
+        list.version += 1;
+        self.storage.save_device_list(&SignedDeviceList::sign(list, identity))?;
+        debug!(device = %self.device_id, "Recorded our endpoint in the device list");
+        Ok(())
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // Profile Packet Operations (Indra's Network)
     // ═══════════════════════════════════════════════════════════════════════
@@ -566,9 +1271,11 @@ impl SyncEngine {
 
         // Initialize the profile log with our DID
         // Try to load existing packets from MirrorStore for persistence across restarts
+        // Each device of the identity writes its own log
         let did = keys.did();
+        self.device_id = self.storage.load_device_id()?;
         let log = if let Some(ref mirror) = self.mirror_store {
-            match mirror.load_log(&did) {
+            match mirror.load_device_log(&did, &self.device_id) {
                 Ok(loaded_log) => {
                     let packet_count = loaded_log.len();
                     if packet_count > 0 {
@@ -665,7 +1372,8 @@ impl SyncEngine {
     /// Get ALL packets from a mirror (inclusive of sequence 0).
     ///
     /// Unlike `mirror_packets_since(did, 0)` which excludes sequence 0,
-    /// this method returns all packets including the very first one.
+    /// this method returns all packets including the very first one, from
+    /// every device the profile is used on.
     pub fn mirror_packets_all(
         &self,
         did: &Did,
@@ -673,7 +1381,7 @@ impl SyncEngine {
         let mirror = self.mirror_store.as_ref().ok_or_else(|| {
             SyncError::Storage("Mirror store not initialized".to_string())
         })?;
-        mirror.get_all(did)
+        mirror.get_all_devices(did)
     }
 
     /// List all DIDs we have mirrors for.
@@ -685,55 +1393,239 @@ impl SyncEngine {
     }
 
     // =========================================================================
-    // Relay Storage Methods (Store-and-Forward)
+    // Log Catch-up (LogRequest / LogResponse)
     // =========================================================================
 
-    /// Get all packets stored for relay to a specific recipient.
-    ///
-    /// This returns packets that were addressed to the given DID but the
-    /// recipient wasn't online to receive them. These packets should be
-    /// forwarded when the recipient comes online.
+    /// Build a request for the packets from `did` that we have not mirrored yet.
     ///
-    /// Used by the store-and-forward relay mechanism.
-    pub fn get_packets_for_recipient(&self, recipient: &Did) -> Result<Vec<PacketEnvelope>, SyncError> {
-        let mirror = self.mirror_store.as_ref().ok_or_else(|| {
-            SyncError::Storage("Mirror store not initialized".to_string())
-        })?;
-        mirror.get_packets_for_recipient(recipient)
+    /// Asks for everything after our current [`mirror_head`](Self::mirror_head),
+    /// or the whole log if we have nothing from them.
+    pub fn log_catch_up_request(&self, did: &Did) -> PacketSyncMessage {
+        PacketSyncMessage::log_request(did.clone(), self.mirror_head(did))
     }
 
-    /// Mark a packet as delivered to a recipient, removing it from the relay index.
+    /// Answer a peer's `LogRequest` from our mirror store.
     ///
-    /// After successfully forwarding a packet to its intended recipient, call
-    /// this method to remove the packet from the relay index. The packet remains
-    /// in the mirror store for redundancy.
+    /// # Returns
     ///
-    /// Used by the store-and-forward relay mechanism.
-    pub fn mark_packet_delivered(&self, recipient: &Did, packet_hash: &[u8; 32]) -> Result<(), SyncError> {
+    /// A `LogResponse` with the packets after the requested sequence, or `None`
+    /// if `request` is not a `LogRequest` or we have nothing newer.
+    pub fn answer_log_request(
+        &self,
+        request: &PacketSyncMessage,
+    ) -> Result<Option<PacketSyncMessage>, SyncError> {
+        let PacketSyncMessage::LogRequest { did, since_sequence } = request else {
+            return Ok(None);
+        };
         let mirror = self.mirror_store.as_ref().ok_or_else(|| {
             SyncError::Storage("Mirror store not initialized".to_string())
         })?;
-        mirror.mark_delivered(recipient, packet_hash)
+
+        let packets = mirror.get_after(did, *since_sequence)?;
+        if packets.is_empty() {
+            return Ok(None);
+        }
+        PacketSyncMessage::log_response(did.clone(), &packets)
+            .map(Some)
+            .map_err(|e| SyncError::Serialization(e.to_string()))
     }
 
-    /// Create and sign a new packet.
-    ///
-    /// Creates a packet with the given payload, signs it with our profile keys,
-    /// and appends it to our log.
-    ///
-    /// # Arguments
+    /// Store the packets from a `LogResponse`, as if each had arrived on its own.
     ///
-    /// * `payload` - The packet content (message, profile update, etc.)
-    /// * `address` - Who can decrypt this packet
+    /// Packets not authored by the log's owner are skipped.
     ///
     /// # Returns
     ///
-    /// The sequence number of the newly created packet.
+    /// The number of packets that were new to us.
     ///
     /// # Errors
     ///
-    /// Returns an error if profile keys have not been initialized.
-    pub fn create_packet(
+    /// Returns `SyncError::Serialization` if the response cannot be decoded,
+    /// or any error from [`handle_incoming_packet`](Self::handle_incoming_packet).
+    pub fn apply_log_response(&mut self, response: &PacketSyncMessage) -> Result<usize, SyncError> {
+        let PacketSyncMessage::LogResponse { did, .. } = response else {
+            return Ok(0);
+        };
+        let Some(entries) = response.extract_entries() else {
+            return Ok(0);
+        };
+        let entries = entries.map_err(|e| SyncError::Serialization(e.to_string()))?;
+
+        let mut stored = 0;
+        for envelope in entries {
+            if envelope.sender != *did {
+                warn!(log = %did, sender = %envelope.sender, "Skipping foreign packet in log response");
+                continue;
+            }
+            if self.handle_incoming_packet(envelope)? {
+                stored += 1;
+            }
+        }
+        debug!(log = %did, stored, "Applied log response");
+        Ok(stored)
+    }
+
+    /// Walk our stored profile log and report the first hash-chain break.
+    ///
+    /// Every packet from genesis to the head must be stored, decode, carry
+    /// our signature and name the packet before it as `prev_hash`. Storage
+    /// corruption otherwise goes unnoticed: loading the log skips packets
+    /// that don't chain, so the log silently ends early.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if profile keys have not been initialized.
+    pub fn verify_log_integrity(&self) -> Result<LogIntegrityReport, SyncError> {
+        let keys = self.profile_keys.as_ref().ok_or_else(|| {
+            SyncError::Identity("Profile keys not initialized. Call init_profile_keys() first.".to_string())
+        })?;
+        let mirror = self.mirror_store.as_ref().ok_or_else(|| {
+            SyncError::Storage("Mirror store not initialized".to_string())
+        })?;
+        let report = mirror.check_chain(&keys.did(), &keys.public_bundle())?;
+        if let Some(log_break) = report.first_break {
+            warn!(
+                sequence = log_break.sequence,
+                kind = ?log_break.kind,
+                "Profile log hash chain is broken"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Repair our profile log from a peer's mirror of it.
+    ///
+    /// `response` is a `LogResponse` for our DID from a peer that mirrors
+    /// our log, e.g. its [`answer_log_request`](Self::answer_log_request) to
+    /// `PacketSyncMessage::log_request(our_did, report.last_intact())`.
+    /// Starting at the first break, mirrored packets that carry our
+    /// signature and continue the intact chain replace what we stored.
+    ///
+    /// # Returns
+    ///
+    /// The log's integrity after the repair; still broken if the mirror
+    /// didn't cover the damage.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidOperation` if `response` is not a
+    /// `LogResponse` for our log, or an error if profile keys have not been
+    /// initialized.
+    pub fn repair_log_from_mirror(
+        &mut self,
+        response: &PacketSyncMessage,
+    ) -> Result<LogIntegrityReport, SyncError> {
+        let report = self.verify_log_integrity()?;
+        let Some(log_break) = report.first_break else {
+            return Ok(report);
+        };
+        let keys = self.profile_keys.as_ref().ok_or_else(|| {
+            SyncError::Identity("Profile keys not initialized. Call init_profile_keys() first.".to_string())
+        })?;
+        let (did, public) = (keys.did(), keys.public_bundle());
+
+        let entries = match response {
+            PacketSyncMessage::LogResponse { did: log, .. } if *log == did => response
+                .extract_entries()
+                .unwrap_or(Ok(Vec::new()))
+                .map_err(|e| SyncError::Serialization(e.to_string()))?,
+            _ => {
+                return Err(SyncError::InvalidOperation(
+                    "Log repair needs a LogResponse for our own log".to_string(),
+                ))
+            }
+        };
+        let mirror = self.mirror_store.as_ref().ok_or_else(|| {
+            SyncError::Storage("Mirror store not initialized".to_string())
+        })?;
+
+        // Take the mirrored packets that pick up where our intact chain ends
+        let mut prev_hash = match report.last_intact() {
+            Some(sequence) => mirror
+                .get_packet(&did, sequence)?
+                .map(|envelope| envelope.hash())
+                .ok_or_else(|| SyncError::Storage(format!("Packet {} vanished", sequence)))?,
+            None => [0u8; 32],
+        };
+        let mut entries: Vec<_> = entries
+            .into_iter()
+            .filter(|envelope| envelope.sender == did && envelope.sequence >= log_break.sequence)
+            .collect();
+        entries.sort_by_key(|envelope| envelope.sequence);
+        let mut replacements = Vec::new();
+        for envelope in entries {
+            let next = log_break.sequence + replacements.len() as u64;
+            if envelope.sequence != next || envelope.prev_hash != prev_hash || !envelope.verify(&public) {
+                break;
+            }
+            prev_hash = envelope.hash();
+            replacements.push(envelope);
+        }
+
+        if replacements.is_empty() {
+            warn!(sequence = log_break.sequence, "Mirror doesn't cover the break in our profile log");
+            return Ok(report);
+        }
+        mirror.overwrite_packets(&replacements)?;
+        self.profile_log = Some(mirror.load_log(&did)?);
+        info!(
+            from = log_break.sequence,
+            replaced = replacements.len(),
+            "Repaired profile log from mirror"
+        );
+        self.verify_log_integrity()
+    }
+
+    // =========================================================================
+    // Relay Storage Methods (Store-and-Forward)
+    // =========================================================================
+
+    /// Get all packets stored for relay to a specific recipient.
+    ///
+    /// This returns packets that were addressed to the given DID but the
+    /// recipient wasn't online to receive them. These packets should be
+    /// forwarded when the recipient comes online.
+    ///
+    /// Used by the store-and-forward relay mechanism.
+    pub fn get_packets_for_recipient(&self, recipient: &Did) -> Result<Vec<PacketEnvelope>, SyncError> {
+        let mirror = self.mirror_store.as_ref().ok_or_else(|| {
+            SyncError::Storage("Mirror store not initialized".to_string())
+        })?;
+        mirror.get_packets_for_recipient(recipient)
+    }
+
+    /// Mark a packet as delivered to a recipient, removing it from the relay index.
+    ///
+    /// After successfully forwarding a packet to its intended recipient, call
+    /// this method to remove the packet from the relay index. The packet remains
+    /// in the mirror store for redundancy.
+    ///
+    /// Used by the store-and-forward relay mechanism.
+    pub fn mark_packet_delivered(&self, recipient: &Did, packet_hash: &[u8; 32]) -> Result<(), SyncError> {
+        let mirror = self.mirror_store.as_ref().ok_or_else(|| {
+            SyncError::Storage("Mirror store not initialized".to_string())
+        })?;
+        mirror.mark_delivered(recipient, packet_hash)
+    }
+
+    /// Create and sign a new packet.
+    ///
+    /// Creates a packet with the given payload, signs it with our profile keys,
+    /// and appends it to our log.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The packet content (message, profile update, etc.)
+    /// * `address` - Who can decrypt this packet
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the newly created packet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if profile keys have not been initialized.
+    pub fn create_packet(
         &mut self,
         payload: PacketPayload,
         address: PacketAddress,
@@ -780,13 +1672,17 @@ impl SyncEngine {
                 recipient_keys.push(sender_keys);
                 PacketEnvelope::create(keys, &payload, &recipient_keys, sequence, prev_hash)?
             }
-            PacketAddress::Group(_realm_id) => {
-                // Group packet for realm members (encrypted with realm key)
-                // For now, treat as global within the realm
-                // TODO: Implement realm-key encryption for group messages
-                PacketEnvelope::create_global(keys, &payload, sequence, prev_hash)?
+            PacketAddress::Group(realm_id) => {
+                // Group packet for realm members - encrypted with the realm key,
+                // so only members can read it; still signed by us
+                let realm_key = self
+                    .storage
+                    .load_realm_key(realm_id)?
+                    .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+                PacketEnvelope::create_group(keys, &payload, realm_id, &realm_key, sequence, prev_hash)?
             }
         };
+        let envelope = envelope.with_device(self.device_id, keys);
 
         // Append to our log (in-memory)
         let seq = envelope.sequence;
@@ -803,7 +1699,7 @@ impl SyncEngine {
             }
         }
 
-        debug!(seq, "Created packet");
+        debug!(seq, correlation_id = %envelope.correlation_id(), "Created packet");
         Ok(seq)
     }
 
@@ -841,6 +1737,7 @@ impl SyncEngine {
             SyncError::Identity(format!("Packet {} not found in log", sequence))
         })?;
         let envelope = entry.envelope.clone();
+        let correlation_id = envelope.correlation_id();
 
         // Create the gossip message
         let msg = crate::sync::ProfileGossipMessage::packet(envelope);
@@ -858,10 +1755,10 @@ impl SyncEngine {
 
                     match &direct_result {
                         Ok(()) => {
-                            info!(sequence, to = %did, "Sent packet to contact via 1:1 topic");
+                            info!(sequence, %correlation_id, to = %did, "Sent packet to contact via 1:1 topic");
                         }
                         Err(e) => {
-                            info!(sequence, to = %did, error = %e, "Direct send to contact failed");
+                            info!(sequence, %correlation_id, to = %did, error = %e, "Direct send to contact failed");
                         }
                     }
 
@@ -869,83 +1766,15 @@ impl SyncEngine {
                     // This is critical because iroh-gossip's broadcast() succeeds silently
                     // even when no peers are connected to the topic. The recipient might
                     // be offline, so we send to mutual peers who can store-and-forward.
-                    //
-                    // We prefer stored mutual_peers (populated via MeshUpdate messages) because
-                    // they represent true mutual contacts. Fall back to dynamic computation
-                    // for backward compatibility with contacts created before MeshUpdate.
-                    let mutual_peers = match self.storage.load_contact(&did.to_string()) {
-                        Ok(Some(contact)) if !contact.mutual_peers.is_empty() => {
-                            debug!(
-                                recipient = %did,
-                                stored_mutual_peers = ?contact.mutual_peers,
-                                "Using stored mutual_peers for relay routing"
-                            );
-                            contact.mutual_peers
-                        }
-                        _ => {
-                            // Fallback: compute dynamically (any of our contacts except recipient)
-                            let computed = self.compute_mutual_peers_with(&did.to_string());
-                            if !computed.is_empty() {
-                                debug!(
-                                    recipient = %did,
-                                    computed_count = computed.len(),
-                                    "Using dynamically computed mutual_peers (stored list empty)"
-                                );
-                            }
-                            computed
-                        }
-                    };
-                    if !mutual_peers.is_empty() {
-                        // Get our DID for the relay wrapper
-                        let our_did = self.did().map(|d| d.to_string()).unwrap_or_default();
-
-                        // Create relay wrapper with the original encrypted bytes
-                        let relay_wrapper = RelayWrapper::new(
-                            did.to_string(),      // final_recipient
-                            our_did,              // original_sender
-                            bytes.clone(),        // original encrypted packet
+                    let relays = self
+                        .send_via_relays(contact_mgr, did.as_ref(), &entry.envelope)
+                        .await;
+                    if !relays.is_empty() {
+                        info!(
+                            sequence,
+                            relay_count = relays.len(),
+                            "Proactive relay complete"
                         );
-
-                        let relay_bytes = match relay_wrapper.to_bytes() {
-                            Ok(b) => b,
-                            Err(e) => {
-                                warn!(error = %e, "Failed to serialize relay wrapper");
-                                Vec::new()
-                            }
-                        };
-
-                        if !relay_bytes.is_empty() {
-                            // Send via ALL mutual peers for redundancy
-                            let mut relay_success_count = 0;
-                            for relay_peer_did in &mutual_peers {
-                                if contact_mgr
-                                    .send_packet_to_contact(relay_peer_did, &relay_bytes)
-                                    .await
-                                    .is_ok()
-                                {
-                                    info!(
-                                        sequence,
-                                        relay_via = %relay_peer_did,
-                                        final_recipient = %did,
-                                        relay_id = ?relay_wrapper.relay_id,
-                                        "Packet also sent via mutual peer (proactive relay)"
-                                    );
-                                    relay_success_count += 1;
-                                } else {
-                                    debug!(
-                                        relay_via = %relay_peer_did,
-                                        "Relay peer unavailable"
-                                    );
-                                }
-                            }
-                            if relay_success_count > 0 {
-                                info!(
-                                    sequence,
-                                    relay_count = relay_success_count,
-                                    "Proactive relay complete"
-                                );
-                            }
-                        }
                     }
 
                     // If direct send failed and no relay was possible, try global gossip
@@ -1100,6 +1929,155 @@ impl SyncEngine {
         Ok(())
     }
 
+    /// Send a sealed packet to `target_did` through mutual peers only.
+    ///
+    /// Each relay stores the packet in its mirror of the target and forwards it
+    /// when the target's contact topic comes back up. Relays see the wrapper
+    /// metadata but cannot read the payload, which stays sealed for the target.
+    ///
+    /// # Returns
+    ///
+    /// The DIDs of the relays that accepted the packet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contact manager is not running or no mutual
+    /// peer could be reached.
+    pub async fn relay_packet_to(
+        &self,
+        target_did: &Did,
+        envelope: &PacketEnvelope,
+    ) -> Result<Vec<String>, SyncError> {
+        let contact_mgr = self.contact_manager.as_ref().ok_or_else(|| {
+            SyncError::Network(
+                "Contact manager not initialized. Cannot relay packet.".to_string(),
+            )
+        })?;
+
+        let target = target_did.to_string();
+        let relays = self.send_via_relays(contact_mgr, &target, envelope).await;
+        let Some(relay_did) = relays.first() else {
+            return Err(SyncError::Network(format!(
+                "No mutual peer available to relay packet to {}",
+                target
+            )));
+        };
+
+        let display_name = |did: &str| {
+            self.storage
+                .load_peer_by_did(did)
+                .ok()
+                .flatten()
+                .map(|p| p.display_name())
+                .unwrap_or_else(|| format!("{}...", &did[..16.min(did.len())]))
+        };
+        let my_did = envelope.sender.to_string();
+        self.packet_event_buffer.record(crate::sync::PacketEvent {
+            id: crate::sync::PacketEvent::make_id(&my_did, envelope.sequence),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            direction: crate::sync::PacketDirection::Outgoing,
+            sequence: envelope.sequence,
+            author_did: my_did,
+            author_name: "Me".to_string(),
+            relay_did: Some(relay_did.clone()),
+            relay_name: Some(display_name(relay_did)),
+            destination_did: target.clone(),
+            destination_name: display_name(&target),
+            decryption_status: crate::sync::DecryptionStatus::Decrypted,
+            content_preview: "[sent]".to_string(),
+            is_delivered: false,
+            peer_did: target,
+        });
+
+        Ok(relays)
+    }
+
+    /// Pick the peers that can store-and-forward packets for `target_did`.
+    ///
+    /// Prefers the stored `mutual_peers` (populated via MeshUpdate messages)
+    /// because they are known contacts of the target, and falls back to
+    /// [`Self::compute_mutual_peers_with`] for contacts created before
+    /// MeshUpdate existed.
+    fn relay_candidates(&self, target_did: &str) -> Vec<String> {
+        match self.storage.load_contact(target_did) {
+            Ok(Some(contact)) if !contact.mutual_peers.is_empty() => {
+                debug!(
+                    recipient = %target_did,
+                    stored_mutual_peers = ?contact.mutual_peers,
+                    "Using stored mutual_peers for relay routing"
+                );
+                contact.mutual_peers
+            }
+            _ => {
+                let computed = self.compute_mutual_peers_with(target_did);
+                if !computed.is_empty() {
+                    debug!(
+                        recipient = %target_did,
+                        computed_count = computed.len(),
+                        "Using dynamically computed mutual_peers (stored list empty)"
+                    );
+                }
+                computed
+            }
+        }
+    }
+
+    /// Wrap `envelope` for store-and-forward and send it to every relay
+    /// candidate for `target_did`, returning the relays that accepted it.
+    async fn send_via_relays(
+        &self,
+        contact_mgr: &ContactManager,
+        target_did: &str,
+        envelope: &PacketEnvelope,
+    ) -> Vec<String> {
+        let mutual_peers = self.relay_candidates(target_did);
+        if mutual_peers.is_empty() {
+            return Vec::new();
+        }
+
+        // The wrapped payload is the encoded envelope itself, which the relay
+        // indexes by recipient without being able to open it
+        let payload = match envelope.encode() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(error = %e, "Failed to encode packet for relay");
+                return Vec::new();
+            }
+        };
+        let our_did = self.did().map(|d| d.to_string()).unwrap_or_default();
+        let relay_wrapper = RelayWrapper::new(target_did.to_string(), our_did, payload);
+        let relay_bytes = match relay_wrapper.to_bytes() {
+            Ok(b) => b,
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize relay wrapper");
+                return Vec::new();
+            }
+        };
+
+        // Send via ALL mutual peers for redundancy
+        let mut accepted = Vec::new();
+        for relay_peer_did in mutual_peers {
+            if contact_mgr
+                .send_packet_to_contact(&relay_peer_did, &relay_bytes)
+                .await
+                .is_ok()
+            {
+                info!(
+                    sequence = envelope.sequence,
+                    correlation_id = %envelope.correlation_id(),
+                    relay_via = %relay_peer_did,
+                    final_recipient = %target_did,
+                    relay_id = ?relay_wrapper.relay_id,
+                    "Packet sent via mutual peer for store-and-forward"
+                );
+                accepted.push(relay_peer_did);
+            } else {
+                debug!(relay_via = %relay_peer_did, "Relay peer unavailable");
+            }
+        }
+        accepted
+    }
+
     /// Create a packet and broadcast it to the network.
     ///
     /// This is the recommended way to send packets as it ensures they
@@ -1130,30 +2108,28 @@ impl SyncEngine {
     /// Helper to get recipient's public keys for sealed boxes.
     ///
     /// This looks up the recipient's ProfilePublicKeys from stored contacts.
-    /// Returns an error if:
-    /// - No contact found for the recipient DID
-    /// - Contact exists but has no encryption keys (legacy contact)
-    /// - Encryption keys are malformed
+    ///
+    /// # Errors
+    ///
+    /// - `SyncError::ContactKeyExchangeIncomplete` if no contact exists for the DID
+    /// - `SyncError::RecipientKeysMissing` if the contact has no encryption keys
+    ///   (a legacy contact that predates E2E encryption)
+    /// - `SyncError::Identity` if the stored keys are malformed
     fn get_recipient_public_keys(
         &self,
         recipient: &Did,
     ) -> Result<crate::profile::ProfilePublicKeys, SyncError> {
-        // Look up contact by DID
-        let contact = self.storage.load_contact(recipient.as_ref())?
-            .ok_or_else(|| SyncError::Identity(format!(
-                "No contact found for recipient: {}. \
-                 Contact exchange must complete before sending E2E encrypted messages.",
-                recipient
-            )))?;
-
-        // Extract encryption keys from contact
-        let enc_keys_bytes = contact.encryption_keys
-            .ok_or_else(|| SyncError::Identity(format!(
-                "Contact {} does not have encryption keys. \
-                 This is a legacy contact that predates E2E encryption support. \
-                 Re-exchanging contact will enable encrypted messaging.",
-                recipient
-            )))?;
+        let contact = self.storage.load_contact(recipient.as_ref())?.ok_or_else(|| {
+            SyncError::ContactKeyExchangeIncomplete {
+                did: recipient.to_string(),
+            }
+        })?;
+
+        let enc_keys_bytes = contact
+            .encryption_keys
+            .ok_or_else(|| SyncError::RecipientKeysMissing {
+                did: recipient.to_string(),
+            })?;
 
         // Deserialize ProfilePublicKeys
         crate::profile::ProfilePublicKeys::from_bytes(&enc_keys_bytes)
@@ -1163,10 +2139,64 @@ impl SyncEngine {
             )))
     }
 
+    /// Check an incoming packet's signature against its sender's known keys.
+    ///
+    /// The sender's signing key comes from our own profile keys, the
+    /// contact's exchanged encryption keys, or their pinned profile, in
+    /// that order.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if the signature is valid, `Ok(false)` if it is not.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::ContactKeyExchangeIncomplete` if we know no key
+    /// for the sender, so the packet can't be checked yet.
+    pub fn verify_incoming_packet(&self, envelope: &PacketEnvelope) -> Result<bool, SyncError> {
+        packet_gate::verify_packet(&self.storage, self.profile_keys.as_ref(), envelope)
+    }
+
+    /// Whether a stored packet came from a sender we had no keys for, so its
+    /// signature has not been checked yet.
+    ///
+    /// The flag is kept in the mirror store, so it survives restarts. It is
+    /// cleared once the sender's keys arrive and the signature checks out;
+    /// a packet that fails the check then is removed instead.
+    pub fn is_provisional_packet(&self, sender: &Did, sequence: u64) -> bool {
+        self.mirror_store
+            .as_ref()
+            .and_then(|mirror| mirror.is_provisional(sender, sequence).ok())
+            .unwrap_or(false)
+    }
+
+    /// Check provisional packets again now that more keys may be known.
+    fn reverify_provisional_packets(&self) {
+        let Some(mirror) = self.mirror_store.as_ref() else {
+            return;
+        };
+        match packet_gate::reverify_provisional(&self.storage, mirror, self.profile_keys.as_ref(), None) {
+            Ok((0, 0)) => {}
+            Ok((confirmed, removed)) => {
+                debug!(confirmed, removed, "Settled provisional packets");
+            }
+            Err(e) => warn!(error = %e, "Failed to re-verify provisional packets"),
+        }
+    }
+
     /// Handle an incoming packet from a peer.
     ///
     /// This validates the packet signature, checks the hash chain,
-    /// and stores it in the appropriate mirror.
+    /// and stores it in the appropriate mirror. Packets from senders whose
+    /// keys we know are rejected unless correctly signed; packets from
+    /// unknown senders are stored but flagged as provisional (see
+    /// [`Self::is_provisional_packet`]). Verified senders over the packet
+    /// rate limit in [`GossipConfig`] have their new packets dropped, with
+    /// one `SyncEvent::PeerRateLimited` per flood; unverified senders share
+    /// one budget. New packets are recorded as
+    /// packet events, with their decryption status and, when we could read
+    /// them, a content preview. Newly stored direct messages addressed to us
+    /// are acknowledged with a receipt to the sender.
     ///
     /// # Arguments
     ///
@@ -1175,14 +2205,10 @@ impl SyncEngine {
     /// # Returns
     ///
     /// `Ok(true)` if the packet was new and stored successfully.
-    /// `Ok(false)` if we already had this packet.
-    /// `Err` if the packet is invalid.
+    /// `Ok(false)` if we already had this packet or the sender is rate limited.
+    /// `Err` if the packet is invalid, e.g. `SyncError::SignatureInvalid`, or
+    /// `SyncError::PayloadTooLarge` if it is over the configured size limit.
     pub fn handle_incoming_packet(&mut self, envelope: PacketEnvelope) -> Result<bool, SyncError> {
-        // TODO: Verify signature using sender's stored public keys
-        // For now, we store without full verification since contact key lookup isn't implemented.
-        // In production, this should call: envelope.verify(&sender_public_keys)
-        // The MirrorStore validates hash chain integrity internally.
-
         // Store in mirror
         let mirror = self.mirror_store.as_ref().ok_or_else(|| {
             SyncError::Storage("Mirror store not initialized".to_string())
@@ -1196,9 +2222,52 @@ impl SyncEngine {
             }
         }
 
-        // Store the packet (validates hash chain)
-        mirror.store_packet(&envelope)?;
-        debug!(sender = %envelope.sender, sequence = envelope.sequence, "Stored incoming packet");
+        // Packets from senders whose keys we know must carry their signature;
+        // from anyone else they are kept provisionally until we learn the keys
+        let Some(trust) = self.packet_gate.admit(
+            &self.storage,
+            mirror,
+            self.profile_keys.as_ref(),
+            &envelope,
+        )?
+        else {
+            return Ok(false);
+        };
+        let provisional = trust == PacketTrust::Provisional;
+        debug!(
+            sender = %envelope.sender,
+            sequence = envelope.sequence,
+            correlation_id = %envelope.correlation_id(),
+            provisional,
+            "Stored incoming packet"
+        );
+        let payload = self.record_incoming_packet(&envelope);
+        if let Some(PacketPayload::DirectMessage { content, .. }) = &payload {
+            if let Some(me) = self.profile_did().filter(|me| *me != envelope.sender) {
+                self.send_receipt(&envelope);
+                let mentioned = crate::chat::find_mentions(content, |handle| self.resolve_mention(handle))
+                    .iter()
+                    .any(|mention| mention.did == me);
+                if mentioned {
+                    debug!(sender = %envelope.sender, sequence = envelope.sequence, "Mentioned in message");
+                    self.emit_event(SyncEvent::Mentioned {
+                        sender_did: envelope.sender.to_string(),
+                        sequence: envelope.sequence,
+                    });
+                }
+            }
+        }
+        if let Some(PacketPayload::ReputationReport {
+            subject_did,
+            violation,
+            severity,
+        }) = &payload
+        {
+            // Unverified senders could be anyone, so their word counts for nothing
+            if !provisional {
+                self.apply_reputation_report(&envelope.sender, subject_did, violation, *severity);
+            }
+        }
 
         Ok(true)
     }
@@ -1217,39 +2286,277 @@ impl SyncEngine {
     /// The decrypted payload if this packet was addressed to us and decryption succeeded.
     /// Returns `None` if we're not a recipient or can't decrypt.
     pub fn decrypt_packet(&self, envelope: &PacketEnvelope) -> Option<PacketPayload> {
-        // Global packets are not encrypted
-        if envelope.is_global() {
-            return envelope.decode_global_payload().ok();
-        }
+        let realm_key = self.group_packet_key(envelope);
+        crate::sync::DecryptionStatus::classify(envelope, self.profile_keys.as_ref(), realm_key.as_ref()).1
+    }
 
-        // Sealed packets require our keys to decrypt
-        let keys = self.profile_keys.as_ref()?;
-        envelope.decrypt_for_recipient(keys).ok()
+    /// Key of the realm a group packet is addressed to, if we are a member.
+    fn group_packet_key(&self, envelope: &PacketEnvelope) -> Option<[u8; 32]> {
+        let realm_keys = self.storage.list_realm_keys().ok()?;
+        envelope.group_realm(&realm_keys).map(|(_, key)| *key)
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Realm Operations
-    // ═══════════════════════════════════════════════════════════════════════
+    /// Number of oversized packets and changes rejected from `sender_did`.
+    ///
+    /// Well-behaved peers never hit the limits, so any count is a mark against them.
+    pub fn oversized_payloads_from(&self, sender_did: &str) -> u64 {
+        self.packet_gate.oversized_payloads_from(sender_did)
+    }
 
-    /// Create a new realm with the given name
+    /// Our trust in the peer with `did`, from 0.0 to 1.0.
     ///
-    /// Creates the realm in storage with a new encryption key and
-    /// opens it for immediate use.
+    /// Starts neutral and drops with every violation we see from the peer
+    /// and, to a lesser degree, with violations other peers report.
+    pub fn peer_trust(&self, did: &str) -> f64 {
+        self.packet_gate.trust(did)
+    }
+
+    /// Warn other peers that `subject` misbehaved toward us.
+    ///
+    /// The violation counts against `subject` locally, and a signed
+    /// [`PacketPayload::ReputationReport`] goes out on the global topic. When
+    /// we aren't networking the report stays in our log, and peers pick it up
+    /// when they next sync it.
     ///
     /// # Returns
     ///
-    /// The ID of the newly created realm.
-    pub async fn create_realm(&mut self, name: &str) -> Result<RealmId, SyncError> {
-        // Prevent creating realms with reserved "Private" name
-        if is_private_realm_name(name) {
-            return Err(SyncError::PrivateRealmOperation(
-                "Cannot create realm with reserved name 'Private'".to_string(),
-            ));
-        }
-
-        info!(name, "Creating new realm");
+    /// The sequence number of the report packet.
+    pub async fn report_peer(
+        &mut self,
+        subject: &Did,
+        violation: &str,
+        severity: u8,
+    ) -> Result<u64, SyncError> {
+        self.init_profile_keys()?;
+        self.packet_gate.record_violation(subject.as_str(), severity);
 
-        let realm_info = RealmInfo::new(name);
+        let payload = PacketPayload::ReputationReport {
+            subject_did: subject.clone(),
+            violation: violation.to_string(),
+            severity,
+        };
+        let seq = self.create_packet(payload, PacketAddress::Global)?;
+        if let Err(e) = self.broadcast_packet(seq, &PacketAddress::Global).await {
+            debug!(%subject, error = %e, "Reputation report kept in log for later sync");
+        }
+        Ok(seq)
+    }
+
+    /// Lower our trust in `subject` after `reporter` warned us about it
+    fn apply_reputation_report(&mut self, reporter: &Did, subject: &Did, violation: &str, severity: u8) {
+        if self.profile_did().is_some_and(|me| &me == subject) {
+            debug!(%reporter, violation, "Ignoring reputation report about us");
+            return;
+        }
+        let taken = self
+            .packet_gate
+            .apply_report(reporter.as_str(), subject.as_str(), severity);
+        debug!(
+            %reporter,
+            %subject,
+            violation,
+            severity,
+            taken,
+            trust = self.packet_gate.trust(subject.as_str()),
+            "Applied reputation report"
+        );
+    }
+
+    /// Number of incoming packets from `sender_did` dropped by the rate limiter.
+    pub fn packet_rate_violations(&self, sender_did: &str) -> u64 {
+        self.packet_gate.rate_violations(sender_did)
+    }
+
+    /// Number of incoming packets addressed to us that failed to decrypt.
+    ///
+    /// A rising count points at tampering or a sender using stale keys.
+    pub fn packet_decryption_failures(&self) -> u64 {
+        self.packet_decryption_failures.values().sum()
+    }
+
+    /// Number of incoming packets from `sender_did` that failed to decrypt.
+    pub fn packet_decryption_failures_from(&self, sender_did: &str) -> u64 {
+        self.packet_decryption_failures
+            .get(sender_did)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Record an incoming packet for the network visualization
+    ///
+    /// Classifies whether we could read it and counts failed decryptions
+    /// against the sender. Returns the payload if we could read it.
+    fn record_incoming_packet(&mut self, envelope: &PacketEnvelope) -> Option<PacketPayload> {
+        let sender_did = envelope.sender.to_string();
+        let realm_key = self.group_packet_key(envelope);
+        let (decryption_status, payload) =
+            crate::sync::DecryptionStatus::classify(envelope, self.profile_keys.as_ref(), realm_key.as_ref());
+
+        if let crate::sync::DecryptionStatus::Failed { reason } = &decryption_status {
+            warn!(sender = %sender_did, sequence = envelope.sequence, %reason, "Failed to decrypt incoming packet");
+            *self
+                .packet_decryption_failures
+                .entry(sender_did.clone())
+                .or_default() += 1;
+        }
+
+        let (destination_did, destination_name) = match &decryption_status {
+            crate::sync::DecryptionStatus::Global => ("global".to_string(), "Everyone".to_string()),
+            crate::sync::DecryptionStatus::NotForMe => {
+                let did = envelope
+                    .recipients()
+                    .first()
+                    .map(|d| d.as_str().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                let name = self
+                    .storage
+                    .load_peer_by_did(&did)
+                    .ok()
+                    .flatten()
+                    .map(|p| p.display_name())
+                    .unwrap_or_else(|| "Unknown".to_string());
+                (did, name)
+            }
+            _ => (
+                self.profile_did().map(|d| d.to_string()).unwrap_or_default(),
+                "Me".to_string(),
+            ),
+        };
+        let author_name = self
+            .storage
+            .load_peer_by_did(&sender_did)
+            .ok()
+            .flatten()
+            .map(|p| p.display_name())
+            .unwrap_or_else(|| format!("{}...", &sender_did[..16.min(sender_did.len())]));
+
+        let event = crate::sync::PacketEvent {
+            id: crate::sync::PacketEvent::make_id(&sender_did, envelope.sequence),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            direction: crate::sync::PacketDirection::Incoming,
+            sequence: envelope.sequence,
+            author_did: sender_did.clone(),
+            author_name,
+            relay_did: None,
+            relay_name: None,
+            destination_did,
+            destination_name,
+            content_preview: crate::sync::PacketEvent::preview_for(&decryption_status, payload.as_ref()),
+            decryption_status,
+            is_delivered: false,
+            peer_did: sender_did,
+        };
+        self.packet_event_buffer.record(event);
+        payload
+    }
+
+    /// Acknowledge a direct message we just stored.
+    ///
+    /// Appends a [`PacketPayload::Receipt`] sealed for the sender to our log
+    /// and, when the contact topic is up, sends it straight away. Callers only
+    /// acknowledge newly stored packets, so each message gets one receipt.
+    fn send_receipt(&mut self, envelope: &PacketEnvelope) {
+        let receipt = PacketPayload::Receipt {
+            original_sender: envelope.sender.clone(),
+            packet_seq: envelope.sequence,
+        };
+        let sequence = match self.create_packet(receipt, PacketAddress::Individual(envelope.sender.clone())) {
+            Ok(seq) => seq,
+            Err(e) => {
+                warn!(sender = %envelope.sender, acked = envelope.sequence, error = %e, "Failed to create receipt");
+                return;
+            }
+        };
+        debug!(sender = %envelope.sender, acked = envelope.sequence, sequence, "Created receipt");
+
+        let Some(contact_mgr) = self.contact_manager.clone() else {
+            return;
+        };
+        let Some(entry) = self.profile_log.as_ref().and_then(|log| log.get(sequence)) else {
+            return;
+        };
+        let bytes = match crate::sync::ProfileGossipMessage::packet(entry.envelope.clone()).to_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(error = %e, "Failed to encode receipt");
+                return;
+            }
+        };
+        let to = envelope.sender.to_string();
+        self.in_flight.spawn(async move {
+            if let Err(e) = contact_mgr.send_packet_to_contact(&to, &bytes).await {
+                debug!(%to, error = %e, "Receipt not sent; it stays in our log for mirror sync");
+            }
+        });
+    }
+
+    /// Sequences of our packets that `contact` has acknowledged with a receipt.
+    fn acked_sequences(&self, contact: &Did) -> BTreeSet<u64> {
+        let Some(my_did) = self.profile_did() else {
+            return BTreeSet::new();
+        };
+        self.mirror_packets_all(contact)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|envelope| match self.decrypt_packet(envelope) {
+                Some(PacketPayload::Receipt { original_sender, packet_seq }) if original_sender == my_did => {
+                    Some(packet_seq)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sequence below which our own log can be garbage collected.
+    ///
+    /// Every direct message before the returned sequence has been
+    /// acknowledged by its recipient, so it is safe to use as the
+    /// `before_sequence` of a [`PacketPayload::Depin`]. Other packet kinds are
+    /// not acknowledged and never hold collection back.
+    pub fn gc_eligible_before(&self) -> u64 {
+        let Some(log) = self.profile_log.as_ref() else {
+            return 0;
+        };
+        let mut acked: HashMap<Did, BTreeSet<u64>> = HashMap::new();
+        for entry in log.entries_ordered() {
+            let Some(PacketPayload::DirectMessage { recipient, .. }) = self.decrypt_packet(&entry.envelope) else {
+                continue;
+            };
+            let acks = acked
+                .entry(recipient.clone())
+                .or_insert_with(|| self.acked_sequences(&recipient));
+            if !acks.contains(&entry.envelope.sequence) {
+                return entry.envelope.sequence;
+            }
+        }
+        log.head_sequence().map(|seq| seq + 1).unwrap_or(0)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Realm Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Create a new realm with the given name
+    ///
+    /// Creates the realm in storage with a new encryption key and
+    /// opens it for immediate use.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly created realm.
+    pub async fn create_realm(&mut self, name: &str) -> Result<RealmId, SyncError> {
+        // Prevent creating realms with reserved "Private" name
+        if is_private_realm_name(name) {
+            return Err(SyncError::PrivateRealmOperation(
+                "Cannot create realm with reserved name 'Private'".to_string(),
+            ));
+        }
+
+        info!(name, "Creating new realm");
+
+        let mut realm_info = RealmInfo::new(name);
+        realm_info.is_creator = true;
+        realm_info.owner = self.did().map(|did| did.to_string());
         let realm_id = realm_info.id.clone();
 
         // Generate encryption key
@@ -1271,6 +2578,10 @@ impl SyncEngine {
                 doc,
                 topic_sender: None,
                 realm_key,
+                listener: None,
+                dirty: false,
+                unsynced: false,
+                paused: false,
             },
         );
 
@@ -1278,6 +2589,46 @@ impl SyncEngine {
         Ok(realm_id)
     }
 
+    /// Create a new realm seeded with a template's tasks
+    ///
+    /// The tasks are created in template order, and `list_tasks` returns
+    /// them in that order. The reserved "Private" name is refused just as
+    /// in [`create_realm`](Self::create_realm).
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly created realm.
+    pub async fn create_realm_from_template(
+        &mut self,
+        name: &str,
+        template: &RealmTemplate,
+    ) -> Result<RealmId, SyncError> {
+        let realm_id = self.create_realm(name).await?;
+
+        let author = self.did().map(|did| did.to_string());
+        {
+            let state = self
+                .realms
+                .get_mut(&realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            // Monotonic IDs keep same-second tasks in template order
+            for entry in &template.tasks {
+                let mut task = Task::new_quest(&entry.title, entry.subtitle.clone(), &entry.description);
+                task.id = self.task_ids.next_id();
+                task.category = entry.category.clone();
+                task.created_by = author.clone();
+                state.doc.insert_task(&task)?;
+            }
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+        }
+
+        self.save_realm(&realm_id).await?;
+        info!(%realm_id, template = %template.name, tasks = template.tasks.len(), "Realm created from template");
+        Ok(realm_id)
+    }
+
     /// List all realms from storage
     pub async fn list_realms(&self) -> Result<Vec<RealmInfo>, SyncError> {
         self.storage.list_realms()
@@ -1288,6 +2639,93 @@ impl SyncEngine {
         self.storage.load_realm(realm_id)
     }
 
+    /// Set a realm's description, cover image, color and emoji
+    ///
+    /// The metadata lives in the realm document, so it reaches other members
+    /// with the next sync and shows up in their [`RealmInfo`] as well.
+    /// Auto-opens and auto-saves the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn set_realm_metadata(
+        &mut self,
+        realm_id: &RealmId,
+        metadata: RealmMetadata,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable(realm_id)?;
+
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
+
+        let author = self.did().map(|did| did.to_string());
+        let sync_data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            state.doc.set_metadata(&metadata)?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+            state.doc.generate_sync_message()
+        };
+
+        self.save_realm(realm_id).await?;
+        self.refresh_realm_metadata(realm_id)?;
+
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast realm metadata (may not be syncing)");
+            }
+        }
+
+        debug!(%realm_id, "Realm metadata updated");
+        Ok(())
+    }
+
+    /// Mute or unmute notifications for a realm
+    ///
+    /// A muted realm keeps syncing as usual; only its reminder events are
+    /// held back. The setting is kept on this node and never shared.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub fn set_realm_muted(&mut self, realm_id: &RealmId, muted: bool) -> Result<(), SyncError> {
+        let mut info = self
+            .storage
+            .load_realm(realm_id)?
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+        if info.muted != muted {
+            info.muted = muted;
+            self.storage.save_realm(&info)?;
+        }
+        debug!(%realm_id, muted, "Realm mute changed");
+        Ok(())
+    }
+
+    /// Whether notifications for a realm are muted on this node
+    pub fn is_realm_muted(&self, realm_id: &RealmId) -> bool {
+        matches!(self.storage.load_realm(realm_id), Ok(Some(info)) if info.muted)
+    }
+
+    /// Copy the metadata in an open realm's document into its stored [`RealmInfo`]
+    fn refresh_realm_metadata(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let Some(state) = self.realms.get(realm_id) else {
+            return Ok(());
+        };
+        let metadata = state.doc.metadata()?;
+        if let Some(mut info) = self.storage.load_realm(realm_id)? {
+            if info.metadata != metadata {
+                info.metadata = metadata;
+                self.storage.save_realm(&info)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Open a realm from storage for use
     ///
     /// Loads the realm's document and encryption key into memory.
@@ -1339,6 +2777,10 @@ impl SyncEngine {
                 doc,
                 topic_sender: None,
                 realm_key,
+                listener: None,
+                dirty: false,
+                unsynced: false,
+                paused: false,
             },
         );
 
@@ -1385,11 +2827,86 @@ impl SyncEngine {
 
         let doc_bytes = state.doc.save();
         self.storage.save_document(realm_id, &doc_bytes)?;
+        state.dirty = false;
 
         debug!(%realm_id, bytes = doc_bytes.len(), "Realm saved");
         Ok(())
     }
 
+    /// Save every open realm whose document has unsaved changes
+    ///
+    /// Realms that fail to save stay dirty and are retried on the next call.
+    ///
+    /// # Returns
+    ///
+    /// The number of realms written to storage.
+    pub fn save_dirty_realms(&mut self) -> usize {
+        let mut saved = 0;
+        for (realm_id, state) in self.realms.iter_mut().filter(|(_, state)| state.dirty) {
+            match self.storage.save_document(realm_id, &state.doc.save()) {
+                Ok(()) => {
+                    state.dirty = false;
+                    saved += 1;
+                }
+                Err(e) => warn!(%realm_id, error = ?e, "Autosave failed; will retry"),
+            }
+        }
+        self.last_autosave = Instant::now();
+        saved
+    }
+
+    /// Save dirty realms if the autosave interval has passed
+    ///
+    /// Called from [`process_pending_sync`](Self::process_pending_sync); hosts
+    /// that don't poll for sync messages should call it on a timer. With
+    /// [`GossipConfig::autosave_interval`] of N seconds, a crash loses at most
+    /// N seconds of edits that weren't otherwise saved.
+    ///
+    /// # Returns
+    ///
+    /// The number of realms written to storage.
+    pub fn autosave(&mut self) -> usize {
+        if self.last_autosave.elapsed() < self.gossip_config.effective_autosave_interval() {
+            return 0;
+        }
+        self.save_dirty_realms()
+    }
+
+    /// What [`Self::delete_realm`] would remove, without removing anything
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::PrivateRealmOperation` for the Private realm.
+    pub fn delete_realm_preview(&self, realm_id: &RealmId) -> Result<RealmDeletePreview, SyncError> {
+        let info = self
+            .storage
+            .load_realm(realm_id)?
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+        if is_private_realm_name(&info.name) {
+            return Err(SyncError::PrivateRealmOperation(
+                "Cannot delete Private realm".to_string(),
+            ));
+        }
+
+        let task_count = match self.realms.get(realm_id) {
+            Some(state) => state.doc.list_tasks()?.len(),
+            None => match self.storage.load_document(realm_id)? {
+                Some(bytes) => RealmDoc::load(&bytes)?.list_tasks()?.len(),
+                None => 0,
+            },
+        };
+
+        Ok(RealmDeletePreview {
+            realm_id: realm_id.clone(),
+            name: info.name,
+            is_shared: info.is_shared,
+            task_count,
+            member_count: self.storage.list_realm_members(realm_id)?.len(),
+            snapshot_count: self.storage.list_realm_snapshots(realm_id)?.len(),
+        })
+    }
+
     /// Delete a realm and all its data
     pub async fn delete_realm(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
         // Check if this is the Private realm
@@ -1406,10 +2923,94 @@ impl SyncEngine {
 
         // Delete from storage
         self.storage.delete_realm(realm_id)?;
+        self.storage.delete_realm_members(realm_id)?;
+        self.storage.delete_realm_roles(realm_id)?;
+        self.storage.delete_realm_snapshots(realm_id)?;
+        self.storage.delete_realm_history(realm_id)?;
         info!(%realm_id, "Deleted realm");
         Ok(())
     }
 
+    /// Leave a shared realm while letting other members keep it
+    ///
+    /// Broadcasts a `Leave` message (best effort) so peers drop us from their
+    /// roster, then unsubscribes from the realm's gossip topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `realm_id` - The realm to leave
+    /// * `keep_copy` - If `true`, the realm stays in storage as an unshared,
+    ///   read-only copy of its current tasks. Otherwise all local data is removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::PrivateRealmOperation` for the Private realm.
+    pub async fn leave_realm(&mut self, realm_id: &RealmId, keep_copy: bool) -> Result<(), SyncError> {
+        let mut info = self
+            .storage
+            .load_realm(realm_id)?
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        if is_private_realm_name(&info.name) {
+            return Err(SyncError::PrivateRealmOperation(
+                "Cannot leave Private realm".to_string(),
+            ));
+        }
+
+        if self.is_realm_syncing(realm_id) {
+            let leave = SyncMessage::Leave {
+                realm_id: realm_id.clone(),
+            };
+            if let Err(e) = self.broadcast_sync(realm_id, leave).await {
+                debug!(%realm_id, error = ?e, "Failed to announce leave (non-fatal)");
+            }
+            self.stop_sync(realm_id).await?;
+        }
+        self.sync_status.lock().unwrap().remove(realm_id);
+        self.storage.delete_realm_members(realm_id)?;
+        self.storage.delete_realm_roles(realm_id)?;
+
+        if keep_copy {
+            if self.realms.contains_key(realm_id) {
+                self.save_realm(realm_id).await?;
+            }
+            info.is_shared = false;
+            info.read_only = true;
+            info.bootstrap_peers.clear();
+            self.storage.save_realm(&info)?;
+            info!(%realm_id, "Left realm, keeping read-only copy");
+        } else {
+            self.realms.remove(realm_id);
+            self.storage.delete_realm(realm_id)?;
+            self.storage.delete_realm_snapshots(realm_id)?;
+            self.storage.delete_realm_history(realm_id)?;
+            info!(%realm_id, "Left realm");
+        }
+
+        Ok(())
+    }
+
+    /// Reject changes to a realm kept as a read-only copy after leaving it,
+    /// or to a realm where we are a viewer (peers would drop the changes)
+    fn ensure_writable(&self, realm_id: &RealmId) -> Result<(), SyncError> {
+        match self.storage.load_realm(realm_id)? {
+            Some(info) if info.read_only => Err(SyncError::InvalidOperation(format!(
+                "Realm {} is read-only",
+                realm_id
+            ))),
+            _ => match self.did() {
+                Some(did) if !self.member_can_edit(realm_id, did.as_ref()) => {
+                    Err(SyncError::InvalidOperation(format!(
+                        "Realm {} is view-only for this member",
+                        realm_id
+                    )))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
     /// Check if a realm is currently open
     pub fn is_realm_open(&self, realm_id: &RealmId) -> bool {
         self.realms.contains_key(realm_id)
@@ -1433,6 +3034,10 @@ impl SyncEngine {
     /// The number of messages processed.
     pub fn process_pending_sync(&mut self) -> usize {
         let mut processed = 0;
+        // Pick up rekeys first so envelopes sealed with a new key can be opened
+        if let Err(e) = self.apply_realm_rekeys() {
+            debug!(error = ?e, "Failed to apply realm rekeys (non-fatal)");
+        }
         // Collect broadcast requests to handle after draining messages
         // (we can't broadcast while iterating because broadcast_changes_with_data is async)
         let mut broadcast_requests: Vec<RealmId> = Vec::new();
@@ -1444,32 +3049,140 @@ impl SyncEngine {
                     realm_id,
                     envelope_bytes,
                 }) => {
+                    self.record_capture(CaptureDirection::Inbound, &realm_id, &envelope_bytes);
                     debug!(
                         %realm_id,
                         envelope_bytes = envelope_bytes.len(),
                         "Pulled IncomingData from channel"
                     );
+                    if self.is_sync_paused(&realm_id) {
+                        debug!(%realm_id, "Sync paused - dropping incoming data");
+                        continue;
+                    }
+                    // The same envelope arriving over another path is dropped
+                    // before it is verified or decrypted again
+                    let envelope_hash = content_hash(&envelope_bytes);
+                    if self.is_repeat_change(&envelope_hash) {
+                        debug!(%realm_id, "Dropped repeated envelope");
+                        continue;
+                    }
                     // Try to process this incoming message
-                    match self.handle_incoming(&realm_id, &envelope_bytes) {
-                        Ok(Some(SyncMessage::SyncResponse { document, .. })) => {
-                            // Apply the full document
-                            if let Err(e) = self.apply_sync_changes(&realm_id, &document, true) {
-                                warn!(%realm_id, error = ?e, "Failed to apply sync response");
-                            } else {
-                                debug!(%realm_id, "Applied sync response (full doc)");
-                                processed += 1;
+                    let opened = self.open_incoming(&realm_id, &envelope_bytes);
+
+                    // Any authenticated message proves the sender participates in the realm,
+                    // except a Leave, which drops them from the roster
+                    if let Ok(Some((sender, message))) = &opened {
+                        if message.is_leave() {
+                            let now = chrono::Utc::now().timestamp();
+                            match self.storage.mark_realm_member_left(&realm_id, sender, now) {
+                                Ok(_) => info!(%realm_id, member = %sender, "Member left realm"),
+                                Err(e) => {
+                                    warn!(%realm_id, error = ?e, "Failed to remove departed member")
+                                }
                             }
+                        } else {
+                            self.note_realm_member(&realm_id, sender);
                         }
-                        Ok(Some(SyncMessage::Changes { data: changes, .. })) => {
-                            // Apply incremental changes
-                            if let Err(e) = self.apply_sync_changes(&realm_id, &changes, false) {
-                                warn!(%realm_id, error = ?e, "Failed to apply incremental changes");
-                            } else {
-                                debug!(%realm_id, "Applied incremental changes");
-                                processed += 1;
-                            }
+
+                        if let SyncMessage::Announce {
+                            sent_at_ms, heads, ..
+                        } = message
+                        {
+                            self.note_clock_sample(&realm_id, sender, *sent_at_ms);
+                            self.note_member_heads(&realm_id, sender, heads);
                         }
-                        Ok(Some(SyncMessage::SyncRequest {
+
+                        // Changes sent by viewers never reach the document
+                        if (message.is_changes() || message.is_full_document())
+                            && !self.member_can_edit(&realm_id, sender)
+                        {
+                            warn!(
+                                %realm_id,
+                                member = %sender,
+                                sync_event = message.kind(),
+                                "Rejected changes from viewer"
+                            );
+                            continue;
+                        }
+
+                        // Oversized changes are dropped before Automerge parses them
+                        let change_bytes = match message {
+                            SyncMessage::Changes { data, .. } => data.len(),
+                            SyncMessage::SyncResponse { document, .. }
+                            | SyncMessage::CompactedDocument { document, .. } => document.len(),
+                            _ => 0,
+                        };
+                        let limit = self.gossip_config.effective_max_change_bytes();
+                        if let Err(e) = self.packet_gate.check_authenticated_size(sender, change_bytes, limit) {
+                            warn!(
+                                %realm_id,
+                                member = %sender,
+                                sync_event = message.kind(),
+                                error = %e,
+                                "Rejected oversized changes"
+                            );
+                            continue;
+                        }
+
+                        // Nor do changes a viewer wrote that an editor relays
+                        if let Some(author) = self.viewer_author(&realm_id, sender, message) {
+                            warn!(
+                                %realm_id,
+                                member = %sender,
+                                %author,
+                                sync_event = message.kind(),
+                                "Rejected relayed changes from viewer"
+                            );
+                            continue;
+                        }
+                    }
+
+                    match opened.map(|o| o.map(|(_, message)| message)) {
+                        Ok(Some(
+                            SyncMessage::SyncResponse { document, .. }
+                            | SyncMessage::CompactedDocument { document, .. },
+                        )) => {
+                            // A re-sealed copy of a document we already merged changes nothing
+                            let change_hash = content_hash(&document);
+                            if self.is_repeat_change(&change_hash) {
+                                debug!(%realm_id, "Skipped repeated sync response");
+                            } else {
+                                match self.apply_sync_response(&realm_id, &document) {
+                                    Err(e) => {
+                                        warn!(%realm_id, error = ?e, "Failed to apply sync response");
+                                        self.note_network_error(Some(&realm_id), format!("apply sync response: {e}"));
+                                    }
+                                    Ok(resynced) => {
+                                        debug!(%realm_id, resynced, "Applied sync response (full doc)");
+                                        // Send the sender our document to adopt
+                                        if resynced && !broadcast_requests.contains(&realm_id) {
+                                            broadcast_requests.push(realm_id.clone());
+                                        }
+                                        self.last_sync_at = Some(Instant::now());
+                                        self.seen_changes.insert(change_hash);
+                                        processed += 1;
+                                    }
+                                }
+                            }
+                            self.seen_changes.insert(envelope_hash);
+                        }
+                        Ok(Some(SyncMessage::Changes { data: changes, .. })) => {
+                            // Apply incremental changes
+                            let change_hash = content_hash(&changes);
+                            if self.is_repeat_change(&change_hash) {
+                                debug!(%realm_id, "Skipped repeated changes");
+                            } else if let Err(e) = self.apply_sync_changes(&realm_id, &changes, false) {
+                                warn!(%realm_id, error = ?e, "Failed to apply incremental changes");
+                                self.note_network_error(Some(&realm_id), format!("apply changes: {e}"));
+                            } else {
+                                debug!(%realm_id, "Applied incremental changes");
+                                self.last_sync_at = Some(Instant::now());
+                                self.seen_changes.insert(change_hash);
+                                processed += 1;
+                            }
+                            self.seen_changes.insert(envelope_hash);
+                        }
+                        Ok(Some(SyncMessage::SyncRequest {
                             realm_id: req_realm_id,
                         })) => {
                             // Peer is requesting our state - queue a broadcast
@@ -1530,18 +3243,33 @@ impl SyncEngine {
                                 }
                             }
                         }
+                        Ok(Some(SyncMessage::Leave { .. })) => {
+                            // Roster already updated above
+                            processed += 1;
+                        }
+                        Ok(Some(SyncMessage::Roles { roles, .. })) => {
+                            match self.apply_realm_roles(&realm_id, roles) {
+                                Ok(true) => processed += 1,
+                                Ok(false) => {
+                                    debug!(%realm_id, "Ignored stale or unauthorized role table")
+                                }
+                                Err(e) => warn!(%realm_id, error = ?e, "Failed to apply role table"),
+                            }
+                        }
                         Ok(None) => {
                             // Message failed verification - ignore
                             debug!(%realm_id, "Incoming message failed verification");
                         }
                         Err(e) => {
                             warn!(%realm_id, error = ?e, "Failed to handle incoming message");
+                            self.note_network_error(Some(&realm_id), format!("incoming message: {e}"));
                         }
                     }
                 }
-                Ok(SyncChannelMessage::BroadcastRequest { realm_id }) => {
+                Ok(SyncChannelMessage::BroadcastRequest { realm_id, neighbor }) => {
                     // Queue broadcast request (will process after draining)
                     debug!(%realm_id, "Received broadcast request from listener (peer connected)");
+                    self.note_realm_neighbor(&realm_id, &neighbor);
                     if !broadcast_requests.contains(&realm_id) {
                         broadcast_requests.push(realm_id);
                     }
@@ -1564,7 +3292,7 @@ impl SyncEngine {
             if let Some(state) = self.realms.get_mut(&realm_id) {
                 if let Some(ref sender) = state.topic_sender {
                     // Get the full document to broadcast
-                    let doc_bytes = state.doc.save();
+                    let message = full_document_message(&realm_id, &mut state.doc);
 
                     // Create signed+encrypted envelope
                     let identity = match &self.identity {
@@ -1578,26 +3306,26 @@ impl SyncEngine {
                     let sender_did = Did::from_public_key(&identity.public_key()).to_string();
                     let sign_fn = |data: &[u8]| identity.sign(data).to_bytes().to_vec();
 
-                    let message = SyncMessage::SyncResponse {
-                        realm_id: realm_id.clone(),
-                        document: doc_bytes,
-                    };
-
                     match SyncEnvelope::seal(&message, &sender_did, &state.realm_key, sign_fn) {
                         Ok(envelope) => {
+                            let correlation_id = envelope.correlation_id();
                             match envelope.to_bytes() {
                                 Ok(bytes) => {
+                                    self.realm_messages_sent.fetch_add(1, Ordering::Relaxed);
+                                    if let Some(recorder) = self.message_capture.lock().as_mut() {
+                                        recorder.record(CaptureDirection::Outbound, &realm_id, &bytes);
+                                    }
                                     // Use blocking broadcast (sender.broadcast is async but we need sync)
                                     // Create a simple oneshot to handle this
                                     let sender_clone = sender.clone();
                                     let realm_id_clone = realm_id.clone();
-                                    tokio::spawn(async move {
+                                    self.in_flight.spawn(async move {
                                         if let Err(e) =
                                             sender_clone.broadcast(bytes::Bytes::from(bytes)).await
                                         {
-                                            warn!(%realm_id_clone, error = ?e, "Failed to broadcast document on peer connect");
+                                            warn!(realm_id = %realm_id_clone, %correlation_id, error = ?e, "Failed to broadcast document on peer connect");
                                         } else {
-                                            info!(%realm_id_clone, "Broadcast full document to newly connected peer");
+                                            info!(realm_id = %realm_id_clone, %correlation_id, sync_event = "sync_response", "Broadcast full document to newly connected peer");
                                         }
                                     });
                                 }
@@ -1612,8 +3340,16 @@ impl SyncEngine {
                     }
                 }
             }
+            self.rebroadcast_realm_roles(&realm_id);
         }
 
+        if processed > 0 {
+            self.save_dirty_realms();
+        } else {
+            self.autosave();
+        }
+        self.fire_due_reminders();
+
         processed
     }
 
@@ -1631,7 +3367,7 @@ impl SyncEngine {
 
         if is_full_doc {
             // Full document sync handling
-            let mut remote_doc = RealmDoc::load(data)?;
+            let remote_doc = RealmDoc::load(data)?;
 
             // Debug: Check task counts before merge
             let remote_task_count = remote_doc.list_tasks().map(|t| t.len()).unwrap_or(0);
@@ -1656,8 +3392,9 @@ impl SyncEngine {
                 state.doc = remote_doc;
             } else {
                 // Normal merge - both have history, or remote is empty
-                // Automerge CRDT merge preserves all changes when documents share history
-                state.doc.merge(&mut remote_doc)?;
+                // Automerge CRDT merge preserves all changes when documents share
+                // history; absorb also bridges a history compaction on either side
+                absorb_archiving(&self.storage, realm_id, &mut state.doc, remote_doc)?;
             }
 
             let local_task_count_after = state.doc.list_tasks().map(|t| t.len()).unwrap_or(0);
@@ -1672,11 +3409,13 @@ impl SyncEngine {
             // Incremental changes - apply sync message
             state.doc.apply_sync_message(data)?;
         }
+        state.dirty = true;
 
         // Save the updated document to disk
         // This ensures sync changes persist across app restarts
         let doc_bytes = state.doc.save();
         self.storage.save_document(realm_id, &doc_bytes)?;
+        state.dirty = false;
 
         // Debug: log task count after merge
         let task_count = state.doc.list_tasks().map(|t| t.len()).unwrap_or(0);
@@ -1688,1992 +3427,2699 @@ impl SyncEngine {
             task_count,
             "Applied and saved sync changes"
         );
+        self.refresh_realm_metadata(realm_id)?;
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Task Operations (with auto-save)
-    // ═══════════════════════════════════════════════════════════════════════
-
-    /// Add a task to a realm
+    /// Apply a peer's full document, resyncing it if it missed a compaction
     ///
-    /// Auto-saves the realm after adding the task.
+    /// A replica that was offline (or never announced itself) while the
+    /// realm was compacted can't absorb the compacted document, nor we its
+    /// copy. If we archived the history it is missing, its copy is merged
+    /// into the archive and its edits are carried forward onto our document,
+    /// which then records the replica's heads as rebased. Once it receives
+    /// our document it adopts it in place of its own (see
+    /// [`RealmDoc::absorb`]).
+    ///
+    /// Returns `true` if the sender was resynced and should be sent our
+    /// document.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub async fn add_task(&mut self, realm_id: &RealmId, title: &str) -> Result<TaskId, SyncError> {
-        // First, ensure realm is open (load from storage if needed)
-        if !self.realms.contains_key(realm_id) {
-            self.open_realm(realm_id).await?;
-        }
-
-        let (task_id, sync_data) = {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-            let task_id = state.doc.add_task(title)?;
-
-            // Capture incremental changes BEFORE save (save resets the checkpoint)
-            let sync_data = state.doc.generate_sync_message();
-
-            (task_id, sync_data)
+    /// Returns `SyncError::IncompatibleDocument` if the documents don't merge
+    /// and we hold no archive the sender's copy grew from.
+    fn apply_sync_response(&mut self, realm_id: &RealmId, document: &[u8]) -> Result<bool, SyncError> {
+        let error = match self.apply_sync_changes(realm_id, document, true) {
+            Err(e @ SyncError::IncompatibleDocument(_)) => e,
+            result => return result.map(|()| false),
         };
 
-        // Auto-save
-        self.save_realm(realm_id).await?;
+        let lagging = RealmDoc::load(document)?;
+        let mut archives = self
+            .storage
+            .list_realm_history(realm_id)?
+            .iter()
+            .map(|bytes| RealmDoc::load(bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(start) = archives.iter().position(|archive| archive.shares_history(&lagging)) else {
+            return Err(error);
+        };
 
-        // Broadcast changes to peers if syncing
-        if !sync_data.is_empty() {
-            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
-                debug!(%realm_id, error = %e, "Failed to broadcast task addition (may not be syncing)");
-            }
+        // Walk the replica's edits forward through each later compaction
+        let mut lagging = lagging;
+        let lagging_heads = lagging.heads();
+        for mut archive in archives.drain(start..) {
+            archive.absorb(lagging)?;
+            lagging = archive;
         }
-
-        debug!(%realm_id, %task_id, title, "Task added");
-        Ok(task_id)
+        let state = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+        state.doc.absorb(lagging)?;
+        state.doc.record_rebased(&lagging_heads)?;
+        self.storage.save_document(realm_id, &state.doc.save())?;
+        state.dirty = false;
+        self.refresh_realm_metadata(realm_id)?;
+        info!(%realm_id, "Rebased lagging replica onto compacted history");
+        Ok(true)
     }
 
-    /// Add a rich "quest" (task with metadata) to a realm
+    // ═══════════════════════════════════════════════════════════════════════
+    // Realm Membership
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// List the known members of a realm
     ///
-    /// Auto-saves the realm after adding the quest.
+    /// Members are DIDs observed on the realm's gossip topic: our own DID once
+    /// sync starts, the authenticated sender of every sync message we accept,
+    /// and every neighbor that joins the topic whose DID we already know.
+    /// Members who have gone offline are still listed, with the `last_seen`
+    /// of their most recent activity.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub async fn add_quest(
-        &mut self,
-        realm_id: &RealmId,
-        title: &str,
-        subtitle: Option<String>,
-        description: &str,
-        category: Option<String>,
-        image_blob_id: Option<String>,
-    ) -> Result<TaskId, SyncError> {
-        // First, ensure realm is open (load from storage if needed)
-        if !self.realms.contains_key(realm_id) {
-            self.open_realm(realm_id).await?;
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub fn realm_members(&self, realm_id: &RealmId) -> Result<Vec<RealmMember>, SyncError> {
+        if self.storage.load_realm(realm_id)?.is_none() {
+            return Err(SyncError::RealmNotFound(realm_id.to_string()));
         }
+        self.storage.list_realm_members(realm_id)
+    }
 
-        let (task_id, sync_data) = {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-            let task_id =
-                state
-                    .doc
-                    .add_quest_full(title, subtitle, description, category, image_blob_id)?;
-
-            // Capture incremental changes BEFORE save (save resets the checkpoint)
-            let sync_data = state.doc.generate_sync_message();
-
-            (task_id, sync_data)
-        };
+    /// Record activity from a realm member, logging when they are new
+    fn note_realm_member(&self, realm_id: &RealmId, did: &str) {
+        let now = chrono::Utc::now().timestamp();
+        match self.storage.record_realm_member(realm_id, did, now) {
+            Ok(true) => info!(%realm_id, member = %did, "New realm member observed"),
+            Ok(false) => {}
+            Err(e) => warn!(%realm_id, member = %did, error = ?e, "Failed to record realm member"),
+        }
+    }
 
-        // Auto-save
-        self.save_realm(realm_id).await?;
+    /// Record a neighbor that joined a realm's gossip topic as a member
+    ///
+    /// Gossip identifies neighbors by endpoint, so only peers whose DID we
+    /// have learned (e.g. contacts) are added here. Others are added once
+    /// their first sync message arrives.
+    fn note_realm_neighbor(&self, realm_id: &RealmId, neighbor: &iroh::PublicKey) {
+        match self.storage.load_peer(neighbor).map(|peer| peer.and_then(|p| p.did)) {
+            Ok(Some(did)) => self.note_realm_member(realm_id, &did),
+            Ok(None) => debug!(%realm_id, ?neighbor, "Neighbor DID not known yet"),
+            Err(e) => debug!(%realm_id, ?neighbor, error = ?e, "Failed to look up neighbor"),
+        }
+    }
 
-        // Broadcast changes to peers if syncing
-        if !sync_data.is_empty() {
-            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
-                debug!(%realm_id, error = %e, "Failed to broadcast quest addition (may not be syncing)");
+    /// Update a peer's clock skew estimate from an announce timestamp
+    ///
+    /// Only peers we already track are measured. Emits
+    /// `SyncEvent::ClockSkewWarning` when the estimate crosses
+    /// `CLOCK_SKEW_WARNING_MS`.
+    fn note_clock_sample(&self, realm_id: &RealmId, did: &str, sent_at_ms: i64) {
+        let mut peer = match self.storage.load_peer_by_did(did) {
+            Ok(Some(peer)) => peer,
+            Ok(None) => return,
+            Err(e) => {
+                debug!(%realm_id, peer_did = %did, error = ?e, "Failed to load peer for clock sample");
+                return;
             }
+        };
+        let was_skewed = peer
+            .clock_skew_ms
+            .is_some_and(|skew| skew.abs() > CLOCK_SKEW_WARNING_MS);
+        let skew_ms = peer.record_clock_sample(sent_at_ms, chrono::Utc::now().timestamp_millis());
+        if let Err(e) = self.storage.save_peer(&peer) {
+            warn!(%realm_id, peer_did = %did, error = ?e, "Failed to save clock skew estimate");
         }
 
-        debug!(%realm_id, %task_id, title, "Quest added");
-        Ok(task_id)
+        if skew_ms.abs() > CLOCK_SKEW_WARNING_MS && !was_skewed {
+            warn!(%realm_id, peer_did = %did, skew_ms, "Peer clock is skewed; timestamps may be misordered");
+            self.emit_event(SyncEvent::ClockSkewWarning {
+                realm_id: realm_id.clone(),
+                peer_did: did.to_string(),
+                skew_ms,
+            });
+        }
     }
 
-    /// List all tasks in a realm
+    /// Remember the document heads a member announced
     ///
-    /// # Errors
-    ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub fn list_tasks(&self, realm_id: &RealmId) -> Result<Vec<Task>, SyncError> {
-        let state = self
-            .realms
-            .get(realm_id)
-            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-        state.doc.list_tasks()
+    /// Hashes that don't parse are dropped.
+    fn note_member_heads(&mut self, realm_id: &RealmId, did: &str, heads: &[Vec<u8>]) {
+        let mut heads: Vec<ChangeHash> = heads
+            .iter()
+            .filter_map(|bytes| ChangeHash::try_from(bytes.as_slice()).ok())
+            .collect();
+        heads.sort();
+        self.member_heads
+            .entry(realm_id.clone())
+            .or_default()
+            .insert(did.to_string(), heads);
     }
 
-    /// Get a specific task
-    ///
-    /// # Errors
+    // ═══════════════════════════════════════════════════════════════════════
+    // Realm Roles
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Get the role table currently accepted for a realm
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub fn get_task(
-        &self,
-        realm_id: &RealmId,
-        task_id: &TaskId,
-    ) -> Result<Option<Task>, SyncError> {
-        let state = self
-            .realms
-            .get(realm_id)
-            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+    /// Returns `None` until an owner assigns the first role, in which case
+    /// every member can edit.
+    pub fn realm_roles(&self, realm_id: &RealmId) -> Result<Option<RealmRoles>, SyncError> {
+        self.storage.load_realm_roles(realm_id)
+    }
 
-        state.doc.get_task(task_id)
+    /// Get a member's role in a realm
+    ///
+    /// Members without an entry in the role table (and everyone, before a
+    /// table exists) are editors.
+    pub fn member_role(&self, realm_id: &RealmId, did: &str) -> Result<RealmRole, SyncError> {
+        Ok(self
+            .storage
+            .load_realm_roles(realm_id)?
+            .map_or(RealmRole::Editor, |roles| roles.role_of(did)))
     }
 
-    /// Toggle a task's completion state
+    /// Assign a member's role in a shared realm
     ///
-    /// Auto-opens the realm if not already open.
-    /// Auto-saves the realm after toggling.
+    /// Only owners can change roles. Before any roles exist, the realm's
+    /// creator is its sole owner. The new table is signed with our identity
+    /// and broadcast to peers if the realm is syncing; peers that are offline
+    /// pick it up when they next connect.
     ///
     /// # Errors
     ///
     /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
-    /// Returns `SyncError::TaskNotFound` if the task doesn't exist.
-    pub async fn toggle_task(
+    /// Returns `SyncError::PrivateRealmOperation` for the Private realm.
+    /// Returns `SyncError::Identity` if no identity is initialized.
+    /// Returns `SyncError::InvalidOperation` if we are not an owner, or if the
+    /// change would leave the realm without an owner.
+    pub async fn set_member_role(
         &mut self,
         realm_id: &RealmId,
-        task_id: &TaskId,
+        did: &Did,
+        role: RealmRole,
     ) -> Result<(), SyncError> {
-        // Ensure realm is open
-        if !self.realms.contains_key(realm_id) {
-            self.open_realm(realm_id).await?;
+        let info = self
+            .storage
+            .load_realm(realm_id)?
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+        if is_private_realm_name(&info.name) {
+            return Err(SyncError::PrivateRealmOperation(
+                "Cannot assign roles in Private realm".to_string(),
+            ));
         }
+        self.ensure_writable(realm_id)?;
 
-        let sync_data = {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-            state.doc.toggle_task(task_id)?;
+        let keypair = self.identity.as_ref().ok_or_else(|| {
+            SyncError::Identity("Identity not initialized. Call init_identity() first.".to_string())
+        })?;
+        let our_did = Did::from_public_key(&keypair.public_key()).to_string();
 
-            // Capture incremental changes BEFORE save
-            state.doc.generate_sync_message()
+        let (version, mut roles) = match self.storage.load_realm_roles(realm_id)? {
+            Some(current) if current.is_owner(&our_did) => (current.version + 1, current.roles),
+            None if info.is_creator => (1, BTreeMap::from([(our_did.clone(), RealmRole::Owner)])),
+            _ => {
+                return Err(SyncError::InvalidOperation(format!(
+                    "Only owners can change roles in realm {}",
+                    realm_id
+                )))
+            }
         };
+        roles.insert(did.to_string(), role);
+        if !roles.values().any(|r| *r == RealmRole::Owner) {
+            return Err(SyncError::InvalidOperation(format!(
+                "Realm {} must keep at least one owner",
+                realm_id
+            )));
+        }
 
-        // Auto-save
-        self.save_realm(realm_id).await?;
+        let mut table = RealmRoles {
+            realm_id: realm_id.clone(),
+            version,
+            roles,
+            signer: our_did,
+            signature: Vec::new(),
+        };
+        table.signature = keypair.sign(&table.signing_bytes()).to_bytes();
+        self.storage.save_realm_roles(&table)?;
+        info!(%realm_id, member = %did, %role, version, "Set member role");
 
-        // Broadcast changes to peers if syncing
-        if !sync_data.is_empty() {
-            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
-                debug!(%realm_id, error = %e, "Failed to broadcast task toggle (may not be syncing)");
+        if self.is_realm_syncing(realm_id) {
+            let message = SyncMessage::Roles {
+                realm_id: realm_id.clone(),
+                roles: table,
+            };
+            if let Err(e) = self.broadcast_sync(realm_id, message).await {
+                debug!(%realm_id, error = ?e, "Failed to broadcast role table (non-fatal)");
             }
         }
-
-        debug!(%realm_id, %task_id, "Task toggled");
         Ok(())
     }
 
-    /// Delete a task from a realm
+    /// Whether `did` owns a realm
+    ///
+    /// Before any role table exists, the realm's first owner is the one named
+    /// when it was created or in the invite we joined with (or we ourselves,
+    /// for realms we created before owners were recorded).
+    fn is_realm_owner(&self, info: &RealmInfo, did: &str) -> Result<bool, SyncError> {
+        Ok(match self.storage.load_realm_roles(&info.id)? {
+            Some(roles) => roles.is_owner(did),
+            None => match info.owner.as_deref() {
+                Some(owner) => owner == did,
+                None => info.is_creator && self.did().is_some_and(|ours| ours.as_ref() == did),
+            },
+        })
+    }
+
+    /// Whether changes from `did` may be applied to a realm
     ///
-    /// Auto-opens the realm if not already open.
-    /// Auto-saves the realm after deleting.
+    /// Only a known viewer is refused; if the role table can't be read we
+    /// fall back to the behaviour of a realm without roles.
+    fn member_can_edit(&self, realm_id: &RealmId, did: &str) -> bool {
+        !matches!(self.member_role(realm_id, did), Ok(RealmRole::Viewer))
+    }
+
+    /// The first viewer among the authors of the changes a message carries
+    ///
+    /// Only changes we don't hold yet are checked. A change recorded without
+    /// an author is attributed to `sender`, who vouches for it by relaying it.
+    /// Returns `None` for messages that carry no changes, and before a role
+    /// table exists (every member is an editor then).
+    fn viewer_author(&mut self, realm_id: &RealmId, sender: &str, message: &SyncMessage) -> Option<String> {
+        let (data, full) = match message {
+            SyncMessage::Changes { data, .. } => (data, false),
+            SyncMessage::SyncResponse { document, .. }
+            | SyncMessage::CompactedDocument { document, .. } => (document, true),
+            _ => return None,
+        };
+        let roles = self.storage.load_realm_roles(realm_id).ok().flatten()?;
+        let state = self.realms.get_mut(realm_id)?;
+        let authors = match state.doc.incoming_authors(data, full) {
+            Ok(authors) => authors,
+            Err(e) => {
+                debug!(%realm_id, error = ?e, "Could not read change authors");
+                return None;
+            }
+        };
+        authors
+            .into_iter()
+            .map(|author| author.unwrap_or_else(|| sender.to_string()))
+            .find(|author| roles.role_of(author) == RealmRole::Viewer)
+    }
+
+    /// Accept a role table received from a peer if it supersedes ours
     ///
-    /// # Errors
+    /// A table is accepted when it is newer than the one we hold, its signer
+    /// was an owner in that table, and the signature verifies against the
+    /// signer's pinned profile. The first table for a realm must be signed by
+    /// the realm's first owner, as named in the invite we joined with; a
+    /// realm whose owner we don't know never accepts one.
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
-    pub async fn delete_task(
-        &mut self,
-        realm_id: &RealmId,
-        task_id: &TaskId,
-    ) -> Result<(), SyncError> {
-        // Ensure realm is open
-        if !self.realms.contains_key(realm_id) {
-            self.open_realm(realm_id).await?;
+    /// Returns `true` if the table was stored.
+    fn apply_realm_roles(&mut self, realm_id: &RealmId, roles: RealmRoles) -> Result<bool, SyncError> {
+        if roles.realm_id != *realm_id {
+            return Ok(false);
         }
-
-        let sync_data = {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-            state.doc.delete_task(task_id)?;
-
-            // Capture incremental changes BEFORE save
-            state.doc.generate_sync_message()
+        let Some(info) = self.storage.load_realm(realm_id)? else {
+            return Ok(false);
         };
 
-        // Auto-save
-        self.save_realm(realm_id).await?;
-
-        // Broadcast changes to peers if syncing
-        if !sync_data.is_empty() {
-            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
-                debug!(%realm_id, error = %e, "Failed to broadcast task deletion (may not be syncing)");
+        let current = self.storage.load_realm_roles(realm_id)?;
+        let authorized = match &current {
+            Some(current) if roles.version <= current.version => return Ok(false),
+            Some(current) => current.is_owner(&roles.signer),
+            None => {
+                info.owner.as_deref() == Some(roles.signer.as_str()) && roles.is_owner(&roles.signer)
             }
+        };
+        if !authorized {
+            warn!(%realm_id, signer = %roles.signer, "Rejected role table from non-owner");
+            return Ok(false);
         }
 
-        debug!(%realm_id, %task_id, "Task deleted");
-        Ok(())
+        let verify = Self::make_verify_fn(&self.storage);
+        if !verify(&roles.signer, &roles.signing_bytes(), &roles.signature) {
+            warn!(%realm_id, signer = %roles.signer, "Rejected role table with invalid signature");
+            return Ok(false);
+        }
+
+        self.storage.save_realm_roles(&roles)?;
+        info!(%realm_id, signer = %roles.signer, version = roles.version, "Accepted role table");
+        Ok(true)
+    }
+
+    /// Re-send our role table so newly connected peers learn it
+    fn rebroadcast_realm_roles(&self, realm_id: &RealmId) {
+        let Ok(Some(roles)) = self.storage.load_realm_roles(realm_id) else {
+            return;
+        };
+        let Some(sender) = self.realms.get(realm_id).and_then(|s| s.topic_sender.clone()) else {
+            return;
+        };
+        let message = SyncMessage::Roles {
+            realm_id: realm_id.clone(),
+            roles,
+        };
+        let bytes = match self
+            .seal_realm_message(realm_id, &message)
+            .and_then(|envelope| envelope.to_bytes())
+        {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(%realm_id, error = ?e, "Failed to seal role table");
+                return;
+            }
+        };
+        self.record_capture(CaptureDirection::Outbound, realm_id, &bytes);
+        let realm_id = realm_id.clone();
+        self.in_flight.spawn(async move {
+            if let Err(e) = sender.broadcast(bytes::Bytes::from(bytes)).await {
+                debug!(%realm_id, error = ?e, "Failed to rebroadcast role table");
+            }
+        });
     }
 
     // ═══════════════════════════════════════════════════════════════════════
-    // P2P Sync Operations
+    // Realm Rekeying
     // ═══════════════════════════════════════════════════════════════════════
 
-    /// Start the P2P networking layer.
+    /// Replace a realm's encryption key and send it to the current members
     ///
-    /// This initializes the iroh-gossip networking stack, including:
-    /// - QUIC endpoint for NAT traversal
-    /// - Gossip protocol for topic-based pub/sub
-    /// - Connection to default relay servers
+    /// Sync traffic is sealed with the realm key, so once the key changes,
+    /// anyone still holding only the old key (a departed member, or someone
+    /// who obtained an earlier invite) can no longer read or apply new changes.
+    /// Invites generated before the rekey stop working for the same reason.
     ///
-    /// After calling this, the node can:
-    /// - Generate invites with its own address as a bootstrap peer
-    /// - Subscribe to realm topics for sync
-    /// - Accept incoming connections from peers
+    /// Documents are kept unencrypted on disk; the stored key is the only
+    /// local state bound to the realm key, and it is replaced in place.
     ///
-    /// This is a no-op if networking is already started.
+    /// The new key is delivered as an E2E encrypted packet over each member's
+    /// 1:1 contact topic. Members from the realm roster who are not contacts
+    /// cannot be reached this way and are reported in
+    /// [`RealmRekeyOutcome::undelivered`]. Members listed in `exclude` are
+    /// not sent the key at all and are taken off the roster, which is how a
+    /// departing member is removed.
+    ///
+    /// Only owners can rekey, and members only accept keys sent by an owner.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::Network` if the gossip layer fails to initialize.
-    pub async fn start_networking(&mut self) -> Result<(), SyncError> {
-        self.networking_requested = true;
-        self.ensure_gossip().await?;
-        info!("P2P networking started");
-        Ok(())
-    }
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::PrivateRealmOperation` for the Private realm.
+    /// Returns `SyncError::InvalidOperation` if the realm is read-only or we
+    /// are not an owner.
+    pub async fn rekey_realm(
+        &mut self,
+        realm_id: &RealmId,
+        exclude: &[Did],
+    ) -> Result<RealmRekeyOutcome, SyncError> {
+        let mut info = self
+            .storage
+            .load_realm(realm_id)?
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-    /// Check if the P2P networking layer is active.
-    pub fn is_networking_active(&self) -> bool {
-        self.gossip.is_some()
-    }
+        if is_private_realm_name(&info.name) {
+            return Err(SyncError::PrivateRealmOperation(
+                "Cannot rekey Private realm".to_string(),
+            ));
+        }
+        self.ensure_writable(realm_id)?;
 
-    /// Get effective bootstrap peers by combining static peers from storage
-    /// with online peers from the peer registry.
-    ///
-    /// This enables dynamic peer discovery - peers discovered in previous sessions
-    /// automatically become bootstrap peers for reconnection.
-    fn get_effective_bootstrap_peers(
-        &self,
-        realm_id: &RealmId,
-    ) -> Result<Vec<iroh::PublicKey>, SyncError> {
-        let mut peer_ids = std::collections::HashSet::new();
+        let our_did = self.did().map(|d| d.to_string());
+        let is_owner = match our_did.as_deref() {
+            Some(did) => self.is_realm_owner(&info, did)?,
+            None => false,
+        };
+        if !is_owner {
+            return Err(SyncError::InvalidOperation(format!(
+                "Only owners can rekey realm {}",
+                realm_id
+            )));
+        }
 
-        // 1. Add static peers from invite/storage
-        if let Some(realm_info) = self.storage.load_realm(realm_id)? {
-            for peer_bytes in &realm_info.bootstrap_peers {
-                match peer_bytes.to_endpoint_addr() {
-                    Ok(endpoint_addr) => {
-                        peer_ids.insert(endpoint_addr.id);
-                    }
-                    Err(e) => {
-                        warn!(%realm_id, error = ?e, "Failed to parse saved peer address");
-                    }
-                }
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
+
+        let mut realm_key = [0u8; 32];
+        rand::rng().fill_bytes(&mut realm_key);
+        let epoch = info.key_epoch + 1;
+        self.install_realm_key(&mut info, realm_key, epoch)?;
+        info!(%realm_id, epoch = info.key_epoch, "Rekeyed realm");
+
+        let now = chrono::Utc::now().timestamp();
+        for did in exclude {
+            if self.storage.mark_realm_member_left(realm_id, did.as_ref(), now)? {
+                info!(%realm_id, member = %did, "Excluded member from new realm key");
             }
         }
 
-        // 2. Add online peers from registry that share this realm
-        match self.peer_registry.list_by_status(PeerStatus::Online) {
-            Ok(online_peers) => {
-                for peer_info in online_peers {
-                    if peer_info.shared_realms.contains(realm_id) {
-                        match iroh::PublicKey::from_bytes(&peer_info.endpoint_id) {
-                            Ok(peer_id) => {
-                                peer_ids.insert(peer_id);
-                            }
-                            Err(e) => {
-                                warn!(error = ?e, "Failed to parse peer endpoint ID from registry");
-                            }
-                        }
-                    }
+        let mut outcome = RealmRekeyOutcome {
+            epoch: info.key_epoch,
+            delivered: Vec::new(),
+            undelivered: Vec::new(),
+        };
+
+        let others: Vec<_> = self
+            .storage
+            .list_realm_members(realm_id)?
+            .into_iter()
+            .filter(|m| our_did.as_deref() != Some(m.did.as_str()))
+            .collect();
+        if !others.is_empty() {
+            self.init_profile_keys()?;
+        }
+
+        for member in others {
+            let sent = match member.did.parse::<Did>() {
+                Ok(did) => {
+                    let payload = PacketPayload::RealmRekey {
+                        realm_id: realm_id.clone(),
+                        realm_key,
+                        epoch: info.key_epoch,
+                    };
+                    self.create_and_broadcast_packet(payload, PacketAddress::Individual(did))
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match sent {
+                Ok(_) => outcome.delivered.push(member.did),
+                Err(e) => {
+                    warn!(%realm_id, member = %member.did, error = %e, "Could not send new realm key");
+                    outcome.undelivered.push(member.did);
                 }
-            }
-            Err(e) => {
-                warn!(error = ?e, "Failed to query online peers from registry");
             }
         }
 
-        let peers: Vec<iroh::PublicKey> = peer_ids.into_iter().collect();
-        if !peers.is_empty() {
-            debug!(%realm_id, peer_count = peers.len(), "Effective bootstrap peers (static + registry)");
-        }
-        Ok(peers)
+        Ok(outcome)
     }
 
-    /// Ensure gossip networking is initialized
-    async fn ensure_gossip(&mut self) -> Result<Arc<GossipSync>, SyncError> {
-        if let Some(ref gossip) = self.gossip {
-            return Ok(gossip.clone());
-        }
+    /// Apply realm keys that other members sent after rekeying
+    ///
+    /// Scans packets received from other profiles for `RealmRekey` payloads.
+    /// A key is only accepted when its sender owns the realm and its epoch is
+    /// newer than the one we hold. When two owners rekey concurrently to the
+    /// same epoch, every member keeps the key whose hash sorts first, so the
+    /// realm settles on one key. Packets can only be read once profile keys
+    /// are initialized.
+    ///
+    /// Called from [`process_pending_sync`](Self::process_pending_sync), so
+    /// incoming sync traffic sealed with a new key can be opened right away.
+    ///
+    /// # Returns
+    ///
+    /// The number of realms whose key was replaced.
+    pub fn apply_realm_rekeys(&mut self) -> Result<usize, SyncError> {
+        let Some(mirror) = self.mirror_store.as_ref() else {
+            return Ok(0);
+        };
+        let our_did = self.did();
 
-        info!("Initializing gossip networking");
+        let mut scanned = Vec::new();
+        for sender in mirror.list_mirrored_dids()? {
+            if our_did.as_ref() == Some(&sender) {
+                continue;
+            }
+            let envelopes = match self.rekey_scan_heads.get(&sender) {
+                Some(&head) => mirror.get_since(&sender, head)?,
+                None => mirror.get_all(&sender)?,
+            };
+            let Some(head) = envelopes.last().map(|e| e.sequence) else {
+                continue;
+            };
+            let rekeys: Vec<_> = envelopes
+                .iter()
+                .filter_map(|envelope| match self.decrypt_packet(envelope) {
+                    Some(PacketPayload::RealmRekey {
+                        realm_id,
+                        realm_key,
+                        epoch,
+                    }) => Some((realm_id, realm_key, epoch)),
+                    _ => None,
+                })
+                .collect();
+            scanned.push((sender, head, rekeys));
+        }
 
-        // Load or generate persistent endpoint secret key
-        let secret_key = match self.storage.load_endpoint_secret_key()? {
-            Some(key_bytes) => {
-                info!("Loaded persistent endpoint secret key from storage");
-                iroh::SecretKey::from(key_bytes)
+        let mut applied = 0;
+        for (sender, head, rekeys) in scanned {
+            let mut deferred = false;
+            for (realm_id, realm_key, epoch) in rekeys {
+                match self.apply_realm_rekey(sender.as_ref(), &realm_id, realm_key, epoch)? {
+                    Some(true) => applied += 1,
+                    Some(false) => {}
+                    None => deferred = true,
+                }
             }
-            None => {
-                info!("No endpoint secret key found, generating new one");
-                let secret_key = iroh::SecretKey::generate(&mut rand::rng());
-                let key_bytes: [u8; 32] = secret_key.to_bytes();
-                self.storage.save_endpoint_secret_key(&key_bytes)?;
-                info!("Saved new endpoint secret key to storage");
-                secret_key
+            // Rescan later if the sender's ownership hasn't reached us yet
+            if !deferred {
+                self.rekey_scan_heads.insert(sender, head);
             }
+        }
+        Ok(applied)
+    }
+
+    /// Install a key received from `sender` if it supersedes ours
+    ///
+    /// A key supersedes ours if its epoch is newer, or if the epoch is the
+    /// same and the key's hash sorts before that of the key we hold.
+    ///
+    /// Returns `Some(true)` if the key was installed, `Some(false)` if it is
+    /// stale or for a realm we don't follow, and `None` if the sender is not
+    /// (yet) known to own the realm.
+    fn apply_realm_rekey(
+        &mut self,
+        sender: &str,
+        realm_id: &RealmId,
+        realm_key: [u8; 32],
+        epoch: u64,
+    ) -> Result<Option<bool>, SyncError> {
+        let Some(mut info) = self.storage.load_realm(realm_id)? else {
+            return Ok(Some(false));
         };
+        if info.read_only || epoch < info.key_epoch {
+            return Ok(Some(false));
+        }
+        if epoch == info.key_epoch {
+            let current = self.storage.load_realm_key(realm_id)?;
+            let wins = current.is_some_and(|current| {
+                blake3::hash(&realm_key).as_bytes() < blake3::hash(&current).as_bytes()
+            });
+            if !wins {
+                return Ok(Some(false));
+            }
+        }
+        if !self.is_realm_owner(&info, sender)? {
+            debug!(%realm_id, %sender, "Holding realm key from sender not known to own the realm");
+            return Ok(None);
+        }
 
-        // Pass storage, event_tx, and local_did so Router can register contact protocol handler
-        // The local_did is required for the simplified protocol's local key derivation
-        let contact_deps = self.identity.as_ref().map(|keypair| {
-            let did = Did::from_public_key(&keypair.public_key());
-            (Arc::new(self.storage.clone()), self.contact_event_tx.clone(), did.to_string())
-        });
+        self.install_realm_key(&mut info, realm_key, epoch)?;
+        info!(%realm_id, %sender, epoch, "Applied new realm key");
+        Ok(Some(true))
+    }
 
-        // Pass profile handler deps if identity is available
-        let profile_deps = self.identity.as_ref().map(|keypair| {
-            let did = Did::from_public_key(&keypair.public_key());
-            (Arc::new(self.storage.clone()), Arc::new(keypair.clone()), did)
-        });
+    /// Persist a new key for a realm and switch the open realm over to it
+    fn install_realm_key(
+        &mut self,
+        info: &mut RealmInfo,
+        realm_key: [u8; 32],
+        epoch: u64,
+    ) -> Result<(), SyncError> {
+        self.storage.save_realm_key(&info.id, &realm_key)?;
+        info.key_epoch = epoch;
+        self.storage.save_realm(info)?;
+        if let Some(state) = self.realms.get_mut(&info.id) {
+            state.realm_key = realm_key;
+        }
+        Ok(())
+    }
 
-        // Pass blob manager for P2P image transfer capability
-        // GossipSync::with_secret_key returns (GossipSync, Option<ActiveContactTopics>)
-        // We store the active_topics for later use by ContactManager
-        let (gossip_sync, active_topics) = GossipSync::with_secret_key(
-            Some(secret_key),
-            contact_deps,
-            profile_deps,
-            Some(&self.blob_manager),
-        ).await?;
-        let gossip = Arc::new(gossip_sync);
-        self.gossip = Some(gossip.clone());
-        self.active_contact_topics = active_topics;
-        Ok(gossip)
+    // ═══════════════════════════════════════════════════════════════════════
+    // Realm Document State
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Get the current Automerge heads of a realm's document
+    ///
+    /// Two replicas that have exchanged all their changes report the same
+    /// heads, so comparing heads is a cheap way to check whether peers are in
+    /// sync. Heads are returned sorted so they can be compared directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn realm_heads(&mut self, realm_id: &RealmId) -> Result<Vec<ChangeHash>, SyncError> {
+        let mut heads = self.realm_doc_mut(realm_id).await?.heads();
+        heads.sort();
+        Ok(heads)
     }
 
-    /// Get a reference to the gossip instance if initialized
+    /// Get the number of changes in a realm's document history
     ///
-    /// Returns an error if gossip has not been initialized yet.
-    /// Use `ensure_gossip()` if you want to initialize it automatically.
-    fn ensure_gossip_ref(&self) -> Result<&GossipSync, SyncError> {
-        self.gossip
-            .as_ref()
-            .map(|g| g.as_ref())
-            .ok_or_else(|| SyncError::NotReady("Gossip networking not initialized".to_string()))
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn realm_change_count(&mut self, realm_id: &RealmId) -> Result<usize, SyncError> {
+        Ok(self.realm_doc_mut(realm_id).await?.change_count())
     }
 
-    /// Ensure contact manager is initialized
+    /// Get the hashes of every change a realm's document contains
     ///
-    /// Initializes the contact manager if not already initialized.
-    /// Requires both gossip and identity to be initialized first.
-    async fn ensure_contact_manager(&mut self) -> Result<Arc<ContactManager>, SyncError> {
-        if let Some(ref manager) = self.contact_manager {
-            return Ok(manager.clone());
+    /// A change made on one node has been delivered to another exactly when
+    /// its hash shows up here. Hashes are returned sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn realm_change_hashes(
+        &mut self,
+        realm_id: &RealmId,
+    ) -> Result<Vec<ChangeHash>, SyncError> {
+        let mut hashes = self.realm_doc_mut(realm_id).await?.change_hashes();
+        hashes.sort();
+        Ok(hashes)
+    }
+
+    /// Drop a realm's change history once every member has caught up
+    ///
+    /// Compaction only happens when each member we know of last announced
+    /// exactly our heads, so the history being dropped is common to everyone
+    /// we know of. Members are those on the roster and those named in the
+    /// role table. The realm is then saved as a single change holding the
+    /// current tasks and metadata and broadcast as a full document.
+    /// Members rebase onto it when it arrives, keeping any edits they made in
+    /// the meantime (see [`RealmDoc::absorb`]), and a peer joining later syncs
+    /// the compacted document like any other. Compacted documents travel as
+    /// `SyncMessage::CompactedDocument`, which nodes older than compaction
+    /// can't decode, so they drop it rather than merge it as an unrelated
+    /// copy of the realm.
+    ///
+    /// A member we never heard from may still hold older history. The
+    /// dropped history is archived locally, so when such a replica sends its
+    /// copy, its edits are rebased onto the compacted document and it adopts
+    /// that (see [`Self::apply_sync_response`]). The archive also keeps task
+    /// activity from before the compaction in [`Self::task_history`] on every
+    /// member that held it; members who join later only see activity from
+    /// the compaction onward.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::InvalidOperation` if the realm is read-only for us.
+    pub async fn compact_realm_history(
+        &mut self,
+        realm_id: &RealmId,
+    ) -> Result<RealmCompaction, SyncError> {
+        self.ensure_writable(realm_id)?;
+        let our_heads = self.realm_heads(realm_id).await?;
+
+        let our_did = self.did().map(|did| did.to_string());
+        let mut members: BTreeSet<String> = self
+            .storage
+            .list_realm_members(realm_id)?
+            .into_iter()
+            .map(|member| member.did)
+            .collect();
+        if let Some(roles) = self.storage.load_realm_roles(realm_id)? {
+            members.extend(roles.roles.into_keys());
+        }
+        let announced = self.member_heads.get(realm_id);
+        let unconverged: Vec<String> = members
+            .into_iter()
+            .filter(|did| our_did.as_deref() != Some(did.as_str()))
+            .filter(|did| announced.and_then(|heads| heads.get(did)) != Some(&our_heads))
+            .collect();
+        if !unconverged.is_empty() {
+            debug!(%realm_id, unconverged = unconverged.len(), "Realm history not compacted");
+            return Ok(RealmCompaction::Unconverged {
+                members: unconverged,
+            });
         }
 
-        info!("Initializing contact manager");
+        let (changes_before, bytes_before, bytes_after, sync_data) = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+            let changes_before = state.doc.change_count();
+            let before = state.doc.save();
+            let mut compacted = state.doc.compacted()?;
+            let bytes_after = compacted.save().len();
+            self.storage.archive_realm_history(realm_id, &before)?;
+            state.doc = compacted;
+            state.dirty = true;
+            (changes_before, before.len(), bytes_after, state.doc.generate_sync_message())
+        };
 
-        // Ensure gossip is initialized
-        let gossip = self.ensure_gossip().await?;
+        self.save_realm(realm_id).await?;
 
-        // Ensure identity is initialized
-        self.init_identity()?;
+        // Peers receive the whole compacted document, not just these bytes
+        if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+            debug!(%realm_id, error = %e, "Failed to broadcast compacted realm (may not be syncing)");
+        }
 
-        // Ensure our own profile is signed and pinned (required for announcements to work)
-        // This must be done BEFORE we borrow keypair to avoid borrow checker issues
-        // This is idempotent - if already signed, it just updates the pin
-        if let Err(e) = self.sign_and_pin_own_profile() {
-            warn!("Failed to sign and pin own profile: {}", e);
-        } else {
-            debug!("Own profile signed and pinned for contact exchange announcements");
-        }
+        info!(%realm_id, changes_before, bytes_before, bytes_after, "Compacted realm history");
+        Ok(RealmCompaction::Compacted {
+            changes_before,
+            bytes_before,
+            bytes_after,
+        })
+    }
 
-        let keypair = self
-            .identity
-            .as_ref()
-            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?;
-        let did = Did::from_public_key(&keypair.public_key());
+    /// Open a realm if needed and borrow its document
+    async fn realm_doc_mut(&mut self, realm_id: &RealmId) -> Result<&mut RealmDoc, SyncError> {
+        if !self.realms.contains_key(realm_id) {
+            if self.storage.load_realm(realm_id)?.is_none() {
+                return Err(SyncError::RealmNotFound(realm_id.to_string()));
+            }
+            self.open_realm(realm_id).await?;
+        }
+        self.realms
+            .get_mut(realm_id)
+            .map(|state| &mut state.doc)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))
+    }
 
-        // Clone gossip for the profile announcer before moving into ContactManager
-        let gossip_for_announcer = gossip.clone();
+    // ═══════════════════════════════════════════════════════════════════════
+    // Realm Snapshots
+    // ═══════════════════════════════════════════════════════════════════════
 
-        // Get the shared active_topics - this was created when gossip was initialized
-        // and is shared with ContactProtocolHandler for coordinating contact topic senders
-        let active_topics = self.active_contact_topics.clone().ok_or_else(|| {
-            SyncError::NotReady(
-                "active_contact_topics not initialized - gossip may have been created without contact deps".to_string()
-            )
-        })?;
+    /// Save a frozen copy of a realm's current state under a label
+    ///
+    /// Snapshots are stored locally and never synced. Taking one before a
+    /// risky bulk change gives a point to compare against or recover from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn snapshot_realm(
+        &mut self,
+        realm_id: &RealmId,
+        label: &str,
+    ) -> Result<SnapshotId, SyncError> {
+        if self.storage.load_realm(realm_id)?.is_none() {
+            return Err(SyncError::RealmNotFound(realm_id.to_string()));
+        }
+        self.open_realm(realm_id).await?;
 
-        // Create contact manager (shares event_tx and active_topics with ContactProtocolHandler)
-        let manager = Arc::new(ContactManager::new(
-            gossip,
-            Arc::new(keypair.clone()),
-            did,
-            Arc::new(self.storage.clone()),
-            self.contact_event_tx.clone(),
-            active_topics,
-            Some(self.packet_event_buffer.clone()),
-        ));
+        let doc_bytes = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?
+            .doc
+            .save();
 
-        // Start the auto-accept task for our own invites
-        manager.clone().start_auto_accept_task();
+        let snapshot = RealmSnapshot {
+            id: SnapshotId::new(),
+            realm_id: realm_id.clone(),
+            label: label.to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        self.storage.save_realm_snapshot(&snapshot, &doc_bytes)?;
 
-        // Start the contact subscription task (ensures we subscribe to contact topics for RECEIVING)
-        // The handler adds sender to active_topics for sending, but this task handles receiving.
-        // This fixes the bug where handler's listener consumed Packet messages before we could process them.
-        manager.clone().start_contact_subscription_task();
+        info!(%realm_id, snapshot = %snapshot.id, label, "Realm snapshot taken");
+        Ok(snapshot.id)
+    }
 
-        // Start the contact accepted profile announcer
-        // This broadcasts our profile when a contact is accepted, triggering auto-pinning
-        Self::start_contact_accepted_profile_announcer(
-            self.contact_event_tx.subscribe(),
-            gossip_for_announcer,
-            self.storage.clone(),
-        );
+    /// List the snapshots taken of a realm, oldest first
+    pub fn list_realm_snapshots(
+        &self,
+        realm_id: &RealmId,
+    ) -> Result<Vec<RealmSnapshot>, SyncError> {
+        self.storage.list_realm_snapshots(realm_id)
+    }
 
-        // Start profile sync listener to process incoming announcements (enables auto-pinning)
-        // This uses empty bootstrap peers since we discover peers through contact exchange
-        if let Err(e) = self.start_profile_sync(vec![]).await {
-            warn!("Failed to start profile sync listener: {}", e);
-        }
+    /// Open a snapshot as a read-only view of its tasks
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::SnapshotNotFound` if the realm has no such snapshot.
+    pub fn open_snapshot(
+        &self,
+        realm_id: &RealmId,
+        snapshot_id: &SnapshotId,
+    ) -> Result<RealmSnapshotView, SyncError> {
+        let (snapshot, doc_bytes) = self
+            .storage
+            .load_realm_snapshot(realm_id, snapshot_id)?
+            .ok_or_else(|| SyncError::SnapshotNotFound(snapshot_id.to_string()))?;
+        RealmSnapshotView::load(snapshot, &doc_bytes)
+    }
 
-        self.contact_manager = Some(manager.clone());
-        Ok(manager)
+    /// Compare two snapshots of a realm
+    ///
+    /// Reports tasks added, removed and modified going from snapshot `a` to
+    /// snapshot `b`, with field-level changes for modified tasks.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::SnapshotNotFound` if either snapshot is missing.
+    pub fn diff_snapshots(
+        &self,
+        realm_id: &RealmId,
+        a: &SnapshotId,
+        b: &SnapshotId,
+    ) -> Result<RealmDiff, SyncError> {
+        let load = |id: &SnapshotId| -> Result<RealmDoc, SyncError> {
+            let (_, bytes) = self
+                .storage
+                .load_realm_snapshot(realm_id, id)?
+                .ok_or_else(|| SyncError::SnapshotNotFound(id.to_string()))?;
+            RealmDoc::load(&bytes)
+        };
+        let mut doc_a = load(a)?;
+        let mut doc_b = load(b)?;
+
+        // Merge so both sets of heads exist in one history, whichever order
+        // the snapshots were taken in
+        let heads_a = doc_a.heads();
+        let heads_b = doc_b.heads();
+        doc_b.merge(&mut doc_a)?;
+        doc_b.diff(&heads_a, &heads_b)
     }
 
-    /// Start a background task that announces our profile when a contact is accepted.
+    // ═══════════════════════════════════════════════════════════════════════
+    // Document Import
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Merge an external Automerge document into a realm
     ///
-    /// When the ContactAccepted event fires (either direction - we accept or they accept us),
-    /// this broadcasts our signed profile to the global profile topic. The peer's profile
-    /// sync listener will receive it and auto-pin because we're now contacts.
+    /// The document must follow the realm task schema. It is imported into a
+    /// fork of the realm first and the result is validated; the realm itself
+    /// is only touched once that succeeds, so a bad document leaves it as it was.
     ///
-    /// This enables mutual profile pinning after contact exchange completes.
-    fn start_contact_accepted_profile_announcer(
-        mut event_rx: broadcast::Receiver<ContactEvent>,
-        gossip: Arc<GossipSync>,
-        storage: Storage,
-    ) {
-        tokio::spawn(async move {
-            loop {
-                match event_rx.recv().await {
-                    Ok(ContactEvent::ContactAccepted { contact }) => {
-                        info!(peer_did = %contact.peer_did, "Contact accepted, announcing profile for auto-pinning");
+    /// # Returns
+    ///
+    /// The number of tasks that were new to the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::IncompatibleDocument` if the bytes are not an
+    /// Automerge document or don't match the task schema.
+    pub async fn import_automerge(
+        &mut self,
+        realm_id: &RealmId,
+        bytes: &[u8],
+    ) -> Result<usize, SyncError> {
+        self.ensure_writable(realm_id)?;
 
-                        // Load our signed profile from storage
-                        match storage.get_own_pinned_profile() {
-                            Ok(Some(pin)) => {
-                                let signed_profile = pin.signed_profile;
+        let mut foreign = RealmDoc::load(bytes)
+            .map_err(|e| SyncError::IncompatibleDocument(e.to_string()))?;
+        foreign.validate_schema()?;
 
-                                // Subscribe to the global profile topic and broadcast
-                                let topic_id = crate::sync::global_profile_topic();
-                                match gossip.subscribe_split(topic_id, vec![]).await {
-                                    Ok((sender, _receiver)) => {
-                                        // Create announcement (no avatar ticket needed for basic pinning)
-                                        let announcement = crate::sync::ProfileGossipMessage::announce(
-                                            signed_profile,
-                                            None,
-                                        );
-                                        match announcement.to_bytes() {
-                                            Ok(bytes) => {
-                                                if let Err(e) = sender.broadcast(bytes).await {
-                                                    warn!(
-                                                        peer_did = %contact.peer_did,
-                                                        error = %e,
-                                                        "Failed to broadcast profile after contact accepted"
-                                                    );
-                                                } else {
-                                                    info!(
-                                                        peer_did = %contact.peer_did,
-                                                        "Profile announced after contact accepted - peer should auto-pin"
-                                                    );
-                                                }
-                                            }
-                                            Err(e) => {
-                                                warn!(
-                                                    error = %e,
-                                                    "Failed to serialize profile announcement"
-                                                );
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        warn!(
-                                            error = %e,
-                                            "Failed to subscribe to profile topic for announcement"
-                                        );
-                                    }
-                                }
-                            }
-                            Ok(None) => {
-                                warn!("No own profile found, cannot announce after contact accepted");
-                            }
-                            Err(e) => {
-                                warn!(error = %e, "Failed to load own profile for announcement");
-                            }
-                        }
-                    }
-                    Ok(_) => {} // Ignore other contact events
-                    Err(broadcast::error::RecvError::Lagged(n)) => {
-                        debug!("Contact event receiver lagged by {} messages", n);
-                        continue;
-                    }
-                    Err(broadcast::error::RecvError::Closed) => {
-                        debug!("Contact event channel closed, stopping profile announcer");
-                        break;
-                    }
-                }
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
+
+        let author = self.did().map(|did| did.to_string());
+        let (imported, sync_data) = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            let mut candidate = state.doc.fork();
+            let imported = candidate.import(&mut foreign)?;
+            candidate.validate_schema()?;
+            candidate.commit(author.as_deref());
+
+            state.doc.merge(&mut candidate)?;
+            state.dirty = true;
+            (imported, state.doc.generate_sync_message())
+        };
+
+        self.save_realm(realm_id).await?;
+
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast import (may not be syncing)");
             }
-        });
+        }
 
-        info!("Contact accepted profile announcer started");
+        info!(%realm_id, imported, "Imported external document");
+        Ok(imported)
     }
 
-    /// Start the peer reconnection background task
-    ///
-    /// This spawns a background task that runs every 5 minutes and attempts
-    /// to reconnect to all inactive peers (offline or unknown status).
+    // ═══════════════════════════════════════════════════════════════════════
+    // Task Operations (with auto-save)
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Replace the source of IDs for tasks created from now on
     ///
-    /// The task will continue running until the engine is dropped.
-    pub fn start_peer_reconnection_task(self: Arc<Self>) {
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(300)); // 5 minutes
-            loop {
-                interval.tick().await;
-                if let Err(e) = self.attempt_reconnect_inactive_peers().await {
-                    tracing::warn!("Peer reconnection error: {}", e);
-                }
-            }
-        });
-        info!("Peer reconnection task started");
+    /// Production keeps the default random ULIDs. Tests can pass a
+    /// [`SequentialTaskIds`](crate::types::SequentialTaskIds) to get the
+    /// same, ordered IDs on every run.
+    pub fn set_task_id_generator(&mut self, generator: impl TaskIdGenerator + 'static) {
+        self.task_ids = Box::new(generator);
     }
 
-    /// Attempt to reconnect to all inactive peers
+    /// Add a task to a realm
     ///
-    /// This iterates through all peers with status Offline or Unknown and
-    /// attempts to establish a connection using exponential backoff.
+    /// Auto-saves the realm after adding the task.
     ///
-    /// Peers are only retried if enough time has passed according to their
-    /// backoff delay (5min * 2^failures, capped at 1 hour). Connection attempts
-    /// and results are tracked to calculate success rates and adjust backoff.
+    /// # Errors
     ///
-    /// This is called automatically by the background reconnection task.
-    pub async fn attempt_reconnect_inactive_peers(&self) -> Result<(), SyncError> {
-        let inactive = self.peer_registry.list_inactive()?;
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub async fn add_task(&mut self, realm_id: &RealmId, title: &str) -> Result<TaskId, SyncError> {
+        self.ensure_writable(realm_id)?;
 
-        if inactive.is_empty() {
-            debug!("No inactive peers to reconnect");
-            return Ok(());
+        // First, ensure realm is open (load from storage if needed)
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
         }
 
-        // Skip if gossip not initialized
-        let Some(ref gossip) = self.gossip else {
-            debug!("Gossip not initialized, skipping peer reconnection");
-            return Ok(());
-        };
+        let author = self.did().map(|did| did.to_string());
+        let mut task = Task::new(title);
+        task.id = self.task_ids.next_id();
+        task.created_by = author.clone();
+        let (task_id, sync_data) = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-        let mut attempted = 0;
-        let mut succeeded = 0;
-        let mut skipped = 0;
+            state.doc.insert_task(&task)?;
+            let task_id = task.id;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
 
-        info!(
-            "Checking {} inactive peers for reconnection (with exponential backoff)",
-            inactive.len()
-        );
+            // Capture incremental changes BEFORE save (save resets the checkpoint)
+            let sync_data = state.doc.generate_sync_message();
 
-        for mut peer_info in inactive {
-            let peer_id = peer_info.public_key();
+            (task_id, sync_data)
+        };
 
-            // Check if enough time has passed for retry (exponential backoff)
-            if !peer_info.should_retry_now() {
-                skipped += 1;
-                debug!(
-                    ?peer_id,
-                    backoff_secs = peer_info.backoff_delay(),
-                    "Skipping peer - backoff not elapsed"
-                );
-                continue;
-            }
+        // Auto-save
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: task_id.to_string_repr(),
+        });
 
-            attempted += 1;
-            peer_info.record_attempt();
-            debug!(
-                ?peer_id,
-                attempt_number = peer_info.connection_attempts,
-                "Attempting to reconnect"
-            );
-
-            // Try to connect using the endpoint
-            match gossip
-                .endpoint()
-                .connect(peer_id, iroh_gossip::net::GOSSIP_ALPN)
-                .await
-            {
-                Ok(_conn) => {
-                    peer_info.record_success();
-                    succeeded += 1;
-                    info!(
-                        ?peer_id,
-                        success_rate = format!("{:.1}%", peer_info.success_rate() * 100.0),
-                        "Successfully reconnected to peer"
-                    );
-                }
-                Err(e) => {
-                    peer_info.record_failure();
-                    debug!(
-                        ?peer_id,
-                        error = ?e,
-                        next_retry_in = format!("{:?}", peer_info.backoff_delay()),
-                        "Failed to reconnect, will retry after backoff"
-                    );
-                }
+        // Broadcast changes to peers if syncing
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task addition (may not be syncing)");
             }
+        }
 
-            // Save updated peer info (with metrics and status)
-            self.peer_registry.add_or_update(&peer_info)?;
+        debug!(%realm_id, %task_id, title, "Task added");
+        Ok(task_id)
+    }
+
+    /// Add a rich "quest" (task with metadata) to a realm
+    ///
+    /// Auto-saves the realm after adding the quest.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub async fn add_quest(
+        &mut self,
+        realm_id: &RealmId,
+        title: &str,
+        subtitle: Option<String>,
+        description: &str,
+        category: Option<String>,
+        image_blob_id: Option<String>,
+    ) -> Result<TaskId, SyncError> {
+        self.ensure_writable(realm_id)?;
+
+        // First, ensure realm is open (load from storage if needed)
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
         }
 
-        if attempted > 0 || skipped > 0 {
-            info!(
-                "Reconnection summary: attempted={}, succeeded={}, skipped={} (backoff)",
-                attempted, succeeded, skipped
-            );
+        let author = self.did().map(|did| did.to_string());
+        let mut task = Task::new_quest(title, subtitle, description);
+        task.id = self.task_ids.next_id();
+        task.created_by = author.clone();
+        task.category = category;
+        task.image_blob_id = image_blob_id;
+        let (task_id, sync_data) = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            state.doc.insert_task(&task)?;
+            let task_id = task.id;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+
+            // Capture incremental changes BEFORE save (save resets the checkpoint)
+            let sync_data = state.doc.generate_sync_message();
+
+            (task_id, sync_data)
+        };
+
+        // Auto-save
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: task_id.to_string_repr(),
+        });
+
+        // Broadcast changes to peers if syncing
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast quest addition (may not be syncing)");
+            }
         }
 
-        Ok(())
+        debug!(%realm_id, %task_id, title, "Quest added");
+        Ok(task_id)
     }
 
-    /// Perform immediate startup sync with all known peers
+    /// List all tasks in a realm
     ///
-    /// This should be called after engine initialization to establish connections
-    /// with known peers as quickly as possible while avoiding the "simultaneous
-    /// wake-up problem" where two peers starting at the same time both fail to
-    /// connect.
+    /// # Errors
     ///
-    /// ## Behavior
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub fn list_tasks(&self, realm_id: &RealmId) -> Result<Vec<Task>, SyncError> {
+        let state = self
+            .realms
+            .get(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        let mut tasks = state.doc.list_tasks()?;
+        self.fill_assignee_names(&mut tasks);
+        Ok(tasks)
+    }
+
+    /// Completion statistics for an open realm, as of now
     ///
-    /// 1. **Initialize gossip** - Ensures we're listening for incoming connections
-    /// 2. **Apply jitter** - Random delay (0-30 seconds) to avoid thundering herd
-    /// 3. **Announce presence** - Broadcasts our profile to the global topic
-    /// 4. **Connect to peers** - Attempts to connect to all known peers, respecting
-    ///    Fibonacci backoff for peers that have failed recently
+    /// # Errors
     ///
-    /// ## The Simultaneous Wake-up Problem
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub fn realm_stats(&self, realm_id: &RealmId) -> Result<RealmStats, SyncError> {
+        let tasks = self.list_tasks(realm_id)?;
+        Ok(RealmStats::from_tasks(&tasks, chrono::Utc::now().timestamp()))
+    }
+
+    /// Our contribution to each realm, and across all of them
+    ///
+    /// Counts tasks we created and completed, as recorded on each task, and
+    /// compares them with all creations and completions in the realm. Realms
+    /// don't need to be open.
+    pub fn stewardship_summary(&self) -> Result<StewardshipSummary, SyncError> {
+        let me = self.did();
+        let mut summary = StewardshipSummary::default();
+        for info in self.storage.list_realms()? {
+            let tasks = match self.realms.get(&info.id) {
+                Some(state) => state.doc.list_tasks()?,
+                None => match self.storage.load_document(&info.id)? {
+                    Some(bytes) => RealmDoc::load(&bytes)?.list_tasks()?,
+                    None => Vec::new(),
+                },
+            };
+            let contribution = Contribution::from_tasks(&tasks, me.as_ref());
+            summary.total.add(&contribution);
+            summary.realms.push(RealmContribution {
+                realm_id: info.id,
+                name: info.name,
+                contribution,
+            });
+        }
+        Ok(summary)
+    }
+
+    /// Tasks in an open realm assigned to `did`
     ///
-    /// When peers A and B both start at the same time:
-    /// - A tries to connect to B → B not ready → fails → backs off
-    /// - B tries to connect to A → A not ready → fails → backs off
+    /// # Errors
     ///
-    /// The jitter + listen-first strategy solves this:
-    /// - Both start listening immediately
-    /// - Random jitter means one will attempt outbound first
-    /// - The other is already listening and accepts the connection
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub fn list_tasks_assigned_to(
+        &self,
+        realm_id: &RealmId,
+        did: &Did,
+    ) -> Result<Vec<Task>, SyncError> {
+        let mut tasks = self.list_tasks(realm_id)?;
+        tasks.retain(|task| task.assignee.as_ref() == Some(did));
+        Ok(tasks)
+    }
+
+    /// Resolve each assigned task's `assignee_name` from pinned profiles
+    fn fill_assignee_names(&self, tasks: &mut [Task]) {
+        let own_did = self.did().map(|did| did.to_string());
+        for task in tasks.iter_mut() {
+            let Some(assignee) = &task.assignee else {
+                continue;
+            };
+            task.assignee_name = if own_did.as_deref() == Some(assignee.as_str()) {
+                self.storage
+                    .load_profile(assignee.as_str())
+                    .ok()
+                    .flatten()
+                    .map(|profile| profile.display_name)
+            } else {
+                self.get_pinned_profile(assignee.as_str())
+                    .ok()
+                    .flatten()
+                    .map(|pin| pin.signed_profile.profile.display_name)
+            };
+        }
+    }
+
+    /// Get a specific task
     ///
-    /// ## Prioritization
+    /// If peers renamed the task concurrently, the titles that lost the
+    /// merge are listed in `conflicting_values` alongside the DID that wrote
+    /// each, so the UI can offer them; renaming the task again settles the
+    /// conflict. Titles from changes with no recorded author are left out.
     ///
-    /// Peers are sorted by priority:
-    /// 1. Contacts (mutually accepted) first
-    /// 2. Then by last_seen (most recently active first)
+    /// # Errors
     ///
-    /// # Returns
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub fn get_task(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+    ) -> Result<Option<Task>, SyncError> {
+        let state = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        let Some(mut task) = state.doc.get_task(task_id)? else {
+            return Ok(None);
+        };
+        task.conflicting_values = state
+            .doc
+            .title_conflicts(task_id)?
+            .into_iter()
+            .filter_map(|(author, title)| Some((Did::parse(&author?).ok()?, title)))
+            .collect();
+        self.fill_assignee_names(std::slice::from_mut(&mut task));
+        Ok(Some(task))
+    }
+
+    /// Toggle a task's completion state
     ///
-    /// A `StartupSyncResult` containing statistics about the sync attempt.
+    /// Completing a recurring task also adds the next open instance of its
+    /// series, due one interval later.
+    ///
+    /// Completing records when and by whom (our identity, if any);
+    /// un-completing clears both. Concurrent toggles resolve last-writer-wins.
+    ///
+    /// Auto-opens the realm if not already open.
+    /// Auto-saves the realm after toggling.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::NotReady` if gossip cannot be initialized.
-    pub async fn startup_sync(&mut self) -> Result<StartupSyncResult, SyncError> {
-        info!("Starting startup sync...");
-
-        // 1. Ensure gossip is initialized (starts listening for incoming connections)
-        self.ensure_gossip().await?;
-
-        // 2. Initialize profile sync (sets profile_gossip_sender so announce_profile works)
-        // This must be called before announce_profile() or profile broadcasts will fail
-        if self.identity.is_some() {
-            if let Err(e) = self.start_profile_sync(vec![]).await {
-                warn!(error = %e, "Failed to start profile sync (non-fatal)");
-            }
-        }
-
-        // 3. Generate random jitter (0-2 seconds) to avoid thundering herd
-        // Kept short to not delay app responsiveness noticeably
-        let jitter_ms: u64 = rand::rng().random_range(0..2_000);
-        debug!(jitter_ms, "Applying startup jitter");
-        tokio::time::sleep(std::time::Duration::from_millis(jitter_ms)).await;
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::TaskNotFound` if the task doesn't exist.
+    pub async fn toggle_task(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable(realm_id)?;
 
-        // 4. Initialize contact manager and reconnect to contact profile topics FIRST
-        // This must happen BEFORE announce_profile() so that:
-        // a) We're subscribed to receive profile updates from contacts
-        // b) contact_manager exists so announce_profile() can broadcast to contact topics
-        if self.identity.is_some() {
-            match self.ensure_contact_manager().await {
-                Ok(manager) => {
-                    if let Err(e) = manager.reconnect_contacts().await {
-                        warn!(error = %e, "Failed to reconnect contacts (non-fatal)");
-                    }
-                }
-                Err(e) => {
-                    warn!(error = %e, "Failed to initialize contact manager at startup (non-fatal)");
-                }
-            }
+        // Ensure realm is open
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
         }
 
-        // 5. Announce presence on global profile topic AND contact topics (if we have a profile)
-        // This now works correctly because contact_manager was initialized above
-        if self.identity.is_some() {
-            match self.announce_profile(None).await {
-                Ok(()) => info!("Presence announced on profile and contact topics"),
-                Err(e) => {
-                    // Non-fatal - we can still connect to peers without announcing
-                    warn!(error = %e, "Failed to announce presence (non-fatal)");
-                }
-            }
-        } else {
-            debug!("No identity initialized, skipping presence announcement");
-        }
+        let did = self.did();
+        let author = did.as_ref().map(|did| did.to_string());
+        let (next_id, sync_data) = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-        // 6. Get all peers and sort by priority (contacts first, then by last_seen)
-        let mut all_peers = self.storage.list_peers()?;
-        all_peers.sort_by(|a, b| {
-            // Contacts first
-            match (a.is_contact(), b.is_contact()) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => {
-                    // Then by last_seen (most recent first)
-                    b.last_seen.cmp(&a.last_seen)
-                }
-            }
-        });
+            let next_id = state.doc.toggle_task_by(task_id, did.as_ref())?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
 
-        // Skip if no gossip or no peers
-        let Some(ref gossip) = self.gossip else {
-            warn!("Gossip not initialized after ensure_gossip (unexpected)");
-            return Ok(StartupSyncResult {
-                jitter_delay_ms: jitter_ms,
-                ..Default::default()
-            });
+            // Capture incremental changes BEFORE save
+            (next_id, state.doc.generate_sync_message())
         };
 
-        if all_peers.is_empty() {
-            debug!("No known peers for startup sync");
-            return Ok(StartupSyncResult {
-                jitter_delay_ms: jitter_ms,
-                ..Default::default()
+        // Auto-save
+        self.save_realm(realm_id).await?;
+        for changed in std::iter::once(task_id).chain(next_id.as_ref()) {
+            self.emit_event(SyncEvent::TaskChanged {
+                realm_id: realm_id.clone(),
+                task_id: changed.to_string_repr(),
             });
         }
 
-        info!(
-            peer_count = all_peers.len(),
-            "Attempting startup sync with known peers"
-        );
-
-        // 5. Attempt connections with Fibonacci backoff
-        let mut result = StartupSyncResult {
-            jitter_delay_ms: jitter_ms,
-            ..Default::default()
-        };
+        // Broadcast changes to peers if syncing
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task toggle (may not be syncing)");
+            }
+        }
 
-        for mut peer in all_peers {
-            let peer_id = peer.public_key();
+        debug!(%realm_id, %task_id, "Task toggled");
+        Ok(())
+    }
 
-            // Skip contacts - they're already connected via gossip topic subscription
-            // from reconnect_contacts() above. Creating a second direct connection here
-            // can interfere with the gossip mesh and cause messages to not be delivered.
-            if peer.is_contact() {
-                // Still record as success since gossip connection is established
-                result.peers_succeeded += 1;
-                debug!(
-                    ?peer_id,
-                    "Skipping contact - already connected via gossip topic"
-                );
-                continue;
-            }
+    /// Rename a task
+    ///
+    /// Also the way to settle a title conflict: the new title supersedes
+    /// every concurrent one. Auto-opens and auto-saves the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::TaskNotFound` if the task doesn't exist.
+    pub async fn rename_task(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+        title: &str,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable(realm_id)?;
 
-            // Check Fibonacci backoff
-            if !peer.should_retry_now() {
-                result.peers_skipped_backoff += 1;
-                debug!(
-                    ?peer_id,
-                    backoff_secs = peer.backoff_delay(),
-                    "Skipping peer - backoff not elapsed"
-                );
-                continue;
-            }
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
 
-            result.peers_attempted += 1;
-            peer.record_attempt();
-            debug!(
-                ?peer_id,
-                is_contact = peer.is_contact(),
-                attempt_number = peer.connection_attempts,
-                "Attempting startup connection"
-            );
+        let author = self.did().map(|did| did.to_string());
+        let sync_data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-            // Try to connect with 10 second timeout
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(10),
-                gossip.endpoint().connect(peer_id, iroh_gossip::net::GOSSIP_ALPN),
-            )
-            .await
-            {
-                Ok(Ok(_conn)) => {
-                    peer.record_success();
-                    result.peers_succeeded += 1;
-                    info!(
-                        ?peer_id,
-                        is_contact = peer.is_contact(),
-                        success_rate = format!("{:.1}%", peer.success_rate() * 100.0),
-                        "Connected on startup"
-                    );
-                }
-                Ok(Err(e)) => {
-                    peer.record_failure();
-                    debug!(
-                        ?peer_id,
-                        error = ?e,
-                        next_retry_in_secs = peer.backoff_delay(),
-                        "Failed to connect on startup"
-                    );
-                }
-                Err(_) => {
-                    peer.record_failure();
-                    debug!(
-                        ?peer_id,
-                        next_retry_in_secs = peer.backoff_delay(),
-                        "Connection timed out on startup"
-                    );
-                }
-            }
+            state.doc.set_task_title(task_id, title)?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+            state.doc.generate_sync_message()
+        };
 
-            // Save updated peer metrics (with connection attempt results)
-            self.storage.save_peer(&peer)?;
-        }
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: task_id.to_string_repr(),
+        });
 
-        info!(
-            attempted = result.peers_attempted,
-            succeeded = result.peers_succeeded,
-            skipped = result.peers_skipped_backoff,
-            jitter_ms = result.jitter_delay_ms,
-            "Startup sync complete"
-        );
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task rename (may not be syncing)");
+            }
+        }
 
-        Ok(result)
+        debug!(%realm_id, %task_id, title, "Task renamed");
+        Ok(())
     }
 
-    /// Manually trigger a sync to refresh peer information and broadcast profile changes.
-    ///
-    /// This is a user-initiated sync that:
-    /// 1. Broadcasts our current profile to all contacts (announces changes)
-    /// 2. Reconnects to contact profile topics (to receive their updates)
-    ///
-    /// Unlike `startup_sync()`, this does not include jitter delays or extensive
-    /// reconnection attempts - it's designed for immediate user feedback.
-    ///
-    /// # Returns
+    /// Set a task's due date and recurrence
     ///
-    /// Returns the number of contacts reconnected to.
+    /// Pass `None` for either to clear it. Auto-opens and auto-saves the realm.
     ///
     /// # Errors
     ///
-    /// Returns error if profile announcement fails (non-fatal errors are logged).
-    pub async fn manual_sync(&mut self) -> Result<usize, SyncError> {
-        info!("Manual sync triggered by user");
-
-        // Count contacts from storage (regardless of reconnection success)
-        let contacts_count = self.storage.list_contacts().map(|c| c.len()).unwrap_or(0);
+    /// Returns `SyncError::InvalidOperation` if a custom interval isn't positive.
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::TaskNotFound` if the task doesn't exist.
+    pub async fn schedule_task(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+        due_at: Option<i64>,
+        recurrence: Option<Recurrence>,
+    ) -> Result<(), SyncError> {
+        if recurrence.is_some_and(|r| r.interval_secs() <= 0) {
+            return Err(SyncError::InvalidOperation(
+                "Recurrence interval must be positive".to_string(),
+            ));
+        }
+        self.ensure_writable(realm_id)?;
 
-        // 1. Broadcast our current profile to announce any changes
-        if self.identity.is_some() {
-            match self.announce_profile(None).await {
-                Ok(()) => {
-                    info!("Profile broadcast successful");
-                }
-                Err(e) => {
-                    // Log but don't fail - we can still reconnect to contacts
-                    warn!(error = %e, "Failed to broadcast profile during manual sync");
-                }
-            }
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
         }
 
-        // 2. Reconnect to contact profile topics to receive their updates
-        // Use ensure_contact_manager to initialize if needed (it's lazy-loaded)
-        if self.identity.is_some() {
-            match self.ensure_contact_manager().await {
-                Ok(manager) => {
-                    if let Err(e) = manager.reconnect_contacts().await {
-                        warn!(error = %e, "Failed to reconnect contacts during manual sync");
-                    } else {
-                        info!(contacts = contacts_count, "Reconnected to contact topics");
-                    }
-                }
-                Err(e) => {
-                    warn!(error = %e, "Failed to initialize contact manager for sync");
-                }
+        let author = self.did().map(|did| did.to_string());
+        let sync_data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            state.doc.set_task_schedule(task_id, due_at, recurrence)?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+            state.doc.generate_sync_message()
+        };
+
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: task_id.to_string_repr(),
+        });
+
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task schedule (may not be syncing)");
             }
         }
 
-        info!(contacts_count, "Manual sync complete");
-        Ok(contacts_count)
+        debug!(%realm_id, %task_id, ?due_at, ?recurrence, "Task scheduled");
+        Ok(())
     }
 
-    /// Start syncing a realm with peers via gossip
+    /// Delete a task from a realm
     ///
-    /// Subscribes to the realm's gossip topic and begins sending/receiving
-    /// sync messages. This method can be called for multiple realms concurrently
-    /// without blocking.
+    /// Auto-opens the realm if not already open.
+    /// Auto-saves the realm after deleting.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub async fn start_sync(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn delete_task(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable(realm_id)?;
+
         // Ensure realm is open
         if !self.realms.contains_key(realm_id) {
             self.open_realm(realm_id).await?;
         }
 
-        self.start_sync_internal(realm_id).await
-    }
+        let author = self.did().map(|did| did.to_string());
+        let sync_data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-    /// Internal sync starter that assumes the realm is already open.
-    /// Used by open_realm to avoid circular recursion.
-    async fn start_sync_internal(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
-        // Check if already syncing
-        if self.is_realm_syncing(realm_id) {
-            debug!(%realm_id, "Already syncing");
-            return Ok(());
-        }
+            state.doc.delete_task(task_id)?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
 
-        info!(%realm_id, "Starting sync");
+            // Capture incremental changes BEFORE save
+            state.doc.generate_sync_message()
+        };
 
-        // Update status to Connecting
-        self.sync_status
-            .lock()
-            .unwrap()
-            .insert(realm_id.clone(), SyncStatus::Connecting);
-        let _ = self.event_tx.send(SyncEvent::StatusChanged {
+        // Auto-save
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
             realm_id: realm_id.clone(),
-            status: SyncStatus::Connecting,
+            task_id: task_id.to_string_repr(),
         });
 
-        // Initialize gossip
-        let gossip = self.ensure_gossip().await?;
+        // Broadcast changes to peers if syncing
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task deletion (may not be syncing)");
+            }
+        }
 
-        // Get effective bootstrap peers (static from storage + online from registry)
-        let bootstrap_node_ids = self.get_effective_bootstrap_peers(realm_id)?;
+        debug!(%realm_id, %task_id, "Task deleted");
+        Ok(())
+    }
 
-        // Add full address info to static discovery for faster reconnection
-        if let Some(realm_info) = self.storage.load_realm(realm_id)? {
-            for peer_bytes in &realm_info.bootstrap_peers {
-                match peer_bytes.to_endpoint_addr() {
-                    Ok(endpoint_addr) => {
-                        debug!(
-                            %realm_id,
-                            peer = %endpoint_addr.id,
-                            relay = ?peer_bytes.relay_url,
-                            addrs = peer_bytes.direct_addresses.len(),
-                            "Adding saved bootstrap peer to static discovery"
-                        );
-                        gossip.add_peer_addr(endpoint_addr.clone());
-                    }
-                    Err(e) => {
-                        warn!(%realm_id, error = ?e, "Failed to convert saved peer address");
-                    }
-                }
-            }
+    /// List who did what to a task, oldest first
+    ///
+    /// Derived from the realm's Automerge change graph: each change that
+    /// created, completed, reopened, retitled, edited or deleted the task is
+    /// attributed to the DID and time its node recorded when committing it.
+    /// That DID is returned as [`TaskActivity::claimed_author`]: it is not
+    /// verified, as changes reach us relayed through other peers.
+    /// Works for deleted tasks too, as long as the task ID is known.
+    /// Activity from before a [`Self::compact_realm_history`] is read from
+    /// the history archived when the compaction was applied here.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn task_history(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+    ) -> Result<Vec<TaskActivity>, SyncError> {
+        let recent = self.realm_doc_mut(realm_id).await?.task_history(task_id)?;
+        let mut history = Vec::new();
+        for bytes in self.storage.list_realm_history(realm_id)? {
+            history.extend(RealmDoc::load(&bytes)?.task_history(task_id)?);
         }
+        history.extend(recent);
+        Ok(history)
+    }
 
-        if !bootstrap_node_ids.is_empty() {
-            info!(%realm_id, peer_count = bootstrap_node_ids.len(), "Using bootstrap peers (static + registry) for reconnection");
-        }
+    // ═══════════════════════════════════════════════════════════════════════
+    // Reminders
+    // ═══════════════════════════════════════════════════════════════════════
 
-        // Subscribe to topic using split API (receiver not wrapped in mutex)
-        let topic_id = TopicId::from_bytes(*realm_id.as_bytes());
-        let (sender, mut receiver) = gossip.subscribe_split(topic_id, bootstrap_node_ids).await?;
+    /// Set how long before its due date a task should be reminded about
+    ///
+    /// `None` restores the default lead time of
+    /// [`DEFAULT_REMINDER_LEAD_SECS`](crate::DEFAULT_REMINDER_LEAD_SECS).
+    /// Auto-opens and auto-saves the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::TaskNotFound` if the task doesn't exist.
+    pub async fn set_task_reminder(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+        lead: Option<Duration>,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable(realm_id)?;
 
-        // Store sender for broadcasting
-        if let Some(state) = self.realms.get_mut(realm_id) {
-            state.topic_sender = Some(sender);
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
         }
 
-        // Spawn background listener task that owns the receiver directly
-        let listener_realm_id = realm_id.clone();
-        let sync_tx = self.sync_tx.clone();
-        let event_tx = self.event_tx.clone();
-        // Clone sync_status Arc for thread-safe peer counting in listener task
-        let sync_status = self.sync_status.clone();
-        // Clone peer_registry for tracking discovered peers
-        let peer_registry = self.peer_registry.clone();
+        let author = self.did().map(|did| did.to_string());
+        let remind_before_secs = lead.map(|lead| lead.as_secs() as i64);
+        let sync_data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-        tokio::spawn(async move {
-            debug!(%listener_realm_id, "Sync listener task started");
-            let mut event_count = 0u64;
-            loop {
-                debug!(%listener_realm_id, event_count, "Listener waiting for next event...");
-                match receiver.recv_event().await {
-                    Some(TopicEvent::Message(msg)) => {
-                        event_count += 1;
-                        debug!(
-                            %listener_realm_id,
-                            event_count,
-                            from = ?msg.from,
-                            bytes = msg.content.len(),
-                            "Received sync message"
-                        );
-                        // Send to channel for processing by main engine
-                        if sync_tx
-                            .send(SyncChannelMessage::IncomingData {
-                                realm_id: listener_realm_id.clone(),
-                                envelope_bytes: msg.content,
-                            })
-                            .is_err()
-                        {
-                            debug!(%listener_realm_id, "Sync channel closed, stopping listener");
-                            break;
-                        }
-                        // Notify listeners that data arrived
-                        let _ = event_tx.send(SyncEvent::RealmChanged {
-                            realm_id: listener_realm_id.clone(),
-                            changes_applied: 1,
-                        });
-                    }
-                    Some(TopicEvent::NeighborUp(peer)) => {
-                        event_count += 1;
-                        debug!(%listener_realm_id, event_count, ?peer, "Peer connected");
+            state.doc.set_task_reminder(task_id, remind_before_secs)?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+            state.doc.generate_sync_message()
+        };
 
-                        // Record peer in registry
-                        let peer_info =
-                            PeerInfo::new(peer, PeerSource::FromRealm(listener_realm_id.clone()))
-                                .with_status(PeerStatus::Online);
-                        if let Err(e) = peer_registry.add_or_update(&peer_info) {
-                            warn!(?peer, error = ?e, "Failed to record peer in registry");
-                        } else {
-                            // Also record the realm for this peer
-                            let _ = peer_registry.add_peer_realm(&peer, &listener_realm_id);
-                            debug!(?peer, "Recorded peer in registry");
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: task_id.to_string_repr(),
+        });
 
-                            // Note: We don't automatically add gossip-discovered peers to
-                            // bootstrap_peers in storage, as we don't have their full EndpointAddr
-                            // at this point. The peer_registry tracks them for reconnection purposes.
-                        }
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task reminder (may not be syncing)");
+            }
+        }
 
-                        // Update peer count in sync_status (thread-safe)
-                        let new_count = {
-                            let mut status_map = sync_status.lock().unwrap();
-                            if let Some(SyncStatus::Syncing { peer_count }) =
-                                status_map.get_mut(&listener_realm_id)
-                            {
-                                *peer_count += 1;
-                                *peer_count
-                            } else {
-                                // Initialize to 1 if not in Syncing state
-                                status_map.insert(
-                                    listener_realm_id.clone(),
-                                    SyncStatus::Syncing { peer_count: 1 },
-                                );
-                                1
-                            }
-                        };
+        Ok(())
+    }
 
-                        // Request broadcast of our full document to the newly connected peer
-                        // This ensures offline changes are shared when peers reconnect
-                        let _ = sync_tx.send(SyncChannelMessage::BroadcastRequest {
-                            realm_id: listener_realm_id.clone(),
-                        });
+    /// Assign a task to a realm member, or clear the assignment with `None`
+    ///
+    /// Concurrent assignments resolve like any other task edit: the last
+    /// writer wins. Auto-opens and auto-saves the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    /// Returns `SyncError::TaskNotFound` if the task doesn't exist.
+    pub async fn assign_task(
+        &mut self,
+        realm_id: &RealmId,
+        task_id: &TaskId,
+        assignee: Option<Did>,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable(realm_id)?;
 
-                        // Emit events
-                        let _ = event_tx.send(SyncEvent::PeerConnected {
-                            realm_id: listener_realm_id.clone(),
-                            peer_id: peer.to_string(),
-                        });
-                        let _ = event_tx.send(SyncEvent::StatusChanged {
-                            realm_id: listener_realm_id.clone(),
-                            status: SyncStatus::Syncing {
-                                peer_count: new_count,
-                            },
-                        });
-                    }
-                    Some(TopicEvent::NeighborDown(peer)) => {
-                        event_count += 1;
-                        debug!(%listener_realm_id, event_count, ?peer, "Peer disconnected");
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
 
-                        // Mark peer as offline in registry
-                        if let Err(e) = peer_registry.update_status(&peer, PeerStatus::Offline) {
-                            warn!(?peer, error = ?e, "Failed to update peer status to offline");
-                        } else {
-                            debug!(?peer, "Marked peer as offline in registry");
-                        }
+        let author = self.did().map(|did| did.to_string());
+        let sync_data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
 
-                        // Update peer count in sync_status (thread-safe)
-                        let new_count = {
-                            let mut status_map = sync_status.lock().unwrap();
-                            if let Some(SyncStatus::Syncing { peer_count }) =
-                                status_map.get_mut(&listener_realm_id)
-                            {
-                                *peer_count = peer_count.saturating_sub(1);
-                                *peer_count
-                            } else {
-                                0
-                            }
-                        };
+            state.doc.set_task_assignee(task_id, assignee)?;
+            state.doc.commit(author.as_deref());
+            state.dirty = true;
+            state.doc.generate_sync_message()
+        };
 
-                        // Emit events
-                        let _ = event_tx.send(SyncEvent::PeerDisconnected {
-                            realm_id: listener_realm_id.clone(),
-                            peer_id: peer.to_string(),
-                        });
-                        let _ = event_tx.send(SyncEvent::StatusChanged {
-                            realm_id: listener_realm_id.clone(),
-                            status: SyncStatus::Syncing {
-                                peer_count: new_count,
-                            },
-                        });
-                    }
-                    None => {
-                        debug!(%listener_realm_id, event_count, "Topic subscription closed");
-                        break;
-                    }
-                }
-            }
-            debug!(%listener_realm_id, event_count, "Sync listener task ended");
+        self.save_realm(realm_id).await?;
+        self.emit_event(SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: task_id.to_string_repr(),
         });
 
-        // Spawn periodic bootstrap reconnection task
-        // This handles the case where both peers start at the same time - the initial
-        // add_peer_addr calls fail because neither peer is ready yet. This task
-        // periodically re-tries the bootstrap peers until we connect.
-        if let Some(realm_info) = self.storage.load_realm(realm_id)? {
-            if !realm_info.bootstrap_peers.is_empty() {
-                let reconnect_realm_id = realm_id.clone();
-                let reconnect_gossip = gossip.clone();
-                let reconnect_sync_status = self.sync_status.clone();
-                let bootstrap_peers = realm_info.bootstrap_peers.clone();
-
-                tokio::spawn(async move {
-                    use std::time::Duration;
+        if !sync_data.is_empty() {
+            if let Err(e) = self.broadcast_changes_with_data(realm_id, sync_data).await {
+                debug!(%realm_id, error = %e, "Failed to broadcast task assignment (may not be syncing)");
+            }
+        }
 
-                    let mut attempt = 0;
-                    const MAX_ATTEMPTS: u32 = 24; // 2 minutes of retries (5s * 24)
-                    const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+        Ok(())
+    }
 
-                    loop {
-                        // Wait before retry (skip on first iteration to allow initial connect attempt)
-                        if attempt > 0 {
-                            tokio::time::sleep(RETRY_INTERVAL).await;
-                        }
-                        attempt += 1;
+    /// Open tasks whose reminder falls due within `within` from now
+    ///
+    /// Only looks at open realms. Tasks already reminded about are left
+    /// out; overdue ones that haven't been are included. Sorted by
+    /// reminder time.
+    pub fn upcoming_reminders(&self, within: Duration) -> Vec<(RealmId, Task)> {
+        let until = chrono::Utc::now().timestamp() + within.as_secs() as i64;
+        self.reminders_due_by(until)
+    }
 
-                        // Check if we already have peers - if so, we're done
-                        let current_peer_count = {
-                            let status_map = reconnect_sync_status.lock().unwrap();
-                            match status_map.get(&reconnect_realm_id) {
-                                Some(SyncStatus::Syncing { peer_count }) => *peer_count,
-                                _ => 0,
-                            }
-                        };
+    /// Emit `SyncEvent::TaskReminder` for every reminder that is now due
+    ///
+    /// Called from [`process_pending_sync`](Self::process_pending_sync);
+    /// hosts that don't poll for sync messages should call it on a timer.
+    /// Each reminder is sent once per due date; rescheduling a task arms it
+    /// again. Sent reminders are only remembered while the engine runs.
+    /// Reminders in muted realms are marked as sent without an event.
+    ///
+    /// # Returns
+    ///
+    /// The number of reminders sent.
+    pub fn fire_due_reminders(&mut self) -> usize {
+        self.fire_reminders_at(chrono::Utc::now().timestamp())
+    }
 
-                        if current_peer_count > 0 {
-                            debug!(
-                                %reconnect_realm_id,
-                                peer_count = current_peer_count,
-                                "Bootstrap reconnection task: peers connected, stopping"
-                            );
-                            break;
-                        }
-
-                        if attempt > MAX_ATTEMPTS {
-                            debug!(
-                                %reconnect_realm_id,
-                                attempt,
-                                "Bootstrap reconnection task: max attempts reached, stopping"
-                            );
-                            break;
-                        }
-
-                        // Re-add all bootstrap peer addresses
-                        for peer_bytes in &bootstrap_peers {
-                            if let Ok(endpoint_addr) = peer_bytes.to_endpoint_addr() {
-                                debug!(
-                                    %reconnect_realm_id,
-                                    attempt,
-                                    peer = %endpoint_addr.id,
-                                    "Bootstrap reconnection: re-adding peer address"
-                                );
-                                reconnect_gossip.add_peer_addr(endpoint_addr);
-                            }
-                        }
-                    }
-                });
+    /// [`fire_due_reminders`](Self::fire_due_reminders) as of Unix time `now`
+    pub fn fire_reminders_at(&mut self, now: i64) -> usize {
+        let due = self.reminders_due_by(now);
+        let mut sent = 0;
+        for (realm_id, task) in &due {
+            if let Some(due_at) = task.due_at {
+                self.reminded.insert((task.id.clone(), due_at));
             }
+            if self.is_realm_muted(realm_id) {
+                debug!(%realm_id, task_id = %task.id, "Task reminder due in muted realm");
+                continue;
+            }
+            debug!(%realm_id, task_id = %task.id, "Task reminder due");
+            self.emit_event(SyncEvent::TaskReminder {
+                realm_id: realm_id.clone(),
+                task_id: task.id.to_string_repr(),
+            });
+            sent += 1;
         }
+        sent
+    }
 
-        // Update status to Syncing
-        self.sync_status
-            .lock()
-            .unwrap()
-            .insert(realm_id.clone(), SyncStatus::Syncing { peer_count: 0 });
-        let _ = self.event_tx.send(SyncEvent::StatusChanged {
-            realm_id: realm_id.clone(),
-            status: SyncStatus::Syncing { peer_count: 0 },
-        });
-
-        debug!(%realm_id, "Sync started");
-
-        // CRITICAL: Broadcast our FULL DOCUMENT when sync starts.
-        // This ensures that when peers reconnect after being offline, they exchange
-        // their complete document states and Automerge merges them automatically.
-        // Without this, peers would just sit waiting and never sync offline changes.
-        if let Err(e) = self.broadcast_changes_with_data(realm_id, vec![]).await {
-            warn!(
-                %realm_id,
-                error = ?e,
-                "Failed to broadcast initial document (will retry on next sync activity)"
-            );
-        } else {
-            info!(%realm_id, "Broadcast initial document to peers");
-        }
-
-        Ok(())
+    /// Unsent reminders whose time is at or before `until`
+    fn reminders_due_by(&self, until: i64) -> Vec<(RealmId, Task)> {
+        let mut due: Vec<(RealmId, Task)> = self
+            .realms
+            .iter()
+            .flat_map(|(realm_id, state)| {
+                state
+                    .doc
+                    .list_tasks()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |task| (realm_id.clone(), task))
+            })
+            .filter(|(_, task)| {
+                task.reminder_at().is_some_and(|at| at <= until)
+                    && task
+                        .due_at
+                        .is_some_and(|due_at| !self.reminded.contains(&(task.id.clone(), due_at)))
+            })
+            .collect();
+        due.sort_by_key(|(_, task)| task.reminder_at());
+        due
     }
 
-    /// Stop syncing a realm
+    // ═══════════════════════════════════════════════════════════════════════
+    // P2P Sync Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Start the P2P networking layer.
     ///
-    /// Removes the realm from the gossip topic. The realm remains open.
+    /// This initializes the iroh-gossip networking stack, including:
+    /// - QUIC endpoint for NAT traversal
+    /// - Gossip protocol for topic-based pub/sub
+    /// - Connection to default relay servers
+    /// - A background scheduler that retries offline peers (see
+    ///   `GossipConfig::reconnect_interval`)
+    ///
+    /// After calling this, the node can:
+    /// - Generate invites with its own address as a bootstrap peer
+    /// - Subscribe to realm topics for sync
+    /// - Accept incoming connections from peers
+    ///
+    /// This is a no-op if networking is already started.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub async fn stop_sync(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
-        let state = self
-            .realms
-            .get_mut(realm_id)
-            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-        if state.topic_sender.is_some() {
-            state.topic_sender = None;
-
-            // Update status to Idle
-            self.sync_status
-                .lock()
-                .unwrap()
-                .insert(realm_id.clone(), SyncStatus::Idle);
-            let _ = self.event_tx.send(SyncEvent::StatusChanged {
-                realm_id: realm_id.clone(),
-                status: SyncStatus::Idle,
-            });
-
-            info!(%realm_id, "Sync stopped");
-        } else {
-            debug!(%realm_id, "Not syncing");
+    /// Returns `SyncError::Network` if the gossip layer fails to initialize.
+    pub async fn start_networking(&mut self) -> Result<(), SyncError> {
+        self.networking_requested = true;
+        let gossip = self.ensure_gossip().await?;
+        if !self.reconnect_scheduler_started {
+            Self::start_reconnect_scheduler(
+                Arc::downgrade(&gossip),
+                Arc::downgrade(&self.peer_registry),
+                self.gossip_config.effective_reconnect_interval(),
+            );
+            self.reconnect_scheduler_started = true;
         }
-
+        info!("P2P networking started");
         Ok(())
     }
 
-    /// Get the sync status for a realm
-    ///
-    /// Returns `SyncStatus::Idle` if the realm is not syncing or not found.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let status = engine.sync_status(&realm_id);
-    /// match status {
-    ///     SyncStatus::Syncing { peer_count } => println!("Syncing with {} peers", peer_count),
-    ///     SyncStatus::Idle => println!("Not syncing"),
-    ///     _ => {}
-    /// }
-    /// ```
-    pub fn sync_status(&self, realm_id: &RealmId) -> SyncStatus {
-        self.sync_status
-            .lock()
-            .unwrap()
-            .get(realm_id)
-            .cloned()
-            .unwrap_or(SyncStatus::Idle)
+    /// Check if the P2P networking layer is active.
+    pub fn is_networking_active(&self) -> bool {
+        self.gossip.is_some()
     }
 
-    /// Get detailed network debug information for a realm.
+    /// Get effective bootstrap peers by combining static peers from storage
+    /// with online peers from the peer registry.
     ///
-    /// Returns information useful for debugging sync issues:
-    /// - Our node ID
-    /// - Current sync status with peer count
-    /// - Whether sync is active
-    /// - Bootstrap peer count
-    pub fn network_debug_info(&self, realm_id: &RealmId) -> NetworkDebugInfo {
-        // Get node ID from gossip if available
-        let (node_id, node_id_full) = if let Some(ref gossip) = self.gossip {
-            let pk = gossip.public_key();
-            let full = format!("{:?}", pk);
-            // Extract just the hex part, e.g., "PublicKey(abc...)" -> "abc..."
-            let short = full
-                .strip_prefix("PublicKey(")
-                .and_then(|s| s.strip_suffix(")"))
-                .map(|s| s.chars().take(8).collect::<String>())
-                .unwrap_or_else(|| "unknown".to_string());
-            (short, full)
-        } else {
-            ("offline".to_string(), "offline".to_string())
-        };
+    /// This enables dynamic peer discovery - peers discovered in previous sessions
+    /// automatically become bootstrap peers for reconnection.
+    fn get_effective_bootstrap_peers(
+        &self,
+        realm_id: &RealmId,
+    ) -> Result<Vec<iroh::PublicKey>, SyncError> {
+        let mut peer_ids = std::collections::HashSet::new();
 
-        // Get sync status
-        let status = self.sync_status(realm_id);
+        // 1. Add static peers from invite/storage
+        if let Some(realm_info) = self.storage.load_realm(realm_id)? {
+            for peer_bytes in &realm_info.bootstrap_peers {
+                match peer_bytes.to_endpoint_addr() {
+                    Ok(endpoint_addr) => {
+                        peer_ids.insert(endpoint_addr.id);
+                    }
+                    Err(e) => {
+                        warn!(%realm_id, error = ?e, "Failed to parse saved peer address");
+                    }
+                }
+            }
+        }
 
-        // Get realm info for bootstrap peers
-        let (bootstrap_peer_count, is_shared) = self
-            .storage
-            .load_realm(realm_id)
-            .ok()
-            .flatten()
-            .map(|info| (info.bootstrap_peers.len(), info.is_shared))
-            .unwrap_or((0, false));
+        // 2. Add online peers from registry that share this realm, plus LAN peers
+        //    found via local discovery (they may be following the realm too)
+        match self.peer_registry.list_by_status(PeerStatus::Online) {
+            Ok(online_peers) => {
+                for peer_info in online_peers {
+                    let on_lan = self.gossip_config.local_discovery
+                        && peer_info.source == PeerSource::LocalDiscovery;
+                    if on_lan || peer_info.shared_realms.contains(realm_id) {
+                        match iroh::PublicKey::from_bytes(&peer_info.endpoint_id) {
+                            Ok(peer_id) => {
+                                peer_ids.insert(peer_id);
+                            }
+                            Err(e) => {
+                                warn!(error = ?e, "Failed to parse peer endpoint ID from registry");
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(error = ?e, "Failed to query online peers from registry");
+            }
+        }
 
-        // Check if sync is active (we have a gossip and status is not Idle)
-        let sync_active = self.gossip.is_some() && !matches!(status, SyncStatus::Idle);
+        let peers: Vec<iroh::PublicKey> = peer_ids.into_iter().collect();
+        if !peers.is_empty() {
+            debug!(%realm_id, peer_count = peers.len(), "Effective bootstrap peers (static + registry)");
+        }
+        Ok(peers)
+    }
 
-        // Extract error if status is Error
-        let last_error = match &status {
-            SyncStatus::Error(msg) => Some(msg.clone()),
-            _ => None,
-        };
+    /// Ensure gossip networking is initialized
+    async fn ensure_gossip(&mut self) -> Result<Arc<GossipSync>, SyncError> {
+        if let Some(ref gossip) = self.gossip {
+            return Ok(gossip.clone());
+        }
 
-        // Get connected peer IDs (currently we only have count, not IDs)
-        // TODO: Track actual peer IDs in sync_status for display
-        let connected_peers = Vec::new();
+        info!("Initializing gossip networking");
 
-        // Get detailed peer information from the peer registry
-        // Filter peers by those that share this realm
-        let peers = self
-            .peer_registry
-            .list_all()
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|peer| peer.shared_realms.contains(realm_id))
-            .map(|peer| {
-                use crate::sync::events::PeerDebugInfo;
-                let pk = peer.public_key();
-                let full = format!("{:?}", pk);
-                let short = full
-                    .strip_prefix("PublicKey(")
-                    .and_then(|s| s.strip_suffix(")"))
-                    .map(|s| s.chars().take(8).collect::<String>())
-                    .unwrap_or_else(|| "unknown".to_string());
+        // Load or generate persistent endpoint secret key
+        let secret_key = match self.storage.load_endpoint_secret_key()? {
+            Some(key_bytes) => {
+                info!("Loaded persistent endpoint secret key from storage");
+                iroh::SecretKey::from(key_bytes)
+            }
+            None => {
+                info!("No endpoint secret key found, generating new one");
+                let secret_key = iroh::SecretKey::generate(&mut rand::rng());
+                let key_bytes: [u8; 32] = secret_key.to_bytes();
+                self.storage.save_endpoint_secret_key(&key_bytes)?;
+                info!("Saved new endpoint secret key to storage");
+                secret_key
+            }
+        };
 
-                // Calculate connection duration (simple: how long since last_seen)
-                let connection_duration_secs = if peer.status == crate::peers::PeerStatus::Online {
-                    // For online peers, calculate time since last_seen
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs();
-                    Some(now.saturating_sub(peer.last_seen))
-                } else {
-                    None
-                };
+        // Pass storage, event_tx, and local_did so Router can register contact protocol handler
+        // The local_did is required for the simplified protocol's local key derivation
+        let contact_deps = self.identity.as_ref().map(|keypair| {
+            let did = Did::from_public_key(&keypair.public_key());
+            (Arc::new(self.storage.clone()), self.contact_event_tx.clone(), did.to_string())
+        });
 
-                PeerDebugInfo {
-                    peer_id: short,
-                    peer_id_full: full,
-                    is_connected: peer.status == crate::peers::PeerStatus::Online,
-                    connection_duration_secs,
-                }
-            })
-            .collect();
+        // Pass profile handler deps if identity is available
+        let profile_deps = self.identity.as_ref().map(|keypair| {
+            let did = Did::from_public_key(&keypair.public_key());
+            (Arc::new(self.storage.clone()), Arc::new(keypair.clone()), did)
+        });
 
-        NetworkDebugInfo {
-            node_id,
-            node_id_full,
-            status,
-            bootstrap_peer_count,
-            is_shared,
-            sync_active,
-            last_error,
-            connected_peers,
-            peers,
+        // Pass blob manager for P2P image transfer capability
+        // GossipSync::with_config returns (GossipSync, Option<ActiveContactTopics>)
+        // We store the active_topics for later use by ContactManager
+        let (gossip_sync, active_topics) = GossipSync::with_config(
+            self.gossip_config.clone(),
+            Some(secret_key),
+            contact_deps,
+            profile_deps,
+            Some(&self.blob_manager),
+        ).await?;
+        let gossip = Arc::new(gossip_sync);
+
+        if let Some(events) = gossip.local_discovery_events().await {
+            Self::start_local_discovery_listener(events, self.peer_registry.clone());
         }
-    }
+        Self::start_keepalive_task(Arc::downgrade(&gossip), Arc::downgrade(&self.peer_registry));
 
-    /// Subscribe to sync events
-    ///
-    /// Returns a receiver that will receive events when:
-    /// - Remote changes arrive for a realm
-    /// - Peer connects or disconnects
-    /// - Sync status changes
-    /// - Errors occur
-    ///
-    /// Multiple subscribers can exist; events are broadcast to all.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let mut events = engine.subscribe_events();
-    ///
-    /// tokio::spawn(async move {
-    ///     while let Ok(event) = events.recv().await {
-    ///         match event {
-    ///             SyncEvent::RealmChanged { realm_id, .. } => {
-    ///                 println!("Realm {} changed!", realm_id);
-    ///             }
-    ///             SyncEvent::StatusChanged { realm_id, status } => {
-    ///                 println!("Realm {} status: {:?}", realm_id, status);
-    ///             }
-    ///             _ => {}
-    ///         }
-    ///     }
-    /// });
-    /// ```
-    pub fn subscribe_events(&self) -> broadcast::Receiver<SyncEvent> {
-        self.event_tx.subscribe()
+        self.gossip = Some(gossip.clone());
+        self.active_contact_topics = active_topics;
+        Ok(gossip)
     }
 
-    /// Register a callback for realm changes
-    ///
-    /// This is a convenience method that spawns a background task to listen
-    /// for `SyncEvent::RealmChanged` events and calls the provided callback.
-    ///
-    /// # Arguments
-    ///
-    /// * `callback` - Function to call when remote changes arrive for any realm
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// engine.on_realm_change(|realm_id| {
-    ///     println!("Realm {} was updated by a peer!", realm_id);
-    /// });
-    /// ```
-    pub fn on_realm_change<F>(&self, callback: F)
-    where
-        F: Fn(RealmId) + Send + 'static,
-    {
-        let mut receiver = self.event_tx.subscribe();
+    /// Start a background task that records LAN peers found via mDNS
+    ///
+    /// New peers are added to the registry as `PeerSource::LocalDiscovery`;
+    /// known peers keep their source and are marked online. Peers whose mDNS
+    /// announcements expire are marked offline.
+    fn start_local_discovery_listener(
+        mut events: impl n0_future::Stream<Item = iroh::discovery::mdns::DiscoveryEvent>
+            + Unpin
+            + Send
+            + 'static,
+        peer_registry: Arc<PeerRegistry>,
+    ) {
+        use iroh::discovery::mdns::DiscoveryEvent;
+        use n0_future::StreamExt;
 
         tokio::spawn(async move {
-            while let Ok(event) = receiver.recv().await {
-                if let SyncEvent::RealmChanged { realm_id, .. } = event {
-                    callback(realm_id);
+            while let Some(event) = events.next().await {
+                let result = match event {
+                    DiscoveryEvent::Discovered { endpoint_info, .. } => {
+                        let endpoint_id = endpoint_info.endpoint_id;
+                        debug!(%endpoint_id, "Discovered peer on local network");
+                        match peer_registry.get(&endpoint_id) {
+                            Ok(Some(_)) => {
+                                peer_registry.update_status(&endpoint_id, PeerStatus::Online)
+                            }
+                            Ok(None) => peer_registry.add_or_update(
+                                &PeerInfo::new(endpoint_id, PeerSource::LocalDiscovery)
+                                    .with_status(PeerStatus::Online),
+                            ),
+                            Err(e) => Err(e),
+                        }
+                    }
+                    DiscoveryEvent::Expired { endpoint_id } => {
+                        debug!(%endpoint_id, "Local network peer expired");
+                        peer_registry.update_status(&endpoint_id, PeerStatus::Offline)
+                    }
+                };
+                if let Err(e) = result {
+                    warn!(error = ?e, "Failed to record local discovery event");
                 }
             }
+            debug!("Local discovery listener stopped");
         });
     }
 
-    /// Get the list of realms currently syncing
-    pub fn syncing_realms(&self) -> Vec<RealmId> {
-        self.sync_status
-            .lock()
-            .unwrap()
-            .iter()
-            .filter_map(|(realm_id, status)| {
-                if !matches!(status, SyncStatus::Idle) {
-                    Some(realm_id.clone())
-                } else {
-                    None
+    /// Start a background task that scores the connection to each online peer
+    ///
+    /// Every `KEEPALIVE_INTERVAL` the endpoint's current latency and path type
+    /// for each online peer are recorded in the registry. A peer the endpoint
+    /// has no path to counts as a lost probe. Only weak references are held
+    /// between rounds, so the task never keeps the database open after the
+    /// engine shuts down.
+    fn start_keepalive_task(
+        gossip: std::sync::Weak<GossipSync>,
+        peer_registry: std::sync::Weak<PeerRegistry>,
+    ) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(KEEPALIVE_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let (Some(gossip), Some(peer_registry)) = (gossip.upgrade(), peer_registry.upgrade())
+                else {
+                    break;
+                };
+                let online = match peer_registry.list_by_status(PeerStatus::Online) {
+                    Ok(peers) => peers,
+                    Err(e) => {
+                        warn!(error = ?e, "Failed to list peers for keepalive");
+                        continue;
+                    }
+                };
+                for peer in online {
+                    let endpoint_id = peer.public_key();
+                    let result = match gossip.endpoint().latency(endpoint_id) {
+                        Some(rtt) => {
+                            let direct = gossip
+                                .endpoint()
+                                .conn_type(endpoint_id)
+                                .map(|mut watcher| {
+                                    use iroh::Watcher;
+                                    matches!(watcher.get(), iroh::endpoint::ConnectionType::Direct(_))
+                                })
+                                .unwrap_or(false);
+                            peer_registry.record_rtt_sample(&endpoint_id, rtt, direct)
+                        }
+                        None => peer_registry.record_probe_loss(&endpoint_id),
+                    };
+                    if let Err(e) = result {
+                        debug!(%endpoint_id, error = ?e, "Failed to record keepalive sample");
+                    }
                 }
-            })
-            .collect()
+            }
+            debug!("Keepalive task stopped");
+        });
     }
 
-    /// Get the count of realms currently syncing
-    pub fn syncing_count(&self) -> usize {
-        self.sync_status
-            .lock()
-            .unwrap()
-            .values()
-            .filter(|s| !matches!(s, SyncStatus::Idle))
-            .count()
+    /// Set the endpoint options used when networking starts
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidOperation` if networking is already running,
+    /// since the options only apply when the endpoint is created.
+    /// Returns `SyncError::InvalidConfig` if the relay settings are invalid.
+    pub fn set_gossip_config(&mut self, config: GossipConfig) -> Result<(), SyncError> {
+        config.validate()?;
+        if self.gossip.is_some() {
+            return Err(SyncError::InvalidOperation(
+                "Networking already started; gossip config must be set before start_networking()"
+                    .to_string(),
+            ));
+        }
+        self.packet_gate.configure(&config);
+        self.seen_changes = SeenCache::new(config.effective_dedup_cache_size());
+        self.gossip_config = config;
+        Ok(())
     }
 
-    /// Emit a sync event (internal helper)
-    #[allow(dead_code)]
-    fn emit_event(&self, event: SyncEvent) {
-        let _ = self.event_tx.send(event);
+    /// Get a reference to the gossip instance if initialized
+    ///
+    /// Returns an error if gossip has not been initialized yet.
+    /// Use `ensure_gossip()` if you want to initialize it automatically.
+    fn ensure_gossip_ref(&self) -> Result<&GossipSync, SyncError> {
+        self.gossip
+            .as_ref()
+            .map(|g| g.as_ref())
+            .ok_or_else(|| SyncError::NotReady("Gossip networking not initialized".to_string()))
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Sync Envelope Operations (Signed + Encrypted)
-    // ═══════════════════════════════════════════════════════════════════════
-
-    /// Broadcast a sync message to all peers in a realm (signed + encrypted)
-    ///
-    /// The message is wrapped in a `SyncEnvelope` which provides:
-    /// - Encryption with the realm's symmetric key (ChaCha20-Poly1305)
-    /// - Signature from our identity for authentication
-    ///
-    /// # Errors
+    /// Ensure contact manager is initialized
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open or not syncing.
-    /// Returns `SyncError::Identity` if identity has not been initialized.
-    pub async fn broadcast_sync(
-        &self,
-        realm_id: &RealmId,
-        message: SyncMessage,
-    ) -> Result<(), SyncError> {
-        // Get realm state (must be open and syncing)
-        let state = self
-            .realms
-            .get(realm_id)
-            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+    /// Initializes the contact manager if not already initialized.
+    /// Requires both gossip and identity to be initialized first.
+    async fn ensure_contact_manager(&mut self) -> Result<Arc<ContactManager>, SyncError> {
+        if let Some(ref manager) = self.contact_manager {
+            return Ok(manager.clone());
+        }
 
-        let topic_sender = state
-            .topic_sender
+        info!("Initializing contact manager");
+
+        // Ensure gossip is initialized
+        let gossip = self.ensure_gossip().await?;
+
+        // Ensure identity is initialized
+        self.init_identity()?;
+
+        // Contacts learn our endpoint from our device list, if we keep one
+        if let Err(e) = self.refresh_own_device_endpoint() {
+            warn!("Failed to record our endpoint in the device list: {}", e);
+        }
+
+        // Ensure our own profile is signed and pinned (required for announcements to work)
+        // This must be done BEFORE we borrow keypair to avoid borrow checker issues
+        // This is idempotent - if already signed, it just updates the pin
+        if let Err(e) = self.sign_and_pin_own_profile() {
+            warn!("Failed to sign and pin own profile: {}", e);
+        } else {
+            debug!("Own profile signed and pinned for contact exchange announcements");
+        }
+
+        let keypair = self
+            .identity
             .as_ref()
-            .ok_or_else(|| SyncError::Gossip("Realm is not syncing".to_string()))?;
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?;
+        let did = Did::from_public_key(&keypair.public_key());
 
-        // Get identity (must be initialized)
-        let keypair = self.identity.as_ref().ok_or_else(|| {
-            SyncError::Identity("Identity not initialized. Call init_identity() first.".to_string())
-        })?;
+        // Clone gossip for the profile announcer before moving into ContactManager
+        let gossip_for_announcer = gossip.clone();
 
-        let sender_did = Did::from_public_key(&keypair.public_key()).to_string();
+        // Get the shared active_topics - this was created when gossip was initialized
+        // and is shared with ContactProtocolHandler for coordinating contact topic senders
+        let active_topics = self.active_contact_topics.clone().ok_or_else(|| {
+            SyncError::NotReady(
+                "active_contact_topics not initialized - gossip may have been created without contact deps".to_string()
+            )
+        })?;
 
-        // Create signing function
-        let sign_fn = |data: &[u8]| -> Vec<u8> { keypair.sign(data).to_bytes() };
+        // Create contact manager (shares event_tx and active_topics with ContactProtocolHandler)
+        let manager = Arc::new(ContactManager::new(
+            gossip,
+            Arc::new(keypair.clone()),
+            did,
+            Arc::new(self.storage.clone()),
+            self.contact_event_tx.clone(),
+            active_topics,
+            Some(self.packet_event_buffer.clone()),
+            self.packet_gate.clone(),
+        ));
 
-        // Seal the message (encrypt + sign)
-        let envelope = SyncEnvelope::seal(&message, &sender_did, &state.realm_key, sign_fn)?;
+        // Start the auto-accept task for our own invites
+        manager.clone().start_auto_accept_task();
 
-        // Serialize envelope
-        let envelope_bytes = envelope.to_bytes()?;
+        // Start the contact subscription task (ensures we subscribe to contact topics for RECEIVING)
+        // The handler adds sender to active_topics for sending, but this task handles receiving.
+        // This fixes the bug where handler's listener consumed Packet messages before we could process them.
+        manager.clone().start_contact_subscription_task();
 
-        debug!(
-            %realm_id,
-            sender = %sender_did,
-            bytes = envelope_bytes.len(),
-            "Broadcasting sync envelope"
+        // Start the contact accepted profile announcer
+        // This broadcasts our profile when a contact is accepted, triggering auto-pinning
+        Self::start_contact_accepted_profile_announcer(
+            self.contact_event_tx.subscribe(),
+            gossip_for_announcer,
+            self.storage.clone(),
         );
 
-        // Broadcast via topic sender
-        topic_sender.broadcast(envelope_bytes).await?;
+        // Start profile sync listener to process incoming announcements (enables auto-pinning)
+        // This uses empty bootstrap peers since we discover peers through contact exchange
+        if let Err(e) = self.start_profile_sync(vec![]).await {
+            warn!("Failed to start profile sync listener: {}", e);
+        }
 
-        Ok(())
+        self.contact_manager = Some(manager.clone());
+        Ok(manager)
     }
 
-    /// Process incoming sync messages from gossip
-    ///
-    /// Verifies the signature and decrypts the envelope, returning the
-    /// inner `SyncMessage` for processing.
-    ///
-    /// # Arguments
-    ///
-    /// * `realm_id` - The realm this message belongs to
-    /// * `envelope_bytes` - The raw bytes received from gossip
-    ///
-    /// # Returns
-    ///
-    /// Returns `Ok(Some(message))` if the envelope is valid.
-    /// Returns `Ok(None)` if signature verification or decryption failed.
-    /// Returns `Err` for other errors (realm not found, etc).
+    /// Start a background task that announces our profile when a contact is accepted.
     ///
-    /// # Errors
+    /// When the ContactAccepted event fires (either direction - we accept or they accept us),
+    /// this broadcasts our signed profile to the global profile topic. The peer's profile
+    /// sync listener will receive it and auto-pin because we're now contacts.
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub fn handle_incoming(
-        &self,
-        realm_id: &RealmId,
-        envelope_bytes: &[u8],
-    ) -> Result<Option<SyncMessage>, SyncError> {
-        // Get realm state
-        let state = self
-            .realms
-            .get(realm_id)
-            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+    /// This enables mutual profile pinning after contact exchange completes.
+    fn start_contact_accepted_profile_announcer(
+        mut event_rx: broadcast::Receiver<ContactEvent>,
+        gossip: Arc<GossipSync>,
+        storage: Storage,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                match event_rx.recv().await {
+                    Ok(ContactEvent::ContactAccepted { contact }) => {
+                        info!(peer_did = %contact.peer_did, "Contact accepted, announcing profile for auto-pinning");
 
-        // Deserialize envelope
-        let envelope = match SyncEnvelope::from_bytes(envelope_bytes) {
-            Ok(env) => env,
-            Err(e) => {
-                warn!(error = ?e, "Failed to deserialize envelope");
-                return Ok(None);
-            }
-        };
+                        // Load our signed profile from storage
+                        match storage.get_own_pinned_profile() {
+                            Ok(Some(pin)) => {
+                                let signed_profile = pin.signed_profile;
 
-        debug!(
-            %realm_id,
-            sender = %envelope.sender(),
-            version = envelope.version(),
-            "Processing incoming envelope"
-        );
+                                // Subscribe to the global profile topic and broadcast
+                                let topic_id = crate::sync::global_profile_topic();
+                                match gossip.subscribe_split(topic_id, vec![]).await {
+                                    Ok((sender, _receiver)) => {
+                                        // Create announcement (no avatar ticket needed for basic pinning)
+                                        let announcement = crate::sync::ProfileGossipMessage::announce(
+                                            signed_profile,
+                                            None,
+                                        );
+                                        match announcement.to_bytes() {
+                                            Ok(bytes) => {
+                                                if let Err(e) = sender.broadcast(bytes).await {
+                                                    warn!(
+                                                        peer_did = %contact.peer_did,
+                                                        error = %e,
+                                                        "Failed to broadcast profile after contact accepted"
+                                                    );
+                                                } else {
+                                                    info!(
+                                                        peer_did = %contact.peer_did,
+                                                        "Profile announced after contact accepted - peer should auto-pin"
+                                                    );
+                                                }
+                                            }
+                                            Err(e) => {
+                                                warn!(
+                                                    error = %e,
+                                                    "Failed to serialize profile announcement"
+                                                );
+                                            }
+                                        }
+                                    }
+                                    Err(e) => {
+                                        warn!(
+                                            error = %e,
+                                            "Failed to subscribe to profile topic for announcement"
+                                        );
+                                    }
+                                }
+                            }
+                            Ok(None) => {
+                                warn!("No own profile found, cannot announce after contact accepted");
+                            }
+                            Err(e) => {
+                                warn!(error = %e, "Failed to load own profile for announcement");
+                            }
+                        }
+                    }
+                    Ok(_) => {} // Ignore other contact events
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!("Contact event receiver lagged by {} messages", n);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        debug!("Contact event channel closed, stopping profile announcer");
+                        break;
+                    }
+                }
+            }
+        });
 
-        // Create verification function that looks up sender's public key from pinned profiles
-        let verify_fn = Self::make_verify_fn(&self.storage);
+        info!("Contact accepted profile announcer started");
+    }
 
-        // Open the envelope (verify signature + decrypt)
-        match envelope.open(&state.realm_key, verify_fn) {
-            Ok(message) => {
-                debug!(
-                    %realm_id,
-                    message_type = ?std::mem::discriminant(&message),
-                    "Successfully opened envelope"
-                );
-                Ok(Some(message))
-            }
-            Err(SyncError::SignatureInvalid(msg)) => {
-                warn!(%realm_id, error = %msg, "Signature verification failed");
-                Ok(None)
-            }
-            Err(SyncError::DecryptionFailed(msg)) => {
-                warn!(%realm_id, error = %msg, "Decryption failed");
-                Ok(None)
+    /// Start the background reconnection scheduler
+    ///
+    /// Every `interval`, plus up to a tenth of it as random jitter, offline
+    /// and unknown peers whose backoff has elapsed are retried. Backoff is
+    /// tracked per peer and capped at an hour, so a peer that is gone for
+    /// good is only tried hourly. Like the keepalive task, it holds only weak
+    /// references between rounds and exits once the engine shuts down.
+    fn start_reconnect_scheduler(
+        gossip: std::sync::Weak<GossipSync>,
+        peer_registry: std::sync::Weak<PeerRegistry>,
+        interval: std::time::Duration,
+    ) {
+        tokio::spawn(async move {
+            let max_jitter_ms = (interval.as_millis() as u64 / 10).max(1);
+            loop {
+                let jitter_ms: u64 = rand::rng().random_range(0..max_jitter_ms);
+                tokio::time::sleep(interval + std::time::Duration::from_millis(jitter_ms)).await;
+                let (Some(gossip), Some(peer_registry)) = (gossip.upgrade(), peer_registry.upgrade())
+                else {
+                    break;
+                };
+                match Self::reconnect_inactive_peers(&gossip, &peer_registry).await {
+                    Ok(result) => debug!(
+                        attempted = result.peers_attempted,
+                        succeeded = result.peers_succeeded,
+                        skipped = result.peers_skipped_backoff,
+                        jitter_ms,
+                        "Scheduled reconnection round finished"
+                    ),
+                    Err(e) => warn!(error = %e, "Peer reconnection error"),
+                }
             }
-            Err(e) => Err(e),
-        }
+            debug!("Reconnection scheduler stopped");
+        });
+        info!(?interval, "Peer reconnection scheduler started");
     }
 
-    /// Sync a realm's current state to peers
-    ///
-    /// Broadcasts an `Announce` message with the current document heads,
-    /// allowing peers to determine if they need to sync.
-    ///
-    /// # Errors
+    /// A peer's connection history, oldest first
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    /// Returns `SyncError::Gossip` if the realm is not syncing.
-    pub async fn sync_realm_state(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
-        // Get the document heads
-        let heads = {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-
-            state
-                .doc
-                .heads()
-                .into_iter()
-                .map(|h| h.0.to_vec())
-                .collect::<Vec<_>>()
-        };
-
-        // Create announce message with our address for peer discovery
-        let sender_addr = self
-            .gossip
-            .as_ref()
-            .map(|g| NodeAddrBytes::from_endpoint_addr(&g.endpoint_addr()));
-
-        let message = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads,
-            sender_addr,
-        };
-
-        // Broadcast it
-        self.broadcast_sync(realm_id, message).await
+    /// Every gossip NeighborUp/NeighborDown on a realm topic is recorded
+    /// with its time and path type, keeping the last
+    /// [`MAX_CONNECTION_EVENTS`](crate::peers::MAX_CONNECTION_EVENTS) per
+    /// peer. Useful for spotting flapping connections.
+    pub fn peer_connection_log(&self, peer_id: &iroh::PublicKey) -> Vec<ConnectionEvent> {
+        self.peer_registry.connection_log(peer_id).unwrap_or_else(|e| {
+            warn!(?peer_id, error = %e, "Failed to read connection log");
+            Vec::new()
+        })
     }
 
-    /// Broadcast incremental changes for a realm
+    /// Add a peer from an address shared out of band
     ///
-    /// Generates an incremental sync message from the document and broadcasts
-    /// it to all peers. This is more efficient than sending the full document.
+    /// The peer is recorded in the registry with its address (keeping any
+    /// existing nickname and metrics), and the address is handed to iroh
+    /// when networking is active, so the peer can be dialed without
+    /// discovery. Reconnection rounds re-register stored addresses.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    /// Returns `SyncError::Gossip` if the realm is not syncing.
-    pub async fn broadcast_changes(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
-        // Generate incremental changes
-        let data = {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+    /// Returns `SyncError::InvalidInvite` if the address is malformed.
+    pub fn add_peer_node_addr(&self, node_addr: &NodeAddrBytes) -> Result<iroh::PublicKey, SyncError> {
+        let endpoint_addr = node_addr.to_endpoint_addr()?;
+        let peer_id = endpoint_addr.id;
 
-            state.doc.generate_sync_message()
+        let peer_info = match self.peer_registry.get(&peer_id)? {
+            Some(existing) => existing.with_node_addr(node_addr.clone()),
+            None => PeerInfo::new(peer_id, PeerSource::FromInvite).with_node_addr(node_addr.clone()),
         };
+        self.peer_registry.add_or_update(&peer_info)?;
 
-        // Only broadcast if there are changes
-        if data.is_empty() {
-            debug!(%realm_id, "No changes to broadcast");
-            return Ok(());
+        if let Some(ref gossip) = self.gossip {
+            gossip.add_peer_addr(endpoint_addr);
         }
-
-        // Create changes message
-        let message = SyncMessage::Changes {
-            realm_id: realm_id.clone(),
-            data,
-        };
-
-        // Broadcast it
-        self.broadcast_sync(realm_id, message).await
+        info!(?peer_id, addrs = node_addr.direct_addresses.len(), "Added peer from shared address");
+        Ok(peer_id)
     }
 
-    /// Broadcast pre-captured changes to peers
+    /// Attempt to reconnect to all inactive peers
     ///
-    /// Use this when you've already captured incremental changes before saving
-    /// (since save() resets the incremental checkpoint).
+    /// This iterates through all peers with status Offline or Unknown and
+    /// attempts to establish a connection, skipping peers whose Fibonacci
+    /// backoff has not elapsed yet (see [`PeerInfo::backoff`]). Connection
+    /// attempts and results are tracked to calculate success rates and adjust
+    /// backoff.
     ///
-    /// # Errors
+    /// The scheduler started by [`start_networking`](Self::start_networking)
+    /// calls this periodically; it can also be triggered by hand.
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    /// Returns `SyncError::Gossip` if the realm is not syncing.
-    pub async fn broadcast_changes_with_data(
-        &mut self,
-        realm_id: &RealmId,
-        _data: Vec<u8>,
-    ) -> Result<(), SyncError> {
-        // Always broadcast the FULL document instead of incremental changes.
-        // This ensures peers with empty/different docs can properly sync.
-        // Incremental changes only work when docs share a common history.
-        let state = self
-            .realms
-            .get_mut(realm_id)
-            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+    /// # Returns
+    ///
+    /// A `StartupSyncResult` with the attempted, succeeded and skipped counts.
+    pub async fn attempt_reconnect_inactive_peers(&self) -> Result<StartupSyncResult, SyncError> {
+        let Some(ref gossip) = self.gossip else {
+            debug!("Gossip not initialized, skipping peer reconnection");
+            return Ok(StartupSyncResult::default());
+        };
+        Self::reconnect_inactive_peers(gossip, &self.peer_registry).await
+    }
 
-        let full_doc = state.doc.save();
+    /// Retry every inactive peer whose backoff has elapsed
+    async fn reconnect_inactive_peers(
+        gossip: &GossipSync,
+        peer_registry: &PeerRegistry,
+    ) -> Result<StartupSyncResult, SyncError> {
+        let mut result = StartupSyncResult::default();
+        let inactive = peer_registry.list_inactive()?;
 
-        // Create sync response with full document
-        let message = SyncMessage::SyncResponse {
-            realm_id: realm_id.clone(),
-            document: full_doc,
-        };
+        if inactive.is_empty() {
+            debug!("No inactive peers to reconnect");
+            return Ok(result);
+        }
 
-        debug!(%realm_id, "Broadcasting full document for sync");
+        debug!(
+            "Checking {} inactive peers for reconnection (with exponential backoff)",
+            inactive.len()
+        );
 
-        // Broadcast it
-        self.broadcast_sync(realm_id, message).await
-    }
+        for mut peer_info in inactive {
+            let peer_id = peer_info.public_key();
 
-    /// Apply incoming changes from a peer
-    ///
-    /// Applies the changes from a `SyncMessage::Changes` to the local document
-    /// and saves the realm.
-    ///
-    /// # Errors
-    ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    /// Returns `SyncError::Serialization` if the changes are invalid.
-    pub async fn apply_incoming_changes(
-        &mut self,
-        realm_id: &RealmId,
-        data: &[u8],
-    ) -> Result<(), SyncError> {
-        {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+            if !peer_info.should_retry_now() {
+                result.peers_skipped_backoff += 1;
+                debug!(
+                    ?peer_id,
+                    backoff_secs = peer_info.backoff_delay(),
+                    "Skipping peer - backoff not elapsed"
+                );
+                continue;
+            }
 
-            state.doc.apply_sync_message(data)?;
+            // Addresses shared out of band let us dial without discovery
+            if let Some(addr) = peer_info.node_addr.as_ref().and_then(|a| a.to_endpoint_addr().ok()) {
+                gossip.add_peer_addr(addr);
+            }
+
+            result.peers_attempted += 1;
+            peer_info.record_attempt();
+            debug!(
+                ?peer_id,
+                attempt_number = peer_info.connection_attempts,
+                "Attempting to reconnect"
+            );
+
+            // Same 10 second cap as startup sync, so an unreachable peer cannot
+            // stall the round
+            match tokio::time::timeout(
+                std::time::Duration::from_secs(10),
+                gossip.endpoint().connect(peer_id, iroh_gossip::net::GOSSIP_ALPN),
+            )
+            .await
+            {
+                Ok(Ok(_conn)) => {
+                    peer_info.record_success();
+                    result.peers_succeeded += 1;
+                    info!(
+                        ?peer_id,
+                        success_rate = format!("{:.1}%", peer_info.success_rate() * 100.0),
+                        "Successfully reconnected to peer"
+                    );
+                }
+                Ok(Err(e)) => {
+                    peer_info.record_failure();
+                    debug!(
+                        ?peer_id,
+                        error = ?e,
+                        next_retry_in_secs = peer_info.backoff_delay(),
+                        "Failed to reconnect, will retry after backoff"
+                    );
+                }
+                Err(_) => {
+                    peer_info.record_failure();
+                    debug!(
+                        ?peer_id,
+                        next_retry_in_secs = peer_info.backoff_delay(),
+                        "Reconnection timed out, will retry after backoff"
+                    );
+                }
+            }
+
+            // Save updated peer info (with metrics and status)
+            peer_registry.add_or_update(&peer_info)?;
         }
 
-        // Save the updated document
-        self.save_realm(realm_id).await?;
+        if result.peers_attempted > 0 {
+            info!(
+                "Reconnection summary: attempted={}, succeeded={}, skipped={} (backoff)",
+                result.peers_attempted, result.peers_succeeded, result.peers_skipped_backoff
+            );
+        }
 
-        debug!(%realm_id, bytes = data.len(), "Applied incoming changes");
-        Ok(())
+        Ok(result)
     }
 
-    /// Apply a full document from a peer
+    /// Perform immediate startup sync with all known peers
     ///
-    /// Loads the full document state and merges it with the local document.
+    /// This should be called after engine initialization to establish connections
+    /// with known peers as quickly as possible while avoiding the "simultaneous
+    /// wake-up problem" where two peers starting at the same time both fail to
+    /// connect.
+    ///
+    /// ## Behavior
+    ///
+    /// 1. **Initialize gossip** - Ensures we're listening for incoming connections
+    /// 2. **Apply jitter** - Random delay (up to
+    ///    [`GossipConfig::startup_jitter_max_ms`], 2 seconds by default) to
+    ///    avoid thundering herd
+    /// 3. **Announce presence** - Broadcasts our profile to the global topic
+    /// 4. **Connect to peers** - Attempts to connect to all known peers, respecting
+    ///    Fibonacci backoff for peers that have failed recently
+    ///
+    /// ## The Simultaneous Wake-up Problem
+    ///
+    /// When peers A and B both start at the same time:
+    /// - A tries to connect to B → B not ready → fails → backs off
+    /// - B tries to connect to A → A not ready → fails → backs off
+    ///
+    /// The jitter + listen-first strategy solves this:
+    /// - Both start listening immediately
+    /// - Random jitter means one will attempt outbound first
+    /// - The other is already listening and accepts the connection
+    ///
+    /// ## Prioritization
+    ///
+    /// Peers are sorted by priority:
+    /// 1. Contacts (mutually accepted) first
+    /// 2. Then by last_seen (most recently active first)
+    ///
+    /// # Returns
+    ///
+    /// A `StartupSyncResult` containing statistics about the sync attempt.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    /// Returns `SyncError::Serialization` if the document is invalid.
-    pub async fn apply_full_document(
-        &mut self,
-        realm_id: &RealmId,
-        document_bytes: &[u8],
-    ) -> Result<(), SyncError> {
-        {
-            let state = self
-                .realms
-                .get_mut(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+    /// Returns `SyncError::NotReady` if gossip cannot be initialized.
+    pub async fn startup_sync(&mut self) -> Result<StartupSyncResult, SyncError> {
+        self.startup_sync_with_progress(None).await
+    }
 
-            // Load the remote document
-            let mut remote_doc = RealmDoc::load(document_bytes)?;
+    /// Start networking and run [`startup_sync`](Self::startup_sync),
+    /// reporting each peer's outcome on `sink` as it happens
+    ///
+    /// Meant for a splash screen during a long startup. Events are sent in
+    /// order and the returned result equals the events summed with
+    /// [`StartupSyncResult::record`]. Sending waits for room in the channel,
+    /// so keep receiving until it closes; if the receiver is dropped,
+    /// startup carries on without reporting.
+    pub async fn start_networking_with_progress(
+        &mut self,
+        sink: tokio::sync::mpsc::Sender<StartupProgress>,
+    ) -> Result<StartupSyncResult, SyncError> {
+        self.start_networking().await?;
+        self.startup_sync_with_progress(Some(&sink)).await
+    }
 
-            // Merge into our document
-            state.doc.merge(&mut remote_doc)?;
+    /// Count a startup sync step and pass it on to the progress sink, if any
+    async fn report_startup(
+        sink: Option<&tokio::sync::mpsc::Sender<StartupProgress>>,
+        result: &mut StartupSyncResult,
+        progress: StartupProgress,
+    ) {
+        result.record(&progress);
+        if let Some(sink) = sink {
+            // A closed receiver just means nobody is watching any more
+            let _ = sink.send(progress).await;
         }
+    }
 
-        // Save the merged document
-        self.save_realm(realm_id).await?;
+    async fn startup_sync_with_progress(
+        &mut self,
+        sink: Option<&tokio::sync::mpsc::Sender<StartupProgress>>,
+    ) -> Result<StartupSyncResult, SyncError> {
+        info!("Starting startup sync...");
 
-        debug!(%realm_id, bytes = document_bytes.len(), "Applied full document");
-        Ok(())
-    }
+        // 1. Ensure gossip is initialized (starts listening for incoming connections)
+        self.ensure_gossip().await?;
 
-    /// Create a verification function for envelope signatures
-    ///
-    /// Verifies hybrid Ed25519 + ML-DSA-65 signatures against the sender's
-    /// public key from their pinned profile. Unknown senders (those without
-    /// a pinned profile) are rejected.
-    fn make_verify_fn(storage: &Storage) -> impl Fn(&str, &[u8], &[u8]) -> bool + '_ {
-        move |sender_did: &str, data: &[u8], sig_bytes: &[u8]| -> bool {
-            use crate::identity::HybridSignature;
-            use tracing::{debug, warn};
+        // 2. Initialize profile sync (sets profile_gossip_sender so announce_profile works)
+        // This must be called before announce_profile() or profile broadcasts will fail
+        if self.identity.is_some() {
+            if let Err(e) = self.start_profile_sync(vec![]).await {
+                warn!(error = %e, "Failed to start profile sync (non-fatal)");
+            }
+        }
 
-            // 1. Deserialize the signature from bytes
-            let signature = match HybridSignature::from_bytes(sig_bytes) {
-                Ok(sig) => sig,
-                Err(e) => {
-                    warn!(sender = %sender_did, error = ?e, "Failed to parse signature");
-                    return false;
-                }
-            };
+        // 3. Generate random jitter to avoid thundering herd
+        // Kept short by default to not delay app responsiveness noticeably
+        let max_jitter_ms = self.gossip_config.effective_startup_jitter_max_ms();
+        let jitter_ms: u64 = if max_jitter_ms == 0 {
+            0
+        } else {
+            rand::rng().random_range(0..=max_jitter_ms)
+        };
+        debug!(jitter_ms, max_jitter_ms, "Applying startup jitter");
+        tokio::time::sleep(std::time::Duration::from_millis(jitter_ms)).await;
 
-            // 2. Look up sender's public key from pinned profiles
-            let public_key = match storage.load_pinned_profile(sender_did) {
-                Ok(Some(pin)) => pin.signed_profile.public_key,
-                Ok(None) => {
-                    debug!(sender = %sender_did, "Unknown sender - no pinned profile");
-                    return false;
+        // 4. Initialize contact manager and reconnect to contact profile topics FIRST
+        // This must happen BEFORE announce_profile() so that:
+        // a) We're subscribed to receive profile updates from contacts
+        // b) contact_manager exists so announce_profile() can broadcast to contact topics
+        if self.identity.is_some() {
+            match self.ensure_contact_manager().await {
+                Ok(manager) => {
+                    if let Err(e) = manager.reconnect_contacts().await {
+                        warn!(error = %e, "Failed to reconnect contacts (non-fatal)");
+                    }
                 }
                 Err(e) => {
-                    warn!(sender = %sender_did, error = ?e, "Failed to load pinned profile");
-                    return false;
+                    warn!(error = %e, "Failed to initialize contact manager at startup (non-fatal)");
                 }
-            };
-
-            // 3. Verify BOTH Ed25519 and ML-DSA-65 signatures
-            let valid = public_key.verify(data, &signature);
-            if !valid {
-                warn!(sender = %sender_did, "Signature verification failed");
             }
-            valid
         }
-    }
-
-    // ═══════════════════════════════════════════════════════════════════════
-    // Invite Operations
-    // ═══════════════════════════════════════════════════════════════════════
 
-    /// Generate an invite ticket for a realm
-    ///
-    /// Creates an invite containing the realm's encryption key and this
-    /// node as a bootstrap peer.
-    ///
-    /// # Errors
-    ///
-    /// Returns `SyncError::RealmNotFound` if the realm is not open.
-    pub async fn generate_invite(&mut self, realm_id: &RealmId) -> Result<InviteTicket, SyncError> {
-        // Check if this is the Private realm
-        if let Ok(Some(info)) = self.storage.load_realm(realm_id) {
-            if is_private_realm_name(&info.name) {
-                return Err(SyncError::PrivateRealmOperation(
-                    "Cannot generate invite for Private realm".to_string(),
-                ));
+        // 5. Announce presence on global profile topic AND contact topics (if we have a profile)
+        // This now works correctly because contact_manager was initialized above
+        if self.identity.is_some() {
+            match self.announce_profile(None).await {
+                Ok(()) => info!("Presence announced on profile and contact topics"),
+                Err(e) => {
+                    // Non-fatal - we can still connect to peers without announcing
+                    warn!(error = %e, "Failed to announce presence (non-fatal)");
+                }
             }
+        } else {
+            debug!("No identity initialized, skipping presence announcement");
         }
 
-        // Ensure realm is open
-        if !self.realms.contains_key(realm_id) {
-            self.open_realm(realm_id).await?;
-        }
-
-        // Get the realm key (copy it to avoid borrow issues)
-        let realm_key = {
-            let state = self
-                .realms
-                .get(realm_id)
-                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
-            state.realm_key
-        };
-
-        // Get realm name for the invite
-        let realm_name = self.storage.load_realm(realm_id)?.map(|info| info.name);
+        // 6. Get all peers and sort by priority (contacts first, then by last_seen)
+        let mut all_peers = self.storage.list_peers()?;
+        all_peers.sort_by(|a, b| {
+            // Contacts first
+            match (a.is_contact(), b.is_contact()) {
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                _ => {
+                    // Then by last_seen (most recent first)
+                    b.last_seen.cmp(&a.last_seen)
+                }
+            }
+        });
 
-        // Ensure gossip is initialized
-        let gossip = self.ensure_gossip().await?;
+        // Skip if no gossip or no peers
+        let mut result = StartupSyncResult::default();
+        let Some(ref gossip) = self.gossip else {
+            warn!("Gossip not initialized after ensure_gossip (unexpected)");
+            let started = StartupProgress::Started {
+                peer_count: 0,
+                jitter_delay_ms: jitter_ms,
+            };
+            Self::report_startup(sink, &mut result, started).await;
+            return Ok(result);
+        };
 
-        // Generate invite (include realm name so joiners see it)
-        let invite = gossip.generate_invite(realm_id, realm_key, realm_name.as_deref())?;
+        let started = StartupProgress::Started {
+            peer_count: all_peers.len(),
+            jitter_delay_ms: jitter_ms,
+        };
+        Self::report_startup(sink, &mut result, started).await;
 
-        // Mark realm as shared
-        if let Ok(Some(mut info)) = self.storage.load_realm(realm_id) {
-            if !info.is_shared {
-                info.is_shared = true;
-                self.storage.save_realm(&info)?;
+        if all_peers.is_empty() {
+            debug!("No known peers for startup sync");
+            return Ok(result);
+        }
+
+        info!(
+            peer_count = all_peers.len(),
+            "Attempting startup sync with known peers"
+        );
+
+        // 5. Attempt connections with Fibonacci backoff
+        for mut peer in all_peers {
+            let peer_id = peer.public_key();
+            let peer_key = peer_id.to_string();
+
+            // Skip contacts - they're already connected via gossip topic subscription
+            // from reconnect_contacts() above. Creating a second direct connection here
+            // can interfere with the gossip mesh and cause messages to not be delivered.
+            if peer.is_contact() {
+                // Still record as success since gossip connection is established
+                let succeeded = StartupProgress::Succeeded { peer_id: peer_key };
+                Self::report_startup(sink, &mut result, succeeded).await;
+                debug!(
+                    ?peer_id,
+                    "Skipping contact - already connected via gossip topic"
+                );
+                continue;
+            }
+
+            // Check Fibonacci backoff
+            if !peer.should_retry_now() {
+                let skipped = StartupProgress::SkippedBackoff { peer_id: peer_key };
+                Self::report_startup(sink, &mut result, skipped).await;
+                debug!(
+                    ?peer_id,
+                    backoff_secs = peer.backoff_delay(),
+                    "Skipping peer - backoff not elapsed"
+                );
+                continue;
+            }
+
+            let attempting = StartupProgress::Attempting { peer_id: peer_key.clone() };
+            Self::report_startup(sink, &mut result, attempting).await;
+            peer.record_attempt();
+            debug!(
+                ?peer_id,
+                is_contact = peer.is_contact(),
+                attempt_number = peer.connection_attempts,
+                "Attempting startup connection"
+            );
+
+            // Try to connect with 10 second timeout
+            match tokio::time::timeout(
+                std::time::Duration::from_secs(10),
+                gossip.endpoint().connect(peer_id, iroh_gossip::net::GOSSIP_ALPN),
+            )
+            .await
+            {
+                Ok(Ok(_conn)) => {
+                    peer.record_success();
+                    info!(
+                        ?peer_id,
+                        is_contact = peer.is_contact(),
+                        success_rate = format!("{:.1}%", peer.success_rate() * 100.0),
+                        "Connected on startup"
+                    );
+                    let succeeded = StartupProgress::Succeeded { peer_id: peer_key };
+                    Self::report_startup(sink, &mut result, succeeded).await;
+                }
+                Ok(Err(e)) => {
+                    peer.record_failure();
+                    debug!(
+                        ?peer_id,
+                        error = ?e,
+                        next_retry_in_secs = peer.backoff_delay(),
+                        "Failed to connect on startup"
+                    );
+                    let failed = StartupProgress::Failed { peer_id: peer_key };
+                    Self::report_startup(sink, &mut result, failed).await;
+                }
+                Err(_) => {
+                    peer.record_failure();
+                    debug!(
+                        ?peer_id,
+                        next_retry_in_secs = peer.backoff_delay(),
+                        "Connection timed out on startup"
+                    );
+                    let failed = StartupProgress::Failed { peer_id: peer_key };
+                    Self::report_startup(sink, &mut result, failed).await;
+                }
             }
+
+            // Save updated peer metrics (with connection attempt results)
+            self.storage.save_peer(&peer)?;
         }
 
-        // Auto-start sync when sharing a realm
-        // This ensures the creator can send/receive sync messages
-        if !self.is_realm_syncing(realm_id) {
-            self.start_sync(realm_id).await?;
+        info!(
+            attempted = result.peers_attempted,
+            succeeded = result.peers_succeeded,
+            skipped = result.peers_skipped_backoff,
+            jitter_ms = result.jitter_delay_ms,
+            "Startup sync complete"
+        );
+
+        Ok(result)
+    }
+
+    /// Manually trigger a sync to refresh peer information and broadcast profile changes.
+    ///
+    /// This is a user-initiated sync that:
+    /// 1. Broadcasts our current profile to all contacts (announces changes)
+    /// 2. Reconnects to contact profile topics (to receive their updates)
+    ///
+    /// Unlike `startup_sync()`, this does not include jitter delays or extensive
+    /// reconnection attempts - it's designed for immediate user feedback.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of contacts reconnected to.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if profile announcement fails (non-fatal errors are logged).
+    pub async fn manual_sync(&mut self) -> Result<usize, SyncError> {
+        info!("Manual sync triggered by user");
+
+        // Count contacts from storage (regardless of reconnection success)
+        let contacts_count = self.storage.list_contacts().map(|c| c.len()).unwrap_or(0);
+
+        // 1. Broadcast our current profile to announce any changes
+        if self.identity.is_some() {
+            match self.announce_profile(None).await {
+                Ok(()) => {
+                    info!("Profile broadcast successful");
+                }
+                Err(e) => {
+                    // Log but don't fail - we can still reconnect to contacts
+                    warn!(error = %e, "Failed to broadcast profile during manual sync");
+                }
+            }
         }
 
-        info!(%realm_id, "Invite generated and sync started");
-        Ok(invite)
+        // 2. Reconnect to contact profile topics to receive their updates
+        // Use ensure_contact_manager to initialize if needed (it's lazy-loaded)
+        if self.identity.is_some() {
+            match self.ensure_contact_manager().await {
+                Ok(manager) => {
+                    if let Err(e) = manager.reconnect_contacts().await {
+                        warn!(error = %e, "Failed to reconnect contacts during manual sync");
+                    } else {
+                        info!(contacts = contacts_count, "Reconnected to contact topics");
+                    }
+                }
+                Err(e) => {
+                    warn!(error = %e, "Failed to initialize contact manager for sync");
+                }
+            }
+        }
+
+        info!(contacts_count, "Manual sync complete");
+        Ok(contacts_count)
     }
 
-    /// Join a realm via invite ticket
+    /// Start syncing a realm with peers via gossip
     ///
-    /// Connects to bootstrap peers, subscribes to the realm's gossip topic,
-    /// and saves the realm to storage.
+    /// Subscribes to the realm's gossip topic and begins sending/receiving
+    /// sync messages. This method can be called for multiple realms concurrently
+    /// without blocking.
     ///
     /// # Errors
     ///
-    /// Returns `SyncError::InvalidInvite` if the invite is expired.
-    pub async fn join_via_invite(&mut self, invite: &InviteTicket) -> Result<RealmId, SyncError> {
-        let realm_id = invite.realm_id();
-        info!(%realm_id, "Joining realm via invite");
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub async fn start_sync(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        // Ensure realm is open
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
 
-        // Debug: Log existing realms for troubleshooting
-        let existing_realms = self.storage.list_realms()?;
-        debug!(
-            joining_realm = %realm_id,
-            joining_base58 = %realm_id.to_base58(),
-            existing_count = existing_realms.len(),
-            existing_ids = ?existing_realms.iter().map(|r| r.id.to_base58()).collect::<Vec<_>>(),
-            "Checking membership before join"
-        );
+        self.start_sync_internal(realm_id).await
+    }
 
-        // Check if we already have this realm
-        if self.storage.load_realm(&realm_id)?.is_some() {
-            warn!(
-                %realm_id,
-                "Join rejected: realm already exists in storage"
-            );
-            return Err(SyncError::InvalidInvite(
-                "Already a member of this realm".into(),
-            ));
+    /// Internal sync starter that assumes the realm is already open.
+    /// Used by open_realm to avoid circular recursion.
+    async fn start_sync_internal(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        // Check if already syncing
+        if self.is_realm_syncing(realm_id) {
+            debug!(%realm_id, "Already syncing");
+            return Ok(());
         }
 
+        self.ensure_writable(realm_id)?;
+
+        info!(%realm_id, "Starting sync");
+
+        // Update status to Connecting
+        self.sync_status
+            .lock()
+            .unwrap()
+            .insert(realm_id.clone(), SyncStatus::Connecting);
+        let _ = self.event_tx.send(SyncEvent::StatusChanged {
+            realm_id: realm_id.clone(),
+            status: SyncStatus::Connecting,
+        });
+
         // Initialize gossip
         let gossip = self.ensure_gossip().await?;
 
-        // Join via invite using split API (receiver not wrapped in mutex)
-        let (sender, mut receiver) = gossip.join_via_invite_split(invite).await?;
-
-        // Spawn background listener task that owns the receiver directly
-        let listener_realm_id = realm_id.clone();
-        let sync_tx = self.sync_tx.clone();
-        let event_tx = self.event_tx.clone();
-        // Clone sync_status Arc for thread-safe peer counting in listener task
-        let sync_status = self.sync_status.clone();
-        // Clone peer_registry for tracking discovered peers
-        let peer_registry = self.peer_registry.clone();
+        // Get effective bootstrap peers (static from storage + online from registry)
+        let bootstrap_node_ids = self.get_effective_bootstrap_peers(realm_id)?;
 
-        tokio::spawn(async move {
-            debug!(%listener_realm_id, "Join sync listener task started");
-            let mut event_count = 0u64;
-            loop {
-                debug!(%listener_realm_id, event_count, "Join listener waiting for next event...");
-                match receiver.recv_event().await {
-                    Some(TopicEvent::Message(msg)) => {
-                        event_count += 1;
-                        let msg_bytes = msg.content.len();
+        // Add full address info to static discovery for faster reconnection
+        if let Some(realm_info) = self.storage.load_realm(realm_id)? {
+            for peer_bytes in &realm_info.bootstrap_peers {
+                match peer_bytes.to_endpoint_addr() {
+                    Ok(endpoint_addr) => {
                         debug!(
-                            %listener_realm_id,
-                            event_count,
-                            from = ?msg.from,
-                            bytes = msg_bytes,
-                            "Received sync message (joined)"
+                            %realm_id,
+                            peer = %endpoint_addr.id,
+                            relay = ?peer_bytes.relay_url,
+                            addrs = peer_bytes.direct_addresses.len(),
+                            "Adding saved bootstrap peer to static discovery"
+                        );
+                        gossip.add_peer_addr(endpoint_addr.clone());
+                    }
+                    Err(e) => {
+                        warn!(%realm_id, error = ?e, "Failed to convert saved peer address");
+                    }
+                }
+            }
+        }
+
+        if !bootstrap_node_ids.is_empty() {
+            info!(%realm_id, peer_count = bootstrap_node_ids.len(), "Using bootstrap peers (static + registry) for reconnection");
+        }
+
+        // Subscribe to topic using split API (receiver not wrapped in mutex)
+        let topic_id = TopicId::from_bytes(*realm_id.as_bytes());
+        let (sender, mut receiver) = gossip.subscribe_split(topic_id, bootstrap_node_ids).await?;
+
+        // Store sender for broadcasting
+        if let Some(state) = self.realms.get_mut(realm_id) {
+            state.topic_sender = Some(sender);
+        }
+
+        // Spawn background listener task that owns the receiver directly
+        let listener_realm_id = realm_id.clone();
+        let sync_tx = self.sync_tx.clone();
+        let event_tx = self.event_tx.clone();
+        // Clone sync_status Arc for thread-safe peer counting in listener task
+        let sync_status = self.sync_status.clone();
+        // Clone peer_registry for tracking discovered peers
+        let peer_registry = self.peer_registry.clone();
+        let listener_gossip = gossip.clone();
+        let topic_neighbors = self.topic_neighbors.clone();
+
+        let listener = tokio::spawn(async move {
+            debug!(%listener_realm_id, "Sync listener task started");
+            let mut event_count = 0u64;
+            loop {
+                debug!(%listener_realm_id, event_count, "Listener waiting for next event...");
+                match receiver.recv_event().await {
+                    Some(TopicEvent::Message(msg)) => {
+                        event_count += 1;
+                        debug!(
+                            %listener_realm_id,
+                            event_count,
+                            from = ?msg.from,
+                            bytes = msg.content.len(),
+                            "Received sync message"
                         );
                         // Send to channel for processing by main engine
-                        let send_result = sync_tx.send(SyncChannelMessage::IncomingData {
-                            realm_id: listener_realm_id.clone(),
-                            envelope_bytes: msg.content,
-                        });
-                        if send_result.is_err() {
+                        if sync_tx
+                            .send(SyncChannelMessage::IncomingData {
+                                realm_id: listener_realm_id.clone(),
+                                envelope_bytes: msg.content,
+                            })
+                            .is_err()
+                        {
                             debug!(%listener_realm_id, "Sync channel closed, stopping listener");
                             break;
                         }
-                        debug!(
-                            %listener_realm_id,
-                            bytes = msg_bytes,
-                            "Sent message to sync channel (joined)"
-                        );
                         // Notify listeners that data arrived
                         let _ = event_tx.send(SyncEvent::RealmChanged {
                             realm_id: listener_realm_id.clone(),
@@ -3682,18 +6128,35 @@ impl SyncEngine {
                     }
                     Some(TopicEvent::NeighborUp(peer)) => {
                         event_count += 1;
-                        debug!(%listener_realm_id, event_count, ?peer, "Peer connected (joined)");
+                        debug!(%listener_realm_id, event_count, ?peer, "Peer connected");
+                        topic_neighbors
+                            .lock()
+                            .unwrap()
+                            .entry(listener_realm_id.clone())
+                            .or_default()
+                            .insert(peer);
+
+                        let event = ConnectionEvent::now(ConnectionChange::Connected)
+                            .with_direct(listener_gossip.path_is_direct(peer))
+                            .with_realm(listener_realm_id.clone());
+                        if let Err(e) = peer_registry.record_connection_event(&peer, event) {
+                            debug!(?peer, error = ?e, "Failed to record connection event");
+                        }
 
                         // Record peer in registry
                         let peer_info =
                             PeerInfo::new(peer, PeerSource::FromRealm(listener_realm_id.clone()))
                                 .with_status(PeerStatus::Online);
                         if let Err(e) = peer_registry.add_or_update(&peer_info) {
-                            warn!(?peer, error = ?e, "Failed to record peer in registry (joined)");
+                            warn!(?peer, error = ?e, "Failed to record peer in registry");
                         } else {
                             // Also record the realm for this peer
                             let _ = peer_registry.add_peer_realm(&peer, &listener_realm_id);
-                            debug!(?peer, "Recorded peer in registry (joined)");
+                            debug!(?peer, "Recorded peer in registry");
+
+                            // Note: We don't automatically add gossip-discovered peers to
+                            // bootstrap_peers in storage, as we don't have their full EndpointAddr
+                            // at this point. The peer_registry tracks them for reconnection purposes.
                         }
 
                         // Update peer count in sync_status (thread-safe)
@@ -3718,6 +6181,7 @@ impl SyncEngine {
                         // This ensures offline changes are shared when peers reconnect
                         let _ = sync_tx.send(SyncChannelMessage::BroadcastRequest {
                             realm_id: listener_realm_id.clone(),
+                            neighbor: peer,
                         });
 
                         // Emit events
@@ -3734,13 +6198,24 @@ impl SyncEngine {
                     }
                     Some(TopicEvent::NeighborDown(peer)) => {
                         event_count += 1;
-                        debug!(%listener_realm_id, event_count, ?peer, "Peer disconnected (joined)");
+                        debug!(%listener_realm_id, event_count, ?peer, "Peer disconnected");
+                        if let Some(neighbors) =
+                            topic_neighbors.lock().unwrap().get_mut(&listener_realm_id)
+                        {
+                            neighbors.remove(&peer);
+                        }
+
+                        let event = ConnectionEvent::now(ConnectionChange::Disconnected)
+                            .with_realm(listener_realm_id.clone());
+                        if let Err(e) = peer_registry.record_connection_event(&peer, event) {
+                            debug!(?peer, error = ?e, "Failed to record connection event");
+                        }
 
                         // Mark peer as offline in registry
                         if let Err(e) = peer_registry.update_status(&peer, PeerStatus::Offline) {
-                            warn!(?peer, error = ?e, "Failed to update peer status to offline (joined)");
+                            warn!(?peer, error = ?e, "Failed to update peer status to offline");
                         } else {
-                            debug!(?peer, "Marked peer as offline in registry (joined)");
+                            debug!(?peer, "Marked peer as offline in registry");
                         }
 
                         // Update peer count in sync_status (thread-safe)
@@ -3769,45 +6244,87 @@ impl SyncEngine {
                         });
                     }
                     None => {
-                        debug!(%listener_realm_id, "Topic subscription closed");
+                        debug!(%listener_realm_id, event_count, "Topic subscription closed");
                         break;
                     }
                 }
             }
-            debug!(%listener_realm_id, "Join sync listener task ended");
+            debug!(%listener_realm_id, event_count, "Sync listener task ended");
         });
+        if let Some(state) = self.realms.get_mut(realm_id) {
+            state.listener = Some(listener.abort_handle());
+        }
 
-        // Create realm info with bootstrap peers for reconnection after restart
-        let info = RealmInfo {
-            id: realm_id.clone(),
-            name: invite
-                .realm_name
-                .clone()
-                .unwrap_or_else(|| "Shared Realm".to_string()),
-            is_shared: true,
-            created_at: chrono::Utc::now().timestamp(),
-            bootstrap_peers: invite.bootstrap_peers.clone(),
-        };
+        // Spawn periodic bootstrap reconnection task
+        // This handles the case where both peers start at the same time - the initial
+        // add_peer_addr calls fail because neither peer is ready yet. This task
+        // periodically re-tries the bootstrap peers until we connect.
+        if let Some(realm_info) = self.storage.load_realm(realm_id)? {
+            if !realm_info.bootstrap_peers.is_empty() {
+                let reconnect_realm_id = realm_id.clone();
+                let reconnect_gossip = gossip.clone();
+                let reconnect_sync_status = self.sync_status.clone();
+                let bootstrap_peers = realm_info.bootstrap_peers.clone();
 
-        // Create document
-        let mut doc = RealmDoc::new();
+                tokio::spawn(async move {
+                    use std::time::Duration;
 
-        // Save to storage
-        self.storage.save_realm(&info)?;
-        self.storage.save_realm_key(&realm_id, &invite.realm_key)?;
-        self.storage.save_document(&realm_id, &doc.save())?;
+                    let mut attempt = 0;
+                    const MAX_ATTEMPTS: u32 = 24; // 2 minutes of retries (5s * 24)
+                    const RETRY_INTERVAL: Duration = Duration::from_secs(5);
 
-        // Add to open realms
-        self.realms.insert(
-            realm_id.clone(),
-            RealmState {
-                doc,
-                topic_sender: Some(sender),
-                realm_key: invite.realm_key,
-            },
-        );
+                    loop {
+                        // Wait before retry (skip on first iteration to allow initial connect attempt)
+                        if attempt > 0 {
+                            tokio::time::sleep(RETRY_INTERVAL).await;
+                        }
+                        attempt += 1;
 
-        // Update sync status
+                        // Check if we already have peers - if so, we're done
+                        let current_peer_count = {
+                            let status_map = reconnect_sync_status.lock().unwrap();
+                            match status_map.get(&reconnect_realm_id) {
+                                Some(SyncStatus::Syncing { peer_count }) => *peer_count,
+                                _ => 0,
+                            }
+                        };
+
+                        if current_peer_count > 0 {
+                            debug!(
+                                %reconnect_realm_id,
+                                peer_count = current_peer_count,
+                                "Bootstrap reconnection task: peers connected, stopping"
+                            );
+                            break;
+                        }
+
+                        if attempt > MAX_ATTEMPTS {
+                            debug!(
+                                %reconnect_realm_id,
+                                attempt,
+                                "Bootstrap reconnection task: max attempts reached, stopping"
+                            );
+                            break;
+                        }
+
+                        // Re-add all bootstrap peer addresses
+                        for peer_bytes in &bootstrap_peers {
+                            if let Ok(endpoint_addr) = peer_bytes.to_endpoint_addr() {
+                                debug!(
+                                    %reconnect_realm_id,
+                                    attempt,
+                                    peer = %endpoint_addr.id,
+                                    "Bootstrap reconnection: re-adding peer address"
+                                );
+                                reconnect_gossip.add_peer_addr(endpoint_addr);
+                            }
+                        }
+                    }
+                });
+            }
+        }
+
+        // Update status to Syncing
         self.sync_status
             .lock()
             .unwrap()
@@ -3817,5329 +6334,10933 @@ impl SyncEngine {
             status: SyncStatus::Syncing { peer_count: 0 },
         });
 
-        debug!(%realm_id, "Joined realm and started sync");
+        debug!(%realm_id, "Sync started");
+
+        // We are a member of every realm we sync
+        if let Some(did) = self.did() {
+            self.note_realm_member(realm_id, did.as_ref());
+        }
+
+        // CRITICAL: Broadcast our FULL DOCUMENT when sync starts.
+        // This ensures that when peers reconnect after being offline, they exchange
+        // their complete document states and Automerge merges them automatically.
+        // Without this, peers would just sit waiting and never sync offline changes.
+        if let Err(e) = self.broadcast_changes_with_data(realm_id, vec![]).await {
+            warn!(
+                %realm_id,
+                error = ?e,
+                "Failed to broadcast initial document (will retry on next sync activity)"
+            );
+        } else {
+            info!(%realm_id, "Broadcast initial document to peers");
+        }
+
+        Ok(())
+    }
+
+    /// Stop syncing a realm
+    ///
+    /// Removes the realm from the gossip topic. The realm remains open.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub async fn stop_sync(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let state = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        if state.topic_sender.is_some() {
+            state.topic_sender = None;
+            // Dropping the receiver together with the sender leaves the gossip topic
+            if let Some(listener) = state.listener.take() {
+                listener.abort();
+            }
+            self.topic_neighbors.lock().unwrap().remove(realm_id);
+
+            // Update status to Idle
+            self.sync_status
+                .lock()
+                .unwrap()
+                .insert(realm_id.clone(), SyncStatus::Idle);
+            let _ = self.event_tx.send(SyncEvent::StatusChanged {
+                realm_id: realm_id.clone(),
+                status: SyncStatus::Idle,
+            });
+
+            info!(%realm_id, "Sync stopped");
+        } else {
+            debug!(%realm_id, "Not syncing");
+        }
+
+        Ok(())
+    }
+
+    /// Pause syncing a realm without leaving its gossip topic
+    ///
+    /// While paused nothing is broadcast for the realm and incoming sync
+    /// messages are dropped. Local edits keep landing in the document and
+    /// go out when [`Self::resume_sync`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub fn pause_sync(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let state = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        if !state.paused {
+            state.paused = true;
+            info!(%realm_id, "Sync paused");
+        }
+        Ok(())
+    }
+
+    /// Resume a realm paused with [`Self::pause_sync`]
+    ///
+    /// Broadcasts the full document so peers pick up edits made while paused,
+    /// and asks them for theirs since their messages were dropped meanwhile.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub async fn resume_sync(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let state = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        if !state.paused {
+            debug!(%realm_id, "Sync not paused");
+            return Ok(());
+        }
+        state.paused = false;
+        let syncing = state.topic_sender.is_some();
+        info!(%realm_id, "Sync resumed");
+
+        if syncing {
+            self.broadcast_changes_with_data(realm_id, vec![]).await?;
+            self.broadcast_sync(
+                realm_id,
+                SyncMessage::SyncRequest {
+                    realm_id: realm_id.clone(),
+                },
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Check if sync is paused for a realm
+    pub fn is_sync_paused(&self, realm_id: &RealmId) -> bool {
+        self.realms.get(realm_id).is_some_and(|s| s.paused)
+    }
+
+    /// Number of incoming envelopes or changes dropped as repeats
+    ///
+    /// Counts copies of data we had already applied, whether the same
+    /// envelope arrived over another path or a peer re-sealed an identical
+    /// payload. See [`GossipConfig::dedup_cache_size`].
+    pub fn duplicate_changes_skipped(&self) -> u64 {
+        self.duplicate_changes
+    }
+
+    /// Remember a sync error for [`Self::network_debug_snapshot`].
+    fn note_network_error(&mut self, realm_id: Option<&RealmId>, message: String) {
+        if self.recent_errors.len() >= RECENT_ERROR_LIMIT {
+            self.recent_errors.pop_front();
+        }
+        self.recent_errors.push_back(NetworkErrorRecord {
+            realm_id: realm_id.cloned(),
+            message,
+            at: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    /// Check the seen-set for `hash`, counting a hit as a skipped duplicate.
+    fn is_repeat_change(&mut self, hash: &ContentHash) -> bool {
+        let seen = self.seen_changes.check(hash);
+        if seen {
+            self.duplicate_changes += 1;
+        }
+        seen
+    }
+
+    /// Current gossip neighbors on a realm's topic
+    ///
+    /// These are the peers we exchange sync messages with directly, which
+    /// can be fewer than the realm's members or the peers in the registry.
+    /// Empty when the realm is not syncing.
+    pub fn topic_neighbors(&self, realm_id: &RealmId) -> Vec<iroh::PublicKey> {
+        if !self.is_realm_syncing(realm_id) {
+            return Vec::new();
+        }
+        let mut neighbors: Vec<_> = self
+            .topic_neighbors
+            .lock()
+            .unwrap()
+            .get(realm_id)
+            .map(|peers| peers.iter().copied().collect())
+            .unwrap_or_default();
+        neighbors.sort();
+        neighbors
+    }
+
+    /// Get the sync status for a realm
+    ///
+    /// Returns `SyncStatus::Idle` if the realm is not syncing or not found.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let status = engine.sync_status(&realm_id);
+    /// match status {
+    ///     SyncStatus::Syncing { peer_count } => println!("Syncing with {} peers", peer_count),
+    ///     SyncStatus::Idle => println!("Not syncing"),
+    ///     _ => {}
+    /// }
+    /// ```
+    pub fn sync_status(&self, realm_id: &RealmId) -> SyncStatus {
+        self.sync_status
+            .lock()
+            .unwrap()
+            .get(realm_id)
+            .cloned()
+            .unwrap_or(SyncStatus::Idle)
+    }
+
+    /// Get detailed network debug information for a realm.
+    ///
+    /// Returns information useful for debugging sync issues:
+    /// - Our node ID
+    /// - Current sync status with peer count
+    /// - Whether sync is active
+    /// - Bootstrap peer count
+    pub fn network_debug_info(&self, realm_id: &RealmId) -> NetworkDebugInfo {
+        let (node_id, node_id_full) = self.debug_node_id();
+
+        // Get sync status
+        let status = self.sync_status(realm_id);
+
+        // Get realm info for bootstrap peers
+        let (bootstrap_peer_count, is_shared) = self
+            .storage
+            .load_realm(realm_id)
+            .ok()
+            .flatten()
+            .map(|info| (info.bootstrap_peers.len(), info.is_shared))
+            .unwrap_or((0, false));
+
+        // Check if sync is active (we have a gossip and status is not Idle)
+        let sync_active = self.gossip.is_some() && !matches!(status, SyncStatus::Idle);
+
+        // Extract error if status is Error
+        let last_error = match &status {
+            SyncStatus::Error(msg) => Some(msg.clone()),
+            _ => None,
+        };
+
+        // Get connected peer IDs (currently we only have count, not IDs)
+        // TODO: Track actual peer IDs in sync_status for display
+        let connected_peers = Vec::new();
+
+        // Get detailed peer information from the peer registry
+        // Filter peers by those that share this realm
+        let peers = self
+            .peer_registry
+            .list_all()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|peer| peer.shared_realms.contains(realm_id))
+            .map(Self::peer_debug_info)
+            .collect();
+
+        NetworkDebugInfo {
+            node_id,
+            node_id_full,
+            status,
+            bootstrap_peer_count,
+            is_shared,
+            sync_active,
+            last_error,
+            connected_peers,
+            peers,
+            ..Default::default()
+        }
+    }
+
+    /// Snapshot of the whole node's network state, for bug reports
+    ///
+    /// Fills [`NetworkDebugInfo`] across all realms rather than for one:
+    /// - `topics` lists every realm topic we are subscribed to, with its
+    ///   current gossip neighbor count
+    /// - `status` is `Syncing` with the number of distinct neighbors over all
+    ///   topics, and `connected_peers` lists them
+    /// - `bootstrap_peer_count` and `is_shared` cover the subscribed realms
+    /// - `peers` holds every peer in the registry
+    /// - relay status, local work not yet synced, and the most recent sync
+    ///   errors (`last_error` is the newest of them)
+    ///
+    /// Serializes to JSON via serde.
+    pub fn network_debug_snapshot(&self) -> NetworkDebugInfo {
+        let (node_id, node_id_full) = self.debug_node_id();
+
+        let mut syncing: Vec<_> = self
+            .realms
+            .keys()
+            .filter(|realm_id| self.is_realm_syncing(realm_id))
+            .cloned()
+            .collect();
+        syncing.sort_by_key(|realm_id| realm_id.to_base58());
+
+        let mut neighbors = BTreeSet::new();
+        let mut bootstrap_peer_count = 0;
+        let mut is_shared = false;
+        let mut topics = Vec::with_capacity(syncing.len());
+        for realm_id in syncing {
+            let topic_neighbors = self.topic_neighbors(&realm_id);
+            let info = self.storage.load_realm(&realm_id).ok().flatten();
+            if let Some(info) = &info {
+                bootstrap_peer_count += info.bootstrap_peers.len();
+                is_shared |= info.is_shared;
+            }
+            topics.push(TopicDebugInfo {
+                realm_name: info.map(|i| i.name).unwrap_or_default(),
+                status: self.sync_status(&realm_id),
+                neighbor_count: topic_neighbors.len(),
+                paused: self.is_sync_paused(&realm_id),
+                realm_id,
+            });
+            neighbors.extend(topic_neighbors);
+        }
+
+        let status = if topics.is_empty() {
+            SyncStatus::Idle
+        } else {
+            SyncStatus::Syncing {
+                peer_count: neighbors.len(),
+            }
+        };
+        let connected_peers = neighbors.iter().map(|pk| debug_key_ids(pk).0).collect();
+
+        let (relay_url, relay_connected) = match &self.gossip {
+            Some(gossip) => {
+                let home = gossip.endpoint_addr().relay_urls().next().cloned();
+                let connected = (!self.gossip_config.disable_relay).then_some(home.is_some());
+                let url = home
+                    .or_else(|| gossip.configured_relay_url().cloned())
+                    .map(|u| u.to_string());
+                (url, connected)
+            }
+            None => (None, None),
+        };
+
+        let pending = self.pending_sync_summary();
+        let recent_errors: Vec<_> = self.recent_errors.iter().cloned().collect();
+
+        NetworkDebugInfo {
+            node_id,
+            node_id_full,
+            status,
+            bootstrap_peer_count,
+            is_shared,
+            sync_active: self.gossip.is_some() && !topics.is_empty(),
+            last_error: recent_errors.last().map(|e| e.message.clone()),
+            connected_peers,
+            peers: self
+                .peer_registry
+                .list_all()
+                .unwrap_or_default()
+                .into_iter()
+                .map(Self::peer_debug_info)
+                .collect(),
+            topics,
+            relay_url,
+            relay_connected,
+            pending_outbox_messages: pending.outbox_messages,
+            pending_unsynced_realms: pending.unsynced_realms,
+            recent_errors,
+        }
+    }
+
+    /// Our node ID for debug output, short and full, or "offline"
+    fn debug_node_id(&self) -> (String, String) {
+        match &self.gossip {
+            Some(gossip) => debug_key_ids(&gossip.public_key()),
+            None => ("offline".to_string(), "offline".to_string()),
+        }
+    }
+
+    fn peer_debug_info(peer: crate::peers::PeerInfo) -> crate::sync::events::PeerDebugInfo {
+        let (short, full) = debug_key_ids(&peer.public_key());
+
+        // Calculate connection duration (simple: how long since last_seen)
+        let connection_duration_secs = if peer.status == crate::peers::PeerStatus::Online {
+            // For online peers, calculate time since last_seen
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            Some(now.saturating_sub(peer.last_seen))
+        } else {
+            None
+        };
+
+        crate::sync::events::PeerDebugInfo {
+            peer_id: short,
+            peer_id_full: full,
+            is_connected: peer.status == crate::peers::PeerStatus::Online,
+            connection_duration_secs,
+        }
+    }
+
+    /// Subscribe to sync events
+    ///
+    /// Returns a receiver that will receive events when:
+    /// - Remote changes arrive for a realm
+    /// - Peer connects or disconnects
+    /// - Sync status changes
+    /// - Errors occur
+    ///
+    /// Multiple subscribers can exist; events are broadcast to all.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut events = engine.subscribe_events();
+    ///
+    /// tokio::spawn(async move {
+    ///     while let Ok(event) = events.recv().await {
+    ///         match event {
+    ///             SyncEvent::RealmChanged { realm_id, .. } => {
+    ///                 println!("Realm {} changed!", realm_id);
+    ///             }
+    ///             SyncEvent::StatusChanged { realm_id, status } => {
+    ///                 println!("Realm {} status: {:?}", realm_id, status);
+    ///             }
+    ///             _ => {}
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SyncEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Register a callback for realm changes
+    ///
+    /// This is a convenience method that spawns a background task to listen
+    /// for `SyncEvent::RealmChanged` events and calls the provided callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Function to call when remote changes arrive for any realm
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// engine.on_realm_change(|realm_id| {
+    ///     println!("Realm {} was updated by a peer!", realm_id);
+    /// });
+    /// ```
+    pub fn on_realm_change<F>(&self, callback: F)
+    where
+        F: Fn(RealmId) + Send + 'static,
+    {
+        let mut receiver = self.event_tx.subscribe();
+
+        tokio::spawn(async move {
+            while let Ok(event) = receiver.recv().await {
+                if let SyncEvent::RealmChanged { realm_id, .. } = event {
+                    callback(realm_id);
+                }
+            }
+        });
+    }
+
+    /// Get the list of realms currently syncing
+    pub fn syncing_realms(&self) -> Vec<RealmId> {
+        self.sync_status
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(realm_id, status)| {
+                if !matches!(status, SyncStatus::Idle) {
+                    Some(realm_id.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Get the count of realms currently syncing
+    pub fn syncing_count(&self) -> usize {
+        self.sync_status
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|s| !matches!(s, SyncStatus::Idle))
+            .count()
+    }
+
+    /// Emit a sync event (internal helper)
+    fn emit_event(&self, event: SyncEvent) {
+        let _ = self.event_tx.send(event);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Sync Envelope Operations (Signed + Encrypted)
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Broadcast a sync message to all peers in a realm (signed + encrypted)
+    ///
+    /// The message is wrapped in a `SyncEnvelope` which provides:
+    /// - Encryption with the realm's symmetric key (ChaCha20-Poly1305)
+    /// - Signature from our identity for authentication
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open or not syncing.
+    /// Returns `SyncError::Identity` if identity has not been initialized.
+    pub async fn broadcast_sync(
+        &self,
+        realm_id: &RealmId,
+        message: SyncMessage,
+    ) -> Result<(), SyncError> {
+        // Get realm state (must be open and syncing)
+        let state = self
+            .realms
+            .get(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        let topic_sender = state
+            .topic_sender
+            .as_ref()
+            .ok_or_else(|| SyncError::Gossip("Realm is not syncing".to_string()))?;
+
+        if state.paused {
+            debug!(%realm_id, sync_event = message.kind(), "Sync paused - not broadcasting");
+            return Ok(());
+        }
+
+        // Seal the message (encrypt + sign)
+        let envelope = self.seal_realm_message(realm_id, &message)?;
+
+        // Serialize envelope
+        let envelope_bytes = envelope.to_bytes()?;
+
+        debug!(
+            %realm_id,
+            correlation_id = %envelope.correlation_id(),
+            sender = %envelope.sender(),
+            bytes = envelope_bytes.len(),
+            "Broadcasting sync envelope"
+        );
+
+        self.record_capture(CaptureDirection::Outbound, realm_id, &envelope_bytes);
+
+        // Broadcast via topic sender
+        topic_sender.broadcast(envelope_bytes).await?;
+
+        Ok(())
+    }
+
+    /// Count an envelope and add it to the running capture, if any
+    fn record_capture(&self, direction: CaptureDirection, realm_id: &RealmId, bytes: &[u8]) {
+        let counter = match direction {
+            CaptureDirection::Inbound => &self.realm_messages_received,
+            CaptureDirection::Outbound => &self.realm_messages_sent,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(recorder) = self.message_capture.lock().as_mut() {
+            recorder.record(direction, realm_id, bytes);
+        }
+    }
+
+    /// Start recording every realm envelope this node sends or receives
+    ///
+    /// The open realms (including their keys) and pinned profiles are
+    /// snapshotted so [`Self::replay_capture`] can rebuild the starting state
+    /// on a fresh node. A capture already running is discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the realm or profile snapshot can't be read.
+    #[cfg(feature = "debug-server")]
+    pub fn start_capture(&mut self) -> Result<(), SyncError> {
+        let mut realms = Vec::new();
+        for (realm_id, state) in self.realms.iter_mut() {
+            let Some(info) = self.storage.load_realm(realm_id)? else {
+                continue;
+            };
+            realms.push(CapturedRealm {
+                info,
+                realm_key: state.realm_key,
+                document: state.doc.save(),
+            });
+        }
+        let profiles = self.storage.list_pinned_profiles()?;
+
+        info!(realms = realms.len(), "Started message capture");
+        *self.message_capture.lock() = Some(CaptureRecorder::new(realms, profiles));
+        Ok(())
+    }
+
+    /// Stop the running capture and return what it recorded
+    #[cfg(feature = "debug-server")]
+    pub fn stop_capture(&mut self) -> Option<MessageCapture> {
+        let capture = self.message_capture.lock().take()?.finish();
+        info!(messages = capture.messages.len(), "Stopped message capture");
+        Some(capture)
+    }
+
+    /// Re-drive this node with the inbound half of a capture
+    ///
+    /// Restores captured realms this node doesn't have and pins the captured
+    /// profiles, then feeds the received envelopes through the gossip receive
+    /// path in their original order.
+    ///
+    /// # Returns
+    ///
+    /// The number of envelopes replayed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a captured realm can't be restored.
+    #[cfg(feature = "debug-server")]
+    pub async fn replay_capture(&mut self, capture: &MessageCapture) -> Result<usize, SyncError> {
+        for realm in &capture.realms {
+            let realm_id = &realm.info.id;
+            if self.storage.load_realm(realm_id)?.is_none() {
+                self.storage.save_realm(&realm.info)?;
+                self.storage.save_realm_key(realm_id, &realm.realm_key)?;
+                self.storage.save_document(realm_id, &realm.document)?;
+            }
+            self.open_realm(realm_id).await?;
+        }
+
+        let own_did = self.did().map(|did| did.to_string());
+        for pin in &capture.profiles {
+            if own_did.as_deref() == Some(pin.did.as_str()) {
+                continue;
+            }
+            if let Err(e) = self.pin_profile(pin.signed_profile.clone(), pin.relationship.clone()) {
+                warn!(did = %pin.did, error = %e, "Skipped captured profile");
+            }
+        }
+
+        let mut replayed = 0;
+        for message in capture.inbound() {
+            self.sync_tx
+                .send(SyncChannelMessage::IncomingData {
+                    realm_id: message.realm_id.clone(),
+                    envelope_bytes: message.envelope_bytes.clone(),
+                })
+                .map_err(|_| SyncError::NotReady("Sync channel closed".to_string()))?;
+            replayed += 1;
+        }
+        self.process_pending_sync();
+
+        info!(replayed, "Replayed message capture");
+        Ok(replayed)
+    }
+
+    /// Encrypt and sign a sync message for a realm with our identity
+    ///
+    /// Logs the envelope's correlation id, which the receivers log again
+    /// when they open it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open, or
+    /// `SyncError::Identity` if no identity is initialized.
+    fn seal_realm_message(
+        &self,
+        realm_id: &RealmId,
+        message: &SyncMessage,
+    ) -> Result<SyncEnvelope, SyncError> {
+        let state = self
+            .realms
+            .get(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        let keypair = self.identity.as_ref().ok_or_else(|| {
+            SyncError::Identity("Identity not initialized. Call init_identity() first.".to_string())
+        })?;
+
+        let sender_did = Did::from_public_key(&keypair.public_key()).to_string();
+        let sign_fn = |data: &[u8]| -> Vec<u8> { keypair.sign(data).to_bytes() };
+        let envelope = SyncEnvelope::seal(message, &sender_did, &state.realm_key, sign_fn)?;
+
+        debug!(
+            %realm_id,
+            correlation_id = %envelope.correlation_id(),
+            sync_event = message.kind(),
+            "Sealed sync envelope"
+        );
+        Ok(envelope)
+    }
+
+    /// Process incoming sync messages from gossip
+    ///
+    /// Verifies the signature and decrypts the envelope, returning the
+    /// inner `SyncMessage` for processing.
+    ///
+    /// # Arguments
+    ///
+    /// * `realm_id` - The realm this message belongs to
+    /// * `envelope_bytes` - The raw bytes received from gossip
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(message))` if the envelope is valid.
+    /// Returns `Ok(None)` if signature verification or decryption failed.
+    /// Returns `Err` for other errors (realm not found, etc).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub fn handle_incoming(
+        &self,
+        realm_id: &RealmId,
+        envelope_bytes: &[u8],
+    ) -> Result<Option<SyncMessage>, SyncError> {
+        Ok(self
+            .open_incoming(realm_id, envelope_bytes)?
+            .map(|(_, message)| message))
+    }
+
+    /// Feed raw bytes into a realm's gossip receive path
+    ///
+    /// The bytes are queued exactly as the topic listener would queue them
+    /// and the queue is processed, so malformed input exercises the same
+    /// decoding and apply code as real traffic. For protocol fuzzing from the
+    /// network debugger.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(kind))` with the message kind if the envelope opened,
+    /// `Ok(None)` if it was rejected as malformed, forged or undecryptable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    #[cfg(feature = "debug-server")]
+    pub fn inject_incoming(
+        &mut self,
+        realm_id: &RealmId,
+        envelope_bytes: Vec<u8>,
+    ) -> Result<Option<&'static str>, SyncError> {
+        let kind = self
+            .open_incoming(realm_id, &envelope_bytes)?
+            .map(|(_, message)| message.kind());
+        self.sync_tx
+            .send(SyncChannelMessage::IncomingData {
+                realm_id: realm_id.clone(),
+                envelope_bytes,
+            })
+            .map_err(|_| SyncError::NotReady("Sync channel closed".to_string()))?;
+        self.process_pending_sync();
+        Ok(kind)
+    }
+
+    /// Verify and decrypt an incoming envelope, keeping the sender's DID
+    ///
+    /// Same contract as [`handle_incoming`](Self::handle_incoming), but also
+    /// returns the authenticated sender so callers can attribute the message.
+    fn open_incoming(
+        &self,
+        realm_id: &RealmId,
+        envelope_bytes: &[u8],
+    ) -> Result<Option<(String, SyncMessage)>, SyncError> {
+        // Get realm state
+        let state = self
+            .realms
+            .get(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        // Deserialize envelope
+        let envelope = match SyncEnvelope::from_bytes(envelope_bytes) {
+            Ok(env) => env,
+            Err(e) => {
+                warn!(error = ?e, "Failed to deserialize envelope");
+                return Ok(None);
+            }
+        };
+
+        let correlation_id = envelope.correlation_id();
+        debug!(
+            %realm_id,
+            %correlation_id,
+            sender = %envelope.sender(),
+            version = envelope.version(),
+            "Processing incoming envelope"
+        );
+
+        // Create verification function that looks up sender's public key from pinned profiles
+        let verify_fn = Self::make_verify_fn(&self.storage);
+
+        // Open the envelope (verify signature + decrypt)
+        match envelope.open(&state.realm_key, verify_fn) {
+            Ok(message) => {
+                debug!(
+                    %realm_id,
+                    %correlation_id,
+                    sync_event = message.kind(),
+                    "Successfully opened envelope"
+                );
+                Ok(Some((envelope.sender().to_string(), message)))
+            }
+            Err(SyncError::SignatureInvalid(msg)) => {
+                warn!(%realm_id, %correlation_id, error = %msg, "Signature verification failed");
+                Ok(None)
+            }
+            Err(SyncError::DecryptionFailed(msg)) => {
+                warn!(%realm_id, %correlation_id, error = %msg, "Decryption failed");
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sync a realm's current state to peers
+    ///
+    /// Broadcasts an `Announce` message with the current document heads,
+    /// allowing peers to determine if they need to sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    /// Returns `SyncError::Gossip` if the realm is not syncing.
+    pub async fn sync_realm_state(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        // Get the document heads
+        let heads = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            state
+                .doc
+                .heads()
+                .into_iter()
+                .map(|h| h.0.to_vec())
+                .collect::<Vec<_>>()
+        };
+
+        // Create announce message with our address for peer discovery
+        let sender_addr = self
+            .gossip
+            .as_ref()
+            .map(|g| NodeAddrBytes::from_endpoint_addr(&g.endpoint_addr()));
+
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads,
+            sender_addr,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
+
+        // Broadcast it
+        self.broadcast_sync(realm_id, message).await
+    }
+
+    /// Broadcast incremental changes for a realm
+    ///
+    /// Generates an incremental sync message from the document and broadcasts
+    /// it to all peers. This is more efficient than sending the full document.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    /// Returns `SyncError::Gossip` if the realm is not syncing.
+    pub async fn broadcast_changes(&mut self, realm_id: &RealmId) -> Result<(), SyncError> {
+        // Generate incremental changes
+        let data = {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            state.doc.generate_sync_message()
+        };
+
+        // Only broadcast if there are changes
+        if data.is_empty() {
+            debug!(%realm_id, "No changes to broadcast");
+            return Ok(());
+        }
+
+        // Create changes message
+        let message = SyncMessage::Changes {
+            realm_id: realm_id.clone(),
+            data,
+        };
+
+        // Broadcast it
+        self.broadcast_sync(realm_id, message).await
+    }
+
+    /// Broadcast pre-captured changes to peers
+    ///
+    /// Use this when you've already captured incremental changes before saving
+    /// (since save() resets the incremental checkpoint).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    /// Returns `SyncError::Gossip` if the realm is not syncing.
+    ///
+    /// A non-empty `data` marks local changes: if the broadcast fails they
+    /// are counted in [`Self::pending_sync_summary`] until a later broadcast
+    /// of the realm succeeds.
+    pub async fn broadcast_changes_with_data(
+        &mut self,
+        realm_id: &RealmId,
+        data: Vec<u8>,
+    ) -> Result<(), SyncError> {
+        // Always broadcast the FULL document instead of incremental changes.
+        // This ensures peers with empty/different docs can properly sync.
+        // Incremental changes only work when docs share a common history.
+        let state = self
+            .realms
+            .get_mut(realm_id)
+            .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+        // Held back until resume_sync, which broadcasts the whole document
+        if state.paused {
+            if !data.is_empty() {
+                state.unsynced = true;
+            }
+            debug!(%realm_id, "Sync paused - holding local changes");
+            return Ok(());
+        }
+
+        // Create sync response with full document
+        let message = full_document_message(realm_id, &mut state.doc);
+
+        debug!(%realm_id, "Broadcasting full document for sync");
+
+        // Broadcast it
+        let result = self.broadcast_sync(realm_id, message).await;
+        if let Some(state) = self.realms.get_mut(realm_id) {
+            if result.is_ok() {
+                state.unsynced = false;
+            } else if !data.is_empty() {
+                state.unsynced = true;
+            }
+        }
+        result
+    }
+
+    /// Apply incoming changes from a peer
+    ///
+    /// Applies the changes from a `SyncMessage::Changes` to the local document
+    /// and saves the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    /// Returns `SyncError::Serialization` if the changes are invalid.
+    pub async fn apply_incoming_changes(
+        &mut self,
+        realm_id: &RealmId,
+        data: &[u8],
+    ) -> Result<(), SyncError> {
+        {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            state.doc.apply_sync_message(data)?;
+            state.dirty = true;
+        }
+
+        // Save the updated document
+        self.save_realm(realm_id).await?;
+        self.refresh_realm_metadata(realm_id)?;
+
+        debug!(%realm_id, bytes = data.len(), "Applied incoming changes");
+        Ok(())
+    }
+
+    /// Apply a full document from a peer
+    ///
+    /// Loads the full document state and merges it with the local document.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    /// Returns `SyncError::Serialization` if the document is invalid.
+    pub async fn apply_full_document(
+        &mut self,
+        realm_id: &RealmId,
+        document_bytes: &[u8],
+    ) -> Result<(), SyncError> {
+        {
+            let state = self
+                .realms
+                .get_mut(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+
+            // Load the remote document
+            let remote_doc = RealmDoc::load(document_bytes)?;
+
+            // Merge into our document
+            absorb_archiving(&self.storage, realm_id, &mut state.doc, remote_doc)?;
+            state.dirty = true;
+        }
+
+        // Save the merged document
+        self.save_realm(realm_id).await?;
+        self.refresh_realm_metadata(realm_id)?;
+
+        debug!(%realm_id, bytes = document_bytes.len(), "Applied full document");
+        Ok(())
+    }
+
+    /// Create a verification function for envelope signatures
+    ///
+    /// Verifies hybrid Ed25519 + ML-DSA-65 signatures against the sender's
+    /// public key from their pinned profile. Unknown senders (those without
+    /// a pinned profile) are rejected.
+    fn make_verify_fn(storage: &Storage) -> impl Fn(&str, &[u8], &[u8]) -> bool + '_ {
+        move |sender_did: &str, data: &[u8], sig_bytes: &[u8]| -> bool {
+            use crate::identity::HybridSignature;
+            use tracing::{debug, warn};
+
+            // 1. Deserialize the signature from bytes
+            let signature = match HybridSignature::from_bytes(sig_bytes) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    warn!(sender = %sender_did, error = ?e, "Failed to parse signature");
+                    return false;
+                }
+            };
+
+            // 2. Look up sender's public key from pinned profiles
+            let public_key = match storage.load_pinned_profile(sender_did) {
+                Ok(Some(pin)) => pin.signed_profile.public_key,
+                Ok(None) => {
+                    debug!(sender = %sender_did, "Unknown sender - no pinned profile");
+                    return false;
+                }
+                Err(e) => {
+                    warn!(sender = %sender_did, error = ?e, "Failed to load pinned profile");
+                    return false;
+                }
+            };
+
+            // 3. Verify BOTH Ed25519 and ML-DSA-65 signatures
+            let valid = public_key.verify(data, &signature);
+            if !valid {
+                warn!(sender = %sender_did, "Signature verification failed");
+            }
+            valid
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Invite Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Generate an invite ticket for a realm
+    ///
+    /// Creates an invite containing the realm's encryption key and this
+    /// node as a bootstrap peer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm is not open.
+    pub async fn generate_invite(&mut self, realm_id: &RealmId) -> Result<InviteTicket, SyncError> {
+        // Check if this is the Private realm
+        if let Ok(Some(info)) = self.storage.load_realm(realm_id) {
+            if is_private_realm_name(&info.name) {
+                return Err(SyncError::PrivateRealmOperation(
+                    "Cannot generate invite for Private realm".to_string(),
+                ));
+            }
+        }
+
+        // Ensure realm is open
+        if !self.realms.contains_key(realm_id) {
+            self.open_realm(realm_id).await?;
+        }
+
+        // Get the realm key (copy it to avoid borrow issues)
+        let realm_key = {
+            let state = self
+                .realms
+                .get(realm_id)
+                .ok_or_else(|| SyncError::RealmNotFound(realm_id.to_string()))?;
+            state.realm_key
+        };
+
+        // Get realm name and first owner for the invite
+        let (realm_name, owner) = match self.storage.load_realm(realm_id)? {
+            Some(info) => (Some(info.name), info.owner),
+            None => (None, None),
+        };
+
+        // Ensure gossip is initialized
+        let gossip = self.ensure_gossip().await?;
+
+        // Generate invite (include realm name so joiners see it, and the owner
+        // so they know whose first role table to trust)
+        let mut invite = gossip.generate_invite(realm_id, realm_key, realm_name.as_deref())?;
+        if let Some(owner) = owner {
+            invite = invite.with_owner(&owner);
+        }
+
+        // Mark realm as shared
+        if let Ok(Some(mut info)) = self.storage.load_realm(realm_id) {
+            if !info.is_shared {
+                info.is_shared = true;
+                self.storage.save_realm(&info)?;
+            }
+        }
+
+        // Auto-start sync when sharing a realm
+        // This ensures the creator can send/receive sync messages
+        if !self.is_realm_syncing(realm_id) {
+            self.start_sync(realm_id).await?;
+        }
+
+        info!(%realm_id, "Invite generated and sync started");
+        Ok(invite)
+    }
+
+    /// Join a realm via invite ticket
+    ///
+    /// Connects to bootstrap peers, subscribes to the realm's gossip topic,
+    /// and saves the realm to storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` if the invite is expired.
+    pub async fn join_via_invite(&mut self, invite: &InviteTicket) -> Result<RealmId, SyncError> {
+        let realm_id = invite.realm_id();
+        info!(%realm_id, "Joining realm via invite");
+
+        // Debug: Log existing realms for troubleshooting
+        let existing_realms = self.storage.list_realms()?;
+        debug!(
+            joining_realm = %realm_id,
+            joining_base58 = %realm_id.to_base58(),
+            existing_count = existing_realms.len(),
+            existing_ids = ?existing_realms.iter().map(|r| r.id.to_base58()).collect::<Vec<_>>(),
+            "Checking membership before join"
+        );
+
+        // Check if we already have this realm
+        if self.storage.load_realm(&realm_id)?.is_some() {
+            warn!(
+                %realm_id,
+                "Join rejected: realm already exists in storage"
+            );
+            return Err(SyncError::InvalidInvite(
+                "Already a member of this realm".into(),
+            ));
+        }
+
+        // Initialize gossip
+        let gossip = self.ensure_gossip().await?;
+
+        // Join via invite using split API (receiver not wrapped in mutex)
+        let (sender, mut receiver) = gossip.join_via_invite_split(invite).await?;
+
+        // Spawn background listener task that owns the receiver directly
+        let listener_realm_id = realm_id.clone();
+        let sync_tx = self.sync_tx.clone();
+        let event_tx = self.event_tx.clone();
+        // Clone sync_status Arc for thread-safe peer counting in listener task
+        let sync_status = self.sync_status.clone();
+        // Clone peer_registry for tracking discovered peers
+        let peer_registry = self.peer_registry.clone();
+        let listener_gossip = gossip.clone();
+        let topic_neighbors = self.topic_neighbors.clone();
+
+        let listener = tokio::spawn(async move {
+            debug!(%listener_realm_id, "Join sync listener task started");
+            let mut event_count = 0u64;
+            loop {
+                debug!(%listener_realm_id, event_count, "Join listener waiting for next event...");
+                match receiver.recv_event().await {
+                    Some(TopicEvent::Message(msg)) => {
+                        event_count += 1;
+                        let msg_bytes = msg.content.len();
+                        debug!(
+                            %listener_realm_id,
+                            event_count,
+                            from = ?msg.from,
+                            bytes = msg_bytes,
+                            "Received sync message (joined)"
+                        );
+                        // Send to channel for processing by main engine
+                        let send_result = sync_tx.send(SyncChannelMessage::IncomingData {
+                            realm_id: listener_realm_id.clone(),
+                            envelope_bytes: msg.content,
+                        });
+                        if send_result.is_err() {
+                            debug!(%listener_realm_id, "Sync channel closed, stopping listener");
+                            break;
+                        }
+                        debug!(
+                            %listener_realm_id,
+                            bytes = msg_bytes,
+                            "Sent message to sync channel (joined)"
+                        );
+                        // Notify listeners that data arrived
+                        let _ = event_tx.send(SyncEvent::RealmChanged {
+                            realm_id: listener_realm_id.clone(),
+                            changes_applied: 1,
+                        });
+                    }
+                    Some(TopicEvent::NeighborUp(peer)) => {
+                        event_count += 1;
+                        debug!(%listener_realm_id, event_count, ?peer, "Peer connected (joined)");
+                        topic_neighbors
+                            .lock()
+                            .unwrap()
+                            .entry(listener_realm_id.clone())
+                            .or_default()
+                            .insert(peer);
+
+                        let event = ConnectionEvent::now(ConnectionChange::Connected)
+                            .with_direct(listener_gossip.path_is_direct(peer))
+                            .with_realm(listener_realm_id.clone());
+                        if let Err(e) = peer_registry.record_connection_event(&peer, event) {
+                            debug!(?peer, error = ?e, "Failed to record connection event");
+                        }
+
+                        // Record peer in registry
+                        let peer_info =
+                            PeerInfo::new(peer, PeerSource::FromRealm(listener_realm_id.clone()))
+                                .with_status(PeerStatus::Online);
+                        if let Err(e) = peer_registry.add_or_update(&peer_info) {
+                            warn!(?peer, error = ?e, "Failed to record peer in registry (joined)");
+                        } else {
+                            // Also record the realm for this peer
+                            let _ = peer_registry.add_peer_realm(&peer, &listener_realm_id);
+                            debug!(?peer, "Recorded peer in registry (joined)");
+                        }
+
+                        // Update peer count in sync_status (thread-safe)
+                        let new_count = {
+                            let mut status_map = sync_status.lock().unwrap();
+                            if let Some(SyncStatus::Syncing { peer_count }) =
+                                status_map.get_mut(&listener_realm_id)
+                            {
+                                *peer_count += 1;
+                                *peer_count
+                            } else {
+                                // Initialize to 1 if not in Syncing state
+                                status_map.insert(
+                                    listener_realm_id.clone(),
+                                    SyncStatus::Syncing { peer_count: 1 },
+                                );
+                                1
+                            }
+                        };
+
+                        // Request broadcast of our full document to the newly connected peer
+                        // This ensures offline changes are shared when peers reconnect
+                        let _ = sync_tx.send(SyncChannelMessage::BroadcastRequest {
+                            realm_id: listener_realm_id.clone(),
+                            neighbor: peer,
+                        });
+
+                        // Emit events
+                        let _ = event_tx.send(SyncEvent::PeerConnected {
+                            realm_id: listener_realm_id.clone(),
+                            peer_id: peer.to_string(),
+                        });
+                        let _ = event_tx.send(SyncEvent::StatusChanged {
+                            realm_id: listener_realm_id.clone(),
+                            status: SyncStatus::Syncing {
+                                peer_count: new_count,
+                            },
+                        });
+                    }
+                    Some(TopicEvent::NeighborDown(peer)) => {
+                        event_count += 1;
+                        debug!(%listener_realm_id, event_count, ?peer, "Peer disconnected (joined)");
+                        if let Some(neighbors) =
+                            topic_neighbors.lock().unwrap().get_mut(&listener_realm_id)
+                        {
+                            neighbors.remove(&peer);
+                        }
+
+                        let event = ConnectionEvent::now(ConnectionChange::Disconnected)
+                            .with_realm(listener_realm_id.clone());
+                        if let Err(e) = peer_registry.record_connection_event(&peer, event) {
+                            debug!(?peer, error = ?e, "Failed to record connection event");
+                        }
+
+                        // Mark peer as offline in registry
+                        if let Err(e) = peer_registry.update_status(&peer, PeerStatus::Offline) {
+                            warn!(?peer, error = ?e, "Failed to update peer status to offline (joined)");
+                        } else {
+                            debug!(?peer, "Marked peer as offline in registry (joined)");
+                        }
+
+                        // Update peer count in sync_status (thread-safe)
+                        let new_count = {
+                            let mut status_map = sync_status.lock().unwrap();
+                            if let Some(SyncStatus::Syncing { peer_count }) =
+                                status_map.get_mut(&listener_realm_id)
+                            {
+                                *peer_count = peer_count.saturating_sub(1);
+                                *peer_count
+                            } else {
+                                0
+                            }
+                        };
+
+                        // Emit events
+                        let _ = event_tx.send(SyncEvent::PeerDisconnected {
+                            realm_id: listener_realm_id.clone(),
+                            peer_id: peer.to_string(),
+                        });
+                        let _ = event_tx.send(SyncEvent::StatusChanged {
+                            realm_id: listener_realm_id.clone(),
+                            status: SyncStatus::Syncing {
+                                peer_count: new_count,
+                            },
+                        });
+                    }
+                    None => {
+                        debug!(%listener_realm_id, "Topic subscription closed");
+                        break;
+                    }
+                }
+            }
+            debug!(%listener_realm_id, "Join sync listener task ended");
+        });
+
+        // Create realm info with bootstrap peers for reconnection after restart
+        let info = RealmInfo {
+            id: realm_id.clone(),
+            name: invite
+                .realm_name
+                .clone()
+                .unwrap_or_else(|| "Shared Realm".to_string()),
+            is_shared: true,
+            created_at: chrono::Utc::now().timestamp(),
+            bootstrap_peers: invite.bootstrap_peers.clone(),
+            read_only: false,
+            muted: false,
+            key_epoch: 0,
+            is_creator: false,
+            owner: invite.owner_did.clone(),
+            metadata: Default::default(),
+        };
+
+        // Create document
+        let mut doc = RealmDoc::new();
+
+        // Save to storage
+        self.storage.save_realm(&info)?;
+        self.storage.save_realm_key(&realm_id, &invite.realm_key)?;
+        self.storage.save_document(&realm_id, &doc.save())?;
+
+        // Add to open realms
+        self.realms.insert(
+            realm_id.clone(),
+            RealmState {
+                doc,
+                topic_sender: Some(sender),
+                realm_key: invite.realm_key,
+                listener: Some(listener.abort_handle()),
+                dirty: false,
+                unsynced: false,
+                paused: false,
+            },
+        );
+
+        // Update sync status
+        self.sync_status
+            .lock()
+            .unwrap()
+            .insert(realm_id.clone(), SyncStatus::Syncing { peer_count: 0 });
+        let _ = self.event_tx.send(SyncEvent::StatusChanged {
+            realm_id: realm_id.clone(),
+            status: SyncStatus::Syncing { peer_count: 0 },
+        });
+
+        debug!(%realm_id, "Joined realm and started sync");
+
+        if let Some(did) = self.did() {
+            self.note_realm_member(&realm_id, did.as_ref());
+        }
+
+        // Wait for connection to establish before announcing
+        // The gossip connection typically takes ~20-50ms to establish
+        // This ensures we have a neighbor to receive our announce
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        debug!(%realm_id, "Connection delay complete, sending announce");
+
+        // Send an announce message to establish bidirectional communication
+        // This allows the inviter to learn our address and send messages back to us
+        // by forcing Joy to send a message first, which establishes the QUIC connection
+        let heads = if let Some(state) = self.realms.get_mut(&realm_id) {
+            state
+                .doc
+                .heads()
+                .into_iter()
+                .map(|h| h.0.to_vec())
+                .collect()
+        } else {
+            vec![]
+        };
+
+        // Include our endpoint address so the receiver can add us to their discovery
+        let sender_addr = self
+            .gossip
+            .as_ref()
+            .map(|g| NodeAddrBytes::from_endpoint_addr(&g.endpoint_addr()));
+
+        let announce = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads,
+            sender_addr,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
+        if let Err(e) = self.broadcast_sync(&realm_id, announce).await {
+            debug!(%realm_id, error = ?e, "Failed to send announce (non-fatal)");
+        } else {
+            debug!(%realm_id, "Sent announce to establish bidirectional connection");
+        }
+
+        Ok(realm_id)
+    }
+
+    /// Create an invite string for a realm (convenience method)
+    ///
+    /// Same as `generate_invite` but returns the encoded string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
+    pub async fn create_invite(&mut self, realm_id: &RealmId) -> Result<String, SyncError> {
+        let ticket = self.generate_invite(realm_id).await?;
+        ticket.encode()
+    }
+
+    /// Join a realm via invite string (convenience method)
+    ///
+    /// Decodes the invite ticket and joins the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` if the ticket is invalid.
+    pub async fn join_realm(&mut self, ticket_str: &str) -> Result<RealmId, SyncError> {
+        let ticket = InviteTicket::decode(ticket_str)?;
+        self.join_via_invite(&ticket).await
+    }
+
+    /// Decode an invite URI of either kind
+    ///
+    /// Accepts `sync-invite:` realm tickets and `sync-contact:` contact
+    /// invites, e.g. from a clicked deep link or a pasted code. Contact
+    /// invites are validated as in [`Self::decode_contact_invite`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` for an unknown scheme or a
+    /// malformed, expired or revoked invite.
+    pub async fn parse_invite_uri(&mut self, uri: &str) -> Result<InviteKind, SyncError> {
+        let uri = uri.trim();
+        match uri.split_once(':').map(|(scheme, _)| scheme) {
+            Some("sync-invite") => Ok(InviteKind::Realm(InviteTicket::decode(uri)?)),
+            Some("sync-contact") => Ok(InviteKind::Contact(Box::new(
+                self.decode_contact_invite(uri).await?,
+            ))),
+            Some(scheme) => Err(SyncError::InvalidInvite(format!(
+                "Unknown invite scheme '{}'",
+                scheme
+            ))),
+            None => Err(SyncError::InvalidInvite(
+                "Invite is not a sync-invite: or sync-contact: URI".to_string(),
+            )),
+        }
+    }
+
+    /// Accept an invite URI of either kind
+    ///
+    /// Joins the realm for a `sync-invite:` ticket, or sends a contact request
+    /// (see [`Self::accept_contact_invite`]) for a `sync-contact:` invite.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` if the URI can't be parsed, or the
+    /// error from the join or contact request.
+    pub async fn accept_invite_uri(&mut self, uri: &str) -> Result<AcceptedInvite, SyncError> {
+        match self.parse_invite_uri(uri).await? {
+            InviteKind::Realm(ticket) => Ok(AcceptedInvite::Realm(
+                self.join_via_invite(&ticket).await?,
+            )),
+            InviteKind::Contact(invite) => Ok(AcceptedInvite::Contact(
+                self.accept_contact_invite(*invite).await?,
+            )),
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Node Info
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Get information about this node
+    pub async fn node_info(&self) -> Result<NodeInfo, SyncError> {
+        let realms = self.storage.list_realms()?;
+
+        let (node_id, relay_url) = if let Some(ref gossip) = self.gossip {
+            let addr = gossip.endpoint_addr();
+            let id = Some(addr.id.to_string());
+            // Before the home relay connects, report the relay we were told to use
+            let relay = addr
+                .relay_urls()
+                .next()
+                .or(gossip.configured_relay_url())
+                .map(|u| u.to_string());
+            (id, relay)
+        } else {
+            (None, None)
+        };
+
+        let did = self.did().map(|d| d.to_string());
+        // Our own packets are mirrored too; only count other profiles
+        let my_did = self.profile_did();
+        let mirror_count = match &self.mirror_store {
+            Some(mirror) => mirror
+                .list_mirrored_dids()?
+                .iter()
+                .filter(|did| Some(*did) != my_did.as_ref())
+                .count(),
+            None => 0,
+        };
+
+        Ok(NodeInfo {
+            data_dir: self.data_dir.clone(),
+            realm_count: realms.len(),
+            node_id,
+            relay_url,
+            did,
+            profile_did: my_did.map(|d| d.to_string()),
+            log_head: self.profile_log.as_ref().and_then(|log| log.head_sequence()),
+            mirror_count,
+            contact_count: self.storage.list_contacts()?.len(),
+            outbox_size: self.unacked_direct_messages(),
+        })
+    }
+
+    /// This node's full network address, for sharing out of band
+    ///
+    /// Unlike [`Self::node_info`], this includes the direct addresses, so a
+    /// peer holding it can connect without discovery. Returns `None` if
+    /// networking is not active.
+    pub fn node_addr(&self) -> Option<NodeAddrBytes> {
+        self.gossip
+            .as_ref()
+            .map(|gossip| NodeAddrBytes::from_endpoint_addr(&gossip.endpoint_addr()))
+    }
+
+    /// Summarize whether this node is in working order
+    ///
+    /// Intended for supervisors and the control API to poll. Only looks at
+    /// in-memory state plus one small local database write; it never waits
+    /// on the network. See [`HealthReport::is_healthy`] for which checks
+    /// count towards overall health.
+    pub fn health(&self) -> HealthReport {
+        let networking_active = self.gossip.is_some();
+        let relay_reachable = match &self.gossip {
+            Some(gossip) if !self.gossip_config.disable_relay => {
+                Some(gossip.endpoint_addr().relay_urls().next().is_some())
+            }
+            _ => None,
+        };
+        let database_writable = match self.storage.check_writable() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(error = %e, "Health check: database is not writable");
+                false
+            }
+        };
+
+        HealthReport {
+            identity_initialized: self.identity.is_some(),
+            networking_requested: self.networking_requested,
+            networking_active,
+            relay_reachable,
+            syncing_realms: self.syncing_count(),
+            last_sync_age: self.last_sync_at.map(|at| at.elapsed()),
+            database_writable,
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Profile Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Save a user profile
+    pub fn save_profile(&self, profile: &crate::types::UserProfile) -> Result<(), SyncError> {
+        self.storage.save_profile(profile)
+    }
+
+    /// Load a profile by peer ID
+    pub fn load_profile(
+        &self,
+        peer_id: &str,
+    ) -> Result<Option<crate::types::UserProfile>, SyncError> {
+        self.storage.load_profile(peer_id)
+    }
+
+    /// Delete a profile by peer ID
+    pub fn delete_profile(&self, peer_id: &str) -> Result<(), SyncError> {
+        self.storage.delete_profile(peer_id)
+    }
+
+    /// List all profiles
+    pub fn list_profiles(&self) -> Result<Vec<crate::types::UserProfile>, SyncError> {
+        self.storage.list_profiles()
+    }
+
+    /// Get or create profile for this node
+    pub fn get_own_profile(&self) -> Result<crate::types::UserProfile, SyncError> {
+        // Use DID as peer_id (stable identifier, available immediately)
+        let peer_id = self
+            .did()
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?
+            .to_string();
+
+        // Try to load existing profile
+        if let Some(profile) = self.storage.load_profile(&peer_id)? {
+            Ok(profile)
+        } else {
+            // Create default profile with placeholder text to trigger edit mode
+            let mut profile = crate::types::UserProfile::new(peer_id.clone(), "Anonymous User".to_string());
+            profile.bio = "**Add your bio here**\n\nDescribe yourself, your interests, or your role in the network.\n\n- Use *markdown* for formatting\n- Add links, lists, and more\n- Express your unique identity".to_string();
+            self.storage.save_profile(&profile)?;
+            Ok(profile)
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Profile Pinning Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Create and store a signed version of our own profile.
+    ///
+    /// This should be called whenever the profile is updated. The signed profile
+    /// is stored as a pin with relationship `Own` and can be announced to peers.
+    ///
+    /// # Returns
+    ///
+    /// The signed profile that was created and stored.
+    pub fn sign_and_pin_own_profile(&mut self) -> Result<crate::types::SignedProfile, SyncError> {
+        let profile = self.get_own_profile()?;
+
+        // Ensure identity is initialized
+        self.init_identity()?;
+        let keypair = self.identity.as_ref().ok_or_else(|| {
+            SyncError::Identity("Identity not initialized".to_string())
+        })?;
+
+        // Create signed profile
+        let signed = crate::types::SignedProfile::sign(&profile, keypair);
+        let did = signed.did().to_string();
+
+        // Create pin with Own relationship
+        let pin = crate::types::ProfilePin::new(
+            did.clone(),
+            signed.clone(),
+            crate::types::PinRelationship::Own,
+        );
+
+        // Save to storage (Own pins are never evicted)
+        self.storage.save_pinned_profile(&pin)?;
+
+        debug!(did = %did, "Signed and pinned own profile");
+        Ok(signed)
+    }
+
+    /// Get our own pinned profile (if exists).
+    pub fn get_own_pinned_profile(&self) -> Result<Option<crate::types::ProfilePin>, SyncError> {
+        self.storage.get_own_pinned_profile()
+    }
+
+    /// Pin a signed profile from another peer.
+    ///
+    /// This stores the profile for redundancy, allowing us to serve it
+    /// to other peers who request it.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed_profile` - The cryptographically signed profile to pin
+    /// * `relationship` - Why we're pinning this profile
+    ///
+    /// # Returns
+    ///
+    /// A list of DIDs that were evicted (if storage limits were reached).
+    pub fn pin_profile(
+        &self,
+        signed_profile: crate::types::SignedProfile,
+        relationship: crate::types::PinRelationship,
+    ) -> Result<Vec<String>, SyncError> {
+        // Verify signature first
+        if !signed_profile.verify() {
+            return Err(SyncError::SignatureInvalid(
+                "Profile signature verification failed".to_string(),
+            ));
+        }
+
+        let did = signed_profile.did().to_string();
+        let mut pin = crate::types::ProfilePin::new(did.clone(), signed_profile, relationship);
+        pin.mark_verified();
+
+        // Use default pinning config
+        let config = crate::storage::PinningConfig::default();
+        let evicted = self.storage.save_pinned_profile_with_limits(&pin, &config)?;
+        self.reverify_provisional_packets();
+
+        debug!(did = %did, evicted = evicted.len(), "Pinned profile");
+        Ok(evicted)
+    }
+
+    /// Get a pinned profile by DID.
+    pub fn get_pinned_profile(&self, did: &str) -> Result<Option<crate::types::ProfilePin>, SyncError> {
+        self.storage.load_pinned_profile(did)
+    }
+
+    /// Get a pinned profile by DID with its signature checked.
+    ///
+    /// `verified` on the returned pin is current. Verification only runs
+    /// when the pinned profile changed since it was last checked, and the
+    /// new result is stored with the pin.
+    pub fn get_verified_pinned_profile(
+        &self,
+        did: &str,
+    ) -> Result<Option<crate::types::ProfilePin>, SyncError> {
+        let Some(mut pin) = self.storage.load_pinned_profile(did)? else {
+            return Ok(None);
+        };
+        let cached = pin.verified_hash;
+        pin.check_signature();
+        if pin.verified_hash != cached {
+            self.storage.save_pinned_profile(&pin)?;
+        }
+        Ok(Some(pin))
+    }
+
+    /// Get the pinned profiles for a list of DIDs, keyed by DID.
+    ///
+    /// Reads them all in one storage pass, so prefer this over calling
+    /// [`get_pinned_profile`](Self::get_pinned_profile) per contact when
+    /// rendering a list. DIDs without a pin are left out.
+    pub fn get_pinned_profiles_bulk(
+        &self,
+        dids: &[&str],
+    ) -> Result<HashMap<String, crate::types::ProfilePin>, SyncError> {
+        self.storage.load_pinned_profiles(dids)
+    }
+
+    /// Unpin a profile by DID.
+    ///
+    /// Note: Own profile cannot be unpinned.
+    pub fn unpin_profile(&self, did: &str) -> Result<(), SyncError> {
+        // Check if this is our own profile
+        if let Some(pin) = self.storage.load_pinned_profile(did)? {
+            if pin.is_own() {
+                return Err(SyncError::InvalidOperation(
+                    "Cannot unpin own profile".to_string(),
+                ));
+            }
+        }
+
+        self.storage.delete_pinned_profile(did)
+    }
+
+    /// List all pinned profiles.
+    pub fn list_pinned_profiles(&self) -> Result<Vec<crate::types::ProfilePin>, SyncError> {
+        self.storage.list_pinned_profiles()
+    }
+
+    /// List pinned profiles by relationship type.
+    pub fn list_pinned_profiles_by_relationship(
+        &self,
+        relationship: &crate::types::PinRelationship,
+    ) -> Result<Vec<crate::types::ProfilePin>, SyncError> {
+        self.storage.list_pinned_profiles_by_relationship(relationship)
+    }
+
+    /// Update an existing pin with a new signed profile.
+    ///
+    /// This is used when receiving profile announcements for peers we already have pinned.
+    pub fn update_pinned_profile(
+        &self,
+        did: &str,
+        signed_profile: crate::types::SignedProfile,
+    ) -> Result<bool, SyncError> {
+        // Verify signature first
+        if !signed_profile.verify() {
+            return Err(SyncError::SignatureInvalid(
+                "Profile signature verification failed".to_string(),
+            ));
+        }
+
+        // Check if we have an existing pin
+        if let Some(mut pin) = self.storage.load_pinned_profile(did)? {
+            // Update the profile
+            if pin.update_profile(signed_profile) {
+                self.storage.save_pinned_profile(&pin)?;
+                debug!(did = %did, "Updated pinned profile");
+                Ok(true)
+            } else {
+                // update_profile returns false if signature is invalid
+                // (but we already verified above, so this shouldn't happen)
+                Ok(false)
+            }
+        } else {
+            // No existing pin for this DID
+            Ok(false)
+        }
+    }
+
+    /// Get the count of pinned profiles (excluding own).
+    pub fn pinned_profile_count(&self) -> Result<usize, SyncError> {
+        self.storage.count_pinned_profiles()
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Network Page: Pinners (Who Pins Our Profile)
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// List all peers who are pinning our profile ("Souls Carrying Your Light").
+    ///
+    /// Returns pinners sorted by pinned_at (newest first).
+    pub fn list_profile_pinners(&self) -> Result<Vec<crate::storage::PinnerInfo>, SyncError> {
+        self.storage.list_pinners()
+    }
+
+    /// Count the number of peers pinning our profile.
+    pub fn count_profile_pinners(&self) -> Result<usize, SyncError> {
+        self.storage.count_pinners()
+    }
+
+    // Note: record_pinner() and remove_pinner() removed - Indra's Net derives pinners from contacts
+
+    /// Realm envelopes this node has broadcast and received since startup
+    pub fn message_counts(&self) -> MessageCounts {
+        MessageCounts {
+            sent: self.realm_messages_sent.load(Ordering::Relaxed),
+            received: self.realm_messages_received.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Get network statistics for the Network page.
+    ///
+    /// Returns counts for peers, pinners, and pinned profiles, plus the
+    /// average link quality of online peers and how long ago a peer's
+    /// changes were last applied.
+    pub fn network_stats(&self) -> NetworkStats {
+        let total_peers = self.peer_registry.count().unwrap_or(0);
+        let online = self
+            .peer_registry
+            .list_by_status(PeerStatus::Online)
+            .unwrap_or_default();
+        let pinners_count = self.storage.count_pinners().unwrap_or(0);
+        let pinning_count = self.storage.count_pinned_profiles().unwrap_or(0);
+
+        let average_quality = if online.is_empty() {
+            None
+        } else {
+            let sum: usize = online.iter().map(|p| p.connection_quality as usize).sum();
+            Some((sum / online.len()) as u8)
+        };
+
+        NetworkStats {
+            total_peers,
+            online_peers: online.len(),
+            pinners_count,
+            pinning_count,
+            average_quality,
+            last_sync_age: self.last_sync_at.map(|at| at.elapsed()),
+        }
+    }
+
+    /// Local work waiting to reach peers.
+    ///
+    /// Counts direct messages in our log that their recipient has not
+    /// acknowledged yet, and open shared realms with local changes that
+    /// could not be broadcast (for example while offline). Realm counts are
+    /// not persisted; on restart the full document is broadcast when sync
+    /// starts anyway.
+    pub fn pending_sync_summary(&self) -> PendingSync {
+        let unsynced_realms = self
+            .realms
+            .iter()
+            .filter(|(_, state)| state.unsynced)
+            .filter(|(realm_id, _)| {
+                matches!(self.storage.load_realm(realm_id), Ok(Some(info)) if info.is_shared)
+            })
+            .count();
+
+        PendingSync {
+            outbox_messages: self.unacked_direct_messages(),
+            unsynced_realms,
+        }
+    }
+
+    /// Number of our direct messages without a receipt from their recipient.
+    fn unacked_direct_messages(&self) -> usize {
+        let Some(log) = self.profile_log.as_ref() else {
+            return 0;
+        };
+        let mut acked: HashMap<Did, BTreeSet<u64>> = HashMap::new();
+        log.entries_ordered()
+            .into_iter()
+            .filter(|entry| {
+                let Some(PacketPayload::DirectMessage { recipient, .. }) = self.decrypt_packet(&entry.envelope) else {
+                    return false;
+                };
+                !acked
+                    .entry(recipient.clone())
+                    .or_insert_with(|| self.acked_sequences(&recipient))
+                    .contains(&entry.envelope.sequence)
+            })
+            .count()
+    }
+
+    /// How alive the node's connection to the network is right now.
+    ///
+    /// See [`ResonanceLevel::from_stats`] for the thresholds.
+    pub fn network_resonance(&self) -> ResonanceLevel {
+        ResonanceLevel::from_stats(&self.network_stats())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Auto-Pinning Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Check if a DID should be automatically pinned.
+    ///
+    /// Returns `true` if the DID belongs to a contact or realm member.
+    pub fn should_auto_pin(&self, did: &str) -> bool {
+        // Check if this DID is an accepted contact
+        if let Ok(contacts) = self.storage.list_contacts() {
+            for contact in contacts {
+                if contact.peer_did == did {
+                    return true;
+                }
+            }
+        }
+
+        // Check if this DID is a member of any realm we're in
+        // For now, we auto-pin any contact; realm member pinning can be
+        // added when realm membership tracking is implemented
+        false
+    }
+
+    /// Determine the relationship for auto-pinning a DID.
+    ///
+    /// Returns the appropriate `PinRelationship` based on our relationship with the DID.
+    pub fn determine_pin_relationship(&self, did: &str) -> Option<crate::types::PinRelationship> {
+        // Check if this DID is a contact
+        if let Ok(contacts) = self.storage.list_contacts() {
+            for contact in contacts {
+                if contact.peer_did == did {
+                    return Some(crate::types::PinRelationship::Contact);
+                }
+            }
+        }
+
+        // TODO: Check if this DID is a realm member
+        // For now, return None if not a contact
+        None
+    }
+
+    /// Process a profile gossip announcement for potential auto-pinning.
+    ///
+    /// If the announcement is from a contact or realm member, their profile
+    /// will be automatically pinned or updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The profile gossip message to process
+    ///
+    /// # Returns
+    ///
+    /// The action taken (UpdatePin, Ignore, etc.) for logging/testing purposes.
+    pub fn process_profile_announcement(
+        &self,
+        message: &crate::sync::ProfileGossipMessage,
+    ) -> Result<crate::sync::ProfileAction, SyncError> {
+        use crate::sync::{ProfileAction, ProfileGossipMessage};
+
+        match message {
+            ProfileGossipMessage::Announce {
+                signed_profile,
+                avatar_ticket,
+            } => {
+                // Verify signature first
+                if !signed_profile.verify() {
+                    warn!("Received profile announcement with invalid signature");
+                    return Ok(ProfileAction::Ignore);
+                }
+
+                let signer_did = signed_profile.did().to_string();
+
+                // Check if we should auto-pin this DID
+                if let Some(relationship) = self.determine_pin_relationship(&signer_did) {
+                    // Check if we already have this profile pinned
+                    if let Some(existing) = self.storage.load_pinned_profile(&signer_did)? {
+                        // Update existing pin
+                        let mut updated = existing.clone();
+                        if updated.update_profile(signed_profile.clone()) {
+                            if let Some(ticket) = avatar_ticket {
+                                // Parse the ticket and extract the hash for avatar tracking
+                                if let Ok(blob_ticket) =
+                                    ticket.parse::<iroh_blobs::ticket::BlobTicket>()
+                                {
+                                    updated.avatar_hash = Some(*blob_ticket.hash().as_bytes());
+                                }
+                            }
+                            self.storage.save_pinned_profile(&updated)?;
+                            debug!(did = %signer_did, "Updated pinned profile from announcement");
+                        }
+                    } else {
+                        // Create new pin
+                        let mut pin = crate::types::ProfilePin::new(
+                            signer_did.clone(),
+                            signed_profile.clone(),
+                            relationship,
+                        );
+                        if let Some(ticket) = avatar_ticket {
+                            if let Ok(blob_ticket) =
+                                ticket.parse::<iroh_blobs::ticket::BlobTicket>()
+                            {
+                                pin.avatar_hash = Some(*blob_ticket.hash().as_bytes());
+                            }
+                        }
+                        let config = crate::storage::PinningConfig::default();
+                        self.storage.save_pinned_profile_with_limits(&pin, &config)?;
+                        debug!(did = %signer_did, "Auto-pinned profile from announcement");
+                    }
+
+                    Ok(ProfileAction::UpdatePin {
+                        signed_profile: signed_profile.clone(),
+                        avatar_ticket: avatar_ticket.clone(),
+                    })
+                } else {
+                    // Not a contact or realm member, ignore
+                    Ok(ProfileAction::Ignore)
+                }
+            }
+            _ => {
+                // Request and Response messages are not auto-pin triggers
+                Ok(ProfileAction::Ignore)
+            }
+        }
+    }
+
+    /// Auto-pin a contact's profile when they are accepted.
+    ///
+    /// This creates a placeholder pin for the contact that will be updated
+    /// when we receive their profile announcement.
+    ///
+    /// # Arguments
+    ///
+    /// * `contact` - The contact info from the acceptance event
+    ///
+    /// # Returns
+    ///
+    /// True if a new pin was created, false if already pinned.
+    pub fn auto_pin_contact(&self, contact: &crate::types::contact::ContactInfo) -> Result<bool, SyncError> {
+        let did = &contact.peer_did;
+
+        // Check if already pinned
+        if self.storage.load_pinned_profile(did)?.is_some() {
+            debug!(did = %did, "Contact already pinned");
+            return Ok(false);
+        }
+
+        // Note: We can't create a proper SignedProfile without the contact's keypair.
+        // The actual pinning will happen when we receive their profile announcement
+        // via process_profile_announcement(). For now, just log that we're interested.
+        debug!(
+            did = %did,
+            name = %contact.profile.display_name,
+            "Contact accepted, will auto-pin on profile announcement"
+        );
+
+        // Return true to indicate we registered interest
+        Ok(true)
+    }
+
+    /// Get all contact DIDs that we should auto-pin profiles for.
+    ///
+    /// This returns a list of DIDs from our accepted contacts.
+    pub fn get_auto_pin_interests(&self) -> Result<Vec<String>, SyncError> {
+        let contacts = self.storage.list_contacts()?;
+        Ok(contacts.into_iter().map(|c| c.peer_did).collect())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Profile Sync Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Announce our profile to the global profile topic.
+    ///
+    /// This broadcasts a signed profile announcement to all peers subscribed
+    /// to the global profile topic. Peers who are interested in our profile
+    /// (contacts, realm members) will auto-pin it.
+    ///
+    /// # Arguments
+    ///
+    /// * `avatar_ticket` - Optional blob ticket for avatar download
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) if announcement was broadcast successfully.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if gossip is not initialized or broadcast fails.
+    pub async fn announce_profile(&mut self, avatar_ticket: Option<String>) -> Result<(), SyncError> {
+        // Ensure we have a signed profile
+        let signed = self.sign_and_pin_own_profile()?;
+
+        // Create the announcement message
+        let announcement = crate::sync::ProfileGossipMessage::announce(signed.clone(), avatar_ticket.clone());
+        let bytes = announcement.to_bytes()?;
+
+        // 1. Broadcast on our own per-peer profile topic (for backwards compatibility)
+        if let Some(sender) = self.profile_gossip_sender.as_ref() {
+            if let Err(e) = sender.broadcast(bytes.clone()).await {
+                warn!(error = %e, "Failed to broadcast on per-peer profile topic (non-fatal)");
+            } else {
+                debug!("Profile announcement broadcast on per-peer topic");
+            }
+        }
+
+        // 2. Broadcast on all 1:1 contact topics (the WORKING channel!)
+        // This is the primary mechanism for profile propagation since contact topics
+        // have proper bi-directional mesh formation from the contact exchange.
+        if let Some(ref manager) = self.contact_manager {
+            let contacts_updated = manager.broadcast_profile_to_contacts(&bytes).await;
+            info!(contacts_updated, "Profile announcement broadcast on contact topics");
+        }
+
+        info!("Profile announcement broadcast complete");
+        Ok(())
+    }
+
+    // Note: send_pin_acknowledgment() and send_pin_removal() removed - Indra's Net derives pinners from contacts
+    // In Indra's Net, contact acceptance = implicit mutual mirroring. No explicit acknowledgment needed.
+
+    /// Update our own profile and broadcast the change to peers.
+    ///
+    /// This is the main method to use when the user edits their profile.
+    /// It saves the profile locally and broadcasts an announcement so peers
+    /// who have pinned our profile can update their copy.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The updated profile data
+    /// * `avatar_ticket` - Optional blob ticket for avatar (if avatar changed)
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) if profile was saved and broadcast successfully.
+    pub async fn update_own_profile(
+        &mut self,
+        profile: &crate::types::UserProfile,
+        avatar_ticket: Option<String>,
+    ) -> Result<(), SyncError> {
+        // Save the profile locally
+        self.storage.save_profile(profile)?;
+
+        // Sign, pin, and broadcast the update
+        self.announce_profile(avatar_ticket).await?;
+
+        info!(peer_id = %profile.peer_id, "Profile updated and broadcast");
+        Ok(())
+    }
+
+    /// Start listening for profile announcements.
+    ///
+    /// This method sets up profile sync by subscribing to:
+    /// 1. **Our own profile topic** (derived from our DID) - for broadcasting our profile updates
+    /// 2. **Global profile topic** (legacy) - for backwards compatibility during migration
+    ///
+    /// When a profile announcement is received from a contact, their profile is auto-pinned.
+    /// Contacts also subscribe to our per-peer topic via `finalize_contact()`.
+    ///
+    /// # Per-Peer Topic Architecture
+    ///
+    /// Each peer has their own profile topic: `BLAKE3("sync-profile" || peer_did)`
+    /// - Only contacts receive your updates (targeted, not global)
+    /// - Scalability: O(contacts) messages instead of O(all users)
+    /// - Privacy: Non-contacts don't receive your profile updates
+    ///
+    /// # Arguments
+    ///
+    /// * `bootstrap_peers` - Initial peers to connect to (can be empty)
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) if subscription started successfully.
+    pub async fn start_profile_sync(
+        &mut self,
+        bootstrap_peers: Vec<iroh::PublicKey>,
+    ) -> Result<(), SyncError> {
+        // Check if already initialized - don't replace existing subscription!
+        // Replacing the subscription would drop the old receiver, closing the topic
+        // and preventing contacts from joining. New bootstrap peers are still joined.
+        if self.profile_gossip_sender.is_some() {
+            match self.global_profile_gossip_sender.as_ref() {
+                Some(sender) if !bootstrap_peers.is_empty() => {
+                    sender.join_peers(bootstrap_peers).await?;
+                    debug!("Profile sync already initialized, joined bootstrap peers");
+                }
+                _ => debug!("Profile sync already initialized, skipping"),
+            }
+            return Ok(());
+        }
+
+        // Get our DID for processing messages directed at us
+        let our_did = self
+            .did()
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?
+            .to_string();
+
+        // Ensure gossip is initialized
+        let gossip = self.ensure_gossip().await?;
+
+        // Subscribe to our OWN profile topic (for broadcasting)
+        // Contacts subscribe to this topic via finalize_contact() to receive our updates
+        let own_topic_id = crate::sync::derive_profile_topic(&our_did);
+        let (sender, own_receiver) = gossip.subscribe_split(own_topic_id, vec![]).await?;
+
+        // Store sender for reuse by announce_profile() and other profile broadcast methods.
+        // This allows all profile announcements to use the same persistent subscription.
+        self.profile_gossip_sender = Some(sender.clone());
+
+        // CRITICAL: Store receiver to keep the subscription alive!
+        // Dropping the receiver would close the gossip topic, preventing contacts from joining.
+        self.profile_gossip_receiver = Some(own_receiver);
+
+        info!(
+            did = %our_did,
+            ?own_topic_id,
+            "Subscribed to own profile topic for broadcasting"
+        );
+
+        // Also subscribe to the global topic for backwards compatibility and packet broadcast
+        // Packets (messages) are broadcast on the global topic so all contacts receive them
+        let global_topic_id = crate::sync::global_profile_topic();
+        let (global_sender, mut receiver) = gossip.subscribe_split(global_topic_id, bootstrap_peers).await?;
+
+        // Store global sender for packet broadcasts (messages to contacts)
+        self.global_profile_gossip_sender = Some(global_sender.clone());
+
+        // Re-announce our profile whenever neighbors join, so newly-arrived
+        // peers get it without waiting for the next manual announce
+        let reannounce_tx = Self::start_profile_reannouncer(
+            self.storage.clone(),
+            vec![global_sender, sender],
+        );
+
+        // Clone dependencies for the background task
+        let storage = self.storage.clone();
+        let blob_manager = self.blob_manager.clone();
+        let endpoint = gossip.endpoint().clone();
+        let contact_event_tx = self.contact_event_tx.clone();
+        let packet_gate = self.packet_gate.clone();
+
+        // Spawn background task to process incoming profile messages
+        tokio::spawn(async move {
+            use crate::sync::TopicEvent;
+
+            info!("Profile sync listener started with P2P blob download support");
+
+            while let Some(event) = receiver.recv_event().await {
+                match event {
+                    TopicEvent::Message(msg) => {
+                        // Try to parse as profile gossip message
+                        match crate::sync::ProfileGossipMessage::from_bytes(&msg.content) {
+                            Ok(profile_msg) => {
+                                match profile_msg {
+                                    crate::sync::ProfileGossipMessage::Announce {
+                                        signed_profile,
+                                        avatar_ticket,
+                                    } => {
+                                        // Verify signature
+                                        if !signed_profile.verify() {
+                                            warn!("Received profile announcement with invalid signature");
+                                            continue;
+                                        }
+
+                                        let signer_did = signed_profile.did().to_string();
+
+                                        // Check if we should auto-pin (is a contact)
+                                        if let Ok(contacts) = storage.list_contacts() {
+                                            let is_contact = contacts.iter().any(|c| c.peer_did == signer_did);
+
+                                            if is_contact {
+                                                // Parse avatar ticket and check if we need to download
+                                                let avatar_hash = if let Some(ticket_str) = &avatar_ticket {
+                                                    if let Ok(blob_ticket) = ticket_str.parse::<iroh_blobs::ticket::BlobTicket>() {
+                                                        let hash = blob_ticket.hash();
+
+                                                        // Check if we already have this avatar
+                                                        let has_blob = blob_manager.has_blob(&hash).await.unwrap_or(false);
+
+                                                        if !has_blob {
+                                                            // Download avatar via P2P
+                                                            match blob_manager.download_blob(&blob_ticket, &endpoint).await {
+                                                                Ok(downloaded_hash) => {
+                                                                    info!(did = %signer_did, ?downloaded_hash, "Downloaded avatar via P2P");
+                                                                }
+                                                                Err(e) => {
+                                                                    warn!(did = %signer_did, error = %e, "Failed to download avatar via P2P");
+                                                                }
+                                                            }
+                                                        }
+
+                                                        Some(*hash.as_bytes())
+                                                    } else {
+                                                        None
+                                                    }
+                                                } else {
+                                                    None
+                                                };
+
+                                                // Check if already pinned
+                                                let is_new_pin = storage.load_pinned_profile(&signer_did)
+                                                    .ok()
+                                                    .flatten()
+                                                    .is_none();
+
+                                                if let Ok(Some(mut existing)) = storage.load_pinned_profile(&signer_did) {
+                                                    // Update existing pin
+                                                    if existing.update_profile(signed_profile.clone()) {
+                                                        if let Some(hash) = avatar_hash {
+                                                            existing.avatar_hash = Some(hash);
+                                                        }
+                                                        let _ = storage.save_pinned_profile(&existing);
+                                                        debug!(did = %signer_did, "Updated pinned profile from gossip");
+                                                    }
+                                                } else {
+                                                    // Create new pin
+                                                    let mut pin = crate::types::ProfilePin::new(
+                                                        signer_did.clone(),
+                                                        signed_profile.clone(),
+                                                        crate::types::PinRelationship::Contact,
+                                                    );
+                                                    if let Some(hash) = avatar_hash {
+                                                        pin.avatar_hash = Some(hash);
+                                                    }
+                                                    let config = crate::storage::PinningConfig::default();
+                                                    let _ = storage.save_pinned_profile_with_limits(&pin, &config);
+                                                    debug!(did = %signer_did, "Auto-pinned contact profile from gossip");
+                                                }
+
+                                                // Also update Peer.profile for UI display
+                                                // This keeps the unified Peer record in sync with profile changes
+                                                if let Ok(Some(mut peer)) = storage.load_peer_by_did(&signer_did) {
+                                                    peer.profile = Some(crate::types::ProfileSnapshot {
+                                                        display_name: signed_profile.profile.display_name.clone(),
+                                                        subtitle: signed_profile.profile.subtitle.clone(),
+                                                        avatar_blob_id: signed_profile.profile.avatar_blob_id.clone(),
+                                                        bio: crate::types::ProfileSnapshot::truncate_bio(&signed_profile.profile.bio),
+                                                    });
+                                                    if let Err(e) = storage.save_peer(&peer) {
+                                                        warn!(did = %signer_did, error = %e, "Failed to update Peer.profile");
+                                                    } else {
+                                                        info!(did = %signer_did, "Updated peer profile from topic");
+                                                        // Notify UI of profile update
+                                                        let _ = contact_event_tx.send(crate::sync::ContactEvent::ProfileUpdated {
+                                                            did: signer_did.clone(),
+                                                        });
+                                                    }
+                                                }
+
+                                                // Note: PinAcknowledgment removed - Indra's Net derives pinners from contacts
+                                                // In Indra's Net, contacts automatically mirror each other
+                                                if is_new_pin {
+                                                    debug!(target_did = %signer_did, "New profile pinned (implicit mirroring)");
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    // Note: PinAcknowledgment and PinRemoval handlers removed
+                                    // Indra's Net derives pinners from contacts instead
+
+                                    // Request and Response are for direct profile lookups
+                                    // They're handled by the profile processor in a different flow
+                                    crate::sync::ProfileGossipMessage::Request { .. } => {
+                                        debug!("Received profile request (handled by processor)");
+                                    }
+                                    crate::sync::ProfileGossipMessage::Response { .. } => {
+                                        debug!("Received profile response (handled by processor)");
+                                    }
+
+                                    // Packet from a profile's append-only log
+                                    crate::sync::ProfileGossipMessage::Packet { envelope } => {
+                                        let sender_did = envelope.sender.to_string();
+                                        debug!(
+                                            sender = %sender_did,
+                                            sequence = envelope.sequence,
+                                            "Received packet from gossip"
+                                        );
+
+                                        // Check if sender is a contact (we mirror contacts)
+                                        let should_mirror = storage.list_contacts()
+                                            .map(|contacts| contacts.iter().any(|c| c.peer_did == sender_did))
+                                            .unwrap_or(false);
+
+                                        if should_mirror {
+                                            // Create MirrorStore and store the packet once it verifies
+                                            let mirror = MirrorStore::new(storage.backend());
+                                            let own_keys = storage.load_profile_keys().ok().flatten();
+                                            match packet_gate.admit(&storage, &mirror, own_keys.as_ref(), &envelope) {
+                                                Ok(None) => {}
+                                                Ok(Some(trust)) => {
+                                                    info!(
+                                                        sender = %sender_did,
+                                                        sequence = envelope.sequence,
+                                                        ?trust,
+                                                        "Stored packet in mirror"
+                                                    );
+                                                }
+                                                Err(e) => {
+                                                    warn!(
+                                                        sender = %sender_did,
+                                                        sequence = envelope.sequence,
+                                                        error = %e,
+                                                        "Failed to store packet in mirror"
+                                                    );
+                                                }
+                                            }
+                                        } else {
+                                            debug!(
+                                                sender = %sender_did,
+                                                "Ignoring packet from non-contact"
+                                            );
+                                        }
+                                    }
+
+                                    // MeshUpdate messages are handled by contact topic listeners,
+                                    // not the global profile topic. Ignore them here.
+                                    crate::sync::ProfileGossipMessage::MeshUpdate { sender_did, .. } => {
+                                        debug!(
+                                            sender = %sender_did,
+                                            "MeshUpdate received on global topic (handled in contact topic)"
+                                        );
+                                    }
+
+                                    // Log catch-up only runs between contacts on their 1:1 topic
+                                    crate::sync::ProfileGossipMessage::LogSync { .. } => {
+                                        debug!("LogSync received on global topic (handled in contact topic)");
+                                    }
+
+                                    // Device lists are only exchanged with contacts
+                                    crate::sync::ProfileGossipMessage::Devices { .. } => {
+                                        debug!("Devices received on global topic (handled in contact topic)");
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                debug!("Failed to parse profile message: {}", e);
+                            }
+                        }
+                    }
+                    TopicEvent::NeighborUp(peer) => {
+                        debug!(?peer, "Profile topic neighbor joined");
+                        let _ = reannounce_tx.send(());
+                    }
+                    TopicEvent::NeighborDown(peer) => {
+                        debug!(?peer, "Profile topic neighbor left");
+                    }
+                }
+            }
+
+            info!("Profile sync listener stopped");
+        });
+
+        info!("Profile sync started on global topic");
+        Ok(())
+    }
+
+    /// Spawn the task that re-announces our profile when profile topic neighbors join.
+    ///
+    /// Each `()` sent on the returned channel is one `NeighborUp`. Announcements
+    /// are debounced to at most one per [`PROFILE_REANNOUNCE_WINDOW`], and carry
+    /// our latest signed own pin, so nothing is sent until the profile has been
+    /// signed at least once.
+    ///
+    /// [`PROFILE_REANNOUNCE_WINDOW`]: crate::sync::PROFILE_REANNOUNCE_WINDOW
+    fn start_profile_reannouncer(
+        storage: Storage,
+        senders: Vec<TopicSender>,
+    ) -> tokio::sync::mpsc::UnboundedSender<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+
+        tokio::spawn(async move {
+            let mut debouncer = crate::sync::AnnounceDebouncer::new(crate::sync::PROFILE_REANNOUNCE_WINDOW);
+
+            loop {
+                let announce = match debouncer.deadline() {
+                    Some(deadline) => tokio::select! {
+                        joined = rx.recv() => match joined {
+                            Some(()) => debouncer.neighbor_up(std::time::Instant::now()),
+                            None => break,
+                        },
+                        _ = tokio::time::sleep_until(deadline.into()) => {
+                            debouncer.poll_deadline(std::time::Instant::now())
+                        }
+                    },
+                    None => match rx.recv().await {
+                        Some(()) => debouncer.neighbor_up(std::time::Instant::now()),
+                        None => break,
+                    },
+                };
+                if !announce {
+                    continue;
+                }
+
+                let pin = match storage.get_own_pinned_profile() {
+                    Ok(Some(pin)) => pin,
+                    Ok(None) => {
+                        debug!("No signed profile yet, skipping re-announce");
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Failed to load own profile for re-announce");
+                        continue;
+                    }
+                };
+                let bytes = match crate::sync::ProfileGossipMessage::announce(pin.signed_profile, None).to_bytes() {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        warn!(error = %e, "Failed to encode profile re-announce");
+                        continue;
+                    }
+                };
+                for sender in &senders {
+                    if let Err(e) = sender.broadcast(bytes.clone()).await {
+                        debug!(error = %e, "Failed to re-announce profile (non-fatal)");
+                    }
+                }
+                debug!("Re-announced profile to new neighbors");
+            }
+        });
+
+        tx
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Contact Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Generate a contact invite for sharing with peers
+    ///
+    /// Creates a signed invite containing profile information that can be shared
+    /// via QR code or text. The invite expires after the specified number of hours.
+    ///
+    /// # Arguments
+    ///
+    /// * `expiry_hours` - Hours until invite expires (max 168 = 7 days)
+    ///
+    /// # Returns
+    ///
+    /// A base58-encoded invite string prefixed with "sync-contact:"
+    ///
+    /// # Errors
+    ///
+    /// Returns error if contact manager initialization fails or profile cannot be loaded.
+    pub async fn generate_contact_invite(
+        &mut self,
+        expiry_hours: u8,
+    ) -> Result<String, SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Get own profile to include in invite
+        let profile = self.get_own_profile()?;
+
+        // Create profile snapshot
+        let snapshot = ProfileSnapshot {
+            display_name: profile.display_name.clone(),
+            subtitle: profile.subtitle.clone(),
+            avatar_blob_id: profile.avatar_blob_id.clone(),
+            bio: ProfileSnapshot::truncate_bio(&profile.bio),
+        };
+
+        // Generate invite
+        manager.generate_invite(snapshot, expiry_hours)
+    }
+
+    /// Generate a contact invite with a greeting for the recipient
+    ///
+    /// The greeting is shown when the invite is opened and stays on the
+    /// recipient's pending request until the contact is accepted. A realm
+    /// can be shared as with [`Self::generate_contact_invite_with_realm`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` if the greeting is longer than
+    /// [`MAX_INVITE_GREETING_CHARS`](crate::types::contact::MAX_INVITE_GREETING_CHARS)
+    /// characters, or any error from generating the realm invite.
+    pub async fn generate_contact_invite_with_greeting(
+        &mut self,
+        expiry_hours: u8,
+        greeting: String,
+        realm_id: Option<&RealmId>,
+    ) -> Result<String, SyncError> {
+        let realm_ticket = match realm_id {
+            Some(realm_id) => {
+                let expires_at =
+                    chrono::Utc::now().timestamp() + expiry_hours.min(168) as i64 * 3600;
+                Some(
+                    self.generate_invite(realm_id)
+                        .await?
+                        .with_expiry(expires_at)
+                        .encode()?,
+                )
+            }
+            None => None,
+        };
+
+        let manager = self.ensure_contact_manager().await?;
+        let profile = self.get_own_profile()?;
+        let snapshot = ProfileSnapshot {
+            display_name: profile.display_name.clone(),
+            subtitle: profile.subtitle.clone(),
+            avatar_blob_id: profile.avatar_blob_id.clone(),
+            bio: ProfileSnapshot::truncate_bio(&profile.bio),
+        };
+
+        manager.generate_invite_with_greeting(snapshot, expiry_hours, realm_ticket, greeting)
+    }
+
+    /// Generate a contact invite that also shares a realm
+    ///
+    /// Accepting the invite with [`Self::accept_contact_invite`] sends the
+    /// contact request and joins the realm in one step. The bundled realm
+    /// ticket expires together with the contact invite.
+    ///
+    /// # Arguments
+    ///
+    /// * `expiry_hours` - Hours until invite expires (max 168 = 7 days)
+    /// * `realm_id` - Realm to share with the new contact
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::PrivateRealmOperation` for the Private realm, or
+    /// any error from generating the realm or contact invite.
+    pub async fn generate_contact_invite_with_realm(
+        &mut self,
+        expiry_hours: u8,
+        realm_id: &RealmId,
+    ) -> Result<String, SyncError> {
+        let expires_at = chrono::Utc::now().timestamp() + expiry_hours.min(168) as i64 * 3600;
+        let realm_ticket = self
+            .generate_invite(realm_id)
+            .await?
+            .with_expiry(expires_at)
+            .encode()?;
+
+        let manager = self.ensure_contact_manager().await?;
+        let profile = self.get_own_profile()?;
+        let snapshot = ProfileSnapshot {
+            display_name: profile.display_name.clone(),
+            subtitle: profile.subtitle.clone(),
+            avatar_blob_id: profile.avatar_blob_id.clone(),
+            bio: ProfileSnapshot::truncate_bio(&profile.bio),
+        };
+
+        manager.generate_invite_with_realm(snapshot, expiry_hours, realm_ticket)
+    }
+
+    /// Accept a decoded contact invite
+    ///
+    /// Sends the contact request and, if the invite bundles a realm ticket,
+    /// joins that realm. A bundled realm that can't be joined (expired
+    /// ticket, already a member, ...) doesn't fail the contact request; it is
+    /// reported in [`ContactInviteAcceptance::realm_skipped`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the contact request cannot be sent.
+    pub async fn accept_contact_invite(
+        &mut self,
+        invite: HybridContactInvite,
+    ) -> Result<ContactInviteAcceptance, SyncError> {
+        let inviter_did = invite.inviter_did.clone();
+        let inviter_name = invite.display_name.clone();
+        let realm_ticket = invite.realm_ticket.clone();
+
+        self.send_contact_request(invite).await?;
+
+        let mut acceptance = ContactInviteAcceptance {
+            inviter_did,
+            inviter_name,
+            joined_realm: None,
+            realm_skipped: None,
+        };
+
+        if let Some(ticket_str) = realm_ticket {
+            let joined = match InviteTicket::decode(&ticket_str) {
+                Ok(ticket) if ticket.is_expired() => {
+                    Err(SyncError::InvalidInvite("Realm invite has expired".to_string()))
+                }
+                Ok(ticket) => self.join_via_invite(&ticket).await,
+                Err(e) => Err(e),
+            };
+            match joined {
+                Ok(realm_id) => acceptance.joined_realm = Some(realm_id),
+                Err(e) => {
+                    warn!(
+                        inviter = %acceptance.inviter_did,
+                        error = %e,
+                        "Contact accepted but bundled realm was skipped"
+                    );
+                    acceptance.realm_skipped = Some(e.to_string());
+                }
+            }
+        }
+
+        Ok(acceptance)
+    }
+
+    /// Decode a contact invite string
+    ///
+    /// Validates the invite signature, checks expiry, and verifies it hasn't been revoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `invite_str` - The invite code (e.g., "sync-contact:...")
+    ///
+    /// # Returns
+    ///
+    /// The decoded and validated invite with profile information
+    ///
+    /// # Errors
+    ///
+    /// Returns error if invite is invalid, expired, or revoked.
+    pub async fn decode_contact_invite(
+        &mut self,
+        invite_str: &str,
+    ) -> Result<HybridContactInvite, SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Decode invite
+        manager.decode_invite(invite_str)
+    }
+
+    /// Send a contact request to a peer
+    ///
+    /// Initiates the contact request protocol by sending a request to the inviter.
+    /// The request will be saved as OutgoingPending until the peer responds.
+    ///
+    /// # Arguments
+    ///
+    /// * `invite` - The decoded contact invite
+    ///
+    /// # Errors
+    ///
+    /// Returns error if contact manager initialization fails or request cannot be sent.
+    pub async fn send_contact_request(
+        &mut self,
+        invite: HybridContactInvite,
+    ) -> Result<(), SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Get own profile to include in request
+        let profile = self.get_own_profile()?;
+
+        // Create profile snapshot
+        let snapshot = ProfileSnapshot {
+            display_name: profile.display_name.clone(),
+            subtitle: profile.subtitle.clone(),
+            avatar_blob_id: profile.avatar_blob_id.clone(),
+            bio: ProfileSnapshot::truncate_bio(&profile.bio),
+        };
+
+        // Send contact request
+        manager.send_contact_request(invite, snapshot).await
+    }
+
+    /// Accept an incoming contact request
+    ///
+    /// Accepts a pending contact request and finalizes the connection if both
+    /// parties have accepted. Once mutually accepted, the contact is saved and
+    /// both nodes subscribe to a shared 1:1 gossip topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `invite_id` - The unique invite ID from the pending request
+    ///
+    /// # Errors
+    ///
+    /// Returns error if invite_id not found or contact finalization fails.
+    pub async fn accept_contact(&mut self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Accept contact request
+        manager.accept_contact_request(invite_id).await
+    }
+
+    /// Decline an incoming contact request
+    ///
+    /// Rejects a pending contact request and removes it from storage.
+    ///
+    /// # Arguments
+    ///
+    /// * `invite_id` - The unique invite ID from the pending request
+    ///
+    /// # Errors
+    ///
+    /// Returns error if invite_id not found.
+    pub async fn decline_contact(&mut self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Decline contact request
+        manager.decline_contact_request(invite_id).await
+    }
+
+    /// Cancel an outgoing contact request
+    ///
+    /// Removes the pending request and revokes the invite so it can no longer be used.
+    ///
+    /// # Arguments
+    ///
+    /// * `invite_id` - The unique invite ID of the outgoing request
+    ///
+    /// # Errors
+    ///
+    /// Returns error if invite_id not found or not in OutgoingPending state.
+    pub async fn cancel_outgoing_request(&mut self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Cancel outgoing request
+        manager.cancel_outgoing_request(invite_id)
+    }
+
+    /// Remove an accepted contact
+    ///
+    /// Leaves the 1:1 contact topic, deletes the contact and its pinned
+    /// profile, and forgets the per-sender packet state we kept for them.
+    /// Without their contact record we no longer hold their keys, so nothing
+    /// more can be sealed to or opened from them. With `keep_messages`, the
+    /// packets already mirrored from them stay readable in history.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::ContactNotFound` if `did` is not a contact.
+    pub async fn remove_contact(&mut self, did: &str, keep_messages: bool) -> Result<(), SyncError> {
+        let manager = self.ensure_contact_manager().await?;
+        manager.remove_contact(did).await?;
+
+        if let Some(pin) = self.storage.load_pinned_profile(did)? {
+            if !pin.is_own() {
+                self.storage.delete_pinned_profile(did)?;
+            }
+        }
+
+        if let Some(mut peer) = self.storage.load_peer_by_did(did)? {
+            if peer.contact_info.take().is_some() {
+                self.storage.save_peer(&peer)?;
+            }
+        }
+
+        self.packet_decryption_failures.remove(did);
+
+        if !keep_messages {
+            if let Some(mirror) = self.mirror_store.as_ref() {
+                mirror.delete_mirror(&Did::parse(did)?)?;
+            }
+        }
+
+        info!(did, keep_messages, "Removed contact");
+        Ok(())
+    }
+
+    /// List all accepted contacts
+    ///
+    /// Returns all contacts that have been mutually accepted, ordered by
+    /// [`ContactInfo::display_name`] (case-insensitive). With `favorites_first`, starred
+    /// contacts come before the rest, each group still in name order.
+    ///
+    /// # Returns
+    ///
+    /// Vector of all stored contacts with their online/offline status.
+    pub fn list_contacts(&self, favorites_first: bool) -> Result<Vec<ContactInfo>, SyncError> {
+        let mut contacts = self.storage.list_contacts()?;
+        contacts.sort_by_cached_key(|c| {
+            (
+                favorites_first && !c.is_favorite,
+                c.display_name().to_lowercase(),
+                c.peer_did.clone(),
+            )
+        });
+        Ok(contacts)
+    }
+
+    /// Star or unstar a contact
+    ///
+    /// Updates both the contact record and the unified peer entry so the
+    /// flag shows up in `list_contacts` and `list_peer_contacts` alike.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::ContactNotFound` if `did` is not a contact.
+    pub fn set_contact_favorite(&self, did: &str, favorite: bool) -> Result<(), SyncError> {
+        let mut contact = self
+            .storage
+            .load_contact(did)?
+            .ok_or_else(|| SyncError::ContactNotFound(did.to_string()))?;
+        contact.is_favorite = favorite;
+        self.storage.save_contact(&contact)?;
+
+        if let Some(mut peer) = self.storage.load_peer_by_did(did)? {
+            if let Some(details) = peer.contact_info.as_mut() {
+                details.is_favorite = favorite;
+                self.storage.save_peer(&peer)?;
+            }
+        }
+
+        debug!(did, favorite, "Updated contact favorite");
+        Ok(())
+    }
+
+    /// Set or clear (`None` or blank) our local alias for a contact
+    ///
+    /// The alias is shown instead of the contact's profile name in
+    /// conversations and contact lists; their profile is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::ContactNotFound` if `did` is not a contact.
+    pub fn set_contact_nickname(&self, did: &str, nickname: Option<String>) -> Result<(), SyncError> {
+        let nickname = nickname
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        let mut contact = self
+            .storage
+            .load_contact(did)?
+            .ok_or_else(|| SyncError::ContactNotFound(did.to_string()))?;
+        contact.nickname = nickname.clone();
+        self.storage.save_contact(&contact)?;
+
+        if let Some(mut peer) = self.storage.load_peer_by_did(did)? {
+            peer.nickname = nickname;
+            self.storage.save_peer(&peer)?;
+        }
+
+        debug!(did, "Updated contact nickname");
+        Ok(())
+    }
+
+    /// Accept contact requests from these DIDs automatically
+    ///
+    /// Replaces the allowlist, which persists across restarts; an empty
+    /// list turns auto-accept off. Accepted requests emit
+    /// `ContactEvent::AutoAccepted`. Requests from anyone else still wait
+    /// for [`Self::accept_contact`], unless they use one of our own invites.
+    pub fn set_contact_autoaccept(&self, dids: Vec<Did>) -> Result<(), SyncError> {
+        let dids: Vec<String> = dids.iter().map(|did| did.to_string()).collect();
+        self.storage.set_contact_autoaccept(&dids)?;
+        debug!(count = dids.len(), "Updated contact auto-accept allowlist");
+        Ok(())
+    }
+
+    /// DIDs whose contact requests are accepted automatically
+    pub fn contact_autoaccept(&self) -> Result<Vec<Did>, SyncError> {
+        self.storage
+            .list_contact_autoaccept()?
+            .iter()
+            .map(|did| Did::parse(did))
+            .collect()
+    }
+
+    /// Find profile display names shared by more than one contact
+    ///
+    /// Names are compared ignoring case and surrounding whitespace, since
+    /// either is enough to pass one contact off as another. Each entry is
+    /// the name as the first contact (by DID) spells it and the DIDs using
+    /// it, sorted; entries are sorted by name, ignoring case. Contacts we have nicknamed
+    /// are still reported, as their profile name is what others see.
+    pub fn find_name_collisions(&self) -> Result<Vec<(String, Vec<Did>)>, SyncError> {
+        let mut contacts = self.storage.list_contacts()?;
+        contacts.sort_by(|a, b| a.peer_did.cmp(&b.peer_did));
+
+        let mut by_name: BTreeMap<String, (String, Vec<Did>)> = BTreeMap::new();
+        for contact in contacts {
+            let name = contact.profile.display_name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let Ok(did) = Did::parse(&contact.peer_did) else {
+                continue;
+            };
+            by_name
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name.to_string(), Vec::new()))
+                .1
+                .push(did);
+        }
+
+        Ok(by_name
+            .into_values()
+            .filter(|(_, dids)| dids.len() > 1)
+            .collect())
+    }
+
+    /// Find contacts by display name or DID prefix, best matches first
+    ///
+    /// Names match case-insensitively and fuzzily (see
+    /// [`ContactInfo::match_score`]); ties are broken by name. An empty
+    /// query returns every contact.
+    pub fn search_contacts(&self, query: &str) -> Result<Vec<ContactInfo>, SyncError> {
+        let contacts = self.storage.list_contacts()?;
+        if query.trim().is_empty() {
+            return Ok(contacts);
+        }
+
+        let mut matches: Vec<(u32, ContactInfo)> = contacts
+            .into_iter()
+            .filter_map(|contact| Some((contact.match_score(query)?, contact)))
+            .collect();
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| a.profile.display_name.cmp(&b.profile.display_name))
+        });
+        Ok(matches.into_iter().map(|(_, contact)| contact).collect())
+    }
+
+    /// Export the contact list for moving to another device
+    ///
+    /// Each contact carries its pinned signed profile, nickname and pin
+    /// relationship. Topic and message keys are not exported.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Identity` if identity is not initialized.
+    pub fn export_contacts(&self) -> Result<ContactsBundle, SyncError> {
+        let exported_by = self
+            .did()
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?;
+
+        let contacts = self.storage.list_contacts()?;
+        let dids: Vec<&str> = contacts.iter().map(|c| c.peer_did.as_str()).collect();
+        let mut pins = self.storage.load_pinned_profiles(&dids)?;
+
+        let mut bundled = Vec::with_capacity(contacts.len());
+        for contact in contacts {
+            let pin = pins.remove(&contact.peer_did);
+            let nickname = match &contact.nickname {
+                Some(nickname) => Some(nickname.clone()),
+                None => self
+                    .storage
+                    .load_peer_by_did(&contact.peer_did)?
+                    .and_then(|peer| peer.nickname),
+            };
+            bundled.push(BundledContact {
+                relationship: pin
+                    .as_ref()
+                    .map_or(crate::types::PinRelationship::Contact, |p| p.relationship.clone()),
+                signed_profile: pin.map(|p| p.signed_profile),
+                did: contact.peer_did,
+                endpoint_id: contact.peer_endpoint_id,
+                node_addr: contact.node_addr,
+                profile: contact.profile,
+                nickname,
+                accepted_at: contact.accepted_at,
+                is_favorite: contact.is_favorite,
+                encryption_keys: contact.encryption_keys,
+            });
+        }
+
+        info!(contacts = bundled.len(), "Exported contacts");
+        Ok(ContactsBundle {
+            version: CONTACTS_BUNDLE_VERSION,
+            exported_by: exported_by.to_string(),
+            exported_by_profile: self.profile_did().map(|did| did.to_string()),
+            exported_at: chrono::Utc::now().timestamp(),
+            contacts: bundled,
+        })
+    }
+
+    /// Re-create contacts from an exported bundle
+    ///
+    /// Contacts are stored, and their profiles re-pinned, as if the contact
+    /// exchange had just finished, but nothing is sent: the 1:1 topics are
+    /// joined the next time contact sync starts. Existing contacts are left
+    /// alone. Contacts that need a fresh exchange before E2E messages work
+    /// are listed in [`ContactImportReport::needs_key_exchange`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Identity` if identity is not initialized,
+    /// `SyncError::InvalidOperation` for an unknown bundle version, or
+    /// `SyncError::SignatureInvalid` if a bundled profile fails verification
+    /// or belongs to another DID. Nothing is imported in those cases.
+    pub fn import_contacts(&self, bundle: &ContactsBundle) -> Result<ContactImportReport, SyncError> {
+        let our_did = self
+            .did()
+            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?
+            .to_string();
+        if bundle.version != CONTACTS_BUNDLE_VERSION {
+            return Err(SyncError::InvalidOperation(format!(
+                "Unsupported contacts bundle version {}",
+                bundle.version
+            )));
+        }
+        for entry in &bundle.contacts {
+            if let Some(signed) = &entry.signed_profile {
+                if !signed.verify() || signed.did().as_str() != entry.did {
+                    return Err(SyncError::SignatureInvalid(format!(
+                        "Bundled profile for {} failed verification",
+                        entry.did
+                    )));
+                }
+            }
+        }
+
+        // Contacts hold the exporter's keys; they only work here if we are the exporter
+        let same_keys = bundle.exported_by == our_did
+            && bundle.exported_by_profile == self.profile_did().map(|did| did.to_string());
+
+        let mut report = ContactImportReport::default();
+        for entry in &bundle.contacts {
+            if entry.did == our_did || self.storage.load_contact(&entry.did)?.is_some() {
+                report.skipped.push(entry.did.clone());
+                continue;
+            }
+
+            let contact_topic = ContactManager::derive_contact_topic(&our_did, &entry.did);
+            let contact_key = ContactManager::derive_contact_key(&our_did, &entry.did);
+            let contact = ContactInfo {
+                peer_did: entry.did.clone(),
+                peer_endpoint_id: entry.endpoint_id,
+                profile: entry.profile.clone(),
+                node_addr: entry.node_addr.clone(),
+                contact_topic,
+                contact_key,
+                accepted_at: entry.accepted_at,
+                last_seen: 0,
+                status: crate::types::contact::ContactStatus::Offline,
+                is_favorite: entry.is_favorite,
+                encryption_keys: entry.encryption_keys.clone(),
+                mutual_peers: Vec::new(),
+                nickname: entry.nickname.clone(),
+            };
+            self.storage.save_contact(&contact)?;
+
+            let endpoint_id = iroh::PublicKey::from_bytes(&entry.endpoint_id)
+                .map_err(|e| SyncError::Identity(format!("Invalid peer endpoint ID: {}", e)))?;
+            let mut peer = crate::types::peer::Peer::new(endpoint_id, crate::types::peer::PeerSource::FromContact)
+                .with_did(entry.did.clone())
+                .with_profile(entry.profile.clone())
+                .with_contact_info(crate::types::peer::ContactDetails {
+                    contact_topic,
+                    contact_key,
+                    accepted_at: entry.accepted_at,
+                    is_favorite: entry.is_favorite,
+                });
+            peer.nickname = entry.nickname.clone();
+            peer.node_addr = Some(entry.node_addr.clone());
+            peer.status = PeerStatus::Offline;
+            self.storage.save_peer(&peer)?;
+
+            if let Some(signed) = &entry.signed_profile {
+                let pin = crate::types::ProfilePin::new(
+                    entry.did.clone(),
+                    signed.clone(),
+                    entry.relationship.clone(),
+                );
+                self.storage.save_pinned_profile(&pin)?;
+            }
+
+            if !same_keys || entry.encryption_keys.is_none() {
+                report.needs_key_exchange.push(entry.did.clone());
+            }
+            report.imported.push(entry.did.clone());
+        }
+
+        info!(
+            imported = report.imported.len(),
+            skipped = report.skipped.len(),
+            needs_key_exchange = report.needs_key_exchange.len(),
+            "Imported contacts"
+        );
+        Ok(report)
+    }
+
+    /// Compute mutual peers dynamically for a given contact.
+    ///
+    /// Returns all contacts we share in common with the target peer.
+    /// This is computed dynamically rather than using stored values to avoid
+    /// race conditions during simultaneous contact creation in mesh topology.
+    ///
+    /// Used by:
+    /// - Relay routing (proactive relay to offline contacts)
+    /// - UI display (BioCard mutual contacts count)
+    ///
+    /// Since we can't know the target's full contact list, this uses a
+    /// simplified approach: any of OUR contacts (excluding the target) can
+    /// serve as potential mutual peers. For relay, the relay peer verifies
+    /// they know the target before forwarding.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_did` - The DID of the contact to compute mutual peers for
+    ///
+    /// # Returns
+    ///
+    /// Vector of DIDs representing mutual/potential relay peers
+    pub fn compute_mutual_peers_with(&self, target_did: &str) -> Vec<String> {
+        // Get all our contacts
+        let our_contacts = match self.storage.list_contacts() {
+            Ok(contacts) => contacts,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to list contacts for relay computation");
+                return Vec::new();
+            }
+        };
+
+        // Any of our contacts (except the target) can serve as potential relays.
+        // The relay peer will verify they have the target as a contact before forwarding.
+        our_contacts
+            .into_iter()
+            .map(|c| c.peer_did)
+            .filter(|did| did != target_did)
+            .collect()
+    }
+
+    /// List pending contact requests
+    ///
+    /// Returns both incoming (awaiting our response) and outgoing (awaiting their response)
+    /// pending contact requests.
+    ///
+    /// # Returns
+    ///
+    /// Tuple of (incoming_requests, outgoing_requests)
+    pub fn list_pending_contacts(&self) -> Result<(Vec<PendingContact>, Vec<PendingContact>), SyncError> {
+        let incoming = self.storage.list_incoming_pending()?;
+        let outgoing = self.storage.list_outgoing_pending()?;
+        Ok((incoming, outgoing))
+    }
+
+    /// Drop pending contact requests that have expired as of `now`
+    ///
+    /// A request expires [`PENDING_CONTACT_TTL_SECS`](crate::types::contact::PENDING_CONTACT_TTL_SECS)
+    /// after it was created. Invites behind expired outgoing requests are not
+    /// revoked, so the same invite can be sent again while it is still valid.
+    ///
+    /// # Returns
+    ///
+    /// The requests that were removed.
+    pub fn expire_stale_pending(&self, now: i64) -> Result<Vec<PendingContact>, SyncError> {
+        let expired = self.storage.delete_expired_pending(now)?;
+        if !expired.is_empty() {
+            info!(count = expired.len(), "Expired stale pending contact requests");
+        }
+        Ok(expired)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Unified Peer Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// List all unified peers
+    ///
+    /// Returns all known network participants, including both contacts
+    /// (mutually accepted peers with full identity) and discovered peers
+    /// (seen via realm gossip but not yet contacts).
+    pub fn list_peers(&self) -> Result<Vec<crate::types::peer::Peer>, SyncError> {
+        self.storage.list_peers()
+    }
+
+    /// Get a peer by endpoint ID
+    pub fn get_peer(&self, endpoint_id: &iroh::PublicKey) -> Result<Option<crate::types::peer::Peer>, SyncError> {
+        self.storage.load_peer(endpoint_id)
+    }
+
+    /// Get a peer by DID
+    ///
+    /// Uses the DID index for fast lookup.
+    pub fn get_peer_by_did(&self, did: &str) -> Result<Option<crate::types::peer::Peer>, SyncError> {
+        self.storage.load_peer_by_did(did)
+    }
+
+    /// List only contacts (peers with mutual acceptance)
+    ///
+    /// These are peers where `contact_info` is Some, meaning both parties
+    /// have accepted the contact exchange.
+    pub fn list_peer_contacts(&self) -> Result<Vec<crate::types::peer::Peer>, SyncError> {
+        self.storage.list_peer_contacts()
+    }
+
+    /// List only discovered peers (non-contacts)
+    ///
+    /// These are peers seen via realm gossip but not yet mutually accepted
+    /// as contacts.
+    pub fn list_discovered_peers(&self) -> Result<Vec<crate::types::peer::Peer>, SyncError> {
+        self.storage.list_discovered_peers()
+    }
+
+    /// Save a unified peer
+    ///
+    /// Updates or creates a peer record. Also updates the DID index
+    /// if the peer has a DID.
+    pub fn save_peer(&self, peer: &crate::types::peer::Peer) -> Result<(), SyncError> {
+        self.storage.save_peer(peer)
+    }
+
+    /// Run migration from old contact/peer tables to unified peers
+    ///
+    /// This is idempotent - safe to call multiple times.
+    /// Returns the number of peers migrated.
+    pub fn migrate_to_unified_peers(&self) -> Result<usize, SyncError> {
+        self.storage.migrate_to_unified_peers()
+    }
+
+    /// Subscribe to contact events
+    ///
+    /// Returns a broadcast receiver for real-time contact event notifications.
+    /// Events include new requests, acceptances, online/offline status changes, etc.
+    ///
+    /// # Returns
+    ///
+    /// Broadcast receiver for ContactEvent messages
+    pub async fn subscribe_contact_events(
+        &mut self,
+    ) -> Result<broadcast::Receiver<ContactEvent>, SyncError> {
+        // Ensure contact manager is initialized
+        let manager = self.ensure_contact_manager().await?;
+
+        // Subscribe to events
+        Ok(manager.subscribe_events())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Packet Event API (for Indra's Network visualization)
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Get packet events for a specific peer.
+    ///
+    /// Returns the recent packet events associated with this peer,
+    /// useful for displaying packet flow in the network visualization.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_did` - The peer's decentralized identifier
+    ///
+    /// # Returns
+    ///
+    /// A vector of [`PacketEvent`] in chronological order (oldest first).
+    pub fn get_packet_events_for_peer(&self, peer_did: &str) -> Vec<crate::sync::PacketEvent> {
+        let events = self.packet_event_buffer.get_events_for_peer(peer_did);
+        info!(
+            peer_did = %peer_did,
+            event_count = events.len(),
+            total_in_buffer = self.packet_event_buffer.total_event_count(),
+            "Getting packet events for peer"
+        );
+        events
+    }
+
+    /// Get all packet events for all peers.
+    ///
+    /// Returns a map of peer DID → events for the network visualization.
+    pub fn get_all_packet_events(
+        &self,
+    ) -> std::collections::HashMap<String, Vec<crate::sync::PacketEvent>> {
+        self.packet_event_buffer.get_all_events()
+    }
+
+    /// Query buffered packet events across all peers.
+    ///
+    /// Returns the events matching `filter` (direction, peer, time range) in
+    /// chronological order. The buffer keeps a bounded number of events per
+    /// peer; see [`packet_events_dropped`](Self::packet_events_dropped) for
+    /// how many were evicted.
+    pub fn packet_events(
+        &self,
+        filter: crate::sync::PacketEventFilter,
+    ) -> Vec<crate::sync::PacketEvent> {
+        self.packet_event_buffer.query(&filter)
+    }
+
+    /// Number of packet events evicted because a peer's buffer was full.
+    pub fn packet_events_dropped(&self) -> u64 {
+        self.packet_event_buffer.dropped_count()
+    }
+
+    /// Export all buffered packet events as JSON.
+    ///
+    /// The document has the shape `{"dropped": n, "events": [...]}`, with
+    /// events oldest first, for inspection by external tools.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if encoding fails.
+    pub fn export_packet_events_json(&self) -> Result<String, SyncError> {
+        serde_json::to_string_pretty(&self.packet_event_buffer.export())
+            .map_err(|e| SyncError::Serialization(e.to_string()))
+    }
+
+    /// Subscribe to real-time packet events.
+    ///
+    /// Returns a receiver that will receive all new packet events as they occur.
+    /// Useful for updating the UI in real-time as packets flow through the network.
+    pub fn subscribe_packet_events(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<crate::sync::PacketEvent> {
+        self.packet_event_buffer.subscribe()
+    }
+
+    /// Record a packet event (internal use).
+    ///
+    /// This is called when packets are sent or received to log them
+    /// for the network visualization.
+    pub(crate) fn record_packet_event(&self, event: crate::sync::PacketEvent) {
+        self.packet_event_buffer.record(event);
+    }
+
+    /// Get the packet event buffer (for sharing with other components).
+    pub(crate) fn packet_event_buffer(&self) -> Arc<crate::sync::PacketEventBuffer> {
+        self.packet_event_buffer.clone()
+    }
+
+    /// Load historical packet events from storage into the in-memory buffer.
+    ///
+    /// This method populates the packet event buffer with packets that were
+    /// stored in MirrorStore (received) and ProfileLog (sent) from previous
+    /// sessions. Call this on startup to show historical packets in the
+    /// network visualization.
+    ///
+    /// # Returns
+    ///
+    /// The number of events loaded into the buffer.
+    pub fn load_historical_packet_events(&self) -> Result<usize, SyncError> {
+        let mut loaded_count = 0;
+
+        // Get our DID for identifying sent messages
+        let my_did = self.profile_did()
+            .ok_or_else(|| SyncError::Identity("Profile keys not initialized".to_string()))?;
+        let my_did_str = my_did.as_str().to_string();
+
+        // Get profile keys for decryption
+        let profile_keys = self.storage.load_profile_keys()?;
+
+        // Get all contacts
+        let contacts = self.list_peer_contacts()?;
+
+        for contact in &contacts {
+            let contact_did = match &contact.did {
+                Some(did) => did.clone(),
+                None => continue,
+            };
+            let contact_name = contact.display_name();
+
+            // Parse contact DID
+            let did = match Did::parse(&contact_did) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+
+            // Load received packets from MirrorStore for this contact
+            if let Ok(received_packets) = self.mirror_packets_all(&did) {
+                for envelope in received_packets {
+                    let sender_did = envelope.sender.as_str().to_string();
+
+                    // Determine decryption status and content preview
+                    let realm_key = self.group_packet_key(&envelope);
+                    let (decryption_status, payload) = crate::sync::DecryptionStatus::classify(
+                        &envelope,
+                        profile_keys.as_ref(),
+                        realm_key.as_ref(),
+                    );
+                    let content_preview =
+                        crate::sync::PacketEvent::preview_for(&decryption_status, payload.as_ref());
+
+                    let event = crate::sync::PacketEvent {
+                        id: crate::sync::PacketEvent::make_id(&sender_did, envelope.sequence),
+                        timestamp: chrono::Utc::now().timestamp_millis(), // Historical, use now
+                        direction: crate::sync::PacketDirection::Incoming,
+                        sequence: envelope.sequence,
+                        author_did: sender_did,
+                        author_name: contact_name.clone(),
+                        relay_did: None,
+                        relay_name: None,
+                        destination_did: my_did_str.clone(),
+                        destination_name: "Me".to_string(),
+                        decryption_status,
+                        content_preview,
+                        is_delivered: false,
+                        peer_did: contact_did.clone(),
+                    };
+                    self.packet_event_buffer.record(event);
+                    loaded_count += 1;
+                }
+            }
+
+            // Load sent packets from ProfileLog for this contact
+            if let Some(ref log) = self.profile_log {
+                for entry in log.entries_ordered() {
+                    // Check if this packet is addressed to this contact
+                    if let Some(payload) = self.decrypt_packet(&entry.envelope) {
+                        if let PacketPayload::DirectMessage { ref recipient, content, .. } = payload {
+                            if recipient == &did {
+                                let event = crate::sync::PacketEvent {
+                                    id: crate::sync::PacketEvent::make_id(&my_did_str, entry.envelope.sequence),
+                                    timestamp: chrono::Utc::now().timestamp_millis(),
+                                    direction: crate::sync::PacketDirection::Outgoing,
+                                    sequence: entry.envelope.sequence,
+                                    author_did: my_did_str.clone(),
+                                    author_name: "Me".to_string(),
+                                    relay_did: None,
+                                    relay_name: None,
+                                    destination_did: contact_did.clone(),
+                                    destination_name: contact_name.clone(),
+                                    decryption_status: crate::sync::DecryptionStatus::Decrypted,
+                                    content_preview: crate::sync::PacketEvent::preview_content(&content),
+                                    is_delivered: false,
+                                    peer_did: contact_did.clone(),
+                                };
+                                self.packet_event_buffer.record(event);
+                                loaded_count += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        info!(
+            loaded_count,
+            contact_count = contacts.len(),
+            "Loaded historical packet events from storage"
+        );
+
+        Ok(loaded_count)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Chat API
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Send a direct message to a contact.
+    ///
+    /// Creates a DirectMessage packet, encrypts it for the recipient,
+    /// and broadcasts it via the 1:1 contact gossip topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `contact_did` - The recipient's decentralized identifier
+    /// * `content` - The message content to send
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the sent packet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Profile keys not initialized
+    /// - Contact not found or keys not exchanged
+    /// - Network error during broadcast
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let seq = engine.send_message("did:sync:friend", "Hello!").await?;
+    /// println!("Sent message with sequence {}", seq);
+    /// ```
+    pub async fn send_message(&mut self, contact_did: &str, content: &str) -> Result<u64, SyncError> {
+        let did = Did::parse(contact_did)?;
+
+        let payload = PacketPayload::DirectMessage {
+            content: content.to_string(),
+            recipient: did.clone(),
+        };
+
+        let address = PacketAddress::Individual(did);
+
+        self.create_and_broadcast_packet(payload, address).await
+    }
+
+    /// Thank a contact, optionally for a particular task.
+    ///
+    /// Sends a signed [`PacketPayload::Gratitude`] sealed to the contact on
+    /// our 1:1 topic. When we aren't networking the packet stays in our log
+    /// and the contact picks it up when they next sync it.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the gratitude packet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::ContactKeyExchangeIncomplete` if we don't have the
+    /// contact's keys to seal the packet to.
+    pub async fn send_gratitude(
+        &mut self,
+        contact_did: &Did,
+        for_task: Option<TaskId>,
+        message: &str,
+    ) -> Result<u64, SyncError> {
+        let payload = PacketPayload::Gratitude {
+            for_task,
+            message: message.to_string(),
+            recipient: contact_did.clone(),
+        };
+        let address = PacketAddress::Individual(contact_did.clone());
+        let seq = self.create_packet(payload, address.clone())?;
+        if let Err(e) = self.broadcast_packet(seq, &address).await {
+            debug!(contact = %contact_did, error = %e, "Gratitude kept in log for later sync");
+        }
+        Ok(seq)
+    }
+
+    /// Number of times `did` has thanked us.
+    pub fn gratitude_received(&self, did: &Did) -> usize {
+        let Some(me) = self.profile_did() else {
+            return 0;
+        };
+        self.mirror_packets_all(did)
+            .unwrap_or_default()
+            .iter()
+            .filter(|envelope| {
+                matches!(
+                    self.decrypt_packet(envelope),
+                    Some(PacketPayload::Gratitude { recipient, .. }) if recipient == me
+                )
+            })
+            .count()
+    }
+
+    /// Number of times we have thanked `did`.
+    pub fn gratitude_given(&self, did: &Did) -> usize {
+        let Some(log) = self.profile_log.as_ref() else {
+            return 0;
+        };
+        log.entries_ordered()
+            .into_iter()
+            .filter(|entry| {
+                matches!(
+                    self.decrypt_packet(&entry.envelope),
+                    Some(PacketPayload::Gratitude { recipient, .. }) if &recipient == did
+                )
+            })
+            .count()
+    }
+
+    /// Get conversation with a specific contact.
+    ///
+    /// Loads all messages exchanged with the contact (both sent and received)
+    /// and returns them as a [`Conversation`].
+    ///
+    /// # Arguments
+    ///
+    /// * `contact_did` - The contact's decentralized identifier
+    ///
+    /// # Returns
+    ///
+    /// A [`Conversation`] containing all messages with this contact.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let convo = engine.get_conversation("did:sync:friend")?;
+    /// println!("Messages with {}: {}", convo.display_name(), convo.len());
+    /// for msg in convo.messages() {
+    ///     println!("{}: {}", msg.display_sender(), msg.content);
+    /// }
+    /// ```
+    pub fn get_conversation(&self, contact_did: &str) -> Result<crate::chat::Conversation, SyncError> {
+        let did = Did::parse(contact_did)?;
+
+        // Get contact info for display name
+        let peer = self.get_peer_by_did(contact_did)?;
+        let contact_name = crate::chat::get_contact_display_name(peer.as_ref());
+
+        // Get our DID for identifying sent messages
+        let my_did = self.profile_did()
+            .ok_or_else(|| SyncError::Identity("Profile keys not initialized".to_string()))?;
+        let my_did_str = my_did.as_str().to_string();
+
+        // Load received packets from this contact
+        // DIAGNOSTIC: Use info level to trace message retrieval
+        info!(
+            contact_did = %contact_did,
+            did_parsed = %did.as_str(),
+            did_bytes = ?did.as_str().as_bytes(),
+            "get_conversation: loading packets from MirrorStore"
+        );
+
+        // Use mirror_packets_all() instead of mirror_packets_since(&did, 0)
+        // because get_since(0) excludes sequence 0 (off-by-one bug fix)
+        let received_packets = match self.mirror_packets_all(&did) {
+            Ok(packets) => {
+                info!(
+                    contact_did = %contact_did,
+                    did_parsed = %did.as_str(),
+                    packet_count = packets.len(),
+                    "get_conversation: loaded {} received packets from MirrorStore",
+                    packets.len()
+                );
+                packets
+            }
+            Err(e) => {
+                warn!(
+                    contact_did = %contact_did,
+                    did_parsed = %did.as_str(),
+                    error = %e,
+                    "get_conversation: FAILED to load received packets from MirrorStore"
+                );
+                vec![]
+            }
+        };
+
+        // Load ALL sent packets first (can't borrow self inside closure due to borrow checker)
+        let mut all_sent_packets: Vec<PacketEnvelope> = self
+            .profile_log
+            .as_ref()
+            .map(|log| {
+                log.entries_ordered()
+                    .into_iter()
+                    .map(|entry| entry.envelope.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Include what our other devices sent, as far as we have mirrored it
+        if let Ok(packets) = self.mirror_packets_all(&my_did) {
+            all_sent_packets.extend(packets.into_iter().filter(|p| p.device != self.device_id));
+        }
+
+        // Filter to only DirectMessages addressed to this contact by checking payload recipient
+        // NOTE: We can't use is_addressed_to() because global packets (sealed_keys empty)
+        // return true for ALL DIDs. Instead, we decrypt and check the actual recipient field.
+        let sent_packets: Vec<PacketEnvelope> = all_sent_packets
+            .into_iter()
+            .filter(|envelope| {
+                if let Some(payload) = self.decrypt_packet(envelope) {
+                    if let PacketPayload::DirectMessage { ref recipient, .. } = payload {
+                        return recipient == &did;
+                    }
+                }
+                false
+            })
+            .collect();
+
+        // DIAGNOSTIC: Summary at info level for easy debugging
+        info!(
+            contact_did = %contact_did,
+            total_in_profile_log = self.profile_log.as_ref().map(|l| l.len()).unwrap_or(0),
+            sent_to_this_contact = sent_packets.len(),
+            received_from_this_contact = received_packets.len(),
+            "get_conversation: SUMMARY - sent={}, received={}",
+            sent_packets.len(),
+            received_packets.len()
+        );
+
+        // Build conversation using the helper
+        let conversation = crate::chat::build_conversation(
+            contact_did,
+            contact_name,
+            received_packets,
+            sent_packets,
+            &my_did_str,
+            |envelope| self.decrypt_packet(envelope),
+            |handle| self.resolve_mention(handle),
+        );
+
+        Ok(conversation)
+    }
+
+    /// List all conversations sorted by last activity.
+    ///
+    /// Returns conversations with all contacts who have exchanged messages,
+    /// sorted with most recent activity first.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let conversations = engine.list_conversations()?;
+    /// for convo in conversations {
+    ///     if let Some(preview) = convo.preview(50) {
+    ///         println!("{}: {} - \"{}\"",
+    ///             convo.display_name(),
+    ///             convo.last_message().map(|m| m.relative_time()).unwrap_or_default(),
+    ///             preview
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    pub fn list_conversations(&self) -> Result<Vec<crate::chat::Conversation>, SyncError> {
+        // Get all contacts
+        let contacts = self.list_peer_contacts()?;
+
+        let mut conversations = Vec::new();
+
+        for contact in contacts {
+            // Get contact DID from the peer's did field
+            if let Some(ref did) = contact.did {
+                if let Ok(convo) = self.get_conversation(did) {
+                    // Only include conversations with messages
+                    if !convo.is_empty() {
+                        conversations.push(convo);
+                    }
+                }
+            }
+        }
+
+        // Sort by last activity (most recent first)
+        conversations.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+
+        Ok(conversations)
+    }
+
+    /// Get new messages from a contact since a specific sequence.
+    ///
+    /// Useful for polling for updates in UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `contact_did` - The contact's DID
+    /// * `since_seq` - Return messages with sequence > this value
+    ///
+    /// # Returns
+    ///
+    /// Vector of new messages since the given sequence.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut last_seq = 0;
+    /// loop {
+    ///     let new_msgs = engine.get_new_messages("did:sync:friend", last_seq)?;
+    ///     for msg in &new_msgs {
+    ///         println!("New: {}", msg.content);
+    ///         if msg.sequence > last_seq {
+    ///             last_seq = msg.sequence;
+    ///         }
+    ///     }
+    ///     tokio::time::sleep(Duration::from_secs(1)).await;
+    /// }
+    /// ```
+    pub fn get_new_messages(
+        &self,
+        contact_did: &str,
+        since_seq: u64,
+    ) -> Result<Vec<crate::chat::ChatMessage>, SyncError> {
+        let did = Did::parse(contact_did)?;
+
+        // Get contact info for display name
+        let peer = self.get_peer_by_did(contact_did)?;
+        let contact_name = crate::chat::get_contact_display_name(peer.as_ref());
+
+        // Get our DID
+        let my_did = self.profile_did()
+            .ok_or_else(|| SyncError::Identity("Profile keys not initialized".to_string()))?;
+        let my_did_str = my_did.as_str().to_string();
+
+        // Get packets since the sequence
+        let packets = self.mirror_packets_since(&did, since_seq)?;
+
+        // Convert to ChatMessages
+        let messages: Vec<crate::chat::ChatMessage> = packets
+            .iter()
+            .filter_map(|envelope| {
+                self.decrypt_packet(envelope).and_then(|payload| {
+                    crate::chat::extract_chat_message(
+                        envelope,
+                        &payload,
+                        &my_did_str,
+                        contact_name.clone(),
+                    )
+                })
+            })
+            .map(|msg| msg.with_mentions(|handle| self.resolve_mention(handle)))
+            .collect();
+
+        Ok(messages)
+    }
+
+    /// Get unread message count across all conversations.
+    ///
+    /// Returns the total number of unread messages (messages received
+    /// after our last reply in each conversation).
+    pub fn total_unread_count(&self) -> Result<usize, SyncError> {
+        let conversations = self.list_conversations()?;
+        Ok(conversations.iter().map(|c| c.unread_count()).sum())
+    }
+
+    /// Write a transcript of the conversation with a contact to `writer`.
+    ///
+    /// Messages are written oldest first, one at a time, so exporting a long
+    /// conversation to a file doesn't build the whole transcript in memory.
+    /// Returns the number of messages written.
+    pub fn export_conversation_to<W: std::io::Write>(
+        &self,
+        contact_did: &str,
+        format: crate::chat::TranscriptFormat,
+        writer: &mut W,
+    ) -> Result<usize, SyncError> {
+        let conversation = self.get_conversation(contact_did)?;
+        Ok(crate::chat::write_transcript(&conversation, format, writer)?)
+    }
+
+    /// A transcript of the conversation with a contact, for archiving.
+    ///
+    /// See [`Self::export_conversation_to`] to write straight to a file.
+    pub fn export_conversation(
+        &self,
+        contact_did: &str,
+        format: crate::chat::TranscriptFormat,
+    ) -> Result<String, SyncError> {
+        let mut transcript = Vec::new();
+        self.export_conversation_to(contact_did, format, &mut transcript)?;
+        Ok(String::from_utf8_lossy(&transcript).into_owned())
+    }
+
+    /// The DID an `@` handle in a chat message refers to, if any.
+    ///
+    /// A handle is either a DID or a name: a contact's nickname or profile
+    /// display name, or our own display name. DIDs only count when they
+    /// are ours or a contact's. Names are matched case-insensitively.
+    pub fn resolve_mention(&self, handle: &str) -> Option<Did> {
+        let my_did = self.profile_did();
+        let contacts = self.list_peer_contacts().unwrap_or_default();
+
+        if handle.starts_with("did:") {
+            let did = Did::parse(handle).ok()?;
+            let known = my_did.as_ref() == Some(&did)
+                || contacts.iter().any(|peer| peer.did.as_deref() == Some(handle));
+            return known.then_some(did);
+        }
+
+        if let Some(me) = &my_did {
+            let own_name = self
+                .storage
+                .load_profile(me.as_str())
+                .ok()
+                .flatten()
+                .map(|profile| profile.display_name);
+            if own_name.is_some_and(|name| name.eq_ignore_ascii_case(handle)) {
+                return Some(me.clone());
+            }
+        }
+        contacts.iter().find_map(|peer| {
+            let name = crate::chat::get_contact_display_name(Some(peer))?;
+            if !name.eq_ignore_ascii_case(handle) {
+                return None;
+            }
+            Did::parse(peer.did.as_deref()?).ok()
+        })
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Image Blob Operations
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Upload an image and return its content hash
+    ///
+    /// The image data is stored content-addressed using BLAKE3.
+    /// Returns the hash hex string that can be used as image_blob_id.
+    pub async fn upload_image(&self, data: Vec<u8>) -> Result<String, SyncError> {
+        let hash = self.blob_manager.import_image(data).await?;
+        Ok(BlobManager::hash_to_blob_id(&hash))
+    }
+
+    /// Upload an avatar image with size validation (256 KB limit)
+    ///
+    /// Also stores a thumbnail of at most
+    /// [`MAX_AVATAR_THUMB_EDGE`](crate::blobs::MAX_AVATAR_THUMB_EDGE) pixels
+    /// as a separate blob, for list views, and records it against the
+    /// avatar for [`avatar_thumbnail`](Self::avatar_thumbnail). Data that
+    /// can't be decoded as an image is stored without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Blob` if `data` is over the size limit.
+    pub async fn upload_avatar(&self, data: Vec<u8>) -> Result<AvatarBlobs, SyncError> {
+        let hash = self.blob_manager.import_avatar(data.clone()).await?;
+        let thumb_blob_id = match crate::blobs::avatar_thumbnail(&data) {
+            Ok(thumb) => {
+                let thumb_hash = self.blob_manager.import_avatar(thumb).await?;
+                Some(BlobManager::hash_to_blob_id(&thumb_hash))
+            }
+            Err(e) => {
+                debug!(error = %e, "Storing avatar without a thumbnail");
+                None
+            }
+        };
+        let blob_id = BlobManager::hash_to_blob_id(&hash);
+        if let Some(thumb_blob_id) = &thumb_blob_id {
+            self.storage.save_avatar_thumbnail(&blob_id, thumb_blob_id)?;
+        }
+        Ok(AvatarBlobs {
+            blob_id,
+            thumb_blob_id,
+        })
+    }
+
+    /// Crop an avatar to the golden ratio and upload it
+    ///
+    /// Decodes `data`, crops it to `orientation`, scales it down if needed
+    /// and stores it re-encoded as JPEG, so avatars look the same however
+    /// they were set. The thumbnail is made as in
+    /// [`upload_avatar`](Self::upload_avatar).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidImage` if `data` isn't a PNG, JPEG or WebP
+    /// image.
+    pub async fn upload_avatar_cropped(
+        &self,
+        data: Vec<u8>,
+        orientation: ImageOrientation,
+    ) -> Result<AvatarBlobs, SyncError> {
+        let cropped = crate::blobs::crop_avatar(&data, orientation)?;
+        self.upload_avatar(cropped).await
+    }
+
+    /// Store a thumbnail for an avatar that is already uploaded
+    ///
+    /// For avatars stored with [`upload_image`](Self::upload_image), such
+    /// as those picked in the desktop app, or fetched from a contact.
+    /// Returns the thumbnail's blob ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Blob` if the avatar isn't stored here, and
+    /// `SyncError::InvalidImage` if it isn't an image.
+    pub async fn create_avatar_thumbnail(&self, blob_id: &str) -> Result<String, SyncError> {
+        let data = self
+            .load_image(blob_id)
+            .await?
+            .ok_or_else(|| SyncError::Blob(format!("Avatar {} is not stored locally", blob_id)))?;
+        let thumb = crate::blobs::avatar_thumbnail(&data)?;
+        let hash = self.blob_manager.import_avatar(thumb).await?;
+        let thumb_blob_id = BlobManager::hash_to_blob_id(&hash);
+        self.storage.save_avatar_thumbnail(blob_id, &thumb_blob_id)?;
+        Ok(thumb_blob_id)
+    }
+
+    /// Blob ID of the thumbnail for an avatar, if one has been made here
+    ///
+    /// Thumbnails aren't part of the signed profile, so list views look
+    /// them up by the profile's `avatar_blob_id` and fall back to the full
+    /// avatar when this returns `None`.
+    pub fn avatar_thumbnail(&self, avatar_blob_id: &str) -> Result<Option<String>, SyncError> {
+        self.storage.load_avatar_thumbnail(avatar_blob_id)
+    }
+
+    /// Load an image by its content hash
+    pub async fn load_image(&self, hash_hex: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
+        let bytes = self.blob_manager.get_bytes(&hash).await?;
+        Ok(bytes.map(|b| b.to_vec()))
+    }
+
+    /// Check if an image blob exists
+    pub async fn image_exists(&self, hash_hex: &str) -> Result<bool, SyncError> {
+        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
+        self.blob_manager.has_blob(&hash).await
+    }
+
+    /// Get the size of an image blob in bytes
+    pub async fn image_size(&self, hash_hex: &str) -> Result<Option<u64>, SyncError> {
+        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
+        self.blob_manager.blob_size(&hash).await
+    }
+
+    /// Delete an image blob
+    ///
+    /// Note: iroh-blobs uses garbage collection based on tags.
+    /// Blobs without tags are eventually cleaned up.
+    pub async fn delete_image(&self, hash_hex: &str) -> Result<(), SyncError> {
+        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
+        self.blob_manager.delete_blob(&hash).await
+    }
+
+    /// Get a reference to the blob manager
+    ///
+    /// This can be used for advanced blob operations like P2P downloads.
+    pub fn blob_manager(&self) -> &BlobManager {
+        &self.blob_manager
+    }
+
+    /// Create a blob ticket for sharing an image via P2P
+    ///
+    /// Returns the base58-encoded ticket string.
+    /// Requires gossip networking to be initialized.
+    pub async fn create_image_ticket(&self, hash_hex: &str) -> Result<String, SyncError> {
+        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
+        let gossip = self.ensure_gossip_ref()?;
+        let ticket = self.blob_manager.create_ticket(hash, gossip.endpoint());
+        Ok(ticket.to_string())
+    }
+
+    /// Download an image from a peer using a ticket
+    ///
+    /// Returns the content hash as a hex string blob ID.
+    pub async fn download_image_from_ticket(&mut self, ticket_str: &str) -> Result<String, SyncError> {
+        let gossip = self.ensure_gossip().await?;
+        let hash = self.blob_manager.download_from_ticket_str(ticket_str, gossip.endpoint()).await?;
+        Ok(BlobManager::hash_to_blob_id(&hash))
+    }
+
+    /// Get this node's endpoint address
+    ///
+    /// Returns the EndpointAddr which can be used by other nodes to connect.
+    /// Returns None if networking is not active.
+    #[cfg(test)]
+    pub fn endpoint_addr(&self) -> Option<iroh::EndpointAddr> {
+        self.gossip.as_ref().map(|g| g.endpoint_addr())
+    }
+
+    /// Add a peer's address to the discovery system
+    ///
+    /// This allows sending messages to the peer directly.
+    #[cfg(test)]
+    pub fn add_peer_addr(&self, addr: iroh::EndpointAddr) {
+        if let Some(ref gossip) = self.gossip {
+            gossip.add_peer_addr(addr);
+        }
+    }
+
+    /// Wait for a peer to connect to a realm's sync topic
+    ///
+    /// Returns true if a peer connected within the timeout, false otherwise.
+    #[cfg(test)]
+    pub async fn wait_for_peer_connection(
+        &self,
+        realm_id: &RealmId,
+        wait_duration: std::time::Duration,
+    ) -> bool {
+        let mut events = self.event_tx.subscribe();
+        let start = std::time::Instant::now();
+
+        loop {
+            let remaining = wait_duration.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                debug!(%realm_id, "Timeout waiting for peer connection");
+                return false;
+            }
+
+            match tokio::time::timeout(remaining, events.recv()).await {
+                Ok(Ok(SyncEvent::PeerConnected {
+                    realm_id: event_realm,
+                    peer_id,
+                })) => {
+                    if &event_realm == realm_id {
+                        debug!(%realm_id, %peer_id, "Peer connected");
+                        return true;
+                    }
+                }
+                Ok(Ok(_)) => {
+                    // Other event, keep waiting
+                    continue;
+                }
+                Ok(Err(_)) => {
+                    // Channel lagged, keep trying
+                    continue;
+                }
+                Err(_) => {
+                    // Timeout
+                    debug!(%realm_id, "Timeout waiting for peer connection");
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Gracefully shutdown the engine
+    ///
+    /// Same as [`shutdown_with_timeout`](Self::shutdown_with_timeout) with a
+    /// ten second limit.
+    pub async fn shutdown(self) -> Result<(), SyncError> {
+        self.shutdown_with_timeout(SHUTDOWN_TIMEOUT).await
+    }
+
+    /// Gracefully shutdown the engine, flushing in-flight work first
+    ///
+    /// In order:
+    /// 1. Stops taking sync messages from the background listeners, and
+    ///    applies the ones already queued.
+    /// 2. Waits for in-flight broadcasts and receipts to finish sending.
+    /// 3. Saves every open realm document.
+    /// 4. Shuts down the router and closes the endpoint.
+    /// 5. Closes the blob store, so the data directory can be copied.
+    ///
+    /// Steps 2 and 4 share `timeout`. If it runs out, what was still pending
+    /// is logged and shutdown carries on; documents are always saved.
+    /// Messages held for offline contacts in the relay store live only in
+    /// memory and are dropped.
+    pub async fn shutdown_with_timeout(mut self, timeout: Duration) -> Result<(), SyncError> {
+        info!(?timeout, "Shutting down SyncEngine");
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        // Stop accepting new sync work, then drain what is already queued
+        self.sync_rx.close();
+        let drained = self.process_pending_sync();
+        if drained > 0 {
+            debug!(drained, "Applied queued sync messages during shutdown");
+        }
+
+        // Let in-flight sends finish
+        self.in_flight.close();
+        if tokio::time::timeout_at(deadline, self.in_flight.wait()).await.is_err() {
+            warn!(
+                pending = self.in_flight.len(),
+                "Shutdown timed out with broadcasts still in flight"
+            );
+        }
+
+        // Save all open realms
+        let realm_ids: Vec<_> = self.realms.keys().cloned().collect();
+        for realm_id in realm_ids {
+            if let Err(e) = self.save_realm(&realm_id).await {
+                warn!(%realm_id, error = ?e, "Failed to save realm during shutdown");
+            }
+        }
+
+        let relayed = self.relay_store.lock().map(|store| store.pending_count()).unwrap_or(0);
+        if relayed > 0 {
+            warn!(relayed, "Dropping messages held for offline contacts");
+        }
+
+        // Shutdown gossip
+        if let Some(gossip) = self.gossip.take() {
+            match tokio::time::timeout_at(deadline, gossip.shutdown()).await {
+                Ok(Err(e)) => warn!(error = ?e, "Failed to shutdown gossip cleanly"),
+                Err(_) => warn!("Shutdown timed out while closing the endpoint"),
+                Ok(Ok(())) => {}
+            }
+        }
+
+        if let Err(e) = self.blob_manager.store().shutdown().await {
+            warn!(error = ?e, "Failed to close the blob store");
+        }
+
+        info!("SyncEngine shutdown complete");
+        Ok(())
+    }
+}
+
+/// Blob IDs of an uploaded avatar, from [`SyncEngine::upload_avatar`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvatarBlobs {
+    /// The full-size avatar, for `UserProfile::avatar_blob_id`
+    pub blob_id: String,
+    /// Its thumbnail, also returned by [`SyncEngine::avatar_thumbnail`];
+    /// `None` if the avatar couldn't be decoded as an image
+    pub thumb_blob_id: Option<String>,
+}
+
+/// Information about this node
+#[derive(Debug, Clone)]
+pub struct NodeInfo {
+    /// Directory where data is stored
+    pub data_dir: PathBuf,
+    /// Number of realms
+    pub realm_count: usize,
+    /// Node's public key (when P2P is active)
+    pub node_id: Option<String>,
+    /// Relay URL (when P2P is active and relays are enabled)
+    pub relay_url: Option<String>,
+    /// Decentralized identifier (when identity is initialized)
+    pub did: Option<String>,
+    /// DID our packets are signed with (when profile keys are initialized)
+    pub profile_did: Option<String>,
+    /// Sequence of the newest packet in our log (`None` before the first one)
+    pub log_head: Option<u64>,
+    /// Number of other profiles whose packet logs we mirror
+    pub mirror_count: usize,
+    /// Number of accepted contacts
+    pub contact_count: usize,
+    /// Direct messages still waiting for a receipt
+    pub outbox_size: usize,
+}
+
+/// Snapshot of a node's health, from [`SyncEngine::health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Whether the identity keypair is loaded
+    pub identity_initialized: bool,
+    /// Whether networking was asked for via `start_networking()`
+    pub networking_requested: bool,
+    /// Whether the P2P endpoint is running
+    pub networking_active: bool,
+    /// Whether a home relay is connected (`None` when networking is off or
+    /// relays are disabled)
+    pub relay_reachable: Option<bool>,
+    /// Number of realms with sync running
+    pub syncing_realms: usize,
+    /// Time since changes from a peer were last applied (`None` if never)
+    pub last_sync_age: Option<Duration>,
+    /// Whether a test write to the database succeeded
+    pub database_writable: bool,
+}
+
+impl HealthReport {
+    /// Whether the node can do its job
+    ///
+    /// Requires a writable database and, if networking was requested, a
+    /// running endpoint. Relay reachability and sync recency are reported
+    /// but not judged, since both depend on peers and the network.
+    pub fn is_healthy(&self) -> bool {
+        self.database_writable && (!self.networking_requested || self.networking_active)
+    }
+}
+
+/// Network statistics for the Network page.
+///
+/// Provides summary counts for peers, pinners, and pins.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkStats {
+    /// Total number of known peers
+    pub total_peers: usize,
+    /// Number of currently online peers
+    pub online_peers: usize,
+    /// Number of peers pinning our profile ("Souls Carrying Your Light")
+    pub pinners_count: usize,
+    /// Number of profiles we are pinning ("Souls You Carry")
+    pub pinning_count: usize,
+    /// Mean connection quality (0-100) of online peers (`None` if none are online)
+    pub average_quality: Option<u8>,
+    /// Time since changes from a peer were last applied (`None` if never)
+    pub last_sync_age: Option<Duration>,
+}
+
+/// Realm gossip traffic, as returned by [`SyncEngine::message_counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MessageCounts {
+    /// Envelopes broadcast to realm topics
+    pub sent: u64,
+    /// Envelopes received from realm topics
+    pub received: u64,
+}
+
+/// Outcome of [`SyncEngine::compact_realm_history`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RealmCompaction {
+    /// History was dropped; sizes are of the saved document in bytes
+    Compacted {
+        changes_before: usize,
+        bytes_before: usize,
+        bytes_after: usize,
+    },
+    /// Nothing changed: these members haven't announced our heads
+    Unconverged { members: Vec<String> },
+}
+
+/// Task completion statistics for one realm, from [`SyncEngine::realm_stats`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RealmStats {
+    /// All tasks in the realm
+    pub total: usize,
+    /// Tasks marked done
+    pub completed: usize,
+    /// Tasks not yet done
+    pub open: usize,
+    /// Tasks completed in the last 7 days
+    pub completed_last_7_days: usize,
+    /// Tasks completed in the last 30 days
+    pub completed_last_30_days: usize,
+    /// Mean time from creation to completion (`None` if nothing is done)
+    pub average_time_to_complete: Option<Duration>,
+}
+
+impl RealmStats {
+    /// Compute stats for `tasks` relative to `now` (Unix seconds)
+    pub(crate) fn from_tasks(tasks: &[Task], now: i64) -> Self {
+        const DAY_SECS: i64 = 24 * 60 * 60;
+
+        let mut stats = Self {
+            total: tasks.len(),
+            ..Self::default()
+        };
+        let mut durations: Vec<u64> = Vec::new();
+        for task in tasks {
+            if !task.completed {
+                stats.open += 1;
+                continue;
+            }
+            stats.completed += 1;
+            // Tasks completed before timestamps were recorded only count as done
+            let Some(completed_at) = task.completed_at else {
+                continue;
+            };
+            let age = now - completed_at;
+            if age <= 7 * DAY_SECS {
+                stats.completed_last_7_days += 1;
+            }
+            if age <= 30 * DAY_SECS {
+                stats.completed_last_30_days += 1;
+            }
+            durations.push(completed_at.saturating_sub(task.created_at).max(0) as u64);
+        }
+        if !durations.is_empty() {
+            let mean = durations.iter().sum::<u64>() / durations.len() as u64;
+            stats.average_time_to_complete = Some(Duration::from_secs(mean));
+        }
+        stats
+    }
+
+    /// Fraction of tasks that are done, from 0.0 to 1.0 (0.0 for an empty realm)
+    pub fn completion_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.completed as f64 / self.total as f64
+        }
+    }
+}
+
+/// Our share of the activity in some set of tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Contribution {
+    /// Tasks we created
+    pub created: usize,
+    /// Tasks we completed
+    pub completed: usize,
+    /// All task creations and completions, by anyone
+    pub activity: usize,
+}
+
+impl Contribution {
+    /// Tally `tasks`, crediting those created or completed by `me`
+    fn from_tasks(tasks: &[Task], me: Option<&Did>) -> Self {
+        let mine = |did: Option<&str>| me.is_some_and(|me| did == Some(me.as_str()));
+        let mut contribution = Self::default();
+        for task in tasks {
+            contribution.activity += 1;
+            if mine(task.created_by.as_deref()) {
+                contribution.created += 1;
+            }
+            if task.completed {
+                contribution.activity += 1;
+                if mine(task.completed_by.as_ref().map(Did::as_str)) {
+                    contribution.completed += 1;
+                }
+            }
+        }
+        contribution
+    }
+
+    fn add(&mut self, other: &Contribution) {
+        self.created += other.created;
+        self.completed += other.completed;
+        self.activity += other.activity;
+    }
+
+    /// Fraction of the activity that was ours, from 0.0 to 1.0 (0.0 if there was none)
+    pub fn share(&self) -> f64 {
+        if self.activity == 0 {
+            0.0
+        } else {
+            (self.created + self.completed) as f64 / self.activity as f64
+        }
+    }
+}
+
+/// Our contribution to one realm
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealmContribution {
+    /// The realm
+    pub realm_id: RealmId,
+    /// Its display name
+    pub name: String,
+    /// What we did there
+    pub contribution: Contribution,
+}
+
+/// Our contribution across realms, from [`SyncEngine::stewardship_summary`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StewardshipSummary {
+    /// Each realm, in the order they are listed
+    pub realms: Vec<RealmContribution>,
+    /// All realms together
+    pub total: Contribution,
+}
+
+/// What deleting a realm would remove, from [`SyncEngine::delete_realm_preview`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RealmDeletePreview {
+    /// The realm that would be deleted
+    pub realm_id: RealmId,
+    /// Its display name
+    pub name: String,
+    /// Whether it is shared (other members keep their copies)
+    pub is_shared: bool,
+    /// Tasks that would be deleted
+    pub task_count: usize,
+    /// Members on the locally stored roster
+    pub member_count: usize,
+    /// Snapshots that would be deleted
+    pub snapshot_count: usize,
+}
+
+/// Format version of the bundle inside an [`IdentityLink`]
+const IDENTITY_LINK_VERSION: u32 = 1;
+
+/// An identity exported for another device, from
+/// [`SyncEngine::export_identity_encrypted`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityLink {
+    /// The encrypted identity bundle, base58 encoded
+    pub bundle: String,
+    /// The key that opens the bundle, base58 encoded
+    pub link_key: String,
+}
+
+/// What an [`IdentityLink`] bundle holds once decrypted
+#[derive(Serialize, Deserialize)]
+struct IdentityLinkBundle {
+    /// Format version ([`IDENTITY_LINK_VERSION`] when written)
+    version: u32,
+    /// The serialized identity keypair
+    identity: Vec<u8>,
+    /// The serialized profile keys
+    profile_keys: Vec<u8>,
+    /// Our own profile, so every device shows the same one
+    profile: Option<crate::types::UserProfile>,
+    /// Our device list, naming at least the exporting device
+    devices: SignedDeviceList,
+    /// Our contacts
+    contacts: ContactsBundle,
+}
 
-        // Wait for connection to establish before announcing
-        // The gossip connection typically takes ~20-50ms to establish
-        // This ensures we have a neighbor to receive our announce
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-        debug!(%realm_id, "Connection delay complete, sending announce");
+/// What regenerating the identity would replace, from
+/// [`SyncEngine::regenerate_identity_preview`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityRegeneratePreview {
+    /// The DID that would stop being ours (`None` before an identity exists)
+    pub current_did: Option<Did>,
+    /// First 8 bytes of the current public key, hex encoded
+    pub current_key_fingerprint: Option<String>,
+    /// Contacts who know us by the current DID
+    pub contact_count: usize,
+}
 
-        // Send an announce message to establish bidirectional communication
-        // This allows the inviter to learn our address and send messages back to us
-        // by forcing Joy to send a message first, which establishes the QUIC connection
-        let heads = if let Some(state) = self.realms.get_mut(&realm_id) {
-            state
-                .doc
-                .heads()
-                .into_iter()
-                .map(|h| h.0.to_vec())
-                .collect()
-        } else {
-            vec![]
-        };
+/// Local work queued for peers, as returned by
+/// [`SyncEngine::pending_sync_summary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PendingSync {
+    /// Direct messages not yet acknowledged by their recipient
+    pub outbox_messages: usize,
+    /// Shared realms with local changes not yet broadcast
+    pub unsynced_realms: usize,
+}
 
-        // Include our endpoint address so the receiver can add us to their discovery
-        let sender_addr = self
-            .gossip
-            .as_ref()
-            .map(|g| NodeAddrBytes::from_endpoint_addr(&g.endpoint_addr()));
+impl PendingSync {
+    /// Total number of queued items ("intentions held")
+    pub fn total(&self) -> usize {
+        self.outbox_messages + self.unsynced_realms
+    }
 
-        let announce = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads,
-            sender_addr,
-        };
-        if let Err(e) = self.broadcast_sync(&realm_id, announce).await {
-            debug!(%realm_id, error = ?e, "Failed to send announce (non-fatal)");
+    /// Whether nothing is waiting to sync
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Coarse liveness of the node's network, shown by the resonance indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResonanceLevel {
+    /// No peers online ("field dormant")
+    #[default]
+    Dormant,
+    /// Peers online, but links are poor or nothing synced recently
+    /// ("field listening")
+    Listening,
+    /// Healthy links and recent sync activity ("field resonating")
+    Resonating,
+}
+
+impl ResonanceLevel {
+    /// Minimum average connection quality for resonating
+    pub const RESONATING_MIN_QUALITY: u8 = 40;
+    /// A sync must have happened within this window for resonating
+    pub const RESONATING_SYNC_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+    /// Map network metrics to a level.
+    ///
+    /// - No online peers: `Dormant`
+    /// - Online peers with average quality of at least
+    ///   [`RESONATING_MIN_QUALITY`](Self::RESONATING_MIN_QUALITY) and a sync
+    ///   within [`RESONATING_SYNC_WINDOW`](Self::RESONATING_SYNC_WINDOW):
+    ///   `Resonating`
+    /// - Any other online state: `Listening`
+    pub fn from_stats(stats: &NetworkStats) -> Self {
+        if stats.online_peers == 0 {
+            return Self::Dormant;
+        }
+        let healthy_links = stats
+            .average_quality
+            .is_some_and(|q| q >= Self::RESONATING_MIN_QUALITY);
+        let recently_synced = stats
+            .last_sync_age
+            .is_some_and(|age| age <= Self::RESONATING_SYNC_WINDOW);
+        if healthy_links && recently_synced {
+            Self::Resonating
         } else {
-            debug!(%realm_id, "Sent announce to establish bidirectional connection");
+            Self::Listening
         }
-
-        Ok(realm_id)
     }
 
-    /// Create an invite string for a realm (convenience method)
-    ///
-    /// Same as `generate_invite` but returns the encoded string.
-    ///
-    /// # Errors
-    ///
-    /// Returns `SyncError::RealmNotFound` if the realm doesn't exist.
-    pub async fn create_invite(&mut self, realm_id: &RealmId) -> Result<String, SyncError> {
-        let ticket = self.generate_invite(realm_id).await?;
-        ticket.encode()
+    /// Display label for this level
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Dormant => "field dormant",
+            Self::Listening => "field listening",
+            Self::Resonating => "field resonating",
+        }
     }
+}
 
-    /// Join a realm via invite string (convenience method)
-    ///
-    /// Decodes the invite ticket and joins the realm.
-    ///
-    /// # Errors
-    ///
-    /// Returns `SyncError::InvalidInvite` if the ticket is invalid.
-    pub async fn join_realm(&mut self, ticket_str: &str) -> Result<RealmId, SyncError> {
-        let ticket = InviteTicket::decode(ticket_str)?;
-        self.join_via_invite(&ticket).await
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Node Info
-    // ═══════════════════════════════════════════════════════════════════════
+    async fn create_test_engine() -> (SyncEngine, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+        (engine, temp_dir)
+    }
 
-    /// Get information about this node
-    pub async fn node_info(&self) -> Result<NodeInfo, SyncError> {
-        let realms = self.storage.list_realms()?;
+    async fn create_memory_engine() -> SyncEngine {
+        SyncEngine::new_ephemeral().await.unwrap()
+    }
 
-        let (node_id, relay_url) = if let Some(ref gossip) = self.gossip {
-            let addr = gossip.endpoint_addr();
-            let id = Some(addr.id.to_string());
-            let relay = addr.relay_urls().next().map(|u| u.to_string());
-            (id, relay)
-        } else {
-            (None, None)
+    #[tokio::test]
+    async fn test_ephemeral_engine_leaves_nothing_on_disk() {
+        let cwd = std::env::current_dir().unwrap();
+        let entries = |dir: &Path| -> Vec<_> {
+            let mut names: Vec<_> = std::fs::read_dir(dir)
+                .unwrap()
+                .map(|e| e.unwrap().file_name())
+                .collect();
+            names.sort();
+            names
         };
+        let before = entries(&cwd);
 
-        let did = self.did().map(|d| d.to_string());
+        let mut engine = SyncEngine::new_ephemeral().await.unwrap();
+        engine.init_identity().unwrap();
+        let realm_id = engine.create_realm("Seed Library").await.unwrap();
+        engine.add_task(&realm_id, "Label the squash seeds").await.unwrap();
 
-        Ok(NodeInfo {
-            data_dir: self.data_dir.clone(),
-            realm_count: realms.len(),
-            node_id,
-            relay_url,
-            did,
-        })
+        let titles: Vec<_> = engine
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["Label the squash seeds"]);
+        assert!(engine
+            .list_realms()
+            .await
+            .unwrap()
+            .iter()
+            .any(|r| r.name == PRIVATE_REALM_NAME));
+
+        drop(engine);
+        assert_eq!(entries(&cwd), before);
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Profile Operations
-    // ═══════════════════════════════════════════════════════════════════════
+    #[tokio::test]
+    async fn test_in_memory_engine_runs_realm_and_task_flows() {
+        let mut engine = create_memory_engine().await;
+        assert_eq!(engine.data_dir(), Path::new(""));
 
-    /// Save a user profile
-    pub fn save_profile(&self, profile: &crate::types::UserProfile) -> Result<(), SyncError> {
-        self.storage.save_profile(profile)
+        // The Private realm is seeded just as on disk
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 1);
+
+        let realm_id = engine.create_realm("Compost").await.unwrap();
+        let turn = engine.add_task(&realm_id, "Turn the pile").await.unwrap();
+        let water = engine.add_task(&realm_id, "Water the pile").await.unwrap();
+        engine.toggle_task(&realm_id, &turn).await.unwrap();
+        engine.delete_task(&realm_id, &water).await.unwrap();
+
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Turn the pile");
+        assert!(tasks[0].completed);
+
+        // Closing and reopening the realm reads it back from the backend
+        engine.realms.remove(&realm_id);
+        engine.open_realm(&realm_id).await.unwrap();
+        assert_eq!(engine.list_tasks(&realm_id).unwrap(), tasks);
+        assert_eq!(engine.list_realms().await.unwrap().len(), 2);
+
+        engine.delete_realm(&realm_id).await.unwrap();
+        assert!(engine.get_realm(&realm_id).await.unwrap().is_none());
+
+        // Each in-memory engine is independent
+        let other = create_memory_engine().await;
+        assert_eq!(other.list_realms().await.unwrap().len(), 1);
     }
 
-    /// Load a profile by peer ID
-    pub fn load_profile(
-        &self,
-        peer_id: &str,
-    ) -> Result<Option<crate::types::UserProfile>, SyncError> {
-        self.storage.load_profile(peer_id)
+    #[tokio::test]
+    async fn test_engine_creates() {
+        let (engine, _temp) = create_test_engine().await;
+        let info = engine.node_info().await.unwrap();
+        // Private realm is auto-created
+        assert_eq!(info.realm_count, 1);
     }
 
-    /// Delete a profile by peer ID
-    pub fn delete_profile(&self, peer_id: &str) -> Result<(), SyncError> {
-        self.storage.delete_profile(peer_id)
+    #[tokio::test]
+    async fn test_create_realm_from_template_seeds_tasks_in_order() {
+        let mut engine = create_memory_engine().await;
+        let template = RealmTemplate {
+            name: "garden-season".to_string(),
+            description: String::new(),
+            tasks: (1..=12)
+                .map(|n| crate::realm::TemplateTask::new(format!("Bed {n}"), format!("Prepare bed {n}")))
+                .collect(),
+        };
+
+        let realm_id = engine
+            .create_realm_from_template("Allotment", &template)
+            .await
+            .unwrap();
+
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        let seeded: Vec<_> = tasks
+            .iter()
+            .map(|t| crate::realm::TemplateTask::new(t.title.clone(), t.description.clone()))
+            .collect();
+        assert_eq!(seeded, template.tasks);
+        assert!(tasks.iter().all(|t| !t.completed));
+
+        let err = engine
+            .create_realm_from_template("private", &template)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SyncError::PrivateRealmOperation(_)));
     }
 
-    /// List all profiles
-    pub fn list_profiles(&self) -> Result<Vec<crate::types::UserProfile>, SyncError> {
-        self.storage.list_profiles()
+    #[tokio::test]
+    async fn test_completing_daily_task_creates_next_instance() {
+        let mut engine = create_memory_engine().await;
+        let realm_id = engine.create_realm("Habits").await.unwrap();
+        let first = engine.add_task(&realm_id, "Stretch").await.unwrap();
+        let due = 1_767_225_600; // 2026-01-01T00:00:00Z
+        engine
+            .schedule_task(&realm_id, &first, Some(due), Some(Recurrence::Daily))
+            .await
+            .unwrap();
+
+        engine.toggle_task(&realm_id, &first).await.unwrap();
+
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 2);
+        let done = tasks.iter().find(|t| t.id == first).unwrap();
+        let next = tasks.iter().find(|t| t.id != first).unwrap();
+        assert!(done.completed);
+        assert_eq!(done.series_id.as_ref(), Some(&first));
+        assert!(!next.completed);
+        assert_eq!(next.title, "Stretch");
+        assert_eq!(next.due_at, Some(due + 24 * 60 * 60));
+        assert_eq!(next.recurrence, Some(Recurrence::Daily));
+        assert_eq!(next.series_id, done.series_id);
+
+        // The series carries on from the new instance
+        let next_id = next.id.clone();
+        engine.toggle_task(&realm_id, &next_id).await.unwrap();
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 3);
+        let open: Vec<_> = tasks.iter().filter(|t| !t.completed).collect();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].due_at, Some(due + 2 * 24 * 60 * 60));
+        assert_eq!(open[0].series_id.as_ref(), Some(&first));
+
+        let err = engine
+            .schedule_task(&realm_id, &first, None, Some(Recurrence::Custom { interval_secs: 0 }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SyncError::InvalidOperation(_)));
     }
 
-    /// Get or create profile for this node
-    pub fn get_own_profile(&self) -> Result<crate::types::UserProfile, SyncError> {
-        // Use DID as peer_id (stable identifier, available immediately)
-        let peer_id = self
-            .did()
-            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?
-            .to_string();
+    #[tokio::test]
+    async fn test_sequential_task_ids_give_reproducible_listing() {
+        use crate::types::SequentialTaskIds;
 
-        // Try to load existing profile
-        if let Some(profile) = self.storage.load_profile(&peer_id)? {
-            Ok(profile)
-        } else {
-            // Create default profile with placeholder text to trigger edit mode
-            let mut profile = crate::types::UserProfile::new(peer_id.clone(), "Anonymous User".to_string());
-            profile.bio = "**Add your bio here**\n\nDescribe yourself, your interests, or your role in the network.\n\n- Use *markdown* for formatting\n- Add links, lists, and more\n- Express your unique identity".to_string();
-            self.storage.save_profile(&profile)?;
-            Ok(profile)
+        const START_MS: u64 = 1_700_000_000_000;
+        let titles = ["Weed", "Sow", "Water", "Harvest"];
+
+        let mut listings = Vec::new();
+        for _ in 0..2 {
+            let mut engine = create_memory_engine().await;
+            engine.set_task_id_generator(SequentialTaskIds::new(START_MS));
+            let realm_id = engine.create_realm("Garden").await.unwrap();
+
+            let mut created = Vec::new();
+            for title in titles {
+                created.push(engine.add_task(&realm_id, title).await.unwrap());
+            }
+            let expected: Vec<_> = (0..titles.len() as u64)
+                .map(|n| TaskId::from_parts(START_MS + n, n as u128))
+                .collect();
+            assert_eq!(created, expected);
+
+            let listed: Vec<_> = engine
+                .list_tasks(&realm_id)
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.id, t.title))
+                .collect();
+            listings.push(listed);
         }
+
+        let titles_listed: Vec<_> = listings[0].iter().map(|(_, title)| title.as_str()).collect();
+        assert_eq!(titles_listed, titles);
+        assert_eq!(listings[0], listings[1]);
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Profile Pinning Operations
-    // ═══════════════════════════════════════════════════════════════════════
+    #[tokio::test]
+    async fn test_reminder_fires_once_as_due_time_approaches() {
+        let mut engine = create_memory_engine().await;
+        let mut events = engine.subscribe_events();
+        let realm_id = engine.create_realm("Appointments").await.unwrap();
+        let task_id = engine.add_task(&realm_id, "Call the beekeeper").await.unwrap();
+        let due = chrono::Utc::now().timestamp() + 2 * 60 * 60;
+        engine.schedule_task(&realm_id, &task_id, Some(due), None).await.unwrap();
+        engine
+            .set_task_reminder(&realm_id, &task_id, Some(Duration::from_secs(30 * 60)))
+            .await
+            .unwrap();
 
-    /// Create and store a signed version of our own profile.
-    ///
-    /// This should be called whenever the profile is updated. The signed profile
-    /// is stored as a pin with relationship `Own` and can be announced to peers.
-    ///
-    /// # Returns
-    ///
-    /// The signed profile that was created and stored.
-    pub fn sign_and_pin_own_profile(&mut self) -> Result<crate::types::SignedProfile, SyncError> {
-        let profile = self.get_own_profile()?;
+        assert!(engine.upcoming_reminders(Duration::from_secs(60 * 60)).is_empty());
+        let upcoming = engine.upcoming_reminders(Duration::from_secs(2 * 60 * 60));
+        assert_eq!(upcoming.len(), 1);
+        assert_eq!(upcoming[0].1.id, task_id);
+
+        // Step a mock clock towards the due time, one minute at a time
+        let mut fired_at = Vec::new();
+        for minute in (-60..=10).map(|m| due - 30 * 60 + m * 60) {
+            if engine.fire_reminders_at(minute) > 0 {
+                fired_at.push(minute);
+            }
+        }
+        assert_eq!(fired_at, [due - 30 * 60]);
+
+        let reminders: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|e| matches!(e, SyncEvent::TaskReminder { .. }))
+            .collect();
+        assert_eq!(reminders.len(), 1);
+        let SyncEvent::TaskReminder { realm_id: r, task_id: t } = &reminders[0] else {
+            unreachable!()
+        };
+        assert_eq!(r, &realm_id);
+        assert_eq!(t, &task_id.to_string_repr());
+        assert!(engine.upcoming_reminders(Duration::from_secs(2 * 60 * 60)).is_empty());
+
+        // Moving the due date arms the reminder again
+        engine
+            .schedule_task(&realm_id, &task_id, Some(due + 24 * 60 * 60), None)
+            .await
+            .unwrap();
+        assert_eq!(engine.fire_reminders_at(due + 24 * 60 * 60), 1);
+    }
+
+    #[tokio::test]
+    async fn test_muted_realm_syncs_but_holds_back_reminders() {
+        let mut engine = create_memory_engine().await;
+        let mut events = engine.subscribe_events();
+        let realm_id = engine.create_realm("Busy").await.unwrap();
+        engine.set_realm_muted(&realm_id, true).unwrap();
+        assert!(engine.is_realm_muted(&realm_id));
+
+        // A peer's task still lands in the muted realm
+        let changes = {
+            let state = engine.realms.get_mut(&realm_id).unwrap();
+            let mut peer_doc = state.doc.fork();
+            peer_doc.add_task("Water the seedlings").unwrap();
+            peer_doc.generate_sync_message()
+        };
+        engine.apply_incoming_changes(&realm_id, &changes).await.unwrap();
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+
+        let due = chrono::Utc::now().timestamp() + 2 * 60 * 60;
+        engine.schedule_task(&realm_id, &tasks[0].id, Some(due), None).await.unwrap();
+        engine
+            .set_task_reminder(&realm_id, &tasks[0].id, Some(Duration::from_secs(30 * 60)))
+            .await
+            .unwrap();
+
+        // The reminder comes due without an event, and doesn't fire later on unmute
+        assert_eq!(engine.fire_reminders_at(due), 0);
+        assert!(!std::iter::from_fn(|| events.try_recv().ok())
+            .any(|e| matches!(e, SyncEvent::TaskReminder { .. })));
+        engine.set_realm_muted(&realm_id, false).unwrap();
+        assert!(!engine.is_realm_muted(&realm_id));
+        assert_eq!(engine.fire_reminders_at(due), 0);
+
+        assert!(matches!(
+            engine.set_realm_muted(&RealmId::new(), true),
+            Err(SyncError::RealmNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_engine_create_realm_persists() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Create a realm
+        let realm_id = engine.create_realm("Test Realm").await.unwrap();
+
+        // Verify it's in storage (should have Private + Test Realm)
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 2);
+        let test_realm = realms.iter().find(|r| r.name == "Test Realm").unwrap();
+        assert_eq!(test_realm.id, realm_id);
+
+        // Verify document exists
+        let doc_bytes = engine.storage.load_document(&realm_id).unwrap();
+        assert!(doc_bytes.is_some());
+
+        // Verify key exists
+        let key = engine.storage.load_realm_key(&realm_id).unwrap();
+        assert!(key.is_some());
+    }
 
-        // Ensure identity is initialized
-        self.init_identity()?;
-        let keypair = self.identity.as_ref().ok_or_else(|| {
-            SyncError::Identity("Identity not initialized".to_string())
-        })?;
+    #[tokio::test]
+    async fn test_engine_open_realm_loads() {
+        let temp_dir = TempDir::new().unwrap();
 
-        // Create signed profile
-        let signed = crate::types::SignedProfile::sign(&profile, keypair);
-        let did = signed.did().to_string();
+        // Create realm in first engine instance
+        let realm_id = {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            let id = engine.create_realm("Persisted Realm").await.unwrap();
 
-        // Create pin with Own relationship
-        let pin = crate::types::ProfilePin::new(
-            did.clone(),
-            signed.clone(),
-            crate::types::PinRelationship::Own,
-        );
+            // Add a task
+            engine.add_task(&id, "Persisted task").await.unwrap();
 
-        // Save to storage (Own pins are never evicted)
-        self.storage.save_pinned_profile(&pin)?;
+            id
+        };
 
-        debug!(did = %did, "Signed and pinned own profile");
-        Ok(signed)
-    }
+        // Open realm in second engine instance
+        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
+        assert!(!engine2.is_realm_open(&realm_id));
 
-    /// Get our own pinned profile (if exists).
-    pub fn get_own_pinned_profile(&self) -> Result<Option<crate::types::ProfilePin>, SyncError> {
-        self.storage.get_own_pinned_profile()
+        engine2.open_realm(&realm_id).await.unwrap();
+        assert!(engine2.is_realm_open(&realm_id));
+
+        // Verify task was persisted
+        let tasks = engine2.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Persisted task");
     }
 
-    /// Pin a signed profile from another peer.
-    ///
-    /// This stores the profile for redundancy, allowing us to serve it
-    /// to other peers who request it.
-    ///
-    /// # Arguments
-    ///
-    /// * `signed_profile` - The cryptographically signed profile to pin
-    /// * `relationship` - Why we're pinning this profile
-    ///
-    /// # Returns
-    ///
-    /// A list of DIDs that were evicted (if storage limits were reached).
-    pub fn pin_profile(
-        &self,
-        signed_profile: crate::types::SignedProfile,
-        relationship: crate::types::PinRelationship,
-    ) -> Result<Vec<String>, SyncError> {
-        // Verify signature first
-        if !signed_profile.verify() {
-            return Err(SyncError::SignatureInvalid(
-                "Profile signature verification failed".to_string(),
-            ));
-        }
+    #[tokio::test]
+    async fn test_engine_task_operations() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        let did = signed_profile.did().to_string();
-        let pin = crate::types::ProfilePin::new(did.clone(), signed_profile, relationship);
+        let realm_id = engine.create_realm("Task Test").await.unwrap();
 
-        // Use default pinning config
-        let config = crate::storage::PinningConfig::default();
-        let evicted = self.storage.save_pinned_profile_with_limits(&pin, &config)?;
+        // Add tasks
+        let task1 = engine.add_task(&realm_id, "Task 1").await.unwrap();
+        let task2 = engine.add_task(&realm_id, "Task 2").await.unwrap();
 
-        debug!(did = %did, evicted = evicted.len(), "Pinned profile");
-        Ok(evicted)
-    }
+        // List tasks
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 2);
 
-    /// Get a pinned profile by DID.
-    pub fn get_pinned_profile(&self, did: &str) -> Result<Option<crate::types::ProfilePin>, SyncError> {
-        self.storage.load_pinned_profile(did)
+        // Toggle task
+        engine.toggle_task(&realm_id, &task1).await.unwrap();
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        let toggled_task = tasks.iter().find(|t| t.id == task1).unwrap();
+        assert!(toggled_task.completed);
+
+        // Delete task
+        engine.delete_task(&realm_id, &task2).await.unwrap();
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, task1);
     }
 
-    /// Unpin a profile by DID.
-    ///
-    /// Note: Own profile cannot be unpinned.
-    pub fn unpin_profile(&self, did: &str) -> Result<(), SyncError> {
-        // Check if this is our own profile
-        if let Some(pin) = self.storage.load_pinned_profile(did)? {
-            if pin.is_own() {
-                return Err(SyncError::InvalidOperation(
-                    "Cannot unpin own profile".to_string(),
-                ));
-            }
-        }
+    #[tokio::test]
+    async fn test_engine_generate_invite() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        self.storage.delete_pinned_profile(did)
-    }
+        let realm_id = engine.create_realm("Invite Test").await.unwrap();
 
-    /// List all pinned profiles.
-    pub fn list_pinned_profiles(&self) -> Result<Vec<crate::types::ProfilePin>, SyncError> {
-        self.storage.list_pinned_profiles()
-    }
+        // Generate invite (starts gossip)
+        let invite = engine.generate_invite(&realm_id).await.unwrap();
 
-    /// List pinned profiles by relationship type.
-    pub fn list_pinned_profiles_by_relationship(
-        &self,
-        relationship: &crate::types::PinRelationship,
-    ) -> Result<Vec<crate::types::ProfilePin>, SyncError> {
-        self.storage.list_pinned_profiles_by_relationship(relationship)
-    }
+        // Verify invite contains our realm info
+        assert_eq!(invite.realm_id(), realm_id);
+        assert!(invite.bootstrap_peers.len() >= 1);
 
-    /// Update an existing pin with a new signed profile.
-    ///
-    /// This is used when receiving profile announcements for peers we already have pinned.
-    pub fn update_pinned_profile(
-        &self,
-        did: &str,
-        signed_profile: crate::types::SignedProfile,
-    ) -> Result<bool, SyncError> {
-        // Verify signature first
-        if !signed_profile.verify() {
-            return Err(SyncError::SignatureInvalid(
-                "Profile signature verification failed".to_string(),
-            ));
-        }
+        // Verify realm is marked as shared
+        let info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        assert!(info.is_shared);
 
-        // Check if we have an existing pin
-        if let Some(mut pin) = self.storage.load_pinned_profile(did)? {
-            // Update the profile
-            if pin.update_profile(signed_profile) {
-                self.storage.save_pinned_profile(&pin)?;
-                debug!(did = %did, "Updated pinned profile");
-                Ok(true)
-            } else {
-                // update_profile returns false if signature is invalid
-                // (but we already verified above, so this shouldn't happen)
-                Ok(false)
-            }
-        } else {
-            // No existing pin for this DID
-            Ok(false)
-        }
+        // Clean shutdown
+        engine.shutdown().await.unwrap();
     }
 
-    /// Get the count of pinned profiles (excluding own).
-    pub fn pinned_profile_count(&self) -> Result<usize, SyncError> {
-        self.storage.count_pinned_profiles()
-    }
+    #[tokio::test]
+    async fn test_engine_realm_not_found() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Network Page: Pinners (Who Pins Our Profile)
-    // ═══════════════════════════════════════════════════════════════════════
+        let fake_id = RealmId::new();
 
-    /// List all peers who are pinning our profile ("Souls Carrying Your Light").
-    ///
-    /// Returns pinners sorted by pinned_at (newest first).
-    pub fn list_profile_pinners(&self) -> Result<Vec<crate::storage::PinnerInfo>, SyncError> {
-        self.storage.list_pinners()
-    }
+        // open_realm should fail
+        let result = engine.open_realm(&fake_id).await;
+        assert!(matches!(result, Err(SyncError::RealmNotFound(_))));
 
-    /// Count the number of peers pinning our profile.
-    pub fn count_profile_pinners(&self) -> Result<usize, SyncError> {
-        self.storage.count_pinners()
+        // list_tasks should fail (realm not open)
+        let result = engine.list_tasks(&fake_id);
+        assert!(matches!(result, Err(SyncError::RealmNotFound(_))));
     }
 
-    // Note: record_pinner() and remove_pinner() removed - Indra's Net derives pinners from contacts
-
-    /// Get network statistics for the Network page.
-    ///
-    /// Returns counts for peers, pinners, and pinned profiles.
-    pub fn network_stats(&self) -> NetworkStats {
-        let total_peers = self.peer_registry.count().unwrap_or(0);
-        let online_peers = self.peer_registry.count_by_status(PeerStatus::Online).unwrap_or(0);
-        let pinners_count = self.storage.count_pinners().unwrap_or(0);
-        let pinning_count = self.storage.count_pinned_profiles().unwrap_or(0);
+    #[tokio::test]
+    async fn test_engine_sync_lifecycle() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        NetworkStats {
-            total_peers,
-            online_peers,
-            pinners_count,
-            pinning_count,
-        }
-    }
+        let realm_id = engine.create_realm("Sync Test").await.unwrap();
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Auto-Pinning Operations
-    // ═══════════════════════════════════════════════════════════════════════
+        // Not syncing initially
+        assert!(!engine.is_realm_syncing(&realm_id));
 
-    /// Check if a DID should be automatically pinned.
-    ///
-    /// Returns `true` if the DID belongs to a contact or realm member.
-    pub fn should_auto_pin(&self, did: &str) -> bool {
-        // Check if this DID is an accepted contact
-        if let Ok(contacts) = self.storage.list_contacts() {
-            for contact in contacts {
-                if contact.peer_did == did {
-                    return true;
-                }
-            }
-        }
+        // Start sync
+        engine.start_sync(&realm_id).await.unwrap();
+        assert!(engine.is_realm_syncing(&realm_id));
 
-        // Check if this DID is a member of any realm we're in
-        // For now, we auto-pin any contact; realm member pinning can be
-        // added when realm membership tracking is implemented
-        false
-    }
+        // Starting again is a no-op
+        engine.start_sync(&realm_id).await.unwrap();
+        assert!(engine.is_realm_syncing(&realm_id));
 
-    /// Determine the relationship for auto-pinning a DID.
-    ///
-    /// Returns the appropriate `PinRelationship` based on our relationship with the DID.
-    pub fn determine_pin_relationship(&self, did: &str) -> Option<crate::types::PinRelationship> {
-        // Check if this DID is a contact
-        if let Ok(contacts) = self.storage.list_contacts() {
-            for contact in contacts {
-                if contact.peer_did == did {
-                    return Some(crate::types::PinRelationship::Contact);
-                }
-            }
-        }
+        // Stop sync
+        engine.stop_sync(&realm_id).await.unwrap();
+        assert!(!engine.is_realm_syncing(&realm_id));
 
-        // TODO: Check if this DID is a realm member
-        // For now, return None if not a contact
-        None
+        // Stopping again is a no-op
+        engine.stop_sync(&realm_id).await.unwrap();
+        assert!(!engine.is_realm_syncing(&realm_id));
+
+        engine.shutdown().await.unwrap();
     }
 
-    /// Process a profile gossip announcement for potential auto-pinning.
-    ///
-    /// If the announcement is from a contact or realm member, their profile
-    /// will be automatically pinned or updated.
-    ///
-    /// # Arguments
-    ///
-    /// * `message` - The profile gossip message to process
-    ///
-    /// # Returns
-    ///
-    /// The action taken (UpdatePin, Ignore, etc.) for logging/testing purposes.
-    pub fn process_profile_announcement(
-        &self,
-        message: &crate::sync::ProfileGossipMessage,
-    ) -> Result<crate::sync::ProfileAction, SyncError> {
-        use crate::sync::{ProfileAction, ProfileGossipMessage};
+    #[tokio::test]
+    async fn test_engine_auto_save() {
+        let temp_dir = TempDir::new().unwrap();
 
-        match message {
-            ProfileGossipMessage::Announce {
-                signed_profile,
-                avatar_ticket,
-            } => {
-                // Verify signature first
-                if !signed_profile.verify() {
-                    warn!("Received profile announcement with invalid signature");
-                    return Ok(ProfileAction::Ignore);
-                }
+        let realm_id = {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            let id = engine.create_realm("Auto Save Test").await.unwrap();
 
-                let signer_did = signed_profile.did().to_string();
+            // Add task (should auto-save)
+            engine.add_task(&id, "Auto saved task").await.unwrap();
 
-                // Check if we should auto-pin this DID
-                if let Some(relationship) = self.determine_pin_relationship(&signer_did) {
-                    // Check if we already have this profile pinned
-                    if let Some(existing) = self.storage.load_pinned_profile(&signer_did)? {
-                        // Update existing pin
-                        let mut updated = existing.clone();
-                        if updated.update_profile(signed_profile.clone()) {
-                            if let Some(ticket) = avatar_ticket {
-                                // Parse the ticket and extract the hash for avatar tracking
-                                if let Ok(blob_ticket) =
-                                    ticket.parse::<iroh_blobs::ticket::BlobTicket>()
-                                {
-                                    updated.avatar_hash = Some(*blob_ticket.hash().as_bytes());
-                                }
-                            }
-                            self.storage.save_pinned_profile(&updated)?;
-                            debug!(did = %signer_did, "Updated pinned profile from announcement");
-                        }
-                    } else {
-                        // Create new pin
-                        let mut pin = crate::types::ProfilePin::new(
-                            signer_did.clone(),
-                            signed_profile.clone(),
-                            relationship,
-                        );
-                        if let Some(ticket) = avatar_ticket {
-                            if let Ok(blob_ticket) =
-                                ticket.parse::<iroh_blobs::ticket::BlobTicket>()
-                            {
-                                pin.avatar_hash = Some(*blob_ticket.hash().as_bytes());
-                            }
-                        }
-                        let config = crate::storage::PinningConfig::default();
-                        self.storage.save_pinned_profile_with_limits(&pin, &config)?;
-                        debug!(did = %signer_did, "Auto-pinned profile from announcement");
-                    }
+            id
+        };
 
-                    Ok(ProfileAction::UpdatePin {
-                        signed_profile: signed_profile.clone(),
-                        avatar_ticket: avatar_ticket.clone(),
-                    })
-                } else {
-                    // Not a contact or realm member, ignore
-                    Ok(ProfileAction::Ignore)
-                }
-            }
-            _ => {
-                // Request and Response messages are not auto-pin triggers
-                Ok(ProfileAction::Ignore)
-            }
-        }
+        // Reload and verify
+        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
+        engine2.open_realm(&realm_id).await.unwrap();
+
+        let tasks = engine2.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Auto saved task");
     }
 
-    /// Auto-pin a contact's profile when they are accepted.
-    ///
-    /// This creates a placeholder pin for the contact that will be updated
-    /// when we receive their profile announcement.
-    ///
-    /// # Arguments
-    ///
-    /// * `contact` - The contact info from the acceptance event
-    ///
-    /// # Returns
-    ///
-    /// True if a new pin was created, false if already pinned.
-    pub fn auto_pin_contact(&self, contact: &crate::types::contact::ContactInfo) -> Result<bool, SyncError> {
-        let did = &contact.peer_did;
+    #[tokio::test]
+    async fn test_create_and_decode_invite() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Check if already pinned
-        if self.storage.load_pinned_profile(did)?.is_some() {
-            debug!(did = %did, "Contact already pinned");
-            return Ok(false);
-        }
+        let realm_id = engine.create_realm("Shared Realm").await.unwrap();
+        let ticket_str = engine.create_invite(&realm_id).await.unwrap();
 
-        // Note: We can't create a proper SignedProfile without the contact's keypair.
-        // The actual pinning will happen when we receive their profile announcement
-        // via process_profile_announcement(). For now, just log that we're interested.
-        debug!(
-            did = %did,
-            name = %contact.profile.display_name,
-            "Contact accepted, will auto-pin on profile announcement"
-        );
+        // Verify ticket is valid
+        assert!(ticket_str.starts_with("sync-invite:"));
 
-        // Return true to indicate we registered interest
-        Ok(true)
-    }
+        // Realm should now be marked as shared
+        let realm = engine.get_realm(&realm_id).await.unwrap().unwrap();
+        assert!(realm.is_shared);
 
-    /// Get all contact DIDs that we should auto-pin profiles for.
-    ///
-    /// This returns a list of DIDs from our accepted contacts.
-    pub fn get_auto_pin_interests(&self) -> Result<Vec<String>, SyncError> {
-        let contacts = self.storage.list_contacts()?;
-        Ok(contacts.into_iter().map(|c| c.peer_did).collect())
+        // Decode and verify
+        let ticket = InviteTicket::decode(&ticket_str).unwrap();
+        assert_eq!(ticket.realm_id(), realm_id);
+
+        engine.shutdown().await.unwrap();
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Profile Sync Operations
-    // ═══════════════════════════════════════════════════════════════════════
+    #[tokio::test]
+    async fn test_join_realm_already_member() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-    /// Announce our profile to the global profile topic.
-    ///
-    /// This broadcasts a signed profile announcement to all peers subscribed
-    /// to the global profile topic. Peers who are interested in our profile
-    /// (contacts, realm members) will auto-pin it.
-    ///
-    /// # Arguments
-    ///
-    /// * `avatar_ticket` - Optional blob ticket for avatar download
-    ///
-    /// # Returns
-    ///
-    /// Ok(()) if announcement was broadcast successfully.
-    ///
-    /// # Errors
-    ///
-    /// Returns error if gossip is not initialized or broadcast fails.
-    pub async fn announce_profile(&mut self, avatar_ticket: Option<String>) -> Result<(), SyncError> {
-        // Ensure we have a signed profile
-        let signed = self.sign_and_pin_own_profile()?;
+        let realm_id = engine.create_realm("Shared").await.unwrap();
+        let ticket = engine.generate_invite(&realm_id).await.unwrap();
 
-        // Create the announcement message
-        let announcement = crate::sync::ProfileGossipMessage::announce(signed.clone(), avatar_ticket.clone());
-        let bytes = announcement.to_bytes()?;
+        // Try to join our own realm
+        let result = engine.join_via_invite(&ticket).await;
+        assert!(matches!(result, Err(SyncError::InvalidInvite(_))));
 
-        // 1. Broadcast on our own per-peer profile topic (for backwards compatibility)
-        if let Some(sender) = self.profile_gossip_sender.as_ref() {
-            if let Err(e) = sender.broadcast(bytes.clone()).await {
-                warn!(error = %e, "Failed to broadcast on per-peer profile topic (non-fatal)");
-            } else {
-                debug!("Profile announcement broadcast on per-peer topic");
-            }
+        engine.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_data_persists_across_restarts() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Create engine and data
+        {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            let realm_id = engine.create_realm("Persistent").await.unwrap();
+            engine.add_task(&realm_id, "Persisted task").await.unwrap();
         }
 
-        // 2. Broadcast on all 1:1 contact topics (the WORKING channel!)
-        // This is the primary mechanism for profile propagation since contact topics
-        // have proper bi-directional mesh formation from the contact exchange.
-        if let Some(ref manager) = self.contact_manager {
-            let contacts_updated = manager.broadcast_profile_to_contacts(&bytes).await;
-            info!(contacts_updated, "Profile announcement broadcast on contact topics");
+        // Reopen and verify
+        {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            let realms = engine.list_realms().await.unwrap();
+            // Should have Private + Persistent
+            assert_eq!(realms.len(), 2);
+            let persistent_realm = realms
+                .iter()
+                .find(|r| r.name == "Persistent")
+                .expect("Persistent realm should exist");
+
+            // Need to open the realm to access tasks
+            engine.open_realm(&persistent_realm.id).await.unwrap();
+            let tasks = engine.list_tasks(&persistent_realm.id).unwrap();
+            assert_eq!(tasks.len(), 1);
+            assert_eq!(tasks[0].title, "Persisted task");
         }
+    }
 
-        info!("Profile announcement broadcast complete");
-        Ok(())
+    #[tokio::test]
+    async fn test_multiple_realms() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Create multiple realms
+        let realm1 = engine.create_realm("Realm 1").await.unwrap();
+        let realm2 = engine.create_realm("Realm 2").await.unwrap();
+        let realm3 = engine.create_realm("Realm 3").await.unwrap();
+
+        // Add tasks to each
+        engine.add_task(&realm1, "Task in Realm 1").await.unwrap();
+        engine.add_task(&realm2, "Task in Realm 2").await.unwrap();
+        engine.add_task(&realm3, "Task in Realm 3").await.unwrap();
+
+        // Verify list_realms (Private + 3 created = 4)
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 4);
+
+        // Verify tasks are isolated
+        assert_eq!(engine.list_tasks(&realm1).unwrap().len(), 1);
+        assert_eq!(engine.list_tasks(&realm2).unwrap().len(), 1);
+        assert_eq!(engine.list_tasks(&realm3).unwrap().len(), 1);
     }
 
-    // Note: send_pin_acknowledgment() and send_pin_removal() removed - Indra's Net derives pinners from contacts
-    // In Indra's Net, contact acceptance = implicit mutual mirroring. No explicit acknowledgment needed.
+    #[tokio::test]
+    async fn test_delete_realm() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        let realm_id = engine.create_realm("To Delete").await.unwrap();
+        engine.add_task(&realm_id, "Task").await.unwrap();
 
-    /// Update our own profile and broadcast the change to peers.
-    ///
-    /// This is the main method to use when the user edits their profile.
-    /// It saves the profile locally and broadcasts an announcement so peers
-    /// who have pinned our profile can update their copy.
-    ///
-    /// # Arguments
-    ///
-    /// * `profile` - The updated profile data
-    /// * `avatar_ticket` - Optional blob ticket for avatar (if avatar changed)
-    ///
-    /// # Returns
-    ///
-    /// Ok(()) if profile was saved and broadcast successfully.
-    pub async fn update_own_profile(
-        &mut self,
-        profile: &crate::types::UserProfile,
-        avatar_ticket: Option<String>,
-    ) -> Result<(), SyncError> {
-        // Save the profile locally
-        self.storage.save_profile(profile)?;
+        engine.delete_realm(&realm_id).await.unwrap();
 
-        // Sign, pin, and broadcast the update
-        self.announce_profile(avatar_ticket).await?;
+        let realm = engine.get_realm(&realm_id).await.unwrap();
+        assert!(realm.is_none());
 
-        info!(peer_id = %profile.peer_id, "Profile updated and broadcast");
-        Ok(())
+        // Should no longer be open
+        assert!(!engine.is_realm_open(&realm_id));
     }
 
-    /// Start listening for profile announcements.
-    ///
-    /// This method sets up profile sync by subscribing to:
-    /// 1. **Our own profile topic** (derived from our DID) - for broadcasting our profile updates
-    /// 2. **Global profile topic** (legacy) - for backwards compatibility during migration
-    ///
-    /// When a profile announcement is received from a contact, their profile is auto-pinned.
-    /// Contacts also subscribe to our per-peer topic via `finalize_contact()`.
-    ///
-    /// # Per-Peer Topic Architecture
-    ///
-    /// Each peer has their own profile topic: `BLAKE3("sync-profile" || peer_did)`
-    /// - Only contacts receive your updates (targeted, not global)
-    /// - Scalability: O(contacts) messages instead of O(all users)
-    /// - Privacy: Non-contacts don't receive your profile updates
-    ///
-    /// # Arguments
-    ///
-    /// * `bootstrap_peers` - Initial peers to connect to (can be empty)
-    ///
-    /// # Returns
-    ///
-    /// Ok(()) if subscription started successfully.
-    pub async fn start_profile_sync(
-        &mut self,
-        bootstrap_peers: Vec<iroh::PublicKey>,
-    ) -> Result<(), SyncError> {
-        // Check if already initialized - don't replace existing subscription!
-        // Replacing the subscription would drop the old receiver, closing the topic
-        // and preventing contacts from joining.
-        if self.profile_gossip_sender.is_some() {
-            debug!("Profile sync already initialized, skipping");
-            return Ok(());
-        }
+    #[tokio::test]
+    async fn test_delete_realm_preview_leaves_realm_intact() {
+        let temp = TempDir::new().unwrap();
+        let realm_id = {
+            let mut engine = SyncEngine::new(temp.path()).await.unwrap();
+            let realm_id = engine.create_realm("Garden").await.unwrap();
+            for title in ["Dig", "Sow", "Water"] {
+                engine.add_task(&realm_id, title).await.unwrap();
+            }
+            engine.snapshot_realm(&realm_id, "before planting").await.unwrap();
+
+            let preview = engine.delete_realm_preview(&realm_id).unwrap();
+            assert_eq!(preview.name, "Garden");
+            assert_eq!(preview.task_count, 3);
+            assert_eq!(preview.snapshot_count, 1);
+            assert!(!preview.is_shared);
+            assert!(engine.is_realm_open(&realm_id));
+            engine.shutdown().await.unwrap();
+            realm_id
+        };
 
-        // Get our DID for processing messages directed at us
-        let our_did = self
-            .did()
-            .ok_or_else(|| SyncError::Identity("Identity not initialized".to_string()))?
-            .to_string();
+        // Tasks are counted from storage when the realm isn't open
+        let engine = SyncEngine::new(temp.path()).await.unwrap();
+        assert!(!engine.is_realm_open(&realm_id));
+        assert_eq!(engine.delete_realm_preview(&realm_id).unwrap().task_count, 3);
+        assert!(engine.get_realm(&realm_id).await.unwrap().is_some());
 
-        // Ensure gossip is initialized
-        let gossip = self.ensure_gossip().await?;
+        let private = engine
+            .list_realms()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|r| r.name == PRIVATE_REALM_NAME)
+            .unwrap();
+        assert!(matches!(
+            engine.delete_realm_preview(&private.id),
+            Err(SyncError::PrivateRealmOperation(_))
+        ));
+        assert!(matches!(
+            engine.delete_realm_preview(&RealmId::new()),
+            Err(SyncError::RealmNotFound(_))
+        ));
+    }
 
-        // Subscribe to our OWN profile topic (for broadcasting)
-        // Contacts subscribe to this topic via finalize_contact() to receive our updates
-        let own_topic_id = crate::sync::derive_profile_topic(&our_did);
-        let (sender, own_receiver) = gossip.subscribe_split(own_topic_id, vec![]).await?;
+    #[tokio::test]
+    async fn test_toggle_task() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Store sender for reuse by announce_profile() and other profile broadcast methods.
-        // This allows all profile announcements to use the same persistent subscription.
-        self.profile_gossip_sender = Some(sender.clone());
+        let realm_id = engine.create_realm("Tasks").await.unwrap();
+        let task_id = engine.add_task(&realm_id, "Toggle me").await.unwrap();
 
-        // CRITICAL: Store receiver to keep the subscription alive!
-        // Dropping the receiver would close the gossip topic, preventing contacts from joining.
-        self.profile_gossip_receiver = Some(own_receiver);
+        // Initially not completed
+        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert!(!task.completed);
 
-        info!(
-            did = %our_did,
-            ?own_topic_id,
-            "Subscribed to own profile topic for broadcasting"
+        // Toggle to completed
+        engine.toggle_task(&realm_id, &task_id).await.unwrap();
+        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert!(task.completed);
+
+        // Toggle back
+        engine.toggle_task(&realm_id, &task_id).await.unwrap();
+        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert!(!task.completed);
+    }
+
+    #[test]
+    fn test_realm_stats_from_known_completion_times() {
+        const DAY: i64 = 24 * 60 * 60;
+        let now = 1_700_000_000;
+        let task = |created_days_ago: i64, completed_days_ago: Option<i64>| {
+            let mut task = Task::new("Chore");
+            task.created_at = now - created_days_ago * DAY;
+            task.completed = completed_days_ago.is_some();
+            task.completed_at = completed_days_ago.map(|days| now - days * DAY);
+            task
+        };
+        let tasks = vec![
+            task(10, Some(2)),  // 8 days to complete, this week
+            task(20, Some(14)), // 6 days, this month
+            task(60, Some(45)), // 15 days, older than a month
+            task(3, None),
+            task(1, None),
+        ];
+
+        let stats = RealmStats::from_tasks(&tasks, now);
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.completed, 3);
+        assert_eq!(stats.open, 2);
+        assert_eq!(stats.completed_last_7_days, 1);
+        assert_eq!(stats.completed_last_30_days, 2);
+        assert_eq!(
+            stats.average_time_to_complete,
+            Some(Duration::from_secs((29 * DAY / 3) as u64))
         );
+        assert!((stats.completion_rate() - 0.6).abs() < f64::EPSILON);
 
-        // Also subscribe to the global topic for backwards compatibility and packet broadcast
-        // Packets (messages) are broadcast on the global topic so all contacts receive them
-        let global_topic_id = crate::sync::global_profile_topic();
-        let (global_sender, mut receiver) = gossip.subscribe_split(global_topic_id, bootstrap_peers).await?;
+        let empty = RealmStats::from_tasks(&[], now);
+        assert_eq!(empty.completion_rate(), 0.0);
+        assert_eq!(empty.average_time_to_complete, None);
+    }
 
-        // Store global sender for packet broadcasts (messages to contacts)
-        self.global_profile_gossip_sender = Some(global_sender);
+    #[tokio::test]
+    async fn test_realm_stats_counts_toggled_tasks() {
+        let (mut engine, _temp) = create_test_engine().await;
+        let realm_id = engine.create_realm("Stats").await.unwrap();
+        let done = engine.add_task(&realm_id, "Done").await.unwrap();
+        engine.add_task(&realm_id, "Open").await.unwrap();
+
+        engine.toggle_task(&realm_id, &done).await.unwrap();
+        let task = engine.get_task(&realm_id, &done).unwrap().unwrap();
+        assert!(task.completed_at.is_some(), "toggling done records when");
+
+        let stats = engine.realm_stats(&realm_id).unwrap();
+        assert_eq!((stats.total, stats.completed, stats.open), (2, 1, 1));
+        assert_eq!(stats.completed_last_7_days, 1);
+        assert_eq!(stats.average_time_to_complete.map(|d| d.as_secs() < 5), Some(true));
+    }
 
-        // Clone dependencies for the background task
-        let storage = self.storage.clone();
-        let blob_manager = self.blob_manager.clone();
-        let endpoint = gossip.endpoint().clone();
-        let contact_event_tx = self.contact_event_tx.clone();
+    #[tokio::test]
+    async fn test_toggle_task_records_completer() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        let realm_id = engine.create_realm("Chores").await.unwrap();
+        let task_id = engine.add_task(&realm_id, "Sweep the porch").await.unwrap();
 
-        // Spawn background task to process incoming profile messages
-        tokio::spawn(async move {
-            use crate::sync::TopicEvent;
+        let before = chrono::Utc::now().timestamp();
+        engine.toggle_task(&realm_id, &task_id).await.unwrap();
+        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert!(task.completed_at.is_some_and(|at| at >= before));
+        assert_eq!(task.completed_by, engine.did());
 
-            info!("Profile sync listener started with P2P blob download support");
+        engine.toggle_task(&realm_id, &task_id).await.unwrap();
+        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert_eq!(task.completed_at, None);
+        assert_eq!(task.completed_by, None);
+    }
 
-            while let Some(event) = receiver.recv_event().await {
-                match event {
-                    TopicEvent::Message(msg) => {
-                        // Try to parse as profile gossip message
-                        match crate::sync::ProfileGossipMessage::from_bytes(&msg.content) {
-                            Ok(profile_msg) => {
-                                match profile_msg {
-                                    crate::sync::ProfileGossipMessage::Announce {
-                                        signed_profile,
-                                        avatar_ticket,
-                                    } => {
-                                        // Verify signature
-                                        if !signed_profile.verify() {
-                                            warn!("Received profile announcement with invalid signature");
-                                            continue;
-                                        }
+    #[tokio::test]
+    async fn test_upload_avatar_cropped_to_golden_ratio() {
+        let engine = create_memory_engine().await;
+
+        // A wide 1600x900 photo cropped to portrait: 556x900, then scaled to fit 512
+        let photo = image::RgbImage::from_pixel(1600, 900, image::Rgb([120, 80, 200]));
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(photo)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
 
-                                        let signer_did = signed_profile.did().to_string();
+        let avatar = engine
+            .upload_avatar_cropped(png, ImageOrientation::Portrait)
+            .await
+            .unwrap();
+        let stored = engine.load_image(&avatar.blob_id).await.unwrap().unwrap();
+        let avatar = image::load_from_memory(&stored).unwrap();
+        assert_eq!((avatar.width(), avatar.height()), (316, 512));
+
+        let not_an_image = engine
+            .upload_avatar_cropped(b"definitely not a picture".to_vec(), ImageOrientation::Portrait)
+            .await;
+        assert!(matches!(not_an_image, Err(SyncError::InvalidImage(_))));
+    }
 
-                                        // Check if we should auto-pin (is a contact)
-                                        if let Ok(contacts) = storage.list_contacts() {
-                                            let is_contact = contacts.iter().any(|c| c.peer_did == signer_did);
+    #[tokio::test]
+    async fn test_upload_avatar_stores_separate_thumbnail() {
+        let engine = create_memory_engine().await;
 
-                                            if is_contact {
-                                                // Parse avatar ticket and check if we need to download
-                                                let avatar_hash = if let Some(ticket_str) = &avatar_ticket {
-                                                    if let Ok(blob_ticket) = ticket_str.parse::<iroh_blobs::ticket::BlobTicket>() {
-                                                        let hash = blob_ticket.hash();
+        let photo = image::RgbImage::from_fn(400, 300, |x, y| image::Rgb([x as u8, y as u8, 90]));
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(photo)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
 
-                                                        // Check if we already have this avatar
-                                                        let has_blob = blob_manager.has_blob(&hash).await.unwrap_or(false);
+        let avatar = engine.upload_avatar(png.clone()).await.unwrap();
+        let thumb_blob_id = avatar.thumb_blob_id.clone().unwrap();
+        assert_ne!(avatar.blob_id, thumb_blob_id);
+        assert_eq!(
+            engine.avatar_thumbnail(&avatar.blob_id).unwrap(),
+            Some(thumb_blob_id.clone())
+        );
+        assert_eq!(engine.load_image(&avatar.blob_id).await.unwrap(), Some(png));
+
+        let thumb = engine.load_image(&thumb_blob_id).await.unwrap().unwrap();
+        let thumb = image::load_from_memory(&thumb).unwrap();
+        assert!(thumb.width() <= crate::blobs::MAX_AVATAR_THUMB_EDGE);
+        assert!(thumb.height() <= crate::blobs::MAX_AVATAR_THUMB_EDGE);
+        assert_eq!(thumb.width(), crate::blobs::MAX_AVATAR_THUMB_EDGE);
+
+        // Thumbnails for avatars uploaded as plain images come out the same
+        let full = engine.load_image(&avatar.blob_id).await.unwrap().unwrap();
+        let image_id = engine.upload_image(full).await.unwrap();
+        assert_eq!(image_id, avatar.blob_id);
+        assert_eq!(
+            engine.create_avatar_thumbnail(&image_id).await.unwrap(),
+            thumb_blob_id
+        );
 
-                                                        if !has_blob {
-                                                            // Download avatar via P2P
-                                                            match blob_manager.download_blob(&blob_ticket, &endpoint).await {
-                                                                Ok(downloaded_hash) => {
-                                                                    info!(did = %signer_did, ?downloaded_hash, "Downloaded avatar via P2P");
-                                                                }
-                                                                Err(e) => {
-                                                                    warn!(did = %signer_did, error = %e, "Failed to download avatar via P2P");
-                                                                }
-                                                            }
-                                                        }
+        // Bytes that aren't an image still upload, just without a thumbnail
+        let raw = engine.upload_avatar(vec![0xAB; 1024]).await.unwrap();
+        assert_eq!(raw.thumb_blob_id, None);
+    }
 
-                                                        Some(*hash.as_bytes())
-                                                    } else {
-                                                        None
-                                                    }
-                                                } else {
-                                                    None
-                                                };
+    #[tokio::test]
+    async fn test_stewardship_summary_across_realms() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        let garden = engine.create_realm("Garden").await.unwrap();
+        let kitchen = engine.create_realm("Kitchen").await.unwrap();
+
+        // Garden: we add two tasks and complete one
+        let weed = engine.add_task(&garden, "Weed").await.unwrap();
+        engine.add_task(&garden, "Water").await.unwrap();
+        engine.toggle_task(&garden, &weed).await.unwrap();
+
+        // Kitchen: another member adds and completes a task, we complete it too
+        let neighbour = HybridKeypair::generate();
+        let neighbour_did = Did::from_public_key(&neighbour.public_key());
+        let mut theirs = Task::new("Bake bread");
+        theirs.created_by = Some(neighbour_did.to_string());
+        theirs.completed = true;
+        theirs.completed_by = Some(neighbour_did);
+        let mut shared = Task::new("Wash up");
+        shared.created_by = theirs.created_by.clone();
+        {
+            let state = engine.realms.get_mut(&kitchen).unwrap();
+            state.doc.insert_task(&theirs).unwrap();
+            state.doc.insert_task(&shared).unwrap();
+        }
+        engine.toggle_task(&kitchen, &shared.id).await.unwrap();
 
-                                                // Check if already pinned
-                                                let is_new_pin = storage.load_pinned_profile(&signer_did)
-                                                    .ok()
-                                                    .flatten()
-                                                    .is_none();
+        let summary = engine.stewardship_summary().unwrap();
+        let find = |id: &RealmId| {
+            summary
+                .realms
+                .iter()
+                .find(|realm| &realm.realm_id == id)
+                .unwrap()
+                .contribution
+        };
 
-                                                if let Ok(Some(mut existing)) = storage.load_pinned_profile(&signer_did) {
-                                                    // Update existing pin
-                                                    if existing.update_profile(signed_profile.clone()) {
-                                                        if let Some(hash) = avatar_hash {
-                                                            existing.avatar_hash = Some(hash);
-                                                        }
-                                                        let _ = storage.save_pinned_profile(&existing);
-                                                        debug!(did = %signer_did, "Updated pinned profile from gossip");
-                                                    }
-                                                } else {
-                                                    // Create new pin
-                                                    let mut pin = crate::types::ProfilePin::new(
-                                                        signer_did.clone(),
-                                                        signed_profile.clone(),
-                                                        crate::types::PinRelationship::Contact,
-                                                    );
-                                                    if let Some(hash) = avatar_hash {
-                                                        pin.avatar_hash = Some(hash);
-                                                    }
-                                                    let config = crate::storage::PinningConfig::default();
-                                                    let _ = storage.save_pinned_profile_with_limits(&pin, &config);
-                                                    debug!(did = %signer_did, "Auto-pinned contact profile from gossip");
-                                                }
+        let garden_stats = find(&garden);
+        assert_eq!((garden_stats.created, garden_stats.completed, garden_stats.activity), (2, 1, 3));
+        assert_eq!(garden_stats.share(), 1.0);
+
+        let kitchen_stats = find(&kitchen);
+        assert_eq!((kitchen_stats.created, kitchen_stats.completed, kitchen_stats.activity), (0, 1, 4));
+        assert_eq!(kitchen_stats.share(), 0.25);
+
+        // The Private realm's onboarding tasks weren't created by us
+        assert_eq!(summary.realms.len(), 3);
+        let private = summary.realms.iter().find(|realm| realm.name == "Private").unwrap();
+        assert_eq!(private.contribution.created, 0);
+        assert_eq!(summary.total.created, 2);
+        assert_eq!(summary.total.completed, 2);
+        assert_eq!(summary.total.activity, 7 + private.contribution.activity);
+    }
+
+    #[tokio::test]
+    async fn test_init_identity() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Initially no identity
+        assert!(!engine.has_identity());
+        assert!(engine.did().is_none());
+
+        // Initialize identity
+        engine.init_identity().unwrap();
 
-                                                // Also update Peer.profile for UI display
-                                                // This keeps the unified Peer record in sync with profile changes
-                                                if let Ok(Some(mut peer)) = storage.load_peer_by_did(&signer_did) {
-                                                    peer.profile = Some(crate::types::ProfileSnapshot {
-                                                        display_name: signed_profile.profile.display_name.clone(),
-                                                        subtitle: signed_profile.profile.subtitle.clone(),
-                                                        avatar_blob_id: signed_profile.profile.avatar_blob_id.clone(),
-                                                        bio: crate::types::ProfileSnapshot::truncate_bio(&signed_profile.profile.bio),
-                                                    });
-                                                    if let Err(e) = storage.save_peer(&peer) {
-                                                        warn!(did = %signer_did, error = %e, "Failed to update Peer.profile");
-                                                    } else {
-                                                        info!(did = %signer_did, "Updated peer profile from topic");
-                                                        // Notify UI of profile update
-                                                        let _ = contact_event_tx.send(crate::sync::ContactEvent::ProfileUpdated {
-                                                            did: signer_did.clone(),
-                                                        });
-                                                    }
-                                                }
+        // Now identity exists
+        assert!(engine.has_identity());
+        let did = engine.did();
+        assert!(did.is_some());
+        assert!(did.unwrap().as_str().starts_with("did:sync:z"));
+    }
 
-                                                // Note: PinAcknowledgment removed - Indra's Net derives pinners from contacts
-                                                // In Indra's Net, contacts automatically mirror each other
-                                                if is_new_pin {
-                                                    debug!(target_did = %signer_did, "New profile pinned (implicit mirroring)");
-                                                }
-                                            }
-                                        }
-                                    }
+    #[tokio::test]
+    async fn test_identity_persists() {
+        let temp_dir = TempDir::new().unwrap();
 
-                                    // Note: PinAcknowledgment and PinRemoval handlers removed
-                                    // Indra's Net derives pinners from contacts instead
+        // Create identity in first engine
+        let original_did = {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            engine.init_identity().unwrap();
+            engine.did().unwrap().to_string()
+        };
 
-                                    // Request and Response are for direct profile lookups
-                                    // They're handled by the profile processor in a different flow
-                                    crate::sync::ProfileGossipMessage::Request { .. } => {
-                                        debug!("Received profile request (handled by processor)");
-                                    }
-                                    crate::sync::ProfileGossipMessage::Response { .. } => {
-                                        debug!("Received profile response (handled by processor)");
-                                    }
+        // Load identity in second engine
+        {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            engine.init_identity().unwrap();
+            let loaded_did = engine.did().unwrap().to_string();
+            assert_eq!(original_did, loaded_did);
+        }
+    }
 
-                                    // Packet from a profile's append-only log
-                                    crate::sync::ProfileGossipMessage::Packet { envelope } => {
-                                        let sender_did = envelope.sender.to_string();
-                                        debug!(
-                                            sender = %sender_did,
-                                            sequence = envelope.sequence,
-                                            "Received packet from gossip"
-                                        );
+    #[tokio::test]
+    async fn test_regenerate_identity() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-                                        // Check if sender is a contact (we mirror contacts)
-                                        let should_mirror = storage.list_contacts()
-                                            .map(|contacts| contacts.iter().any(|c| c.peer_did == sender_did))
-                                            .unwrap_or(false);
+        engine.init_identity().unwrap();
+        let original_did = engine.did().unwrap().to_string();
 
-                                        if should_mirror {
-                                            // Create MirrorStore and store the packet
-                                            match MirrorStore::new(storage.db_handle()) {
-                                                Ok(mirror) => {
-                                                    match mirror.store_packet(&envelope) {
-                                                        Ok(_) => {
-                                                            info!(
-                                                                sender = %sender_did,
-                                                                sequence = envelope.sequence,
-                                                                "Stored packet in mirror"
-                                                            );
-                                                        }
-                                                        Err(e) => {
-                                                            warn!(
-                                                                sender = %sender_did,
-                                                                sequence = envelope.sequence,
-                                                                error = %e,
-                                                                "Failed to store packet in mirror"
-                                                            );
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    warn!(error = %e, "Failed to create MirrorStore");
-                                                }
-                                            }
-                                        } else {
-                                            debug!(
-                                                sender = %sender_did,
-                                                "Ignoring packet from non-contact"
-                                            );
-                                        }
-                                    }
+        // Regenerate
+        engine.regenerate_identity().unwrap();
+        let new_did = engine.did().unwrap().to_string();
 
-                                    // MeshUpdate messages are handled by contact topic listeners,
-                                    // not the global profile topic. Ignore them here.
-                                    crate::sync::ProfileGossipMessage::MeshUpdate { sender_did, .. } => {
-                                        debug!(
-                                            sender = %sender_did,
-                                            "MeshUpdate received on global topic (handled in contact topic)"
-                                        );
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                debug!("Failed to parse profile message: {}", e);
-                            }
-                        }
-                    }
-                    TopicEvent::NeighborUp(peer) => {
-                        debug!(?peer, "Profile topic neighbor joined");
-                    }
-                    TopicEvent::NeighborDown(peer) => {
-                        debug!(?peer, "Profile topic neighbor left");
-                    }
-                }
-            }
+        // Should be different
+        assert_ne!(original_did, new_did);
+    }
 
-            info!("Profile sync listener stopped");
-        });
+    #[tokio::test]
+    async fn test_regenerate_identity_preview_changes_nothing() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        let did = engine.did().unwrap();
 
-        info!("Profile sync started on global topic");
-        Ok(())
+        let preview = engine.regenerate_identity_preview().unwrap();
+        assert_eq!(preview.current_did, Some(did.clone()));
+        assert_eq!(preview.current_key_fingerprint.unwrap().len(), 16);
+        assert_eq!(preview.contact_count, 0);
+        assert_eq!(engine.did(), Some(did));
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Contact Operations
-    // ═══════════════════════════════════════════════════════════════════════
+    #[tokio::test]
+    async fn test_export_public_key_formats() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-    /// Generate a contact invite for sharing with peers
-    ///
-    /// Creates a signed invite containing profile information that can be shared
-    /// via QR code or text. The invite expires after the specified number of hours.
-    ///
-    /// # Arguments
-    ///
-    /// * `expiry_hours` - Hours until invite expires (max 168 = 7 days)
-    ///
-    /// # Returns
-    ///
-    /// A base58-encoded invite string prefixed with "sync-contact:"
-    ///
-    /// # Errors
-    ///
-    /// Returns error if contact manager initialization fails or profile cannot be loaded.
-    pub async fn generate_contact_invite(
-        &mut self,
-        expiry_hours: u8,
-    ) -> Result<String, SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+        // Test base58 format
+        let base58 = engine.export_public_key("base58");
+        assert!(base58.is_some());
 
-        // Get own profile to include in invite
-        let profile = self.get_own_profile()?;
+        // Test hex format
+        let hex = engine.export_public_key("hex");
+        assert!(hex.is_some());
 
-        // Create profile snapshot
-        let snapshot = ProfileSnapshot {
-            display_name: profile.display_name.clone(),
-            subtitle: profile.subtitle.clone(),
-            avatar_blob_id: profile.avatar_blob_id.clone(),
-            bio: ProfileSnapshot::truncate_bio(&profile.bio),
-        };
+        // Test json format
+        let json = engine.export_public_key("json");
+        assert!(json.is_some());
+        let json_str = json.unwrap();
+        assert!(json_str.contains("did"));
+        assert!(json_str.contains("public_key_base58"));
+    }
 
-        // Generate invite
-        manager.generate_invite(snapshot, expiry_hours)
+    #[tokio::test]
+    async fn test_node_info_includes_did() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Before init_identity, DID should be None
+        let info = engine.node_info().await.unwrap();
+        assert!(info.did.is_none());
+
+        // After init_identity, DID should be Some
+        engine.init_identity().unwrap();
+        let info = engine.node_info().await.unwrap();
+        assert!(info.did.is_some());
+        assert!(info.did.unwrap().starts_with("did:sync:z"));
     }
 
-    /// Decode a contact invite string
-    ///
-    /// Validates the invite signature, checks expiry, and verifies it hasn't been revoked.
-    ///
-    /// # Arguments
-    ///
-    /// * `invite_str` - The invite code (e.g., "sync-contact:...")
-    ///
-    /// # Returns
-    ///
-    /// The decoded and validated invite with profile information
-    ///
-    /// # Errors
-    ///
-    /// Returns error if invite is invalid, expired, or revoked.
-    pub async fn decode_contact_invite(
-        &mut self,
-        invite_str: &str,
-    ) -> Result<HybridContactInvite, SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+    #[tokio::test]
+    async fn test_shared_node_addr_is_added_to_registry() {
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (joy, _joy_dir) = create_test_engine().await;
+        assert!(love.node_addr().is_none(), "No address before networking");
 
-        // Decode invite
-        manager.decode_invite(invite_str)
+        love.start_networking().await.unwrap();
+        let addr = love.node_addr().unwrap();
+        let encoded = addr.encode().unwrap();
+        assert!(encoded.starts_with("sync-addr:"));
+
+        let parsed = NodeAddrBytes::decode(&encoded).unwrap();
+        assert_eq!(parsed, addr);
+        assert!(NodeAddrBytes::decode(&encoded.replace("sync-addr:", "sync-invite:")).is_err());
+
+        let peer_id = joy.add_peer_node_addr(&parsed).unwrap();
+        assert_eq!(peer_id.as_bytes(), &addr.node_id);
+        let peer = joy.peer_registry().get(&peer_id).unwrap().unwrap();
+        assert_eq!(peer.node_addr, Some(addr));
+        assert_eq!(peer.source, PeerSource::FromInvite);
+        assert_eq!(joy.peer_registry().count().unwrap(), 1);
+
+        // Adding it again updates the entry instead of duplicating it
+        joy.peer_registry().update_nickname(&peer_id, "Love").unwrap();
+        joy.add_peer_node_addr(&parsed).unwrap();
+        assert_eq!(joy.peer_registry().count().unwrap(), 1);
+        let peer = joy.peer_registry().get(&peer_id).unwrap().unwrap();
+        assert_eq!(peer.nickname.as_deref(), Some("Love"));
     }
 
-    /// Send a contact request to a peer
-    ///
-    /// Initiates the contact request protocol by sending a request to the inviter.
-    /// The request will be saved as OutgoingPending until the peer responds.
-    ///
-    /// # Arguments
-    ///
-    /// * `invite` - The decoded contact invite
-    ///
-    /// # Errors
-    ///
-    /// Returns error if contact manager initialization fails or request cannot be sent.
-    pub async fn send_contact_request(
-        &mut self,
-        invite: HybridContactInvite,
-    ) -> Result<(), SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+    // ═══════════════════════════════════════════════════════════════════════
+    // Identity Tests (required by task)
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_engine_init_identity_creates_new() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Initially no identity
+        assert!(!engine.has_identity());
+        assert!(engine.did().is_none());
+        assert!(engine.public_key().is_none());
+
+        // Initialize identity
+        engine.init_identity().unwrap();
+
+        // Identity should now exist
+        assert!(engine.has_identity());
+        let did = engine.did().unwrap();
+        assert!(did.as_str().starts_with("did:sync:z"));
 
-        // Get own profile to include in request
-        let profile = self.get_own_profile()?;
+        // Public key should also be available
+        let public_key = engine.public_key();
+        assert!(public_key.is_some());
+    }
 
-        // Create profile snapshot
-        let snapshot = ProfileSnapshot {
-            display_name: profile.display_name.clone(),
-            subtitle: profile.subtitle.clone(),
-            avatar_blob_id: profile.avatar_blob_id.clone(),
-            bio: ProfileSnapshot::truncate_bio(&profile.bio),
+    #[tokio::test]
+    async fn test_engine_init_identity_loads_existing() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Create identity in first engine instance
+        let (original_did, original_pk_bytes) = {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            engine.init_identity().unwrap();
+            let did = engine.did().unwrap();
+            let pk = engine.public_key().unwrap();
+            (did.to_string(), pk.to_bytes())
         };
 
-        // Send contact request
-        manager.send_contact_request(invite, snapshot).await
-    }
+        // Load identity in new engine instance
+        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
 
-    /// Accept an incoming contact request
-    ///
-    /// Accepts a pending contact request and finalizes the connection if both
-    /// parties have accepted. Once mutually accepted, the contact is saved and
-    /// both nodes subscribe to a shared 1:1 gossip topic.
-    ///
-    /// # Arguments
-    ///
-    /// * `invite_id` - The unique invite ID from the pending request
-    ///
-    /// # Errors
-    ///
-    /// Returns error if invite_id not found or contact finalization fails.
-    pub async fn accept_contact(&mut self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+        // Before init, no identity in memory
+        assert!(!engine2.has_identity());
 
-        // Accept contact request
-        manager.accept_contact_request(invite_id).await
-    }
+        // Init should load existing identity
+        engine2.init_identity().unwrap();
 
-    /// Decline an incoming contact request
-    ///
-    /// Rejects a pending contact request and removes it from storage.
-    ///
-    /// # Arguments
-    ///
-    /// * `invite_id` - The unique invite ID from the pending request
-    ///
-    /// # Errors
-    ///
-    /// Returns error if invite_id not found.
-    pub async fn decline_contact(&mut self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+        // Verify it's the same identity
+        let loaded_did = engine2.did().unwrap().to_string();
+        let loaded_pk = engine2.public_key().unwrap();
 
-        // Decline contact request
-        manager.decline_contact_request(invite_id).await
+        assert_eq!(original_did, loaded_did);
+        assert_eq!(original_pk_bytes, loaded_pk.to_bytes());
     }
 
-    /// Cancel an outgoing contact request
-    ///
-    /// Removes the pending request and revokes the invite so it can no longer be used.
-    ///
-    /// # Arguments
-    ///
-    /// * `invite_id` - The unique invite ID of the outgoing request
-    ///
-    /// # Errors
-    ///
-    /// Returns error if invite_id not found or not in OutgoingPending state.
-    pub async fn cancel_outgoing_request(&mut self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+    #[tokio::test]
+    async fn test_engine_sign_and_verify() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Cancel outgoing request
-        manager.cancel_outgoing_request(invite_id)
-    }
+        // Sign without identity should fail
+        let message = b"Test message to sign";
+        let result = engine.sign(message);
+        assert!(result.is_err());
 
-    /// List all accepted contacts
-    ///
-    /// Returns all contacts that have been mutually accepted.
-    ///
-    /// # Returns
-    ///
-    /// Vector of all stored contacts with their online/offline status.
-    pub fn list_contacts(&self) -> Result<Vec<ContactInfo>, SyncError> {
-        self.storage.list_contacts()
-    }
+        // Initialize identity
+        engine.init_identity().unwrap();
 
-    /// Compute mutual peers dynamically for a given contact.
-    ///
-    /// Returns all contacts we share in common with the target peer.
-    /// This is computed dynamically rather than using stored values to avoid
-    /// race conditions during simultaneous contact creation in mesh topology.
-    ///
-    /// Used by:
-    /// - Relay routing (proactive relay to offline contacts)
-    /// - UI display (BioCard mutual contacts count)
-    ///
-    /// Since we can't know the target's full contact list, this uses a
-    /// simplified approach: any of OUR contacts (excluding the target) can
-    /// serve as potential mutual peers. For relay, the relay peer verifies
-    /// they know the target before forwarding.
-    ///
-    /// # Arguments
-    ///
-    /// * `target_did` - The DID of the contact to compute mutual peers for
-    ///
-    /// # Returns
-    ///
-    /// Vector of DIDs representing mutual/potential relay peers
-    pub fn compute_mutual_peers_with(&self, target_did: &str) -> Vec<String> {
-        // Get all our contacts
-        let our_contacts = match self.storage.list_contacts() {
-            Ok(contacts) => contacts,
-            Err(e) => {
-                tracing::warn!(error = %e, "Failed to list contacts for relay computation");
-                return Vec::new();
-            }
-        };
+        // Sign should now succeed
+        let signature = engine.sign(message).unwrap();
 
-        // Any of our contacts (except the target) can serve as potential relays.
-        // The relay peer will verify they have the target as a contact before forwarding.
-        our_contacts
-            .into_iter()
-            .map(|c| c.peer_did)
-            .filter(|did| did != target_did)
-            .collect()
-    }
+        // Get our public key for verification
+        let public_key = engine.public_key().unwrap();
 
-    /// List pending contact requests
-    ///
-    /// Returns both incoming (awaiting our response) and outgoing (awaiting their response)
-    /// pending contact requests.
-    ///
-    /// # Returns
-    ///
-    /// Tuple of (incoming_requests, outgoing_requests)
-    pub fn list_pending_contacts(&self) -> Result<(Vec<PendingContact>, Vec<PendingContact>), SyncError> {
-        let incoming = self.storage.list_incoming_pending()?;
-        let outgoing = self.storage.list_outgoing_pending()?;
-        Ok((incoming, outgoing))
+        // Verify should succeed with correct message
+        assert!(engine.verify(&public_key, message, &signature));
+
+        // Verify should fail with different message
+        let wrong_message = b"Different message";
+        assert!(!engine.verify(&public_key, wrong_message, &signature));
+
+        // Verify should fail with different public key
+        let other_keypair = crate::identity::HybridKeypair::generate();
+        let other_pk = other_keypair.public_key();
+        assert!(!engine.verify(&other_pk, message, &signature));
     }
 
     // ═══════════════════════════════════════════════════════════════════════
-    // Unified Peer Operations
+    // Profile Packet Tests (Indra's Network)
     // ═══════════════════════════════════════════════════════════════════════
 
-    /// List all unified peers
-    ///
-    /// Returns all known network participants, including both contacts
-    /// (mutually accepted peers with full identity) and discovered peers
-    /// (seen via realm gossip but not yet contacts).
-    pub fn list_peers(&self) -> Result<Vec<crate::types::peer::Peer>, SyncError> {
-        self.storage.list_peers()
-    }
+    #[tokio::test]
+    async fn test_init_profile_keys_creates_new() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-    /// Get a peer by endpoint ID
-    pub fn get_peer(&self, endpoint_id: &iroh::PublicKey) -> Result<Option<crate::types::peer::Peer>, SyncError> {
-        self.storage.load_peer(endpoint_id)
-    }
+        // Initially no profile keys
+        assert!(!engine.has_profile_keys());
+        assert!(engine.profile_did().is_none());
 
-    /// Get a peer by DID
-    ///
-    /// Uses the DID index for fast lookup.
-    pub fn get_peer_by_did(&self, did: &str) -> Result<Option<crate::types::peer::Peer>, SyncError> {
-        self.storage.load_peer_by_did(did)
-    }
+        // Identity must be initialized before profile keys
+        engine.init_identity().unwrap();
+        let identity_did = engine.did().unwrap();
 
-    /// List only contacts (peers with mutual acceptance)
-    ///
-    /// These are peers where `contact_info` is Some, meaning both parties
-    /// have accepted the contact exchange.
-    pub fn list_peer_contacts(&self) -> Result<Vec<crate::types::peer::Peer>, SyncError> {
-        self.storage.list_peer_contacts()
-    }
+        // Initialize profile keys (derived from identity)
+        engine.init_profile_keys().unwrap();
 
-    /// List only discovered peers (non-contacts)
-    ///
-    /// These are peers seen via realm gossip but not yet mutually accepted
-    /// as contacts.
-    pub fn list_discovered_peers(&self) -> Result<Vec<crate::types::peer::Peer>, SyncError> {
-        self.storage.list_discovered_peers()
-    }
+        // Profile keys should now exist
+        assert!(engine.has_profile_keys());
+        let profile_did = engine.profile_did().unwrap();
+        assert!(profile_did.as_str().starts_with("did:sync:z"));
 
-    /// Save a unified peer
-    ///
-    /// Updates or creates a peer record. Also updates the DID index
-    /// if the peer has a DID.
-    pub fn save_peer(&self, peer: &crate::types::peer::Peer) -> Result<(), SyncError> {
-        self.storage.save_peer(peer)
+        // Profile DID should match Identity DID (unified system)
+        assert_eq!(profile_did, identity_did, "Profile DID should match Identity DID");
     }
 
-    /// Run migration from old contact/peer tables to unified peers
-    ///
-    /// This is idempotent - safe to call multiple times.
-    /// Returns the number of peers migrated.
-    pub fn migrate_to_unified_peers(&self) -> Result<usize, SyncError> {
-        self.storage.migrate_to_unified_peers()
-    }
+    #[tokio::test]
+    async fn test_profile_keys_persist_across_instances() {
+        let temp_dir = TempDir::new().unwrap();
 
-    /// Subscribe to contact events
-    ///
-    /// Returns a broadcast receiver for real-time contact event notifications.
-    /// Events include new requests, acceptances, online/offline status changes, etc.
-    ///
-    /// # Returns
-    ///
-    /// Broadcast receiver for ContactEvent messages
-    pub async fn subscribe_contact_events(
-        &mut self,
-    ) -> Result<broadcast::Receiver<ContactEvent>, SyncError> {
-        // Ensure contact manager is initialized
-        let manager = self.ensure_contact_manager().await?;
+        // Create profile keys in first engine instance
+        let original_did = {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            engine.init_identity().unwrap();
+            engine.init_profile_keys().unwrap();
+            engine.profile_did().unwrap().to_string()
+        };
 
-        // Subscribe to events
-        Ok(manager.subscribe_events())
-    }
+        // Load profile keys in new engine instance
+        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Packet Event API (for Indra's Network visualization)
-    // ═══════════════════════════════════════════════════════════════════════
+        // Before init, no keys in memory
+        assert!(!engine2.has_profile_keys());
 
-    /// Get packet events for a specific peer.
-    ///
-    /// Returns the recent packet events associated with this peer,
-    /// useful for displaying packet flow in the network visualization.
-    ///
-    /// # Arguments
-    ///
-    /// * `peer_did` - The peer's decentralized identifier
-    ///
-    /// # Returns
-    ///
-    /// A vector of [`PacketEvent`] in chronological order (oldest first).
-    pub fn get_packet_events_for_peer(&self, peer_did: &str) -> Vec<crate::sync::PacketEvent> {
-        let events = self.packet_event_buffer.get_events_for_peer(peer_did);
-        info!(
-            peer_did = %peer_did,
-            event_count = events.len(),
-            total_in_buffer = self.packet_event_buffer.total_event_count(),
-            "Getting packet events for peer"
-        );
-        events
-    }
+        // Init identity and profile keys (should load existing)
+        engine2.init_identity().unwrap();
+        engine2.init_profile_keys().unwrap();
 
-    /// Get all packet events for all peers.
-    ///
-    /// Returns a map of peer DID → events for the network visualization.
-    pub fn get_all_packet_events(
-        &self,
-    ) -> std::collections::HashMap<String, Vec<crate::sync::PacketEvent>> {
-        self.packet_event_buffer.get_all_events()
-    }
+        // Verify it's the same identity
+        let loaded_did = engine2.profile_did().unwrap().to_string();
+        assert_eq!(original_did, loaded_did);
 
-    /// Subscribe to real-time packet events.
-    ///
-    /// Returns a receiver that will receive all new packet events as they occur.
-    /// Useful for updating the UI in real-time as packets flow through the network.
-    pub fn subscribe_packet_events(
-        &self,
-    ) -> tokio::sync::broadcast::Receiver<crate::sync::PacketEvent> {
-        self.packet_event_buffer.subscribe()
+        // Also verify profile DID matches identity DID
+        assert_eq!(engine2.did().unwrap(), engine2.profile_did().unwrap());
     }
 
-    /// Record a packet event (internal use).
-    ///
-    /// This is called when packets are sent or received to log them
-    /// for the network visualization.
-    pub(crate) fn record_packet_event(&self, event: crate::sync::PacketEvent) {
-        self.packet_event_buffer.record(event);
-    }
+    #[tokio::test]
+    async fn test_profile_log_initialized_with_keys() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-    /// Get the packet event buffer (for sharing with other components).
-    pub(crate) fn packet_event_buffer(&self) -> Arc<crate::sync::PacketEventBuffer> {
-        self.packet_event_buffer.clone()
-    }
+        // Before profile keys init, log should be None
+        assert!(engine.my_log().is_none());
+        assert_eq!(engine.log_head_sequence(), 0);
 
-    /// Load historical packet events from storage into the in-memory buffer.
-    ///
-    /// This method populates the packet event buffer with packets that were
-    /// stored in MirrorStore (received) and ProfileLog (sent) from previous
-    /// sessions. Call this on startup to show historical packets in the
-    /// network visualization.
-    ///
-    /// # Returns
-    ///
-    /// The number of events loaded into the buffer.
-    pub fn load_historical_packet_events(&self) -> Result<usize, SyncError> {
-        let mut loaded_count = 0;
+        // Identity must be initialized before profile keys
+        engine.init_identity().unwrap();
 
-        // Get our DID for identifying sent messages
-        let my_did = self.profile_did()
-            .ok_or_else(|| SyncError::Identity("Profile keys not initialized".to_string()))?;
-        let my_did_str = my_did.as_str().to_string();
+        // Initialize profile keys (derived from identity)
+        engine.init_profile_keys().unwrap();
 
-        // Get profile keys for decryption
-        let profile_keys = self.storage.load_profile_keys()?;
+        // Log should now be available
+        assert!(engine.my_log().is_some());
+        assert_eq!(engine.log_head_sequence(), 0);
+    }
 
-        // Get all contacts
-        let contacts = self.list_peer_contacts()?;
+    #[tokio::test]
+    async fn test_create_global_packet() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        for contact in &contacts {
-            let contact_did = match &contact.did {
-                Some(did) => did.clone(),
-                None => continue,
-            };
-            let contact_name = contact.display_name();
+        // Initialize identity and profile keys
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
 
-            // Parse contact DID
-            let did = match Did::parse(&contact_did) {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
+        // Create a global (public) packet
+        let payload = crate::profile::PacketPayload::Heartbeat {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
 
-            // Load received packets from MirrorStore for this contact
-            if let Ok(received_packets) = self.mirror_packets_all(&did) {
-                for envelope in received_packets {
-                    let sender_did = envelope.sender.as_str().to_string();
+        let sequence = engine
+            .create_packet(payload, crate::profile::PacketAddress::Global)
+            .unwrap();
 
-                    // Determine decryption status and content preview
-                    let (decryption_status, content_preview) = if envelope.is_global() {
-                        let preview = match envelope.decode_global_payload() {
-                            Ok(PacketPayload::DirectMessage { content, .. }) => {
-                                crate::sync::PacketEvent::preview_content(&content)
-                            }
-                            Ok(_) => "[profile update]".to_string(),
-                            Err(_) => "[global]".to_string(),
-                        };
-                        (crate::sync::DecryptionStatus::Global, preview)
-                    } else if let Some(ref keys) = profile_keys {
-                        match envelope.decrypt_for_recipient(keys) {
-                            Ok(payload) => {
-                                let preview = match payload {
-                                    PacketPayload::DirectMessage { content, .. } => {
-                                        crate::sync::PacketEvent::preview_content(&content)
-                                    }
-                                    _ => "[packet]".to_string(),
-                                };
-                                (crate::sync::DecryptionStatus::Decrypted, preview)
-                            }
-                            Err(_) => {
-                                (crate::sync::DecryptionStatus::CannotDecrypt {
-                                    reason: "not recipient".to_string()
-                                }, "[encrypted]".to_string())
-                            }
-                        }
-                    } else {
-                        (crate::sync::DecryptionStatus::NotAttempted, "[encrypted]".to_string())
-                    };
+        // Should be sequence 0 (first packet)
+        assert_eq!(sequence, 0);
 
-                    let event = crate::sync::PacketEvent {
-                        id: crate::sync::PacketEvent::make_id(&sender_did, envelope.sequence),
-                        timestamp: chrono::Utc::now().timestamp_millis(), // Historical, use now
-                        direction: crate::sync::PacketDirection::Incoming,
-                        sequence: envelope.sequence,
-                        author_did: sender_did,
-                        author_name: contact_name.clone(),
-                        relay_did: None,
-                        relay_name: None,
-                        destination_did: my_did_str.clone(),
-                        destination_name: "Me".to_string(),
-                        decryption_status,
-                        content_preview,
-                        is_delivered: false,
-                        peer_did: contact_did.clone(),
-                    };
-                    self.packet_event_buffer.record(event);
-                    loaded_count += 1;
-                }
-            }
+        // Log should now have head sequence 0
+        assert_eq!(engine.log_head_sequence(), 0);
 
-            // Load sent packets from ProfileLog for this contact
-            if let Some(ref log) = self.profile_log {
-                for entry in log.entries_ordered() {
-                    // Check if this packet is addressed to this contact
-                    if let Some(payload) = self.decrypt_packet(&entry.envelope) {
-                        if let PacketPayload::DirectMessage { ref recipient, content, .. } = payload {
-                            if recipient == &did {
-                                let event = crate::sync::PacketEvent {
-                                    id: crate::sync::PacketEvent::make_id(&my_did_str, entry.envelope.sequence),
-                                    timestamp: chrono::Utc::now().timestamp_millis(),
-                                    direction: crate::sync::PacketDirection::Outgoing,
-                                    sequence: entry.envelope.sequence,
-                                    author_did: my_did_str.clone(),
-                                    author_name: "Me".to_string(),
-                                    relay_did: None,
-                                    relay_name: None,
-                                    destination_did: contact_did.clone(),
-                                    destination_name: contact_name.clone(),
-                                    decryption_status: crate::sync::DecryptionStatus::Decrypted,
-                                    content_preview: crate::sync::PacketEvent::preview_content(&content),
-                                    is_delivered: false,
-                                    peer_did: contact_did.clone(),
-                                };
-                                self.packet_event_buffer.record(event);
-                                loaded_count += 1;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        // Create another packet
+        let payload2 = crate::profile::PacketPayload::Heartbeat {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
 
-        info!(
-            loaded_count,
-            contact_count = contacts.len(),
-            "Loaded historical packet events from storage"
-        );
+        let sequence2 = engine
+            .create_packet(payload2, crate::profile::PacketAddress::Global)
+            .unwrap();
 
-        Ok(loaded_count)
+        assert_eq!(sequence2, 1);
+        assert_eq!(engine.log_head_sequence(), 1);
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Chat API
-    // ═══════════════════════════════════════════════════════════════════════
-
-    /// Send a direct message to a contact.
-    ///
-    /// Creates a DirectMessage packet, encrypts it for the recipient,
-    /// and broadcasts it via the 1:1 contact gossip topic.
-    ///
-    /// # Arguments
-    ///
-    /// * `contact_did` - The recipient's decentralized identifier
-    /// * `content` - The message content to send
-    ///
-    /// # Returns
-    ///
-    /// The sequence number of the sent packet.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Profile keys not initialized
-    /// - Contact not found or keys not exchanged
-    /// - Network error during broadcast
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let seq = engine.send_message("did:sync:friend", "Hello!").await?;
-    /// println!("Sent message with sequence {}", seq);
-    /// ```
-    pub async fn send_message(&mut self, contact_did: &str, content: &str) -> Result<u64, SyncError> {
-        let did = Did::parse(contact_did)?;
+    #[tokio::test]
+    async fn test_create_packet_requires_profile_keys() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        let payload = PacketPayload::DirectMessage {
-            content: content.to_string(),
-            recipient: did.clone(),
-        };
+        // Don't initialize profile keys
 
-        let address = PacketAddress::Individual(did);
+        // Create packet should fail
+        let payload = crate::profile::PacketPayload::Heartbeat {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
 
-        self.create_and_broadcast_packet(payload, address).await
+        let result = engine.create_packet(payload, crate::profile::PacketAddress::Global);
+        assert!(result.is_err());
     }
 
-    /// Get conversation with a specific contact.
-    ///
-    /// Loads all messages exchanged with the contact (both sent and received)
-    /// and returns them as a [`Conversation`].
-    ///
-    /// # Arguments
-    ///
-    /// * `contact_did` - The contact's decentralized identifier
-    ///
-    /// # Returns
-    ///
-    /// A [`Conversation`] containing all messages with this contact.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let convo = engine.get_conversation("did:sync:friend")?;
-    /// println!("Messages with {}: {}", convo.display_name(), convo.len());
-    /// for msg in convo.messages() {
-    ///     println!("{}: {}", msg.display_sender(), msg.content);
-    /// }
-    /// ```
-    pub fn get_conversation(&self, contact_did: &str) -> Result<crate::chat::Conversation, SyncError> {
-        let did = Did::parse(contact_did)?;
+    #[tokio::test]
+    async fn test_handle_incoming_packet() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Get contact info for display name
-        let peer = self.get_peer_by_did(contact_did)?;
-        let contact_name = crate::chat::get_contact_display_name(peer.as_ref());
+        // Create a packet from another profile
+        let other_keys = crate::profile::ProfileKeys::generate();
+        let payload = crate::profile::PacketPayload::Heartbeat {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+        let envelope = crate::profile::PacketEnvelope::create_global(
+            &other_keys,
+            &payload,
+            0,
+            [0u8; 32],
+        )
+        .unwrap();
 
-        // Get our DID for identifying sent messages
-        let my_did = self.profile_did()
-            .ok_or_else(|| SyncError::Identity("Profile keys not initialized".to_string()))?;
-        let my_did_str = my_did.as_str().to_string();
+        // Handle the incoming packet
+        let is_new = engine.handle_incoming_packet(envelope.clone()).unwrap();
+        assert!(is_new);
 
-        // Load received packets from this contact
-        // DIAGNOSTIC: Use info level to trace message retrieval
-        info!(
-            contact_did = %contact_did,
-            did_parsed = %did.as_str(),
-            did_bytes = ?did.as_str().as_bytes(),
-            "get_conversation: loading packets from MirrorStore"
-        );
+        // Check mirror has the packet
+        let head = engine.mirror_head(&other_keys.did());
+        assert_eq!(head, Some(0));
 
-        // Use mirror_packets_all() instead of mirror_packets_since(&did, 0)
-        // because get_since(0) excludes sequence 0 (off-by-one bug fix)
-        let received_packets = match self.mirror_packets_all(&did) {
-            Ok(packets) => {
-                info!(
-                    contact_did = %contact_did,
-                    did_parsed = %did.as_str(),
-                    packet_count = packets.len(),
-                    "get_conversation: loaded {} received packets from MirrorStore",
-                    packets.len()
-                );
-                packets
-            }
-            Err(e) => {
-                warn!(
-                    contact_did = %contact_did,
-                    did_parsed = %did.as_str(),
-                    error = %e,
-                    "get_conversation: FAILED to load received packets from MirrorStore"
-                );
-                vec![]
-            }
-        };
+        // Handle the same packet again (should be duplicate)
+        let is_new2 = engine.handle_incoming_packet(envelope).unwrap();
+        assert!(!is_new2);
+    }
 
-        // Load ALL sent packets first (can't borrow self inside closure due to borrow checker)
-        let all_sent_packets: Vec<PacketEnvelope> = self
-            .profile_log
-            .as_ref()
-            .map(|log| {
-                log.entries_ordered()
-                    .into_iter()
-                    .map(|entry| entry.envelope.clone())
-                    .collect()
-            })
-            .unwrap_or_default();
+    #[tokio::test]
+    async fn test_decrypt_global_packet() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Filter to only DirectMessages addressed to this contact by checking payload recipient
-        // NOTE: We can't use is_addressed_to() because global packets (sealed_keys empty)
-        // return true for ALL DIDs. Instead, we decrypt and check the actual recipient field.
-        let sent_packets: Vec<PacketEnvelope> = all_sent_packets
-            .into_iter()
-            .filter(|envelope| {
-                if let Some(payload) = self.decrypt_packet(envelope) {
-                    if let PacketPayload::DirectMessage { ref recipient, .. } = payload {
-                        return recipient == &did;
-                    }
-                }
-                false
-            })
-            .collect();
+        // Initialize identity and profile keys
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
 
-        // DIAGNOSTIC: Summary at info level for easy debugging
-        info!(
-            contact_did = %contact_did,
-            total_in_profile_log = self.profile_log.as_ref().map(|l| l.len()).unwrap_or(0),
-            sent_to_this_contact = sent_packets.len(),
-            received_from_this_contact = received_packets.len(),
-            "get_conversation: SUMMARY - sent={}, received={}",
-            sent_packets.len(),
-            received_packets.len()
-        );
+        // Create a global packet from another profile
+        let other_keys = crate::profile::ProfileKeys::generate();
+        let my_did = engine.profile_did().unwrap();
+        let payload = crate::profile::PacketPayload::DirectMessage {
+            content: "Hello, world!".to_string(),
+            recipient: my_did,
+        };
+        let envelope = crate::profile::PacketEnvelope::create_global(
+            &other_keys,
+            &payload,
+            0,
+            [0u8; 32],
+        )
+        .unwrap();
 
-        // Build conversation using the helper
-        let conversation = crate::chat::build_conversation(
-            contact_did,
-            contact_name,
-            received_packets,
-            sent_packets,
-            &my_did_str,
-            |envelope| self.decrypt_packet(envelope),
-        );
+        // Global packets can be decrypted (really just decoded) by anyone
+        let decrypted = engine.decrypt_packet(&envelope);
+        assert!(decrypted.is_some());
 
-        Ok(conversation)
+        match decrypted.unwrap() {
+            crate::profile::PacketPayload::DirectMessage { content, recipient: _ } => {
+                assert_eq!(content, "Hello, world!");
+            }
+            _ => panic!("Wrong payload type"),
+        }
     }
 
-    /// List all conversations sorted by last activity.
-    ///
-    /// Returns conversations with all contacts who have exchanged messages,
-    /// sorted with most recent activity first.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let conversations = engine.list_conversations()?;
-    /// for convo in conversations {
-    ///     if let Some(preview) = convo.preview(50) {
-    ///         println!("{}: {} - \"{}\"",
-    ///             convo.display_name(),
-    ///             convo.last_message().map(|m| m.relative_time()).unwrap_or_default(),
-    ///             preview
-    ///         );
-    ///     }
-    /// }
-    /// ```
-    pub fn list_conversations(&self) -> Result<Vec<crate::chat::Conversation>, SyncError> {
-        // Get all contacts
-        let contacts = self.list_peer_contacts()?;
+    #[tokio::test]
+    async fn test_incoming_packets_record_decryption_status() {
+        use crate::profile::{PacketEnvelope, PacketPayload, ProfileKeys};
+        use crate::sync::{DecryptionStatus, PacketEventFilter};
 
-        let mut conversations = Vec::new();
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
+        let my_did = engine.profile_did().unwrap();
+        let my_keys = engine.profile_keys.as_ref().unwrap().public_bundle();
 
-        for contact in contacts {
-            // Get contact DID from the peer's did field
-            if let Some(ref did) = contact.did {
-                if let Ok(convo) = self.get_conversation(did) {
-                    // Only include conversations with messages
-                    if !convo.is_empty() {
-                        conversations.push(convo);
-                    }
-                }
-            }
-        }
+        let message = |recipient: &Did| PacketPayload::DirectMessage {
+            content: "Meet at the garden".to_string(),
+            recipient: recipient.clone(),
+        };
 
-        // Sort by last activity (most recent first)
-        conversations.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+        // Sealed for us
+        let friend = ProfileKeys::generate();
+        let for_us = PacketEnvelope::create(&friend, &message(&my_did), &[my_keys.clone()], 0, [0u8; 32]).unwrap();
+
+        // Sealed for someone else, passing through us
+        let relayed = ProfileKeys::generate();
+        let stranger = ProfileKeys::generate();
+        let for_stranger = PacketEnvelope::create(
+            &relayed,
+            &message(&stranger.did()),
+            &[stranger.public_bundle()],
+            0,
+            [0u8; 32],
+        )
+        .unwrap();
 
-        Ok(conversations)
+        // Sealed for us, but tampered with in transit
+        let attacker = ProfileKeys::generate();
+        let mut tampered = PacketEnvelope::create(&attacker, &message(&my_did), &[my_keys], 0, [0u8; 32]).unwrap();
+        tampered.ciphertext[0] ^= 0xff;
+
+        for envelope in [for_us, for_stranger, tampered] {
+            assert!(engine.handle_incoming_packet(envelope).unwrap());
+        }
+
+        let status_of = |sender: &ProfileKeys| {
+            let events = engine.packet_events(PacketEventFilter::new().peer(sender.did().to_string()));
+            assert_eq!(events.len(), 1);
+            (events[0].decryption_status.clone(), events[0].content_preview.clone())
+        };
+        assert_eq!(
+            status_of(&friend),
+            (DecryptionStatus::Decrypted, "Meet at the garden".to_string())
+        );
+        assert_eq!(status_of(&relayed), (DecryptionStatus::NotForMe, "[encrypted]".to_string()));
+        let (failed, preview) = status_of(&attacker);
+        assert!(matches!(failed, DecryptionStatus::Failed { .. }));
+        assert_eq!(preview, "[encrypted]");
+
+        // Only the tampered packet counts as a failure
+        assert_eq!(engine.packet_decryption_failures(), 1);
+        assert_eq!(engine.packet_decryption_failures_from(attacker.did().as_str()), 1);
+        assert_eq!(engine.packet_decryption_failures_from(relayed.did().as_str()), 0);
     }
 
-    /// Get new messages from a contact since a specific sequence.
-    ///
-    /// Useful for polling for updates in UI.
-    ///
-    /// # Arguments
-    ///
-    /// * `contact_did` - The contact's DID
-    /// * `since_seq` - Return messages with sequence > this value
-    ///
-    /// # Returns
-    ///
-    /// Vector of new messages since the given sequence.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let mut last_seq = 0;
-    /// loop {
-    ///     let new_msgs = engine.get_new_messages("did:sync:friend", last_seq)?;
-    ///     for msg in &new_msgs {
-    ///         println!("New: {}", msg.content);
-    ///         if msg.sequence > last_seq {
-    ///             last_seq = msg.sequence;
-    ///         }
-    ///     }
-    ///     tokio::time::sleep(Duration::from_secs(1)).await;
-    /// }
-    /// ```
-    pub fn get_new_messages(
-        &self,
-        contact_did: &str,
-        since_seq: u64,
-    ) -> Result<Vec<crate::chat::ChatMessage>, SyncError> {
-        let did = Did::parse(contact_did)?;
+    // ═══════════════════════════════════════════════════════════════════════
+    // Networking Tests
+    // ═══════════════════════════════════════════════════════════════════════
 
-        // Get contact info for display name
-        let peer = self.get_peer_by_did(contact_did)?;
-        let contact_name = crate::chat::get_contact_display_name(peer.as_ref());
+    #[tokio::test]
+    async fn test_start_networking() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Get our DID
-        let my_did = self.profile_did()
-            .ok_or_else(|| SyncError::Identity("Profile keys not initialized".to_string()))?;
-        let my_did_str = my_did.as_str().to_string();
+        // Initially not active
+        assert!(!engine.is_networking_active());
 
-        // Get packets since the sequence
-        let packets = self.mirror_packets_since(&did, since_seq)?;
+        // Start networking
+        engine.start_networking().await.unwrap();
 
-        // Convert to ChatMessages
-        let messages: Vec<crate::chat::ChatMessage> = packets
-            .iter()
-            .filter_map(|envelope| {
-                self.decrypt_packet(envelope).and_then(|payload| {
-                    crate::chat::extract_chat_message(
-                        envelope,
-                        &payload,
-                        &my_did_str,
-                        contact_name.clone(),
-                    )
-                })
-            })
-            .collect();
+        // Now active
+        assert!(engine.is_networking_active());
 
-        Ok(messages)
+        // Node info should have node_id
+        let info = engine.node_info().await.unwrap();
+        assert!(info.node_id.is_some());
+
+        // Starting again is a no-op (doesn't error)
+        engine.start_networking().await.unwrap();
+        assert!(engine.is_networking_active());
+
+        engine.shutdown().await.unwrap();
     }
 
-    /// Get unread message count across all conversations.
-    ///
-    /// Returns the total number of unread messages (messages received
-    /// after our last reply in each conversation).
-    pub fn total_unread_count(&self) -> Result<usize, SyncError> {
-        let conversations = self.list_conversations()?;
-        Ok(conversations.iter().map(|c| c.unread_count()).sum())
+    #[tokio::test]
+    async fn test_start_networking_then_sync() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Create a realm
+        let realm_id = engine.create_realm("Sync Test").await.unwrap();
+
+        // Start networking first
+        engine.start_networking().await.unwrap();
+        assert!(engine.is_networking_active());
+
+        // Now start syncing the realm
+        engine.start_sync(&realm_id).await.unwrap();
+        assert!(engine.is_realm_syncing(&realm_id));
+
+        engine.shutdown().await.unwrap();
     }
 
     // ═══════════════════════════════════════════════════════════════════════
-    // Image Blob Operations
+    // Sync Envelope Tests
     // ═══════════════════════════════════════════════════════════════════════
 
-    /// Upload an image and return its content hash
-    ///
-    /// The image data is stored content-addressed using BLAKE3.
-    /// Returns the hash hex string that can be used as image_blob_id.
-    pub async fn upload_image(&self, data: Vec<u8>) -> Result<String, SyncError> {
-        let hash = self.blob_manager.import_image(data).await?;
-        Ok(BlobManager::hash_to_blob_id(&hash))
-    }
+    #[tokio::test]
+    async fn test_broadcast_sync_sends_envelope() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-    /// Upload an avatar image with size validation (256 KB limit)
-    ///
-    /// Returns the content hash as a hex string blob ID.
-    pub async fn upload_avatar(&self, data: Vec<u8>) -> Result<String, SyncError> {
-        let hash = self.blob_manager.import_avatar(data).await?;
-        Ok(BlobManager::hash_to_blob_id(&hash))
-    }
+        // Initialize identity (required for signing)
+        engine.init_identity().unwrap();
 
-    /// Load an image by its content hash
-    pub async fn load_image(&self, hash_hex: &str) -> Result<Option<Vec<u8>>, SyncError> {
-        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
-        let bytes = self.blob_manager.get_bytes(&hash).await?;
-        Ok(bytes.map(|b| b.to_vec()))
-    }
+        // Create realm and start syncing
+        let realm_id = engine.create_realm("Envelope Test").await.unwrap();
+        engine.start_sync(&realm_id).await.unwrap();
 
-    /// Check if an image blob exists
-    pub async fn image_exists(&self, hash_hex: &str) -> Result<bool, SyncError> {
-        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
-        self.blob_manager.has_blob(&hash).await
-    }
+        // Create a sync message
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads: vec![vec![0u8; 32]],
+            sender_addr: None,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
 
-    /// Get the size of an image blob in bytes
-    pub async fn image_size(&self, hash_hex: &str) -> Result<Option<u64>, SyncError> {
-        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
-        self.blob_manager.blob_size(&hash).await
-    }
+        // Broadcast should succeed (message goes to empty topic, but no error)
+        let result = engine.broadcast_sync(&realm_id, message).await;
+        assert!(result.is_ok());
 
-    /// Delete an image blob
-    ///
-    /// Note: iroh-blobs uses garbage collection based on tags.
-    /// Blobs without tags are eventually cleaned up.
-    pub async fn delete_image(&self, hash_hex: &str) -> Result<(), SyncError> {
-        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
-        self.blob_manager.delete_blob(&hash).await
+        engine.shutdown().await.unwrap();
     }
 
-    /// Get a reference to the blob manager
-    ///
-    /// This can be used for advanced blob operations like P2P downloads.
-    pub fn blob_manager(&self) -> &BlobManager {
-        &self.blob_manager
-    }
+    #[tokio::test]
+    async fn test_broadcast_sync_requires_identity() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-    /// Create a blob ticket for sharing an image via P2P
-    ///
-    /// Returns the base58-encoded ticket string.
-    /// Requires gossip networking to be initialized.
-    pub async fn create_image_ticket(&self, hash_hex: &str) -> Result<String, SyncError> {
-        let hash = BlobManager::blob_id_to_hash(hash_hex)?;
-        let gossip = self.ensure_gossip_ref()?;
-        let ticket = self.blob_manager.create_ticket(hash, gossip.endpoint());
-        Ok(ticket.to_string())
-    }
+        // Create realm and start syncing WITHOUT initializing identity
+        let realm_id = engine.create_realm("No Identity Test").await.unwrap();
+        engine.start_sync(&realm_id).await.unwrap();
 
-    /// Download an image from a peer using a ticket
-    ///
-    /// Returns the content hash as a hex string blob ID.
-    pub async fn download_image_from_ticket(&mut self, ticket_str: &str) -> Result<String, SyncError> {
-        let gossip = self.ensure_gossip().await?;
-        let hash = self.blob_manager.download_from_ticket_str(ticket_str, gossip.endpoint()).await?;
-        Ok(BlobManager::hash_to_blob_id(&hash))
-    }
+        // Create a sync message
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads: vec![],
+            sender_addr: None,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
 
-    /// Get this node's endpoint address
-    ///
-    /// Returns the EndpointAddr which can be used by other nodes to connect.
-    /// Returns None if networking is not active.
-    #[cfg(test)]
-    pub fn endpoint_addr(&self) -> Option<iroh::EndpointAddr> {
-        self.gossip.as_ref().map(|g| g.endpoint_addr())
+        // Broadcast should fail because identity is not initialized
+        let result = engine.broadcast_sync(&realm_id, message).await;
+        assert!(matches!(result, Err(SyncError::Identity(_))));
+
+        engine.shutdown().await.unwrap();
     }
 
-    /// Add a peer's address to the discovery system
-    ///
-    /// This allows sending messages to the peer directly.
-    #[cfg(test)]
-    pub fn add_peer_addr(&self, addr: iroh::EndpointAddr) {
-        if let Some(ref gossip) = self.gossip {
-            gossip.add_peer_addr(addr);
-        }
+    #[tokio::test]
+    async fn test_broadcast_sync_requires_syncing() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Initialize identity
+        engine.init_identity().unwrap();
+
+        // Create realm but DON'T start syncing
+        let realm_id = engine.create_realm("Not Syncing Test").await.unwrap();
+
+        // Create a sync message
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads: vec![],
+            sender_addr: None,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
+
+        // Broadcast should fail because realm is not syncing
+        let result = engine.broadcast_sync(&realm_id, message).await;
+        assert!(matches!(result, Err(SyncError::Gossip(_))));
     }
 
-    /// Wait for a peer to connect to a realm's sync topic
-    ///
-    /// Returns true if a peer connected within the timeout, false otherwise.
-    #[cfg(test)]
-    pub async fn wait_for_peer_connection(
-        &self,
-        realm_id: &RealmId,
-        wait_duration: std::time::Duration,
-    ) -> bool {
-        let mut events = self.event_tx.subscribe();
-        let start = std::time::Instant::now();
+    #[tokio::test]
+    async fn test_handle_incoming_valid_envelope() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        loop {
-            let remaining = wait_duration.saturating_sub(start.elapsed());
-            if remaining.is_zero() {
-                debug!(%realm_id, "Timeout waiting for peer connection");
-                return false;
-            }
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Initialize identity
+        engine.init_identity().unwrap();
+
+        // Create realm
+        let realm_id = engine.create_realm("Incoming Test").await.unwrap();
+
+        // Get the realm key to create a valid envelope
+        let realm_key = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
+
+        // Create a peer keypair and pin their profile (simulating a known contact)
+        let peer_keypair = HybridKeypair::generate();
+        let peer_profile = UserProfile::new("peer_sender".to_string(), "Peer Sender".to_string());
+        let signed_profile = SignedProfile::sign(&peer_profile, &peer_keypair);
+        let sender_did = signed_profile.did().to_string();
+
+        // Pin the peer's profile so they're recognized
+        engine
+            .pin_profile(signed_profile, PinRelationship::Contact)
+            .unwrap();
+
+        // Create a valid envelope from the peer
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads: vec![vec![1, 2, 3]],
+            sender_addr: None,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
+
+        let sign_fn = |data: &[u8]| peer_keypair.sign(data).to_bytes();
+
+        let envelope = SyncEnvelope::seal(&message, &sender_did, &realm_key, sign_fn).unwrap();
+        let envelope_bytes = envelope.to_bytes().unwrap();
+
+        // Process the envelope
+        let result = engine.handle_incoming(&realm_id, &envelope_bytes);
+        assert!(result.is_ok());
 
-            match tokio::time::timeout(remaining, events.recv()).await {
-                Ok(Ok(SyncEvent::PeerConnected {
-                    realm_id: event_realm,
-                    peer_id,
-                })) => {
-                    if &event_realm == realm_id {
-                        debug!(%realm_id, %peer_id, "Peer connected");
-                        return true;
-                    }
-                }
-                Ok(Ok(_)) => {
-                    // Other event, keep waiting
-                    continue;
-                }
-                Ok(Err(_)) => {
-                    // Channel lagged, keep trying
-                    continue;
-                }
-                Err(_) => {
-                    // Timeout
-                    debug!(%realm_id, "Timeout waiting for peer connection");
-                    return false;
-                }
+        let maybe_message = result.unwrap();
+        assert!(maybe_message.is_some());
+
+        // Verify the message content
+        match maybe_message.unwrap() {
+            SyncMessage::Announce { heads, .. } => {
+                assert_eq!(heads.len(), 1);
+                assert_eq!(heads[0], vec![1, 2, 3]);
             }
+            _ => panic!("Expected Announce message"),
         }
     }
 
-    /// Gracefully shutdown the engine
-    ///
-    /// Saves all open realms and shuts down the gossip network.
-    pub async fn shutdown(mut self) -> Result<(), SyncError> {
-        info!("Shutting down SyncEngine");
+    #[tokio::test]
+    async fn test_handle_incoming_invalid_signature() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Save all open realms
-        let realm_ids: Vec<_> = self.realms.keys().cloned().collect();
-        for realm_id in realm_ids {
-            if let Err(e) = self.save_realm(&realm_id).await {
-                warn!(%realm_id, error = ?e, "Failed to save realm during shutdown");
-            }
-        }
+        // Initialize identity
+        engine.init_identity().unwrap();
 
-        // Shutdown gossip
-        if let Some(gossip) = self.gossip.take() {
-            if let Ok(gossip) = Arc::try_unwrap(gossip) {
-                if let Err(e) = gossip.shutdown().await {
-                    warn!(error = ?e, "Failed to shutdown gossip cleanly");
-                }
-            }
-        }
+        // Create realm
+        let realm_id = engine.create_realm("Invalid Sig Test").await.unwrap();
 
-        info!("SyncEngine shutdown complete");
-        Ok(())
-    }
-}
+        // Get the realm key
+        let realm_key = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
 
-/// Information about this node
-#[derive(Debug, Clone)]
-pub struct NodeInfo {
-    /// Directory where data is stored
-    pub data_dir: PathBuf,
-    /// Number of realms
-    pub realm_count: usize,
-    /// Node's public key (when P2P is active)
-    pub node_id: Option<String>,
-    /// Relay URL (when P2P is active)
-    pub relay_url: Option<String>,
-    /// Decentralized identifier (when identity is initialized)
-    pub did: Option<String>,
-}
+        // Create an envelope with an empty signature (invalid)
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads: vec![],
+            sender_addr: None,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
 
-/// Network statistics for the Network page.
-///
-/// Provides summary counts for peers, pinners, and pins.
-#[derive(Debug, Clone, Default)]
-pub struct NetworkStats {
-    /// Total number of known peers
-    pub total_peers: usize,
-    /// Number of currently online peers
-    pub online_peers: usize,
-    /// Number of peers pinning our profile ("Souls Carrying Your Light")
-    pub pinners_count: usize,
-    /// Number of profiles we are pinning ("Souls You Carry")
-    pub pinning_count: usize,
-}
+        let mut envelope = SyncEnvelope::seal(&message, "did:example:test", &realm_key, |_| {
+            vec![0x51, 0x9E, 1]
+        })
+        .unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+        // Tamper with the signature to make it invalid (empty)
+        envelope.signature = vec![];
 
-    async fn create_test_engine() -> (SyncEngine, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-        (engine, temp_dir)
+        let envelope_bytes = envelope.to_bytes().unwrap();
+
+        // Process the envelope - should return None because signature is invalid
+        let result = engine.handle_incoming(&realm_id, &envelope_bytes);
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_none()); // Returns None for invalid signature
     }
 
     #[tokio::test]
-    async fn test_engine_creates() {
-        let (engine, _temp) = create_test_engine().await;
-        let info = engine.node_info().await.unwrap();
-        // Private realm is auto-created
-        assert_eq!(info.realm_count, 1);
+    async fn test_handle_incoming_wrong_key() {
+        let (mut engine, _temp) = create_test_engine().await;
+
+        // Initialize identity
+        engine.init_identity().unwrap();
+
+        // Create realm
+        let realm_id = engine.create_realm("Wrong Key Test").await.unwrap();
+
+        // Create an envelope with a DIFFERENT key
+        let wrong_key = [99u8; 32];
+        let message = SyncMessage::Announce {
+            realm_id: realm_id.clone(),
+            heads: vec![],
+            sender_addr: None,
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
+
+        let keypair = engine.identity.as_ref().unwrap();
+        let sender_did = crate::identity::Did::from_public_key(&keypair.public_key()).to_string();
+        let sign_fn = |data: &[u8]| keypair.sign(data).to_bytes();
+
+        let envelope = SyncEnvelope::seal(&message, &sender_did, &wrong_key, sign_fn).unwrap();
+        let envelope_bytes = envelope.to_bytes().unwrap();
+
+        // Process the envelope - should return None because decryption fails
+        let result = engine.handle_incoming(&realm_id, &envelope_bytes);
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_none()); // Returns None for decryption failure
     }
 
     #[tokio::test]
-    async fn test_engine_create_realm_persists() {
+    async fn test_sync_after_task_add() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        // Create a realm
-        let realm_id = engine.create_realm("Test Realm").await.unwrap();
+        // Initialize identity
+        engine.init_identity().unwrap();
 
-        // Verify it's in storage (should have Private + Test Realm)
-        let realms = engine.list_realms().await.unwrap();
-        assert_eq!(realms.len(), 2);
-        let test_realm = realms.iter().find(|r| r.name == "Test Realm").unwrap();
-        assert_eq!(test_realm.id, realm_id);
+        // Create realm and start syncing
+        let realm_id = engine.create_realm("Auto Sync Test").await.unwrap();
+        engine.start_sync(&realm_id).await.unwrap();
 
-        // Verify document exists
-        let doc_bytes = engine.storage.load_document(&realm_id).unwrap();
-        assert!(doc_bytes.is_some());
+        // Add a task
+        let task_id = engine.add_task(&realm_id, "Test task").await.unwrap();
+        assert!(!task_id.to_string().is_empty());
 
-        // Verify key exists
-        let key = engine.storage.load_realm_key(&realm_id).unwrap();
-        assert!(key.is_some());
+        // Sync realm state - should broadcast Announce
+        let result = engine.sync_realm_state(&realm_id).await;
+        assert!(result.is_ok());
+
+        // Broadcast changes - should broadcast Changes message
+        let result = engine.broadcast_changes(&realm_id).await;
+        assert!(result.is_ok());
+
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_engine_open_realm_loads() {
-        let temp_dir = TempDir::new().unwrap();
-
-        // Create realm in first engine instance
-        let realm_id = {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            let id = engine.create_realm("Persisted Realm").await.unwrap();
+    async fn test_apply_incoming_changes() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-            // Add a task
-            engine.add_task(&id, "Persisted task").await.unwrap();
+        // Create realm
+        let realm_id = engine.create_realm("Apply Changes Test").await.unwrap();
 
-            id
-        };
+        // Get initial task count
+        let initial_tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(initial_tasks.len(), 0);
 
-        // Open realm in second engine instance
-        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
-        assert!(!engine2.is_realm_open(&realm_id));
+        // Create a fork with changes
+        let state = engine.realms.get_mut(&realm_id).unwrap();
+        let mut forked_doc = state.doc.fork();
+        forked_doc.add_task("Synced task from peer").unwrap();
+        let changes = forked_doc.generate_sync_message();
 
-        engine2.open_realm(&realm_id).await.unwrap();
-        assert!(engine2.is_realm_open(&realm_id));
+        // Apply the changes
+        engine
+            .apply_incoming_changes(&realm_id, &changes)
+            .await
+            .unwrap();
 
-        // Verify task was persisted
-        let tasks = engine2.list_tasks(&realm_id).unwrap();
+        // Verify the task was added
+        let tasks = engine.list_tasks(&realm_id).unwrap();
         assert_eq!(tasks.len(), 1);
-        assert_eq!(tasks[0].title, "Persisted task");
+        assert_eq!(tasks[0].title, "Synced task from peer");
     }
 
     #[tokio::test]
-    async fn test_engine_task_operations() {
+    async fn test_apply_full_document() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("Task Test").await.unwrap();
+        // Create realm with a task
+        let realm_id = engine.create_realm("Apply Doc Test").await.unwrap();
+        engine.add_task(&realm_id, "Local task").await.unwrap();
 
-        // Add tasks
-        let task1 = engine.add_task(&realm_id, "Task 1").await.unwrap();
-        let task2 = engine.add_task(&realm_id, "Task 2").await.unwrap();
+        // Fork our own document to create a "remote" document with shared history
+        let remote_bytes = {
+            let state = engine.realms.get_mut(&realm_id).unwrap();
+            let mut remote_doc = state.doc.fork();
+            remote_doc.add_task("Remote task 1").unwrap();
+            remote_doc.add_task("Remote task 2").unwrap();
+            remote_doc.save()
+        };
 
-        // List tasks
-        let tasks = engine.list_tasks(&realm_id).unwrap();
-        assert_eq!(tasks.len(), 2);
+        // Apply the full document
+        engine
+            .apply_full_document(&realm_id, &remote_bytes)
+            .await
+            .unwrap();
 
-        // Toggle task
-        engine.toggle_task(&realm_id, &task1).await.unwrap();
+        // Verify merge - should have all three tasks
         let tasks = engine.list_tasks(&realm_id).unwrap();
-        let toggled_task = tasks.iter().find(|t| t.id == task1).unwrap();
-        assert!(toggled_task.completed);
+        assert_eq!(tasks.len(), 3);
+
+        let titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert!(titles.contains(&"Local task"));
+        assert!(titles.contains(&"Remote task 1"));
+        assert!(titles.contains(&"Remote task 2"));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Multi-Realm Sync Tests
+    // ═══════════════════════════════════════════════════════════════════════
 
-        // Delete task
-        engine.delete_task(&realm_id, &task2).await.unwrap();
-        let tasks = engine.list_tasks(&realm_id).unwrap();
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(tasks[0].id, task1);
+    #[tokio::test]
+    async fn test_sync_status_returns_idle_for_unsynced_realm() {
+        let (engine, _temp) = create_test_engine().await;
+        let realm_id = RealmId::new();
+
+        assert_eq!(engine.sync_status(&realm_id), SyncStatus::Idle);
     }
 
     #[tokio::test]
-    async fn test_engine_generate_invite() {
+    async fn test_sync_status_changes_when_sync_starts() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("Invite Test").await.unwrap();
+        let realm_id = engine.create_realm("Status Test").await.unwrap();
 
-        // Generate invite (starts gossip)
-        let invite = engine.generate_invite(&realm_id).await.unwrap();
+        // Initially idle
+        assert_eq!(engine.sync_status(&realm_id), SyncStatus::Idle);
 
-        // Verify invite contains our realm info
-        assert_eq!(invite.realm_id(), realm_id);
-        assert!(invite.bootstrap_peers.len() >= 1);
+        // Start sync
+        engine.start_sync(&realm_id).await.unwrap();
 
-        // Verify realm is marked as shared
-        let info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
-        assert!(info.is_shared);
+        // Should be syncing now
+        let status = engine.sync_status(&realm_id);
+        assert!(matches!(status, SyncStatus::Syncing { .. }));
 
-        // Clean shutdown
         engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_engine_realm_not_found() {
+    async fn test_sync_status_returns_idle_after_stop() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let fake_id = RealmId::new();
+        let realm_id = engine.create_realm("Stop Status Test").await.unwrap();
 
-        // open_realm should fail
-        let result = engine.open_realm(&fake_id).await;
-        assert!(matches!(result, Err(SyncError::RealmNotFound(_))));
+        // Start and stop sync
+        engine.start_sync(&realm_id).await.unwrap();
+        assert!(matches!(
+            engine.sync_status(&realm_id),
+            SyncStatus::Syncing { .. }
+        ));
 
-        // list_tasks should fail (realm not open)
-        let result = engine.list_tasks(&fake_id);
-        assert!(matches!(result, Err(SyncError::RealmNotFound(_))));
+        engine.stop_sync(&realm_id).await.unwrap();
+        assert_eq!(engine.sync_status(&realm_id), SyncStatus::Idle);
+
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_engine_sync_lifecycle() {
+    async fn test_multiple_realms_can_sync_concurrently() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("Sync Test").await.unwrap();
+        // Create multiple realms
+        let realm1 = engine.create_realm("Realm 1").await.unwrap();
+        let realm2 = engine.create_realm("Realm 2").await.unwrap();
+        let realm3 = engine.create_realm("Realm 3").await.unwrap();
 
-        // Not syncing initially
-        assert!(!engine.is_realm_syncing(&realm_id));
+        // Start syncing all three
+        engine.start_sync(&realm1).await.unwrap();
+        engine.start_sync(&realm2).await.unwrap();
+        engine.start_sync(&realm3).await.unwrap();
 
-        // Start sync
-        engine.start_sync(&realm_id).await.unwrap();
-        assert!(engine.is_realm_syncing(&realm_id));
+        // All should be syncing
+        assert!(engine.is_realm_syncing(&realm1));
+        assert!(engine.is_realm_syncing(&realm2));
+        assert!(engine.is_realm_syncing(&realm3));
 
-        // Starting again is a no-op
-        engine.start_sync(&realm_id).await.unwrap();
-        assert!(engine.is_realm_syncing(&realm_id));
+        // Check status for each
+        assert!(matches!(
+            engine.sync_status(&realm1),
+            SyncStatus::Syncing { .. }
+        ));
+        assert!(matches!(
+            engine.sync_status(&realm2),
+            SyncStatus::Syncing { .. }
+        ));
+        assert!(matches!(
+            engine.sync_status(&realm3),
+            SyncStatus::Syncing { .. }
+        ));
 
-        // Stop sync
-        engine.stop_sync(&realm_id).await.unwrap();
-        assert!(!engine.is_realm_syncing(&realm_id));
+        // Verify syncing_count
+        assert_eq!(engine.syncing_count(), 3);
 
-        // Stopping again is a no-op
-        engine.stop_sync(&realm_id).await.unwrap();
-        assert!(!engine.is_realm_syncing(&realm_id));
+        // Verify syncing_realms
+        let syncing = engine.syncing_realms();
+        assert!(syncing.contains(&realm1));
+        assert!(syncing.contains(&realm2));
+        assert!(syncing.contains(&realm3));
 
         engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_engine_auto_save() {
-        let temp_dir = TempDir::new().unwrap();
+    async fn test_start_sync_multiple_realms_without_blocking() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            let id = engine.create_realm("Auto Save Test").await.unwrap();
+        // Create realms
+        let realm1 = engine.create_realm("Realm A").await.unwrap();
+        let realm2 = engine.create_realm("Realm B").await.unwrap();
 
-            // Add task (should auto-save)
-            engine.add_task(&id, "Auto saved task").await.unwrap();
+        // Measure time for starting both syncs
+        let start = std::time::Instant::now();
 
-            id
-        };
+        engine.start_sync(&realm1).await.unwrap();
+        engine.start_sync(&realm2).await.unwrap();
 
-        // Reload and verify
-        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
-        engine2.open_realm(&realm_id).await.unwrap();
+        let elapsed = start.elapsed();
 
-        let tasks = engine2.list_tasks(&realm_id).unwrap();
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(tasks[0].title, "Auto saved task");
+        // Should complete quickly (not blocking)
+        assert!(
+            elapsed.as_millis() < 5000,
+            "start_sync took too long: {:?}",
+            elapsed
+        );
+
+        // Both should be syncing
+        assert!(engine.is_realm_syncing(&realm1));
+        assert!(engine.is_realm_syncing(&realm2));
+
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_create_and_decode_invite() {
+    async fn test_stop_one_realm_does_not_affect_others() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("Shared Realm").await.unwrap();
-        let ticket_str = engine.create_invite(&realm_id).await.unwrap();
+        let realm1 = engine.create_realm("Keep Syncing").await.unwrap();
+        let realm2 = engine.create_realm("Stop Syncing").await.unwrap();
 
-        // Verify ticket is valid
-        assert!(ticket_str.starts_with("sync-invite:"));
+        engine.start_sync(&realm1).await.unwrap();
+        engine.start_sync(&realm2).await.unwrap();
 
-        // Realm should now be marked as shared
-        let realm = engine.get_realm(&realm_id).await.unwrap().unwrap();
-        assert!(realm.is_shared);
+        // Stop only realm2
+        engine.stop_sync(&realm2).await.unwrap();
 
-        // Decode and verify
-        let ticket = InviteTicket::decode(&ticket_str).unwrap();
-        assert_eq!(ticket.realm_id(), realm_id);
+        // realm1 should still be syncing
+        assert!(engine.is_realm_syncing(&realm1));
+        assert!(!engine.is_realm_syncing(&realm2));
+
+        assert!(matches!(
+            engine.sync_status(&realm1),
+            SyncStatus::Syncing { .. }
+        ));
+        assert_eq!(engine.sync_status(&realm2), SyncStatus::Idle);
 
         engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_join_realm_already_member() {
+    async fn test_subscribe_events_receives_status_changes() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("Shared").await.unwrap();
-        let ticket = engine.generate_invite(&realm_id).await.unwrap();
+        let realm_id = engine.create_realm("Event Test").await.unwrap();
 
-        // Try to join our own realm
-        let result = engine.join_via_invite(&ticket).await;
-        assert!(matches!(result, Err(SyncError::InvalidInvite(_))));
+        // Subscribe before starting sync
+        let mut events = engine.subscribe_events();
 
-        engine.shutdown().await.unwrap();
-    }
+        // Start sync - should emit events
+        engine.start_sync(&realm_id).await.unwrap();
 
-    #[tokio::test]
-    async fn test_data_persists_across_restarts() {
-        let temp_dir = TempDir::new().unwrap();
+        // Try to receive events (with timeout to avoid hanging)
+        let mut found_connecting = false;
+        let mut found_syncing = false;
 
-        // Create engine and data
-        {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            let realm_id = engine.create_realm("Persistent").await.unwrap();
-            engine.add_task(&realm_id, "Persisted task").await.unwrap();
+        // Use a short timeout for testing
+        for _ in 0..10 {
+            match tokio::time::timeout(std::time::Duration::from_millis(100), events.recv()).await {
+                Ok(Ok(SyncEvent::StatusChanged {
+                    status: SyncStatus::Connecting,
+                    ..
+                })) => {
+                    found_connecting = true;
+                }
+                Ok(Ok(SyncEvent::StatusChanged {
+                    status: SyncStatus::Syncing { .. },
+                    ..
+                })) => {
+                    found_syncing = true;
+                }
+                _ => break,
+            }
         }
 
-        // Reopen and verify
-        {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            let realms = engine.list_realms().await.unwrap();
-            // Should have Private + Persistent
-            assert_eq!(realms.len(), 2);
-            let persistent_realm = realms
-                .iter()
-                .find(|r| r.name == "Persistent")
-                .expect("Persistent realm should exist");
+        // Should have received at least the connecting or syncing event
+        assert!(
+            found_connecting || found_syncing,
+            "Should receive status change events"
+        );
 
-            // Need to open the realm to access tasks
-            engine.open_realm(&persistent_realm.id).await.unwrap();
-            let tasks = engine.list_tasks(&persistent_realm.id).unwrap();
-            assert_eq!(tasks.len(), 1);
-            assert_eq!(tasks[0].title, "Persisted task");
-        }
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_multiple_realms() {
+    async fn test_local_task_changes_emit_events() {
         let (mut engine, _temp) = create_test_engine().await;
+        let realm_id = engine.create_realm("Task Events").await.unwrap();
+        let mut events = engine.subscribe_events();
 
-        // Create multiple realms
-        let realm1 = engine.create_realm("Realm 1").await.unwrap();
-        let realm2 = engine.create_realm("Realm 2").await.unwrap();
-        let realm3 = engine.create_realm("Realm 3").await.unwrap();
-
-        // Add tasks to each
-        engine.add_task(&realm1, "Task in Realm 1").await.unwrap();
-        engine.add_task(&realm2, "Task in Realm 2").await.unwrap();
-        engine.add_task(&realm3, "Task in Realm 3").await.unwrap();
-
-        // Verify list_realms (Private + 3 created = 4)
-        let realms = engine.list_realms().await.unwrap();
-        assert_eq!(realms.len(), 4);
+        let task_id = engine.add_task(&realm_id, "Water tomatoes").await.unwrap();
+        engine.toggle_task(&realm_id, &task_id).await.unwrap();
+        engine.delete_task(&realm_id, &task_id).await.unwrap();
 
-        // Verify tasks are isolated
-        assert_eq!(engine.list_tasks(&realm1).unwrap().len(), 1);
-        assert_eq!(engine.list_tasks(&realm2).unwrap().len(), 1);
-        assert_eq!(engine.list_tasks(&realm3).unwrap().len(), 1);
+        for _ in 0..3 {
+            match events.try_recv() {
+                Ok(SyncEvent::TaskChanged {
+                    realm_id: r,
+                    task_id: t,
+                }) => {
+                    assert_eq!(r, realm_id);
+                    assert_eq!(t, task_id.to_string_repr());
+                }
+                other => panic!("Expected TaskChanged, got {:?}", other),
+            }
+        }
+        assert!(events.try_recv().is_err());
     }
 
     #[tokio::test]
-    async fn test_delete_realm() {
+    async fn test_syncing_realms_returns_only_active() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("To Delete").await.unwrap();
-        engine.add_task(&realm_id, "Task").await.unwrap();
+        let realm1 = engine.create_realm("Active 1").await.unwrap();
+        let realm2 = engine.create_realm("Active 2").await.unwrap();
+        let realm3 = engine.create_realm("Inactive").await.unwrap();
 
-        engine.delete_realm(&realm_id).await.unwrap();
+        // Start syncing only realm1 and realm2
+        engine.start_sync(&realm1).await.unwrap();
+        engine.start_sync(&realm2).await.unwrap();
 
-        let realm = engine.get_realm(&realm_id).await.unwrap();
-        assert!(realm.is_none());
+        let syncing = engine.syncing_realms();
+        assert_eq!(syncing.len(), 2);
+        assert!(syncing.contains(&realm1));
+        assert!(syncing.contains(&realm2));
+        assert!(!syncing.contains(&realm3));
 
-        // Should no longer be open
-        assert!(!engine.is_realm_open(&realm_id));
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_toggle_task() {
+    async fn test_syncing_count_tracks_active_syncs() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm_id = engine.create_realm("Tasks").await.unwrap();
-        let task_id = engine.add_task(&realm_id, "Toggle me").await.unwrap();
+        assert_eq!(engine.syncing_count(), 0);
 
-        // Initially not completed
-        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
-        assert!(!task.completed);
+        let realm1 = engine.create_realm("Count 1").await.unwrap();
+        let realm2 = engine.create_realm("Count 2").await.unwrap();
 
-        // Toggle to completed
-        engine.toggle_task(&realm_id, &task_id).await.unwrap();
-        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
-        assert!(task.completed);
+        engine.start_sync(&realm1).await.unwrap();
+        assert_eq!(engine.syncing_count(), 1);
 
-        // Toggle back
-        engine.toggle_task(&realm_id, &task_id).await.unwrap();
-        let task = engine.get_task(&realm_id, &task_id).unwrap().unwrap();
-        assert!(!task.completed);
+        engine.start_sync(&realm2).await.unwrap();
+        assert_eq!(engine.syncing_count(), 2);
+
+        engine.stop_sync(&realm1).await.unwrap();
+        assert_eq!(engine.syncing_count(), 1);
+
+        engine.stop_sync(&realm2).await.unwrap();
+        assert_eq!(engine.syncing_count(), 0);
+
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_init_identity() {
+    async fn test_can_add_tasks_while_syncing_multiple_realms() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        // Initially no identity
-        assert!(!engine.has_identity());
-        assert!(engine.did().is_none());
+        let realm1 = engine.create_realm("Tasks Realm 1").await.unwrap();
+        let realm2 = engine.create_realm("Tasks Realm 2").await.unwrap();
 
-        // Initialize identity
-        engine.init_identity().unwrap();
+        // Start syncing both
+        engine.start_sync(&realm1).await.unwrap();
+        engine.start_sync(&realm2).await.unwrap();
 
-        // Now identity exists
-        assert!(engine.has_identity());
-        let did = engine.did();
-        assert!(did.is_some());
-        assert!(did.unwrap().as_str().starts_with("did:sync:z"));
+        // Add tasks to both realms while syncing
+        let task1 = engine.add_task(&realm1, "Task in Realm 1").await.unwrap();
+        let task2 = engine.add_task(&realm2, "Task in Realm 2").await.unwrap();
+
+        // Verify tasks were added
+        let tasks1 = engine.list_tasks(&realm1).unwrap();
+        let tasks2 = engine.list_tasks(&realm2).unwrap();
+
+        assert_eq!(tasks1.len(), 1);
+        assert_eq!(tasks2.len(), 1);
+        assert_eq!(tasks1[0].id, task1);
+        assert_eq!(tasks2[0].id, task2);
+
+        engine.shutdown().await.unwrap();
     }
 
+    /// Test that two engines can sync tasks via invite flow
+    ///
+    /// This is the critical user flow:
+    /// 1. Love creates a realm and generates an invite
+    /// 2. Joy joins via the invite
+    /// 3. Love adds a task
+    /// 4. Joy should see Love's task (after sync propagates)
     #[tokio::test]
-    async fn test_identity_persists() {
-        let temp_dir = TempDir::new().unwrap();
+    async fn test_two_engines_sync_tasks_via_invite() {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
+        use std::time::Duration;
 
-        // Create identity in first engine
-        let original_did = {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            engine.init_identity().unwrap();
-            engine.did().unwrap().to_string()
-        };
+        let _ = tracing_subscriber::fmt::try_init();
 
-        // Load identity in second engine
+        // Create Love's engine
+        let temp_dir_love = TempDir::new().unwrap();
+        let mut love = SyncEngine::new(temp_dir_love.path()).await.unwrap();
+        love.init_identity().unwrap(); // Required for signing sync messages
+
+        // Create Joy's engine
+        let temp_dir_joy = TempDir::new().unwrap();
+        let mut joy = SyncEngine::new(temp_dir_joy.path()).await.unwrap();
+        joy.init_identity().unwrap(); // Required for signing sync messages
+
+        // CRITICAL: Exchange and pin profiles so signature verification works.
+        // In production, this happens via contact exchange. For tests, we do it manually.
         {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            engine.init_identity().unwrap();
-            let loaded_did = engine.did().unwrap().to_string();
-            assert_eq!(original_did, loaded_did);
+            // Create signed profiles for both engines
+            let love_keypair = love.identity.as_ref().unwrap();
+            let love_profile = UserProfile::new("love_peer".to_string(), "Love".to_string());
+            let love_signed = SignedProfile::sign(&love_profile, love_keypair);
+
+            let joy_keypair = joy.identity.as_ref().unwrap();
+            let joy_profile = UserProfile::new("joy_peer".to_string(), "Joy".to_string());
+            let joy_signed = SignedProfile::sign(&joy_profile, joy_keypair);
+
+            // Pin each other's profiles (Love knows Joy, Joy knows Love)
+            love
+                .pin_profile(joy_signed.clone(), PinRelationship::Contact)
+                .unwrap();
+            joy.pin_profile(love_signed.clone(), PinRelationship::Contact)
+                .unwrap();
+            debug!("Exchanged and pinned profiles between Love and Joy");
         }
-    }
 
-    #[tokio::test]
-    async fn test_regenerate_identity() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // CRITICAL: Start networking on BOTH engines and exchange addresses BEFORE
+        // subscribing to any gossip topics. This matches the pattern used in the
+        // working p2p_integration tests. The iroh-gossip layer seems to require
+        // peer addresses to be in the static discovery BEFORE topic subscription
+        // for message delivery to work properly.
+        love.start_networking().await.unwrap();
+        joy.start_networking().await.unwrap();
 
-        engine.init_identity().unwrap();
-        let original_did = engine.did().unwrap().to_string();
+        // Exchange peer addresses bidirectionally
+        if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr()) {
+            debug!("Adding bidirectional peer addresses before gossip subscription");
+            love.add_peer_addr(joy_addr);
+            joy.add_peer_addr(love_addr);
+        }
 
-        // Regenerate
-        engine.regenerate_identity().unwrap();
-        let new_did = engine.did().unwrap().to_string();
+        // Small delay to let discovery propagate
+        tokio::time::sleep(Duration::from_millis(50)).await;
 
-        // Should be different
-        assert_ne!(original_did, new_did);
-    }
+        // CRITICAL: Subscribe to events BEFORE any sync operations start.
+        // This avoids the race condition where PeerConnected events fire
+        // before we start listening for them.
+        let mut love_events = love.subscribe_events();
+        let mut joy_events = joy.subscribe_events();
 
-    #[tokio::test]
-    async fn test_export_public_key_formats() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
+        // Love creates a realm
+        let realm_id = love.create_realm("Shared Tasks").await.unwrap();
 
-        // Test base58 format
-        let base58 = engine.export_public_key("base58");
-        assert!(base58.is_some());
+        // Love generates an invite (this should auto-start sync!)
+        let invite_str = love.create_invite(&realm_id).await.unwrap();
+        debug!(
+            "Love generated invite: {}...",
+            &invite_str[..50.min(invite_str.len())]
+        );
 
-        // Test hex format
-        let hex = engine.export_public_key("hex");
-        assert!(hex.is_some());
+        // Verify Love is now syncing
+        assert!(
+            love.is_realm_syncing(&realm_id),
+            "Love should be syncing after generating invite"
+        );
 
-        // Test json format
-        let json = engine.export_public_key("json");
-        assert!(json.is_some());
-        let json_str = json.unwrap();
-        assert!(json_str.contains("did"));
-        assert!(json_str.contains("public_key_base58"));
-    }
+        // Joy joins via invite
+        let joined_realm_id = joy.join_realm(&invite_str).await.unwrap();
+        assert_eq!(joined_realm_id, realm_id, "Joy should join the same realm");
 
-    #[tokio::test]
-    async fn test_node_info_includes_did() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // Verify Joy is syncing
+        assert!(
+            joy.is_realm_syncing(&realm_id),
+            "Joy should be syncing after joining"
+        );
 
-        // Before init_identity, DID should be None
-        let info = engine.node_info().await.unwrap();
-        assert!(info.did.is_none());
+        // CRITICAL: Wait for peers to connect before sending any messages.
+        // The gossip mesh takes time to establish, and messages sent before
+        // neighbors are connected won't be delivered.
+        debug!("Waiting for peer connections to establish...");
 
-        // After init_identity, DID should be Some
-        engine.init_identity().unwrap();
-        let info = engine.node_info().await.unwrap();
-        assert!(info.did.is_some());
-        assert!(info.did.unwrap().starts_with("did:sync:z"));
-    }
+        // Helper to wait for PeerConnected event on an existing subscription
+        async fn wait_for_peer_connected(
+            events: &mut broadcast::Receiver<SyncEvent>,
+            target_realm: &RealmId,
+            timeout_duration: Duration,
+        ) -> bool {
+            let start = std::time::Instant::now();
+            loop {
+                let remaining = timeout_duration.saturating_sub(start.elapsed());
+                if remaining.is_zero() {
+                    return false;
+                }
+                match tokio::time::timeout(remaining, events.recv()).await {
+                    Ok(Ok(SyncEvent::PeerConnected { realm_id, peer_id })) => {
+                        if &realm_id == target_realm {
+                            debug!(%realm_id, %peer_id, "Peer connected event received");
+                            return true;
+                        }
+                    }
+                    Ok(Ok(_)) => continue, // Other event
+                    Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue, // Lagged, keep trying
+                    Ok(Err(_)) => return false,                                  // Channel closed
+                    Err(_) => return false,                                      // Timeout
+                }
+            }
+        }
+
+        // Wait for connections using the pre-made subscriptions
+        let love_connected =
+            wait_for_peer_connected(&mut love_events, &realm_id, Duration::from_secs(10)).await;
+        let joy_connected =
+            wait_for_peer_connected(&mut joy_events, &realm_id, Duration::from_secs(10)).await;
+
+        debug!(love_connected, joy_connected, "Peer connection status");
+
+        // At least one side should see a connection (typically Joy sees Love first since he used Love as bootstrap)
+        assert!(
+            love_connected || joy_connected,
+            "At least one peer should have connected within 10 seconds"
+        );
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Identity Tests (required by task)
-    // ═══════════════════════════════════════════════════════════════════════
+        // Give time for gossip mesh to stabilize after peer connection.
+        // The iroh-gossip layer needs time to establish reliable message routing.
+        tokio::time::sleep(Duration::from_millis(500)).await;
 
-    #[tokio::test]
-    async fn test_engine_init_identity_creates_new() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // Process any pending BroadcastRequests from NeighborUp events
+        love.process_pending_sync();
+        joy.process_pending_sync();
 
-        // Initially no identity
-        assert!(!engine.has_identity());
-        assert!(engine.did().is_none());
-        assert!(engine.public_key().is_none());
+        // Additional stabilization - let any triggered broadcasts complete
+        tokio::time::sleep(Duration::from_millis(200)).await;
 
-        // Initialize identity
-        engine.init_identity().unwrap();
+        // Love adds a task (this should broadcast via sync)
+        debug!("Love adding task...");
+        let _task_id = love.add_task(&realm_id, "Love's task").await.unwrap();
+        debug!("Love added task, waiting for sync to Joy...");
 
-        // Identity should now exist
-        assert!(engine.has_identity());
-        let did = engine.did().unwrap();
-        assert!(did.as_str().starts_with("did:sync:z"));
+        // Wait for sync to propagate to Joy (up to 5 seconds)
+        let mut synced = false;
+        for i in 0..50 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            // Process any pending sync messages
+            let processed = joy.process_pending_sync();
+            if processed > 0 {
+                debug!(
+                    "Joy processed {} sync messages at iteration {}",
+                    processed, i
+                );
+            }
+            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
+            debug!(
+                iteration = i,
+                processed,
+                task_count = joy_tasks.len(),
+                "Checking Joy's tasks"
+            );
+            if !joy_tasks.is_empty() {
+                debug!("Joy received task after {}ms", (i + 1) * 100);
+                assert_eq!(joy_tasks[0].title, "Love's task");
+                synced = true;
+                break;
+            }
+        }
 
-        // Public key should also be available
-        let public_key = engine.public_key();
-        assert!(public_key.is_some());
+        assert!(
+            synced,
+            "Joy should have received Love's task within 5 seconds"
+        );
+
+        // Cleanup
+        love.shutdown().await.unwrap();
+        joy.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_engine_init_identity_loads_existing() {
+    async fn test_relocate_preserves_realms_and_tasks() {
         let temp_dir = TempDir::new().unwrap();
+        let old_dir = temp_dir.path().join("old");
+        let new_dir = temp_dir.path().join("new");
 
-        // Create identity in first engine instance
-        let (original_did, original_pk_bytes) = {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+        let (realm_id, did) = {
+            let mut engine = SyncEngine::new(&old_dir).await.unwrap();
             engine.init_identity().unwrap();
+            let realm_id = engine.create_realm("Garden").await.unwrap();
+            engine.add_task(&realm_id, "Plant garlic").await.unwrap();
             let did = engine.did().unwrap();
-            let pk = engine.public_key().unwrap();
-            (did.to_string(), pk.to_bytes())
+            engine.shutdown().await.unwrap();
+            (realm_id, did)
         };
 
-        // Load identity in new engine instance
-        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
-
-        // Before init, no identity in memory
-        assert!(!engine2.has_identity());
-
-        // Init should load existing identity
-        engine2.init_identity().unwrap();
-
-        // Verify it's the same identity
-        let loaded_did = engine2.did().unwrap().to_string();
-        let loaded_pk = engine2.public_key().unwrap();
-
-        assert_eq!(original_did, loaded_did);
-        assert_eq!(original_pk_bytes, loaded_pk.to_bytes());
-    }
-
-    #[tokio::test]
-    async fn test_engine_sign_and_verify() {
-        let (mut engine, _temp) = create_test_engine().await;
+        let engine = SyncEngine::relocate(&old_dir, &new_dir).await.unwrap();
+        assert_eq!(engine.data_dir(), new_dir.as_path());
+        engine.shutdown().await.unwrap();
 
-        // Sign without identity should fail
-        let message = b"Test message to sign";
-        let result = engine.sign(message);
-        assert!(result.is_err());
+        std::fs::remove_dir_all(&old_dir).unwrap();
 
-        // Initialize identity
+        let mut engine = SyncEngine::new(&new_dir).await.unwrap();
         engine.init_identity().unwrap();
-
-        // Sign should now succeed
-        let signature = engine.sign(message).unwrap();
-
-        // Get our public key for verification
-        let public_key = engine.public_key().unwrap();
-
-        // Verify should succeed with correct message
-        assert!(engine.verify(&public_key, message, &signature));
-
-        // Verify should fail with different message
-        let wrong_message = b"Different message";
-        assert!(!engine.verify(&public_key, wrong_message, &signature));
-
-        // Verify should fail with different public key
-        let other_keypair = crate::identity::HybridKeypair::generate();
-        let other_pk = other_keypair.public_key();
-        assert!(!engine.verify(&other_pk, message, &signature));
+        assert_eq!(engine.did(), Some(did));
+        engine.open_realm(&realm_id).await.unwrap();
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Plant garlic");
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Profile Packet Tests (Indra's Network)
-    // ═══════════════════════════════════════════════════════════════════════
-
     #[tokio::test]
-    async fn test_init_profile_keys_creates_new() {
-        let (mut engine, _temp) = create_test_engine().await;
-
-        // Initially no profile keys
-        assert!(!engine.has_profile_keys());
-        assert!(engine.profile_did().is_none());
-
-        // Identity must be initialized before profile keys
-        engine.init_identity().unwrap();
-        let identity_did = engine.did().unwrap();
-
-        // Initialize profile keys (derived from identity)
-        engine.init_profile_keys().unwrap();
-
-        // Profile keys should now exist
-        assert!(engine.has_profile_keys());
-        let profile_did = engine.profile_did().unwrap();
-        assert!(profile_did.as_str().starts_with("did:sync:z"));
-
-        // Profile DID should match Identity DID (unified system)
-        assert_eq!(profile_did, identity_did, "Profile DID should match Identity DID");
+    async fn test_relocate_refuses_non_empty_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_dir = temp_dir.path().join("old");
+        let new_dir = temp_dir.path().join("new");
+        SyncEngine::new(&old_dir).await.unwrap().shutdown().await.unwrap();
+        std::fs::create_dir_all(&new_dir).unwrap();
+        std::fs::write(new_dir.join("notes.txt"), "keep me").unwrap();
+
+        let result = SyncEngine::relocate(&old_dir, &new_dir).await;
+        assert!(matches!(result, Err(SyncError::InvalidOperation(_))));
+        assert_eq!(std::fs::read_to_string(new_dir.join("notes.txt")).unwrap(), "keep me");
+
+        let nested = SyncEngine::relocate(&old_dir, old_dir.join("inner")).await;
+        assert!(matches!(nested, Err(SyncError::InvalidOperation(_))));
+        assert!(!old_dir.join("inner").exists());
     }
 
+    /// Regression test for sync persistence bug
+    ///
+    /// Verifies that sync changes (from gossip messages) are saved to disk
+    /// and persist across engine restarts. This test simulates:
+    /// 1. Receiving a full document sync message
+    /// 2. Applying it to memory
+    /// 3. Restarting the engine
+    /// 4. Verifying the synced data persisted to disk
     #[tokio::test]
-    async fn test_profile_keys_persist_across_instances() {
+    async fn test_sync_changes_persist_across_restart() {
         let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
 
-        // Create profile keys in first engine instance
-        let original_did = {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+        // Phase 1: Create engine, create realm, simulate receiving sync data
+        let realm_id = {
+            let mut engine = SyncEngine::new(data_dir).await.unwrap();
             engine.init_identity().unwrap();
-            engine.init_profile_keys().unwrap();
-            engine.profile_did().unwrap().to_string()
-        };
-
-        // Load profile keys in new engine instance
-        let mut engine2 = SyncEngine::new(temp_dir.path()).await.unwrap();
-
-        // Before init, no keys in memory
-        assert!(!engine2.has_profile_keys());
 
-        // Init identity and profile keys (should load existing)
-        engine2.init_identity().unwrap();
-        engine2.init_profile_keys().unwrap();
+            // Create a realm
+            let realm_id = engine.create_realm("Test Realm").await.unwrap();
 
-        // Verify it's the same identity
-        let loaded_did = engine2.profile_did().unwrap().to_string();
-        assert_eq!(original_did, loaded_did);
+            // Create a separate document with a task (simulating what we'd receive from a peer)
+            let mut remote_doc = RealmDoc::new();
+            let _ = remote_doc.add_task("Synced Task from Peer");
 
-        // Also verify profile DID matches identity DID
-        assert_eq!(engine2.did().unwrap(), engine2.profile_did().unwrap());
-    }
+            // Simulate receiving a full document sync (SyncResponse message)
+            let remote_doc_bytes = remote_doc.save();
+            let result = engine.apply_sync_changes(&realm_id, &remote_doc_bytes, true);
+            assert!(result.is_ok(), "Should apply sync changes successfully");
 
-    #[tokio::test]
-    async fn test_profile_log_initialized_with_keys() {
-        let (mut engine, _temp) = create_test_engine().await;
+            // Verify the task exists in memory
+            let tasks = engine.list_tasks(&realm_id).unwrap();
+            assert_eq!(tasks.len(), 1, "Should have 1 task in memory after sync");
+            assert_eq!(tasks[0].title, "Synced Task from Peer");
 
-        // Before profile keys init, log should be None
-        assert!(engine.my_log().is_none());
-        assert_eq!(engine.log_head_sequence(), 0);
+            realm_id
+        }; // Engine drops here, releasing all resources
 
-        // Identity must be initialized before profile keys
-        engine.init_identity().unwrap();
+        // Phase 2: Create new engine instance with same data directory
+        {
+            let mut engine2 = SyncEngine::new(data_dir).await.unwrap();
+            engine2.init_identity().unwrap();
 
-        // Initialize profile keys (derived from identity)
-        engine.init_profile_keys().unwrap();
+            // Open the realm (loads from disk)
+            engine2.open_realm(&realm_id).await.unwrap();
 
-        // Log should now be available
-        assert!(engine.my_log().is_some());
-        assert_eq!(engine.log_head_sequence(), 0);
+            // Verify the synced task persisted to disk
+            let tasks = engine2.list_tasks(&realm_id).unwrap();
+            assert_eq!(
+                tasks.len(),
+                1,
+                "Should have 1 task after restart (loaded from disk)"
+            );
+            assert_eq!(
+                tasks[0].title, "Synced Task from Peer",
+                "Synced task should persist across restart"
+            );
+        }
     }
 
+    /// Test that incremental sync changes also persist
     #[tokio::test]
-    async fn test_create_global_packet() {
-        let (mut engine, _temp) = create_test_engine().await;
+    async fn test_incremental_sync_changes_persist() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
 
-        // Initialize identity and profile keys
-        engine.init_identity().unwrap();
-        engine.init_profile_keys().unwrap();
+        let realm_id = {
+            let mut engine = SyncEngine::new(data_dir).await.unwrap();
+            engine.init_identity().unwrap();
 
-        // Create a global (public) packet
-        let payload = crate::profile::PacketPayload::Heartbeat {
-            timestamp: chrono::Utc::now().timestamp_millis(),
-        };
+            // Create a realm with an initial task
+            let realm_id = engine.create_realm("Test Realm").await.unwrap();
+            engine.add_task(&realm_id, "Initial Task").await.unwrap();
 
-        let sequence = engine
-            .create_packet(payload, crate::profile::PacketAddress::Global)
-            .unwrap();
+            // Get the current document state
+            let state = engine.realms.get_mut(&realm_id).unwrap();
+            let initial_doc_bytes = state.doc.save();
+
+            // Create a remote doc with the same initial state
+            let mut remote_doc = RealmDoc::load(&initial_doc_bytes).unwrap();
+            remote_doc.add_task("Synced Task").unwrap();
 
-        // Should be sequence 0 (first packet)
-        assert_eq!(sequence, 0);
+            // Generate incremental changes using the public API
+            let changes = remote_doc.generate_sync_message();
 
-        // Log should now have head sequence 0
-        assert_eq!(engine.log_head_sequence(), 0);
+            // Apply incremental sync changes
+            let result = engine.apply_sync_changes(&realm_id, &changes, false);
+            assert!(result.is_ok(), "Should apply incremental changes");
 
-        // Create another packet
-        let payload2 = crate::profile::PacketPayload::Heartbeat {
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            // Verify both tasks exist in memory
+            let tasks = engine.list_tasks(&realm_id).unwrap();
+            assert_eq!(tasks.len(), 2, "Should have 2 tasks after incremental sync");
+
+            realm_id
         };
 
-        let sequence2 = engine
-            .create_packet(payload2, crate::profile::PacketAddress::Global)
-            .unwrap();
+        // Restart and verify
+        {
+            let mut engine2 = SyncEngine::new(data_dir).await.unwrap();
+            engine2.init_identity().unwrap();
+            engine2.open_realm(&realm_id).await.unwrap();
 
-        assert_eq!(sequence2, 1);
-        assert_eq!(engine.log_head_sequence(), 1);
+            let tasks = engine2.list_tasks(&realm_id).unwrap();
+            assert_eq!(
+                tasks.len(),
+                2,
+                "Both tasks should persist after restart (incremental sync)"
+            );
+        }
     }
 
+    /// Test that bootstrap peers are saved during join_via_invite and loaded during start_sync
+    ///
+    /// This regression test verifies the fix for the "sync lost after restart" bug where:
+    /// - Bootstrap peers from the invite were used once during initial join
+    /// - After restart, start_sync() passed empty peers, creating isolated nodes
+    ///
+    /// The fix saves bootstrap_peers in RealmInfo and loads them in start_sync().
     #[tokio::test]
-    async fn test_create_packet_requires_profile_keys() {
-        let (mut engine, _temp) = create_test_engine().await;
+    async fn test_bootstrap_peers_persist_for_reconnection() {
+        use crate::invite::NodeAddrBytes;
 
-        // Don't initialize profile keys
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
 
-        // Create packet should fail
-        let payload = crate::profile::PacketPayload::Heartbeat {
-            timestamp: chrono::Utc::now().timestamp_millis(),
+        // Create a mock bootstrap peer address
+        let mock_peer = NodeAddrBytes {
+            node_id: [42u8; 32],
+            relay_url: Some("https://relay.example.com".to_string()),
+            direct_addresses: vec!["192.168.1.100:4433".to_string()],
         };
 
-        let result = engine.create_packet(payload, crate::profile::PacketAddress::Global);
-        assert!(result.is_err());
-    }
+        // Phase 1: Create engine, create a realm and manually set up RealmInfo with peers
+        // (simulating what happens after join_via_invite)
+        let realm_id = {
+            let mut engine = SyncEngine::new(data_dir).await.unwrap();
+            engine.init_identity().unwrap();
 
-    #[tokio::test]
-    async fn test_handle_incoming_packet() {
-        let (mut engine, _temp) = create_test_engine().await;
+            // Create a realm
+            let realm_id = engine.create_realm("Shared Test Realm").await.unwrap();
 
-        // Create a packet from another profile
-        let other_keys = crate::profile::ProfileKeys::generate();
-        let payload = crate::profile::PacketPayload::Heartbeat {
-            timestamp: chrono::Utc::now().timestamp_millis(),
-        };
-        let envelope = crate::profile::PacketEnvelope::create_global(
-            &other_keys,
-            &payload,
-            0,
-            [0u8; 32],
-        )
-        .unwrap();
+            // Simulate what join_via_invite does: update the realm with bootstrap peers
+            let mut info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+            info.is_shared = true;
+            info.bootstrap_peers = vec![mock_peer.clone()];
+            engine.storage.save_realm(&info).unwrap();
 
-        // Handle the incoming packet
-        let is_new = engine.handle_incoming_packet(envelope.clone()).unwrap();
-        assert!(is_new);
+            realm_id
+        }; // Engine drops here
 
-        // Check mirror has the packet
-        let head = engine.mirror_head(&other_keys.did());
-        assert_eq!(head, Some(0));
+        // Phase 2: Create new engine instance and verify peers are loaded from storage
+        {
+            let engine2 = SyncEngine::new(data_dir).await.unwrap();
 
-        // Handle the same packet again (should be duplicate)
-        let is_new2 = engine.handle_incoming_packet(envelope).unwrap();
-        assert!(!is_new2);
+            // Load the realm info from storage
+            let loaded_info = engine2.storage.load_realm(&realm_id).unwrap().unwrap();
+
+            // Verify bootstrap peers were persisted
+            assert_eq!(
+                loaded_info.bootstrap_peers.len(),
+                1,
+                "Bootstrap peers should persist to storage"
+            );
+            assert_eq!(
+                loaded_info.bootstrap_peers[0].node_id, mock_peer.node_id,
+                "Peer node_id should match"
+            );
+            assert_eq!(
+                loaded_info.bootstrap_peers[0].relay_url, mock_peer.relay_url,
+                "Peer relay_url should match"
+            );
+            assert_eq!(
+                loaded_info.bootstrap_peers[0].direct_addresses, mock_peer.direct_addresses,
+                "Peer direct_addresses should match"
+            );
+        }
     }
 
+    /// Test that RealmInfo can roundtrip through storage with and without bootstrap_peers
+    /// (backwards compatibility - old realms without the field should still load)
     #[tokio::test]
-    async fn test_decrypt_global_packet() {
-        let (mut engine, _temp) = create_test_engine().await;
+    async fn test_realm_info_backwards_compatible() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
 
-        // Initialize identity and profile keys
+        let mut engine = SyncEngine::new(data_dir).await.unwrap();
         engine.init_identity().unwrap();
-        engine.init_profile_keys().unwrap();
 
-        // Create a global packet from another profile
-        let other_keys = crate::profile::ProfileKeys::generate();
-        let my_did = engine.profile_did().unwrap();
-        let payload = crate::profile::PacketPayload::DirectMessage {
-            content: "Hello, world!".to_string(),
-            recipient: my_did,
-        };
-        let envelope = crate::profile::PacketEnvelope::create_global(
-            &other_keys,
-            &payload,
-            0,
-            [0u8; 32],
-        )
-        .unwrap();
+        // Create a realm using the constructor (which sets bootstrap_peers to empty vec)
+        let realm_id = engine.create_realm("Legacy Realm").await.unwrap();
 
-        // Global packets can be decrypted (really just decoded) by anyone
-        let decrypted = engine.decrypt_packet(&envelope);
-        assert!(decrypted.is_some());
+        // Load and verify it has an empty bootstrap_peers vec
+        let info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        assert!(
+            info.bootstrap_peers.is_empty(),
+            "New realms should have empty bootstrap_peers by default"
+        );
 
-        match decrypted.unwrap() {
-            crate::profile::PacketPayload::DirectMessage { content, recipient: _ } => {
-                assert_eq!(content, "Hello, world!");
-            }
-            _ => panic!("Wrong payload type"),
-        }
+        // The #[serde(default)] attribute ensures old stored realms without
+        // the bootstrap_peers field will deserialize with an empty vec
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Networking Tests
-    // ═══════════════════════════════════════════════════════════════════════
-
+    /// Test that peer addresses learned from Announce messages are persisted to storage
+    ///
+    /// This regression test verifies the fix for the "creator can't reconnect after restart" bug:
+    /// - Creator creates realm and has EMPTY bootstrap_peers
+    /// - Joiner joins and sends Announce with their address
+    /// - Creator MUST persist that address to bootstrap_peers
+    /// - After restart, creator can use saved peers to reconnect
     #[tokio::test]
-    async fn test_start_networking() {
-        let (mut engine, _temp) = create_test_engine().await;
-
-        // Initially not active
-        assert!(!engine.is_networking_active());
-
-        // Start networking
-        engine.start_networking().await.unwrap();
-
-        // Now active
-        assert!(engine.is_networking_active());
-
-        // Node info should have node_id
-        let info = engine.node_info().await.unwrap();
-        assert!(info.node_id.is_some());
-
-        // Starting again is a no-op (doesn't error)
-        engine.start_networking().await.unwrap();
-        assert!(engine.is_networking_active());
+    async fn test_creator_learns_and_persists_peer_from_announce() {
+        use crate::identity::HybridKeypair;
+        use crate::invite::NodeAddrBytes;
+        use crate::sync::{SyncEnvelope, SyncMessage};
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        engine.shutdown().await.unwrap();
-    }
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
 
-    #[tokio::test]
-    async fn test_start_networking_then_sync() {
-        let (mut engine, _temp) = create_test_engine().await;
+        let mut engine = SyncEngine::new(data_dir).await.unwrap();
+        engine.init_identity().unwrap();
 
-        // Create a realm
-        let realm_id = engine.create_realm("Sync Test").await.unwrap();
+        // Create a realm (simulating the "love" instance)
+        let realm_id = engine.create_realm("Creator Realm").await.unwrap();
 
-        // Start networking first
-        engine.start_networking().await.unwrap();
-        assert!(engine.is_networking_active());
+        // Verify creator starts with NO bootstrap peers
+        let info_before = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        assert!(
+            info_before.bootstrap_peers.is_empty(),
+            "Creator should start with empty bootstrap_peers"
+        );
 
-        // Now start syncing the realm
-        engine.start_sync(&realm_id).await.unwrap();
-        assert!(engine.is_realm_syncing(&realm_id));
+        // Mark realm as shared (happens during generate_invite)
+        let mut info = info_before.clone();
+        info.is_shared = true;
+        engine.storage.save_realm(&info).unwrap();
 
-        engine.shutdown().await.unwrap();
-    }
+        // Save a realm key (required for envelope handling)
+        let realm_key = [42u8; 32];
+        engine
+            .storage
+            .save_realm_key(&realm_id, &realm_key)
+            .unwrap();
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Sync Envelope Tests
-    // ═══════════════════════════════════════════════════════════════════════
+        // Open the realm so it's in memory
+        engine.open_realm(&realm_id).await.unwrap();
 
-    #[tokio::test]
-    async fn test_broadcast_sync_sends_envelope() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // Update the in-memory state with the realm key
+        if let Some(state) = engine.realms.get_mut(&realm_id) {
+            state.realm_key = realm_key;
+        }
 
-        // Initialize identity (required for signing)
-        engine.init_identity().unwrap();
+        // Create a joiner's keypair and pin their profile (required for signature verification)
+        let joiner_keypair = HybridKeypair::generate();
+        let joiner_profile = UserProfile::new("joiner_peer".to_string(), "Joiner".to_string());
+        let joiner_signed = SignedProfile::sign(&joiner_profile, &joiner_keypair);
+        let joiner_did = joiner_signed.did().to_string();
+        engine
+            .pin_profile(joiner_signed.clone(), PinRelationship::Contact)
+            .unwrap();
 
-        // Create realm and start syncing
-        let realm_id = engine.create_realm("Envelope Test").await.unwrap();
-        engine.start_sync(&realm_id).await.unwrap();
+        // Simulate receiving an Announce from a joiner with their address
+        let joiner_addr = NodeAddrBytes {
+            node_id: [99u8; 32], // Different node
+            relay_url: Some("https://relay.joiner.com".to_string()),
+            direct_addresses: vec!["10.0.0.50:4433".to_string()],
+        };
 
-        // Create a sync message
-        let message = SyncMessage::Announce {
+        let announce = SyncMessage::Announce {
             realm_id: realm_id.clone(),
-            heads: vec![vec![0u8; 32]],
-            sender_addr: None,
+            heads: vec![],
+            sender_addr: Some(joiner_addr.clone()),
+            sent_at_ms: chrono::Utc::now().timestamp_millis(),
         };
 
-        // Broadcast should succeed (message goes to empty topic, but no error)
-        let result = engine.broadcast_sync(&realm_id, message).await;
-        assert!(result.is_ok());
+        // Create signed envelope from the joiner
+        let envelope_bytes = {
+            let sign_fn = |data: &[u8]| joiner_keypair.sign(data).to_bytes().to_vec();
+            let envelope = SyncEnvelope::seal(&announce, &joiner_did, &realm_key, sign_fn)
+                .expect("Should create envelope");
+            envelope.to_bytes().expect("Should serialize")
+        };
 
-        engine.shutdown().await.unwrap();
-    }
+        // Send to the sync channel (simulating incoming gossip message)
+        engine
+            .sync_tx
+            .send(SyncChannelMessage::IncomingData {
+                realm_id: realm_id.clone(),
+                envelope_bytes,
+            })
+            .expect("Should send");
 
-    #[tokio::test]
-    async fn test_broadcast_sync_requires_identity() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // Process pending sync messages (this should persist the peer)
+        engine.process_pending_sync();
 
-        // Create realm and start syncing WITHOUT initializing identity
-        let realm_id = engine.create_realm("No Identity Test").await.unwrap();
-        engine.start_sync(&realm_id).await.unwrap();
+        // Verify the joiner's address was persisted
+        let info_after = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        assert_eq!(
+            info_after.bootstrap_peers.len(),
+            1,
+            "Creator should have learned 1 peer from announce"
+        );
+        assert_eq!(
+            info_after.bootstrap_peers[0].node_id, joiner_addr.node_id,
+            "Saved peer should match joiner's node_id"
+        );
+        assert_eq!(
+            info_after.bootstrap_peers[0].relay_url, joiner_addr.relay_url,
+            "Saved peer should match joiner's relay_url"
+        );
 
-        // Create a sync message
-        let message = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads: vec![],
-            sender_addr: None,
+        // Verify idempotency - processing the same announce again shouldn't duplicate
+        let envelope_bytes2 = {
+            let sign_fn = |data: &[u8]| joiner_keypair.sign(data).to_bytes().to_vec();
+            let envelope = SyncEnvelope::seal(&announce, &joiner_did, &realm_key, sign_fn)
+                .expect("Should create envelope");
+            envelope.to_bytes().expect("Should serialize")
         };
 
-        // Broadcast should fail because identity is not initialized
-        let result = engine.broadcast_sync(&realm_id, message).await;
-        assert!(matches!(result, Err(SyncError::Identity(_))));
+        engine
+            .sync_tx
+            .send(SyncChannelMessage::IncomingData {
+                realm_id: realm_id.clone(),
+                envelope_bytes: envelope_bytes2,
+            })
+            .expect("Should send");
+        engine.process_pending_sync();
 
-        engine.shutdown().await.unwrap();
+        let info_final = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        assert_eq!(
+            info_final.bootstrap_peers.len(),
+            1,
+            "Should not duplicate peer on repeated announce"
+        );
     }
 
+    /// Test that sync_status() returns updated peer count when peers connect
+    ///
+    /// This is a TDD test that verifies the fix for the peer counting bug:
+    /// - Two engines connect via gossip
+    /// - Both should report `Syncing { peer_count: 1 }` (or more) after connection
+    ///
+    /// ROOT CAUSE BEING TESTED: The listener task spawned in start_sync_internal
+    /// receives NeighborUp/NeighborDown events but the sync_status HashMap is
+    /// not accessible from that task (it's not thread-safe).
     #[tokio::test]
-    async fn test_broadcast_sync_requires_syncing() {
-        let (mut engine, _temp) = create_test_engine().await;
+    async fn test_sync_status_updates_peer_count() {
+        use std::time::Duration;
 
-        // Initialize identity
-        engine.init_identity().unwrap();
+        let _ = tracing_subscriber::fmt::try_init();
 
-        // Create realm but DON'T start syncing
-        let realm_id = engine.create_realm("Not Syncing Test").await.unwrap();
+        // Create Love's engine
+        let temp_dir_love = TempDir::new().unwrap();
+        let mut love = SyncEngine::new(temp_dir_love.path()).await.unwrap();
+        love.init_identity().unwrap();
 
-        // Create a sync message
-        let message = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads: vec![],
-            sender_addr: None,
-        };
+        // Create Joy's engine
+        let temp_dir_joy = TempDir::new().unwrap();
+        let mut joy = SyncEngine::new(temp_dir_joy.path()).await.unwrap();
+        joy.init_identity().unwrap();
 
-        // Broadcast should fail because realm is not syncing
-        let result = engine.broadcast_sync(&realm_id, message).await;
-        assert!(matches!(result, Err(SyncError::Gossip(_))));
-    }
+        // Start networking on both
+        love.start_networking().await.unwrap();
+        joy.start_networking().await.unwrap();
 
-    #[tokio::test]
-    async fn test_handle_incoming_valid_envelope() {
-        use crate::identity::HybridKeypair;
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
+        // Exchange peer addresses bidirectionally
+        if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr()) {
+            love.add_peer_addr(joy_addr);
+            joy.add_peer_addr(love_addr);
+        }
 
-        let (mut engine, _temp) = create_test_engine().await;
+        // Small delay to let discovery propagate
+        tokio::time::sleep(Duration::from_millis(50)).await;
 
-        // Initialize identity
-        engine.init_identity().unwrap();
+        // Subscribe to events before sync starts
+        let mut love_events = love.subscribe_events();
+        let mut joy_events = joy.subscribe_events();
 
-        // Create realm
-        let realm_id = engine.create_realm("Incoming Test").await.unwrap();
+        // Love creates a realm and generates an invite
+        let realm_id = love.create_realm("Peer Count Test").await.unwrap();
+        let invite_str = love.create_invite(&realm_id).await.unwrap();
 
-        // Get the realm key to create a valid envelope
-        let realm_key = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        // Verify Love is syncing (but peer_count should be 0 initially)
+        let love_status_initial = love.sync_status(&realm_id);
+        info!(?love_status_initial, "Love initial sync status");
+        assert!(
+            matches!(love_status_initial, SyncStatus::Syncing { .. }),
+            "Love should be in Syncing state"
+        );
 
-        // Create a peer keypair and pin their profile (simulating a known contact)
-        let peer_keypair = HybridKeypair::generate();
-        let peer_profile = UserProfile::new("peer_sender".to_string(), "Peer Sender".to_string());
-        let signed_profile = SignedProfile::sign(&peer_profile, &peer_keypair);
-        let sender_did = signed_profile.did().to_string();
+        // Joy joins via invite
+        let _joined_realm_id = joy.join_realm(&invite_str).await.unwrap();
 
-        // Pin the peer's profile so they're recognized
-        engine
-            .pin_profile(signed_profile, PinRelationship::Contact)
-            .unwrap();
+        // Wait for PeerConnected events (using existing helper pattern)
+        async fn wait_for_peer(
+            events: &mut broadcast::Receiver<SyncEvent>,
+            target_realm: &RealmId,
+        ) -> bool {
+            let timeout = Duration::from_secs(10);
+            let start = std::time::Instant::now();
+            loop {
+                let remaining = timeout.saturating_sub(start.elapsed());
+                if remaining.is_zero() {
+                    return false;
+                }
+                match tokio::time::timeout(remaining, events.recv()).await {
+                    Ok(Ok(SyncEvent::PeerConnected { realm_id, .. })) => {
+                        if &realm_id == target_realm {
+                            return true;
+                        }
+                    }
+                    Ok(Ok(_)) => continue,
+                    Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
+                    Ok(Err(_)) => return false,
+                    Err(_) => return false,
+                }
+            }
+        }
 
-        // Create a valid envelope from the peer
-        let message = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads: vec![vec![1, 2, 3]],
-            sender_addr: None,
-        };
+        // Wait for at least one peer connection
+        let love_connected = wait_for_peer(&mut love_events, &realm_id).await;
+        let joy_connected = wait_for_peer(&mut joy_events, &realm_id).await;
 
-        let sign_fn = |data: &[u8]| peer_keypair.sign(data).to_bytes();
+        debug!(
+            love_connected,
+            joy_connected, "Peer connection events received"
+        );
 
-        let envelope = SyncEnvelope::seal(&message, &sender_did, &realm_key, sign_fn).unwrap();
-        let envelope_bytes = envelope.to_bytes().unwrap();
+        assert!(
+            love_connected || joy_connected,
+            "At least one peer should have connected"
+        );
 
-        // Process the envelope
-        let result = engine.handle_incoming(&realm_id, &envelope_bytes);
-        assert!(result.is_ok());
+        // Give a moment for status to settle
+        tokio::time::sleep(Duration::from_millis(200)).await;
 
-        let maybe_message = result.unwrap();
-        assert!(maybe_message.is_some());
+        // THE CRITICAL ASSERTION: After peers connect, sync_status should report peer_count >= 1
+        let love_status = love.sync_status(&realm_id);
+        let joy_status = joy.sync_status(&realm_id);
 
-        // Verify the message content
-        match maybe_message.unwrap() {
-            SyncMessage::Announce { heads, .. } => {
-                assert_eq!(heads.len(), 1);
-                assert_eq!(heads[0], vec![1, 2, 3]);
+        info!(
+            ?love_status,
+            ?joy_status,
+            "Final sync status after peer connection"
+        );
+
+        // Check Love's peer count
+        match love_status {
+            SyncStatus::Syncing { peer_count } => {
+                assert!(
+                    peer_count >= 1,
+                    "Love should report at least 1 peer, but got peer_count={}",
+                    peer_count
+                );
+            }
+            other => panic!("Love should be Syncing, but got {:?}", other),
+        }
+
+        // Check Joy's peer count
+        match joy_status {
+            SyncStatus::Syncing { peer_count } => {
+                assert!(
+                    peer_count >= 1,
+                    "Joy should report at least 1 peer, but got peer_count={}",
+                    peer_count
+                );
             }
-            _ => panic!("Expected Announce message"),
+            other => panic!("Joy should be Syncing, but got {:?}", other),
         }
+
+        // Cleanup
+        love.shutdown().await.unwrap();
+        joy.shutdown().await.unwrap();
     }
 
+    /// Test that offline changes sync correctly after restart
+    ///
+    /// This verifies the complete offline-to-online sync flow:
+    /// 1. Two engines sync initially
+    /// 2. Both shut down
+    /// 3. Each adds tasks while offline (different tasks)
+    /// 4. Both restart and reconnect
+    /// 5. After sync, both should have ALL tasks from BOTH peers
+    ///
+    /// This tests Automerge's CRDT merge behavior for offline changes.
     #[tokio::test]
-    async fn test_handle_incoming_invalid_signature() {
-        let (mut engine, _temp) = create_test_engine().await;
-
-        // Initialize identity
-        engine.init_identity().unwrap();
+    async fn test_offline_changes_sync_after_restart() {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
+        use std::time::Duration;
 
-        // Create realm
-        let realm_id = engine.create_realm("Invalid Sig Test").await.unwrap();
+        let _ = tracing_subscriber::fmt::try_init();
 
-        // Get the realm key
-        let realm_key = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        // Use persistent directories
+        let temp_dir_love = TempDir::new().unwrap();
+        let temp_dir_joy = TempDir::new().unwrap();
+        let love_path = temp_dir_love.path().to_path_buf();
+        let joy_path = temp_dir_joy.path().to_path_buf();
 
-        // Create an envelope with an empty signature (invalid)
-        let message = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads: vec![],
-            sender_addr: None,
-        };
+        // === Phase 1: Initial sync setup ===
+        let realm_id = {
+            let mut love = SyncEngine::new(&love_path).await.unwrap();
+            love.init_identity().unwrap();
 
-        let mut envelope = SyncEnvelope::seal(&message, "did:example:test", &realm_key, |_| {
-            vec![0x51, 0x9E, 1]
-        })
-        .unwrap();
+            let mut joy = SyncEngine::new(&joy_path).await.unwrap();
+            joy.init_identity().unwrap();
 
-        // Tamper with the signature to make it invalid (empty)
-        envelope.signature = vec![];
+            // CRITICAL: Exchange and pin profiles so signature verification works across restarts
+            {
+                let love_keypair = love.identity.as_ref().unwrap();
+                let love_profile = UserProfile::new("love_offline".to_string(), "Love".to_string());
+                let love_signed = SignedProfile::sign(&love_profile, love_keypair);
 
-        let envelope_bytes = envelope.to_bytes().unwrap();
+                let joy_keypair = joy.identity.as_ref().unwrap();
+                let joy_profile = UserProfile::new("joy_offline".to_string(), "Joy".to_string());
+                let joy_signed = SignedProfile::sign(&joy_profile, joy_keypair);
 
-        // Process the envelope - should return None because signature is invalid
-        let result = engine.handle_incoming(&realm_id, &envelope_bytes);
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_none()); // Returns None for invalid signature
-    }
+                love
+                    .pin_profile(joy_signed.clone(), PinRelationship::Contact)
+                    .unwrap();
+                joy.pin_profile(love_signed.clone(), PinRelationship::Contact)
+                    .unwrap();
+                debug!("Exchanged and pinned profiles between Love and Joy");
+            }
 
-    #[tokio::test]
-    async fn test_handle_incoming_wrong_key() {
-        let (mut engine, _temp) = create_test_engine().await;
+            // Start networking
+            love.start_networking().await.unwrap();
+            joy.start_networking().await.unwrap();
 
-        // Initialize identity
-        engine.init_identity().unwrap();
+            // Exchange addresses
+            if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr())
+            {
+                love.add_peer_addr(joy_addr);
+                joy.add_peer_addr(love_addr);
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
 
-        // Create realm
-        let realm_id = engine.create_realm("Wrong Key Test").await.unwrap();
+            // Subscribe to events
+            let mut love_events = love.subscribe_events();
 
-        // Create an envelope with a DIFFERENT key
-        let wrong_key = [99u8; 32];
-        let message = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads: vec![],
-            sender_addr: None,
-        };
+            // Create realm and invite
+            let realm_id = love.create_realm("Offline Sync Test").await.unwrap();
+            let invite_str = love.create_invite(&realm_id).await.unwrap();
 
-        let keypair = engine.identity.as_ref().unwrap();
-        let sender_did = crate::identity::Did::from_public_key(&keypair.public_key()).to_string();
-        let sign_fn = |data: &[u8]| keypair.sign(data).to_bytes();
+            // Joy joins
+            let _joined = joy.join_realm(&invite_str).await.unwrap();
 
-        let envelope = SyncEnvelope::seal(&message, &sender_did, &wrong_key, sign_fn).unwrap();
-        let envelope_bytes = envelope.to_bytes().unwrap();
+            // Wait for connection
+            let connected = tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    match love_events.recv().await {
+                        Ok(SyncEvent::PeerConnected { realm_id: r, .. }) if r == realm_id => {
+                            return true;
+                        }
+                        Ok(_) => continue,
+                        Err(_) => return false,
+                    }
+                }
+            })
+            .await
+            .unwrap_or(false);
 
-        // Process the envelope - should return None because decryption fails
-        let result = engine.handle_incoming(&realm_id, &envelope_bytes);
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_none()); // Returns None for decryption failure
-    }
+            assert!(connected, "Initial connection should succeed");
 
-    #[tokio::test]
-    async fn test_sync_after_task_add() {
-        let (mut engine, _temp) = create_test_engine().await;
+            // Love adds initial task and waits for sync
+            love
+                .add_task(&realm_id, "Initial shared task")
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            joy.process_pending_sync();
 
-        // Initialize identity
-        engine.init_identity().unwrap();
+            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
+            assert!(!joy_tasks.is_empty(), "Joy should have initial task");
 
-        // Create realm and start syncing
-        let realm_id = engine.create_realm("Auto Sync Test").await.unwrap();
-        engine.start_sync(&realm_id).await.unwrap();
+            // Shutdown both
+            love.shutdown().await.unwrap();
+            joy.shutdown().await.unwrap();
 
-        // Add a task
-        let task_id = engine.add_task(&realm_id, "Test task").await.unwrap();
-        assert!(!task_id.to_string().is_empty());
+            realm_id
+        };
 
-        // Sync realm state - should broadcast Announce
-        let result = engine.sync_realm_state(&realm_id).await;
-        assert!(result.is_ok());
+        info!(?realm_id, "Phase 1 complete - both engines shutdown");
 
-        // Broadcast changes - should broadcast Changes message
-        let result = engine.broadcast_changes(&realm_id).await;
-        assert!(result.is_ok());
+        // === Phase 2: Offline changes ===
+        // Love adds tasks while offline (no networking)
+        {
+            let mut love = SyncEngine::new(&love_path).await.unwrap();
+            love.init_identity().unwrap();
+            love.open_realm(&realm_id).await.unwrap();
 
-        engine.shutdown().await.unwrap();
-    }
+            love
+                .add_task(&realm_id, "Love offline task 1")
+                .await
+                .unwrap();
+            love
+                .add_task(&realm_id, "Love offline task 2")
+                .await
+                .unwrap();
 
-    #[tokio::test]
-    async fn test_apply_incoming_changes() {
-        let (mut engine, _temp) = create_test_engine().await;
+            let love_tasks = love.list_tasks(&realm_id).unwrap();
+            info!(count = love_tasks.len(), "Love offline tasks");
 
-        // Create realm
-        let realm_id = engine.create_realm("Apply Changes Test").await.unwrap();
+            // Shutdown without starting networking
+            love.shutdown().await.unwrap();
+        }
 
-        // Get initial task count
-        let initial_tasks = engine.list_tasks(&realm_id).unwrap();
-        assert_eq!(initial_tasks.len(), 0);
+        // Joy adds tasks while offline
+        {
+            let mut joy = SyncEngine::new(&joy_path).await.unwrap();
+            joy.init_identity().unwrap();
+            joy.open_realm(&realm_id).await.unwrap();
 
-        // Create a fork with changes
-        let state = engine.realms.get_mut(&realm_id).unwrap();
-        let mut forked_doc = state.doc.fork();
-        forked_doc.add_task("Synced task from peer").unwrap();
-        let changes = forked_doc.generate_sync_message();
+            joy.add_task(&realm_id, "Joy offline task 1").await.unwrap();
+            joy.add_task(&realm_id, "Joy offline task 2").await.unwrap();
 
-        // Apply the changes
-        engine
-            .apply_incoming_changes(&realm_id, &changes)
-            .await
-            .unwrap();
+            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
+            info!(count = joy_tasks.len(), "Joy offline tasks");
 
-        // Verify the task was added
-        let tasks = engine.list_tasks(&realm_id).unwrap();
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(tasks[0].title, "Synced task from peer");
-    }
+            joy.shutdown().await.unwrap();
+        }
 
-    #[tokio::test]
-    async fn test_apply_full_document() {
-        let (mut engine, _temp) = create_test_engine().await;
+        info!("Phase 2 complete - both added offline tasks");
 
-        // Create realm with a task
-        let realm_id = engine.create_realm("Apply Doc Test").await.unwrap();
-        engine.add_task(&realm_id, "Local task").await.unwrap();
+        // Allow database handles to fully release
+        tokio::time::sleep(Duration::from_millis(100)).await;
 
-        // Fork our own document to create a "remote" document with shared history
-        let remote_bytes = {
-            let state = engine.realms.get_mut(&realm_id).unwrap();
-            let mut remote_doc = state.doc.fork();
-            remote_doc.add_task("Remote task 1").unwrap();
-            remote_doc.add_task("Remote task 2").unwrap();
-            remote_doc.save()
-        };
+        // === Phase 3: Restart and sync ===
+        let mut love = SyncEngine::new(&love_path).await.unwrap();
+        love.init_identity().unwrap();
 
-        // Apply the full document
-        engine
-            .apply_full_document(&realm_id, &remote_bytes)
-            .await
-            .unwrap();
+        let mut joy = SyncEngine::new(&joy_path).await.unwrap();
+        joy.init_identity().unwrap();
 
-        // Verify merge - should have all three tasks
-        let tasks = engine.list_tasks(&realm_id).unwrap();
-        assert_eq!(tasks.len(), 3);
+        // Start networking
+        love.start_networking().await.unwrap();
+        joy.start_networking().await.unwrap();
 
-        let titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
-        assert!(titles.contains(&"Local task"));
-        assert!(titles.contains(&"Remote task 1"));
-        assert!(titles.contains(&"Remote task 2"));
-    }
+        // Exchange addresses AND update bootstrap_peers in storage
+        // This simulates what happens when users re-exchange invites after restart
+        // (The iroh endpoint has a new node ID after restart, so old saved addresses are stale)
+        if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr()) {
+            love.add_peer_addr(joy_addr.clone());
+            joy.add_peer_addr(love_addr.clone());
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Multi-Realm Sync Tests
-    // ═══════════════════════════════════════════════════════════════════════
+            // Update Love's realm with Joy's fresh address as bootstrap peer
+            if let Ok(Some(mut love_realm_info)) = love.storage.load_realm(&realm_id) {
+                love_realm_info.bootstrap_peers =
+                    vec![NodeAddrBytes::from_endpoint_addr(&joy_addr)];
+                love.storage.save_realm(&love_realm_info).unwrap();
+            }
 
-    #[tokio::test]
-    async fn test_sync_status_returns_idle_for_unsynced_realm() {
-        let (engine, _temp) = create_test_engine().await;
-        let realm_id = RealmId::new();
+            // Update Joy's realm with Love's fresh address as bootstrap peer
+            if let Ok(Some(mut joy_realm_info)) = joy.storage.load_realm(&realm_id) {
+                joy_realm_info.bootstrap_peers =
+                    vec![NodeAddrBytes::from_endpoint_addr(&love_addr)];
+                joy.storage.save_realm(&joy_realm_info).unwrap();
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
 
-        assert_eq!(engine.sync_status(&realm_id), SyncStatus::Idle);
-    }
+        // Open realms (should auto-start sync for shared realms)
+        love.open_realm(&realm_id).await.unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-    #[tokio::test]
-    async fn test_sync_status_changes_when_sync_starts() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // Wait for sync to complete
+        let mut synced = false;
+        for i in 0..100 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            love.process_pending_sync();
+            joy.process_pending_sync();
 
-        let realm_id = engine.create_realm("Status Test").await.unwrap();
+            let love_tasks = love.list_tasks(&realm_id).unwrap();
+            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
 
-        // Initially idle
-        assert_eq!(engine.sync_status(&realm_id), SyncStatus::Idle);
+            debug!(
+                iteration = i,
+                love_count = love_tasks.len(),
+                joy_count = joy_tasks.len(),
+                "Checking sync progress"
+            );
 
-        // Start sync
-        engine.start_sync(&realm_id).await.unwrap();
+            // Both should have 5 tasks:
+            // 1. Initial shared task
+            // 2. Love offline task 1
+            // 3. Love offline task 2
+            // 4. Joy offline task 1
+            // 5. Joy offline task 2
+            if love_tasks.len() >= 5 && joy_tasks.len() >= 5 {
+                synced = true;
+                break;
+            }
+        }
 
-        // Should be syncing now
-        let status = engine.sync_status(&realm_id);
-        assert!(matches!(status, SyncStatus::Syncing { .. }));
+        let love_tasks = love.list_tasks(&realm_id).unwrap();
+        let joy_tasks = joy.list_tasks(&realm_id).unwrap();
 
-        engine.shutdown().await.unwrap();
-    }
+        info!(
+            love_count = love_tasks.len(),
+            joy_count = joy_tasks.len(),
+            "Final task counts"
+        );
 
-    #[tokio::test]
-    async fn test_sync_status_returns_idle_after_stop() {
-        let (mut engine, _temp) = create_test_engine().await;
+        // Log actual task titles for debugging
+        for (i, task) in love_tasks.iter().enumerate() {
+            debug!(i, title = %task.title, "Love task");
+        }
+        for (i, task) in joy_tasks.iter().enumerate() {
+            debug!(i, title = %task.title, "Joy task");
+        }
 
-        let realm_id = engine.create_realm("Stop Status Test").await.unwrap();
+        assert!(
+            synced,
+            "Both should have all 5 tasks. Love has {}, Joy has {}",
+            love_tasks.len(),
+            joy_tasks.len()
+        );
 
-        // Start and stop sync
-        engine.start_sync(&realm_id).await.unwrap();
-        assert!(matches!(
-            engine.sync_status(&realm_id),
-            SyncStatus::Syncing { .. }
-        ));
+        // Verify specific tasks exist on both
+        let love_titles: std::collections::HashSet<_> =
+            love_tasks.iter().map(|t| t.title.as_str()).collect();
+        let joy_titles: std::collections::HashSet<_> =
+            joy_tasks.iter().map(|t| t.title.as_str()).collect();
 
-        engine.stop_sync(&realm_id).await.unwrap();
-        assert_eq!(engine.sync_status(&realm_id), SyncStatus::Idle);
+        assert!(
+            love_titles.contains("Love offline task 1"),
+            "Love should have her offline task 1"
+        );
+        assert!(
+            love_titles.contains("Joy offline task 1"),
+            "Love should have Joy's offline task 1"
+        );
+        assert!(
+            joy_titles.contains("Love offline task 1"),
+            "Joy should have Love's offline task 1"
+        );
+        assert!(
+            joy_titles.contains("Joy offline task 1"),
+            "Joy should have his offline task 1"
+        );
 
-        engine.shutdown().await.unwrap();
+        // Cleanup
+        love.shutdown().await.unwrap();
+        joy.shutdown().await.unwrap();
     }
 
+    // ═══════════════════════════════════════════════════════════════════════
+    // Private Realm Tests
+    // ═══════════════════════════════════════════════════════════════════════
+
     #[tokio::test]
-    async fn test_multiple_realms_can_sync_concurrently() {
+    async fn test_private_realm_auto_created() {
         let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-        // Create multiple realms
-        let realm1 = engine.create_realm("Realm 1").await.unwrap();
-        let realm2 = engine.create_realm("Realm 2").await.unwrap();
-        let realm3 = engine.create_realm("Realm 3").await.unwrap();
-
-        // Start syncing all three
-        engine.start_sync(&realm1).await.unwrap();
-        engine.start_sync(&realm2).await.unwrap();
-        engine.start_sync(&realm3).await.unwrap();
-
-        // All should be syncing
-        assert!(engine.is_realm_syncing(&realm1));
-        assert!(engine.is_realm_syncing(&realm2));
-        assert!(engine.is_realm_syncing(&realm3));
-
-        // Check status for each
-        assert!(matches!(
-            engine.sync_status(&realm1),
-            SyncStatus::Syncing { .. }
-        ));
-        assert!(matches!(
-            engine.sync_status(&realm2),
-            SyncStatus::Syncing { .. }
-        ));
-        assert!(matches!(
-            engine.sync_status(&realm3),
-            SyncStatus::Syncing { .. }
-        ));
+        // Private realm should exist after engine creation
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 1, "Private realm should auto-create");
+        assert_eq!(realms[0].name, "Private");
+        assert!(!realms[0].is_shared, "Private realm should not be shared");
+    }
 
-        // Verify syncing_count
-        assert_eq!(engine.syncing_count(), 3);
+    #[tokio::test]
+    async fn test_private_realm_with_custom_onboarding() {
+        let temp = TempDir::new().unwrap();
+        let mut engine = SyncEngine::new_with_onboarding(temp.path(), Vec::new())
+            .await
+            .unwrap();
 
-        // Verify syncing_realms
-        let syncing = engine.syncing_realms();
-        assert!(syncing.contains(&realm1));
-        assert!(syncing.contains(&realm2));
-        assert!(syncing.contains(&realm3));
+        // The reserved realm is still there, just empty
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 1);
+        assert_eq!(realms[0].name, "Private");
+        let private_id = realms[0].id.clone();
+        engine.open_realm(&private_id).await.unwrap();
+        assert!(engine.list_tasks(&private_id).unwrap().is_empty());
 
-        engine.shutdown().await.unwrap();
+        let temp = TempDir::new().unwrap();
+        let tasks = vec![("Read the manual".to_string(), "Start here".to_string())];
+        let mut engine = SyncEngine::new_with_onboarding(temp.path(), tasks)
+            .await
+            .unwrap();
+        let private_id = engine.list_realms().await.unwrap()[0].id.clone();
+        engine.open_realm(&private_id).await.unwrap();
+        let titles: Vec<String> = engine
+            .list_tasks(&private_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Read the manual"]);
     }
 
     #[tokio::test]
-    async fn test_start_sync_multiple_realms_without_blocking() {
+    async fn test_private_realm_has_onboarding_tasks() {
         let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-        // Create realms
-        let realm1 = engine.create_realm("Realm A").await.unwrap();
-        let realm2 = engine.create_realm("Realm B").await.unwrap();
-
-        // Measure time for starting both syncs
-        let start = std::time::Instant::now();
-
-        engine.start_sync(&realm1).await.unwrap();
-        engine.start_sync(&realm2).await.unwrap();
+        // Find Private realm
+        let realms = engine.list_realms().await.unwrap();
+        let private_realm = realms.iter().find(|r| r.name == "Private").unwrap();
 
-        let elapsed = start.elapsed();
+        // Open and check tasks
+        engine.open_realm(&private_realm.id).await.unwrap();
+        let tasks = engine.list_tasks(&private_realm.id).unwrap();
 
-        // Should complete quickly (not blocking)
+        // Should have sacred onboarding tasks
         assert!(
-            elapsed.as_millis() < 5000,
-            "start_sync took too long: {:?}",
-            elapsed
+            tasks.len() >= 3,
+            "Private realm should have onboarding tasks"
         );
 
-        // Both should be syncing
-        assert!(engine.is_realm_syncing(&realm1));
-        assert!(engine.is_realm_syncing(&realm2));
-
-        engine.shutdown().await.unwrap();
+        // Verify sacred language in task titles
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        let has_sacred_language = titles.iter().any(|t| {
+            let lower = t.to_lowercase();
+            lower.contains("field")
+                || lower.contains("intention")
+                || lower.contains("manifest")
+                || lower.contains("synchronicities")
+        });
+        assert!(
+            has_sacred_language,
+            "Onboarding tasks should use sacred language"
+        );
     }
 
     #[tokio::test]
-    async fn test_stop_one_realm_does_not_affect_others() {
+    async fn test_cannot_share_private_realm() {
         let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-        let realm1 = engine.create_realm("Keep Syncing").await.unwrap();
-        let realm2 = engine.create_realm("Stop Syncing").await.unwrap();
-
-        engine.start_sync(&realm1).await.unwrap();
-        engine.start_sync(&realm2).await.unwrap();
-
-        // Stop only realm2
-        engine.stop_sync(&realm2).await.unwrap();
-
-        // realm1 should still be syncing
-        assert!(engine.is_realm_syncing(&realm1));
-        assert!(!engine.is_realm_syncing(&realm2));
+        // Find Private realm
+        let realms = engine.list_realms().await.unwrap();
+        let private_realm = realms.iter().find(|r| r.name == "Private").unwrap();
 
-        assert!(matches!(
-            engine.sync_status(&realm1),
-            SyncStatus::Syncing { .. }
-        ));
-        assert_eq!(engine.sync_status(&realm2), SyncStatus::Idle);
+        // Attempt to generate invite should fail
+        let result = engine.generate_invite(&private_realm.id).await;
+        assert!(
+            result.is_err(),
+            "Should not be able to generate invite for Private realm"
+        );
 
+        // Shutdown to avoid gossip background tasks
         engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_subscribe_events_receives_status_changes() {
+    async fn test_cannot_create_realm_named_private() {
         let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-        let realm_id = engine.create_realm("Event Test").await.unwrap();
-
-        // Subscribe before starting sync
-        let mut events = engine.subscribe_events();
-
-        // Start sync - should emit events
-        engine.start_sync(&realm_id).await.unwrap();
-
-        // Try to receive events (with timeout to avoid hanging)
-        let mut found_connecting = false;
-        let mut found_syncing = false;
-
-        // Use a short timeout for testing
-        for _ in 0..10 {
-            match tokio::time::timeout(std::time::Duration::from_millis(100), events.recv()).await {
-                Ok(Ok(SyncEvent::StatusChanged {
-                    status: SyncStatus::Connecting,
-                    ..
-                })) => {
-                    found_connecting = true;
-                }
-                Ok(Ok(SyncEvent::StatusChanged {
-                    status: SyncStatus::Syncing { .. },
-                    ..
-                })) => {
-                    found_syncing = true;
-                }
-                _ => break,
-            }
-        }
+        // Attempt to create another "Private" realm should fail
+        let result = engine.create_realm("Private").await;
+        assert!(
+            result.is_err(),
+            "Should not be able to create realm named 'Private'"
+        );
 
-        // Should have received at least the connecting or syncing event
+        // Case variations should also fail
+        let result = engine.create_realm("private").await;
         assert!(
-            found_connecting || found_syncing,
-            "Should receive status change events"
+            result.is_err(),
+            "Should not be able to create realm named 'private'"
         );
 
-        engine.shutdown().await.unwrap();
+        let result = engine.create_realm("PRIVATE").await;
+        assert!(
+            result.is_err(),
+            "Should not be able to create realm named 'PRIVATE'"
+        );
     }
 
     #[tokio::test]
-    async fn test_syncing_realms_returns_only_active() {
+    async fn test_cannot_delete_private_realm() {
         let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-        let realm1 = engine.create_realm("Active 1").await.unwrap();
-        let realm2 = engine.create_realm("Active 2").await.unwrap();
-        let realm3 = engine.create_realm("Inactive").await.unwrap();
-
-        // Start syncing only realm1 and realm2
-        engine.start_sync(&realm1).await.unwrap();
-        engine.start_sync(&realm2).await.unwrap();
+        // Find Private realm
+        let realms = engine.list_realms().await.unwrap();
+        let private_realm = realms.iter().find(|r| r.name == "Private").unwrap();
 
-        let syncing = engine.syncing_realms();
-        assert_eq!(syncing.len(), 2);
-        assert!(syncing.contains(&realm1));
-        assert!(syncing.contains(&realm2));
-        assert!(!syncing.contains(&realm3));
+        // Attempt to delete should fail
+        let result = engine.delete_realm(&private_realm.id).await;
+        assert!(
+            result.is_err(),
+            "Should not be able to delete Private realm"
+        );
 
-        engine.shutdown().await.unwrap();
+        // Verify it still exists
+        let realms = engine.list_realms().await.unwrap();
+        assert!(
+            realms.iter().any(|r| r.name == "Private"),
+            "Private realm should still exist after delete attempt"
+        );
     }
 
     #[tokio::test]
-    async fn test_syncing_count_tracks_active_syncs() {
-        let (mut engine, _temp) = create_test_engine().await;
-
-        assert_eq!(engine.syncing_count(), 0);
+    async fn test_private_realm_persists_across_restarts() {
+        let temp_dir = TempDir::new().unwrap();
 
-        let realm1 = engine.create_realm("Count 1").await.unwrap();
-        let realm2 = engine.create_realm("Count 2").await.unwrap();
+        // First engine instance
+        {
+            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            engine.init_identity().unwrap();
 
-        engine.start_sync(&realm1).await.unwrap();
-        assert_eq!(engine.syncing_count(), 1);
+            let realms = engine.list_realms().await.unwrap();
+            assert_eq!(realms.len(), 1);
+            assert_eq!(realms[0].name, "Private");
+        }
 
-        engine.start_sync(&realm2).await.unwrap();
-        assert_eq!(engine.syncing_count(), 2);
+        // Second engine instance (simulating restart)
+        {
+            let engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+            let realms = engine.list_realms().await.unwrap();
+            assert_eq!(
+                realms.len(),
+                1,
+                "Private realm should not be duplicated on restart"
+            );
+            assert_eq!(realms[0].name, "Private");
+        }
+    }
 
-        engine.stop_sync(&realm1).await.unwrap();
-        assert_eq!(engine.syncing_count(), 1);
+    #[tokio::test]
+    async fn test_private_realm_independent_from_identity() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        engine.stop_sync(&realm2).await.unwrap();
-        assert_eq!(engine.syncing_count(), 0);
+        // Private realm should exist even before identity init
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 1);
+        assert_eq!(realms[0].name, "Private");
 
-        engine.shutdown().await.unwrap();
+        // Identity init should not affect Private realm
+        engine.init_identity().unwrap();
+        let realms = engine.list_realms().await.unwrap();
+        assert_eq!(realms.len(), 1);
+        assert_eq!(realms[0].name, "Private");
     }
 
+    // ═══════════════════════════════════════════════════════════════════════
+    // Profile Pinning Tests
+    // ═══════════════════════════════════════════════════════════════════════
+
     #[tokio::test]
-    async fn test_can_add_tasks_while_syncing_multiple_realms() {
+    async fn test_sign_and_pin_own_profile() {
         let (mut engine, _temp) = create_test_engine().await;
 
-        let realm1 = engine.create_realm("Tasks Realm 1").await.unwrap();
-        let realm2 = engine.create_realm("Tasks Realm 2").await.unwrap();
-
-        // Start syncing both
-        engine.start_sync(&realm1).await.unwrap();
-        engine.start_sync(&realm2).await.unwrap();
+        // Initialize identity first (required for get_own_profile and signing)
+        engine.init_identity().unwrap();
+        let did = engine.did().unwrap().to_string();
 
-        // Add tasks to both realms while syncing
-        let task1 = engine.add_task(&realm1, "Task in Realm 1").await.unwrap();
-        let task2 = engine.add_task(&realm2, "Task in Realm 2").await.unwrap();
+        // Set up a profile - use the DID as peer_id
+        let mut profile = crate::types::UserProfile::new(
+            did.clone(),
+            "Test User".to_string(),
+        );
+        profile.subtitle = Some("Test tagline".to_string());
+        engine.save_profile(&profile).unwrap();
 
-        // Verify tasks were added
-        let tasks1 = engine.list_tasks(&realm1).unwrap();
-        let tasks2 = engine.list_tasks(&realm2).unwrap();
+        // Sign and pin the profile
+        let signed = engine.sign_and_pin_own_profile().unwrap();
 
-        assert_eq!(tasks1.len(), 1);
-        assert_eq!(tasks2.len(), 1);
-        assert_eq!(tasks1[0].id, task1);
-        assert_eq!(tasks2[0].id, task2);
+        // Verify the signed profile
+        assert!(signed.verify());
+        assert_eq!(signed.profile.display_name, "Test User");
 
-        engine.shutdown().await.unwrap();
+        // Should be able to retrieve our own pinned profile
+        let retrieved = engine.get_own_pinned_profile().unwrap();
+        assert!(retrieved.is_some());
+        let pin = retrieved.unwrap();
+        assert!(pin.is_own());
+        assert_eq!(pin.signed_profile.profile.display_name, "Test User");
     }
 
-    /// Test that two engines can sync tasks via invite flow
-    ///
-    /// This is the critical user flow:
-    /// 1. Love creates a realm and generates an invite
-    /// 2. Joy joins via the invite
-    /// 3. Love adds a task
-    /// 4. Joy should see Love's task (after sync propagates)
     #[tokio::test]
-    async fn test_two_engines_sync_tasks_via_invite() {
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
-        use std::time::Duration;
+    async fn test_pin_peer_profile() {
+        let (engine, _temp) = create_test_engine().await;
 
-        let _ = tracing_subscriber::fmt::try_init();
+        // Create a signed profile from a "remote peer"
+        let keypair = crate::identity::HybridKeypair::generate();
+        let profile = crate::types::UserProfile::new(
+            "peer_remote".to_string(),
+            "Remote User".to_string(),
+        );
+        let signed = crate::types::SignedProfile::sign(&profile, &keypair);
+        let did = signed.did().to_string();
 
-        // Create Love's engine
-        let temp_dir_love = TempDir::new().unwrap();
-        let mut love = SyncEngine::new(temp_dir_love.path()).await.unwrap();
-        love.init_identity().unwrap(); // Required for signing sync messages
+        // Pin as a contact
+        let evicted = engine
+            .pin_profile(signed.clone(), crate::types::PinRelationship::Contact)
+            .unwrap();
+        assert!(evicted.is_empty());
 
-        // Create Joy's engine
-        let temp_dir_joy = TempDir::new().unwrap();
-        let mut joy = SyncEngine::new(temp_dir_joy.path()).await.unwrap();
-        joy.init_identity().unwrap(); // Required for signing sync messages
+        // Should be retrievable
+        let retrieved = engine.get_pinned_profile(&did).unwrap();
+        assert!(retrieved.is_some());
+        let pin = retrieved.unwrap();
+        assert_eq!(pin.relationship, crate::types::PinRelationship::Contact);
+        assert_eq!(pin.signed_profile.profile.display_name, "Remote User");
+    }
 
-        // CRITICAL: Exchange and pin profiles so signature verification works.
-        // In production, this happens via contact exchange. For tests, we do it manually.
-        {
-            // Create signed profiles for both engines
-            let love_keypair = love.identity.as_ref().unwrap();
-            let love_profile = UserProfile::new("love_peer".to_string(), "Love".to_string());
-            let love_signed = SignedProfile::sign(&love_profile, love_keypair);
+    #[tokio::test]
+    async fn test_bulk_pinned_profiles_match_individual_lookups() {
+        use crate::types::{PinRelationship, ProfilePin, SignedProfile, UserProfile};
 
-            let joy_keypair = joy.identity.as_ref().unwrap();
-            let joy_profile = UserProfile::new("joy_peer".to_string(), "Joy".to_string());
-            let joy_signed = SignedProfile::sign(&joy_profile, joy_keypair);
+        let engine = create_memory_engine().await;
+        let keypair = crate::identity::HybridKeypair::generate();
+        let dids: Vec<String> = (0..50).map(|i| format!("did:sync:zContact{i}")).collect();
+        for (i, did) in dids.iter().enumerate() {
+            let profile = UserProfile::new(format!("peer_{i}"), format!("Contact {i}"));
+            let pin = ProfilePin::new(did.clone(), SignedProfile::sign(&profile, &keypair), PinRelationship::Contact);
+            engine.storage.save_pinned_profile(&pin).unwrap();
+        }
 
-            // Pin each other's profiles (Love knows Joy, Joy knows Love)
-            love
-                .pin_profile(joy_signed.clone(), PinRelationship::Contact)
-                .unwrap();
-            joy.pin_profile(love_signed.clone(), PinRelationship::Contact)
-                .unwrap();
-            debug!("Exchanged and pinned profiles between Love and Joy");
+        let mut wanted: Vec<&str> = dids.iter().map(String::as_str).collect();
+        wanted.push("did:sync:zNotPinned");
+        let bulk = engine.get_pinned_profiles_bulk(&wanted).unwrap();
+
+        assert_eq!(bulk.len(), 50);
+        for did in &dids {
+            let single = engine.get_pinned_profile(did).unwrap().unwrap();
+            assert_eq!(
+                postcard::to_allocvec(&bulk[did]).unwrap(),
+                postcard::to_allocvec(&single).unwrap()
+            );
         }
+        assert!(!bulk.contains_key("did:sync:zNotPinned"));
+    }
 
-        // CRITICAL: Start networking on BOTH engines and exchange addresses BEFORE
-        // subscribing to any gossip topics. This matches the pattern used in the
-        // working p2p_integration tests. The iroh-gossip layer seems to require
-        // peer addresses to be in the static discovery BEFORE topic subscription
-        // for message delivery to work properly.
-        love.start_networking().await.unwrap();
-        joy.start_networking().await.unwrap();
+    #[tokio::test]
+    async fn test_pinned_profile_verification_is_cached() {
+        use crate::types::profile::verify_calls;
+        use crate::types::{PinRelationship, ProfilePin, SignedProfile, UserProfile};
+
+        let engine = create_memory_engine().await;
+        let keypair = crate::identity::HybridKeypair::generate();
+        let profile = UserProfile::new("peer_remote".to_string(), "Remote User".to_string());
+        let did = SignedProfile::sign(&profile, &keypair).did().to_string();
 
-        // Exchange peer addresses bidirectionally
-        if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr()) {
-            debug!("Adding bidirectional peer addresses before gossip subscription");
-            love.add_peer_addr(joy_addr);
-            joy.add_peer_addr(love_addr);
+        // Stored without verification, e.g. by a storage-level import
+        let pin = ProfilePin::new(did.clone(), SignedProfile::sign(&profile, &keypair), PinRelationship::Contact);
+        engine.storage.save_pinned_profile(&pin).unwrap();
+
+        let before = verify_calls();
+        for _ in 0..3 {
+            let pin = engine.get_verified_pinned_profile(&did).unwrap().unwrap();
+            assert!(pin.verified);
         }
+        assert_eq!(verify_calls() - before, 1, "Repeated reads should hit the cache");
 
-        // Small delay to let discovery propagate
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        // A changed profile invalidates the cached result
+        let renamed = UserProfile::new("peer_remote".to_string(), "Renamed".to_string());
+        let mut pin = engine.storage.load_pinned_profile(&did).unwrap().unwrap();
+        pin.signed_profile = SignedProfile::sign(&renamed, &keypair);
+        pin.signed_profile.profile.bio = "tampered".to_string();
+        engine.storage.save_pinned_profile(&pin).unwrap();
 
-        // CRITICAL: Subscribe to events BEFORE any sync operations start.
-        // This avoids the race condition where PeerConnected events fire
-        // before we start listening for them.
-        let mut love_events = love.subscribe_events();
-        let mut joy_events = joy.subscribe_events();
+        let before = verify_calls();
+        assert!(!engine.get_verified_pinned_profile(&did).unwrap().unwrap().verified);
+        assert!(!engine.get_verified_pinned_profile(&did).unwrap().unwrap().verified);
+        assert_eq!(verify_calls() - before, 1);
 
-        // Love creates a realm
-        let realm_id = love.create_realm("Shared Tasks").await.unwrap();
+        // pin_profile verifies up front, so the first read is already cached
+        engine
+            .pin_profile(SignedProfile::sign(&renamed, &keypair), PinRelationship::Contact)
+            .unwrap();
+        let before = verify_calls();
+        assert!(engine.get_verified_pinned_profile(&did).unwrap().unwrap().verified);
+        assert_eq!(verify_calls(), before);
+    }
 
-        // Love generates an invite (this should auto-start sync!)
-        let invite_str = love.create_invite(&realm_id).await.unwrap();
-        debug!(
-            "Love generated invite: {}...",
-            &invite_str[..50.min(invite_str.len())]
-        );
+    #[tokio::test]
+    async fn test_pin_profile_rejects_invalid_signature() {
+        let (engine, _temp) = create_test_engine().await;
 
-        // Verify Love is now syncing
-        assert!(
-            love.is_realm_syncing(&realm_id),
-            "Love should be syncing after generating invite"
+        // Create a signed profile and tamper with it
+        let keypair = crate::identity::HybridKeypair::generate();
+        let profile = crate::types::UserProfile::new(
+            "peer_tampered".to_string(),
+            "Tampered User".to_string(),
         );
+        let mut signed = crate::types::SignedProfile::sign(&profile, &keypair);
 
-        // Joy joins via invite
-        let joined_realm_id = joy.join_realm(&invite_str).await.unwrap();
-        assert_eq!(joined_realm_id, realm_id, "Joy should join the same realm");
+        // Tamper with the profile
+        signed.profile.display_name = "HACKED".to_string();
 
-        // Verify Joy is syncing
-        assert!(
-            joy.is_realm_syncing(&realm_id),
-            "Joy should be syncing after joining"
+        // Should reject the invalid signature
+        let result = engine.pin_profile(signed, crate::types::PinRelationship::Manual);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), SyncError::SignatureInvalid(_)));
+    }
+
+    #[tokio::test]
+    async fn test_unpin_profile() {
+        let (engine, _temp) = create_test_engine().await;
+
+        // Pin a profile
+        let keypair = crate::identity::HybridKeypair::generate();
+        let profile = crate::types::UserProfile::new(
+            "peer_unpin".to_string(),
+            "Unpin User".to_string(),
         );
+        let signed = crate::types::SignedProfile::sign(&profile, &keypair);
+        let did = signed.did().to_string();
 
-        // CRITICAL: Wait for peers to connect before sending any messages.
-        // The gossip mesh takes time to establish, and messages sent before
-        // neighbors are connected won't be delivered.
-        debug!("Waiting for peer connections to establish...");
+        engine
+            .pin_profile(signed, crate::types::PinRelationship::Manual)
+            .unwrap();
 
-        // Helper to wait for PeerConnected event on an existing subscription
-        async fn wait_for_peer_connected(
-            events: &mut broadcast::Receiver<SyncEvent>,
-            target_realm: &RealmId,
-            timeout_duration: Duration,
-        ) -> bool {
-            let start = std::time::Instant::now();
-            loop {
-                let remaining = timeout_duration.saturating_sub(start.elapsed());
-                if remaining.is_zero() {
-                    return false;
-                }
-                match tokio::time::timeout(remaining, events.recv()).await {
-                    Ok(Ok(SyncEvent::PeerConnected { realm_id, peer_id })) => {
-                        if &realm_id == target_realm {
-                            debug!(%realm_id, %peer_id, "Peer connected event received");
-                            return true;
-                        }
-                    }
-                    Ok(Ok(_)) => continue, // Other event
-                    Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue, // Lagged, keep trying
-                    Ok(Err(_)) => return false,                                  // Channel closed
-                    Err(_) => return false,                                      // Timeout
-                }
-            }
-        }
+        // Verify it's pinned
+        assert!(engine.get_pinned_profile(&did).unwrap().is_some());
 
-        // Wait for connections using the pre-made subscriptions
-        let love_connected =
-            wait_for_peer_connected(&mut love_events, &realm_id, Duration::from_secs(10)).await;
-        let joy_connected =
-            wait_for_peer_connected(&mut joy_events, &realm_id, Duration::from_secs(10)).await;
+        // Unpin
+        engine.unpin_profile(&did).unwrap();
 
-        debug!(love_connected, joy_connected, "Peer connection status");
+        // Should no longer be pinned
+        assert!(engine.get_pinned_profile(&did).unwrap().is_none());
+    }
 
-        // At least one side should see a connection (typically Joy sees Love first since he used Love as bootstrap)
-        assert!(
-            love_connected || joy_connected,
-            "At least one peer should have connected within 10 seconds"
-        );
+    #[tokio::test]
+    async fn test_cannot_unpin_own_profile() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Give time for gossip mesh to stabilize after peer connection.
-        // The iroh-gossip layer needs time to establish reliable message routing.
-        tokio::time::sleep(Duration::from_millis(500)).await;
+        // Initialize identity
+        engine.init_identity().unwrap();
+        let did = engine.did().unwrap().to_string();
 
-        // Process any pending BroadcastRequests from NeighborUp events
-        love.process_pending_sync();
-        joy.process_pending_sync();
+        // Set up and pin our own profile
+        let profile = crate::types::UserProfile::new(
+            did.clone(),
+            "Own User".to_string(),
+        );
+        engine.save_profile(&profile).unwrap();
+        let signed = engine.sign_and_pin_own_profile().unwrap();
+        let signed_did = signed.did().to_string();
 
-        // Additional stabilization - let any triggered broadcasts complete
-        tokio::time::sleep(Duration::from_millis(200)).await;
+        // Trying to unpin our own profile should fail
+        let result = engine.unpin_profile(&signed_did);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), SyncError::InvalidOperation(_)));
 
-        // Love adds a task (this should broadcast via sync)
-        debug!("Love adding task...");
-        let _task_id = love.add_task(&realm_id, "Love's task").await.unwrap();
-        debug!("Love added task, waiting for sync to Joy...");
+        // Should still be pinned
+        assert!(engine.get_own_pinned_profile().unwrap().is_some());
+    }
 
-        // Wait for sync to propagate to Joy (up to 5 seconds)
-        let mut synced = false;
-        for i in 0..50 {
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            // Process any pending sync messages
-            let processed = joy.process_pending_sync();
-            if processed > 0 {
-                debug!(
-                    "Joy processed {} sync messages at iteration {}",
-                    processed, i
-                );
-            }
-            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
-            debug!(
-                iteration = i,
-                processed,
-                task_count = joy_tasks.len(),
-                "Checking Joy's tasks"
+    #[tokio::test]
+    async fn test_list_pinned_profiles_by_relationship() {
+        let (engine, _temp) = create_test_engine().await;
+
+        // Pin some profiles with different relationships
+        for (suffix, relationship) in [
+            ("contact1", crate::types::PinRelationship::Contact),
+            ("contact2", crate::types::PinRelationship::Contact),
+            ("manual1", crate::types::PinRelationship::Manual),
+        ] {
+            let keypair = crate::identity::HybridKeypair::generate();
+            let profile = crate::types::UserProfile::new(
+                format!("peer_{}", suffix),
+                format!("User {}", suffix),
             );
-            if !joy_tasks.is_empty() {
-                debug!("Joy received task after {}ms", (i + 1) * 100);
-                assert_eq!(joy_tasks[0].title, "Love's task");
-                synced = true;
-                break;
-            }
+            let signed = crate::types::SignedProfile::sign(&profile, &keypair);
+            engine.pin_profile(signed, relationship).unwrap();
         }
 
-        assert!(
-            synced,
-            "Joy should have received Love's task within 5 seconds"
-        );
+        // List by contact relationship
+        let contacts = engine
+            .list_pinned_profiles_by_relationship(&crate::types::PinRelationship::Contact)
+            .unwrap();
+        assert_eq!(contacts.len(), 2);
 
-        // Cleanup
-        love.shutdown().await.unwrap();
-        joy.shutdown().await.unwrap();
+        // List by manual relationship
+        let manual = engine
+            .list_pinned_profiles_by_relationship(&crate::types::PinRelationship::Manual)
+            .unwrap();
+        assert_eq!(manual.len(), 1);
     }
 
-    /// Regression test for sync persistence bug
-    ///
-    /// Verifies that sync changes (from gossip messages) are saved to disk
-    /// and persist across engine restarts. This test simulates:
-    /// 1. Receiving a full document sync message
-    /// 2. Applying it to memory
-    /// 3. Restarting the engine
-    /// 4. Verifying the synced data persisted to disk
     #[tokio::test]
-    async fn test_sync_changes_persist_across_restart() {
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path();
+    async fn test_update_pinned_profile() {
+        let (engine, _temp) = create_test_engine().await;
 
-        // Phase 1: Create engine, create realm, simulate receiving sync data
-        let realm_id = {
-            let mut engine = SyncEngine::new(data_dir).await.unwrap();
-            engine.init_identity().unwrap();
+        // Pin a profile
+        let keypair = crate::identity::HybridKeypair::generate();
+        let profile = crate::types::UserProfile::new(
+            "peer_update".to_string(),
+            "Original Name".to_string(),
+        );
+        let signed = crate::types::SignedProfile::sign(&profile, &keypair);
+        let did = signed.did().to_string();
 
-            // Create a realm
-            let realm_id = engine.create_realm("Test Realm").await.unwrap();
+        engine
+            .pin_profile(signed, crate::types::PinRelationship::Contact)
+            .unwrap();
 
-            // Create a separate document with a task (simulating what we'd receive from a peer)
-            let mut remote_doc = RealmDoc::new();
-            let _ = remote_doc.add_task("Synced Task from Peer");
+        // Create an updated profile (same keypair, different data)
+        let updated_profile = crate::types::UserProfile::new(
+            "peer_update".to_string(),
+            "Updated Name".to_string(),
+        );
+        let updated_signed = crate::types::SignedProfile::sign(&updated_profile, &keypair);
 
-            // Simulate receiving a full document sync (SyncResponse message)
-            let remote_doc_bytes = remote_doc.save();
-            let result = engine.apply_sync_changes(&realm_id, &remote_doc_bytes, true);
-            assert!(result.is_ok(), "Should apply sync changes successfully");
+        // Update the pin
+        let updated = engine.update_pinned_profile(&did, updated_signed).unwrap();
+        assert!(updated);
+
+        // Verify the update
+        let retrieved = engine.get_pinned_profile(&did).unwrap().unwrap();
+        assert_eq!(retrieved.signed_profile.profile.display_name, "Updated Name");
+        // Relationship should be preserved
+        assert_eq!(retrieved.relationship, crate::types::PinRelationship::Contact);
+    }
 
-            // Verify the task exists in memory
-            let tasks = engine.list_tasks(&realm_id).unwrap();
-            assert_eq!(tasks.len(), 1, "Should have 1 task in memory after sync");
-            assert_eq!(tasks[0].title, "Synced Task from Peer");
+    #[tokio::test]
+    async fn test_pinned_profile_count() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-            realm_id
-        }; // Engine drops here, releasing all resources
+        // Initially should be 0 (own profile doesn't count)
+        assert_eq!(engine.pinned_profile_count().unwrap(), 0);
 
-        // Phase 2: Create new engine instance with same data directory
-        {
-            let mut engine2 = SyncEngine::new(data_dir).await.unwrap();
-            engine2.init_identity().unwrap();
+        // Initialize identity and add our own profile (shouldn't count)
+        engine.init_identity().unwrap();
+        let did = engine.did().unwrap().to_string();
+        let profile = crate::types::UserProfile::new(did.clone(), "Self".to_string());
+        engine.save_profile(&profile).unwrap();
+        engine.sign_and_pin_own_profile().unwrap();
 
-            // Open the realm (loads from disk)
-            engine2.open_realm(&realm_id).await.unwrap();
+        // Still 0 (own profile excluded)
+        assert_eq!(engine.pinned_profile_count().unwrap(), 0);
 
-            // Verify the synced task persisted to disk
-            let tasks = engine2.list_tasks(&realm_id).unwrap();
-            assert_eq!(
-                tasks.len(),
-                1,
-                "Should have 1 task after restart (loaded from disk)"
-            );
-            assert_eq!(
-                tasks[0].title, "Synced Task from Peer",
-                "Synced task should persist across restart"
+        // Add peer profiles
+        for i in 0..3 {
+            let keypair = crate::identity::HybridKeypair::generate();
+            let profile = crate::types::UserProfile::new(
+                format!("peer_{}", i),
+                format!("User {}", i),
             );
+            let signed = crate::types::SignedProfile::sign(&profile, &keypair);
+            engine
+                .pin_profile(signed, crate::types::PinRelationship::Manual)
+                .unwrap();
         }
+
+        // Should now be 3
+        assert_eq!(engine.pinned_profile_count().unwrap(), 3);
     }
 
-    /// Test that incremental sync changes also persist
+    // ═══════════════════════════════════════════════════════════════════════
+    // Auto-Pinning Tests
+    // ═══════════════════════════════════════════════════════════════════════
+
     #[tokio::test]
-    async fn test_incremental_sync_changes_persist() {
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path();
+    async fn test_process_profile_announcement_for_contact() {
+        let (engine, _temp) = create_test_engine().await;
 
-        let realm_id = {
-            let mut engine = SyncEngine::new(data_dir).await.unwrap();
-            engine.init_identity().unwrap();
+        // Create a "contact's" keypair and signed profile
+        let contact_keypair = crate::identity::HybridKeypair::generate();
+        let contact_profile = crate::types::UserProfile::new(
+            "peer_contact".to_string(),
+            "Contact User".to_string(),
+        );
+        let signed = crate::types::SignedProfile::sign(&contact_profile, &contact_keypair);
+        let contact_did = signed.did().to_string();
 
-            // Create a realm with an initial task
-            let realm_id = engine.create_realm("Test Realm").await.unwrap();
-            engine.add_task(&realm_id, "Initial Task").await.unwrap();
+        // Create a fake ContactInfo and save it to storage to simulate an accepted contact
+        let contact_info = crate::types::contact::ContactInfo {
+            peer_did: contact_did.clone(),
+            peer_endpoint_id: [0u8; 32],
+            profile: crate::types::contact::ProfileSnapshot {
+                display_name: "Contact User".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: crate::invite::NodeAddrBytes {
+                node_id: [0u8; 32],
+                relay_url: None,
+                direct_addresses: vec![],
+            },
+            contact_topic: [0u8; 32],
+            contact_key: [0u8; 32],
+            accepted_at: chrono::Utc::now().timestamp(),
+            last_seen: 0,
+            status: crate::types::contact::ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: None,
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact_info).unwrap();
 
-            // Get the current document state
-            let state = engine.realms.get_mut(&realm_id).unwrap();
-            let initial_doc_bytes = state.doc.save();
+        // Verify we should auto-pin this contact
+        assert!(engine.should_auto_pin(&contact_did));
+        assert_eq!(
+            engine.determine_pin_relationship(&contact_did),
+            Some(crate::types::PinRelationship::Contact)
+        );
 
-            // Create a remote doc with the same initial state
-            let mut remote_doc = RealmDoc::load(&initial_doc_bytes).unwrap();
-            remote_doc.add_task("Synced Task").unwrap();
+        // Process a profile announcement
+        let announcement = crate::sync::ProfileGossipMessage::announce(signed.clone(), None);
+        let action = engine.process_profile_announcement(&announcement).unwrap();
 
-            // Generate incremental changes using the public API
-            let changes = remote_doc.generate_sync_message();
+        // Should have auto-pinned
+        match action {
+            crate::sync::ProfileAction::UpdatePin { signed_profile, .. } => {
+                assert_eq!(signed_profile.profile.display_name, "Contact User");
+            }
+            _ => panic!("Expected UpdatePin action"),
+        }
 
-            // Apply incremental sync changes
-            let result = engine.apply_sync_changes(&realm_id, &changes, false);
-            assert!(result.is_ok(), "Should apply incremental changes");
+        // Verify the pin was created
+        let pin = engine.get_pinned_profile(&contact_did).unwrap();
+        assert!(pin.is_some());
+        let pin = pin.unwrap();
+        assert_eq!(pin.relationship, crate::types::PinRelationship::Contact);
+    }
 
-            // Verify both tasks exist in memory
-            let tasks = engine.list_tasks(&realm_id).unwrap();
-            assert_eq!(tasks.len(), 2, "Should have 2 tasks after incremental sync");
+    #[tokio::test]
+    async fn test_process_profile_announcement_ignores_unknown_peer() {
+        let (engine, _temp) = create_test_engine().await;
 
-            realm_id
-        };
+        // Create an unknown peer's profile
+        let unknown_keypair = crate::identity::HybridKeypair::generate();
+        let unknown_profile = crate::types::UserProfile::new(
+            "peer_unknown".to_string(),
+            "Unknown User".to_string(),
+        );
+        let signed = crate::types::SignedProfile::sign(&unknown_profile, &unknown_keypair);
+        let unknown_did = signed.did().to_string();
 
-        // Restart and verify
-        {
-            let mut engine2 = SyncEngine::new(data_dir).await.unwrap();
-            engine2.init_identity().unwrap();
-            engine2.open_realm(&realm_id).await.unwrap();
+        // Verify we should NOT auto-pin this peer
+        assert!(!engine.should_auto_pin(&unknown_did));
+        assert_eq!(engine.determine_pin_relationship(&unknown_did), None);
 
-            let tasks = engine2.list_tasks(&realm_id).unwrap();
-            assert_eq!(
-                tasks.len(),
-                2,
-                "Both tasks should persist after restart (incremental sync)"
-            );
-        }
+        // Process a profile announcement
+        let announcement = crate::sync::ProfileGossipMessage::announce(signed, None);
+        let action = engine.process_profile_announcement(&announcement).unwrap();
+
+        // Should have been ignored
+        assert!(matches!(action, crate::sync::ProfileAction::Ignore));
+
+        // Verify no pin was created
+        assert!(engine.get_pinned_profile(&unknown_did).unwrap().is_none());
     }
 
-    /// Test that bootstrap peers are saved during join_via_invite and loaded during start_sync
-    ///
-    /// This regression test verifies the fix for the "sync lost after restart" bug where:
-    /// - Bootstrap peers from the invite were used once during initial join
-    /// - After restart, start_sync() passed empty peers, creating isolated nodes
-    ///
-    /// The fix saves bootstrap_peers in RealmInfo and loads them in start_sync().
     #[tokio::test]
-    async fn test_bootstrap_peers_persist_for_reconnection() {
-        use crate::invite::NodeAddrBytes;
+    async fn test_process_profile_announcement_updates_existing_pin() {
+        let (engine, _temp) = create_test_engine().await;
 
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path();
+        // Create a contact's keypair
+        let contact_keypair = crate::identity::HybridKeypair::generate();
+        let contact_did = crate::identity::Did::from_public_key(
+            &contact_keypair.public_key()
+        ).to_string();
 
-        // Create a mock bootstrap peer address
-        let mock_peer = NodeAddrBytes {
-            node_id: [42u8; 32],
-            relay_url: Some("https://relay.example.com".to_string()),
-            direct_addresses: vec!["192.168.1.100:4433".to_string()],
+        // Save as a contact
+        let contact_info = crate::types::contact::ContactInfo {
+            peer_did: contact_did.clone(),
+            peer_endpoint_id: [0u8; 32],
+            profile: crate::types::contact::ProfileSnapshot {
+                display_name: "Original Name".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: crate::invite::NodeAddrBytes {
+                node_id: [0u8; 32],
+                relay_url: None,
+                direct_addresses: vec![],
+            },
+            contact_topic: [0u8; 32],
+            contact_key: [0u8; 32],
+            accepted_at: chrono::Utc::now().timestamp(),
+            last_seen: 0,
+            status: crate::types::contact::ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: None,
+            mutual_peers: vec![],
+            nickname: None,
         };
+        engine.storage.save_contact(&contact_info).unwrap();
 
-        // Phase 1: Create engine, create a realm and manually set up RealmInfo with peers
-        // (simulating what happens after join_via_invite)
-        let realm_id = {
-            let mut engine = SyncEngine::new(data_dir).await.unwrap();
-            engine.init_identity().unwrap();
+        // Create and process initial announcement
+        let initial_profile = crate::types::UserProfile::new(
+            contact_did.clone(),
+            "Original Name".to_string(),
+        );
+        let initial_signed = crate::types::SignedProfile::sign(&initial_profile, &contact_keypair);
+        let announcement = crate::sync::ProfileGossipMessage::announce(initial_signed, None);
+        engine.process_profile_announcement(&announcement).unwrap();
 
-            // Create a realm
-            let realm_id = engine.create_realm("Shared Test Realm").await.unwrap();
+        // Verify initial pin
+        let pin = engine.get_pinned_profile(&contact_did).unwrap().unwrap();
+        assert_eq!(pin.signed_profile.profile.display_name, "Original Name");
 
-            // Simulate what join_via_invite does: update the realm with bootstrap peers
-            let mut info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
-            info.is_shared = true;
-            info.bootstrap_peers = vec![mock_peer.clone()];
-            engine.storage.save_realm(&info).unwrap();
+        // Create and process updated announcement
+        let updated_profile = crate::types::UserProfile::new(
+            contact_did.clone(),
+            "Updated Name".to_string(),
+        );
+        let updated_signed = crate::types::SignedProfile::sign(&updated_profile, &contact_keypair);
+        let announcement = crate::sync::ProfileGossipMessage::announce(updated_signed, None);
+        engine.process_profile_announcement(&announcement).unwrap();
 
-            realm_id
-        }; // Engine drops here
+        // Verify pin was updated
+        let pin = engine.get_pinned_profile(&contact_did).unwrap().unwrap();
+        assert_eq!(pin.signed_profile.profile.display_name, "Updated Name");
+        // Relationship should be preserved
+        assert_eq!(pin.relationship, crate::types::PinRelationship::Contact);
+    }
 
-        // Phase 2: Create new engine instance and verify peers are loaded from storage
-        {
-            let engine2 = SyncEngine::new(data_dir).await.unwrap();
+    #[tokio::test]
+    async fn test_get_auto_pin_interests() {
+        let (engine, _temp) = create_test_engine().await;
 
-            // Load the realm info from storage
-            let loaded_info = engine2.storage.load_realm(&realm_id).unwrap().unwrap();
+        // Initially no interests
+        let interests = engine.get_auto_pin_interests().unwrap();
+        assert!(interests.is_empty());
 
-            // Verify bootstrap peers were persisted
-            assert_eq!(
-                loaded_info.bootstrap_peers.len(),
-                1,
-                "Bootstrap peers should persist to storage"
-            );
-            assert_eq!(
-                loaded_info.bootstrap_peers[0].node_id, mock_peer.node_id,
-                "Peer node_id should match"
-            );
-            assert_eq!(
-                loaded_info.bootstrap_peers[0].relay_url, mock_peer.relay_url,
-                "Peer relay_url should match"
-            );
-            assert_eq!(
-                loaded_info.bootstrap_peers[0].direct_addresses, mock_peer.direct_addresses,
-                "Peer direct_addresses should match"
-            );
+        // Add some contacts
+        for i in 0..3 {
+            let contact_info = crate::types::contact::ContactInfo {
+                peer_did: format!("did:sync:contact{}", i),
+                peer_endpoint_id: [i as u8; 32],
+                profile: crate::types::contact::ProfileSnapshot {
+                    display_name: format!("Contact {}", i),
+                    subtitle: None,
+                    avatar_blob_id: None,
+                    bio: String::new(),
+                },
+                node_addr: crate::invite::NodeAddrBytes {
+                    node_id: [i as u8; 32],
+                    relay_url: None,
+                    direct_addresses: vec![],
+                },
+                contact_topic: [i as u8; 32],
+                contact_key: [i as u8; 32],
+                accepted_at: chrono::Utc::now().timestamp(),
+                last_seen: 0,
+                status: crate::types::contact::ContactStatus::Offline,
+                is_favorite: false,
+                encryption_keys: None,
+                mutual_peers: vec![],
+                nickname: None,
+            };
+            engine.storage.save_contact(&contact_info).unwrap();
         }
+
+        // Should now have 3 interests
+        let interests = engine.get_auto_pin_interests().unwrap();
+        assert_eq!(interests.len(), 3);
+        assert!(interests.contains(&"did:sync:contact0".to_string()));
+        assert!(interests.contains(&"did:sync:contact1".to_string()));
+        assert!(interests.contains(&"did:sync:contact2".to_string()));
     }
 
-    /// Test that RealmInfo can roundtrip through storage with and without bootstrap_peers
-    /// (backwards compatibility - old realms without the field should still load)
+    // ═══════════════════════════════════════════════════════════════════════
+    // Startup Sync Tests
+    // ═══════════════════════════════════════════════════════════════════════
+
     #[tokio::test]
-    async fn test_realm_info_backwards_compatible() {
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path();
+    async fn test_startup_sync_with_no_peers() {
+        let (mut engine, _temp) = create_test_engine().await;
 
-        let mut engine = SyncEngine::new(data_dir).await.unwrap();
+        // Initialize identity for the engine
         engine.init_identity().unwrap();
 
-        // Create a realm using the constructor (which sets bootstrap_peers to empty vec)
-        let realm_id = engine.create_realm("Legacy Realm").await.unwrap();
-
-        // Load and verify it has an empty bootstrap_peers vec
-        let info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
-        assert!(
-            info.bootstrap_peers.is_empty(),
-            "New realms should have empty bootstrap_peers by default"
-        );
+        // Startup sync with no peers should complete immediately after jitter
+        let result = engine.startup_sync().await.unwrap();
 
-        // The #[serde(default)] attribute ensures old stored realms without
-        // the bootstrap_peers field will deserialize with an empty vec
+        // No peers to connect to
+        assert_eq!(result.peers_attempted, 0);
+        assert_eq!(result.peers_succeeded, 0);
+        assert_eq!(result.peers_skipped_backoff, 0);
+        // Jitter should be in range 0-30000 ms
+        assert!(result.jitter_delay_ms < 30_000);
     }
 
-    /// Test that peer addresses learned from Announce messages are persisted to storage
-    ///
-    /// This regression test verifies the fix for the "creator can't reconnect after restart" bug:
-    /// - Creator creates realm and has EMPTY bootstrap_peers
-    /// - Joiner joins and sends Announce with their address
-    /// - Creator MUST persist that address to bootstrap_peers
-    /// - After restart, creator can use saved peers to reconnect
     #[tokio::test]
-    async fn test_creator_learns_and_persists_peer_from_announce() {
-        use crate::identity::HybridKeypair;
-        use crate::invite::NodeAddrBytes;
-        use crate::sync::{SyncEnvelope, SyncMessage};
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
-
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path();
+    async fn test_startup_sync_result_default() {
+        let result = StartupSyncResult::default();
+        assert_eq!(result.peers_attempted, 0);
+        assert_eq!(result.peers_succeeded, 0);
+        assert_eq!(result.peers_skipped_backoff, 0);
+        assert_eq!(result.profiles_updated, 0);
+        assert_eq!(result.jitter_delay_ms, 0);
+    }
 
-        let mut engine = SyncEngine::new(data_dir).await.unwrap();
+    #[tokio::test]
+    async fn test_startup_sync_respects_backoff() {
+        let (mut engine, _temp) = create_test_engine().await;
         engine.init_identity().unwrap();
 
-        // Create a realm (simulating the "love" instance)
-        let realm_id = engine.create_realm("Creator Realm").await.unwrap();
-
-        // Verify creator starts with NO bootstrap peers
-        let info_before = engine.storage.load_realm(&realm_id).unwrap().unwrap();
-        assert!(
-            info_before.bootstrap_peers.is_empty(),
-            "Creator should start with empty bootstrap_peers"
+        // Add a peer with many failed attempts (should be in backoff)
+        let mut peer = crate::types::peer::Peer::new(
+            iroh::SecretKey::generate(&mut rand::rng()).public(),
+            crate::types::peer::PeerSource::FromInvite,
         );
+        // Simulate many failures
+        peer.connection_attempts = 5;
+        peer.successful_connections = 0;
+        // Set last_attempt to now (still in backoff period)
+        peer.last_attempt = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        engine.storage.save_peer(&peer).unwrap();
 
-        // Mark realm as shared (happens during generate_invite)
-        let mut info = info_before.clone();
-        info.is_shared = true;
-        engine.storage.save_realm(&info).unwrap();
-
-        // Save a realm key (required for envelope handling)
-        let realm_key = [42u8; 32];
-        engine
-            .storage
-            .save_realm_key(&realm_id, &realm_key)
-            .unwrap();
-
-        // Open the realm so it's in memory
-        engine.open_realm(&realm_id).await.unwrap();
+        // Run startup sync
+        let result = engine.startup_sync().await.unwrap();
 
-        // Update the in-memory state with the realm key
-        if let Some(state) = engine.realms.get_mut(&realm_id) {
-            state.realm_key = realm_key;
-        }
+        // Peer should be skipped due to backoff
+        assert_eq!(result.peers_attempted, 0);
+        assert_eq!(result.peers_skipped_backoff, 1);
+    }
 
-        // Create a joiner's keypair and pin their profile (required for signature verification)
-        let joiner_keypair = HybridKeypair::generate();
-        let joiner_profile = UserProfile::new("joiner_peer".to_string(), "Joiner".to_string());
-        let joiner_signed = SignedProfile::sign(&joiner_profile, &joiner_keypair);
-        let joiner_did = joiner_signed.did().to_string();
+    #[tokio::test]
+    async fn test_startup_jitter_window_is_configurable() {
+        // No window: no delay
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
         engine
-            .pin_profile(joiner_signed.clone(), PinRelationship::Contact)
+            .set_gossip_config(GossipConfig {
+                startup_jitter_max_ms: Some(0),
+                ..Default::default()
+            })
             .unwrap();
+        let started = Instant::now();
+        let result = engine.startup_sync().await.unwrap();
+        assert_eq!(result.jitter_delay_ms, 0);
+        assert!(started.elapsed() < Duration::from_millis(
+            GossipConfig::DEFAULT_STARTUP_JITTER_MAX_MS
+        ));
+        engine.shutdown().await.unwrap();
 
-        // Simulate receiving an Announce from a joiner with their address
-        let joiner_addr = NodeAddrBytes {
-            node_id: [99u8; 32], // Different node
-            relay_url: Some("https://relay.joiner.com".to_string()),
-            direct_addresses: vec!["10.0.0.50:4433".to_string()],
-        };
-
-        let announce = SyncMessage::Announce {
-            realm_id: realm_id.clone(),
-            heads: vec![],
-            sender_addr: Some(joiner_addr.clone()),
-        };
-
-        // Create signed envelope from the joiner
-        let envelope_bytes = {
-            let sign_fn = |data: &[u8]| joiner_keypair.sign(data).to_bytes().to_vec();
-            let envelope = SyncEnvelope::seal(&announce, &joiner_did, &realm_key, sign_fn)
-                .expect("Should create envelope");
-            envelope.to_bytes().expect("Should serialize")
-        };
-
-        // Send to the sync channel (simulating incoming gossip message)
+        // A small window: the delay stays inside it and is actually waited out
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
         engine
-            .sync_tx
-            .send(SyncChannelMessage::IncomingData {
-                realm_id: realm_id.clone(),
-                envelope_bytes,
+            .set_gossip_config(GossipConfig {
+                startup_jitter_max_ms: Some(50),
+                ..Default::default()
             })
-            .expect("Should send");
+            .unwrap();
+        let started = Instant::now();
+        let result = engine.startup_sync().await.unwrap();
+        assert!(result.jitter_delay_ms <= 50);
+        assert!(started.elapsed() >= Duration::from_millis(result.jitter_delay_ms));
+        engine.shutdown().await.unwrap();
+    }
 
-        // Process pending sync messages (this should persist the peer)
-        engine.process_pending_sync();
+    #[tokio::test]
+    async fn test_startup_progress_sums_to_result() {
+        use crate::types::peer::{ContactDetails, Peer, PeerSource};
 
-        // Verify the joiner's address was persisted
-        let info_after = engine.storage.load_realm(&realm_id).unwrap().unwrap();
-        assert_eq!(
-            info_after.bootstrap_peers.len(),
-            1,
-            "Creator should have learned 1 peer from announce"
-        );
-        assert_eq!(
-            info_after.bootstrap_peers[0].node_id, joiner_addr.node_id,
-            "Saved peer should match joiner's node_id"
-        );
-        assert_eq!(
-            info_after.bootstrap_peers[0].relay_url, joiner_addr.relay_url,
-            "Saved peer should match joiner's relay_url"
-        );
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
 
-        // Verify idempotency - processing the same announce again shouldn't duplicate
-        let envelope_bytes2 = {
-            let sign_fn = |data: &[u8]| joiner_keypair.sign(data).to_bytes().to_vec();
-            let envelope = SyncEnvelope::seal(&announce, &joiner_did, &realm_key, sign_fn)
-                .expect("Should create envelope");
-            envelope.to_bytes().expect("Should serialize")
+        let new_peer = || {
+            Peer::new(
+                iroh::SecretKey::generate(&mut rand::rng()).public(),
+                PeerSource::FromInvite,
+            )
         };
+        let contact = new_peer().with_contact_info(ContactDetails::new([1; 32], [2; 32]));
+        let mut backing_off = new_peer();
+        backing_off.connection_attempts = 5;
+        backing_off.last_attempt = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let unreachable = new_peer();
+        for peer in [&contact, &backing_off, &unreachable] {
+            engine.storage.save_peer(peer).unwrap();
+        }
 
-        engine
-            .sync_tx
-            .send(SyncChannelMessage::IncomingData {
-                realm_id: realm_id.clone(),
-                envelope_bytes: envelope_bytes2,
-            })
-            .expect("Should send");
-        engine.process_pending_sync();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let collector = tokio::spawn(async move {
+            let mut events = Vec::new();
+            while let Some(event) = rx.recv().await {
+                events.push(event);
+            }
+            events
+        });
+        let result = engine.start_networking_with_progress(tx).await.unwrap();
+        let events = collector.await.unwrap();
 
-        let info_final = engine.storage.load_realm(&realm_id).unwrap().unwrap();
-        assert_eq!(
-            info_final.bootstrap_peers.len(),
-            1,
-            "Should not duplicate peer on repeated announce"
-        );
+        assert!(matches!(events[0], StartupProgress::Started { peer_count: 3, .. }));
+        let mut summed = StartupSyncResult::default();
+        for event in &events {
+            summed.record(event);
+        }
+        assert_eq!(summed.peers_attempted, result.peers_attempted);
+        assert_eq!(summed.peers_succeeded, result.peers_succeeded);
+        assert_eq!(summed.peers_skipped_backoff, result.peers_skipped_backoff);
+        assert_eq!(summed.jitter_delay_ms, result.jitter_delay_ms);
+
+        let id = |peer: &Peer| peer.public_key().to_string();
+        assert!(events.contains(&StartupProgress::Succeeded { peer_id: id(&contact) }));
+        assert!(events.contains(&StartupProgress::SkippedBackoff { peer_id: id(&backing_off) }));
+        let attempt = events
+            .iter()
+            .position(|e| *e == StartupProgress::Attempting { peer_id: id(&unreachable) })
+            .expect("Unreachable peer is dialed");
+        assert!(events[attempt + 1..].iter().any(|e| matches!(
+            e,
+            StartupProgress::Succeeded { peer_id } | StartupProgress::Failed { peer_id }
+                if *peer_id == id(&unreachable)
+        )));
+        assert_eq!(result.peers_attempted, 1);
+        assert_eq!(result.peers_skipped_backoff, 1);
+
+        engine.shutdown().await.unwrap();
     }
 
-    /// Test that sync_status() returns updated peer count when peers connect
+    // ═══════════════════════════════════════════════════════════════════════
+    // Chat/Messaging Integration Tests
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Test that packets created with create_packet appear in get_conversation().
     ///
-    /// This is a TDD test that verifies the fix for the peer counting bug:
-    /// - Two engines connect via gossip
-    /// - Both should report `Syncing { peer_count: 1 }` (or more) after connection
+    /// This is the core regression test for the "sent messages don't appear in sender's chat" bug.
+    /// It tests the fundamental flow WITHOUT network dependencies:
     ///
-    /// ROOT CAUSE BEING TESTED: The listener task spawned in start_sync_internal
-    /// receives NeighborUp/NeighborDown events but the sync_status HashMap is
-    /// not accessible from that task (it's not thread-safe).
+    /// 1. create_packet() → stores DirectMessage packet in profile_log
+    /// 2. get_conversation() → loads sent packets from profile_log, filters by recipient
+    /// 3. The sent message should appear with is_mine=true
+    ///
+    /// If this test FAILS: the bug is in the core library (packet storage or conversation loading)
+    /// If this test PASSES: the bug is in either:
+    ///   - The UI layer (Dioxus state management), OR
+    ///   - Network-related code paths (contact manager, broadcast)
+    ///
+    /// Note: This test sets up a contact with encryption keys to enable E2E encryption.
     #[tokio::test]
-    async fn test_sync_status_updates_peer_count() {
-        use std::time::Duration;
-
-        let _ = tracing_subscriber::fmt::try_init();
-
-        // Create Love's engine
-        let temp_dir_love = TempDir::new().unwrap();
-        let mut love = SyncEngine::new(temp_dir_love.path()).await.unwrap();
-        love.init_identity().unwrap();
+    async fn test_created_packet_appears_in_conversation() {
+        use crate::profile::{PacketAddress, PacketPayload, ProfileKeys};
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
 
-        // Create Joy's engine
-        let temp_dir_joy = TempDir::new().unwrap();
-        let mut joy = SyncEngine::new(temp_dir_joy.path()).await.unwrap();
-        joy.init_identity().unwrap();
+        let (mut engine, _temp) = create_test_engine().await;
 
-        // Start networking on both
-        love.start_networking().await.unwrap();
-        joy.start_networking().await.unwrap();
+        // Initialize identity and profile keys (required for create_packet)
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
 
-        // Exchange peer addresses bidirectionally
-        if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr()) {
-            love.add_peer_addr(joy_addr);
-            joy.add_peer_addr(love_addr);
-        }
+        // Generate ProfileKeys for the contact to enable E2E encryption
+        let contact_profile_keys = ProfileKeys::generate();
+        let contact_pubkeys = contact_profile_keys.public_bundle();
+        let contact_did_str = contact_pubkeys.did().to_string();
 
-        // Small delay to let discovery propagate
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Set up the contact with encryption keys
+        let contact = ContactInfo {
+            peer_did: contact_did_str.clone(),
+            peer_endpoint_id: [0u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Test Contact".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([0u8; 32]),
+            contact_topic: [1u8; 32],
+            contact_key: [2u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: Some(contact_pubkeys.to_bytes()),
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact).unwrap();
 
-        // Subscribe to events before sync starts
-        let mut love_events = love.subscribe_events();
-        let mut joy_events = joy.subscribe_events();
+        let contact_did = Did::parse(&contact_did_str).unwrap();
 
-        // Love creates a realm and generates an invite
-        let realm_id = love.create_realm("Peer Count Test").await.unwrap();
-        let invite_str = love.create_invite(&realm_id).await.unwrap();
+        // Create an E2E encrypted DirectMessage payload
+        let content = "Hello from integration test!";
+        let payload = PacketPayload::DirectMessage {
+            content: content.to_string(),
+            recipient: contact_did.clone(),
+        };
+        let address = PacketAddress::Individual(contact_did);
 
-        // Verify Love is syncing (but peer_count should be 0 initially)
-        let love_status_initial = love.sync_status(&realm_id);
-        info!(?love_status_initial, "Love initial sync status");
+        // Create the packet (stores it in our log with E2E encryption)
+        let seq_result = engine.create_packet(payload, address);
         assert!(
-            matches!(love_status_initial, SyncStatus::Syncing { .. }),
-            "Love should be in Syncing state"
+            seq_result.is_ok(),
+            "create_packet should succeed: {:?}",
+            seq_result.err()
         );
+        let seq = seq_result.unwrap();
+        println!("Created E2E encrypted packet with sequence: {}", seq);
 
-        // Joy joins via invite
-        let _joined_realm_id = joy.join_realm(&invite_str).await.unwrap();
+        // Verify profile_log has the packet
+        let log_count = engine
+            .profile_log
+            .as_ref()
+            .map(|log| log.entries_ordered().len())
+            .unwrap_or(0);
+        println!("profile_log has {} entries", log_count);
+        assert!(
+            log_count > 0,
+            "profile_log should have at least 1 entry after create_packet"
+        );
 
-        // Wait for PeerConnected events (using existing helper pattern)
-        async fn wait_for_peer(
-            events: &mut broadcast::Receiver<SyncEvent>,
-            target_realm: &RealmId,
-        ) -> bool {
-            let timeout = Duration::from_secs(10);
-            let start = std::time::Instant::now();
-            loop {
-                let remaining = timeout.saturating_sub(start.elapsed());
-                if remaining.is_zero() {
-                    return false;
-                }
-                match tokio::time::timeout(remaining, events.recv()).await {
-                    Ok(Ok(SyncEvent::PeerConnected { realm_id, .. })) => {
-                        if &realm_id == target_realm {
-                            return true;
-                        }
-                    }
-                    Ok(Ok(_)) => continue,
-                    Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
-                    Ok(Err(_)) => return false,
-                    Err(_) => return false,
-                }
-            }
-        }
+        // Get the conversation - THIS IS THE KEY TEST
+        let conversation_result = engine.get_conversation(&contact_did_str);
+        assert!(
+            conversation_result.is_ok(),
+            "get_conversation should succeed: {:?}",
+            conversation_result.err()
+        );
 
-        // Wait for at least one peer connection
-        let love_connected = wait_for_peer(&mut love_events, &realm_id).await;
-        let joy_connected = wait_for_peer(&mut joy_events, &realm_id).await;
+        let conversation = conversation_result.unwrap();
+        println!("Conversation has {} messages", conversation.len());
 
-        debug!(
-            love_connected,
-            joy_connected, "Peer connection events received"
+        // THE KEY ASSERTION: sent message should appear
+        assert_eq!(
+            conversation.len(),
+            1,
+            "Conversation should have exactly 1 message (the created one)"
         );
 
-        assert!(
-            love_connected || joy_connected,
-            "At least one peer should have connected"
+        // Verify message properties
+        let msg = conversation
+            .messages()
+            .first()
+            .expect("Should have a message");
+        assert_eq!(msg.content, content, "Message content should match");
+        assert!(msg.is_mine, "Message should be marked as is_mine=true");
+        println!(
+            "SUCCESS: Message content='{}', is_mine={}",
+            msg.content, msg.is_mine
         );
+    }
 
-        // Give a moment for status to settle
-        tokio::time::sleep(Duration::from_millis(200)).await;
+    /// Test that multiple messages to the same contact all appear in conversation.
+    ///
+    /// This catches potential issues with sequence handling or duplicate filtering.
+    /// Note: This test sets up a contact with encryption keys to enable E2E encryption.
+    #[tokio::test]
+    async fn test_multiple_messages_appear_in_conversation() {
+        use crate::profile::{PacketAddress, PacketPayload, ProfileKeys};
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
 
-        // THE CRITICAL ASSERTION: After peers connect, sync_status should report peer_count >= 1
-        let love_status = love.sync_status(&realm_id);
-        let joy_status = joy.sync_status(&realm_id);
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
 
-        info!(
-            ?love_status,
-            ?joy_status,
-            "Final sync status after peer connection"
-        );
+        // Generate ProfileKeys for the contact to enable E2E encryption
+        let contact_profile_keys = ProfileKeys::generate();
+        let contact_pubkeys = contact_profile_keys.public_bundle();
+        let contact_did_str = contact_pubkeys.did().to_string();
 
-        // Check Love's peer count
-        match love_status {
-            SyncStatus::Syncing { peer_count } => {
-                assert!(
-                    peer_count >= 1,
-                    "Love should report at least 1 peer, but got peer_count={}",
-                    peer_count
-                );
-            }
-            other => panic!("Love should be Syncing, but got {:?}", other),
+        // Set up the contact with encryption keys
+        let contact = ContactInfo {
+            peer_did: contact_did_str.clone(),
+            peer_endpoint_id: [0u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Test Contact".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([0u8; 32]),
+            contact_topic: [1u8; 32],
+            contact_key: [2u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: Some(contact_pubkeys.to_bytes()),
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact).unwrap();
+
+        let contact_did = Did::parse(&contact_did_str).unwrap();
+
+        // Send multiple E2E encrypted messages
+        let messages = ["First message", "Second message", "Third message"];
+        for msg in &messages {
+            let payload = PacketPayload::DirectMessage {
+                content: msg.to_string(),
+                recipient: contact_did.clone(),
+            };
+            let address = PacketAddress::Individual(contact_did.clone());
+            engine.create_packet(payload, address).unwrap();
         }
 
-        // Check Joy's peer count
-        match joy_status {
-            SyncStatus::Syncing { peer_count } => {
-                assert!(
-                    peer_count >= 1,
-                    "Joy should report at least 1 peer, but got peer_count={}",
-                    peer_count
-                );
-            }
-            other => panic!("Joy should be Syncing, but got {:?}", other),
-        }
+        // Verify all messages appear
+        let conversation = engine.get_conversation(&contact_did_str).unwrap();
+        assert_eq!(
+            conversation.len(),
+            3,
+            "Conversation should have all 3 messages"
+        );
 
-        // Cleanup
-        love.shutdown().await.unwrap();
-        joy.shutdown().await.unwrap();
+        // Verify each message content (they should be sorted by timestamp/sequence)
+        let conv_messages = conversation.messages();
+        for (i, msg) in conv_messages.iter().enumerate() {
+            assert_eq!(msg.content, messages[i], "Message {} content should match", i);
+            assert!(msg.is_mine, "Message {} should be marked as is_mine=true", i);
+        }
     }
 
-    /// Test that offline changes sync correctly after restart
-    ///
-    /// This verifies the complete offline-to-online sync flow:
-    /// 1. Two engines sync initially
-    /// 2. Both shut down
-    /// 3. Each adds tasks while offline (different tasks)
-    /// 4. Both restart and reconnect
-    /// 5. After sync, both should have ALL tasks from BOTH peers
-    ///
-    /// This tests Automerge's CRDT merge behavior for offline changes.
     #[tokio::test]
-    async fn test_offline_changes_sync_after_restart() {
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
-        use std::time::Duration;
-
-        let _ = tracing_subscriber::fmt::try_init();
-
-        // Use persistent directories
-        let temp_dir_love = TempDir::new().unwrap();
-        let temp_dir_joy = TempDir::new().unwrap();
-        let love_path = temp_dir_love.path().to_path_buf();
-        let joy_path = temp_dir_joy.path().to_path_buf();
-
-        // === Phase 1: Initial sync setup ===
-        let realm_id = {
-            let mut love = SyncEngine::new(&love_path).await.unwrap();
-            love.init_identity().unwrap();
+    async fn test_group_packet_readable_only_by_realm_members() {
+        use crate::profile::{PacketAddress, PacketPayload};
 
-            let mut joy = SyncEngine::new(&joy_path).await.unwrap();
-            joy.init_identity().unwrap();
-
-            // CRITICAL: Exchange and pin profiles so signature verification works across restarts
-            {
-                let love_keypair = love.identity.as_ref().unwrap();
-                let love_profile = UserProfile::new("love_offline".to_string(), "Love".to_string());
-                let love_signed = SignedProfile::sign(&love_profile, love_keypair);
+        let (mut author, _temp_a) = create_test_engine().await;
+        author.init_identity().unwrap();
+        author.init_profile_keys().unwrap();
+        let realm_id = author.create_realm("Circle").await.unwrap();
 
-                let joy_keypair = joy.identity.as_ref().unwrap();
-                let joy_profile = UserProfile::new("joy_offline".to_string(), "Joy".to_string());
-                let joy_signed = SignedProfile::sign(&joy_profile, joy_keypair);
+        let payload = PacketPayload::TaskReference {
+            realm_id: realm_id.clone(),
+            task_id: "task-1".to_string(),
+            description: "Only for the circle".to_string(),
+        };
+        let seq = author
+            .create_packet(payload.clone(), PacketAddress::Group(realm_id.clone()))
+            .unwrap();
+        let envelope = author.profile_log.as_ref().unwrap().get(seq).unwrap().envelope.clone();
+        assert!(envelope.is_group());
+        assert_eq!(author.decrypt_packet(&envelope), Some(payload.clone()));
 
-                love
-                    .pin_profile(joy_signed.clone(), PinRelationship::Contact)
-                    .unwrap();
-                joy.pin_profile(love_signed.clone(), PinRelationship::Contact)
-                    .unwrap();
-                debug!("Exchanged and pinned profiles between Love and Joy");
-            }
+        let (mut other, _temp_b) = create_test_engine().await;
+        other.init_identity().unwrap();
+        other.init_profile_keys().unwrap();
+        assert_eq!(other.decrypt_packet(&envelope), None, "Non-member must not read it");
 
-            // Start networking
-            love.start_networking().await.unwrap();
-            joy.start_networking().await.unwrap();
+        let realm_key = author.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        other.storage.save_realm_key(&realm_id, &realm_key).unwrap();
+        assert_eq!(other.decrypt_packet(&envelope), Some(payload));
 
-            // Exchange addresses
-            if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr())
-            {
-                love.add_peer_addr(joy_addr);
-                joy.add_peer_addr(love_addr);
-            }
-            tokio::time::sleep(Duration::from_millis(50)).await;
+        // Can't address a realm we don't hold the key for
+        let unknown = RealmId::new();
+        assert!(matches!(
+            author.create_packet(
+                PacketPayload::Heartbeat { timestamp: 0 },
+                PacketAddress::Group(unknown)
+            ),
+            Err(SyncError::RealmNotFound(_))
+        ));
+    }
 
-            // Subscribe to events
-            let mut love_events = love.subscribe_events();
+    /// Test that messages to different contacts are properly separated.
+    ///
+    /// This ensures the recipient filtering in get_conversation works correctly.
+    /// Note: This test sets up contacts with encryption keys to enable E2E encryption.
+    #[tokio::test]
+    async fn test_messages_separated_by_contact() {
+        use crate::profile::{PacketAddress, PacketPayload, ProfileKeys};
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
 
-            // Create realm and invite
-            let realm_id = love.create_realm("Offline Sync Test").await.unwrap();
-            let invite_str = love.create_invite(&realm_id).await.unwrap();
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
 
-            // Joy joins
-            let _joined = joy.join_realm(&invite_str).await.unwrap();
+        // Generate profile keys for contacts to enable E2E encryption
+        let contact1_keys = ProfileKeys::generate();
+        let contact1_pubkeys = contact1_keys.public_bundle();
+        let contact1_did = contact1_pubkeys.did().to_string();
 
-            // Wait for connection
-            let connected = tokio::time::timeout(Duration::from_secs(10), async {
-                loop {
-                    match love_events.recv().await {
-                        Ok(SyncEvent::PeerConnected { realm_id: r, .. }) if r == realm_id => {
-                            return true;
-                        }
-                        Ok(_) => continue,
-                        Err(_) => return false,
-                    }
-                }
-            })
-            .await
-            .unwrap_or(false);
+        let contact2_keys = ProfileKeys::generate();
+        let contact2_pubkeys = contact2_keys.public_bundle();
+        let contact2_did = contact2_pubkeys.did().to_string();
 
-            assert!(connected, "Initial connection should succeed");
+        // Create contacts with encryption keys
+        let contact1 = ContactInfo {
+            peer_did: contact1_did.clone(),
+            peer_endpoint_id: [1u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Contact 1".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([1u8; 32]),
+            contact_topic: [10u8; 32],
+            contact_key: [11u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: Some(contact1_pubkeys.to_bytes()),
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact1).unwrap();
 
-            // Love adds initial task and waits for sync
-            love
-                .add_task(&realm_id, "Initial shared task")
-                .await
-                .unwrap();
-            tokio::time::sleep(Duration::from_millis(500)).await;
-            joy.process_pending_sync();
+        let contact2 = ContactInfo {
+            peer_did: contact2_did.clone(),
+            peer_endpoint_id: [2u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Contact 2".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([2u8; 32]),
+            contact_topic: [20u8; 32],
+            contact_key: [21u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: Some(contact2_pubkeys.to_bytes()),
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact2).unwrap();
 
-            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
-            assert!(!joy_tasks.is_empty(), "Joy should have initial task");
+        let did1 = Did::parse(&contact1_did).unwrap();
+        let did2 = Did::parse(&contact2_did).unwrap();
 
-            // Shutdown both
-            love.shutdown().await.unwrap();
-            joy.shutdown().await.unwrap();
+        // Send E2E encrypted message to contact 1
+        let payload1 = PacketPayload::DirectMessage {
+            content: "Hello Contact 1".to_string(),
+            recipient: did1.clone(),
+        };
+        engine.create_packet(payload1, PacketAddress::Individual(did1)).unwrap();
 
-            realm_id
+        // Send E2E encrypted message to contact 2
+        let payload2 = PacketPayload::DirectMessage {
+            content: "Hello Contact 2".to_string(),
+            recipient: did2.clone(),
         };
+        engine.create_packet(payload2, PacketAddress::Individual(did2)).unwrap();
 
-        info!(?realm_id, "Phase 1 complete - both engines shutdown");
+        // Verify contact 1's conversation only has their message
+        let convo1 = engine.get_conversation(&contact1_did).unwrap();
+        assert_eq!(convo1.len(), 1, "Contact 1 should have exactly 1 message");
+        assert_eq!(convo1.messages()[0].content, "Hello Contact 1");
 
-        // === Phase 2: Offline changes ===
-        // Love adds tasks while offline (no networking)
-        {
-            let mut love = SyncEngine::new(&love_path).await.unwrap();
-            love.init_identity().unwrap();
-            love.open_realm(&realm_id).await.unwrap();
+        // Verify contact 2's conversation only has their message
+        let convo2 = engine.get_conversation(&contact2_did).unwrap();
+        assert_eq!(convo2.len(), 1, "Contact 2 should have exactly 1 message");
+        assert_eq!(convo2.messages()[0].content, "Hello Contact 2");
+    }
 
-            love
-                .add_task(&realm_id, "Love offline task 1")
-                .await
-                .unwrap();
-            love
-                .add_task(&realm_id, "Love offline task 2")
-                .await
-                .unwrap();
+    #[tokio::test]
+    async fn test_contact_nickname_overrides_name_in_conversation() {
+        use crate::invite::NodeAddrBytes;
+        use crate::profile::ProfileKeys;
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
 
-            let love_tasks = love.list_tasks(&realm_id).unwrap();
-            info!(count = love_tasks.len(), "Love offline tasks");
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
 
-            // Shutdown without starting networking
-            love.shutdown().await.unwrap();
+        // Two contacts who both call themselves "Sam"
+        let mut dids = Vec::new();
+        for i in 0..2u8 {
+            let did = ProfileKeys::generate().public_bundle().did().to_string();
+            let endpoint_id = iroh::SecretKey::generate(&mut rand::rng()).public();
+            let profile = ProfileSnapshot {
+                display_name: "Sam".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            };
+            engine
+                .storage
+                .save_contact(&ContactInfo {
+                    peer_did: did.clone(),
+                    peer_endpoint_id: *endpoint_id.as_bytes(),
+                    profile: profile.clone(),
+                    node_addr: NodeAddrBytes::new(*endpoint_id.as_bytes()),
+                    contact_topic: [i; 32],
+                    contact_key: [i; 32],
+                    accepted_at: 0,
+                    last_seen: 0,
+                    status: ContactStatus::Offline,
+                    is_favorite: false,
+                    encryption_keys: None,
+                    mutual_peers: vec![],
+                    nickname: None,
+                })
+                .unwrap();
+            let peer = crate::types::peer::Peer::new(endpoint_id, crate::types::peer::PeerSource::FromContact)
+                .with_did(did.clone())
+                .with_profile(profile);
+            engine.save_peer(&peer).unwrap();
+            dids.push(did);
         }
 
-        // Joy adds tasks while offline
-        {
-            let mut joy = SyncEngine::new(&joy_path).await.unwrap();
-            joy.init_identity().unwrap();
-            joy.open_realm(&realm_id).await.unwrap();
-
-            joy.add_task(&realm_id, "Joy offline task 1").await.unwrap();
-            joy.add_task(&realm_id, "Joy offline task 2").await.unwrap();
-
-            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
-            info!(count = joy_tasks.len(), "Joy offline tasks");
-
-            joy.shutdown().await.unwrap();
-        }
+        engine
+            .set_contact_nickname(&dids[0], Some("  Sam from work ".to_string()))
+            .unwrap();
 
-        info!("Phase 2 complete - both added offline tasks");
+        assert_eq!(engine.get_conversation(&dids[0]).unwrap().display_name(), "Sam from work");
+        assert_eq!(engine.get_conversation(&dids[1]).unwrap().display_name(), "Sam");
+        let contact = engine.storage.load_contact(&dids[0]).unwrap().unwrap();
+        assert_eq!(contact.display_name(), "Sam from work");
+        assert_eq!(contact.profile.display_name, "Sam");
+        let peer = engine.get_peer_by_did(&dids[0]).unwrap().unwrap();
+        assert_eq!(peer.profile.unwrap().display_name, "Sam");
+
+        // Blank clears the alias
+        engine.set_contact_nickname(&dids[0], Some(" ".to_string())).unwrap();
+        assert_eq!(engine.get_conversation(&dids[0]).unwrap().display_name(), "Sam");
+        assert!(matches!(
+            engine.set_contact_nickname("did:sync:zNobody", None),
+            Err(SyncError::ContactNotFound(_))
+        ));
+    }
 
-        // Allow database handles to fully release
-        tokio::time::sleep(Duration::from_millis(100)).await;
+    // ═══════════════════════════════════════════════════════════════════════
+    // Signature Verification Tests (make_verify_fn)
+    // ═══════════════════════════════════════════════════════════════════════
 
-        // === Phase 3: Restart and sync ===
-        let mut love = SyncEngine::new(&love_path).await.unwrap();
-        love.init_identity().unwrap();
+    #[tokio::test]
+    async fn test_verify_fn_known_sender_valid_signature() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        let mut joy = SyncEngine::new(&joy_path).await.unwrap();
-        joy.init_identity().unwrap();
+        let (engine, _temp) = create_test_engine().await;
 
-        // Start networking
-        love.start_networking().await.unwrap();
-        joy.start_networking().await.unwrap();
+        // Create a contact's keypair and profile
+        let contact_keypair = HybridKeypair::generate();
+        let contact_profile = UserProfile::new("peer_love".to_string(), "Love".to_string());
+        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
+        let contact_did = signed_profile.did().to_string();
 
-        // Exchange addresses AND update bootstrap_peers in storage
-        // This simulates what happens when users re-exchange invites after restart
-        // (The iroh endpoint has a new node ID after restart, so old saved addresses are stale)
-        if let (Some(love_addr), Some(joy_addr)) = (love.endpoint_addr(), joy.endpoint_addr()) {
-            love.add_peer_addr(joy_addr.clone());
-            joy.add_peer_addr(love_addr.clone());
+        // Pin the profile so they're a known contact
+        engine
+            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
+            .unwrap();
 
-            // Update Love's realm with Joy's fresh address as bootstrap peer
-            if let Ok(Some(mut love_realm_info)) = love.storage.load_realm(&realm_id) {
-                love_realm_info.bootstrap_peers =
-                    vec![NodeAddrBytes::from_endpoint_addr(&joy_addr)];
-                love.storage.save_realm(&love_realm_info).unwrap();
-            }
+        // Sign some test data
+        let test_data = b"Hello, world!";
+        let signature = contact_keypair.sign(test_data);
+        let sig_bytes = signature.to_bytes();
 
-            // Update Joy's realm with Love's fresh address as bootstrap peer
-            if let Ok(Some(mut joy_realm_info)) = joy.storage.load_realm(&realm_id) {
-                joy_realm_info.bootstrap_peers =
-                    vec![NodeAddrBytes::from_endpoint_addr(&love_addr)];
-                joy.storage.save_realm(&joy_realm_info).unwrap();
-            }
-        }
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Create verify function and test
+        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
+        assert!(
+            verify_fn(&contact_did, test_data, &sig_bytes),
+            "Valid signature from known sender should verify"
+        );
+    }
 
-        // Open realms (should auto-start sync for shared realms)
-        love.open_realm(&realm_id).await.unwrap();
-        joy.open_realm(&realm_id).await.unwrap();
+    #[tokio::test]
+    async fn test_verify_fn_unknown_sender_returns_false() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{SignedProfile, UserProfile};
 
-        // Wait for sync to complete
-        let mut synced = false;
-        for i in 0..100 {
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            love.process_pending_sync();
-            joy.process_pending_sync();
+        let (engine, _temp) = create_test_engine().await;
 
-            let love_tasks = love.list_tasks(&realm_id).unwrap();
-            let joy_tasks = joy.list_tasks(&realm_id).unwrap();
+        // Create a stranger's keypair (NOT pinned)
+        let stranger_keypair = HybridKeypair::generate();
+        let stranger_profile = UserProfile::new("peer_stranger".to_string(), "Stranger".to_string());
+        let signed_profile = SignedProfile::sign(&stranger_profile, &stranger_keypair);
+        let stranger_did = signed_profile.did().to_string();
 
-            debug!(
-                iteration = i,
-                love_count = love_tasks.len(),
-                joy_count = joy_tasks.len(),
-                "Checking sync progress"
-            );
+        // Sign some test data
+        let test_data = b"Hello from stranger";
+        let signature = stranger_keypair.sign(test_data);
+        let sig_bytes = signature.to_bytes();
 
-            // Both should have 5 tasks:
-            // 1. Initial shared task
-            // 2. Love offline task 1
-            // 3. Love offline task 2
-            // 4. Joy offline task 1
-            // 5. Joy offline task 2
-            if love_tasks.len() >= 5 && joy_tasks.len() >= 5 {
-                synced = true;
-                break;
-            }
-        }
+        // Create verify function and test - should fail because sender is unknown
+        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
+        assert!(
+            !verify_fn(&stranger_did, test_data, &sig_bytes),
+            "Signature from unknown sender should be rejected"
+        );
+    }
 
-        let love_tasks = love.list_tasks(&realm_id).unwrap();
-        let joy_tasks = joy.list_tasks(&realm_id).unwrap();
+    #[tokio::test]
+    async fn test_verify_fn_wrong_signature_returns_false() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        info!(
-            love_count = love_tasks.len(),
-            joy_count = joy_tasks.len(),
-            "Final task counts"
-        );
+        let (engine, _temp) = create_test_engine().await;
 
-        // Log actual task titles for debugging
-        for (i, task) in love_tasks.iter().enumerate() {
-            debug!(i, title = %task.title, "Love task");
-        }
-        for (i, task) in joy_tasks.iter().enumerate() {
-            debug!(i, title = %task.title, "Joy task");
-        }
+        // Create a contact's keypair and profile
+        let contact_keypair = HybridKeypair::generate();
+        let contact_profile = UserProfile::new("peer_joy".to_string(), "Joy".to_string());
+        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
+        let contact_did = signed_profile.did().to_string();
 
-        assert!(
-            synced,
-            "Both should have all 5 tasks. Love has {}, Joy has {}",
-            love_tasks.len(),
-            joy_tasks.len()
-        );
+        // Pin the profile
+        engine
+            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
+            .unwrap();
 
-        // Verify specific tasks exist on both
-        let love_titles: std::collections::HashSet<_> =
-            love_tasks.iter().map(|t| t.title.as_str()).collect();
-        let joy_titles: std::collections::HashSet<_> =
-            joy_tasks.iter().map(|t| t.title.as_str()).collect();
+        // Create a different keypair and sign with it (attacker trying to impersonate)
+        let attacker_keypair = HybridKeypair::generate();
+        let test_data = b"Forged message from attacker";
+        let forged_signature = attacker_keypair.sign(test_data);
+        let sig_bytes = forged_signature.to_bytes();
 
+        // Create verify function and test - should fail because signature doesn't match
+        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
         assert!(
-            love_titles.contains("Love offline task 1"),
-            "Love should have her offline task 1"
-        );
-        assert!(
-            love_titles.contains("Joy offline task 1"),
-            "Love should have Joy's offline task 1"
-        );
-        assert!(
-            joy_titles.contains("Love offline task 1"),
-            "Joy should have Love's offline task 1"
-        );
-        assert!(
-            joy_titles.contains("Joy offline task 1"),
-            "Joy should have his offline task 1"
+            !verify_fn(&contact_did, test_data, &sig_bytes),
+            "Forged signature should be rejected"
         );
-
-        // Cleanup
-        love.shutdown().await.unwrap();
-        joy.shutdown().await.unwrap();
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Private Realm Tests
-    // ═══════════════════════════════════════════════════════════════════════
-
     #[tokio::test]
-    async fn test_private_realm_auto_created() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
+    async fn test_verify_fn_tampered_message_returns_false() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Private realm should exist after engine creation
-        let realms = engine.list_realms().await.unwrap();
-        assert_eq!(realms.len(), 1, "Private realm should auto-create");
-        assert_eq!(realms[0].name, "Private");
-        assert!(!realms[0].is_shared, "Private realm should not be shared");
-    }
+        let (engine, _temp) = create_test_engine().await;
 
-    #[tokio::test]
-    async fn test_private_realm_has_onboarding_tasks() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
+        // Create a contact's keypair and profile
+        let contact_keypair = HybridKeypair::generate();
+        let contact_profile = UserProfile::new("peer_peace".to_string(), "Peace".to_string());
+        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
+        let contact_did = signed_profile.did().to_string();
 
-        // Find Private realm
-        let realms = engine.list_realms().await.unwrap();
-        let private_realm = realms.iter().find(|r| r.name == "Private").unwrap();
+        // Pin the profile
+        engine
+            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
+            .unwrap();
 
-        // Open and check tasks
-        engine.open_realm(&private_realm.id).await.unwrap();
-        let tasks = engine.list_tasks(&private_realm.id).unwrap();
+        // Sign original data
+        let original_data = b"Original message";
+        let signature = contact_keypair.sign(original_data);
+        let sig_bytes = signature.to_bytes();
 
-        // Should have sacred onboarding tasks
-        assert!(
-            tasks.len() >= 3,
-            "Private realm should have onboarding tasks"
-        );
+        // Try to verify with tampered data
+        let tampered_data = b"Tampered message";
 
-        // Verify sacred language in task titles
-        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
-        let has_sacred_language = titles.iter().any(|t| {
-            let lower = t.to_lowercase();
-            lower.contains("field")
-                || lower.contains("intention")
-                || lower.contains("manifest")
-                || lower.contains("synchronicities")
-        });
+        // Create verify function and test - should fail because data was tampered
+        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
         assert!(
-            has_sacred_language,
-            "Onboarding tasks should use sacred language"
+            !verify_fn(&contact_did, tampered_data, &sig_bytes),
+            "Signature should fail for tampered message"
         );
     }
 
     #[tokio::test]
-    async fn test_cannot_share_private_realm() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
+    async fn test_verify_fn_malformed_signature_returns_false() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Find Private realm
-        let realms = engine.list_realms().await.unwrap();
-        let private_realm = realms.iter().find(|r| r.name == "Private").unwrap();
+        let (engine, _temp) = create_test_engine().await;
 
-        // Attempt to generate invite should fail
-        let result = engine.generate_invite(&private_realm.id).await;
-        assert!(
-            result.is_err(),
-            "Should not be able to generate invite for Private realm"
-        );
+        // Create a contact's keypair and profile
+        let contact_keypair = HybridKeypair::generate();
+        let contact_profile = UserProfile::new("peer_dave".to_string(), "Dave".to_string());
+        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
+        let contact_did = signed_profile.did().to_string();
 
-        // Shutdown to avoid gossip background tasks
-        engine.shutdown().await.unwrap();
-    }
+        // Pin the profile
+        engine
+            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
+            .unwrap();
 
-    #[tokio::test]
-    async fn test_cannot_create_realm_named_private() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
+        let test_data = b"Test message";
 
-        // Attempt to create another "Private" realm should fail
-        let result = engine.create_realm("Private").await;
+        // Test with various malformed signatures
+        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
+
+        // Empty signature
         assert!(
-            result.is_err(),
-            "Should not be able to create realm named 'Private'"
+            !verify_fn(&contact_did, test_data, &[]),
+            "Empty signature should be rejected"
         );
 
-        // Case variations should also fail
-        let result = engine.create_realm("private").await;
+        // Signature too short (less than 68 bytes needed for Ed25519 + length)
         assert!(
-            result.is_err(),
-            "Should not be able to create realm named 'private'"
+            !verify_fn(&contact_did, test_data, &[0u8; 50]),
+            "Too-short signature should be rejected"
         );
 
-        let result = engine.create_realm("PRIVATE").await;
+        // Random garbage bytes (invalid signature format)
+        let garbage: Vec<u8> = (0..400).map(|i| (i % 256) as u8).collect();
         assert!(
-            result.is_err(),
-            "Should not be able to create realm named 'PRIVATE'"
+            !verify_fn(&contact_did, test_data, &garbage),
+            "Garbage signature should be rejected"
         );
     }
 
     #[tokio::test]
-    async fn test_cannot_delete_private_realm() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
-
-        // Find Private realm
-        let realms = engine.list_realms().await.unwrap();
-        let private_realm = realms.iter().find(|r| r.name == "Private").unwrap();
+    async fn test_search_contacts_ranks_partial_name_matches() {
+        use crate::invite::NodeAddrBytes;
+        use crate::types::contact::{ContactStatus, ProfileSnapshot};
 
-        // Attempt to delete should fail
-        let result = engine.delete_realm(&private_realm.id).await;
-        assert!(
-            result.is_err(),
-            "Should not be able to delete Private realm"
-        );
+        let engine = create_memory_engine().await;
+        for (i, name) in ["Bob", "Amara", "Marianne", "maría lópez", "Ana Marsh"].iter().enumerate() {
+            engine
+                .storage
+                .save_contact(&ContactInfo {
+                    peer_did: format!("did:sync:zContact{i}"),
+                    peer_endpoint_id: [i as u8; 32],
+                    profile: ProfileSnapshot {
+                        display_name: name.to_string(),
+                        subtitle: None,
+                        avatar_blob_id: None,
+                        bio: String::new(),
+                    },
+                    node_addr: NodeAddrBytes::new([i as u8; 32]),
+                    contact_topic: [1u8; 32],
+                    contact_key: [2u8; 32],
+                    accepted_at: 0,
+                    last_seen: 0,
+                    status: ContactStatus::Offline,
+                    is_favorite: false,
+                    encryption_keys: None,
+                    mutual_peers: vec![],
+                    nickname: None,
+                })
+                .unwrap();
+        }
+        let names = |query: &str| -> Vec<String> {
+            engine
+                .search_contacts(query)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.profile.display_name)
+                .collect()
+        };
 
-        // Verify it still exists
-        let realms = engine.list_realms().await.unwrap();
-        assert!(
-            realms.iter().any(|r| r.name == "Private"),
-            "Private realm should still exist after delete attempt"
-        );
+        // Prefixes first (ties by name), then later words, substrings, subsequences
+        assert_eq!(names("MAR"), ["Marianne", "maría lópez", "Ana Marsh", "Amara"]);
+        assert_eq!(names("MARÍA"), ["maría lópez"]);
+        assert_eq!(names("mlz"), ["maría lópez"]);
+        assert_eq!(names("zContact0"), ["Bob"]);
+        assert_eq!(names("Contact1"), ["Amara"]);
+        assert_eq!(names("did:sync:zContact4"), ["Ana Marsh"]);
+        assert!(names("xyz").is_empty());
+        assert_eq!(names("").len(), 5);
     }
 
     #[tokio::test]
-    async fn test_private_realm_persists_across_restarts() {
-        let temp_dir = TempDir::new().unwrap();
-
-        // First engine instance
-        {
-            let mut engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            engine.init_identity().unwrap();
+    async fn test_favorite_contacts_persist_and_sort_first() {
+        use crate::invite::NodeAddrBytes;
+        use crate::types::contact::ContactStatus;
 
-            let realms = engine.list_realms().await.unwrap();
-            assert_eq!(realms.len(), 1);
-            assert_eq!(realms[0].name, "Private");
+        let (engine, temp_dir) = create_test_engine().await;
+        for (i, name) in ["dora", "Bob", "Cleo", "alice"].iter().enumerate() {
+            engine
+                .storage
+                .save_contact(&ContactInfo {
+                    peer_did: format!("did:sync:z{name}"),
+                    peer_endpoint_id: [i as u8; 32],
+                    profile: ProfileSnapshot {
+                        display_name: name.to_string(),
+                        subtitle: None,
+                        avatar_blob_id: None,
+                        bio: String::new(),
+                    },
+                    node_addr: NodeAddrBytes::new([i as u8; 32]),
+                    contact_topic: [1u8; 32],
+                    contact_key: [2u8; 32],
+                    accepted_at: 0,
+                    last_seen: 0,
+                    status: ContactStatus::Offline,
+                    is_favorite: false,
+                    encryption_keys: None,
+                    mutual_peers: vec![],
+                    nickname: None,
+                })
+                .unwrap();
         }
+        engine.set_contact_favorite("did:sync:zdora", true).unwrap();
+        engine.set_contact_favorite("did:sync:zBob", true).unwrap();
+        engine.set_contact_favorite("did:sync:zBob", false).unwrap();
+        engine.set_contact_favorite("did:sync:zCleo", true).unwrap();
+        assert!(matches!(
+            engine.set_contact_favorite("did:sync:zNobody", true),
+            Err(SyncError::ContactNotFound(_))
+        ));
+        drop(engine);
 
-        // Second engine instance (simulating restart)
+        let engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+        let names = |favorites_first: bool| -> Vec<String> {
+            engine
+                .list_contacts(favorites_first)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.profile.display_name)
+                .collect()
+        };
+        assert_eq!(names(false), ["alice", "Bob", "Cleo", "dora"]);
+        assert_eq!(names(true), ["Cleo", "dora", "alice", "Bob"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_name_collisions_reports_shared_display_names() {
+        use crate::invite::NodeAddrBytes;
+        use crate::types::contact::ContactStatus;
+
+        let engine = create_memory_engine().await;
+        for (i, (did, name)) in [
+            ("did:sync:zSamB", "Sam"),
+            ("did:sync:zUnique", "Robin"),
+            ("did:sync:zSamA", " sam "),
+        ]
+        .iter()
+        .enumerate()
         {
-            let engine = SyncEngine::new(temp_dir.path()).await.unwrap();
-            let realms = engine.list_realms().await.unwrap();
-            assert_eq!(
-                realms.len(),
-                1,
-                "Private realm should not be duplicated on restart"
-            );
-            assert_eq!(realms[0].name, "Private");
+            engine
+                .storage
+                .save_contact(&ContactInfo {
+                    peer_did: did.to_string(),
+                    peer_endpoint_id: [i as u8; 32],
+                    profile: ProfileSnapshot {
+                        display_name: name.to_string(),
+                        subtitle: None,
+                        avatar_blob_id: None,
+                        bio: String::new(),
+                    },
+                    node_addr: NodeAddrBytes::new([i as u8; 32]),
+                    contact_topic: [1u8; 32],
+                    contact_key: [2u8; 32],
+                    accepted_at: 0,
+                    last_seen: 0,
+                    status: ContactStatus::Offline,
+                    is_favorite: false,
+                    encryption_keys: None,
+                    mutual_peers: vec![],
+                    nickname: None,
+                })
+                .unwrap();
         }
+
+        let collisions = engine.find_name_collisions().unwrap();
+        assert_eq!(collisions.len(), 1, "Robin is unique: {:?}", collisions);
+        let (name, dids) = &collisions[0];
+        assert_eq!(name, "sam");
+        let dids: Vec<&str> = dids.iter().map(|d| d.as_str()).collect();
+        assert_eq!(dids, ["did:sync:zSamA", "did:sync:zSamB"]);
     }
 
     #[tokio::test]
-    async fn test_private_realm_independent_from_identity() {
-        let (mut engine, _temp) = create_test_engine().await;
+    async fn test_contacts_bundle_round_trips_to_fresh_engine() {
+        use crate::invite::NodeAddrBytes;
+        use crate::types::contact::ContactStatus;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Private realm should exist even before identity init
-        let realms = engine.list_realms().await.unwrap();
-        assert_eq!(realms.len(), 1);
-        assert_eq!(realms[0].name, "Private");
+        let mut old_device = create_memory_engine().await;
+        old_device.init_identity().unwrap();
+        let mut new_device = create_memory_engine().await;
+        new_device.init_identity().unwrap();
 
-        // Identity init should not affect Private realm
-        engine.init_identity().unwrap();
-        let realms = engine.list_realms().await.unwrap();
-        assert_eq!(realms.len(), 1);
-        assert_eq!(realms[0].name, "Private");
+        let friend_keypair = HybridKeypair::generate();
+        let signed = SignedProfile::sign(
+            &UserProfile::new("friend".to_string(), "Friend".to_string()),
+            &friend_keypair,
+        );
+        let friend_did = signed.did().to_string();
+        let endpoint_id = iroh::SecretKey::generate(&mut rand::rng()).public();
+        old_device
+            .storage
+            .save_contact(&ContactInfo {
+                peer_did: friend_did.clone(),
+                peer_endpoint_id: *endpoint_id.as_bytes(),
+                profile: ProfileSnapshot {
+                    display_name: "Friend".to_string(),
+                    subtitle: None,
+                    avatar_blob_id: None,
+                    bio: String::new(),
+                },
+                node_addr: NodeAddrBytes::new(*endpoint_id.as_bytes()),
+                contact_topic: [1u8; 32],
+                contact_key: [2u8; 32],
+                accepted_at: 1_700_000_000,
+                last_seen: 0,
+                status: ContactStatus::Online,
+                is_favorite: true,
+                encryption_keys: Some(vec![7u8; 8]),
+                mutual_peers: vec![],
+                nickname: None,
+            })
+            .unwrap();
+        old_device
+            .pin_profile(signed, PinRelationship::Manual)
+            .unwrap();
+        let mut peer = old_device.get_peer(&endpoint_id).unwrap().unwrap_or_else(|| {
+            crate::types::peer::Peer::new(endpoint_id, crate::types::peer::PeerSource::FromContact)
+                .with_did(friend_did.clone())
+        });
+        peer.nickname = Some("Bestie".to_string());
+        old_device.save_peer(&peer).unwrap();
+
+        let json = old_device.export_contacts().unwrap().to_json().unwrap();
+        let bundle = ContactsBundle::from_json(&json).unwrap();
+        let report = new_device.import_contacts(&bundle).unwrap();
+
+        assert_eq!(report.imported, vec![friend_did.clone()]);
+        // A different identity cannot reuse the exporter's E2E keys
+        assert_eq!(report.needs_key_exchange, vec![friend_did.clone()]);
+
+        let contact = new_device.storage.load_contact(&friend_did).unwrap().unwrap();
+        assert!(contact.is_favorite);
+        assert_eq!(contact.accepted_at, 1_700_000_000);
+        assert_eq!(contact.status, ContactStatus::Offline);
+        let our_did = new_device.did().unwrap().to_string();
+        assert_eq!(
+            contact.contact_topic,
+            ContactManager::derive_contact_topic(&our_did, &friend_did)
+        );
+
+        let pin = new_device.storage.load_pinned_profile(&friend_did).unwrap().unwrap();
+        assert_eq!(pin.relationship, PinRelationship::Manual);
+        assert_eq!(pin.signed_profile.profile.display_name, "Friend");
+        let peer = new_device.get_peer_by_did(&friend_did).unwrap().unwrap();
+        assert_eq!(peer.nickname.as_deref(), Some("Bestie"));
+
+        let again = new_device.import_contacts(&bundle).unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.skipped, vec![friend_did]);
     }
 
     // ═══════════════════════════════════════════════════════════════════════
-    // Profile Pinning Tests
+    // E2E Encryption Key Lookup Tests
     // ═══════════════════════════════════════════════════════════════════════
 
     #[tokio::test]
-    async fn test_sign_and_pin_own_profile() {
-        let (mut engine, _temp) = create_test_engine().await;
+    async fn test_get_recipient_public_keys_no_contact() {
+        let (engine, _temp) = create_test_engine().await;
 
-        // Initialize identity first (required for get_own_profile and signing)
-        engine.init_identity().unwrap();
-        let did = engine.did().unwrap().to_string();
+        let unknown_did = Did::parse("did:sync:zUnknownContact123").unwrap();
+        let result = engine.get_recipient_public_keys(&unknown_did);
 
-        // Set up a profile - use the DID as peer_id
-        let mut profile = crate::types::UserProfile::new(
-            did.clone(),
-            "Test User".to_string(),
+        assert!(
+            matches!(
+                result,
+                Err(SyncError::ContactKeyExchangeIncomplete { ref did }) if *did == unknown_did.to_string()
+            ),
+            "Unknown contact should report incomplete key exchange: {:?}",
+            result.err()
         );
-        profile.subtitle = Some("Test tagline".to_string());
-        engine.save_profile(&profile).unwrap();
+    }
 
-        // Sign and pin the profile
-        let signed = engine.sign_and_pin_own_profile().unwrap();
+    #[tokio::test]
+    async fn test_get_recipient_public_keys_legacy_contact_no_keys() {
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
 
-        // Verify the signed profile
-        assert!(signed.verify());
-        assert_eq!(signed.profile.display_name, "Test User");
+        let (engine, _temp) = create_test_engine().await;
 
-        // Should be able to retrieve our own pinned profile
-        let retrieved = engine.get_own_pinned_profile().unwrap();
-        assert!(retrieved.is_some());
-        let pin = retrieved.unwrap();
-        assert!(pin.is_own());
-        assert_eq!(pin.signed_profile.profile.display_name, "Test User");
+        // Create a legacy contact without encryption keys
+        let peer_did = "did:sync:zLegacyContact456";
+        let legacy_contact = ContactInfo {
+            peer_did: peer_did.to_string(),
+            peer_endpoint_id: [0u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Legacy Contact".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([0u8; 32]),
+            contact_topic: [1u8; 32],
+            contact_key: [2u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: None, // Legacy contact - no encryption keys
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&legacy_contact).unwrap();
+
+        let did = Did::parse(peer_did).unwrap();
+        let result = engine.get_recipient_public_keys(&did);
+
+        assert!(
+            matches!(result, Err(SyncError::RecipientKeysMissing { ref did }) if did == peer_did),
+            "Legacy contact should report missing keys: {:?}",
+            result.err()
+        );
     }
 
     #[tokio::test]
-    async fn test_pin_peer_profile() {
+    async fn test_get_recipient_public_keys_with_valid_keys() {
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
+        use crate::profile::ProfileKeys;
+
         let (engine, _temp) = create_test_engine().await;
 
-        // Create a signed profile from a "remote peer"
-        let keypair = crate::identity::HybridKeypair::generate();
-        let profile = crate::types::UserProfile::new(
-            "peer_remote".to_string(),
-            "Remote User".to_string(),
-        );
-        let signed = crate::types::SignedProfile::sign(&profile, &keypair);
-        let did = signed.did().to_string();
+        // Generate real ProfileKeys to get valid encryption keys
+        let contact_profile_keys = ProfileKeys::generate();
+        let public_keys = contact_profile_keys.public_bundle();
+        let enc_keys_bytes = public_keys.to_bytes();
 
-        // Pin as a contact
-        let evicted = engine
-            .pin_profile(signed.clone(), crate::types::PinRelationship::Contact)
-            .unwrap();
-        assert!(evicted.is_empty());
+        // Create a contact with encryption keys
+        let peer_did = public_keys.did().to_string();
+        let contact = ContactInfo {
+            peer_did: peer_did.clone(),
+            peer_endpoint_id: [0u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Encrypted Contact".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([0u8; 32]),
+            contact_topic: [1u8; 32],
+            contact_key: [2u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: Some(enc_keys_bytes),
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact).unwrap();
 
-        // Should be retrievable
-        let retrieved = engine.get_pinned_profile(&did).unwrap();
-        assert!(retrieved.is_some());
-        let pin = retrieved.unwrap();
-        assert_eq!(pin.relationship, crate::types::PinRelationship::Contact);
-        assert_eq!(pin.signed_profile.profile.display_name, "Remote User");
+        let did = Did::parse(&peer_did).unwrap();
+        let result = engine.get_recipient_public_keys(&did);
+
+        assert!(result.is_ok(), "Should succeed for contact with valid keys");
+        let retrieved_keys = result.unwrap();
+
+        // Verify the retrieved keys match the original
+        assert_eq!(
+            retrieved_keys.did().to_string(),
+            public_keys.did().to_string(),
+            "Retrieved keys should have matching DID"
+        );
     }
 
     #[tokio::test]
-    async fn test_pin_profile_rejects_invalid_signature() {
+    async fn test_get_recipient_public_keys_malformed_keys() {
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
+
         let (engine, _temp) = create_test_engine().await;
 
-        // Create a signed profile and tamper with it
-        let keypair = crate::identity::HybridKeypair::generate();
-        let profile = crate::types::UserProfile::new(
-            "peer_tampered".to_string(),
-            "Tampered User".to_string(),
-        );
-        let mut signed = crate::types::SignedProfile::sign(&profile, &keypair);
+        // Create a contact with malformed encryption keys
+        // Note: DID identifier must be valid base58 (excludes 0, O, I, lowercase l)
+        let peer_did = "did:sync:zBadKeyDataContact789";
+        let contact = ContactInfo {
+            peer_did: peer_did.to_string(),
+            peer_endpoint_id: [0u8; 32],
+            profile: ProfileSnapshot {
+                display_name: "Malformed Contact".to_string(),
+                subtitle: None,
+                avatar_blob_id: None,
+                bio: String::new(),
+            },
+            node_addr: NodeAddrBytes::new([0u8; 32]),
+            contact_topic: [1u8; 32],
+            contact_key: [2u8; 32],
+            accepted_at: 0,
+            last_seen: 0,
+            status: ContactStatus::Offline,
+            is_favorite: false,
+            encryption_keys: Some(vec![0xDE, 0xAD, 0xBE, 0xEF]), // Invalid key data
+            mutual_peers: vec![],
+            nickname: None,
+        };
+        engine.storage.save_contact(&contact).unwrap();
 
-        // Tamper with the profile
-        signed.profile.display_name = "HACKED".to_string();
+        let did = Did::parse(peer_did).unwrap();
+        let result = engine.get_recipient_public_keys(&did);
 
-        // Should reject the invalid signature
-        let result = engine.pin_profile(signed, crate::types::PinRelationship::Manual);
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), SyncError::SignatureInvalid(_)));
+        assert!(result.is_err(), "Should fail for malformed keys");
+        let err_msg = result.unwrap_err().to_string();
+        assert!(
+            err_msg.contains("Failed to parse encryption keys"),
+            "Error should indicate parsing failure: {}",
+            err_msg
+        );
     }
 
     #[tokio::test]
-    async fn test_unpin_profile() {
-        let (engine, _temp) = create_test_engine().await;
+    async fn test_send_message_without_key_exchange_returns_structured_errors() {
+        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+        use crate::invite::NodeAddrBytes;
 
-        // Pin a profile
-        let keypair = crate::identity::HybridKeypair::generate();
-        let profile = crate::types::UserProfile::new(
-            "peer_unpin".to_string(),
-            "Unpin User".to_string(),
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        engine.init_profile_keys().unwrap();
+
+        // Never exchanged contacts
+        let stranger = "did:sync:zStrangerWithoutExchange";
+        let result = engine.send_message(stranger, "hello?").await;
+        assert!(
+            matches!(result, Err(SyncError::ContactKeyExchangeIncomplete { ref did }) if did == stranger),
+            "Expected ContactKeyExchangeIncomplete, got {:?}",
+            result
         );
-        let signed = crate::types::SignedProfile::sign(&profile, &keypair);
-        let did = signed.did().to_string();
 
+        // Contact accepted, but from before encryption keys were exchanged
+        let legacy = "did:sync:zLegacyContactSend";
         engine
-            .pin_profile(signed, crate::types::PinRelationship::Manual)
+            .storage
+            .save_contact(&ContactInfo {
+                peer_did: legacy.to_string(),
+                peer_endpoint_id: [0u8; 32],
+                profile: ProfileSnapshot {
+                    display_name: "Legacy".to_string(),
+                    subtitle: None,
+                    avatar_blob_id: None,
+                    bio: String::new(),
+                },
+                node_addr: NodeAddrBytes::new([0u8; 32]),
+                contact_topic: [1u8; 32],
+                contact_key: [2u8; 32],
+                accepted_at: 0,
+                last_seen: 0,
+                status: ContactStatus::Offline,
+                is_favorite: false,
+                encryption_keys: None,
+                mutual_peers: vec![],
+                nickname: None,
+            })
             .unwrap();
-
-        // Verify it's pinned
-        assert!(engine.get_pinned_profile(&did).unwrap().is_some());
-
-        // Unpin
-        engine.unpin_profile(&did).unwrap();
-
-        // Should no longer be pinned
-        assert!(engine.get_pinned_profile(&did).unwrap().is_none());
+        let result = engine.send_message(legacy, "hello?").await;
+        assert!(
+            matches!(result, Err(SyncError::RecipientKeysMissing { ref did }) if did == legacy),
+            "Expected RecipientKeysMissing, got {:?}",
+            result
+        );
     }
 
-    #[tokio::test]
-    async fn test_cannot_unpin_own_profile() {
-        let (mut engine, _temp) = create_test_engine().await;
+    /// Create two networked engines that trust each other's signatures
+    ///
+    /// Both engines get identities, pin each other's signed profiles (so sync
+    /// envelopes verify), start networking, and know each other's addresses.
+    async fn create_linked_engines() -> ((SyncEngine, TempDir), (SyncEngine, TempDir)) {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Initialize identity
-        engine.init_identity().unwrap();
-        let did = engine.did().unwrap().to_string();
+        let (mut love, love_dir) = create_test_engine().await;
+        let (mut joy, joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
 
-        // Set up and pin our own profile
-        let profile = crate::types::UserProfile::new(
-            did.clone(),
-            "Own User".to_string(),
+        let love_signed = SignedProfile::sign(
+            &UserProfile::new("love_peer".to_string(), "Love".to_string()),
+            love.identity.as_ref().unwrap(),
         );
-        engine.save_profile(&profile).unwrap();
-        let signed = engine.sign_and_pin_own_profile().unwrap();
-        let signed_did = signed.did().to_string();
+        let joy_signed = SignedProfile::sign(
+            &UserProfile::new("joy_peer".to_string(), "Joy".to_string()),
+            joy.identity.as_ref().unwrap(),
+        );
+        love.pin_profile(joy_signed, PinRelationship::Contact).unwrap();
+        joy.pin_profile(love_signed, PinRelationship::Contact).unwrap();
 
-        // Trying to unpin our own profile should fail
-        let result = engine.unpin_profile(&signed_did);
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), SyncError::InvalidOperation(_)));
+        love.start_networking().await.unwrap();
+        joy.start_networking().await.unwrap();
+        let love_addr = love.endpoint_addr().unwrap();
+        let joy_addr = joy.endpoint_addr().unwrap();
+        love.add_peer_addr(joy_addr);
+        joy.add_peer_addr(love_addr);
 
-        // Should still be pinned
-        assert!(engine.get_own_pinned_profile().unwrap().is_some());
+        ((love, love_dir), (joy, joy_dir))
     }
 
     #[tokio::test]
-    async fn test_list_pinned_profiles_by_relationship() {
+    async fn test_realm_members_unknown_realm() {
         let (engine, _temp) = create_test_engine().await;
-
-        // Pin some profiles with different relationships
-        for (suffix, relationship) in [
-            ("contact1", crate::types::PinRelationship::Contact),
-            ("contact2", crate::types::PinRelationship::Contact),
-            ("manual1", crate::types::PinRelationship::Manual),
-        ] {
-            let keypair = crate::identity::HybridKeypair::generate();
-            let profile = crate::types::UserProfile::new(
-                format!("peer_{}", suffix),
-                format!("User {}", suffix),
-            );
-            let signed = crate::types::SignedProfile::sign(&profile, &keypair);
-            engine.pin_profile(signed, relationship).unwrap();
-        }
-
-        // List by contact relationship
-        let contacts = engine
-            .list_pinned_profiles_by_relationship(&crate::types::PinRelationship::Contact)
-            .unwrap();
-        assert_eq!(contacts.len(), 2);
-
-        // List by manual relationship
-        let manual = engine
-            .list_pinned_profiles_by_relationship(&crate::types::PinRelationship::Manual)
-            .unwrap();
-        assert_eq!(manual.len(), 1);
+        let result = engine.realm_members(&RealmId::new());
+        assert!(matches!(result, Err(SyncError::RealmNotFound(_))));
     }
 
     #[tokio::test]
-    async fn test_update_pinned_profile() {
-        let (engine, _temp) = create_test_engine().await;
-
-        // Pin a profile
-        let keypair = crate::identity::HybridKeypair::generate();
-        let profile = crate::types::UserProfile::new(
-            "peer_update".to_string(),
-            "Original Name".to_string(),
-        );
-        let signed = crate::types::SignedProfile::sign(&profile, &keypair);
-        let did = signed.did().to_string();
+    async fn test_realm_members_includes_self_when_syncing() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        let realm_id = engine.create_realm("Roster").await.unwrap();
 
-        engine
-            .pin_profile(signed, crate::types::PinRelationship::Contact)
-            .unwrap();
+        // Not syncing yet: nobody observed
+        assert!(engine.realm_members(&realm_id).unwrap().is_empty());
 
-        // Create an updated profile (same keypair, different data)
-        let updated_profile = crate::types::UserProfile::new(
-            "peer_update".to_string(),
-            "Updated Name".to_string(),
-        );
-        let updated_signed = crate::types::SignedProfile::sign(&updated_profile, &keypair);
+        engine.start_sync(&realm_id).await.unwrap();
+        let members = engine.realm_members(&realm_id).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].did, engine.did().unwrap().to_string());
 
-        // Update the pin
-        let updated = engine.update_pinned_profile(&did, updated_signed).unwrap();
-        assert!(updated);
+        // Deleting the realm drops its roster
+        engine.delete_realm(&realm_id).await.unwrap();
+        assert!(engine.storage.list_realm_members(&realm_id).unwrap().is_empty());
 
-        // Verify the update
-        let retrieved = engine.get_pinned_profile(&did).unwrap().unwrap();
-        assert_eq!(retrieved.signed_profile.profile.display_name, "Updated Name");
-        // Relationship should be preserved
-        assert_eq!(retrieved.relationship, crate::types::PinRelationship::Contact);
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_pinned_profile_count() {
-        let (mut engine, _temp) = create_test_engine().await;
-
-        // Initially should be 0 (own profile doesn't count)
-        assert_eq!(engine.pinned_profile_count().unwrap(), 0);
-
-        // Initialize identity and add our own profile (shouldn't count)
-        engine.init_identity().unwrap();
-        let did = engine.did().unwrap().to_string();
-        let profile = crate::types::UserProfile::new(did.clone(), "Self".to_string());
-        engine.save_profile(&profile).unwrap();
-        engine.sign_and_pin_own_profile().unwrap();
+    async fn test_snapshot_preserves_original_tasks() {
+        let (mut engine, temp_dir) = create_test_engine().await;
+        let realm_id = engine.create_realm("Snapshot Realm").await.unwrap();
+        let keep_id = engine.add_task(&realm_id, "Water the seedlings").await.unwrap();
+        let doomed_id = engine.add_task(&realm_id, "Mend the fence").await.unwrap();
+
+        let snapshot_id = engine
+            .snapshot_realm(&realm_id, "before bulk cleanup")
+            .await
+            .unwrap();
 
-        // Still 0 (own profile excluded)
-        assert_eq!(engine.pinned_profile_count().unwrap(), 0);
+        // Mutate the live realm after the snapshot
+        engine.delete_task(&realm_id, &doomed_id).await.unwrap();
+        engine.toggle_task(&realm_id, &keep_id).await.unwrap();
+        engine.add_task(&realm_id, "Plant garlic").await.unwrap();
 
-        // Add peer profiles
-        for i in 0..3 {
-            let keypair = crate::identity::HybridKeypair::generate();
-            let profile = crate::types::UserProfile::new(
-                format!("peer_{}", i),
-                format!("User {}", i),
-            );
-            let signed = crate::types::SignedProfile::sign(&profile, &keypair);
-            engine
-                .pin_profile(signed, crate::types::PinRelationship::Manual)
-                .unwrap();
-        }
+        let view = engine.open_snapshot(&realm_id, &snapshot_id).unwrap();
+        assert_eq!(view.snapshot().label, "before bulk cleanup");
+        let tasks = view.list_tasks().unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.iter().all(|t| !t.completed));
+        assert!(view.get_task(&doomed_id).unwrap().is_some());
 
-        // Should now be 3
-        assert_eq!(engine.pinned_profile_count().unwrap(), 3);
+        // Snapshots survive a restart
+        drop(engine);
+        let engine = SyncEngine::new(temp_dir.path()).await.unwrap();
+        let snapshots = engine.list_realm_snapshots(&realm_id).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].id, snapshot_id);
+        let view = engine.open_snapshot(&realm_id, &snapshot_id).unwrap();
+        assert_eq!(view.list_tasks().unwrap().len(), 2);
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Auto-Pinning Tests
-    // ═══════════════════════════════════════════════════════════════════════
-
     #[tokio::test]
-    async fn test_process_profile_announcement_for_contact() {
-        let (engine, _temp) = create_test_engine().await;
-
-        // Create a "contact's" keypair and signed profile
-        let contact_keypair = crate::identity::HybridKeypair::generate();
-        let contact_profile = crate::types::UserProfile::new(
-            "peer_contact".to_string(),
-            "Contact User".to_string(),
-        );
-        let signed = crate::types::SignedProfile::sign(&contact_profile, &contact_keypair);
-        let contact_did = signed.did().to_string();
+    async fn test_diff_snapshots_reports_add_and_modification() {
+        let (mut engine, _temp_dir) = create_test_engine().await;
+        let realm_id = engine.create_realm("Diff Realm").await.unwrap();
+        let toggled = engine.add_task(&realm_id, "Turn the compost").await.unwrap();
+        engine.add_task(&realm_id, "Untouched").await.unwrap();
+        let before = engine.snapshot_realm(&realm_id, "before").await.unwrap();
+
+        let added = engine.add_task(&realm_id, "Sow cover crop").await.unwrap();
+        engine.toggle_task(&realm_id, &toggled).await.unwrap();
+        let after = engine.snapshot_realm(&realm_id, "after").await.unwrap();
+
+        let diff = engine.diff_snapshots(&realm_id, &before, &after).unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, added);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].id, toggled);
+        assert!(diff.modified[0].fields.iter().any(|f| f.field == "completed"
+            && f.before == serde_json::json!(false)
+            && f.after == serde_json::json!(true)));
+
+        // Reversing the order turns the addition into a removal
+        let reverse = engine.diff_snapshots(&realm_id, &after, &before).unwrap();
+        assert_eq!(reverse.removed.len(), 1);
+        assert!(reverse.added.is_empty());
+    }
 
-        // Create a fake ContactInfo and save it to storage to simulate an accepted contact
-        let contact_info = crate::types::contact::ContactInfo {
-            peer_did: contact_did.clone(),
-            peer_endpoint_id: [0u8; 32],
-            profile: crate::types::contact::ProfileSnapshot {
-                display_name: "Contact User".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: crate::invite::NodeAddrBytes {
-                node_id: [0u8; 32],
-                relay_url: None,
-                direct_addresses: vec![],
-            },
-            contact_topic: [0u8; 32],
-            contact_key: [0u8; 32],
-            accepted_at: chrono::Utc::now().timestamp(),
-            last_seen: 0,
-            status: crate::types::contact::ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: None,
-            mutual_peers: vec![],
-        };
-        engine.storage.save_contact(&contact_info).unwrap();
+    #[tokio::test]
+    async fn test_import_automerge_merges_compatible_doc() {
+        let (mut engine, _temp_dir) = create_test_engine().await;
+        let realm_id = engine.create_realm("Import Target").await.unwrap();
+        engine.add_task(&realm_id, "Existing").await.unwrap();
 
-        // Verify we should auto-pin this contact
-        assert!(engine.should_auto_pin(&contact_did));
-        assert_eq!(
-            engine.determine_pin_relationship(&contact_did),
-            Some(crate::types::PinRelationship::Contact)
-        );
+        let mut external = RealmDoc::new();
+        external.add_task("Migrated 1").unwrap();
+        external.add_task("Migrated 2").unwrap();
 
-        // Process a profile announcement
-        let announcement = crate::sync::ProfileGossipMessage::announce(signed.clone(), None);
-        let action = engine.process_profile_announcement(&announcement).unwrap();
+        let imported = engine
+            .import_automerge(&realm_id, &external.save())
+            .await
+            .unwrap();
+        assert_eq!(imported, 2);
+        assert_eq!(engine.list_tasks(&realm_id).unwrap().len(), 3);
+    }
 
-        // Should have auto-pinned
-        match action {
-            crate::sync::ProfileAction::UpdatePin { signed_profile, .. } => {
-                assert_eq!(signed_profile.profile.display_name, "Contact User");
-            }
-            _ => panic!("Expected UpdatePin action"),
-        }
+    #[tokio::test]
+    async fn test_import_automerge_rejects_incompatible_doc() {
+        use automerge::{transaction::Transactable, AutoCommit, ROOT};
 
-        // Verify the pin was created
-        let pin = engine.get_pinned_profile(&contact_did).unwrap();
-        assert!(pin.is_some());
-        let pin = pin.unwrap();
-        assert_eq!(pin.relationship, crate::types::PinRelationship::Contact);
+        let (mut engine, _temp_dir) = create_test_engine().await;
+        let realm_id = engine.create_realm("Import Target").await.unwrap();
+        engine.add_task(&realm_id, "Existing").await.unwrap();
+
+        let mut foreign = AutoCommit::new();
+        foreign.put(ROOT, "items", "something else").unwrap();
+        let result = engine.import_automerge(&realm_id, &foreign.save()).await;
+        assert!(matches!(result, Err(SyncError::IncompatibleDocument(_))));
+
+        let result = engine.import_automerge(&realm_id, b"not automerge").await;
+        assert!(matches!(result, Err(SyncError::IncompatibleDocument(_))));
+
+        // The realm is unchanged
+        let tasks = engine.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Existing");
     }
 
     #[tokio::test]
-    async fn test_process_profile_announcement_ignores_unknown_peer() {
-        let (engine, _temp) = create_test_engine().await;
+    async fn test_snapshot_errors() {
+        let (mut engine, _temp_dir) = create_test_engine().await;
 
-        // Create an unknown peer's profile
-        let unknown_keypair = crate::identity::HybridKeypair::generate();
-        let unknown_profile = crate::types::UserProfile::new(
-            "peer_unknown".to_string(),
-            "Unknown User".to_string(),
-        );
-        let signed = crate::types::SignedProfile::sign(&unknown_profile, &unknown_keypair);
-        let unknown_did = signed.did().to_string();
+        let missing = RealmId::new();
+        assert!(matches!(
+            engine.snapshot_realm(&missing, "nope").await,
+            Err(SyncError::RealmNotFound(_))
+        ));
 
-        // Verify we should NOT auto-pin this peer
-        assert!(!engine.should_auto_pin(&unknown_did));
-        assert_eq!(engine.determine_pin_relationship(&unknown_did), None);
+        let realm_id = engine.create_realm("Empty").await.unwrap();
+        assert!(matches!(
+            engine.open_snapshot(&realm_id, &SnapshotId::new()),
+            Err(SyncError::SnapshotNotFound(_))
+        ));
+    }
 
-        // Process a profile announcement
-        let announcement = crate::sync::ProfileGossipMessage::announce(signed, None);
-        let action = engine.process_profile_announcement(&announcement).unwrap();
+    #[tokio::test]
+    async fn test_leave_private_realm_rejected() {
+        let (mut engine, _temp) = create_test_engine().await;
+        let private = engine.list_realms().await.unwrap().remove(0);
 
-        // Should have been ignored
-        assert!(matches!(action, crate::sync::ProfileAction::Ignore));
+        let result = engine.leave_realm(&private.id, false).await;
+        assert!(matches!(result, Err(SyncError::PrivateRealmOperation(_))));
+    }
 
-        // Verify no pin was created
-        assert!(engine.get_pinned_profile(&unknown_did).unwrap().is_none());
+    #[tokio::test]
+    async fn test_leave_realm_keeps_read_only_copy() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        let realm_id = engine.create_realm("Leaving").await.unwrap();
+        let task_id = engine.add_task(&realm_id, "Remember me").await.unwrap();
+        engine.start_sync(&realm_id).await.unwrap();
+
+        engine.leave_realm(&realm_id, true).await.unwrap();
+
+        assert!(!engine.is_realm_syncing(&realm_id));
+        assert!(engine.realm_members(&realm_id).unwrap().is_empty());
+        let info = engine.get_realm(&realm_id).await.unwrap().unwrap();
+        assert!(info.read_only);
+        assert!(!info.is_shared);
+
+        // Tasks remain readable but the copy can no longer change or sync
+        assert_eq!(engine.list_tasks(&realm_id).unwrap().len(), 1);
+        assert!(matches!(
+            engine.add_task(&realm_id, "New").await,
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            engine.toggle_task(&realm_id, &task_id).await,
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            engine.start_sync(&realm_id).await,
+            Err(SyncError::InvalidOperation(_))
+        ));
+
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_process_profile_announcement_updates_existing_pin() {
-        let (engine, _temp) = create_test_engine().await;
+    async fn test_leave_realm_without_copy_removes_it() {
+        let (mut engine, _temp) = create_test_engine().await;
+        let realm_id = engine.create_realm("Gone").await.unwrap();
 
-        // Create a contact's keypair
-        let contact_keypair = crate::identity::HybridKeypair::generate();
-        let contact_did = crate::identity::Did::from_public_key(
-            &contact_keypair.public_key()
-        ).to_string();
+        engine.leave_realm(&realm_id, false).await.unwrap();
 
-        // Save as a contact
-        let contact_info = crate::types::contact::ContactInfo {
-            peer_did: contact_did.clone(),
-            peer_endpoint_id: [0u8; 32],
-            profile: crate::types::contact::ProfileSnapshot {
-                display_name: "Original Name".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: crate::invite::NodeAddrBytes {
-                node_id: [0u8; 32],
-                relay_url: None,
-                direct_addresses: vec![],
-            },
-            contact_topic: [0u8; 32],
-            contact_key: [0u8; 32],
-            accepted_at: chrono::Utc::now().timestamp(),
-            last_seen: 0,
-            status: crate::types::contact::ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: None,
-            mutual_peers: vec![],
+        assert!(engine.get_realm(&realm_id).await.unwrap().is_none());
+        assert!(!engine.is_realm_open(&realm_id));
+    }
+
+    #[tokio::test]
+    async fn test_leave_realm_removes_member_from_peer_roster() {
+        use std::time::Duration;
+
+        let ((mut love, _love_dir), (mut joy, _joy_dir)) = create_linked_engines().await;
+        let joy_did = joy.did().unwrap().to_string();
+
+        let realm_id = love.create_realm("Shared Departure").await.unwrap();
+        let invite = love.create_invite(&realm_id).await.unwrap();
+        joy.join_realm(&invite).await.unwrap();
+
+        let love_sees_joy = |love: &SyncEngine| {
+            love.realm_members(&realm_id)
+                .unwrap()
+                .iter()
+                .any(|m| m.did == joy_did)
         };
-        engine.storage.save_contact(&contact_info).unwrap();
 
-        // Create and process initial announcement
-        let initial_profile = crate::types::UserProfile::new(
-            contact_did.clone(),
-            "Original Name".to_string(),
-        );
-        let initial_signed = crate::types::SignedProfile::sign(&initial_profile, &contact_keypair);
-        let announcement = crate::sync::ProfileGossipMessage::announce(initial_signed, None);
-        engine.process_profile_announcement(&announcement).unwrap();
+        let mut joined = false;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            love.process_pending_sync();
+            joy.process_pending_sync();
+            if love_sees_joy(&love) {
+                joined = true;
+                break;
+            }
+        }
+        assert!(joined, "Love should observe Joy joining");
 
-        // Verify initial pin
-        let pin = engine.get_pinned_profile(&contact_did).unwrap().unwrap();
-        assert_eq!(pin.signed_profile.profile.display_name, "Original Name");
+        joy.leave_realm(&realm_id, false).await.unwrap();
+        assert!(!joy.is_realm_syncing(&realm_id));
+        assert!(joy.get_realm(&realm_id).await.unwrap().is_none());
 
-        // Create and process updated announcement
-        let updated_profile = crate::types::UserProfile::new(
-            contact_did.clone(),
-            "Updated Name".to_string(),
-        );
-        let updated_signed = crate::types::SignedProfile::sign(&updated_profile, &contact_keypair);
-        let announcement = crate::sync::ProfileGossipMessage::announce(updated_signed, None);
-        engine.process_profile_announcement(&announcement).unwrap();
+        let mut departed = false;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            love.process_pending_sync();
+            if !love_sees_joy(&love) {
+                departed = true;
+                break;
+            }
+        }
+        assert!(departed, "Joy should be removed from Love's roster after leaving");
 
-        // Verify pin was updated
-        let pin = engine.get_pinned_profile(&contact_did).unwrap().unwrap();
-        assert_eq!(pin.signed_profile.profile.display_name, "Updated Name");
-        // Relationship should be preserved
-        assert_eq!(pin.relationship, crate::types::PinRelationship::Contact);
+        // Messages Joy sent just before leaving must not bring her back
+        for _ in 0..10 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            love.process_pending_sync();
+        }
+        assert!(!love_sees_joy(&love), "Late messages should not re-add Joy");
+
+        love.shutdown().await.unwrap();
+        joy.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_get_auto_pin_interests() {
-        let (engine, _temp) = create_test_engine().await;
+    async fn test_rekey_realm_locks_out_old_key() {
+        use crate::identity::HybridKeypair;
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Initially no interests
-        let interests = engine.get_auto_pin_interests().unwrap();
-        assert!(interests.is_empty());
+        let (mut engine, _temp) = create_test_engine().await;
+        engine.init_identity().unwrap();
+        let realm_id = engine.create_realm("Rekeyed").await.unwrap();
+        let old_key = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
 
-        // Add some contacts
-        for i in 0..3 {
-            let contact_info = crate::types::contact::ContactInfo {
-                peer_did: format!("did:sync:contact{}", i),
-                peer_endpoint_id: [i as u8; 32],
-                profile: crate::types::contact::ProfileSnapshot {
-                    display_name: format!("Contact {}", i),
-                    subtitle: None,
-                    avatar_blob_id: None,
-                    bio: String::new(),
-                },
-                node_addr: crate::invite::NodeAddrBytes {
-                    node_id: [i as u8; 32],
-                    relay_url: None,
-                    direct_addresses: vec![],
-                },
-                contact_topic: [i as u8; 32],
-                contact_key: [i as u8; 32],
-                accepted_at: chrono::Utc::now().timestamp(),
-                last_seen: 0,
-                status: crate::types::contact::ContactStatus::Offline,
-                is_favorite: false,
-                encryption_keys: None,
-                mutual_peers: vec![],
+        let peer_keypair = HybridKeypair::generate();
+        let signed_profile = SignedProfile::sign(
+            &UserProfile::new("peer".to_string(), "Peer".to_string()),
+            &peer_keypair,
+        );
+        let sender_did = signed_profile.did().to_string();
+        engine
+            .pin_profile(signed_profile, PinRelationship::Contact)
+            .unwrap();
+
+        let seal_with = |key: &[u8; 32]| {
+            let message = SyncMessage::Announce {
+                realm_id: realm_id.clone(),
+                heads: vec![],
+                sender_addr: None,
+                sent_at_ms: chrono::Utc::now().timestamp_millis(),
             };
-            engine.storage.save_contact(&contact_info).unwrap();
-        }
+            let sign_fn = |data: &[u8]| peer_keypair.sign(data).to_bytes();
+            SyncEnvelope::seal(&message, &sender_did, key, sign_fn)
+                .unwrap()
+                .to_bytes()
+                .unwrap()
+        };
 
-        // Should now have 3 interests
-        let interests = engine.get_auto_pin_interests().unwrap();
-        assert_eq!(interests.len(), 3);
-        assert!(interests.contains(&"did:sync:contact0".to_string()));
-        assert!(interests.contains(&"did:sync:contact1".to_string()));
-        assert!(interests.contains(&"did:sync:contact2".to_string()));
-    }
+        // The departing peer is left off the new key and the roster
+        engine.storage.record_realm_member(&realm_id, &sender_did, 100).unwrap();
+        let departing: Did = sender_did.parse().unwrap();
+        let outcome = engine.rekey_realm(&realm_id, &[departing]).await.unwrap();
+        assert_eq!(outcome.epoch, 1);
+        assert!(outcome.delivered.is_empty());
+        assert!(outcome.undelivered.is_empty());
+        assert!(engine.storage.list_realm_members(&realm_id).unwrap().is_empty());
+
+        let new_key = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        assert_ne!(new_key, old_key);
+        let info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        assert_eq!(info.key_epoch, 1);
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Startup Sync Tests
-    // ═══════════════════════════════════════════════════════════════════════
+        assert!(engine.handle_incoming(&realm_id, &seal_with(&old_key)).unwrap().is_none());
+        assert!(engine.handle_incoming(&realm_id, &seal_with(&new_key)).unwrap().is_some());
+
+        let realms = engine.list_realms().await.unwrap();
+        let private = realms.iter().find(|r| r.name == PRIVATE_REALM_NAME).unwrap();
+        assert!(matches!(
+            engine.rekey_realm(&private.id, &[]).await,
+            Err(SyncError::PrivateRealmOperation(_))
+        ));
+
+        // Only owners rekey
+        let mut info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        info.owner = Some(sender_did.clone());
+        engine.storage.save_realm(&info).unwrap();
+        assert!(matches!(
+            engine.rekey_realm(&realm_id, &[]).await,
+            Err(SyncError::InvalidOperation(_))
+        ));
+    }
 
     #[tokio::test]
-    async fn test_startup_sync_with_no_peers() {
+    async fn test_apply_realm_rekey_requires_owner_and_newer_epoch() {
         let (mut engine, _temp) = create_test_engine().await;
+        let realm_id = engine.create_realm("Followed").await.unwrap();
+        let original = engine.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        let owner = "did:sync:owner";
+
+        // A member is held back until they are known to own the realm
+        engine.storage.record_realm_member(&realm_id, owner, 100).unwrap();
+        let result = engine.apply_realm_rekey(owner, &realm_id, [1u8; 32], 1).unwrap();
+        assert_eq!(result, None);
+        assert_eq!(engine.storage.load_realm_key(&realm_id).unwrap(), Some(original));
+
+        // As if we had joined with an invite naming them as owner
+        let mut info = engine.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        info.owner = Some(owner.to_string());
+        engine.storage.save_realm(&info).unwrap();
+        let result = engine.apply_realm_rekey(owner, &realm_id, [1u8; 32], 1).unwrap();
+        assert_eq!(result, Some(true));
+        assert_eq!(engine.realms[&realm_id].realm_key, [1u8; 32]);
+
+        // A replayed older key is ignored
+        let result = engine.apply_realm_rekey(owner, &realm_id, [2u8; 32], 0).unwrap();
+        assert_eq!(result, Some(false));
+        assert_eq!(engine.storage.load_realm_key(&realm_id).unwrap(), Some([1u8; 32]));
+
+        // Rival keys for one epoch settle on the same winner in either order
+        let rival = [2u8; 32];
+        let rival_wins = blake3::hash(&rival).as_bytes() < blake3::hash(&[1u8; 32]).as_bytes();
+        let (winner, loser) = if rival_wins { (rival, [1u8; 32]) } else { ([1u8; 32], rival) };
+        let result = engine.apply_realm_rekey(owner, &realm_id, rival, 1).unwrap();
+        assert_eq!(result, Some(rival_wins));
+        let result = engine.apply_realm_rekey(owner, &realm_id, loser, 1).unwrap();
+        assert_eq!(result, Some(false));
+        assert_eq!(engine.realms[&realm_id].realm_key, winner);
+    }
 
-        // Initialize identity for the engine
-        engine.init_identity().unwrap();
+    #[tokio::test]
+    async fn test_viewer_changes_rejected_by_peers_while_editor_changes_apply() {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Startup sync with no peers should complete immediately after jitter
-        let result = engine.startup_sync().await.unwrap();
+        // Seal a message as `from` and hand it to `to` as if it came over gossip
+        fn deliver(from: &SyncEngine, to: &mut SyncEngine, realm_id: &RealmId, message: SyncMessage) {
+            let envelope_bytes = from
+                .seal_realm_message(realm_id, &message)
+                .unwrap()
+                .to_bytes()
+                .unwrap();
+            to.sync_tx
+                .send(SyncChannelMessage::IncomingData {
+                    realm_id: realm_id.clone(),
+                    envelope_bytes,
+                })
+                .unwrap();
+            to.process_pending_sync();
+        }
 
-        // No peers to connect to
-        assert_eq!(result.peers_attempted, 0);
-        assert_eq!(result.peers_succeeded, 0);
-        assert_eq!(result.peers_skipped_backoff, 0);
-        // Jitter should be in range 0-30000 ms
-        assert!(result.jitter_delay_ms < 30_000);
-    }
+        fn full_document(engine: &mut SyncEngine, realm_id: &RealmId) -> SyncMessage {
+            SyncMessage::SyncResponse {
+                realm_id: realm_id.clone(),
+                document: engine.realms.get_mut(realm_id).unwrap().doc.save(),
+            }
+        }
+
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        let (mut peace, _peace_dir) = create_test_engine().await;
+        let mut signed = Vec::new();
+        for (engine, name) in [(&mut love, "Love"), (&mut joy, "Joy"), (&mut peace, "Peace")] {
+            engine.init_identity().unwrap();
+            let profile = UserProfile::new(name.to_lowercase(), name.to_string());
+            signed.push(SignedProfile::sign(&profile, engine.identity.as_ref().unwrap()));
+        }
+        for engine in [&mut love, &mut joy, &mut peace] {
+            for profile in &signed {
+                engine.pin_profile(profile.clone(), PinRelationship::Contact).unwrap();
+            }
+        }
+        let joy_did = joy.did().unwrap();
+        let peace_did = peace.did().unwrap();
+
+        // Love creates the realm; Joy and Peace hold replicas of it
+        let realm_id = love.create_realm("Garden Plot").await.unwrap();
+        let mut info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        let key = love.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        let doc = love.storage.load_document(&realm_id).unwrap().unwrap();
+        for replica in [&mut joy, &mut peace] {
+            replica.storage.save_realm(&info).unwrap();
+            replica.storage.save_realm_key(&realm_id, &key).unwrap();
+            replica.storage.save_document(&realm_id, &doc).unwrap();
+            replica.open_realm(&realm_id).await.unwrap();
+        }
 
-    #[tokio::test]
-    async fn test_startup_sync_result_default() {
-        let result = StartupSyncResult::default();
-        assert_eq!(result.peers_attempted, 0);
-        assert_eq!(result.peers_succeeded, 0);
-        assert_eq!(result.peers_skipped_backoff, 0);
-        assert_eq!(result.profiles_updated, 0);
-        assert_eq!(result.jitter_delay_ms, 0);
-    }
+        // Only owners assign roles; before any exist, that is the creator
+        assert!(matches!(
+            joy.set_member_role(&realm_id, &peace_did, RealmRole::Owner).await,
+            Err(SyncError::InvalidOperation(_))
+        ));
+        love.set_member_role(&realm_id, &joy_did, RealmRole::Editor).await.unwrap();
+        love.set_member_role(&realm_id, &peace_did, RealmRole::Viewer).await.unwrap();
+        let roles = love.realm_roles(&realm_id).unwrap().unwrap();
+        assert_eq!(roles.version, 2);
+        assert!(roles.is_owner(love.did().unwrap().as_ref()));
 
-    #[tokio::test]
-    async fn test_startup_sync_respects_backoff() {
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
+        deliver(&love, &mut joy, &realm_id, SyncMessage::Roles {
+            realm_id: realm_id.clone(),
+            roles: roles.clone(),
+        });
+        assert_eq!(joy.realm_roles(&realm_id).unwrap(), Some(roles.clone()));
 
-        // Add a peer with many failed attempts (should be in backoff)
-        let mut peer = crate::types::peer::Peer::new(
-            iroh::SecretKey::generate(&mut rand::rng()).public(),
-            crate::types::peer::PeerSource::FromInvite,
-        );
-        // Simulate many failures
-        peer.connection_attempts = 5;
-        peer.successful_connections = 0;
-        // Set last_attempt to now (still in backoff period)
-        peer.last_attempt = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
+        // A table Peace signs for herself is refused: she is not an owner
+        let mut forged = RealmRoles {
+            version: 99,
+            signer: peace_did.to_string(),
+            ..roles.clone()
+        };
+        forged.roles.insert(peace_did.to_string(), RealmRole::Owner);
+        forged.signature = peace
+            .identity
+            .as_ref()
             .unwrap()
-            .as_secs();
-        engine.storage.save_peer(&peer).unwrap();
+            .sign(&forged.signing_bytes())
+            .to_bytes();
+        deliver(&peace, &mut joy, &realm_id, SyncMessage::Roles {
+            realm_id: realm_id.clone(),
+            roles: forged,
+        });
+        assert_eq!(joy.member_role(&realm_id, peace_did.as_ref()).unwrap(), RealmRole::Viewer);
 
-        // Run startup sync
-        let result = engine.startup_sync().await.unwrap();
+        // Peace hasn't heard about her role yet, so she edits locally
+        joy.add_task(&realm_id, "Plant beans").await.unwrap();
+        peace.add_task(&realm_id, "Pick tomatoes").await.unwrap();
 
-        // Peer should be skipped due to backoff
-        assert_eq!(result.peers_attempted, 0);
-        assert_eq!(result.peers_skipped_backoff, 1);
-    }
+        let message = full_document(&mut peace, &realm_id);
+        deliver(&peace, &mut love, &realm_id, message);
+        let message = full_document(&mut joy, &realm_id);
+        deliver(&joy, &mut love, &realm_id, message);
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Chat/Messaging Integration Tests
-    // ═══════════════════════════════════════════════════════════════════════
+        let titles: Vec<_> = love
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Plant beans".to_string()]);
+
+        // Peace's change is refused even when Joy, an editor, relays it
+        let mut peace_doc = RealmDoc::load(&peace.realms.get_mut(&realm_id).unwrap().doc.save()).unwrap();
+        joy.realms.get_mut(&realm_id).unwrap().doc.merge(&mut peace_doc).unwrap();
+        let message = full_document(&mut joy, &realm_id);
+        deliver(&joy, &mut love, &realm_id, message);
+        let titles: Vec<_> = love
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Plant beans".to_string()]);
 
-    /// Test that packets created with create_packet appear in get_conversation().
-    ///
-    /// This is the core regression test for the "sent messages don't appear in sender's chat" bug.
-    /// It tests the fundamental flow WITHOUT network dependencies:
-    ///
-    /// 1. create_packet() → stores DirectMessage packet in profile_log
-    /// 2. get_conversation() → loads sent packets from profile_log, filters by recipient
-    /// 3. The sent message should appear with is_mine=true
-    ///
-    /// If this test FAILS: the bug is in the core library (packet storage or conversation loading)
-    /// If this test PASSES: the bug is in either:
-    ///   - The UI layer (Dioxus state management), OR
-    ///   - Network-related code paths (contact manager, broadcast)
-    ///
-    /// Note: This test sets up a contact with encryption keys to enable E2E encryption.
-    #[tokio::test]
-    async fn test_created_packet_appears_in_conversation() {
-        use crate::profile::{PacketAddress, PacketPayload, ProfileKeys};
-        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
-        use crate::invite::NodeAddrBytes;
+        // Peace only takes a first table signed by the owner she was invited by
+        let mut usurped = RealmRoles {
+            version: u64::MAX,
+            signer: joy_did.to_string(),
+            ..roles.clone()
+        };
+        usurped.roles.insert(joy_did.to_string(), RealmRole::Owner);
+        usurped.signature = joy
+            .identity
+            .as_ref()
+            .unwrap()
+            .sign(&usurped.signing_bytes())
+            .to_bytes();
+        deliver(&joy, &mut peace, &realm_id, SyncMessage::Roles {
+            realm_id: realm_id.clone(),
+            roles: usurped,
+        });
+        assert_eq!(peace.realm_roles(&realm_id).unwrap(), None);
 
-        let (mut engine, _temp) = create_test_engine().await;
+        // Once Peace learns the table, her own node refuses the edit up front
+        deliver(&love, &mut peace, &realm_id, SyncMessage::Roles {
+            realm_id: realm_id.clone(),
+            roles: roles.clone(),
+        });
+        assert_eq!(peace.realm_roles(&realm_id).unwrap(), Some(roles));
+        assert!(matches!(
+            peace.add_task(&realm_id, "Water squash").await,
+            Err(SyncError::InvalidOperation(_))
+        ));
+    }
 
-        // Initialize identity and profile keys (required for create_packet)
-        engine.init_identity().unwrap();
-        engine.init_profile_keys().unwrap();
+    #[tokio::test]
+    async fn test_announce_timestamps_estimate_peer_clock_skew() {
+        use crate::types::peer::{Peer, PeerSource};
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        // Generate ProfileKeys for the contact to enable E2E encryption
-        let contact_profile_keys = ProfileKeys::generate();
-        let contact_pubkeys = contact_profile_keys.public_bundle();
-        let contact_did_str = contact_pubkeys.did().to_string();
+        // Announce from `from`, stamped by a clock running `offset_ms` off real time
+        fn announce_with_offset(from: &SyncEngine, to: &mut SyncEngine, realm_id: &RealmId, offset_ms: i64) {
+            let message = SyncMessage::Announce {
+                realm_id: realm_id.clone(),
+                heads: vec![],
+                sender_addr: None,
+                sent_at_ms: chrono::Utc::now().timestamp_millis() + offset_ms,
+            };
+            let envelope_bytes = from
+                .seal_realm_message(realm_id, &message)
+                .unwrap()
+                .to_bytes()
+                .unwrap();
+            to.sync_tx
+                .send(SyncChannelMessage::IncomingData {
+                    realm_id: realm_id.clone(),
+                    envelope_bytes,
+                })
+                .unwrap();
+            to.process_pending_sync();
+        }
 
-        // Set up the contact with encryption keys
-        let contact = ContactInfo {
-            peer_did: contact_did_str.clone(),
-            peer_endpoint_id: [0u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Test Contact".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([0u8; 32]),
-            contact_topic: [1u8; 32],
-            contact_key: [2u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: Some(contact_pubkeys.to_bytes()),
-            mutual_peers: vec![],
-        };
-        engine.storage.save_contact(&contact).unwrap();
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        let (mut peace, _peace_dir) = create_test_engine().await;
+        let mut signed = Vec::new();
+        for (engine, name) in [(&mut love, "Love"), (&mut joy, "Joy"), (&mut peace, "Peace")] {
+            engine.init_identity().unwrap();
+            let profile = UserProfile::new(name.to_lowercase(), name.to_string());
+            signed.push(SignedProfile::sign(&profile, engine.identity.as_ref().unwrap()));
+        }
+        for profile in &signed {
+            love.pin_profile(profile.clone(), PinRelationship::Contact).unwrap();
+        }
+        let joy_did = joy.did().unwrap().to_string();
+        let peace_did = peace.did().unwrap().to_string();
+
+        let realm_id = love.create_realm("Sundial").await.unwrap();
+        let mut info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        let key = love.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        let doc = love.storage.load_document(&realm_id).unwrap().unwrap();
+        for replica in [&mut joy, &mut peace] {
+            replica.storage.save_realm(&info).unwrap();
+            replica.storage.save_realm_key(&realm_id, &key).unwrap();
+            replica.storage.save_document(&realm_id, &doc).unwrap();
+            replica.open_realm(&realm_id).await.unwrap();
+        }
+        for did in [&joy_did, &peace_did] {
+            let endpoint_id = iroh::SecretKey::generate(&mut rand::rng()).public();
+            let peer = Peer::new(endpoint_id, PeerSource::FromRealm(realm_id.clone())).with_did(did);
+            love.storage.save_peer(&peer).unwrap();
+        }
+        let mut events = love.subscribe_events();
+
+        // Joy's clock runs 90s fast, Peace's 1.5s slow
+        announce_with_offset(&joy, &mut love, &realm_id, 90_000);
+        announce_with_offset(&peace, &mut love, &realm_id, -1_500);
+
+        let joy_skew = love.get_peer_by_did(&joy_did).unwrap().unwrap().clock_skew_ms.unwrap();
+        assert!((joy_skew - 90_000).abs() < 1_000, "estimated {joy_skew}ms");
+        let peace_skew = love.get_peer_by_did(&peace_did).unwrap().unwrap().clock_skew_ms.unwrap();
+        assert!((peace_skew + 1_500).abs() < 1_000, "estimated {peace_skew}ms");
+
+        // Joy stays skewed, but the warning is only sent when crossing the threshold
+        announce_with_offset(&joy, &mut love, &realm_id, 90_000);
+        let warnings: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter_map(|e| match e {
+                SyncEvent::ClockSkewWarning { peer_did, skew_ms, .. } => Some((peer_did, skew_ms)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].0, joy_did);
+        assert!(warnings[0].1 > CLOCK_SKEW_WARNING_MS);
+    }
 
-        let contact_did = Did::parse(&contact_did_str).unwrap();
+    #[tokio::test]
+    async fn test_realm_heads_match_after_merge_and_differ_when_diverged() {
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
 
-        // Create an E2E encrypted DirectMessage payload
-        let content = "Hello from integration test!";
-        let payload = PacketPayload::DirectMessage {
-            content: content.to_string(),
-            recipient: contact_did.clone(),
-        };
-        let address = PacketAddress::Individual(contact_did);
+        let realm_id = love.create_realm("Replicated").await.unwrap();
+        love.add_task(&realm_id, "Shared start").await.unwrap();
 
-        // Create the packet (stores it in our log with E2E encryption)
-        let seq_result = engine.create_packet(payload, address);
-        assert!(
-            seq_result.is_ok(),
-            "create_packet should succeed: {:?}",
-            seq_result.err()
-        );
-        let seq = seq_result.unwrap();
-        println!("Created E2E encrypted packet with sequence: {}", seq);
+        // Give Joy an identical replica of the realm
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
 
-        // Verify profile_log has the packet
-        let log_count = engine
-            .profile_log
-            .as_ref()
-            .map(|log| log.entries_ordered().len())
-            .unwrap_or(0);
-        println!("profile_log has {} entries", log_count);
-        assert!(
-            log_count > 0,
-            "profile_log should have at least 1 entry after create_packet"
+        assert_eq!(
+            love.realm_heads(&realm_id).await.unwrap(),
+            joy.realm_heads(&realm_id).await.unwrap()
         );
 
-        // Get the conversation - THIS IS THE KEY TEST
-        let conversation_result = engine.get_conversation(&contact_did_str);
-        assert!(
-            conversation_result.is_ok(),
-            "get_conversation should succeed: {:?}",
-            conversation_result.err()
+        // Concurrent edits diverge the replicas
+        love.add_task(&realm_id, "Love's idea").await.unwrap();
+        joy.add_task(&realm_id, "Joy's idea").await.unwrap();
+        assert_ne!(
+            love.realm_heads(&realm_id).await.unwrap(),
+            joy.realm_heads(&realm_id).await.unwrap()
         );
 
-        let conversation = conversation_result.unwrap();
-        println!("Conversation has {} messages", conversation.len());
+        // Merging each other's changes converges them again
+        let love_bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        let joy_bytes = joy.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.import_automerge(&realm_id, &love_bytes).await.unwrap();
+        love.import_automerge(&realm_id, &joy_bytes).await.unwrap();
 
-        // THE KEY ASSERTION: sent message should appear
+        let heads = love.realm_heads(&realm_id).await.unwrap();
+        assert_eq!(heads.len(), 2, "Both concurrent changes should be heads");
+        assert_eq!(heads, joy.realm_heads(&realm_id).await.unwrap());
         assert_eq!(
-            conversation.len(),
-            1,
-            "Conversation should have exactly 1 message (the created one)"
+            love.realm_change_count(&realm_id).await.unwrap(),
+            joy.realm_change_count(&realm_id).await.unwrap()
         );
 
-        // Verify message properties
-        let msg = conversation
-            .messages()
-            .first()
-            .expect("Should have a message");
-        assert_eq!(msg.content, content, "Message content should match");
-        assert!(msg.is_mine, "Message should be marked as is_mine=true");
-        println!(
-            "SUCCESS: Message content='{}', is_mine={}",
-            msg.content, msg.is_mine
-        );
+        assert!(matches!(
+            love.realm_heads(&RealmId::new()).await,
+            Err(SyncError::RealmNotFound(_))
+        ));
     }
 
-    /// Test that multiple messages to the same contact all appear in conversation.
-    ///
-    /// This catches potential issues with sequence handling or duplicate filtering.
-    /// Note: This test sets up a contact with encryption keys to enable E2E encryption.
     #[tokio::test]
-    async fn test_multiple_messages_appear_in_conversation() {
-        use crate::profile::{PacketAddress, PacketPayload, ProfileKeys};
-        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
-        use crate::invite::NodeAddrBytes;
-
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
-        engine.init_profile_keys().unwrap();
-
-        // Generate ProfileKeys for the contact to enable E2E encryption
-        let contact_profile_keys = ProfileKeys::generate();
-        let contact_pubkeys = contact_profile_keys.public_bundle();
-        let contact_did_str = contact_pubkeys.did().to_string();
+    async fn test_compact_realm_history_after_members_converge() {
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        let (mut sam, _sam_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let joy_did = joy.did().unwrap().to_string();
+
+        let realm_id = love.create_realm("Orchard").await.unwrap();
+        let prune = love.add_task(&realm_id, "Prune apples").await.unwrap();
+        let scrap = love.add_task(&realm_id, "Scrapped idea").await.unwrap();
+        let mulch = love.add_task(&realm_id, "Mulch pears").await.unwrap();
+        love.toggle_task(&realm_id, &prune).await.unwrap();
+        love.rename_task(&realm_id, &prune, "Prune the apples").await.unwrap();
+        love.delete_task(&realm_id, &scrap).await.unwrap();
+
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
+        love.note_realm_member(&realm_id, &joy_did);
+        let announce = |heads: Vec<ChangeHash>| -> Vec<Vec<u8>> {
+            heads.iter().map(|h| h.0.to_vec()).collect()
+        };
 
-        // Set up the contact with encryption keys
-        let contact = ContactInfo {
-            peer_did: contact_did_str.clone(),
-            peer_endpoint_id: [0u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Test Contact".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([0u8; 32]),
-            contact_topic: [1u8; 32],
-            contact_key: [2u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: Some(contact_pubkeys.to_bytes()),
-            mutual_peers: vec![],
+        // Joy hasn't announced anything yet, then announces heads we've moved past
+        let unconverged = RealmCompaction::Unconverged {
+            members: vec![joy_did.clone()],
         };
-        engine.storage.save_contact(&contact).unwrap();
+        assert_eq!(love.compact_realm_history(&realm_id).await.unwrap(), unconverged);
+        let stale = joy.realm_heads(&realm_id).await.unwrap();
+        love.add_task(&realm_id, "Net the cherries").await.unwrap();
+        love.note_member_heads(&realm_id, &joy_did, &announce(stale));
+        assert_eq!(love.compact_realm_history(&realm_id).await.unwrap(), unconverged);
+
+        // Once Joy has caught up, the history can go
+        let love_bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.apply_full_document(&realm_id, &love_bytes).await.unwrap();
+        let current = joy.realm_heads(&realm_id).await.unwrap();
+        love.note_member_heads(&realm_id, &joy_did, &announce(current));
+
+        let tasks_before = love.list_tasks(&realm_id).unwrap();
+        let changes_before = love.realm_change_count(&realm_id).await.unwrap();
+        let compaction = love.compact_realm_history(&realm_id).await.unwrap();
+        let RealmCompaction::Compacted {
+            changes_before: reported,
+            ..
+        } = compaction
+        else {
+            panic!("expected compaction, got {:?}", compaction);
+        };
+        assert_eq!(reported, changes_before);
+        assert_eq!(love.realm_change_count(&realm_id).await.unwrap(), 1);
+        assert_eq!(love.list_tasks(&realm_id).unwrap(), tasks_before);
+
+        // A fresh peer syncs the compacted realm as gossip delivers it, and
+        // its edits come back
+        let compacted = love.storage.load_document(&realm_id).unwrap().unwrap();
+        sam.storage.save_realm(&info).unwrap();
+        sam.open_realm(&realm_id).await.unwrap();
+        sam.apply_sync_changes(&realm_id, &compacted, true).unwrap();
+        assert_eq!(sam.list_tasks(&realm_id).unwrap(), tasks_before);
+        let from_sam = sam.add_task(&realm_id, "Count the hives").await.unwrap();
+        let sam_bytes = sam.storage.load_document(&realm_id).unwrap().unwrap();
+        love.apply_full_document(&realm_id, &sam_bytes).await.unwrap();
+        assert!(love.get_task(&realm_id, &from_sam).unwrap().is_some());
+
+        // Joy still has the full history and kept editing, including a task
+        // Love edited after compacting; Joy rebases onto the compacted
+        // document without losing either edit
+        love.rename_task(&realm_id, &mulch, "Mulch the pears").await.unwrap();
+        let from_joy = joy.add_task(&realm_id, "Fix the ladder").await.unwrap();
+        joy.toggle_task(&realm_id, &mulch).await.unwrap();
+        let love_bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.apply_full_document(&realm_id, &love_bytes).await.unwrap();
+        assert!(joy.realm_change_count(&realm_id).await.unwrap() < changes_before);
+        let joy_bytes = joy.storage.load_document(&realm_id).unwrap().unwrap();
+        love.apply_full_document(&realm_id, &joy_bytes).await.unwrap();
 
-        let contact_did = Did::parse(&contact_did_str).unwrap();
+        assert_eq!(
+            love.realm_heads(&realm_id).await.unwrap(),
+            joy.realm_heads(&realm_id).await.unwrap()
+        );
+        let tasks = love.list_tasks(&realm_id).unwrap();
+        assert_eq!(tasks.len(), tasks_before.len() + 2);
+        assert!(tasks.iter().any(|t| t.id == from_joy));
+        let mulch = love.get_task(&realm_id, &mulch).unwrap().unwrap();
+        assert_eq!(mulch.title, "Mulch the pears");
+        assert!(mulch.completed);
+        assert_eq!(tasks, joy.list_tasks(&realm_id).unwrap());
+    }
 
-        // Send multiple E2E encrypted messages
-        let messages = ["First message", "Second message", "Third message"];
-        for msg in &messages {
-            let payload = PacketPayload::DirectMessage {
-                content: msg.to_string(),
-                recipient: contact_did.clone(),
-            };
-            let address = PacketAddress::Individual(contact_did.clone());
-            engine.create_packet(payload, address).unwrap();
-        }
+    #[tokio::test]
+    async fn test_compaction_waits_for_role_members_and_rebases_silent_replicas() {
+        use crate::types::TaskActivityKind;
 
-        // Verify all messages appear
-        let conversation = engine.get_conversation(&contact_did_str).unwrap();
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        let (mut sam, _sam_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        sam.init_identity().unwrap();
+        let sam_did = sam.did().unwrap();
+
+        let realm_id = love.create_realm("Orchard").await.unwrap();
+        let prune = love.add_task(&realm_id, "Prune apples").await.unwrap();
+        love.toggle_task(&realm_id, &prune).await.unwrap();
+
+        // Joy holds a replica but has never announced herself
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
+        love.add_task(&realm_id, "Net the cherries").await.unwrap();
+
+        // A member named in the role table counts even if never heard from
+        love.set_member_role(&realm_id, &sam_did, RealmRole::Editor).await.unwrap();
         assert_eq!(
-            conversation.len(),
-            3,
-            "Conversation should have all 3 messages"
+            love.compact_realm_history(&realm_id).await.unwrap(),
+            RealmCompaction::Unconverged {
+                members: vec![sam_did.to_string()],
+            }
+        );
+        let heads: Vec<Vec<u8>> = love
+            .realm_heads(&realm_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|h| h.0.to_vec())
+            .collect();
+        love.note_member_heads(&realm_id, sam_did.as_ref(), &heads);
+        assert!(matches!(
+            love.compact_realm_history(&realm_id).await.unwrap(),
+            RealmCompaction::Compacted { .. }
+        ));
+
+        // Joy kept editing and can't follow the compacted document yet
+        let ladder = joy.add_task(&realm_id, "Fix the ladder").await.unwrap();
+        let compacted = love.storage.load_document(&realm_id).unwrap().unwrap();
+        assert!(matches!(
+            joy.apply_full_document(&realm_id, &compacted).await,
+            Err(SyncError::IncompatibleDocument(_))
+        ));
+
+        // Love rebases Joy's copy from the archived history; Joy then adopts
+        let joy_bytes = joy.storage.load_document(&realm_id).unwrap().unwrap();
+        assert!(love.apply_sync_response(&realm_id, &joy_bytes).unwrap());
+        assert!(love.get_task(&realm_id, &ladder).unwrap().is_some());
+        let love_bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.apply_full_document(&realm_id, &love_bytes).await.unwrap();
+        assert_eq!(
+            joy.realm_heads(&realm_id).await.unwrap(),
+            love.realm_heads(&realm_id).await.unwrap()
         );
+        assert_eq!(joy.list_tasks(&realm_id).unwrap().len(), 3);
 
-        // Verify each message content (they should be sorted by timestamp/sequence)
-        let conv_messages = conversation.messages();
-        for (i, msg) in conv_messages.iter().enumerate() {
-            assert_eq!(msg.content, messages[i], "Message {} content should match", i);
-            assert!(msg.is_mine, "Message {} should be marked as is_mine=true", i);
+        // Activity from before the compaction is still listed on both sides
+        for engine in [&mut love, &mut joy] {
+            let kinds: Vec<_> = engine
+                .task_history(&realm_id, &prune)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|activity| activity.kind)
+                .collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    TaskActivityKind::Created {
+                        title: "Prune apples".to_string()
+                    },
+                    TaskActivityKind::Completed,
+                ]
+            );
         }
     }
 
-    /// Test that messages to different contacts are properly separated.
-    ///
-    /// This ensures the recipient filtering in get_conversation works correctly.
-    /// Note: This test sets up contacts with encryption keys to enable E2E encryption.
     #[tokio::test]
-    async fn test_messages_separated_by_contact() {
-        use crate::profile::{PacketAddress, PacketPayload, ProfileKeys};
-        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
-        use crate::invite::NodeAddrBytes;
+    async fn test_realm_metadata_persists_and_reaches_members() {
+        let love_dir = TempDir::new().unwrap();
+        let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+        let (mut joy, _joy_dir) = create_test_engine().await;
+
+        let realm_id = love.create_realm("Garden").await.unwrap();
+
+        // Give Joy a replica from before the metadata was set
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        let (mut engine, _temp) = create_test_engine().await;
-        engine.init_identity().unwrap();
-        engine.init_profile_keys().unwrap();
+        let metadata = RealmMetadata {
+            description: Some("Beds, compost and the shared shed".to_string()),
+            cover_blob_id: Some("ab".repeat(32)),
+            color: Some("#2f855a".to_string()),
+            emoji: Some("🌻".to_string()),
+        };
+        love.set_realm_metadata(&realm_id, metadata.clone())
+            .await
+            .unwrap();
+        let love_info = love.get_realm(&realm_id).await.unwrap().unwrap();
+        assert_eq!(love_info.metadata, metadata);
+        assert_eq!(love_info.name, "Garden");
 
-        // Generate profile keys for contacts to enable E2E encryption
-        let contact1_keys = ProfileKeys::generate();
-        let contact1_pubkeys = contact1_keys.public_bundle();
-        let contact1_did = contact1_pubkeys.did().to_string();
+        // Joy picks it up from the synced document
+        let love_bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.apply_full_document(&realm_id, &love_bytes)
+            .await
+            .unwrap();
+        let joy_info = joy.get_realm(&realm_id).await.unwrap().unwrap();
+        assert_eq!(joy_info.metadata, metadata);
 
-        let contact2_keys = ProfileKeys::generate();
-        let contact2_pubkeys = contact2_keys.public_bundle();
-        let contact2_did = contact2_pubkeys.did().to_string();
+        // And it survives a restart
+        love.shutdown().await.unwrap();
+        let love = SyncEngine::new(love_dir.path()).await.unwrap();
+        let reloaded = love.get_realm(&realm_id).await.unwrap().unwrap();
+        assert_eq!(reloaded.metadata, metadata);
+    }
 
-        // Create contacts with encryption keys
-        let contact1 = ContactInfo {
-            peer_did: contact1_did.clone(),
-            peer_endpoint_id: [1u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Contact 1".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([1u8; 32]),
-            contact_topic: [10u8; 32],
-            contact_key: [11u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: Some(contact1_pubkeys.to_bytes()),
-            mutual_peers: vec![],
-        };
-        engine.storage.save_contact(&contact1).unwrap();
+    #[tokio::test]
+    async fn test_task_assignment_reaches_members() {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        let contact2 = ContactInfo {
-            peer_did: contact2_did.clone(),
-            peer_endpoint_id: [2u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Contact 2".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([2u8; 32]),
-            contact_topic: [20u8; 32],
-            contact_key: [21u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: Some(contact2_pubkeys.to_bytes()),
-            mutual_peers: vec![],
-        };
-        engine.storage.save_contact(&contact2).unwrap();
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let love_did = love.did().unwrap().clone();
+        let joy_did = joy.did().unwrap().clone();
+
+        // Love knows Joy's profile, so the assignee has a name
+        let joy_profile = UserProfile::new(joy_did.to_string(), "Joy".to_string());
+        let joy_signed = SignedProfile::sign(&joy_profile, joy.identity.as_ref().unwrap());
+        love.pin_profile(joy_signed, PinRelationship::Contact).unwrap();
+
+        let realm_id = love.create_realm("Work Party").await.unwrap();
+        let fence = love.add_task(&realm_id, "Mend the fence").await.unwrap();
+        let soup = love.add_task(&realm_id, "Make soup").await.unwrap();
+
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        let did1 = Did::parse(&contact1_did).unwrap();
-        let did2 = Did::parse(&contact2_did).unwrap();
+        love.assign_task(&realm_id, &fence, Some(joy_did.clone()))
+            .await
+            .unwrap();
+        let assigned = love.list_tasks_assigned_to(&realm_id, &joy_did).unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].id, fence);
+        assert_eq!(assigned[0].assignee_name.as_deref(), Some("Joy"));
+
+        // Joy sees the assignment after syncing
+        let love_bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.apply_full_document(&realm_id, &love_bytes).await.unwrap();
+        let mine = joy.list_tasks_assigned_to(&realm_id, &joy_did).unwrap();
+        assert_eq!(mine.iter().map(|t| &t.id).collect::<Vec<_>>(), vec![&fence]);
+
+        // Joy hands the fence back and gives Love the soup; Love's copy follows
+        joy.assign_task(&realm_id, &fence, None).await.unwrap();
+        joy.assign_task(&realm_id, &soup, Some(love_did.clone()))
+            .await
+            .unwrap();
+        let joy_bytes = joy.storage.load_document(&realm_id).unwrap().unwrap();
+        love.apply_full_document(&realm_id, &joy_bytes).await.unwrap();
 
-        // Send E2E encrypted message to contact 1
-        let payload1 = PacketPayload::DirectMessage {
-            content: "Hello Contact 1".to_string(),
-            recipient: did1.clone(),
-        };
-        engine.create_packet(payload1, PacketAddress::Individual(did1)).unwrap();
+        assert!(love.list_tasks_assigned_to(&realm_id, &joy_did).unwrap().is_empty());
+        let loves = love.list_tasks_assigned_to(&realm_id, &love_did).unwrap();
+        assert_eq!(loves.iter().map(|t| &t.id).collect::<Vec<_>>(), vec![&soup]);
+    }
 
-        // Send E2E encrypted message to contact 2
-        let payload2 = PacketPayload::DirectMessage {
-            content: "Hello Contact 2".to_string(),
-            recipient: did2.clone(),
-        };
-        engine.create_packet(payload2, PacketAddress::Individual(did2)).unwrap();
+    #[tokio::test]
+    async fn test_task_history_lists_both_peers_actions_in_order() {
+        use crate::types::TaskActivityKind;
 
-        // Verify contact 1's conversation only has their message
-        let convo1 = engine.get_conversation(&contact1_did).unwrap();
-        assert_eq!(convo1.len(), 1, "Contact 1 should have exactly 1 message");
-        assert_eq!(convo1.messages()[0].content, "Hello Contact 1");
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let love_did = love.did().unwrap().to_string();
+        let joy_did = joy.did().unwrap().to_string();
 
-        // Verify contact 2's conversation only has their message
-        let convo2 = engine.get_conversation(&contact2_did).unwrap();
-        assert_eq!(convo2.len(), 1, "Contact 2 should have exactly 1 message");
-        assert_eq!(convo2.messages()[0].content, "Hello Contact 2");
-    }
+        let realm_id = love.create_realm("Seed Library").await.unwrap();
+        let task_id = love.add_task(&realm_id, "Sort squash seeds").await.unwrap();
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Signature Verification Tests (make_verify_fn)
-    // ═══════════════════════════════════════════════════════════════════════
+        // Joy gets a replica, completes the task, and Love merges it back
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
+        joy.toggle_task(&realm_id, &task_id).await.unwrap();
+
+        let joy_bytes = joy.storage.load_document(&realm_id).unwrap().unwrap();
+        love.import_automerge(&realm_id, &joy_bytes).await.unwrap();
+
+        let history = love.task_history(&realm_id, &task_id).await.unwrap();
+        let entries: Vec<_> = history
+            .iter()
+            .map(|a| (a.claimed_author.clone(), a.kind.clone()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    Some(love_did),
+                    TaskActivityKind::Created {
+                        title: "Sort squash seeds".to_string()
+                    }
+                ),
+                (Some(joy_did), TaskActivityKind::Completed),
+            ]
+        );
+        assert!(history.iter().all(|a| a.timestamp.is_some()));
+
+        assert!(matches!(
+            love.task_history(&RealmId::new(), &task_id).await,
+            Err(SyncError::RealmNotFound(_))
+        ));
+    }
 
     #[tokio::test]
-    async fn test_verify_fn_known_sender_valid_signature() {
-        use crate::identity::HybridKeypair;
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
+    async fn test_network_resonance_from_metrics() {
+        let stats = |online_peers, average_quality, last_sync_secs: Option<u64>| NetworkStats {
+            total_peers: online_peers,
+            online_peers,
+            average_quality,
+            last_sync_age: last_sync_secs.map(Duration::from_secs),
+            ..Default::default()
+        };
+
+        let cases = [
+            (stats(0, None, None), ResonanceLevel::Dormant),
+            // A recent sync doesn't count once every peer has gone
+            (stats(0, None, Some(5)), ResonanceLevel::Dormant),
+            (stats(1, Some(0), None), ResonanceLevel::Listening),
+            (stats(3, Some(90), None), ResonanceLevel::Listening),
+            (stats(3, Some(90), Some(10 * 60)), ResonanceLevel::Listening),
+            (stats(2, Some(39), Some(5)), ResonanceLevel::Listening),
+            (stats(2, Some(40), Some(5)), ResonanceLevel::Resonating),
+            (stats(1, Some(85), Some(5 * 60)), ResonanceLevel::Resonating),
+        ];
+        for (stats, expected) in cases {
+            assert_eq!(ResonanceLevel::from_stats(&stats), expected, "{stats:?}");
+        }
+        assert_eq!(ResonanceLevel::Resonating.label(), "field resonating");
 
         let (engine, _temp) = create_test_engine().await;
+        let stats = engine.network_stats();
+        assert_eq!(stats.average_quality, None);
+        assert_eq!(stats.last_sync_age, None);
+        assert_eq!(engine.network_resonance(), ResonanceLevel::Dormant);
+    }
 
-        // Create a contact's keypair and profile
-        let contact_keypair = HybridKeypair::generate();
-        let contact_profile = UserProfile::new("peer_love".to_string(), "Love".to_string());
-        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
-        let contact_did = signed_profile.did().to_string();
+    #[tokio::test]
+    async fn test_health_reports_healthy_until_database_writes_fail() {
+        use crate::storage::{ReadTxn, WriteTxn};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// In-memory backend whose writes start failing on request
+        struct FailingBackend {
+            inner: InMemoryBackend,
+            failing: Arc<AtomicBool>,
+        }
 
-        // Pin the profile so they're a known contact
-        engine
-            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
-            .unwrap();
+        impl StorageBackend for FailingBackend {
+            fn begin_read(&self) -> Result<Box<dyn ReadTxn + '_>, SyncError> {
+                self.inner.begin_read()
+            }
+            fn begin_write(&self) -> Result<Box<dyn WriteTxn + '_>, SyncError> {
+                if self.failing.load(Ordering::SeqCst) {
+                    return Err(SyncError::Storage("simulated disk failure".to_string()));
+                }
+                self.inner.begin_write()
+            }
+        }
 
-        // Sign some test data
-        let test_data = b"Hello, world!";
-        let signature = contact_keypair.sign(test_data);
-        let sig_bytes = signature.to_bytes();
+        let (mut engine, _temp) = create_test_engine().await;
+        let health = engine.health();
+        assert!(health.is_healthy());
+        assert!(health.database_writable);
+        assert!(!health.identity_initialized);
+        assert!(!health.networking_active);
+        assert_eq!(health.relay_reachable, None);
+        assert_eq!(health.syncing_realms, 0);
+        assert_eq!(health.last_sync_age, None);
+
+        let failing = Arc::new(AtomicBool::new(false));
+        engine.storage = Storage::with_backend(FailingBackend {
+            inner: InMemoryBackend::new(),
+            failing: failing.clone(),
+        });
+        assert!(engine.health().database_writable);
 
-        // Create verify function and test
-        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
-        assert!(
-            verify_fn(&contact_did, test_data, &sig_bytes),
-            "Valid signature from known sender should verify"
-        );
+        failing.store(true, Ordering::SeqCst);
+        let health = engine.health();
+        assert!(!health.database_writable);
+        assert!(!health.is_healthy());
     }
 
     #[tokio::test]
-    async fn test_verify_fn_unknown_sender_returns_false() {
-        use crate::identity::HybridKeypair;
-        use crate::types::{SignedProfile, UserProfile};
+    async fn test_shutdown_flushes_local_and_queued_changes() {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        let (engine, _temp) = create_test_engine().await;
+        let (mut love, love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let joy_profile = UserProfile::new("joy".to_string(), "Joy".to_string());
+        let signed = SignedProfile::sign(&joy_profile, joy.identity.as_ref().unwrap());
+        love.pin_profile(signed, PinRelationship::Contact).unwrap();
+
+        // Joy holds a replica of Love's realm
+        let realm_id = love.create_realm("Pantry").await.unwrap();
+        let mut info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage
+            .save_realm_key(&realm_id, &love.storage.load_realm_key(&realm_id).unwrap().unwrap())
+            .unwrap();
+        joy.storage
+            .save_document(&realm_id, &love.storage.load_document(&realm_id).unwrap().unwrap())
+            .unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        // Create a stranger's keypair (NOT pinned)
-        let stranger_keypair = HybridKeypair::generate();
-        let stranger_profile = UserProfile::new("peer_stranger".to_string(), "Stranger".to_string());
-        let signed_profile = SignedProfile::sign(&stranger_profile, &stranger_keypair);
-        let stranger_did = signed_profile.did().to_string();
+        // Joy's change arrives but is still queued when Love shuts down
+        joy.add_task(&realm_id, "Label jars").await.unwrap();
+        let message = SyncMessage::SyncResponse {
+            realm_id: realm_id.clone(),
+            document: joy.realms.get_mut(&realm_id).unwrap().doc.save(),
+        };
+        let envelope_bytes = joy
+            .seal_realm_message(&realm_id, &message)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        love.sync_tx
+            .send(SyncChannelMessage::IncomingData {
+                realm_id: realm_id.clone(),
+                envelope_bytes,
+            })
+            .unwrap();
+        love.add_task(&realm_id, "Restock oats").await.unwrap();
 
-        // Sign some test data
-        let test_data = b"Hello from stranger";
-        let signature = stranger_keypair.sign(test_data);
-        let sig_bytes = signature.to_bytes();
+        love.shutdown_with_timeout(Duration::from_secs(1)).await.unwrap();
 
-        // Create verify function and test - should fail because sender is unknown
-        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
-        assert!(
-            !verify_fn(&stranger_did, test_data, &sig_bytes),
-            "Signature from unknown sender should be rejected"
-        );
+        let mut reopened = SyncEngine::new(love_dir.path()).await.unwrap();
+        reopened.open_realm(&realm_id).await.unwrap();
+        let mut titles: Vec<_> = reopened
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Label jars".to_string(), "Restock oats".to_string()]);
     }
 
     #[tokio::test]
-    async fn test_verify_fn_wrong_signature_returns_false() {
-        use crate::identity::HybridKeypair;
+    async fn test_oversized_changes_rejected_before_apply() {
         use crate::types::{PinRelationship, SignedProfile, UserProfile};
 
-        let (engine, _temp) = create_test_engine().await;
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let joy_did = joy.did().unwrap().to_string();
+        let joy_profile = UserProfile::new("joy".to_string(), "Joy".to_string());
+        let signed = SignedProfile::sign(&joy_profile, joy.identity.as_ref().unwrap());
+        love.pin_profile(signed, PinRelationship::Contact).unwrap();
+        love.set_gossip_config(GossipConfig {
+            max_change_bytes: Some(64 * 1024),
+            ..Default::default()
+        })
+        .unwrap();
 
-        // Create a contact's keypair and profile
-        let contact_keypair = HybridKeypair::generate();
-        let contact_profile = UserProfile::new("peer_joy".to_string(), "Joy".to_string());
-        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
-        let contact_did = signed_profile.did().to_string();
+        let realm_id = love.create_realm("Pantry").await.unwrap();
+        let mut info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage
+            .save_realm_key(&realm_id, &love.storage.load_realm_key(&realm_id).unwrap().unwrap())
+            .unwrap();
+        joy.storage
+            .save_document(&realm_id, &love.storage.load_document(&realm_id).unwrap().unwrap())
+            .unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        // Pin the profile
-        engine
-            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
+        // An oversized change never reaches Automerge, so junk bytes are enough
+        let oversized = SyncMessage::Changes {
+            realm_id: realm_id.clone(),
+            data: vec![0u8; 64 * 1024 + 1],
+        };
+        joy.add_task(&realm_id, "Label jars").await.unwrap();
+        let normal = SyncMessage::SyncResponse {
+            realm_id: realm_id.clone(),
+            document: joy.realms.get_mut(&realm_id).unwrap().doc.save(),
+        };
+        for message in [oversized, normal] {
+            let envelope_bytes = joy
+                .seal_realm_message(&realm_id, &message)
+                .unwrap()
+                .to_bytes()
+                .unwrap();
+            love.sync_tx
+                .send(SyncChannelMessage::IncomingData {
+                    realm_id: realm_id.clone(),
+                    envelope_bytes,
+                })
+                .unwrap();
+        }
+
+        assert_eq!(love.process_pending_sync(), 1);
+        assert_eq!(love.oversized_payloads_from(&joy_did), 1);
+        let titles: Vec<_> = love
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Label jars".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_changes_are_applied_once() {
+        use crate::types::{PinRelationship, SignedProfile, UserProfile};
+
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let joy_profile = UserProfile::new("joy".to_string(), "Joy".to_string());
+        let signed = SignedProfile::sign(&joy_profile, joy.identity.as_ref().unwrap());
+        love.pin_profile(signed, PinRelationship::Contact).unwrap();
+
+        let realm_id = love.create_realm("Seed Library").await.unwrap();
+        let mut info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        info.is_creator = false;
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage
+            .save_realm_key(&realm_id, &love.storage.load_realm_key(&realm_id).unwrap().unwrap())
+            .unwrap();
+        joy.storage
+            .save_document(&realm_id, &love.storage.load_document(&realm_id).unwrap().unwrap())
             .unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        // Create a different keypair and sign with it (attacker trying to impersonate)
-        let attacker_keypair = HybridKeypair::generate();
-        let test_data = b"Forged message from attacker";
-        let forged_signature = attacker_keypair.sign(test_data);
-        let sig_bytes = forged_signature.to_bytes();
+        joy.add_task(&realm_id, "Sort bean seeds").await.unwrap();
+        let message = SyncMessage::SyncResponse {
+            realm_id: realm_id.clone(),
+            document: joy.realms.get_mut(&realm_id).unwrap().doc.save(),
+        };
+        let envelope_bytes = joy
+            .seal_realm_message(&realm_id, &message)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        // The same envelope over two gossip paths, then the same document
+        // re-sealed in a fresh envelope
+        let resealed = joy
+            .seal_realm_message(&realm_id, &message)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        assert_ne!(envelope_bytes, resealed);
+        for bytes in [envelope_bytes.clone(), envelope_bytes, resealed] {
+            love.sync_tx
+                .send(SyncChannelMessage::IncomingData {
+                    realm_id: realm_id.clone(),
+                    envelope_bytes: bytes,
+                })
+                .unwrap();
+        }
 
-        // Create verify function and test - should fail because signature doesn't match
-        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
-        assert!(
-            !verify_fn(&contact_did, test_data, &sig_bytes),
-            "Forged signature should be rejected"
-        );
+        assert_eq!(love.process_pending_sync(), 1);
+        assert_eq!(love.duplicate_changes_skipped(), 2);
+        let titles: Vec<_> = love
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Sort bean seeds".to_string()]);
     }
 
     #[tokio::test]
-    async fn test_verify_fn_tampered_message_returns_false() {
-        use crate::identity::HybridKeypair;
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
-
-        let (engine, _temp) = create_test_engine().await;
-
-        // Create a contact's keypair and profile
-        let contact_keypair = HybridKeypair::generate();
-        let contact_profile = UserProfile::new("peer_peace".to_string(), "Peace".to_string());
-        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
-        let contact_did = signed_profile.did().to_string();
-
-        // Pin the profile
+    async fn test_autosave_persists_dirty_realms_before_crash() {
+        let (mut engine, temp) = create_test_engine().await;
         engine
-            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
+            .set_gossip_config(GossipConfig {
+                autosave_interval: Some(Duration::from_millis(50)),
+                ..Default::default()
+            })
             .unwrap();
+        let realm_id = engine.create_realm("Root Cellar").await.unwrap();
 
-        // Sign original data
-        let original_data = b"Original message";
-        let signature = contact_keypair.sign(original_data);
-        let sig_bytes = signature.to_bytes();
+        // An edit held only in memory, as if its save had not happened yet
+        let state = engine.realms.get_mut(&realm_id).unwrap();
+        state.doc.add_task("Turn the potatoes").unwrap();
+        state.dirty = true;
 
-        // Try to verify with tampered data
-        let tampered_data = b"Tampered message";
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        engine.process_pending_sync();
+        assert!(!engine.realms[&realm_id].dirty);
 
-        // Create verify function and test - should fail because data was tampered
-        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
-        assert!(
-            !verify_fn(&contact_did, tampered_data, &sig_bytes),
-            "Signature should fail for tampered message"
-        );
+        // Crash: no shutdown, no explicit save
+        drop(engine);
+
+        let mut reopened = SyncEngine::new(temp.path()).await.unwrap();
+        reopened.open_realm(&realm_id).await.unwrap();
+        let titles: Vec<_> = reopened
+            .list_tasks(&realm_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Turn the potatoes".to_string()]);
     }
 
     #[tokio::test]
-    async fn test_verify_fn_malformed_signature_returns_false() {
-        use crate::identity::HybridKeypair;
-        use crate::types::{PinRelationship, SignedProfile, UserProfile};
-
-        let (engine, _temp) = create_test_engine().await;
+    async fn test_concurrent_renames_report_conflicting_title() {
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let love_did = love.did().unwrap();
+        let joy_did = joy.did().unwrap();
 
-        // Create a contact's keypair and profile
-        let contact_keypair = HybridKeypair::generate();
-        let contact_profile = UserProfile::new("peer_dave".to_string(), "Dave".to_string());
-        let signed_profile = SignedProfile::sign(&contact_profile, &contact_keypair);
-        let contact_did = signed_profile.did().to_string();
+        let realm_id = love.create_realm("Herb Spiral").await.unwrap();
+        let task_id = love.add_task(&realm_id, "Plant thyme").await.unwrap();
 
-        // Pin the profile
-        engine
-            .pin_profile(signed_profile.clone(), PinRelationship::Contact)
-            .unwrap();
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let bytes = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_document(&realm_id, &bytes).unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        let test_data = b"Test message";
+        // Both rename without seeing the other's change, then merge
+        love.rename_task(&realm_id, &task_id, "Plant lemon thyme").await.unwrap();
+        joy.rename_task(&realm_id, &task_id, "Plant creeping thyme").await.unwrap();
+        let joy_bytes = joy.storage.load_document(&realm_id).unwrap().unwrap();
+        love.import_automerge(&realm_id, &joy_bytes).await.unwrap();
+
+        let task = love.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert_eq!(task.conflicting_values.len(), 1);
+        let mut reported = vec![
+            (task.conflicting_values[0].0.clone(), task.conflicting_values[0].1.clone()),
+            (
+                if task.title == "Plant lemon thyme" { love_did.clone() } else { joy_did.clone() },
+                task.title.clone(),
+            ),
+        ];
+        reported.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            reported,
+            vec![
+                (joy_did, "Plant creeping thyme".to_string()),
+                (love_did, "Plant lemon thyme".to_string()),
+            ]
+        );
 
-        // Test with various malformed signatures
-        let verify_fn = SyncEngine::make_verify_fn(&engine.storage);
+        // Picking a title settles the conflict
+        love.rename_task(&realm_id, &task_id, "Plant creeping thyme").await.unwrap();
+        let task = love.get_task(&realm_id, &task_id).unwrap().unwrap();
+        assert_eq!(task.title, "Plant creeping thyme");
+        assert!(task.conflicting_values.is_empty());
+    }
 
-        // Empty signature
-        assert!(
-            !verify_fn(&contact_did, test_data, &[]),
-            "Empty signature should be rejected"
-        );
+    #[tokio::test]
+    async fn test_configured_relay_reported_in_node_info() {
+        let (mut engine, _temp) = create_test_engine().await;
+        let relay = GossipConfig::parse_relay_url("https://relay.example.org").unwrap();
+        engine
+            .set_gossip_config(GossipConfig {
+                relay_url: Some(relay),
+                ..Default::default()
+            })
+            .unwrap();
+        engine.start_networking().await.unwrap();
 
-        // Signature too short (less than 68 bytes needed for Ed25519 + length)
-        assert!(
-            !verify_fn(&contact_did, test_data, &[0u8; 50]),
-            "Too-short signature should be rejected"
-        );
+        let info = engine.node_info().await.unwrap();
+        assert_eq!(info.relay_url.as_deref(), Some("https://relay.example.org./"));
 
-        // Random garbage bytes (invalid signature format)
-        let garbage: Vec<u8> = (0..400).map(|i| (i % 256) as u8).collect();
-        assert!(
-            !verify_fn(&contact_did, test_data, &garbage),
-            "Garbage signature should be rejected"
-        );
+        // The endpoint is already built, so the config can no longer change
+        let result = engine.set_gossip_config(GossipConfig::default());
+        assert!(matches!(result, Err(SyncError::InvalidOperation(_))));
+        engine.shutdown().await.unwrap();
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // E2E Encryption Key Lookup Tests
-    // ═══════════════════════════════════════════════════════════════════════
-
     #[tokio::test]
-    async fn test_get_recipient_public_keys_no_contact() {
-        let (engine, _temp) = create_test_engine().await;
-
-        let unknown_did = Did::parse("did:sync:zUnknownContact123").unwrap();
-        let result = engine.get_recipient_public_keys(&unknown_did);
+    async fn test_disabled_relay_reports_no_relay() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine
+            .set_gossip_config(GossipConfig {
+                disable_relay: true,
+                ..Default::default()
+            })
+            .unwrap();
+        engine.start_networking().await.unwrap();
 
-        assert!(result.is_err(), "Should fail for unknown contact");
-        let err_msg = result.unwrap_err().to_string();
-        assert!(
-            err_msg.contains("No contact found"),
-            "Error should indicate no contact: {}",
-            err_msg
-        );
+        let info = engine.node_info().await.unwrap();
+        assert!(info.node_id.is_some());
+        assert!(info.relay_url.is_none());
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_get_recipient_public_keys_legacy_contact_no_keys() {
-        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
-        use crate::invite::NodeAddrBytes;
+    async fn test_reconnect_scheduler_retries_offline_peer_with_growing_backoff() {
+        let (mut engine, _temp) = create_test_engine().await;
+        engine
+            .set_gossip_config(GossipConfig {
+                disable_relay: true,
+                reconnect_interval: Some(std::time::Duration::from_millis(100)),
+                ..Default::default()
+            })
+            .unwrap();
+        engine.start_networking().await.unwrap();
 
-        let (engine, _temp) = create_test_engine().await;
+        // A peer with no known address can never be reached
+        let peer_id = iroh::SecretKey::generate(&mut rand::rng()).public();
+        engine
+            .peer_registry()
+            .add_or_update(&PeerInfo::new(peer_id, PeerSource::FromInvite).with_status(PeerStatus::Offline))
+            .unwrap();
 
-        // Create a legacy contact without encryption keys
-        let peer_did = "did:sync:zLegacyContact456";
-        let legacy_contact = ContactInfo {
-            peer_did: peer_did.to_string(),
-            peer_endpoint_id: [0u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Legacy Contact".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([0u8; 32]),
-            contact_topic: [1u8; 32],
-            contact_key: [2u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: None, // Legacy contact - no encryption keys
-            mutual_peers: vec![],
-        };
-        engine.storage.save_contact(&legacy_contact).unwrap();
+        let mut delays = Vec::new();
+        for attempt in 1..=3u32 {
+            // Wait for the scheduler to pick the peer up
+            let mut peer = None;
+            for _ in 0..50 {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                let info = engine.peer_registry().get(&peer_id).unwrap().unwrap();
+                if info.connection_attempts == attempt {
+                    peer = Some(info);
+                    break;
+                }
+            }
+            let mut peer = peer.expect("scheduler should retry the offline peer");
+            assert_eq!(peer.status, PeerStatus::Offline);
+
+            // The backoff holds further attempts off until it elapses
+            let backoff = peer.backoff();
+            assert_eq!(backoff.consecutive_failures, attempt);
+            assert!(backoff.next_attempt_at > peer.last_attempt);
+            delays.push(backoff.delay_secs);
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            assert_eq!(
+                engine.peer_registry().get(&peer_id).unwrap().unwrap().connection_attempts,
+                attempt
+            );
 
-        let did = Did::parse(peer_did).unwrap();
-        let result = engine.get_recipient_public_keys(&did);
+            // Pretend the backoff has elapsed
+            peer.last_attempt -= backoff.delay_secs;
+            engine.peer_registry().add_or_update(&peer).unwrap();
+        }
 
-        assert!(result.is_err(), "Should fail for legacy contact without keys");
-        let err_msg = result.unwrap_err().to_string();
-        assert!(
-            err_msg.contains("does not have encryption keys"),
-            "Error should indicate missing encryption keys: {}",
-            err_msg
-        );
+        assert!(delays[1] > delays[0], "backoff should grow: {:?}", delays);
+        assert!(delays[2] > delays[1], "backoff should grow: {:?}", delays);
+        engine.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_get_recipient_public_keys_with_valid_keys() {
-        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
-        use crate::invite::NodeAddrBytes;
-        use crate::profile::ProfileKeys;
-
-        let (engine, _temp) = create_test_engine().await;
-
-        // Generate real ProfileKeys to get valid encryption keys
-        let contact_profile_keys = ProfileKeys::generate();
-        let public_keys = contact_profile_keys.public_bundle();
-        let enc_keys_bytes = public_keys.to_bytes();
+    async fn test_correlation_id_links_send_and_receive_in_logs() {
+        use crate::logging::{trace_correlation, JsonlLayer};
+        use crate::types::PinRelationship;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let logs = TempDir::new().unwrap();
+        let (mut love, _love_dir) = create_test_engine().await;
+        let (mut joy, _joy_dir) = create_test_engine().await;
+        love.init_identity().unwrap();
+        joy.init_identity().unwrap();
+        let love_signed = love.sign_and_pin_own_profile().unwrap();
+        joy.pin_profile(love_signed, PinRelationship::Contact).unwrap();
+
+        // Joy holds a copy of Love's realm
+        let realm_id = love.create_realm("Traced Realm").await.unwrap();
+        let info = love.storage.load_realm(&realm_id).unwrap().unwrap();
+        let key = love.storage.load_realm_key(&realm_id).unwrap().unwrap();
+        let doc = love.storage.load_document(&realm_id).unwrap().unwrap();
+        joy.storage.save_realm(&info).unwrap();
+        joy.storage.save_realm_key(&realm_id, &key).unwrap();
+        joy.storage.save_document(&realm_id, &doc).unwrap();
+        joy.open_realm(&realm_id).await.unwrap();
 
-        // Create a contact with encryption keys
-        let peer_did = public_keys.did().to_string();
-        let contact = ContactInfo {
-            peer_did: peer_did.clone(),
-            peer_endpoint_id: [0u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Encrypted Contact".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([0u8; 32]),
-            contact_topic: [1u8; 32],
-            contact_key: [2u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: Some(enc_keys_bytes),
-            mutual_peers: vec![],
+        let message = SyncMessage::SyncRequest {
+            realm_id: realm_id.clone(),
         };
-        engine.storage.save_contact(&contact).unwrap();
-
-        let did = Did::parse(&peer_did).unwrap();
-        let result = engine.get_recipient_public_keys(&did);
+        let envelope = {
+            let layer = JsonlLayer::new(logs.path(), "love").unwrap();
+            let _guard =
+                tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+            love.seal_realm_message(&realm_id, &message).unwrap()
+        };
+        let correlation_id = envelope.correlation_id();
+        let bytes = envelope.to_bytes().unwrap();
 
-        assert!(result.is_ok(), "Should succeed for contact with valid keys");
-        let retrieved_keys = result.unwrap();
+        // Keep the receive strictly after the send in timestamp order
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        {
+            let layer = JsonlLayer::new(logs.path(), "joy").unwrap();
+            let _guard =
+                tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+            let received = joy.handle_incoming(&realm_id, &bytes).unwrap();
+            assert!(matches!(received, Some(SyncMessage::SyncRequest { .. })));
+        }
 
-        // Verify the retrieved keys match the original
+        let timeline = trace_correlation(logs.path(), &correlation_id).unwrap();
+        let steps: Vec<(&str, &str)> = timeline
+            .iter()
+            .map(|e| (e.instance.as_str(), e.msg.as_str()))
+            .collect();
         assert_eq!(
-            retrieved_keys.did().to_string(),
-            public_keys.did().to_string(),
-            "Retrieved keys should have matching DID"
+            steps,
+            [
+                ("love", "Sealed sync envelope"),
+                ("joy", "Processing incoming envelope"),
+                ("joy", "Successfully opened envelope"),
+            ]
         );
     }
 
     #[tokio::test]
-    async fn test_get_recipient_public_keys_malformed_keys() {
-        use crate::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
-        use crate::invite::NodeAddrBytes;
+    async fn test_packet_events_query_and_export() {
+        use crate::sync::{DecryptionStatus, PacketDirection, PacketEvent, PacketEventFilter};
 
         let (engine, _temp) = create_test_engine().await;
-
-        // Create a contact with malformed encryption keys
-        // Note: DID identifier must be valid base58 (excludes 0, O, I, lowercase l)
-        let peer_did = "did:sync:zBadKeyDataContact789";
-        let contact = ContactInfo {
-            peer_did: peer_did.to_string(),
-            peer_endpoint_id: [0u8; 32],
-            profile: ProfileSnapshot {
-                display_name: "Malformed Contact".to_string(),
-                subtitle: None,
-                avatar_blob_id: None,
-                bio: String::new(),
-            },
-            node_addr: NodeAddrBytes::new([0u8; 32]),
-            contact_topic: [1u8; 32],
-            contact_key: [2u8; 32],
-            accepted_at: 0,
-            last_seen: 0,
-            status: ContactStatus::Offline,
-            is_favorite: false,
-            encryption_keys: Some(vec![0xDE, 0xAD, 0xBE, 0xEF]), // Invalid key data
-            mutual_peers: vec![],
+        let event = |peer: &str, sequence: u64, direction: PacketDirection| PacketEvent {
+            id: PacketEvent::make_id(peer, sequence),
+            timestamp: sequence as i64,
+            direction,
+            sequence,
+            author_did: peer.to_string(),
+            author_name: peer.to_string(),
+            relay_did: None,
+            relay_name: None,
+            destination_did: "did:sync:me".to_string(),
+            destination_name: "Me".to_string(),
+            decryption_status: DecryptionStatus::Decrypted,
+            content_preview: format!("Message {}", sequence),
+            is_delivered: false,
+            peer_did: peer.to_string(),
         };
-        engine.storage.save_contact(&contact).unwrap();
-
-        let did = Did::parse(peer_did).unwrap();
-        let result = engine.get_recipient_public_keys(&did);
-
-        assert!(result.is_err(), "Should fail for malformed keys");
-        let err_msg = result.unwrap_err().to_string();
-        assert!(
-            err_msg.contains("Failed to parse encryption keys"),
-            "Error should indicate parsing failure: {}",
-            err_msg
-        );
+        engine.record_packet_event(event("did:sync:joy", 1, PacketDirection::Outgoing));
+        engine.record_packet_event(event("did:sync:joy", 2, PacketDirection::Incoming));
+        engine.record_packet_event(event("did:sync:peace", 3, PacketDirection::Incoming));
+
+        let incoming = engine.packet_events(PacketEventFilter::new().direction(PacketDirection::Incoming));
+        assert_eq!(incoming.iter().map(|e| e.sequence).collect::<Vec<_>>(), [2, 3]);
+
+        let joy = engine.packet_events(PacketEventFilter::new().peer("did:sync:joy"));
+        assert_eq!(joy.iter().map(|e| e.sequence).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(engine.packet_events_dropped(), 0);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&engine.export_packet_events_json().unwrap()).unwrap();
+        assert_eq!(json["dropped"], 0);
+        assert_eq!(json["events"].as_array().unwrap().len(), 3);
+        assert_eq!(json["events"][0]["direction"], "Outgoing");
+        assert_eq!(json["events"][2]["peer_did"], "did:sync:peace");
     }
 }
@@ -13,6 +13,10 @@ pub enum SyncError {
     #[error("Task not found: {0}")]
     TaskNotFound(String),
 
+    /// Snapshot was not found for the specified realm
+    #[error("Snapshot not found: {0}")]
+    SnapshotNotFound(String),
+
     /// Error during gossip protocol operations
     #[error("Gossip error: {0}")]
     Gossip(String),
@@ -97,17 +101,52 @@ pub enum SyncError {
     #[error("Contact not found: {0}")]
     ContactNotFound(String),
 
+    /// No completed contact exchange with the recipient, so there are no keys to encrypt to
+    #[error("Contact key exchange with {did} is not complete")]
+    ContactKeyExchangeIncomplete {
+        /// DID of the intended recipient
+        did: String,
+    },
+
+    /// The recipient is a contact but never shared encryption keys (legacy contact)
+    #[error("Contact {did} has no encryption keys; re-exchange contacts to enable encrypted messaging")]
+    RecipientKeysMissing {
+        /// DID of the intended recipient
+        did: String,
+    },
+
     /// Invalid operation for current state
     #[error("Invalid operation: {0}")]
     InvalidOperation(String),
 
+    /// An external document does not follow the realm task schema
+    #[error("Incompatible document: {0}")]
+    IncompatibleDocument(String),
+
     /// Blob storage/transfer error
     #[error("Blob error: {0}")]
     Blob(String),
 
+    /// Bytes that should be an image could not be decoded as one
+    #[error("Not a supported image (PNG, JPEG or WebP): {0}")]
+    InvalidImage(String),
+
     /// Operation requires a component that hasn't been initialized yet
     #[error("Not ready: {0}")]
     NotReady(String),
+
+    /// A configuration value is malformed or contradicts another
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// An incoming packet or document change is over the configured size limit
+    #[error("Payload of {size} bytes exceeds the {limit}-byte limit")]
+    PayloadTooLarge {
+        /// Size of the rejected payload in bytes
+        size: usize,
+        /// Configured limit in bytes
+        limit: usize,
+    },
 }
 
 /// Result type alias using SyncError
@@ -0,0 +1,273 @@
+//! Devices sharing one identity
+//!
+//! An identity can live on several devices: the identity keys are exported
+//! encrypted from one device and imported on another, so both sign as the
+//! same DID. Each device writes its own packet log, told apart by the
+//! [`DeviceId`] in the packet envelope, so two devices never fork each
+//! other's hash chain.
+//!
+//! The devices of an identity are published as a [`SignedDeviceList`],
+//! signed with the identity key, so peers can tell several endpoints apart
+//! as one person rather than someone impersonating them.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Did, HybridKeypair, HybridPublicKey, HybridSignature};
+
+/// Length of a device ID in bytes
+pub const DEVICE_ID_SIZE: usize = 16;
+
+/// Identifies one device among those sharing an identity.
+///
+/// The device an identity was created on is [`DeviceId::PRIMARY`]; devices
+/// linked to it later get a random ID.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DeviceId([u8; DEVICE_ID_SIZE]);
+
+impl DeviceId {
+    /// The device the identity was created on
+    pub const PRIMARY: Self = Self([0; DEVICE_ID_SIZE]);
+
+    /// Generate a random ID for a newly linked device.
+    pub fn generate() -> Self {
+        loop {
+            let id = Self(rand::random());
+            if !id.is_primary() {
+                return id;
+            }
+        }
+    }
+
+    /// Create a device ID from raw bytes.
+    pub fn from_bytes(bytes: [u8; DEVICE_ID_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get the raw bytes.
+    pub fn as_bytes(&self) -> &[u8; DEVICE_ID_SIZE] {
+        &self.0
+    }
+
+    /// Check if this is the device the identity was created on.
+    pub fn is_primary(&self) -> bool {
+        *self == Self::PRIMARY
+    }
+}
+
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DeviceId({})", self)
+    }
+}
+
+/// One device in a [`DeviceList`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceEntry {
+    /// The device's ID
+    pub device: DeviceId,
+    /// Iroh endpoint ID the device is reachable at, once it has networked
+    pub endpoint_id: Option<[u8; 32]>,
+    /// Name the device was linked under
+    pub name: String,
+    /// Unix timestamp of when the device was added
+    pub added_at: i64,
+}
+
+/// The devices an identity is in use on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceList {
+    /// The identity's DID
+    pub did: Did,
+    /// Bumped on every change; the highest version wins
+    pub version: u64,
+    /// The devices, ordered by ID
+    pub devices: Vec<DeviceEntry>,
+}
+
+impl DeviceList {
+    /// Create an empty list for `did`.
+    pub fn new(did: Did) -> Self {
+        Self {
+            did,
+            version: 0,
+            devices: Vec::new(),
+        }
+    }
+
+    /// Find a device by ID.
+    pub fn device(&self, device: &DeviceId) -> Option<&DeviceEntry> {
+        self.devices.iter().find(|entry| entry.device == *device)
+    }
+
+    /// Find the device reachable at an endpoint.
+    pub fn device_at(&self, endpoint_id: &[u8; 32]) -> Option<&DeviceEntry> {
+        self.devices
+            .iter()
+            .find(|entry| entry.endpoint_id.as_ref() == Some(endpoint_id))
+    }
+
+    /// Add a device, or update the entry of one already listed.
+    ///
+    /// Returns whether the list changed. A known endpoint is never cleared.
+    pub fn upsert(&mut self, entry: DeviceEntry) -> bool {
+        match self.devices.iter_mut().find(|e| e.device == entry.device) {
+            Some(existing) => {
+                let endpoint_id = entry.endpoint_id.or(existing.endpoint_id);
+                if existing.endpoint_id == endpoint_id && existing.name == entry.name {
+                    return false;
+                }
+                existing.endpoint_id = endpoint_id;
+                existing.name = entry.name;
+            }
+            None => {
+                self.devices.push(entry);
+                self.devices.sort_by_key(|e| e.device);
+            }
+        }
+        true
+    }
+
+    /// Take in every device `other` lists that this list lacks or knows
+    /// less about. Returns whether the list changed.
+    pub fn merge(&mut self, other: &DeviceList) -> bool {
+        let mut changed = false;
+        for entry in &other.devices {
+            let learned = match self.device(&entry.device) {
+                Some(known) => known.endpoint_id.is_none() && entry.endpoint_id.is_some(),
+                None => true,
+            };
+            if learned {
+                changed |= self.upsert(entry.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// A [`DeviceList`] signed by the identity it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedDeviceList {
+    /// The signed list
+    pub list: DeviceList,
+    /// Hybrid signature over the serialized list
+    pub signature: HybridSignature,
+    /// The identity's public key, to verify without looking it up
+    pub public_key: HybridPublicKey,
+}
+
+impl SignedDeviceList {
+    /// Sign a device list with the identity keypair.
+    pub fn sign(list: DeviceList, keypair: &HybridKeypair) -> Self {
+        let bytes = postcard::to_allocvec(&list).expect("Device list serialization should never fail");
+        Self {
+            signature: keypair.sign(&bytes),
+            public_key: keypair.public_key(),
+            list,
+        }
+    }
+
+    /// Check that the list was signed by the identity it names.
+    pub fn verify(&self) -> bool {
+        if Did::from_public_key(&self.public_key) != self.list.did {
+            return false;
+        }
+        match postcard::to_allocvec(&self.list) {
+            Ok(bytes) => self.public_key.verify(&bytes, &self.signature),
+            Err(_) => false,
+        }
+    }
+
+    /// Check whether this list should replace `other`.
+    ///
+    /// The higher version wins. Two devices may sign the same version
+    /// concurrently, so ties go to the list with more devices, then to the
+    /// larger hash, and everyone keeps the same one.
+    pub fn supersedes(&self, other: &SignedDeviceList) -> bool {
+        let rank = |signed: &SignedDeviceList| {
+            let bytes = postcard::to_allocvec(&signed.list).unwrap_or_default();
+            (signed.list.version, signed.list.devices.len(), *blake3::hash(&bytes).as_bytes())
+        };
+        rank(self) > rank(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(device: DeviceId, endpoint_id: Option<[u8; 32]>) -> DeviceEntry {
+        DeviceEntry {
+            device,
+            endpoint_id,
+            name: "laptop".to_string(),
+            added_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_generated_device_is_never_primary() {
+        let device = DeviceId::generate();
+        assert!(!device.is_primary());
+        assert_ne!(device, DeviceId::generate());
+        assert!(DeviceId::PRIMARY.is_primary());
+        assert_eq!(DeviceId::PRIMARY.to_string().len(), DEVICE_ID_SIZE * 2);
+    }
+
+    #[test]
+    fn test_signed_device_list_verifies_only_for_its_identity() {
+        let keypair = HybridKeypair::generate();
+        let mut list = DeviceList::new(Did::from_public_key(&keypair.public_key()));
+        list.upsert(entry(DeviceId::PRIMARY, Some([1; 32])));
+        let signed = SignedDeviceList::sign(list.clone(), &keypair);
+        assert!(signed.verify());
+
+        // Tampering with the list breaks the signature
+        let mut tampered = signed.clone();
+        tampered.list.upsert(entry(DeviceId::generate(), Some([2; 32])));
+        assert!(!tampered.verify());
+
+        // Someone else can't sign a list for this identity
+        let mallory = HybridKeypair::generate();
+        assert!(!SignedDeviceList::sign(list, &mallory).verify());
+    }
+
+    #[test]
+    fn test_merge_keeps_every_device_and_known_endpoints() {
+        let did = Did::from_public_key(&HybridKeypair::generate().public_key());
+        let linked = DeviceId::generate();
+
+        let mut ours = DeviceList::new(did.clone());
+        ours.upsert(entry(DeviceId::PRIMARY, Some([1; 32])));
+        ours.upsert(entry(linked, None));
+
+        let mut theirs = DeviceList::new(did);
+        theirs.upsert(entry(linked, Some([2; 32])));
+
+        assert!(ours.merge(&theirs));
+        assert!(!ours.merge(&theirs));
+        assert_eq!(ours.devices.len(), 2);
+        assert_eq!(ours.device_at(&[2; 32]).map(|e| e.device), Some(linked));
+        assert_eq!(ours.device_at(&[1; 32]).map(|e| e.device), Some(DeviceId::PRIMARY));
+    }
+
+    #[test]
+    fn test_higher_version_supersedes() {
+        let keypair = HybridKeypair::generate();
+        let mut list = DeviceList::new(Did::from_public_key(&keypair.public_key()));
+        list.upsert(entry(DeviceId::PRIMARY, None));
+        let old = SignedDeviceList::sign(list.clone(), &keypair);
+        list.version += 1;
+        let new = SignedDeviceList::sign(list, &keypair);
+
+        assert!(new.supersedes(&old));
+        assert!(!old.supersedes(&new));
+        assert!(!new.supersedes(&new));
+    }
+}
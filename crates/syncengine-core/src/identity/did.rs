@@ -106,6 +106,72 @@ impl Did {
         Self::validate_format(did_str)?;
         Ok(Did(did_str.to_string()))
     }
+
+    /// Bytes that drive the generated identicon
+    ///
+    /// Hashing the whole DID (rather than reading the identifier's bytes)
+    /// spreads even near-identical DIDs across very different visuals.
+    fn identicon_seed(&self) -> [u8; 32] {
+        *blake3::hash(self.0.as_bytes()).as_bytes()
+    }
+
+    /// Deterministic RGB color for this DID, for use when there is no avatar
+    ///
+    /// The hue comes from the DID's hash; saturation and lightness stay in a
+    /// range that reads well on both dark and light backgrounds.
+    pub fn to_identicon_color(&self) -> (u8, u8, u8) {
+        let seed = self.identicon_seed();
+        let hue = f32::from(u16::from_be_bytes([seed[0], seed[1]])) / 65536.0 * 360.0;
+        let saturation = 0.55 + f32::from(seed[2]) / 255.0 * 0.2;
+        let lightness = 0.45 + f32::from(seed[3]) / 255.0 * 0.15;
+        hsl_to_rgb(hue, saturation, lightness)
+    }
+
+    /// Deterministic 5×5 mirrored identicon for this DID, as an SVG document
+    ///
+    /// Filled cells use [`to_identicon_color`](Self::to_identicon_color).
+    pub fn to_identicon_svg(&self) -> String {
+        let seed = self.identicon_seed();
+        let (r, g, b) = self.to_identicon_color();
+
+        let mut cells = String::new();
+        for row in 0..5 {
+            for col in 0..3 {
+                // One bit per cell of the left half; the right half mirrors it
+                let bit = row * 3 + col;
+                if (seed[4 + bit / 8] >> (bit % 8)) & 1 == 0 {
+                    continue;
+                }
+                let mirror = 4 - col;
+                for x in if mirror == col { vec![col] } else { vec![col, mirror] } {
+                    cells.push_str(&format!(r#"<rect x="{}" y="{}" width="1" height="1"/>"#, x, row));
+                }
+            }
+        }
+
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -1 7 7" shape-rendering="crispEdges"><rect x="-1" y="-1" width="7" height="7" fill="#f0f0f0"/><g fill="#{:02x}{:02x}{:02x}">{}</g></svg>"##,
+            r, g, b, cells
+        )
+    }
+}
+
+/// Convert HSL (hue in degrees, saturation and lightness in 0..=1) to RGB
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |v: f32| ((v + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
 }
 
 impl fmt::Display for Did {
@@ -264,6 +330,30 @@ mod tests {
         assert_eq!(full, format!("did:sync:z{}", identifier));
     }
 
+    #[test]
+    fn test_identicon_is_stable_and_distinct_per_did() {
+        let did1 = Did::from_public_key(&HybridKeypair::generate().public_key());
+        let did2 = Did::from_public_key(&HybridKeypair::generate().public_key());
+
+        assert_eq!(did1.to_identicon_color(), did1.to_identicon_color());
+        assert_eq!(did1.to_identicon_svg(), did1.clone().to_identicon_svg());
+        assert_ne!(did1.to_identicon_color(), did2.to_identicon_color());
+        assert_ne!(did1.to_identicon_svg(), did2.to_identicon_svg());
+
+        let (r, g, b) = did1.to_identicon_color();
+        let svg = did1.to_identicon_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(&format!("#{:02x}{:02x}{:02x}", r, g, b)));
+    }
+
+    #[test]
+    fn test_hsl_to_rgb_primaries() {
+        assert_eq!(hsl_to_rgb(0.0, 1.0, 0.5), (255, 0, 0));
+        assert_eq!(hsl_to_rgb(120.0, 1.0, 0.5), (0, 255, 0));
+        assert_eq!(hsl_to_rgb(240.0, 1.0, 0.5), (0, 0, 255));
+        assert_eq!(hsl_to_rgb(0.0, 0.0, 1.0), (255, 255, 255));
+    }
+
     #[test]
     fn test_did_as_ref() {
         let keypair = HybridKeypair::generate();
@@ -9,6 +9,7 @@
 //! - **Hybrid Keypairs**: Ed25519 + ML-DSA-65 for quantum-resistant signatures
 //! - **DIDs**: Decentralized identifiers in the format `did:sync:z{base58}`
 //! - **Signatures**: Both classical and post-quantum signatures must verify
+//! - **Devices**: One identity shared by several devices, listed in a signed device list
 //!
 //! ## Example
 //!
@@ -42,11 +43,13 @@
 //! - If ML-DSA-65 is found to have a flaw, Ed25519 still protects you
 //! - Signatures are larger but provide future-proof security
 
+mod devices;
 mod did;
 mod keypair;
 mod signature;
 
 // Re-export public types
+pub use devices::{DeviceEntry, DeviceId, DeviceList, SignedDeviceList, DEVICE_ID_SIZE};
 pub use did::Did;
 pub use keypair::{HybridKeypair, HybridPublicKey};
 pub use signature::HybridSignature;
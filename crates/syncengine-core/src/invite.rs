@@ -20,6 +20,9 @@ use serde::{Deserialize, Serialize};
 /// Prefix for encoded invite strings
 const INVITE_PREFIX: &str = "sync-invite:";
 
+/// Prefix for encoded node address strings
+const ADDR_PREFIX: &str = "sync-addr:";
+
 /// Current protocol version
 const PROTOCOL_VERSION: u8 = 1;
 
@@ -116,6 +119,47 @@ impl NodeAddrBytes {
 
         Ok(addr)
     }
+
+    /// Encode the address as a `sync-addr:{base58}` string for sharing
+    /// out of band.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if encoding fails.
+    pub fn encode(&self) -> Result<String, SyncError> {
+        let bytes = postcard::to_stdvec(self)
+            .map_err(|e| SyncError::Serialization(format!("Failed to encode address: {}", e)))?;
+        Ok(format!("{}{}", ADDR_PREFIX, bs58::encode(&bytes).into_string()))
+    }
+
+    /// Decode an address from a `sync-addr:{base58}` string.
+    ///
+    /// The decoded address is checked with [`Self::to_endpoint_addr`], so a
+    /// successful decode can be dialed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` if the prefix, base58 or binary data
+    /// is invalid, or the address itself is malformed.
+    pub fn decode(s: &str) -> Result<Self, SyncError> {
+        let data = s.strip_prefix(ADDR_PREFIX).ok_or_else(|| {
+            SyncError::InvalidInvite(format!(
+                "Invalid prefix: expected '{}', got '{}'",
+                ADDR_PREFIX,
+                s.chars().take(15).collect::<String>()
+            ))
+        })?;
+
+        let bytes = bs58::decode(data)
+            .into_vec()
+            .map_err(|e| SyncError::InvalidInvite(format!("Invalid base58: {}", e)))?;
+
+        let addr: NodeAddrBytes = postcard::from_bytes(&bytes)
+            .map_err(|e| SyncError::InvalidInvite(format!("Invalid address data: {}", e)))?;
+        addr.to_endpoint_addr()?;
+
+        Ok(addr)
+    }
 }
 
 impl From<&EndpointAddr> for NodeAddrBytes {
@@ -193,6 +237,10 @@ pub struct InviteTicket {
     pub expires_at: Option<i64>,
     /// Maximum number of times this invite can be used (None = unlimited)
     pub max_uses: Option<u32>,
+    /// DID of the realm's first owner (its creator), if the inviter knows it
+    ///
+    /// Joiners only accept a first role table signed by this DID.
+    pub owner_did: Option<String>,
 }
 
 impl InviteTicket {
@@ -224,6 +272,7 @@ impl InviteTicket {
             realm_name: None,
             expires_at: None,
             max_uses: None,
+            owner_did: None,
         }
     }
 
@@ -239,6 +288,12 @@ impl InviteTicket {
         self
     }
 
+    /// Set the DID of the realm's first owner (builder pattern).
+    pub fn with_owner(mut self, owner_did: &str) -> Self {
+        self.owner_did = Some(owner_did.to_string());
+        self
+    }
+
     /// Set maximum number of uses (builder pattern).
     pub fn with_max_uses(mut self, max: u32) -> Self {
         self.max_uses = Some(max);
@@ -366,6 +421,7 @@ mod tests {
         assert_eq!(decoded.realm_name, None);
         assert_eq!(decoded.expires_at, None);
         assert_eq!(decoded.max_uses, None);
+        assert_eq!(decoded.owner_did, None);
     }
 
     #[test]
@@ -384,7 +440,8 @@ mod tests {
         let ticket = InviteTicket::new(&realm_id, realm_key, vec![peer1, peer2])
             .with_name("Test Realm")
             .with_expiry(expires)
-            .with_max_uses(5);
+            .with_max_uses(5)
+            .with_owner("did:sync:love");
 
         // Encode and decode
         let encoded = ticket.encode().expect("Failed to encode");
@@ -394,6 +451,7 @@ mod tests {
         assert_eq!(decoded.realm_name, Some("Test Realm".to_string()));
         assert_eq!(decoded.expires_at, Some(expires));
         assert_eq!(decoded.max_uses, Some(5));
+        assert_eq!(decoded.owner_did.as_deref(), Some("did:sync:love"));
 
         // Verify bootstrap peers
         assert_eq!(decoded.bootstrap_peers.len(), 2);
@@ -56,32 +56,41 @@ pub mod sync;
 pub mod types;
 
 // Re-exports
-pub use blobs::{BlobManager, BlobProtocolHandler};
+pub use blobs::{BlobManager, BlobProtocolHandler, ImageOrientation};
 pub use crypto::RealmCrypto;
-pub use engine::{NetworkStats, NodeInfo, StartupSyncResult, SyncEngine};
+pub use engine::{
+    AcceptedInvite, AvatarBlobs, ContactInviteAcceptance, Contribution, HealthReport,
+    IdentityLink, IdentityRegeneratePreview, InviteKind, MessageCounts, NetworkStats, NodeInfo,
+    PendingSync, RealmCompaction, RealmContribution, RealmDeletePreview, RealmRekeyOutcome,
+    RealmStats, ResonanceLevel, StartupProgress, StartupSyncResult, StewardshipSummary, SyncEngine,
+};
 pub use error::SyncError;
 pub use identity::{Did, HybridKeypair, HybridPublicKey, HybridSignature};
 pub use invite::{InviteTicket, NodeAddrBytes};
 // Legacy peer types (deprecated in favor of unified Peer type)
-pub use peers::{PeerInfo, PeerRegistry};
+pub use peers::{ConnectionChange, ConnectionEvent, PeerBackoff, PeerInfo, PeerRegistry};
 // Re-export from types module (the unified version)
-pub use types::peer::{ContactDetails, Peer, PeerSource, PeerStatus};
-pub use realm::RealmDoc;
-pub use storage::{PinnerInfo, PinningConfig, Storage};
+pub use types::peer::{ContactDetails, LinkStats, Peer, PeerSource, PeerStatus};
+pub use realm::{RealmDoc, RealmSnapshotView, RealmTemplate, TemplateTask};
+pub use storage::{InMemoryBackend, PinnerInfo, PinningConfig, Storage, StorageBackend};
 pub use sync::{
-    ContactEvent, DecryptionStatus, GossipMessage, GossipSync, NetworkDebugInfo,
-    PacketDirection, PacketEvent, PacketEventBuffer, PacketEventBufferConfig, SyncEnvelope,
-    SyncEvent, SyncManager, SyncMessage, SyncStatus, TopicHandle, WireMessage, ENVELOPE_VERSION,
+    ContactEvent, DecryptionStatus, GossipConfig, GossipMessage, GossipSync, NetworkDebugInfo,
+    NetworkErrorRecord, PacketDirection, PacketEvent, PacketEventBuffer, PacketEventBufferConfig,
+    PacketEventExport, PacketEventFilter, SyncEnvelope, SyncEvent, SyncManager, SyncMessage,
+    SyncStatus, TopicDebugInfo, TopicHandle, WireMessage, ENVELOPE_VERSION,
 };
 pub use types::*;
 
+// Automerge change hashes identify document states (see `SyncEngine::realm_heads`)
+pub use automerge::ChangeHash;
+
 // Chat module
-pub use chat::{ChatMessage, Conversation};
+pub use chat::{ChatMessage, Conversation, Mention, MessageSegment, TranscriptFormat};
 
 // Profile packet layer (Indra's Network)
 pub use profile::{
     derive_profile_packet_topic, derive_realm_packet_topic, ForkDetection, HybridKeyExchange,
-    LogEntry, MirrorStore, PacketAddress, PacketBuilder, PacketEnvelope, PacketPayload,
-    PacketRoute, ProfileKeys, ProfileLog, ProfilePublicKeys, ProfileTopicTracker, SealedBox,
-    SealedKey,
+    LogBreak, LogBreakKind, LogEntry, LogIntegrityReport, MirrorStore, PacketAddress,
+    PacketBuilder, PacketEnvelope, PacketPayload, PacketRoute, ProfileKeys, ProfileLog,
+    ProfilePublicKeys, ProfileTopicTracker, SealedBox, SealedKey,
 };
@@ -69,6 +69,16 @@ impl JsonLogEntry {
         self
     }
 
+    /// Look up a structured field by name.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields.as_ref()?.get(name)
+    }
+
+    /// The sync correlation id recorded with this entry, if any.
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.field("correlation_id")?.as_str()
+    }
+
     /// Serialize to a single JSON line (no trailing newline).
     pub fn to_json_line(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -14,7 +14,7 @@ use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 
 use super::entry::JsonLogEntry;
-use super::writer::InstanceLogWriter;
+use super::writer::{prune_old_logs, InstanceLogWriter};
 
 /// A tracing Layer that writes events to JSONL files.
 ///
@@ -40,8 +40,22 @@ impl JsonlLayer {
         })
     }
 
-    /// Get the path to the log file.
-    pub fn log_path(&self) -> &std::path::Path {
+    /// Create a JSONL layer whose files roll over at `max_file_bytes`.
+    ///
+    /// See [`InstanceLogWriter::with_max_file_size`].
+    pub fn with_max_file_size(
+        logs_dir: impl AsRef<std::path::Path>,
+        instance: impl Into<String>,
+        max_file_bytes: u64,
+    ) -> std::io::Result<Self> {
+        let writer = InstanceLogWriter::with_max_file_size(logs_dir, instance, max_file_bytes)?;
+        Ok(Self {
+            writer: Arc::new(writer),
+        })
+    }
+
+    /// Get the path to the log file currently being written.
+    pub fn log_path(&self) -> std::path::PathBuf {
         self.writer.path()
     }
 
@@ -175,6 +189,8 @@ pub struct LoggingBuilder {
     instance: String,
     console_output: bool,
     env_filter: Option<String>,
+    retention_days: Option<u32>,
+    max_file_bytes: Option<u64>,
 }
 
 impl LoggingBuilder {
@@ -185,6 +201,8 @@ impl LoggingBuilder {
             instance: instance.into(),
             console_output: true,
             env_filter: None,
+            retention_days: None,
+            max_file_bytes: None,
         }
     }
 
@@ -200,9 +218,29 @@ impl LoggingBuilder {
         self
     }
 
+    /// Delete raw JSONL files older than `days` when the layer is built.
+    pub fn with_retention(mut self, days: u32) -> Self {
+        self.retention_days = Some(days);
+        self
+    }
+
+    /// Roll a day's file over to `_001`, `_002`, ... once it reaches `bytes`.
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_bytes = Some(bytes);
+        self
+    }
+
     /// Build and return the JSONL layer (for manual composition).
+    ///
+    /// Prunes expired log files first if a retention window is set.
     pub fn build_layer(&self) -> std::io::Result<JsonlLayer> {
-        JsonlLayer::new(&self.logs_dir, &self.instance)
+        if let Some(days) = self.retention_days {
+            prune_old_logs(&self.logs_dir, days)?;
+        }
+        match self.max_file_bytes {
+            Some(max) => JsonlLayer::with_max_file_size(&self.logs_dir, &self.instance, max),
+            None => JsonlLayer::new(&self.logs_dir, &self.instance),
+        }
     }
 }
 
@@ -238,4 +276,23 @@ mod tests {
         assert!(lines[1].contains("Warning with field"));
         assert!(lines[1].contains("\"count\""));
     }
+
+    #[test]
+    fn test_builder_prunes_expired_logs_on_build() {
+        let temp = TempDir::new().unwrap();
+        let logs_dir = temp.path().join("logs");
+        let raw_dir = logs_dir.join("raw");
+        std::fs::create_dir_all(&raw_dir).unwrap();
+        let stale = raw_dir.join("2000-01-01_love.jsonl");
+        std::fs::write(&stale, "{}\n").unwrap();
+
+        let layer = LoggingBuilder::new(&logs_dir, "love")
+            .with_retention(14)
+            .with_max_file_size(1024)
+            .build_layer()
+            .unwrap();
+
+        assert!(!stale.exists());
+        assert!(layer.log_path().exists());
+    }
 }
@@ -10,6 +10,7 @@
 //! logs/
 //! ├── raw/                              # Machine-readable (one file per instance per day)
 //! │   ├── 2026-01-21_love.jsonl
+//! │   ├── 2026-01-21_love_001.jsonl      # Rolled part, when a size cap is set
 //! │   ├── 2026-01-21_joy.jsonl
 //! │   └── 2026-01-21_peace.jsonl
 //! ├── sessions/                         # Session metadata
@@ -36,6 +37,16 @@
 //! tracing::subscriber::set_global_default(subscriber)?;
 //! ```
 //!
+//! ### Retention and rotation
+//!
+//! ```ignore
+//! // Keep two weeks of logs and roll files over at 10 MB
+//! let jsonl_layer = LoggingBuilder::new("./logs", "love")
+//!     .with_retention(14)
+//!     .with_max_file_size(10 * 1024 * 1024)
+//!     .build_layer()?;
+//! ```
+//!
 //! ### Generating reports
 //!
 //! ```ignore
@@ -46,6 +57,18 @@
 //! std::fs::write("LOGS.md", markdown)?;
 //! ```
 //!
+//! ### Querying logs in-process
+//!
+//! ```ignore
+//! use syncengine_core::logging::{query, QueryFilter};
+//!
+//! let errors = query("./logs", &QueryFilter::new().instance("love").level("error"))?;
+//! ```
+//!
+//! Sync envelopes and packets carry a correlation id that is logged as the
+//! `correlation_id` field on both the sending and the receiving instance;
+//! `trace_correlation("./logs", id)` returns that operation's timeline.
+//!
 //! ### Querying logs with jq
 //!
 //! ```bash
@@ -61,11 +84,19 @@
 
 pub mod entry;
 pub mod layer;
+pub mod query;
 pub mod report;
 pub mod writer;
 
 // Re-exports for convenience
 pub use entry::{JsonLogEntry, SessionMetadata};
 pub use layer::{JsonlLayer, LoggingBuilder};
-pub use report::{generate_report, generate_timeline, write_report, LogStats, ReportOptions};
-pub use writer::{read_all_entries, read_entries_for_date, write_session_metadata, InstanceLogWriter};
+pub use query::{query, trace_correlation, QueryFilter};
+pub use report::{
+    generate_report, generate_sync_timeline, generate_timeline, write_report, LogStats,
+    ReportOptions,
+};
+pub use writer::{
+    prune_old_logs, read_all_entries, read_entries_for_date, write_session_metadata,
+    InstanceLogWriter,
+};
@@ -0,0 +1,218 @@
+//! Programmatic queries over JSONL log entries.
+//!
+//! The in-process counterpart of the `jq` recipes in the module docs, for
+//! log viewers that should not shell out.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+use super::entry::JsonLogEntry;
+use super::writer::read_all_entries;
+
+/// Criteria for selecting log entries.
+///
+/// Every field left as `None` matches all entries; set fields must all match.
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilter {
+    /// Only entries from this instance (e.g., "love")
+    pub instance: Option<String>,
+
+    /// Only entries at this level (case-insensitive, e.g., "warn")
+    pub level: Option<String>,
+
+    /// Only entries at or after this time (inclusive)
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only entries strictly before this time (exclusive)
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only entries whose target or message contains this text (case-sensitive)
+    pub contains: Option<String>,
+
+    /// Only entries carrying this `correlation_id` field
+    pub correlation_id: Option<String>,
+}
+
+impl QueryFilter {
+    /// Create a filter that matches every entry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to one instance.
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Restrict to one level.
+    pub fn level(mut self, level: impl Into<String>) -> Self {
+        self.level = Some(level.into());
+        self
+    }
+
+    /// Restrict to the half-open time range `[since, until)`.
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    /// Restrict to entries whose target or message contains `text`.
+    pub fn contains(mut self, text: impl Into<String>) -> Self {
+        self.contains = Some(text.into());
+        self
+    }
+
+    /// Restrict to entries logged for one sync operation.
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+
+    /// Check whether an entry satisfies the filter.
+    ///
+    /// Entries with an unparseable timestamp never match a time bound.
+    pub fn matches(&self, entry: &JsonLogEntry) -> bool {
+        if let Some(instance) = &self.instance {
+            if entry.instance != *instance {
+                return false;
+            }
+        }
+        if let Some(level) = &self.level {
+            if !entry.level.eq_ignore_ascii_case(level) {
+                return false;
+            }
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Ok(ts) = DateTime::parse_from_rfc3339(&entry.ts) else {
+                return false;
+            };
+            let ts = ts.with_timezone(&Utc);
+            if self.since.is_some_and(|since| ts < since) {
+                return false;
+            }
+            if self.until.is_some_and(|until| ts >= until) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.contains {
+            if !entry.target.contains(text.as_str()) && !entry.msg.contains(text.as_str()) {
+                return false;
+            }
+        }
+        if let Some(id) = &self.correlation_id {
+            if entry.correlation_id() != Some(id.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Read every entry under `logs_dir` that matches `filter`, sorted by timestamp.
+pub fn query(logs_dir: impl AsRef<Path>, filter: &QueryFilter) -> std::io::Result<Vec<JsonLogEntry>> {
+    let mut entries = read_all_entries(logs_dir)?;
+    entries.retain(|entry| filter.matches(entry));
+    Ok(entries)
+}
+
+/// Follow one sync operation across instances.
+///
+/// Returns every entry tagged with `correlation_id`, in timestamp order, so
+/// the send on one instance is followed by the receive on the others.
+pub fn trace_correlation(
+    logs_dir: impl AsRef<Path>,
+    correlation_id: &str,
+) -> std::io::Result<Vec<JsonLogEntry>> {
+    query(logs_dir, &QueryFilter::new().correlation_id(correlation_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn entry(ts: &str, level: &str, instance: &str, target: &str, msg: &str) -> String {
+        let mut entry = JsonLogEntry::new(level, instance, target, msg);
+        entry.ts = ts.to_string();
+        entry.to_json_line().unwrap()
+    }
+
+    fn at(ts: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(ts).unwrap().with_timezone(&Utc)
+    }
+
+    /// Two instances, mixed levels, one second apart
+    fn fixture() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let raw_dir = temp.path().join("raw");
+        fs::create_dir_all(&raw_dir).unwrap();
+        let love = [
+            entry("2026-01-21T10:00:00.000Z", "info", "love", "syncengine_core::sync::gossip", "Joined topic"),
+            entry("2026-01-21T10:00:02.000Z", "warn", "love", "syncengine_core::engine", "Peer slow to respond"),
+            entry("2026-01-21T10:00:04.000Z", "error", "love", "syncengine_core::engine", "Sync failed"),
+        ];
+        let joy = [
+            entry("2026-01-21T10:00:01.000Z", "debug", "joy", "syncengine_core::sync::gossip", "Neighbor up"),
+            entry("2026-01-21T10:00:03.000Z", "WARN", "joy", "syncengine_core::storage", "Slow commit"),
+        ];
+        fs::write(raw_dir.join("2026-01-21_love.jsonl"), love.join("\n")).unwrap();
+        fs::write(raw_dir.join("2026-01-21_joy.jsonl"), joy.join("\n")).unwrap();
+        temp
+    }
+
+    fn messages(entries: &[JsonLogEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.msg.as_str()).collect()
+    }
+
+    #[test]
+    fn test_query_filters_select_matching_entries() {
+        let logs = fixture();
+
+        let all = query(logs.path(), &QueryFilter::new()).unwrap();
+        assert_eq!(all.len(), 5);
+
+        let love = query(logs.path(), &QueryFilter::new().instance("love")).unwrap();
+        assert_eq!(messages(&love), ["Joined topic", "Peer slow to respond", "Sync failed"]);
+
+        let warnings = query(logs.path(), &QueryFilter::new().level("warn")).unwrap();
+        assert_eq!(messages(&warnings), ["Peer slow to respond", "Slow commit"]);
+
+        let joy_warnings = query(logs.path(), &QueryFilter::new().instance("joy").level("warn")).unwrap();
+        assert_eq!(messages(&joy_warnings), ["Slow commit"]);
+
+        // Substring matches the target or the message
+        let gossip = query(logs.path(), &QueryFilter::new().contains("sync::gossip")).unwrap();
+        assert_eq!(messages(&gossip), ["Joined topic", "Neighbor up"]);
+        let slow = query(logs.path(), &QueryFilter::new().contains("Slow")).unwrap();
+        assert_eq!(messages(&slow), ["Slow commit"]);
+    }
+
+    #[test]
+    fn test_query_time_range_is_half_open() {
+        let logs = fixture();
+
+        let filter = QueryFilter::new().between(
+            at("2026-01-21T10:00:01.000Z"),
+            at("2026-01-21T10:00:03.000Z"),
+        );
+        let window = query(logs.path(), &filter).unwrap();
+        // 10:00:01 is included, 10:00:03 is not
+        assert_eq!(messages(&window), ["Neighbor up", "Peer slow to respond"]);
+
+        let since = QueryFilter {
+            since: Some(at("2026-01-21T10:00:04.000Z")),
+            ..Default::default()
+        };
+        assert_eq!(messages(&query(logs.path(), &since).unwrap()), ["Sync failed"]);
+
+        let until = QueryFilter {
+            until: Some(at("2026-01-21T10:00:00.000Z")),
+            ..Default::default()
+        };
+        assert!(query(logs.path(), &until).unwrap().is_empty());
+    }
+}
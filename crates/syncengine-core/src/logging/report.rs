@@ -3,7 +3,7 @@
 //! The generated LOGS.md is a view of the raw JSONL data - it can be
 //! regenerated at any time from the source files.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 use std::fs;
 use std::path::Path;
@@ -54,6 +54,9 @@ pub struct ReportOptions {
 
     /// Show full timestamps (vs relative)
     pub full_timestamps: bool,
+
+    /// Add a per-realm section listing sync events across instances
+    pub include_sync_timeline: bool,
 }
 
 impl Default for ReportOptions {
@@ -63,6 +66,7 @@ impl Default for ReportOptions {
             include_debug: true,
             max_per_instance: 0,
             full_timestamps: false,
+            include_sync_timeline: false,
         }
     }
 }
@@ -76,6 +80,16 @@ pub fn generate_report(logs_dir: impl AsRef<Path>, options: &ReportOptions) -> s
         return Ok("# Synchronicity Engine - Run Logs\n\nNo log entries found.\n".to_string());
     }
 
+    // Sync events are mostly debug-level, so collect them before level filtering
+    let mut sync_by_realm: BTreeMap<String, Vec<JsonLogEntry>> = BTreeMap::new();
+    if options.include_sync_timeline {
+        for entry in &entries {
+            if let Some((realm_id, _)) = sync_event(entry) {
+                sync_by_realm.entry(realm_id.to_string()).or_default().push(entry.clone());
+            }
+        }
+    }
+
     // Filter by log level
     let entries: Vec<_> = entries
         .into_iter()
@@ -165,6 +179,16 @@ pub fn generate_report(logs_dir: impl AsRef<Path>, options: &ReportOptions) -> s
         writeln!(report).unwrap();
     }
 
+    // Sync timeline section, one table per realm
+    if !sync_by_realm.is_empty() {
+        writeln!(report, "## Sync Timeline").unwrap();
+        writeln!(report).unwrap();
+        for (realm_id, events) in &sync_by_realm {
+            let events: Vec<_> = events.iter().collect();
+            write_sync_timeline(&mut report, realm_id, &events, options.full_timestamps);
+        }
+    }
+
     // Per-instance sections
     writeln!(report, "---").unwrap();
     writeln!(report).unwrap();
@@ -284,10 +308,58 @@ pub fn generate_timeline(logs_dir: impl AsRef<Path>, limit: Option<usize>) -> st
     Ok(output)
 }
 
+/// Generate the sync timeline of one realm as markdown.
+///
+/// Lists every sync event (announce, request, response, changes, leave)
+/// logged for `realm_id` by any instance, in chronological order. A realm
+/// whose requests are never followed by a response stands out at a glance.
+pub fn generate_sync_timeline(logs_dir: impl AsRef<Path>, realm_id: &str) -> std::io::Result<String> {
+    let entries = read_all_entries(logs_dir)?;
+    let events: Vec<_> = entries
+        .iter()
+        .filter(|e| sync_event(e).is_some_and(|(realm, _)| realm == realm_id))
+        .collect();
+
+    let mut output = String::new();
+    write_sync_timeline(&mut output, realm_id, &events, true);
+    Ok(output)
+}
+
+/// The realm and event name of a sync-layer entry, from its structured fields.
+fn sync_event(entry: &JsonLogEntry) -> Option<(&str, &str)> {
+    let event = entry.field("sync_event")?.as_str()?;
+    let realm_id = entry.field("realm_id")?.as_str()?;
+    Some((realm_id, event))
+}
+
+/// Write one realm's sync events as a markdown table.
+fn write_sync_timeline(out: &mut String, realm_id: &str, events: &[&JsonLogEntry], full_timestamps: bool) {
+    writeln!(out, "### Realm `{}`", realm_id).unwrap();
+    writeln!(out).unwrap();
+    if events.is_empty() {
+        writeln!(out, "No sync events found.").unwrap();
+        writeln!(out).unwrap();
+        return;
+    }
+    writeln!(out, "| Time | Instance | Event | Message |").unwrap();
+    writeln!(out, "|------|----------|-------|---------|").unwrap();
+    for entry in events {
+        let ts = if full_timestamps {
+            entry.ts.as_str()
+        } else {
+            entry.ts.split('T').nth(1).unwrap_or(&entry.ts)
+        };
+        let event = sync_event(entry).map(|(_, event)| event).unwrap_or_default();
+        writeln!(out, "| {} | {} | {} | {} |", ts, entry.instance, event, entry.msg).unwrap();
+    }
+    writeln!(out).unwrap();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::logging::writer::InstanceLogWriter;
+    use std::fs;
     use tempfile::TempDir;
 
     #[test]
@@ -330,6 +402,66 @@ mod tests {
         assert!(report.contains("## Instance: `love`"));
     }
 
+    /// A sync-layer entry as the engine logs it
+    fn sync_line(ts: &str, instance: &str, realm_id: &str, event: &str, msg: &str) -> String {
+        let mut entry = JsonLogEntry::new("debug", instance, "syncengine_core::engine", msg)
+            .with_fields(serde_json::json!({ "realm_id": realm_id, "sync_event": event }));
+        entry.ts = ts.to_string();
+        entry.to_json_line().unwrap()
+    }
+
+    #[test]
+    fn test_sync_timeline_orders_events_by_realm() {
+        let temp = TempDir::new().unwrap();
+        let raw_dir = temp.path().join("raw");
+        fs::create_dir_all(&raw_dir).unwrap();
+
+        let love = [
+            sync_line("2026-01-21T10:00:00.000Z", "love", "garden", "announce", "Sealed sync envelope"),
+            sync_line("2026-01-21T10:00:02.000Z", "love", "garden", "sync_request", "Successfully opened envelope"),
+            sync_line("2026-01-21T10:00:05.000Z", "love", "kitchen", "changes", "Sealed sync envelope"),
+            JsonLogEntry::new("info", "love", "syncengine_core::engine", "Unrelated")
+                .to_json_line()
+                .unwrap(),
+        ];
+        let joy = [
+            sync_line("2026-01-21T10:00:01.000Z", "joy", "garden", "sync_request", "Sealed sync envelope"),
+            sync_line("2026-01-21T10:00:03.000Z", "joy", "garden", "sync_response", "Successfully opened envelope"),
+        ];
+        fs::write(raw_dir.join("2026-01-21_love.jsonl"), love.join("\n")).unwrap();
+        fs::write(raw_dir.join("2026-01-21_joy.jsonl"), joy.join("\n")).unwrap();
+
+        let garden = generate_sync_timeline(temp.path(), "garden").unwrap();
+        assert!(garden.starts_with("### Realm `garden`"));
+        let rows: Vec<&str> = garden.lines().filter(|l| l.starts_with("| 2026")).collect();
+        assert_eq!(
+            rows,
+            [
+                "| 2026-01-21T10:00:00.000Z | love | announce | Sealed sync envelope |",
+                "| 2026-01-21T10:00:01.000Z | joy | sync_request | Sealed sync envelope |",
+                "| 2026-01-21T10:00:02.000Z | love | sync_request | Successfully opened envelope |",
+                "| 2026-01-21T10:00:03.000Z | joy | sync_response | Successfully opened envelope |",
+            ]
+        );
+        assert!(!garden.contains("kitchen") && !garden.contains("Unrelated"));
+
+        // The report groups events by realm, and only when asked to
+        let options = ReportOptions {
+            include_debug: false,
+            include_sync_timeline: true,
+            ..Default::default()
+        };
+        let report = generate_report(temp.path(), &options).unwrap();
+        let section = report.find("## Sync Timeline").unwrap();
+        let garden_at = report.find("### Realm `garden`").unwrap();
+        let kitchen_at = report.find("### Realm `kitchen`").unwrap();
+        assert!(section < garden_at && garden_at < kitchen_at);
+        assert!(report.contains("| 10:00:05.000Z | love | changes | Sealed sync envelope |"));
+
+        let plain = generate_report(temp.path(), &ReportOptions::default()).unwrap();
+        assert!(!plain.contains("## Sync Timeline"));
+    }
+
     #[test]
     fn test_generate_timeline() {
         let temp = TempDir::new().unwrap();
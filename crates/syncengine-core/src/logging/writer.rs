@@ -13,15 +13,53 @@ use super::entry::{JsonLogEntry, SessionMetadata};
 /// Writer that appends log entries to a JSONL file.
 ///
 /// Each instance gets its own file: `logs/raw/2026-01-21_love.jsonl`
+///
+/// With a size cap, a file that would grow past the cap rolls over to the
+/// next part: `2026-01-21_love_001.jsonl`, `2026-01-21_love_002.jsonl`, ...
 pub struct InstanceLogWriter {
     /// Instance name (e.g., "love", "joy")
     instance: String,
 
-    /// Buffered file writer (wrapped in Mutex for thread safety)
-    writer: Mutex<BufWriter<File>>,
+    /// Directory holding the JSONL files
+    raw_dir: PathBuf,
+
+    /// File name stem shared by every part (`{date}_{instance}`)
+    stem: String,
+
+    /// Maximum size of one part in bytes, if rotation is enabled
+    max_file_bytes: Option<u64>,
+
+    /// Open file and its bookkeeping (wrapped in Mutex for thread safety)
+    state: Mutex<WriterState>,
+}
 
-    /// Path to the JSONL file
+/// The part currently being appended to
+struct WriterState {
+    writer: BufWriter<File>,
     path: PathBuf,
+    part: u32,
+    bytes: u64,
+}
+
+/// Path of a numbered part (part 0 is the un-suffixed file)
+fn part_path(raw_dir: &Path, stem: &str, part: u32) -> PathBuf {
+    if part == 0 {
+        raw_dir.join(format!("{}.jsonl", stem))
+    } else {
+        raw_dir.join(format!("{}_{:03}.jsonl", stem, part))
+    }
+}
+
+fn open_part(raw_dir: &Path, stem: &str, part: u32) -> std::io::Result<WriterState> {
+    let path = part_path(raw_dir, stem, part);
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let bytes = file.metadata()?.len();
+    Ok(WriterState {
+        writer: BufWriter::new(file),
+        path,
+        part,
+        bytes,
+    })
 }
 
 impl InstanceLogWriter {
@@ -36,6 +74,26 @@ impl InstanceLogWriter {
     ///     └── 2026-01-21T14-13-48.json
     /// ```
     pub fn new(logs_dir: impl AsRef<Path>, instance: impl Into<String>) -> std::io::Result<Self> {
+        Self::open(logs_dir, instance, None)
+    }
+
+    /// Create a log writer that rolls to a new part once a file reaches `max_file_bytes`.
+    ///
+    /// Appends to the latest existing part for today, so restarting an
+    /// instance does not reset the rotation.
+    pub fn with_max_file_size(
+        logs_dir: impl AsRef<Path>,
+        instance: impl Into<String>,
+        max_file_bytes: u64,
+    ) -> std::io::Result<Self> {
+        Self::open(logs_dir, instance, Some(max_file_bytes))
+    }
+
+    fn open(
+        logs_dir: impl AsRef<Path>,
+        instance: impl Into<String>,
+        max_file_bytes: Option<u64>,
+    ) -> std::io::Result<Self> {
         let instance = instance.into();
         let logs_dir = logs_dir.as_ref();
 
@@ -43,23 +101,25 @@ impl InstanceLogWriter {
         let raw_dir = logs_dir.join("raw");
         fs::create_dir_all(&raw_dir)?;
 
-        // Generate filename with date and instance name
+        // File names start with the date and instance name
         let date = chrono::Local::now().format("%Y-%m-%d");
-        let filename = format!("{}_{}.jsonl", date, instance);
-        let path = raw_dir.join(&filename);
+        let stem = format!("{}_{}", date, instance);
 
-        // Open file in append mode
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)?;
-
-        let writer = BufWriter::new(file);
+        // Resume at the newest part written today
+        let mut part = 0;
+        if max_file_bytes.is_some() {
+            while part_path(&raw_dir, &stem, part + 1).exists() {
+                part += 1;
+            }
+        }
+        let state = open_part(&raw_dir, &stem, part)?;
 
         Ok(Self {
             instance,
-            writer: Mutex::new(writer),
-            path,
+            raw_dir,
+            stem,
+            max_file_bytes,
+            state: Mutex::new(state),
         })
     }
 
@@ -68,23 +128,34 @@ impl InstanceLogWriter {
         &self.instance
     }
 
-    /// Get the path to the JSONL file.
-    pub fn path(&self) -> &Path {
-        &self.path
+    /// Get the path to the JSONL file currently being written.
+    pub fn path(&self) -> PathBuf {
+        self.state.lock().unwrap().path.clone()
     }
 
     /// Write a log entry to the file.
     ///
     /// Each entry is written as a single line followed by a newline.
     /// The write is atomic at the OS level for lines under ~4KB.
+    /// If the line would push the current part past the size cap, the
+    /// writer first rolls over to the next part.
     pub fn write(&self, entry: &JsonLogEntry) -> std::io::Result<()> {
         let json = entry
             .to_json_line()
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-
-        let mut writer = self.writer.lock().unwrap();
-        writeln!(writer, "{}", json)?;
-        writer.flush()?;
+        let line_len = json.len() as u64 + 1;
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(max) = self.max_file_bytes {
+            // An empty part always takes the line, even an oversized one
+            if state.bytes > 0 && state.bytes + line_len > max {
+                state.writer.flush()?;
+                *state = open_part(&self.raw_dir, &self.stem, state.part + 1)?;
+            }
+        }
+        writeln!(state.writer, "{}", json)?;
+        state.writer.flush()?;
+        state.bytes += line_len;
 
         Ok(())
     }
@@ -106,8 +177,7 @@ impl InstanceLogWriter {
 
     /// Flush any buffered data to disk.
     pub fn flush(&self) -> std::io::Result<()> {
-        let mut writer = self.writer.lock().unwrap();
-        writer.flush()
+        self.state.lock().unwrap().writer.flush()
     }
 }
 
@@ -136,7 +206,46 @@ pub fn write_session_metadata(
     Ok(path)
 }
 
+/// Delete raw JSONL files dated more than `retention_days` days before today.
+///
+/// The date is taken from the file name (`2026-01-21_love.jsonl` and its
+/// rolled parts), so with a retention of 7 days, files from the last 7 days
+/// and today are kept. Files whose names do not start with a date are left
+/// alone. Returns the number of files removed.
+pub fn prune_old_logs(logs_dir: impl AsRef<Path>, retention_days: u32) -> std::io::Result<usize> {
+    let raw_dir = logs_dir.as_ref().join("raw");
+
+    if !raw_dir.exists() {
+        return Ok(0);
+    }
+
+    let cutoff = chrono::Local::now().date_naive() - chrono::Duration::days(retention_days as i64);
+    let mut removed = 0;
+
+    for entry in fs::read_dir(&raw_dir)? {
+        let path = entry?.path();
+        let Some(filename) = path.file_name().and_then(|f| f.to_str()) else {
+            continue;
+        };
+        if !filename.ends_with(".jsonl") {
+            continue;
+        }
+        let file_date = filename
+            .get(..10)
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        if matches!(file_date, Some(date) if date < cutoff) {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
 /// Read all JSONL files from the raw logs directory.
+///
+/// Rolled parts (`_001`, `_002`, ...) are ordinary JSONL files and are read
+/// like any other; entries come back sorted by timestamp.
 pub fn read_all_entries(logs_dir: impl AsRef<Path>) -> std::io::Result<Vec<JsonLogEntry>> {
     let raw_dir = logs_dir.as_ref().join("raw");
 
@@ -146,10 +255,14 @@ pub fn read_all_entries(logs_dir: impl AsRef<Path>) -> std::io::Result<Vec<JsonL
 
     let mut entries = Vec::new();
 
-    for entry in fs::read_dir(&raw_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    // Read files in name order so that entries sharing a timestamp keep the
+    // order they were written in, across rolled parts too
+    let mut paths = fs::read_dir(&raw_dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
 
+    for path in paths {
         if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
             let content = fs::read_to_string(&path)?;
             for line in content.lines() {
@@ -297,4 +410,65 @@ mod tests {
         assert!(content.contains("\"love\""));
         assert!(content.contains("\"joy\""));
     }
+
+    #[test]
+    fn test_prune_old_logs_respects_retention() {
+        let temp = TempDir::new().unwrap();
+        let logs_dir = temp.path().join("logs");
+        let raw_dir = logs_dir.join("raw");
+        fs::create_dir_all(&raw_dir).unwrap();
+
+        let today = chrono::Local::now().date_naive();
+        let dated = |days_ago: i64, suffix: &str| {
+            let date = (today - chrono::Duration::days(days_ago)).format("%Y-%m-%d");
+            raw_dir.join(format!("{}_love{}.jsonl", date, suffix))
+        };
+        let keep = [dated(0, ""), dated(7, ""), dated(7, "_001")];
+        let expire = [dated(8, ""), dated(8, "_001"), dated(30, "")];
+        let unrelated = raw_dir.join("notes.jsonl");
+        for path in keep.iter().chain(&expire).chain([&unrelated]) {
+            fs::write(path, "{}\n").unwrap();
+        }
+
+        let removed = prune_old_logs(&logs_dir, 7).unwrap();
+
+        assert_eq!(removed, expire.len());
+        assert!(keep.iter().all(|p| p.exists()));
+        assert!(expire.iter().all(|p| !p.exists()));
+        assert!(unrelated.exists());
+    }
+
+    #[test]
+    fn test_writer_rolls_over_at_size_cap() {
+        let temp = TempDir::new().unwrap();
+        let logs_dir = temp.path().join("logs");
+
+        let writer = InstanceLogWriter::with_max_file_size(&logs_dir, "peace", 300).unwrap();
+        let first_path = writer.path();
+        for i in 0..10 {
+            writer
+                .write_raw("info", "test::rotation", &format!("Message {}", i), None)
+                .unwrap();
+        }
+
+        let second_path = writer.path();
+        assert_ne!(first_path, second_path);
+        assert!(second_path.to_string_lossy().contains("peace_00"));
+        for entry in fs::read_dir(logs_dir.join("raw")).unwrap() {
+            let len = entry.unwrap().metadata().unwrap().len();
+            assert!(len <= 300, "part exceeded the cap: {} bytes", len);
+        }
+        drop(writer);
+
+        // A restarted writer keeps appending to the newest part
+        let reopened = InstanceLogWriter::with_max_file_size(&logs_dir, "peace", 300).unwrap();
+        assert_eq!(reopened.path(), second_path);
+        drop(reopened);
+
+        // Every part is read back, in order
+        let entries = read_all_entries(&logs_dir).unwrap();
+        let messages: Vec<_> = entries.iter().map(|e| e.msg.clone()).collect();
+        let expected: Vec<_> = (0..10).map(|i| format!("Message {}", i)).collect();
+        assert_eq!(messages, expected);
+    }
 }
@@ -13,26 +13,92 @@
 //! │  Peer Discovery Flow                                            │
 //! │  1. User joins realm via invite                                 │
 //! │  2. GossipEvent::NeighborUp → record peer                       │
-//! │  3. Peer stored in redb with metadata                           │
+//! │  3. Peer stored in the database with metadata                   │
 //! │  4. Background task periodically tries to reconnect             │
 //! │  5. Connection status updated based on results                  │
 //! └─────────────────────────────────────────────────────────────────┘
 //! ```
 
 use crate::error::SyncError;
+use crate::invite::NodeAddrBytes;
 use crate::types::RealmId;
 use iroh::PublicKey;
-use parking_lot::RwLock;
-use redb::{Database, ReadableTable, TableDefinition};
+use crate::storage::StorageBackend;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Re-export from types::peer for backwards compatibility
-pub use crate::types::peer::{PeerSource, PeerStatus};
+pub use crate::types::peer::{LinkStats, PeerSource, PeerStatus};
 
-// Table definition for peer registry
-const PEERS_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("peers");
+// Table for the peer registry (key: hex endpoint_id)
+const PEERS_TABLE: &str = "peers";
+
+// Connection history per peer, keyed like PEERS_TABLE
+const PEER_CONNECTIONS_TABLE: &str = "peer_connections";
+
+/// Connection events kept per peer; older ones are dropped
+pub const MAX_CONNECTION_EVENTS: usize = 50;
+
+/// Whether a peer came or went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionChange {
+    /// Gossip reported the peer as a neighbor (NeighborUp)
+    Connected,
+    /// Gossip dropped the peer as a neighbor (NeighborDown)
+    Disconnected,
+}
+
+/// One entry in a peer's connection history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    /// Unix timestamp in milliseconds
+    pub at_ms: u64,
+    /// Whether the peer connected or disconnected
+    pub change: ConnectionChange,
+    /// Whether the path was direct (`Some(false)` for relayed, `None` if unknown)
+    pub direct: Option<bool>,
+    /// Realm topic the event was seen on
+    pub realm_id: Option<RealmId>,
+}
+
+impl ConnectionEvent {
+    /// Create an event stamped with the current time
+    pub fn now(change: ConnectionChange) -> Self {
+        Self {
+            at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            change,
+            direct: None,
+            realm_id: None,
+        }
+    }
+
+    /// Set whether the path was direct
+    pub fn with_direct(mut self, direct: Option<bool>) -> Self {
+        self.direct = direct;
+        self
+    }
+
+    /// Set the realm topic the event was seen on
+    pub fn with_realm(mut self, realm_id: RealmId) -> Self {
+        self.realm_id = Some(realm_id);
+        self
+    }
+}
+
+/// Reconnection backoff state of a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerBackoff {
+    /// Failed attempts not yet offset by successful connections
+    pub consecutive_failures: u32,
+    /// Current delay between attempts, in seconds
+    pub delay_secs: u64,
+    /// Unix timestamp from which the next attempt is allowed (0 = immediately)
+    pub next_attempt_at: u64,
+}
 
 /// Information about a discovered peer
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -58,6 +124,15 @@ pub struct PeerInfo {
     /// Unix timestamp of last connection attempt
     #[serde(default)]
     pub last_attempt: u64,
+    /// Smoothed RTT, loss and path measurements from keepalives
+    #[serde(default)]
+    pub link: LinkStats,
+    /// Connection quality score (0-100) derived from `link`
+    #[serde(default)]
+    pub connection_quality: u8,
+    /// Address shared out of band, dialed without discovery
+    #[serde(default)]
+    pub node_addr: Option<NodeAddrBytes>,
 }
 
 impl PeerInfo {
@@ -73,6 +148,9 @@ impl PeerInfo {
             connection_attempts: 0,
             successful_connections: 0,
             last_attempt: 0,
+            link: LinkStats::default(),
+            connection_quality: 0,
+            node_addr: None,
         }
     }
 
@@ -101,6 +179,12 @@ impl PeerInfo {
         self
     }
 
+    /// Set the peer's network address
+    pub fn with_node_addr(mut self, node_addr: NodeAddrBytes) -> Self {
+        self.node_addr = Some(node_addr);
+        self
+    }
+
     /// Add a realm to the shared_realms list if not already present
     pub fn add_realm(&mut self, realm_id: RealmId) {
         if !self.shared_realms.contains(&realm_id) {
@@ -140,6 +224,18 @@ impl PeerInfo {
         }
     }
 
+    /// Record a keepalive round trip and rescore the connection
+    pub fn record_rtt_sample(&mut self, rtt: Duration, direct: bool) {
+        self.link.record_rtt(rtt, direct);
+        self.connection_quality = self.link.quality();
+    }
+
+    /// Record an unanswered keepalive and rescore the connection
+    pub fn record_probe_loss(&mut self) {
+        self.link.record_loss();
+        self.connection_quality = self.link.quality();
+    }
+
     /// Calculate the number of consecutive failures
     fn consecutive_failures(&self) -> u32 {
         // If we have no attempts or no successes, count all attempts as failures
@@ -189,6 +285,20 @@ impl PeerInfo {
         }
     }
 
+    /// Current reconnection backoff state
+    pub fn backoff(&self) -> PeerBackoff {
+        let delay_secs = self.backoff_delay();
+        PeerBackoff {
+            consecutive_failures: self.consecutive_failures(),
+            delay_secs,
+            next_attempt_at: if self.last_attempt == 0 {
+                0
+            } else {
+                self.last_attempt.saturating_add(delay_secs)
+            },
+        }
+    }
+
     /// Check if enough time has passed since last attempt to retry
     pub fn should_retry_now(&self) -> bool {
         if self.last_attempt == 0 {
@@ -207,26 +317,16 @@ impl PeerInfo {
 /// Peer registry for managing discovered peers
 #[derive(Clone)]
 pub struct PeerRegistry {
-    db: Arc<RwLock<Database>>,
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl PeerRegistry {
-    /// Create a new peer registry using the same database as Storage
+    /// Create a new peer registry using the same backend as Storage
     ///
-    /// This reuses the existing database connection to avoid having multiple
-    /// database instances pointing at the same file.
-    pub fn new(db: Arc<RwLock<Database>>) -> Result<Self, SyncError> {
-        // Initialize the peers table if it doesn't exist
-        {
-            let database = db.read();
-            let write_txn = database.begin_write()?;
-            {
-                let _ = write_txn.open_table(PEERS_TABLE)?;
-            }
-            write_txn.commit()?;
-        }
-
-        Ok(Self { db })
+    /// This reuses the existing backend to avoid having multiple database
+    /// instances pointing at the same file.
+    pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
+        Self { backend }
     }
 
     /// Add or update a peer in the registry
@@ -234,32 +334,25 @@ impl PeerRegistry {
     /// If the peer already exists, updates the last_seen timestamp and status.
     /// If adding a realm, it will be added to the shared_realms list.
     pub fn add_or_update(&self, peer_info: &PeerInfo) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PEERS_TABLE)?;
+        // Serialize the peer info
+        let data = postcard::to_allocvec(peer_info)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
 
-            // Serialize the peer info
-            let data = postcard::to_allocvec(peer_info)
-                .map_err(|e| SyncError::Serialization(e.to_string()))?;
-
-            // Use endpoint_id as key
-            table.insert(&peer_info.endpoint_id[..], data.as_slice())?;
-        }
+        // Use endpoint_id as key
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(PEERS_TABLE, &hex::encode(peer_info.endpoint_id), &data)?;
         write_txn.commit()?;
         Ok(())
     }
 
     /// Get a peer's info by endpoint ID
     pub fn get(&self, endpoint_id: &PublicKey) -> Result<Option<PeerInfo>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PEERS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        let key = endpoint_id.as_bytes();
-        match table.get(&key[..])? {
+        let key = hex::encode(endpoint_id.as_bytes());
+        match read_txn.get(PEERS_TABLE, &key)? {
             Some(v) => {
-                let peer_info: PeerInfo = postcard::from_bytes(v.value())
+                let peer_info: PeerInfo = postcard::from_bytes(&v)
                     .map_err(|e| SyncError::Serialization(e.to_string()))?;
                 Ok(Some(peer_info))
             }
@@ -269,14 +362,11 @@ impl PeerRegistry {
 
     /// List all peers in the registry
     pub fn list_all(&self) -> Result<Vec<PeerInfo>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PEERS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut peers = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let peer_info: PeerInfo = postcard::from_bytes(value.value())
+        for (_, value) in read_txn.iter(PEERS_TABLE)? {
+            let peer_info: PeerInfo = postcard::from_bytes(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             peers.push(peer_info);
         }
@@ -342,6 +432,73 @@ impl PeerRegistry {
         Ok(())
     }
 
+    /// Get a peer's reconnection backoff state
+    pub fn backoff_state(&self, endpoint_id: &PublicKey) -> Result<Option<PeerBackoff>, SyncError> {
+        Ok(self.get(endpoint_id)?.map(|p| p.backoff()))
+    }
+
+    /// Record a keepalive round trip for a peer
+    ///
+    /// Returns the peer's updated connection quality, or `None` if the peer
+    /// is not in the registry.
+    pub fn record_rtt_sample(
+        &self,
+        endpoint_id: &PublicKey,
+        rtt: Duration,
+        direct: bool,
+    ) -> Result<Option<u8>, SyncError> {
+        let Some(mut peer_info) = self.get(endpoint_id)? else {
+            return Ok(None);
+        };
+        peer_info.record_rtt_sample(rtt, direct);
+        self.add_or_update(&peer_info)?;
+        Ok(Some(peer_info.connection_quality))
+    }
+
+    /// Record an unanswered keepalive for a peer
+    ///
+    /// Returns the peer's updated connection quality, or `None` if the peer
+    /// is not in the registry.
+    pub fn record_probe_loss(&self, endpoint_id: &PublicKey) -> Result<Option<u8>, SyncError> {
+        let Some(mut peer_info) = self.get(endpoint_id)? else {
+            return Ok(None);
+        };
+        peer_info.record_probe_loss();
+        self.add_or_update(&peer_info)?;
+        Ok(Some(peer_info.connection_quality))
+    }
+
+    /// Append to a peer's connection history
+    ///
+    /// Only the last [`MAX_CONNECTION_EVENTS`] events are kept.
+    pub fn record_connection_event(
+        &self,
+        endpoint_id: &PublicKey,
+        event: ConnectionEvent,
+    ) -> Result<(), SyncError> {
+        let mut events = self.connection_log(endpoint_id)?;
+        events.push(event);
+        let excess = events.len().saturating_sub(MAX_CONNECTION_EVENTS);
+        events.drain(..excess);
+
+        let data =
+            postcard::to_allocvec(&events).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(PEER_CONNECTIONS_TABLE, &hex::encode(endpoint_id.as_bytes()), &data)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// A peer's connection history, oldest first
+    pub fn connection_log(&self, endpoint_id: &PublicKey) -> Result<Vec<ConnectionEvent>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+
+        match read_txn.get(PEER_CONNECTIONS_TABLE, &hex::encode(endpoint_id.as_bytes()))? {
+            Some(v) => postcard::from_bytes(&v).map_err(|e| SyncError::Serialization(e.to_string())),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Count total peers in registry
     pub fn count(&self) -> Result<usize, SyncError> {
         Ok(self.list_all()?.len())
@@ -356,14 +513,14 @@ impl PeerRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::RedbBackend;
     use iroh::SecretKey;
     use tempfile::TempDir;
 
     fn create_test_registry() -> (PeerRegistry, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.redb");
-        let db = Database::create(&db_path).unwrap();
-        let registry = PeerRegistry::new(Arc::new(RwLock::new(db))).unwrap();
+        let registry = PeerRegistry::new(Arc::new(RedbBackend::create(&db_path).unwrap()));
         (registry, temp_dir)
     }
 
@@ -606,8 +763,7 @@ mod tests {
 
         // Create first registry instance and add peer
         {
-            let db = Database::create(&db_path).unwrap();
-            let registry = PeerRegistry::new(Arc::new(RwLock::new(db))).unwrap();
+            let registry = PeerRegistry::new(Arc::new(RedbBackend::create(&db_path).unwrap()));
             let peer = PeerInfo::new(endpoint_id, PeerSource::FromInvite)
                 .with_nickname("Love")
                 .with_status(PeerStatus::Online);
@@ -616,8 +772,7 @@ mod tests {
 
         // Create second registry instance and verify peer exists
         {
-            let db = Database::create(&db_path).unwrap();
-            let registry = PeerRegistry::new(Arc::new(RwLock::new(db))).unwrap();
+            let registry = PeerRegistry::new(Arc::new(RedbBackend::create(&db_path).unwrap()));
             let retrieved = registry.get(&endpoint_id).unwrap();
             assert!(retrieved.is_some());
             let retrieved = retrieved.unwrap();
@@ -834,6 +989,49 @@ mod tests {
         assert!(peer.should_retry_now());
     }
 
+    #[test]
+    fn test_connection_log_keeps_recent_events_in_order() {
+        let (registry, _temp) = create_test_registry();
+        let endpoint_id = create_test_public_key();
+        let realm_id = RealmId::new();
+        assert!(registry.connection_log(&endpoint_id).unwrap().is_empty());
+
+        // A flapping peer: up over a relay, down, up directly, down
+        let simulated = [
+            (ConnectionChange::Connected, Some(false)),
+            (ConnectionChange::Disconnected, None),
+            (ConnectionChange::Connected, Some(true)),
+            (ConnectionChange::Disconnected, None),
+        ];
+        for (i, (change, direct)) in simulated.iter().enumerate() {
+            let mut event = ConnectionEvent::now(*change)
+                .with_direct(*direct)
+                .with_realm(realm_id.clone());
+            event.at_ms = 1_000 + i as u64;
+            registry.record_connection_event(&endpoint_id, event).unwrap();
+        }
+
+        let log = registry.connection_log(&endpoint_id).unwrap();
+        let recorded: Vec<_> = log.iter().map(|e| (e.change, e.direct)).collect();
+        assert_eq!(recorded, simulated);
+        assert!(log.windows(2).all(|w| w[0].at_ms < w[1].at_ms));
+        assert!(log.iter().all(|e| e.realm_id.as_ref() == Some(&realm_id)));
+
+        // Other peers have their own history
+        assert!(registry.connection_log(&create_test_public_key()).unwrap().is_empty());
+
+        // The log is a ring buffer: the oldest events make way for new ones
+        for i in 0..MAX_CONNECTION_EVENTS as u64 {
+            let mut event = ConnectionEvent::now(ConnectionChange::Connected);
+            event.at_ms = 10_000 + i;
+            registry.record_connection_event(&endpoint_id, event).unwrap();
+        }
+        let log = registry.connection_log(&endpoint_id).unwrap();
+        assert_eq!(log.len(), MAX_CONNECTION_EVENTS);
+        assert_eq!(log[0].at_ms, 10_000);
+        assert_eq!(log.last().unwrap().at_ms, 10_000 + MAX_CONNECTION_EVENTS as u64 - 1);
+    }
+
     #[test]
     fn test_connection_metrics_persist() {
         let (registry, _temp) = create_test_registry();
@@ -855,4 +1053,51 @@ mod tests {
         assert_eq!(retrieved.successful_connections, 2);
         assert_eq!(retrieved.last_attempt, 123456);
     }
+
+    #[test]
+    fn test_connection_quality_tracks_rtt_samples() {
+        let (registry, _temp) = create_test_registry();
+        let endpoint_id = create_test_public_key();
+        registry
+            .add_or_update(&PeerInfo::new(endpoint_id, PeerSource::FromInvite))
+            .unwrap();
+
+        // Unmeasured peers score zero
+        assert_eq!(registry.get(&endpoint_id).unwrap().unwrap().connection_quality, 0);
+
+        // Healthy direct link
+        for _ in 0..4 {
+            registry
+                .record_rtt_sample(&endpoint_id, Duration::from_millis(20), true)
+                .unwrap();
+        }
+        let healthy = registry.get(&endpoint_id).unwrap().unwrap().connection_quality;
+        assert_eq!(healthy, 100);
+
+        // Link degrades: slow, relayed, and dropping probes
+        for _ in 0..4 {
+            registry
+                .record_rtt_sample(&endpoint_id, Duration::from_millis(600), false)
+                .unwrap();
+            registry.record_probe_loss(&endpoint_id).unwrap();
+        }
+        let degraded = registry.get(&endpoint_id).unwrap().unwrap();
+        assert!(degraded.connection_quality < healthy);
+        assert!(degraded.link.loss_permille > 0);
+        assert!(!degraded.link.direct);
+
+        // Recovers once direct low-latency samples come back
+        for _ in 0..8 {
+            registry
+                .record_rtt_sample(&endpoint_id, Duration::from_millis(15), true)
+                .unwrap();
+        }
+        let recovered = registry.get(&endpoint_id).unwrap().unwrap();
+        assert!(recovered.connection_quality > degraded.connection_quality);
+        assert!(recovered.link.direct);
+
+        // Unknown peers are ignored
+        let stranger = create_test_public_key();
+        assert_eq!(registry.record_probe_loss(&stranger).unwrap(), None);
+    }
 }
@@ -78,6 +78,56 @@ pub enum ForkDetection {
     },
 }
 
+/// Why a stored log stops being trustworthy at some sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogBreakKind {
+    /// No packet is stored at this sequence
+    Missing,
+    /// The stored bytes don't decode, or decode to another log's packet
+    Unreadable,
+    /// The packet's signature doesn't verify against the owner's keys
+    BadSignature,
+    /// The packet's prev_hash doesn't match the packet before it
+    ChainMismatch,
+}
+
+/// First place a stored log's hash chain breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogBreak {
+    /// Sequence of the first packet that can't be trusted
+    pub sequence: u64,
+    /// What is wrong with it
+    pub kind: LogBreakKind,
+}
+
+/// Result of walking a stored log from genesis to its head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogIntegrityReport {
+    /// Owner of the log
+    pub did: Did,
+    /// Highest stored sequence
+    pub head: Option<u64>,
+    /// Packets that checked out before the first break (or all of them)
+    pub checked: u64,
+    /// The first break, if any
+    pub first_break: Option<LogBreak>,
+}
+
+impl LogIntegrityReport {
+    /// Check if every packet up to the head checked out.
+    pub fn is_intact(&self) -> bool {
+        self.first_break.is_none()
+    }
+
+    /// Highest sequence up to which the chain is intact.
+    pub fn last_intact(&self) -> Option<u64> {
+        match self.first_break {
+            Some(b) => b.sequence.checked_sub(1),
+            None => self.head,
+        }
+    }
+}
+
 /// Append-only log for a profile.
 ///
 /// Maintains a linear sequence of packets with hash chain integrity.
@@ -9,72 +9,67 @@
 //! ## Storage Schema
 //!
 //! ```text
-//! PROFILE_LOGS table: (did_str, sequence) -> PacketEnvelope bytes
-//! LOG_HEADS table: did_str -> latest_sequence
+//! PROFILE_LOGS table: (log, sequence) -> PacketEnvelope bytes
+//! LOG_HEADS table: log -> latest_sequence
+//! PROVISIONAL_PACKETS table: (log, sequence) -> () for unverified packets
 //! ```
 //!
+//! A profile's log is named by its DID. An identity shared by several
+//! devices has one log per device: the primary device's under the DID, the
+//! others' under `"{did}#{device}"`. Methods taking only a DID work on the
+//! primary log.
+//!
 //! ## Mirror vs Own Log
 //!
 //! - **Own log**: Append-only, we create packets, sign them
 //! - **Mirror**: Read-only copy of another profile's log, we verify packets
 
 use crate::error::SyncError;
-use crate::identity::Did;
-use parking_lot::RwLock;
-use redb::{Database, ReadableTable, TableDefinition};
+use crate::identity::{DeviceId, Did, DEVICE_ID_SIZE};
+use crate::storage::{ReadTxn, StorageBackend};
 use std::sync::Arc;
 use tracing::{debug, info};
 
 use super::keys::ProfilePublicKeys;
 use super::packet::PacketEnvelope;
-use super::log::{ProfileLog, ForkDetection};
+use super::log::{ForkDetection, LogBreak, LogBreakKind, LogIntegrityReport, ProfileLog};
 
 /// Table for storing packet envelopes
-/// Key: "{did}:{sequence}" (e.g., "did:sync:z123:42")
+/// Key: "{log}:{sequence}" (e.g., "did:sync:z123:42")
 /// Value: Serialized PacketEnvelope bytes
-pub(crate) const PROFILE_LOGS_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("profile_logs");
+pub(crate) const PROFILE_LOGS_TABLE: &str = "profile_logs";
 
 /// Table for storing log head sequence numbers
-/// Key: did string
+/// Key: log name (the DID, or "{did}#{device}" for a linked device)
 /// Value: Latest sequence number (as 8-byte LE u64)
-pub(crate) const LOG_HEADS_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("log_heads");
+pub(crate) const LOG_HEADS_TABLE: &str = "log_heads";
 
 /// Table for indexing packets by recipient (for offline relay delivery)
 /// Key: "{recipient_did}:{packet_hash_hex}"
-/// Value: Serialized (sender_did_str, sequence) for lookup in PROFILE_LOGS
-pub(crate) const PACKETS_FOR_RECIPIENT_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("packets_for_recipient");
+/// Value: Serialized (sender log name, sequence) for lookup in PROFILE_LOGS
+pub(crate) const PACKETS_FOR_RECIPIENT_TABLE: &str = "packets_for_recipient";
+
+/// Table flagging packets stored before their signature could be checked
+/// Key: "{log}:{sequence}", as in PROFILE_LOGS
+/// Value: empty
+pub(crate) const PROVISIONAL_PACKETS_TABLE: &str = "provisional_packets";
 
 /// Storage for profile mirrors.
 ///
 /// Provides persistence for profile logs, both owned and mirrored from others.
 pub struct MirrorStore {
-    db: Arc<RwLock<Database>>,
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl MirrorStore {
-    /// Create a new mirror store with a shared database handle.
-    pub fn new(db: Arc<RwLock<Database>>) -> Result<Self, SyncError> {
-        // Initialize tables
-        {
-            let db_guard = db.read();
-            let write_txn = db_guard.begin_write()?;
-            {
-                let _ = write_txn.open_table(PROFILE_LOGS_TABLE)?;
-                let _ = write_txn.open_table(LOG_HEADS_TABLE)?;
-                let _ = write_txn.open_table(PACKETS_FOR_RECIPIENT_TABLE)?;
-            }
-            write_txn.commit()?;
-        }
-
-        Ok(Self { db })
+    /// Create a new mirror store on a shared storage backend.
+    pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
+        Self { backend }
     }
 
-    /// Get the database handle.
-    pub fn db_handle(&self) -> Arc<RwLock<Database>> {
-        self.db.clone()
+    /// Get the storage backend.
+    pub fn backend(&self) -> Arc<dyn StorageBackend> {
+        self.backend.clone()
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -86,9 +81,21 @@ impl MirrorStore {
     /// This stores the packet and updates the log head if this is the newest packet.
     /// Returns the fork detection result.
     pub fn store_packet(&self, envelope: &PacketEnvelope) -> Result<ForkDetection, SyncError> {
+        self.store(envelope, false)
+    }
+
+    /// Store a packet whose signature we could not check yet, flagged as
+    /// provisional until [`confirm_packet`](Self::confirm_packet) or
+    /// [`remove_packet`](Self::remove_packet) settles it.
+    pub fn store_provisional_packet(&self, envelope: &PacketEnvelope) -> Result<ForkDetection, SyncError> {
+        self.store(envelope, true)
+    }
+
+    fn store(&self, envelope: &PacketEnvelope, provisional: bool) -> Result<ForkDetection, SyncError> {
         let did_str = envelope.sender.as_str();
+        let log = log_name(did_str, &envelope.device);
         let sequence = envelope.sequence;
-        let key = format_packet_key(did_str, sequence);
+        let key = format_packet_key(&log, sequence);
         let new_hash = envelope.hash();
 
         // DIAGNOSTIC: Info level with DID bytes for key comparison debugging
@@ -104,15 +111,10 @@ impl MirrorStore {
         // Serialize envelope
         let bytes = envelope.encode()?;
 
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
+        let mut write_txn = self.backend.begin_write()?;
 
         // First, check for existing entry (read operation)
-        let existing_data: Option<Vec<u8>> = {
-            let logs_table = write_txn.open_table(PROFILE_LOGS_TABLE)?;
-            let result = logs_table.get(key.as_str())?.map(|v| v.value().to_vec());
-            result
-        };
+        let existing_data = write_txn.get(PROFILE_LOGS_TABLE, &key)?;
 
         // Now handle based on whether entry exists
         let fork_result = if let Some(existing_bytes) = existing_data {
@@ -132,19 +134,15 @@ impl MirrorStore {
             }
         } else {
             // Store new packet (write operations)
-            {
-                let mut logs_table = write_txn.open_table(PROFILE_LOGS_TABLE)?;
-                logs_table.insert(key.as_str(), bytes.as_slice())?;
-            }
+            write_txn.insert(PROFILE_LOGS_TABLE, &key, &bytes)?;
 
             // Index packet by recipient for relay forwarding
             // Only index non-global packets (those with specific recipients)
             if !envelope.is_global() {
-                let mut recipient_index = write_txn.open_table(PACKETS_FOR_RECIPIENT_TABLE)?;
                 for recipient_did in envelope.recipients() {
                     let index_key = format_recipient_index_key(recipient_did.as_str(), &new_hash);
-                    let value_data = format_recipient_index_value(did_str, sequence);
-                    recipient_index.insert(index_key.as_str(), value_data.as_slice())?;
+                    let value_data = format_recipient_index_value(&log, sequence);
+                    write_txn.insert(PACKETS_FOR_RECIPIENT_TABLE, &index_key, &value_data)?;
                     debug!(
                         recipient = %recipient_did,
                         sender = %did_str,
@@ -154,13 +152,14 @@ impl MirrorStore {
                 }
             }
 
+            if provisional {
+                write_txn.insert(PROVISIONAL_PACKETS_TABLE, &key, &[])?;
+            }
+
             // Update head if this is the newest
-            {
-                let mut heads_table = write_txn.open_table(LOG_HEADS_TABLE)?;
-                let current_head = self.get_head_sequence_from_table(&heads_table, did_str)?;
-                if current_head.map(|h| sequence > h).unwrap_or(true) {
-                    heads_table.insert(did_str, &sequence.to_le_bytes()[..])?;
-                }
+            let current_head = head_sequence(&*write_txn, &log)?;
+            if current_head.map(|h| sequence > h).unwrap_or(true) {
+                write_txn.insert(LOG_HEADS_TABLE, &log, &sequence.to_le_bytes())?;
             }
 
             ForkDetection::NoFork
@@ -172,28 +171,123 @@ impl MirrorStore {
 
     /// Load a packet by DID and sequence.
     pub fn get_packet(&self, did: &Did, sequence: u64) -> Result<Option<PacketEnvelope>, SyncError> {
-        let key = format_packet_key(did.as_str(), sequence);
+        self.get_device_packet(did, &DeviceId::PRIMARY, sequence)
+    }
+
+    /// Load a packet from the log of one of a DID's devices.
+    pub fn get_device_packet(
+        &self,
+        did: &Did,
+        device: &DeviceId,
+        sequence: u64,
+    ) -> Result<Option<PacketEnvelope>, SyncError> {
+        let key = format_packet_key(&log_name(did.as_str(), device), sequence);
 
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PROFILE_LOGS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        match table.get(key.as_str())? {
+        match read_txn.get(PROFILE_LOGS_TABLE, &key)? {
             Some(v) => {
-                let envelope = PacketEnvelope::decode(v.value())?;
+                let envelope = PacketEnvelope::decode(&v)?;
                 Ok(Some(envelope))
             }
             None => Ok(None),
         }
     }
 
+    /// Whether a stored packet is still flagged provisional.
+    pub fn is_provisional(&self, did: &Did, sequence: u64) -> Result<bool, SyncError> {
+        let key = format_packet_key(did.as_str(), sequence);
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(PROVISIONAL_PACKETS_TABLE, &key)?.is_some())
+    }
+
+    /// All packets flagged provisional, as (sender, device, sequence).
+    pub fn provisional_packets(&self) -> Result<Vec<(Did, DeviceId, u64)>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+
+        let mut packets = Vec::new();
+        for (key, _) in read_txn.iter(PROVISIONAL_PACKETS_TABLE)? {
+            packets.push(parse_packet_key(&key)?);
+        }
+        Ok(packets)
+    }
+
+    /// Clear a packet's provisional flag once its signature checked out.
+    pub fn confirm_packet(&self, did: &Did, device: &DeviceId, sequence: u64) -> Result<(), SyncError> {
+        let key = format_packet_key(&log_name(did.as_str(), device), sequence);
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(PROVISIONAL_PACKETS_TABLE, &key)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Remove one packet, e.g. a provisional packet whose signature turned
+    /// out to be forged.
+    ///
+    /// Also drops its provisional flag and relay index entries, and moves
+    /// the log head back if it was the newest packet. Returns whether the
+    /// packet was there.
+    pub fn remove_packet(&self, did: &Did, device: &DeviceId, sequence: u64) -> Result<bool, SyncError> {
+        let log = log_name(did.as_str(), device);
+        let key = format_packet_key(&log, sequence);
+
+        let mut write_txn = self.backend.begin_write()?;
+        let Some(bytes) = write_txn.remove(PROFILE_LOGS_TABLE, &key)? else {
+            return Ok(false);
+        };
+        let envelope = PacketEnvelope::decode(&bytes)?;
+
+        write_txn.remove(PROVISIONAL_PACKETS_TABLE, &key)?;
+
+        if !envelope.is_global() {
+            let hash = envelope.hash();
+            for recipient_did in envelope.recipients() {
+                let index_key = format_recipient_index_key(recipient_did.as_str(), &hash);
+                write_txn.remove(PACKETS_FOR_RECIPIENT_TABLE, &index_key)?;
+            }
+        }
+
+        if head_sequence(&*write_txn, &log)? == Some(sequence) {
+            let mut previous = None;
+            for seq in (0..sequence).rev() {
+                if write_txn.get(PROFILE_LOGS_TABLE, &format_packet_key(&log, seq))?.is_some() {
+                    previous = Some(seq);
+                    break;
+                }
+            }
+            match previous {
+                Some(seq) => {
+                    write_txn.insert(LOG_HEADS_TABLE, &log, &seq.to_le_bytes())?;
+                }
+                None => {
+                    write_txn.remove(LOG_HEADS_TABLE, &log)?;
+                }
+            }
+        }
+        write_txn.commit()?;
+        Ok(true)
+    }
+
     /// Get the head sequence for a DID.
     pub fn get_head(&self, did: &Did) -> Result<Option<u64>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(LOG_HEADS_TABLE)?;
+        self.get_device_head(did, &DeviceId::PRIMARY)
+    }
+
+    /// Get the head sequence of one of a DID's device logs.
+    pub fn get_device_head(&self, did: &Did, device: &DeviceId) -> Result<Option<u64>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        head_sequence(&*read_txn, &log_name(did.as_str(), device))
+    }
 
-        self.get_head_sequence_from_table(&table, did.as_str())
+    /// Devices other than the primary that we hold a log for.
+    pub fn linked_devices(&self, did: &Did) -> Result<Vec<DeviceId>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        let (start, end) = device_logs_range(did.as_str());
+        read_txn
+            .range(LOG_HEADS_TABLE, Some(&start), Some(&end))?
+            .into_iter()
+            .map(|(log, _)| parse_log_name(&log).map(|(_, device)| device))
+            .collect()
     }
 
     /// Get packets in a range (inclusive).
@@ -203,15 +297,25 @@ impl MirrorStore {
         from: u64,
         to: u64,
     ) -> Result<Vec<PacketEnvelope>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PROFILE_LOGS_TABLE)?;
+        self.get_device_range(did, &DeviceId::PRIMARY, from, to)
+    }
+
+    /// Get packets in a range (inclusive) of one of a DID's device logs.
+    pub fn get_device_range(
+        &self,
+        did: &Did,
+        device: &DeviceId,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<PacketEnvelope>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        let log = log_name(did.as_str(), device);
 
         let mut result = Vec::new();
         for seq in from..=to {
-            let key = format_packet_key(did.as_str(), seq);
-            if let Some(v) = table.get(key.as_str())? {
-                let envelope = PacketEnvelope::decode(v.value())?;
+            let key = format_packet_key(&log, seq);
+            if let Some(v) = read_txn.get(PROFILE_LOGS_TABLE, &key)? {
+                let envelope = PacketEnvelope::decode(&v)?;
                 result.push(envelope);
             }
         }
@@ -304,24 +408,39 @@ impl MirrorStore {
         }
     }
 
+    /// Get all packets of every device log of a DID, primary log first.
+    pub fn get_all_devices(&self, did: &Did) -> Result<Vec<PacketEnvelope>, SyncError> {
+        let mut packets = self.get_all(did)?;
+        for device in self.linked_devices(did)? {
+            if let Some(head) = self.get_device_head(did, &device)? {
+                packets.extend(self.get_device_range(did, &device, 0, head)?);
+            }
+        }
+        Ok(packets)
+    }
+
+    /// Get packets after `since` (exclusive), or the whole log for `None`.
+    ///
+    /// Pairs with [`get_head`](Self::get_head): pass a peer's head to get
+    /// exactly the packets they are missing.
+    pub fn get_after(&self, did: &Did, since: Option<u64>) -> Result<Vec<PacketEnvelope>, SyncError> {
+        match since {
+            Some(sequence) => self.get_since(did, sequence),
+            None => self.get_all(did),
+        }
+    }
+
     /// Delete packets before a given sequence (for garbage collection).
     pub fn delete_before(&self, did: &Did, sequence: u64) -> Result<usize, SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
+        let mut write_txn = self.backend.begin_write()?;
 
-        let deleted = {
-            let mut table = write_txn.open_table(PROFILE_LOGS_TABLE)?;
-            let mut count = 0;
-
-            for seq in 0..sequence {
-                let key = format_packet_key(did.as_str(), seq);
-                if table.remove(key.as_str())?.is_some() {
-                    count += 1;
-                }
+        let mut deleted = 0;
+        for seq in 0..sequence {
+            let key = format_packet_key(did.as_str(), seq);
+            if write_txn.remove(PROFILE_LOGS_TABLE, &key)?.is_some() {
+                deleted += 1;
             }
-
-            count
-        };
+        }
 
         write_txn.commit()?;
         Ok(deleted)
@@ -335,15 +454,20 @@ impl MirrorStore {
     ///
     /// Returns a ProfileLog populated with all stored packets for the given DID.
     pub fn load_log(&self, did: &Did) -> Result<ProfileLog, SyncError> {
+        self.load_device_log(did, &DeviceId::PRIMARY)
+    }
+
+    /// Load the log one of a DID's devices writes.
+    pub fn load_device_log(&self, did: &Did, device: &DeviceId) -> Result<ProfileLog, SyncError> {
         let mut log = ProfileLog::new(did.clone());
 
-        let head = match self.get_head(did)? {
+        let head = match self.get_device_head(did, device)? {
             Some(h) => h,
             None => return Ok(log), // Empty log
         };
 
         // Load all packets
-        let packets = self.get_range(did, 0, head)?;
+        let packets = self.get_device_range(did, device, 0, head)?;
         for packet in packets {
             // Ignore fork detection here - we're loading from storage
             let _ = log.append(packet);
@@ -354,15 +478,14 @@ impl MirrorStore {
 
     /// List all DIDs that have stored packets.
     pub fn list_mirrored_dids(&self) -> Result<Vec<Did>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(LOG_HEADS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut dids = Vec::new();
-        for entry in table.iter()? {
-            let (key, _) = entry?;
-            let did = Did::parse(key.value())?;
-            dids.push(did);
+        for (key, _) in read_txn.iter(LOG_HEADS_TABLE)? {
+            let (did, _) = parse_log_name(&key)?;
+            if !dids.contains(&did) {
+                dids.push(did);
+            }
         }
 
         Ok(dids)
@@ -370,45 +493,107 @@ impl MirrorStore {
 
     /// Count total stored packets across all mirrors.
     pub fn total_packet_count(&self) -> Result<usize, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PROFILE_LOGS_TABLE)?;
-
-        let count = table.iter()?.count();
-        Ok(count)
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.iter(PROFILE_LOGS_TABLE)?.len())
     }
 
-    /// Delete all packets for a DID.
+    /// Delete all packets for a DID, from every device log.
     pub fn delete_mirror(&self, did: &Did) -> Result<usize, SyncError> {
-        let head = match self.get_head(did)? {
-            Some(h) => h,
-            None => return Ok(0),
-        };
+        let mut devices = self.linked_devices(did)?;
+        devices.push(DeviceId::PRIMARY);
 
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
+        let mut write_txn = self.backend.begin_write()?;
+        let mut deleted = 0;
 
-        let deleted = {
-            let mut logs_table = write_txn.open_table(PROFILE_LOGS_TABLE)?;
-            let mut heads_table = write_txn.open_table(LOG_HEADS_TABLE)?;
-            let mut count = 0;
+        for device in devices {
+            let log = log_name(did.as_str(), &device);
+            let Some(head) = head_sequence(&*write_txn, &log)? else {
+                continue;
+            };
 
             // Delete all packets
             for seq in 0..=head {
-                let key = format_packet_key(did.as_str(), seq);
-                if logs_table.remove(key.as_str())?.is_some() {
-                    count += 1;
+                let key = format_packet_key(&log, seq);
+                if write_txn.remove(PROFILE_LOGS_TABLE, &key)?.is_some() {
+                    deleted += 1;
                 }
+                write_txn.remove(PROVISIONAL_PACKETS_TABLE, &key)?;
             }
 
             // Delete head entry
-            heads_table.remove(did.as_str())?;
+            write_txn.remove(LOG_HEADS_TABLE, &log)?;
+        }
+
+        write_txn.commit()?;
+        Ok(deleted)
+    }
 
-            count
+    /// Walk a stored log from genesis and report where its hash chain breaks.
+    ///
+    /// Each packet must decode, belong to this log at its sequence, verify
+    /// against `owner` and chain to the packet before it. Unlike
+    /// [`load_log`](Self::load_log), which skips what doesn't fit, this stops
+    /// at the first packet that fails.
+    pub fn check_chain(
+        &self,
+        did: &Did,
+        owner: &ProfilePublicKeys,
+    ) -> Result<LogIntegrityReport, SyncError> {
+        let head = self.get_head(did)?;
+        let mut report = LogIntegrityReport {
+            did: did.clone(),
+            head,
+            checked: 0,
+            first_break: None,
+        };
+        let Some(head) = head else {
+            return Ok(report);
         };
 
+        let mut prev_hash = [0u8; 32]; // Genesis prev_hash
+        for sequence in 0..=head {
+            let kind = match self.get_packet(did, sequence) {
+                Ok(None) => Some(LogBreakKind::Missing),
+                Err(_) => Some(LogBreakKind::Unreadable),
+                Ok(Some(envelope)) if envelope.sender != *did || envelope.sequence != sequence => {
+                    Some(LogBreakKind::Unreadable)
+                }
+                Ok(Some(envelope)) if !envelope.verify(owner) => Some(LogBreakKind::BadSignature),
+                Ok(Some(envelope)) if envelope.prev_hash != prev_hash => {
+                    Some(LogBreakKind::ChainMismatch)
+                }
+                Ok(Some(envelope)) => {
+                    prev_hash = envelope.hash();
+                    report.checked += 1;
+                    None
+                }
+            };
+            if let Some(kind) = kind {
+                report.first_break = Some(LogBreak { sequence, kind });
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Store packets over whatever is stored at their sequences.
+    ///
+    /// For repairing a damaged log from a trusted copy; callers must check
+    /// the packets first. Moves the head forward if they go past it.
+    pub fn overwrite_packets(&self, packets: &[PacketEnvelope]) -> Result<(), SyncError> {
+        let mut write_txn = self.backend.begin_write()?;
+        for envelope in packets {
+            let log = log_name(envelope.sender.as_str(), &envelope.device);
+            let key = format_packet_key(&log, envelope.sequence);
+            write_txn.insert(PROFILE_LOGS_TABLE, &key, &envelope.encode()?)?;
+
+            let current_head = head_sequence(&*write_txn, &log)?;
+            if current_head.map(|h| envelope.sequence > h).unwrap_or(true) {
+                write_txn.insert(LOG_HEADS_TABLE, &log, &envelope.sequence.to_le_bytes())?;
+            }
+        }
         write_txn.commit()?;
-        Ok(deleted)
+        Ok(())
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -421,32 +606,21 @@ impl MirrorStore {
     /// a peer who just came online. Returns packets from all senders
     /// that were encrypted to this recipient.
     pub fn get_packets_for_recipient(&self, recipient: &Did) -> Result<Vec<PacketEnvelope>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let recipient_index = read_txn.open_table(PACKETS_FOR_RECIPIENT_TABLE)?;
-        let logs_table = read_txn.open_table(PROFILE_LOGS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut packets = Vec::new();
         let recipient_str = recipient.as_str();
-        let prefix = format!("{}:", recipient_str);
+        let (start, end) = recipient_range(recipient_str);
 
         // Scan index for entries starting with "recipient_did:"
-        for entry in recipient_index.iter()? {
-            let (key, value) = entry?;
-            let key_str = key.value();
-
-            // Only process entries for this recipient
-            if !key_str.starts_with(&prefix) {
-                continue;
-            }
-
-            // Parse the stored value to get sender + sequence
-            match parse_recipient_index_value(value.value()) {
-                Ok((sender_did_str, sequence)) => {
+        for (key_str, value) in read_txn.range(PACKETS_FOR_RECIPIENT_TABLE, Some(&start), Some(&end))? {
+            // Parse the stored value to get sender log + sequence
+            match parse_recipient_index_value(&value) {
+                Ok((sender_log, sequence)) => {
                     // Look up the full packet
-                    let packet_key = format_packet_key(&sender_did_str, sequence);
-                    if let Ok(Some(packet_data)) = logs_table.get(packet_key.as_str()) {
-                        if let Ok(envelope) = PacketEnvelope::decode(packet_data.value()) {
+                    let packet_key = format_packet_key(&sender_log, sequence);
+                    if let Ok(Some(packet_data)) = read_txn.get(PROFILE_LOGS_TABLE, &packet_key) {
+                        if let Ok(envelope) = PacketEnvelope::decode(&packet_data) {
                             packets.push(envelope);
                         }
                     }
@@ -475,19 +649,15 @@ impl MirrorStore {
         recipient: &Did,
         packet_hash: &[u8; 32],
     ) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
+        let mut write_txn = self.backend.begin_write()?;
 
-        {
-            let mut recipient_index = write_txn.open_table(PACKETS_FOR_RECIPIENT_TABLE)?;
-            let index_key = format_recipient_index_key(recipient.as_str(), packet_hash);
-            recipient_index.remove(index_key.as_str())?;
-            debug!(
-                recipient = %recipient,
-                hash = %hex::encode(packet_hash),
-                "Marked packet as delivered (removed from relay index)"
-            );
-        }
+        let index_key = format_recipient_index_key(recipient.as_str(), packet_hash);
+        write_txn.remove(PACKETS_FOR_RECIPIENT_TABLE, &index_key)?;
+        debug!(
+            recipient = %recipient,
+            hash = %hex::encode(packet_hash),
+            "Marked packet as delivered (removed from relay index)"
+        );
 
         write_txn.commit()?;
         Ok(())
@@ -497,65 +667,78 @@ impl MirrorStore {
     ///
     /// Useful for testing and cleanup. Returns the number of entries removed.
     pub fn clear_recipient_relay_entries(&self, recipient: &Did) -> Result<usize, SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-
-        let count = {
-            let mut recipient_index = write_txn.open_table(PACKETS_FOR_RECIPIENT_TABLE)?;
-            let recipient_str = recipient.as_str();
-            let prefix = format!("{}:", recipient_str);
-
-            // Collect keys to remove (can't modify while iterating)
-            let keys_to_remove: Vec<String> = recipient_index
-                .iter()?
-                .filter_map(|entry| {
-                    entry.ok().and_then(|(key, _)| {
-                        let key_str = key.value();
-                        if key_str.starts_with(&prefix) {
-                            Some(key_str.to_string())
-                        } else {
-                            None
-                        }
-                    })
-                })
-                .collect();
+        let mut write_txn = self.backend.begin_write()?;
 
-            let mut deleted = 0;
-            for key in keys_to_remove {
-                if recipient_index.remove(key.as_str())?.is_some() {
-                    deleted += 1;
-                }
-            }
-            deleted
-        };
+        let (start, end) = recipient_range(recipient.as_str());
+        let entries = write_txn.range(PACKETS_FOR_RECIPIENT_TABLE, Some(&start), Some(&end))?;
+        let count = entries.len();
+        write_txn.remove_range(PACKETS_FOR_RECIPIENT_TABLE, Some(&start), Some(&end))?;
 
         write_txn.commit()?;
         Ok(count)
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // Internal Helpers
-    // ═══════════════════════════════════════════════════════════════════════
+}
 
-    fn get_head_sequence_from_table<T: redb::ReadableTable<&'static str, &'static [u8]>>(
-        &self,
-        table: &T,
-        did_str: &str,
-    ) -> Result<Option<u64>, SyncError> {
-        match table.get(did_str)? {
-            Some(v) => {
-                let bytes: [u8; 8] = v.value().try_into()
-                    .map_err(|_| SyncError::Storage("Invalid head sequence bytes".to_string()))?;
-                Ok(Some(u64::from_le_bytes(bytes)))
-            }
-            None => Ok(None),
+/// Read a log's head sequence within a transaction.
+fn head_sequence<T: ReadTxn + ?Sized>(txn: &T, log: &str) -> Result<Option<u64>, SyncError> {
+    match txn.get(LOG_HEADS_TABLE, log)? {
+        Some(v) => {
+            let bytes: [u8; 8] = v.as_slice().try_into()
+                .map_err(|_| SyncError::Storage("Invalid head sequence bytes".to_string()))?;
+            Ok(Some(u64::from_le_bytes(bytes)))
         }
+        None => Ok(None),
     }
 }
 
+/// Name of the log a DID's device writes: the DID itself for the primary
+/// device, "{did}#{device}" for the others.
+fn log_name(did_str: &str, device: &DeviceId) -> String {
+    if device.is_primary() {
+        did_str.to_string()
+    } else {
+        format!("{}#{}", did_str, device)
+    }
+}
+
+/// Split a log name back into DID and device.
+fn parse_log_name(log: &str) -> Result<(Did, DeviceId), SyncError> {
+    let Some((did, device)) = log.split_once('#') else {
+        return Ok((Did::parse(log)?, DeviceId::PRIMARY));
+    };
+    let bytes: [u8; DEVICE_ID_SIZE] = hex::decode(device)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SyncError::Storage(format!("Malformed log name: {}", log)))?;
+    Ok((Did::parse(did)?, DeviceId::from_bytes(bytes)))
+}
+
+/// Key range covering a DID's linked device logs (`'$'` sorts right after `'#'`)
+fn device_logs_range(did_str: &str) -> (String, String) {
+    (format!("{}#", did_str), format!("{}$", did_str))
+}
+
 /// Format a key for the profile logs table.
-fn format_packet_key(did_str: &str, sequence: u64) -> String {
-    format!("{}:{}", did_str, sequence)
+fn format_packet_key(log: &str, sequence: u64) -> String {
+    format!("{}:{}", log, sequence)
+}
+
+/// Split a "{log}:{sequence}" key back into its parts.
+fn parse_packet_key(key: &str) -> Result<(Did, DeviceId, u64), SyncError> {
+    let (log, sequence) = key
+        .rsplit_once(':')
+        .ok_or_else(|| SyncError::Storage(format!("Malformed packet key: {}", key)))?;
+    let sequence = sequence
+        .parse()
+        .map_err(|_| SyncError::Storage(format!("Malformed packet key: {}", key)))?;
+    let (did, device) = parse_log_name(log)?;
+    Ok((did, device, sequence))
+}
+
+/// Key range covering a recipient's index entries (`';'` sorts right after `':'`)
+fn recipient_range(recipient_did_str: &str) -> (String, String) {
+    (format!("{}:", recipient_did_str), format!("{};", recipient_did_str))
 }
 
 /// Format a key for the recipient index table.
@@ -618,15 +801,14 @@ mod tests {
     use super::*;
     use crate::profile::keys::ProfileKeys;
     use crate::profile::packet::PacketPayload;
+    use crate::storage::RedbBackend;
     use tempfile::TempDir;
-    use redb::Database;
 
     fn create_test_store() -> (MirrorStore, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.redb");
-        let db = Database::create(&db_path).unwrap();
-        let db = Arc::new(RwLock::new(db));
-        let store = MirrorStore::new(db).unwrap();
+        let backend = RedbBackend::create(&db_path).unwrap();
+        let store = MirrorStore::new(Arc::new(backend));
         (store, temp_dir)
     }
 
@@ -658,6 +840,34 @@ mod tests {
         assert_eq!(loaded.sender, keys.did());
     }
 
+    #[test]
+    fn test_provisional_flag_until_confirmed_or_removed() {
+        let (store, _temp) = create_test_store();
+        let keys = ProfileKeys::generate();
+        let did = keys.did();
+
+        let envelope0 = create_test_envelope(&keys, 0, [0u8; 32]);
+        let envelope1 = create_test_envelope(&keys, 1, envelope0.hash());
+        store.store_provisional_packet(&envelope0).unwrap();
+        store.store_provisional_packet(&envelope1).unwrap();
+        assert!(store.is_provisional(&did, 0).unwrap());
+        assert_eq!(store.provisional_packets().unwrap().len(), 2);
+
+        store.confirm_packet(&did, &DeviceId::PRIMARY, 0).unwrap();
+        assert!(!store.is_provisional(&did, 0).unwrap());
+        assert_eq!(
+            store.provisional_packets().unwrap(),
+            vec![(did.clone(), DeviceId::PRIMARY, 1)]
+        );
+
+        // Removing the newest packet moves the head back
+        assert!(store.remove_packet(&did, &DeviceId::PRIMARY, 1).unwrap());
+        assert!(!store.remove_packet(&did, &DeviceId::PRIMARY, 1).unwrap());
+        assert!(store.provisional_packets().unwrap().is_empty());
+        assert_eq!(store.get_head(&did).unwrap(), Some(0));
+        assert!(store.get_packet(&did, 1).unwrap().is_none());
+    }
+
     #[test]
     fn test_get_head() {
         let (store, _temp) = create_test_store();
@@ -818,6 +1028,34 @@ mod tests {
         assert!(store.get_packet(&keys2.did(), 0).unwrap().is_some());
     }
 
+    #[test]
+    fn test_linked_device_logs_do_not_fork_the_primary_log() {
+        let (store, _temp) = create_test_store();
+        let keys = ProfileKeys::generate();
+        let did = keys.did();
+        let device = DeviceId::generate();
+
+        let primary = create_test_envelope(&keys, 0, [0u8; 32]);
+        let linked = create_test_envelope(&keys, 0, [0u8; 32]).with_device(device, &keys);
+        assert_eq!(store.store_packet(&primary).unwrap(), ForkDetection::NoFork);
+        assert_eq!(store.store_packet(&linked).unwrap(), ForkDetection::NoFork);
+        let next = create_test_envelope(&keys, 1, linked.hash()).with_device(device, &keys);
+        store.store_provisional_packet(&next).unwrap();
+
+        assert_eq!(store.get_head(&did).unwrap(), Some(0));
+        assert_eq!(store.get_device_head(&did, &device).unwrap(), Some(1));
+        assert_eq!(store.linked_devices(&did).unwrap(), vec![device]);
+        assert_eq!(store.list_mirrored_dids().unwrap(), vec![did.clone()]);
+        assert_eq!(store.get_all(&did).unwrap().len(), 1);
+        assert_eq!(store.get_all_devices(&did).unwrap().len(), 3);
+        assert_eq!(store.load_device_log(&did, &device).unwrap().head_sequence(), Some(1));
+        assert_eq!(store.provisional_packets().unwrap(), vec![(did.clone(), device, 1)]);
+
+        assert_eq!(store.delete_mirror(&did).unwrap(), 3);
+        assert!(store.get_all_devices(&did).unwrap().is_empty());
+        assert!(store.provisional_packets().unwrap().is_empty());
+    }
+
     #[test]
     fn test_fork_detection() {
         let (store, _temp) = create_test_store();
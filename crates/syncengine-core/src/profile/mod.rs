@@ -53,7 +53,9 @@ mod topic;
 
 // Re-exports
 pub use keys::{ProfileKeys, ProfilePublicKeys};
-pub use log::{ProfileLog, LogEntry, ForkDetection, PacketBuilder};
+pub use log::{
+    ProfileLog, LogEntry, ForkDetection, PacketBuilder, LogBreak, LogBreakKind, LogIntegrityReport,
+};
 pub use mirror::MirrorStore;
 pub use packet::{PacketEnvelope, PacketPayload, PacketAddress};
 pub use sealed::{SealedBox, SealedKey, HybridKeyExchange};
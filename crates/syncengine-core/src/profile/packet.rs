@@ -19,19 +19,42 @@
 //! │  sealed_keys: Vec<SealedKey>  - Per-recipient keys      │
 //! │  nonce: [u8; 12]       - Encryption nonce               │
 //! │  ciphertext: Vec<u8>   - Encrypted PacketPayload        │
+//! │  device: DeviceId      - Which of the sender's devices  │
 //! └─────────────────────────────────────────────────────────┘
 //! ```
+//!
+//! Each device of an identity keeps its own log, so `sequence` and
+//! `prev_hash` count within the log of `device`. Packets from the primary
+//! device sign and hash exactly as they did before devices existed.
+//!
+//! The three packet kinds differ only in how the body is protected:
+//!
+//! | Kind       | `sealed_keys` | `nonce`  | `ciphertext`                          |
+//! |------------|---------------|----------|---------------------------------------|
+//! | Sealed     | per recipient | random   | payload under a random content key    |
+//! | Group      | empty         | random   | realm ID ‖ payload under the realm key |
+//! | Global     | empty         | zero     | cleartext payload                     |
 
 use crate::crypto::{RealmCrypto, NONCE_SIZE};
 use crate::error::SyncError;
-use crate::identity::{Did, HybridSignature};
-use crate::types::RealmId;
+use crate::identity::{DeviceId, Did, HybridPublicKey, HybridSignature, DEVICE_ID_SIZE};
+use crate::types::{RealmId, TaskId};
 
 use super::keys::{ProfileKeys, ProfilePublicKeys};
 use super::sealed::SealedKey;
 
 use serde::{Deserialize, Serialize};
 
+/// Context for deriving the group packet key from a realm key, so group
+/// packets and realm sync envelopes never share a key.
+const GROUP_PACKET_KEY_CONTEXT: &str = "syncengine 2025 group packet key";
+
+/// Context for deriving the key that tags group packets with their realm.
+const GROUP_PACKET_TAG_CONTEXT: &str = "syncengine 2025 group packet tag";
+
+/// Length of the realm tag at the front of a group packet's ciphertext.
+const GROUP_TAG_SIZE: usize = 32;
+
 /// Packet envelope containing signed, encrypted content.
 ///
 /// The envelope has cleartext metadata (sender, sequence, timestamp) that
@@ -54,6 +77,38 @@ pub struct PacketEnvelope {
     pub nonce: [u8; NONCE_SIZE],
     /// Encrypted payload
     pub ciphertext: Vec<u8>,
+    /// The sender's device whose log this packet belongs to
+    pub device: DeviceId,
+}
+
+/// Envelope layout from before devices, which packets stored by older
+/// versions are still in.
+#[derive(Deserialize)]
+struct LegacyEnvelope {
+    sender: Did,
+    sequence: u64,
+    prev_hash: [u8; 32],
+    timestamp: i64,
+    signature: HybridSignature,
+    sealed_keys: Vec<SealedKey>,
+    nonce: [u8; NONCE_SIZE],
+    ciphertext: Vec<u8>,
+}
+
+impl From<LegacyEnvelope> for PacketEnvelope {
+    fn from(legacy: LegacyEnvelope) -> Self {
+        Self {
+            sender: legacy.sender,
+            sequence: legacy.sequence,
+            prev_hash: legacy.prev_hash,
+            timestamp: legacy.timestamp,
+            signature: legacy.signature,
+            sealed_keys: legacy.sealed_keys,
+            nonce: legacy.nonce,
+            ciphertext: legacy.ciphertext,
+            device: DeviceId::PRIMARY,
+        }
+    }
 }
 
 impl PacketEnvelope {
@@ -115,6 +170,7 @@ impl PacketEnvelope {
             sealed_keys,
             nonce,
             ciphertext,
+            device: DeviceId::PRIMARY,
         })
     }
 
@@ -160,6 +216,62 @@ impl PacketEnvelope {
             sealed_keys,
             nonce,
             ciphertext,
+            device: DeviceId::PRIMARY,
+        })
+    }
+
+    /// Create a group packet readable by every holder of the realm key.
+    ///
+    /// The payload is encrypted with a key derived from `realm_key`. The
+    /// front of the ciphertext is a tag keyed by the realm key over the
+    /// nonce and realm ID, so members can tell which realm a packet is for
+    /// while everyone else learns nothing, not even whether two packets are
+    /// for the same realm. The envelope is signed like any other.
+    pub fn create_group(
+        sender_keys: &ProfileKeys,
+        payload: &PacketPayload,
+        realm_id: &RealmId,
+        realm_key: &[u8; 32],
+        sequence: u64,
+        prev_hash: [u8; 32],
+    ) -> Result<Self, SyncError> {
+        let sender = sender_keys.did();
+        let timestamp = chrono::Utc::now().timestamp_millis();
+
+        let payload_bytes = postcard::to_allocvec(payload)
+            .map_err(|e| SyncError::Serialization(format!("Failed to serialize payload: {}", e)))?;
+
+        // A zero nonce marks a global packet; regenerate on the (negligible) collision
+        let mut nonce = RealmCrypto::generate_nonce();
+        while nonce == [0u8; NONCE_SIZE] {
+            nonce = RealmCrypto::generate_nonce();
+        }
+        let crypto = RealmCrypto::new(&Self::group_key(realm_key));
+        let mut ciphertext = Self::group_tag(realm_id, realm_key, &nonce).as_bytes().to_vec();
+        ciphertext.extend(crypto.encrypt_with_nonce(&payload_bytes, &nonce)?);
+
+        let sealed_keys = Vec::new();
+        let sign_payload = Self::create_sign_payload(
+            &sender,
+            sequence,
+            &prev_hash,
+            timestamp,
+            &sealed_keys,
+            &nonce,
+            &ciphertext,
+        );
+        let signature = sender_keys.sign(&sign_payload);
+
+        Ok(Self {
+            sender,
+            sequence,
+            prev_hash,
+            timestamp,
+            signature,
+            sealed_keys,
+            nonce,
+            ciphertext,
+            device: DeviceId::PRIMARY,
         })
     }
 
@@ -168,28 +280,32 @@ impl PacketEnvelope {
     /// This verifies that the envelope was signed by the sender and hasn't
     /// been tampered with. It does NOT decrypt the payload.
     pub fn verify(&self, sender_public: &ProfilePublicKeys) -> bool {
+        self.verify_signing_key(&sender_public.signing)
+    }
+
+    /// Verify the envelope's signature against the sender's signing key alone.
+    ///
+    /// Same as [`verify`](Self::verify), for when only the signing key is
+    /// known (e.g. from a pinned profile) and not the full key bundle.
+    pub fn verify_signing_key(&self, signing: &HybridPublicKey) -> bool {
         // Verify sender matches
-        if sender_public.did() != self.sender {
+        if Did::from_public_key(signing) != self.sender {
             return false;
         }
 
-        let sign_payload = Self::create_sign_payload(
-            &self.sender,
-            self.sequence,
-            &self.prev_hash,
-            self.timestamp,
-            &self.sealed_keys,
-            &self.nonce,
-            &self.ciphertext,
-        );
-
-        sender_public.signing.verify(&sign_payload, &self.signature)
+        signing.verify(&self.sign_payload(), &self.signature)
     }
 
     /// Open the envelope and decrypt the payload.
     ///
     /// Returns the decrypted payload if the recipient is in the sealed keys list.
     pub fn open(&self, recipient_keys: &ProfileKeys) -> Result<PacketPayload, SyncError> {
+        if self.is_group() {
+            return Err(SyncError::Crypto(
+                "Group packet must be opened with the realm key".to_string(),
+            ));
+        }
+
         // Check if this is a global packet (no sealed keys)
         if self.sealed_keys.is_empty() {
             // Global packet - payload is not encrypted
@@ -221,9 +337,12 @@ impl PacketEnvelope {
     }
 
     /// Check if this envelope is addressed to a specific DID.
+    ///
+    /// Group packets are addressed to realm members, which can't be told
+    /// from the DID alone, so they report `false`; see [`Self::group_realm`].
     pub fn is_addressed_to(&self, did: &Did) -> bool {
         // Global packets are addressed to everyone
-        if self.sealed_keys.is_empty() {
+        if self.is_global() {
             return true;
         }
         self.sealed_keys.iter().any(|sk| &sk.recipient == did)
@@ -231,7 +350,64 @@ impl PacketEnvelope {
 
     /// Check if this is a global (public) packet.
     pub fn is_global(&self) -> bool {
-        self.sealed_keys.is_empty()
+        self.sealed_keys.is_empty() && self.nonce == [0u8; NONCE_SIZE]
+    }
+
+    /// Check if this is a group packet encrypted with a realm key.
+    pub fn is_group(&self) -> bool {
+        self.sealed_keys.is_empty() && self.nonce != [0u8; NONCE_SIZE]
+    }
+
+    /// Find the realm a group packet is addressed to among `realm_keys`.
+    ///
+    /// Returns the matching realm ID and key, or `None` for other packets and
+    /// for group packets of realms not in `realm_keys`.
+    pub fn group_realm<'a>(
+        &self,
+        realm_keys: &'a [(RealmId, [u8; 32])],
+    ) -> Option<&'a (RealmId, [u8; 32])> {
+        if !self.is_group() {
+            return None;
+        }
+        let tag: [u8; GROUP_TAG_SIZE] = self.ciphertext.get(..GROUP_TAG_SIZE)?.try_into().ok()?;
+        let tag = blake3::Hash::from(tag);
+        realm_keys
+            .iter()
+            .find(|(realm_id, realm_key)| Self::group_tag(realm_id, realm_key, &self.nonce) == tag)
+    }
+
+    /// Decrypt a group packet with the key of the realm it is addressed to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Crypto` if this is not a group packet or the key
+    /// is wrong.
+    pub fn open_group(&self, realm_key: &[u8; 32]) -> Result<PacketPayload, SyncError> {
+        if !self.is_group() || self.ciphertext.len() < GROUP_TAG_SIZE {
+            return Err(SyncError::Crypto("Not a group packet".to_string()));
+        }
+
+        let crypto = RealmCrypto::new(&Self::group_key(realm_key));
+        let plaintext = crypto.decrypt_with_nonce(&self.ciphertext[GROUP_TAG_SIZE..], &self.nonce)?;
+
+        postcard::from_bytes(&plaintext)
+            .map_err(|e| SyncError::Serialization(format!("Failed to decode payload: {}", e)))
+    }
+
+    /// Key used to encrypt group packets for the realm with `realm_key`.
+    fn group_key(realm_key: &[u8; 32]) -> [u8; 32] {
+        blake3::derive_key(GROUP_PACKET_KEY_CONTEXT, realm_key)
+    }
+
+    /// Tag naming a group packet's realm to holders of `realm_key` only.
+    ///
+    /// Keyed over the nonce too, so the tag differs for every packet.
+    fn group_tag(realm_id: &RealmId, realm_key: &[u8; 32], nonce: &[u8; NONCE_SIZE]) -> blake3::Hash {
+        let tag_key = blake3::derive_key(GROUP_PACKET_TAG_CONTEXT, realm_key);
+        let mut hasher = blake3::Hasher::new_keyed(&tag_key);
+        hasher.update(nonce);
+        hasher.update(realm_id.as_bytes());
+        hasher.finalize()
     }
 
     /// Get all recipient DIDs.
@@ -240,9 +416,39 @@ impl PacketEnvelope {
     }
 
     /// Compute the hash of this envelope (for hash chain).
+    ///
+    /// The primary device's packets hash without the trailing device ID (a
+    /// fixed-size array postcard writes as raw bytes), as they did before
+    /// devices existed.
     pub fn hash(&self) -> [u8; 32] {
         let bytes = postcard::to_allocvec(self).expect("Envelope should serialize");
-        *blake3::hash(&bytes).as_bytes()
+        let hashed = if self.device.is_primary() {
+            &bytes[..bytes.len() - DEVICE_ID_SIZE]
+        } else {
+            &bytes[..]
+        };
+        *blake3::hash(hashed).as_bytes()
+    }
+
+    /// Re-sign the envelope as a packet from another of the sender's devices.
+    ///
+    /// The constructors create packets for the primary device; a linked
+    /// device passes its own ID here before appending the packet to its log.
+    pub fn with_device(mut self, device: DeviceId, sender_keys: &ProfileKeys) -> Self {
+        if device == self.device {
+            return self;
+        }
+        self.device = device;
+        self.signature = sender_keys.sign(&self.sign_payload());
+        self
+    }
+
+    /// Short id for following this packet through the logs of every instance.
+    ///
+    /// A prefix of the envelope hash, so it is fixed when the packet is
+    /// created and identical wherever the packet is relayed or received.
+    pub fn correlation_id(&self) -> String {
+        hex::encode(&self.hash()[..8])
     }
 
     /// Create the payload to be signed.
@@ -290,6 +496,26 @@ impl PacketEnvelope {
         payload
     }
 
+    /// The bytes this envelope's signature covers.
+    ///
+    /// The device is left out for the primary device, so its signatures are
+    /// the same as before devices existed.
+    fn sign_payload(&self) -> Vec<u8> {
+        let mut payload = Self::create_sign_payload(
+            &self.sender,
+            self.sequence,
+            &self.prev_hash,
+            self.timestamp,
+            &self.sealed_keys,
+            &self.nonce,
+            &self.ciphertext,
+        );
+        if !self.device.is_primary() {
+            payload.extend_from_slice(self.device.as_bytes());
+        }
+        payload
+    }
+
     /// Encode envelope to bytes.
     pub fn encode(&self) -> Result<Vec<u8>, SyncError> {
         postcard::to_allocvec(self)
@@ -297,8 +523,12 @@ impl PacketEnvelope {
     }
 
     /// Decode envelope from bytes.
+    ///
+    /// Also reads envelopes stored before devices existed, as packets from
+    /// the primary device.
     pub fn decode(bytes: &[u8]) -> Result<Self, SyncError> {
         postcard::from_bytes(bytes)
+            .or_else(|e| postcard::from_bytes::<LegacyEnvelope>(bytes).map(Self::from).map_err(|_| e))
             .map_err(|e| SyncError::Serialization(format!("Failed to decode envelope: {}", e)))
     }
 
@@ -376,6 +606,19 @@ pub enum PacketPayload {
         realm_name: String,
     },
 
+    /// Replacement key for a realm the recipient is a member of.
+    ///
+    /// Sent to each remaining member after the realm is rekeyed. The epoch
+    /// increases with every rekey so stale keys are never re-applied.
+    RealmRekey {
+        /// The realm whose key changed
+        realm_id: RealmId,
+        /// New symmetric key for the realm
+        realm_key: [u8; 32],
+        /// Key generation this key belongs to
+        epoch: u64,
+    },
+
     /// Task/intention reference within a realm.
     TaskReference {
         /// The realm containing the task
@@ -441,6 +684,29 @@ pub enum PacketPayload {
         /// Optional: unique ID to deduplicate relay attempts
         relay_id: [u8; 16],
     },
+
+    /// Warning about a peer the sender saw misbehaving.
+    ///
+    /// Receivers lower their trust in the subject, weighted by how much they
+    /// trust the sender (see [`crate::sync::reputation`]).
+    ReputationReport {
+        /// The peer that misbehaved
+        subject_did: Did,
+        /// What it did, e.g. "invalid_signature"
+        violation: String,
+        /// How serious it was (1-10)
+        severity: u8,
+    },
+
+    /// Thanks to a contact for something they gave or did.
+    Gratitude {
+        /// The task being acknowledged, if any
+        for_task: Option<TaskId>,
+        /// Words of thanks
+        message: String,
+        /// Recipient DID (for counting given gratitude when using topic-level privacy)
+        recipient: Did,
+    },
 }
 
 /// Addressing modes for packets.
@@ -573,6 +839,48 @@ mod tests {
         assert_eq!(opened, payload);
     }
 
+    #[test]
+    fn test_packet_envelope_group_needs_realm_key() {
+        let sender_keys = ProfileKeys::generate();
+        let realm_id = RealmId::new();
+        let realm_key = RealmCrypto::generate_key();
+
+        let payload = PacketPayload::TaskReference {
+            realm_id: realm_id.clone(),
+            task_id: "task-1".to_string(),
+            description: "members only".to_string(),
+        };
+        let envelope =
+            PacketEnvelope::create_group(&sender_keys, &payload, &realm_id, &realm_key, 1, [0u8; 32])
+                .expect("Should create group envelope");
+
+        assert!(envelope.is_group());
+        assert!(!envelope.is_global());
+        assert!(envelope.verify(&sender_keys.public_bundle()));
+
+        // Only the realm key links the packet to its realm
+        let other = (RealmId::new(), RealmCrypto::generate_key());
+        let member_keys = [other.clone(), (realm_id.clone(), realm_key)];
+        assert_eq!(envelope.group_realm(&member_keys), Some(&member_keys[1]));
+        assert_eq!(envelope.group_realm(&[other, (realm_id.clone(), RealmCrypto::generate_key())]), None);
+        assert!(!envelope.ciphertext.windows(32).any(|w| w == realm_id.as_bytes()));
+        let again =
+            PacketEnvelope::create_group(&sender_keys, &payload, &realm_id, &realm_key, 2, envelope.hash())
+                .expect("Should create group envelope");
+        assert_ne!(again.ciphertext[..32], envelope.ciphertext[..32]);
+
+        // Without the realm key the payload can't be read
+        let outsider = ProfileKeys::generate();
+        assert!(!envelope.is_addressed_to(&outsider.did()));
+        assert!(envelope.open(&outsider).is_err());
+        assert!(envelope.decode_global_payload().is_err());
+        assert!(envelope.open_group(&RealmCrypto::generate_key()).is_err());
+        let plaintext = postcard::to_allocvec(&payload).unwrap();
+        assert!(!envelope.ciphertext.windows(plaintext.len()).any(|w| w == plaintext));
+
+        assert_eq!(envelope.open_group(&realm_key).expect("Member should open"), payload);
+    }
+
     #[test]
     fn test_packet_envelope_non_recipient_cannot_open() {
         let sender_keys = ProfileKeys::generate();
@@ -681,6 +989,35 @@ mod tests {
         assert_eq!(opened, payload);
     }
 
+    #[test]
+    fn test_packet_envelope_from_linked_device() {
+        let sender_keys = ProfileKeys::generate();
+        let payload = PacketPayload::Heartbeat { timestamp: 1 };
+        let primary = PacketEnvelope::create_global(&sender_keys, &payload, 0, [0u8; 32]).unwrap();
+
+        // Envelopes stored before devices existed decode as primary packets
+        // and keep their hash
+        let encoded = primary.encode().unwrap();
+        let legacy = PacketEnvelope::decode(&encoded[..encoded.len() - DEVICE_ID_SIZE]).unwrap();
+        assert_eq!(legacy.device, DeviceId::PRIMARY);
+        assert_eq!(legacy.hash(), primary.hash());
+        assert!(legacy.verify(&sender_keys.public_bundle()));
+
+        let device = DeviceId::generate();
+        let linked = primary.clone().with_device(device, &sender_keys);
+        assert!(linked.verify(&sender_keys.public_bundle()));
+        assert_ne!(linked.hash(), primary.hash());
+
+        let decoded = PacketEnvelope::decode(&linked.encode().unwrap()).unwrap();
+        assert_eq!(decoded.device, device);
+        assert!(decoded.verify(&sender_keys.public_bundle()));
+
+        // The device is signed, so it can't be moved to another device's log
+        let mut moved = linked;
+        moved.device = DeviceId::generate();
+        assert!(!moved.verify(&sender_keys.public_bundle()));
+    }
+
     #[test]
     fn test_packet_payload_variants() {
         // Test all payload variants can be serialized
@@ -695,6 +1032,11 @@ mod tests {
                 realm_key: [42u8; 32],
                 realm_name: "Test Realm".to_string(),
             },
+            PacketPayload::RealmRekey {
+                realm_id: RealmId::new(),
+                realm_key: [7u8; 32],
+                epoch: 2,
+            },
             PacketPayload::TaskReference {
                 realm_id: RealmId::new(),
                 task_id: "task-123".to_string(),
@@ -719,6 +1061,16 @@ mod tests {
                 new_public_keys: vec![1, 2, 3],
                 old_key_signature: vec![4, 5, 6],
             },
+            PacketPayload::ReputationReport {
+                subject_did: ProfileKeys::generate().did(),
+                violation: "invalid_signature".to_string(),
+                severity: 7,
+            },
+            PacketPayload::Gratitude {
+                for_task: Some(TaskId::new()),
+                message: "Thank you for the seedlings".to_string(),
+                recipient: ProfileKeys::generate().did(),
+            },
         ];
 
         for payload in payloads {
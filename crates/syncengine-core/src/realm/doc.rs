@@ -3,9 +3,22 @@
 //! RealmDoc wraps an Automerge document and provides CRUD operations for tasks.
 //! It handles serialization, merging, and incremental sync message generation.
 
-use automerge::{transaction::Transactable, AutoCommit, ObjType, ReadDoc, ROOT};
+use std::collections::{BTreeSet, HashSet};
 
-use crate::{SyncError, Task, TaskId};
+use automerge::transaction::{CommitOptions, Transactable};
+use automerge::{AutoCommit, ChangeHash, ObjId, ObjType, PatchAction, ReadDoc, ROOT};
+
+use crate::identity::Did;
+use crate::{
+    FieldChange, RealmDiff, RealmMetadata, Recurrence, SyncError, Task, TaskActivity,
+    TaskActivityKind, TaskChange, TaskId,
+};
+
+/// Root key recording the heads a compacted document was taken at
+const COMPACTED_FROM_KEY: &str = "compacted_from";
+
+/// Root map of the heads of replicas rebased onto a compacted document
+const REBASED_KEY: &str = "rebased";
 
 /// Automerge document wrapper for a realm's tasks
 ///
@@ -170,6 +183,19 @@ impl RealmDoc {
         task.image_blob_id = image_blob_id;
         let task_id = task.id.clone();
 
+        self.insert_task(&task)?;
+        Ok(task_id)
+    }
+
+    /// Store a fully built task under its own ID
+    ///
+    /// Lets callers choose the ID, e.g. to control the listing order of
+    /// tasks created within the same second.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if the task cannot be serialized.
+    pub fn insert_task(&mut self, task: &Task) -> Result<(), SyncError> {
         let tasks = self
             .doc
             .get(ROOT, "tasks")
@@ -180,13 +206,13 @@ impl RealmDoc {
 
         // Store task as JSON string in the map
         let task_json =
-            serde_json::to_string(&task).map_err(|e| SyncError::Serialization(e.to_string()))?;
+            serde_json::to_string(task).map_err(|e| SyncError::Serialization(e.to_string()))?;
 
         self.doc
-            .put(&tasks_obj_id, task_id.to_string(), task_json)
+            .put(&tasks_obj_id, task.id.to_string(), task_json)
             .map_err(|e| SyncError::Serialization(e.to_string()))?;
 
-        Ok(task_id)
+        Ok(())
     }
 
     /// Get a task by its ID
@@ -210,7 +236,7 @@ impl RealmDoc {
             {
                 let json = value
                     .to_str()
-                    .ok_or_else(|| SyncError::Serialization("task value is not a string".into()))?;
+                    .ok_or_else(|| SyncError::Serialization("stored value is not a string".into()))?;
                 let task: Task = serde_json::from_str(json)
                     .map_err(|e| SyncError::Serialization(e.to_string()))?;
                 return Ok(Some(task));
@@ -221,7 +247,8 @@ impl RealmDoc {
 
     /// List all tasks in the realm
     ///
-    /// Returns tasks sorted by creation time (oldest first).
+    /// Returns tasks sorted by creation time (oldest first), with tasks
+    /// created in the same second ordered by ID.
     ///
     /// # Errors
     ///
@@ -251,34 +278,86 @@ impl RealmDoc {
         }
 
         // Sort by created_at for consistent ordering
-        tasks.sort_by_key(|t| t.created_at);
+        tasks.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.0.cmp(&b.id.0)));
         Ok(tasks)
     }
 
     /// Toggle the completion state of a task
     ///
+    /// Completing a recurring task adds the next open instance of its
+    /// series; the completed one stays as history.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the new instance, if one was created.
+    ///
     /// # Errors
     ///
     /// Returns `SyncError::TaskNotFound` if the task does not exist.
     /// Returns `SyncError::Serialization` if the operation fails.
-    pub fn toggle_task(&mut self, id: &TaskId) -> Result<(), SyncError> {
+    pub fn toggle_task(&mut self, id: &TaskId) -> Result<Option<TaskId>, SyncError> {
+        self.toggle_task_by(id, None)
+    }
+
+    /// Toggle the completion state of a task, crediting `by` if it becomes done
+    ///
+    /// See [`Self::toggle_task`]. Un-completing clears the completer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::TaskNotFound` if the task does not exist.
+    /// Returns `SyncError::Serialization` if the operation fails.
+    pub fn toggle_task_by(&mut self, id: &TaskId, by: Option<&Did>) -> Result<Option<TaskId>, SyncError> {
         let mut task = self
             .get_task(id)?
             .ok_or_else(|| SyncError::TaskNotFound(id.to_string()))?;
 
-        task.toggle();
+        task.toggle_by(by);
+        let next = if task.completed { task.next_occurrence() } else { None };
 
-        let tasks = self
-            .doc
-            .get(ROOT, "tasks")
-            .map_err(|e| SyncError::Serialization(e.to_string()))?
-            .ok_or_else(|| SyncError::Serialization("tasks map not found".into()))?;
+        self.insert_task(&task)?;
+        if let Some(next) = &next {
+            self.insert_task(next)?;
+        }
 
-        let (_, tasks_obj_id) = tasks;
+        Ok(next.map(|t| t.id))
+    }
+
+    /// Set when a task is due and whether it recurs
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::TaskNotFound` if the task does not exist.
+    /// Returns `SyncError::Serialization` if the operation fails.
+    pub fn set_task_schedule(
+        &mut self,
+        id: &TaskId,
+        due_at: Option<i64>,
+        recurrence: Option<Recurrence>,
+    ) -> Result<(), SyncError> {
+        let mut task = self
+            .get_task(id)?
+            .ok_or_else(|| SyncError::TaskNotFound(id.to_string()))?;
+        task.due_at = due_at;
+        task.recurrence = recurrence;
+        self.insert_task(&task)
+    }
 
+    /// Change a task's title
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::TaskNotFound` if the task does not exist.
+    /// Returns `SyncError::Serialization` if the operation fails.
+    pub fn set_task_title(&mut self, id: &TaskId, title: &str) -> Result<(), SyncError> {
+        let mut task = self
+            .get_task(id)?
+            .ok_or_else(|| SyncError::TaskNotFound(id.to_string()))?;
+        task.title = title.to_string();
+
+        let tasks_obj_id = self.tasks_obj_id()?;
         let task_json =
             serde_json::to_string(&task).map_err(|e| SyncError::Serialization(e.to_string()))?;
-
         self.doc
             .put(&tasks_obj_id, id.to_string(), task_json)
             .map_err(|e| SyncError::Serialization(e.to_string()))?;
@@ -286,6 +365,101 @@ impl RealmDoc {
         Ok(())
     }
 
+    /// Set how long before its due date a task should be reminded about
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::TaskNotFound` if the task does not exist.
+    /// Returns `SyncError::Serialization` if the operation fails.
+    pub fn set_task_reminder(
+        &mut self,
+        id: &TaskId,
+        remind_before_secs: Option<i64>,
+    ) -> Result<(), SyncError> {
+        let mut task = self
+            .get_task(id)?
+            .ok_or_else(|| SyncError::TaskNotFound(id.to_string()))?;
+        task.remind_before_secs = remind_before_secs;
+        self.insert_task(&task)
+    }
+
+    /// Assign a task to a realm member, or clear the assignment with `None`
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::TaskNotFound` if the task does not exist.
+    /// Returns `SyncError::Serialization` if the operation fails.
+    pub fn set_task_assignee(&mut self, id: &TaskId, assignee: Option<Did>) -> Result<(), SyncError> {
+        let mut task = self
+            .get_task(id)?
+            .ok_or_else(|| SyncError::TaskNotFound(id.to_string()))?;
+        task.assignee = assignee;
+        self.insert_task(&task)
+    }
+
+    /// Titles written concurrently with the current one that lost the merge
+    ///
+    /// When peers edit the same task without seeing each other's change,
+    /// Automerge keeps every value and picks one deterministically; the rest
+    /// stay readable as conflicts until someone writes the task again. Returns
+    /// each losing value whose title differs from the winner's, paired with
+    /// the author recorded on the change that wrote it (`None` if unknown).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if the document cannot be read.
+    pub fn title_conflicts(
+        &mut self,
+        id: &TaskId,
+    ) -> Result<Vec<(Option<String>, String)>, SyncError> {
+        let tasks_obj_id = self.tasks_obj_id()?;
+        let values: Vec<(Option<String>, ObjId)> = self
+            .doc
+            .get_all(&tasks_obj_id, id.to_string())
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+            .into_iter()
+            .map(|(value, op)| (value.to_str().map(str::to_string), op))
+            .collect();
+        if values.len() < 2 {
+            return Ok(Vec::new());
+        }
+        let Some(winner) = self.get_task(id)? else {
+            return Ok(Vec::new());
+        };
+        let winner_op = self
+            .doc
+            .get(&tasks_obj_id, id.to_string())
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+            .map(|(_, op)| op);
+
+        let changes = self.doc.get_changes(&[]);
+        let mut conflicts = Vec::new();
+        for (json, op) in values {
+            if Some(&op) == winner_op.as_ref() {
+                continue;
+            }
+            let Some(task) = json.and_then(|json| serde_json::from_str::<Task>(&json).ok()) else {
+                continue;
+            };
+            if task.title == winner.title {
+                continue;
+            }
+            let author = match &op {
+                ObjId::Id(counter, actor, _) => changes
+                    .iter()
+                    .find(|change| {
+                        let start = change.start_op().get();
+                        change.actor_id() == actor
+                            && (start..start + change.len() as u64).contains(counter)
+                    })
+                    .and_then(|change| change.message().cloned()),
+                ObjId::Root => None,
+            };
+            conflicts.push((author, task.title));
+        }
+        Ok(conflicts)
+    }
+
     /// Delete a task from the realm
     ///
     /// # Errors
@@ -307,6 +481,390 @@ impl RealmDoc {
         Ok(())
     }
 
+    /// Check that the document follows the realm task schema
+    ///
+    /// A realm document has a `tasks` map at its root whose values are
+    /// JSON-encoded [`Task`]s keyed by task id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::IncompatibleDocument` describing the first violation found.
+    pub fn validate_schema(&self) -> Result<(), SyncError> {
+        let tasks_obj_id = match self.doc.get(ROOT, "tasks") {
+            Ok(Some((automerge::Value::Object(ObjType::Map), id))) => id,
+            Ok(Some(_)) => {
+                return Err(SyncError::IncompatibleDocument(
+                    "root `tasks` is not a map".into(),
+                ))
+            }
+            Ok(None) => {
+                return Err(SyncError::IncompatibleDocument(
+                    "missing root `tasks` map".into(),
+                ))
+            }
+            Err(e) => return Err(SyncError::IncompatibleDocument(e.to_string())),
+        };
+
+        for key in self.doc.keys(&tasks_obj_id) {
+            let value = self
+                .doc
+                .get(&tasks_obj_id, &key)
+                .map_err(|e| SyncError::IncompatibleDocument(e.to_string()))?
+                .map(|(value, _)| value);
+            let json = value.as_ref().and_then(|v| v.to_str()).ok_or_else(|| {
+                SyncError::IncompatibleDocument(format!("task `{}` is not a JSON string", key))
+            })?;
+            let task: Task = serde_json::from_str(json).map_err(|e| {
+                SyncError::IncompatibleDocument(format!("task `{}` is malformed: {}", key, e))
+            })?;
+            if task.id.to_string() != key {
+                return Err(SyncError::IncompatibleDocument(format!(
+                    "task `{}` is stored under the wrong key",
+                    task.id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bring the tasks of another realm document into this one
+    ///
+    /// A document that shares this one's history (e.g. an exported copy of
+    /// the same realm) is merged as usual. An independently created document
+    /// has its own `tasks` map, which a plain merge would resolve as a
+    /// conflict and hide, so its tasks are copied across instead. Callers
+    /// should validate `other` with [`Self::validate_schema`] first.
+    ///
+    /// Returns the number of tasks that were not in this document before.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if reading or writing tasks fails.
+    pub fn import(&mut self, other: &mut RealmDoc) -> Result<usize, SyncError> {
+        let before: HashSet<TaskId> = self.list_tasks()?.into_iter().map(|t| t.id).collect();
+
+        let ours = self.tasks_obj_id()?;
+        let theirs = other.tasks_obj_id()?;
+        if ours.to_string() == theirs.to_string() {
+            self.merge(other)?;
+        } else {
+            for task in other.list_tasks()? {
+                if self.get_task(&task.id)?.as_ref() == Some(&task) {
+                    continue;
+                }
+                let task_json = serde_json::to_string(&task)
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?;
+                self.doc
+                    .put(&ours, task.id.to_string(), task_json)
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            }
+        }
+
+        let after = self.list_tasks()?;
+        Ok(after.iter().filter(|t| !before.contains(&t.id)).count())
+    }
+
+    /// A copy of this document without its change history
+    ///
+    /// The copy holds the current tasks and metadata in a single change and
+    /// records the heads it was taken at (see [`Self::compacted_from`]). It
+    /// shares no history with this document, so bring it together with
+    /// other copies of the realm through [`Self::absorb`] rather than
+    /// [`Self::merge`]. Nodes that predate compaction would merge it as an
+    /// unrelated copy, so it must only be sent to peers as
+    /// `SyncMessage::CompactedDocument`, which they refuse.
+    ///
+    /// The dropped history is what [`Self::task_history`] reads, so the copy
+    /// reports each task's activity only from the compaction onward; keep
+    /// this document if the earlier activity is still wanted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if reading or writing fails.
+    pub fn compacted(&mut self) -> Result<RealmDoc, SyncError> {
+        let base: Vec<String> = self.heads().iter().map(|h| h.to_string()).collect();
+        let mut compacted = RealmDoc::new();
+
+        let ours = self.tasks_obj_id()?;
+        let theirs = compacted.tasks_obj_id()?;
+        for key in self.doc.keys(&ours) {
+            if let Some(json) = self.raw_string(&ours, &key)? {
+                compacted
+                    .doc
+                    .put(&theirs, key, json)
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            }
+        }
+        if let Some(json) = self.raw_string(&ROOT, "metadata")? {
+            compacted
+                .doc
+                .put(ROOT, "metadata", json)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        }
+        let base = serde_json::to_string(&base).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        compacted
+            .doc
+            .put(ROOT, COMPACTED_FROM_KEY, base)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        compacted
+            .doc
+            .put_object(ROOT, REBASED_KEY, ObjType::Map)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        compacted.commit(None);
+        Ok(compacted)
+    }
+
+    /// Whether this document was made by [`Self::compacted`]
+    pub fn is_compacted(&self) -> bool {
+        matches!(self.doc.get(ROOT, COMPACTED_FROM_KEY), Ok(Some(_)))
+    }
+
+    /// Heads of the history this document was compacted from
+    ///
+    /// Empty for a document that was never compacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if the recorded heads are malformed.
+    pub fn compacted_from(&self) -> Result<Vec<ChangeHash>, SyncError> {
+        let Some(json) = self.raw_string(&ROOT, COMPACTED_FROM_KEY)? else {
+            return Ok(Vec::new());
+        };
+        let hashes: Vec<String> =
+            serde_json::from_str(&json).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        hashes
+            .iter()
+            .map(|h| {
+                h.parse::<ChangeHash>()
+                    .map_err(|e| SyncError::Serialization(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Merge another copy of this realm, across a history compaction if needed
+    ///
+    /// Copies that share history are merged as usual. When one side is a
+    /// compaction of history the other still holds in full, the full side is
+    /// rebased onto the compacted one: the tasks and metadata it changed
+    /// after the compaction point are carried over and the compacted history
+    /// is kept. Two compactions of the same history keep the one whose first
+    /// change hash sorts lowest, so every peer settles on the same one. A
+    /// copy that lacks the compacted history is replaced by `other` if
+    /// `other` records its heads as rebased (see [`Self::record_rebased`]).
+    ///
+    /// A carried-over task is merged field by field with the compacted
+    /// side's copy, so edits made on both sides since the compaction are
+    /// kept. Where both changed the same field, the compacted side's value
+    /// wins.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::IncompatibleDocument` if either side is compacted
+    /// from history the other doesn't have, such as a replica restored from
+    /// an older backup; merging them would hide one side's tasks. Returns
+    /// `SyncError::Serialization` if reading or writing fails.
+    pub fn absorb(&mut self, mut other: RealmDoc) -> Result<(), SyncError> {
+        if self.tasks_obj_id()?.to_string() == other.tasks_obj_id()?.to_string() {
+            return self.merge(&mut other);
+        }
+
+        let our_base = self.compacted_from()?;
+        let their_base = other.compacted_from()?;
+        if !their_base.is_empty() && self.knows(&their_base) {
+            let our_heads = self.heads();
+            self.carry_changes_onto(&their_base, &our_heads, &mut other)?;
+            *self = other;
+        } else if !our_base.is_empty() && other.knows(&our_base) {
+            let their_heads = other.heads();
+            other.carry_changes_onto(&our_base, &their_heads, self)?;
+        } else if !our_base.is_empty() && our_base == their_base {
+            let (Some(our_root), Some(their_root)) = (self.root_change(), other.root_change())
+            else {
+                return self.merge(&mut other);
+            };
+            if their_root < our_root {
+                let our_heads = self.heads();
+                self.carry_changes_onto(&[our_root], &our_heads, &mut other)?;
+                *self = other;
+            } else {
+                let their_heads = other.heads();
+                other.carry_changes_onto(&[their_root], &their_heads, self)?;
+            }
+        } else if our_base.is_empty() && their_base.is_empty() {
+            self.merge(&mut other)?;
+        } else if !their_base.is_empty() && other.was_rebased(&self.heads()) {
+            *self = other;
+        } else {
+            return Err(SyncError::IncompatibleDocument(
+                "document was compacted from history the other copy doesn't have".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether every one of `hashes` is in this document's history
+    pub fn knows(&mut self, hashes: &[ChangeHash]) -> bool {
+        hashes
+            .iter()
+            .all(|hash| self.doc.get_change_by_hash(hash).is_some())
+    }
+
+    /// Whether this document and `other` grew from the same history
+    ///
+    /// Such copies merge directly; otherwise [`Self::absorb`] has to bridge
+    /// a compaction between them.
+    pub fn shares_history(&self, other: &RealmDoc) -> bool {
+        match (self.tasks_obj_id(), other.tasks_obj_id()) {
+            (Ok(ours), Ok(theirs)) => ours.to_string() == theirs.to_string(),
+            _ => false,
+        }
+    }
+
+    /// Record that a replica with `heads` has been rebased onto this document
+    ///
+    /// Call once that replica's changes have been carried over. It can then
+    /// adopt this document in [`Self::absorb`] even though it lacks the
+    /// history this one was compacted from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if this document is not compacted
+    /// or writing fails.
+    pub fn record_rebased(&mut self, heads: &[ChangeHash]) -> Result<(), SyncError> {
+        let rebased = self.rebased_obj_id()?;
+        for head in heads {
+            self.doc
+                .put(&rebased, head.to_string(), true)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        }
+        self.commit(None);
+        Ok(())
+    }
+
+    /// Whether a replica with `heads` was rebased onto this document
+    fn was_rebased(&self, heads: &[ChangeHash]) -> bool {
+        let Ok(rebased) = self.rebased_obj_id() else {
+            return false;
+        };
+        !heads.is_empty()
+            && heads
+                .iter()
+                .all(|head| matches!(self.doc.get(&rebased, head.to_string()), Ok(Some(_))))
+    }
+
+    /// Object id of the root `rebased` map of a compacted document
+    fn rebased_obj_id(&self) -> Result<ObjId, SyncError> {
+        self.doc
+            .get(ROOT, REBASED_KEY)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+            .map(|(_, id)| id)
+            .ok_or_else(|| SyncError::Serialization("document is not compacted".into()))
+    }
+
+    /// Hash of the first change in this document's history
+    fn root_change(&mut self) -> Option<ChangeHash> {
+        self.doc.get_changes(&[]).first().map(|c| c.hash())
+    }
+
+    /// Copy what changed between `from` and `to` here onto `target`
+    ///
+    /// `target` must hold the state at `from` plus its own later edits.
+    /// Touched tasks and changed metadata are merged with `target`'s copy
+    /// field by field (see [`merge_fields`]).
+    fn carry_changes_onto(
+        &mut self,
+        from: &[ChangeHash],
+        to: &[ChangeHash],
+        target: &mut RealmDoc,
+    ) -> Result<(), SyncError> {
+        let ours = self.tasks_obj_id()?;
+        let theirs = target.tasks_obj_id()?;
+        let target_heads = target.heads();
+        for key in self.touched_task_keys(&ours, from, to) {
+            let base = self.json_at(&ours, &key, from)?;
+            let mine = self.json_at(&ours, &key, to)?;
+            let current = target.json_at(&theirs, &key, &target_heads)?;
+            let merged = merge_fields(base, mine, current.clone());
+            if merged == current {
+                continue;
+            }
+            let result = match merged {
+                Some(value) => target.doc.put(&theirs, key, value.to_string()),
+                None => target.doc.delete(&theirs, key),
+            };
+            result.map_err(|e| SyncError::Serialization(e.to_string()))?;
+        }
+
+        let base = self.json_at(&ROOT, "metadata", from)?;
+        let mine = self.json_at(&ROOT, "metadata", to)?;
+        if mine != base {
+            let current = target.json_at(&ROOT, "metadata", &target_heads)?;
+            let merged = merge_fields(base, mine, current.clone());
+            if let Some(value) = merged.filter(|merged| Some(merged) != current.as_ref()) {
+                target
+                    .doc
+                    .put(ROOT, "metadata", value.to_string())
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            }
+        }
+        target.commit(None);
+        Ok(())
+    }
+
+    /// Read a string value from a map, if present
+    fn raw_string(&self, obj: &ObjId, key: &str) -> Result<Option<String>, SyncError> {
+        Ok(self
+            .doc
+            .get(obj, key)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+            .and_then(|(value, _)| value.to_str().map(str::to_string)))
+    }
+
+    /// Object id of the root `tasks` map
+    fn tasks_obj_id(&self) -> Result<automerge::ObjId, SyncError> {
+        self.doc
+            .get(ROOT, "tasks")
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+            .map(|(_, id)| id)
+            .ok_or_else(|| SyncError::Serialization("tasks map not found".into()))
+    }
+
+    /// The realm's shared metadata, or the default if none was ever set
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if the stored metadata is malformed.
+    pub fn metadata(&self) -> Result<RealmMetadata, SyncError> {
+        let value = self
+            .doc
+            .get(ROOT, "metadata")
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+            .map(|(value, _)| value);
+        match value.as_ref().and_then(|v| v.to_str()) {
+            Some(json) => {
+                serde_json::from_str(json).map_err(|e| SyncError::Serialization(e.to_string()))
+            }
+            None => Ok(RealmMetadata::default()),
+        }
+    }
+
+    /// Replace the realm's shared metadata
+    ///
+    /// Stored as a single JSON value, so concurrent edits resolve to one
+    /// writer's metadata as a whole rather than a mix of fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if the operation fails.
+    pub fn set_metadata(&mut self, metadata: &RealmMetadata) -> Result<(), SyncError> {
+        let json = serde_json::to_string(metadata)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        self.doc
+            .put(ROOT, "metadata", json)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        Ok(())
+    }
+
     /// Generate an incremental sync message
     ///
     /// Returns the changes since the last save, suitable for
@@ -329,6 +887,37 @@ impl RealmDoc {
         Ok(())
     }
 
+    /// Authors recorded on the changes `data` would bring into this document
+    ///
+    /// `data` is a full document (`full`) or incremental changes, as passed
+    /// to [`Self::absorb`] or [`Self::apply_sync_message`]. Changes this
+    /// document already holds are skipped; a change committed without an
+    /// author yields `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if `data` can't be loaded.
+    pub fn incoming_authors(
+        &mut self,
+        data: &[u8],
+        full: bool,
+    ) -> Result<Vec<Option<String>>, SyncError> {
+        let mut incoming = if full {
+            RealmDoc::load(data)?
+        } else {
+            let mut fork = self.fork();
+            fork.apply_sync_message(data)?;
+            fork
+        };
+        Ok(incoming
+            .doc
+            .get_changes(&[])
+            .into_iter()
+            .filter(|change| self.doc.get_change_by_hash(&change.hash()).is_none())
+            .map(|change| change.message().cloned())
+            .collect())
+    }
+
     /// Get the document heads (change hashes)
     ///
     /// Returns the current heads of the document DAG, useful for
@@ -336,6 +925,273 @@ impl RealmDoc {
     pub fn heads(&mut self) -> Vec<automerge::ChangeHash> {
         self.doc.get_heads()
     }
+
+    /// Get the number of changes in the document's history
+    pub fn change_count(&mut self) -> usize {
+        self.doc.get_changes(&[]).len()
+    }
+
+    /// Get the hashes of every change in the document's history
+    pub fn change_hashes(&mut self) -> Vec<ChangeHash> {
+        self.doc.get_changes(&[]).iter().map(|c| c.hash()).collect()
+    }
+
+    /// Compare the tasks at two points in the document's history
+    ///
+    /// Both sets of heads must be known to this document; merge in any
+    /// document they came from first. Only tasks touched by changes between
+    /// `before` and `after` are compared.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if either state cannot be read.
+    pub fn diff(
+        &mut self,
+        before: &[ChangeHash],
+        after: &[ChangeHash],
+    ) -> Result<RealmDiff, SyncError> {
+        let Some((_, tasks_obj_id)) = self
+            .doc
+            .get(ROOT, "tasks")
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+        else {
+            return Ok(RealmDiff::default());
+        };
+
+        let mut diff = RealmDiff::default();
+        for key in self.touched_task_keys(&tasks_obj_id, before, after) {
+            let old = self.json_at(&tasks_obj_id, &key, before)?;
+            let new = self.json_at(&tasks_obj_id, &key, after)?;
+            match (old, new) {
+                (None, Some(new)) => diff.added.push(task_from_json(new)?),
+                (Some(old), None) => diff.removed.push(task_from_json(old)?),
+                (Some(old), Some(new)) if old != new => {
+                    let fields = field_changes(&old, &new);
+                    let task = task_from_json(new)?;
+                    diff.modified.push(TaskChange {
+                        id: task.id,
+                        title: task.title,
+                        fields,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        diff.added.sort_by_key(|t| t.created_at);
+        diff.removed.sort_by_key(|t| t.created_at);
+        Ok(diff)
+    }
+
+    /// Commit pending operations as a single change
+    ///
+    /// Records the current time and, if given, the author's DID in the
+    /// change's timestamp and message; [`task_history`](Self::task_history)
+    /// reads them back. Operations left uncommitted are committed without
+    /// this metadata the next time the document is saved or synced.
+    pub fn commit(&mut self, author: Option<&str>) {
+        let mut options = CommitOptions::default().with_time(chrono::Utc::now().timestamp());
+        if let Some(author) = author {
+            options = options.with_message(author);
+        }
+        self.doc.commit_with(options);
+    }
+
+    /// List what each change in the document's history did to a task
+    ///
+    /// Walks the change graph in causal order and compares the task before
+    /// and after each change that wrote the task's key. Concurrent changes appear in an order
+    /// consistent with causality, not necessarily by timestamp. A compacted
+    /// document has no history before the compaction, and the compacting
+    /// change itself is skipped, so activity from before it must be read
+    /// from the document that was compacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if a past state cannot be read.
+    pub fn task_history(&mut self, id: &TaskId) -> Result<Vec<TaskActivity>, SyncError> {
+        let Some((_, tasks_obj_id)) = self
+            .doc
+            .get(ROOT, "tasks")
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+        else {
+            return Ok(Vec::new());
+        };
+        let key = id.to_string();
+        let tasks_obj = tasks_obj_id.to_string();
+        // A compacted document starts with the change that wrote its state
+        let compaction = if self.is_compacted() {
+            self.root_change()
+        } else {
+            None
+        };
+
+        let mut history = Vec::new();
+        for change in self.doc.get_changes(&[]) {
+            if Some(change.hash()) == compaction || !writes_key(&change, &tasks_obj, &key) {
+                continue;
+            }
+            let before = self.json_at(&tasks_obj_id, &key, change.deps())?;
+            let after = self.json_at(&tasks_obj_id, &key, &[change.hash()])?;
+            let kinds = match (before, after) {
+                (None, Some(new)) => vec![TaskActivityKind::Created {
+                    title: task_from_json(new)?.title,
+                }],
+                (Some(_), None) => vec![TaskActivityKind::Deleted],
+                (Some(old), Some(new)) if old != new => activity_kinds(&old, &new),
+                _ => continue,
+            };
+
+            let claimed_author = change.message().cloned();
+            let timestamp = (change.timestamp() != 0).then(|| change.timestamp());
+            history.extend(kinds.into_iter().map(|kind| TaskActivity {
+                claimed_author: claimed_author.clone(),
+                timestamp,
+                kind,
+            }));
+        }
+        Ok(history)
+    }
+
+    /// Keys of the tasks touched by changes between `before` and `after`
+    fn touched_task_keys(
+        &mut self,
+        tasks_obj_id: &ObjId,
+        before: &[ChangeHash],
+        after: &[ChangeHash],
+    ) -> BTreeSet<String> {
+        // Each task is a single JSON value in the tasks map, so map-level
+        // patches on that object name exactly the tasks that changed
+        self.doc
+            .diff(before, after)
+            .into_iter()
+            .filter(|patch| &patch.obj == tasks_obj_id)
+            .filter_map(|patch| match patch.action {
+                PatchAction::PutMap { key, .. } | PatchAction::DeleteMap { key } => Some(key),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Read a stored JSON value (a task or the metadata) as of the given heads
+    fn json_at(
+        &self,
+        obj: &automerge::ObjId,
+        key: &str,
+        heads: &[ChangeHash],
+    ) -> Result<Option<serde_json::Value>, SyncError> {
+        let Some((value, _)) = self
+            .doc
+            .get_at(obj, key, heads)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        let json = value
+            .to_str()
+            .ok_or_else(|| SyncError::Serialization("stored value is not a string".into()))?;
+        serde_json::from_str(json)
+            .map(Some)
+            .map_err(|e| SyncError::Serialization(e.to_string()))
+    }
+}
+
+/// Whether a change has an operation on `key` of the map object `obj`
+fn writes_key(change: &automerge::Change, obj: &str, key: &str) -> bool {
+    change.decode().operations.iter().any(|op| {
+        matches!(&op.key, automerge::legacy::Key::Map(k) if k == key) && op.obj.to_string() == obj
+    })
+}
+
+/// Deserialize a task from its stored JSON value
+fn task_from_json(value: serde_json::Value) -> Result<Task, SyncError> {
+    serde_json::from_value(value).map_err(|e| SyncError::Serialization(e.to_string()))
+}
+
+/// List the top-level fields that differ between two task JSON objects
+fn field_changes(old: &serde_json::Value, new: &serde_json::Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let before = old.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            let after = new.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            (before != after).then(|| FieldChange {
+                field: field.clone(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+/// Three-way merge of two edited copies of a JSON object
+///
+/// `mine` and `theirs` both started from `base`. A field changed on only one
+/// side takes that side's value; a field both changed takes `theirs`. An
+/// edit on one side beats a deletion on the other.
+fn merge_fields(
+    base: Option<serde_json::Value>,
+    mine: Option<serde_json::Value>,
+    theirs: Option<serde_json::Value>,
+) -> Option<serde_json::Value> {
+    if mine == base || mine == theirs {
+        return theirs;
+    }
+    if theirs == base {
+        return mine;
+    }
+    let (Some(mine), Some(theirs)) = (mine.clone(), theirs.clone()) else {
+        return theirs.or(mine);
+    };
+    let (serde_json::Value::Object(mine), serde_json::Value::Object(mut merged)) = (mine, theirs.clone())
+    else {
+        return Some(theirs);
+    };
+    let base = match base {
+        Some(serde_json::Value::Object(base)) => base,
+        _ => serde_json::Map::new(),
+    };
+    for (field, value) in &mine {
+        if base.get(field) != Some(value) && merged.get(field) == base.get(field) {
+            merged.insert(field.clone(), value.clone());
+        }
+    }
+    for (field, value) in &base {
+        if !mine.contains_key(field) && merged.get(field) == Some(value) {
+            merged.remove(field);
+        }
+    }
+    Some(serde_json::Value::Object(merged))
+}
+
+/// Classify the field changes between two versions of a task
+fn activity_kinds(old: &serde_json::Value, new: &serde_json::Value) -> Vec<TaskActivityKind> {
+    let mut kinds = Vec::new();
+    let mut edited = Vec::new();
+    for change in field_changes(old, new) {
+        match change.field.as_str() {
+            "completed" if change.after == serde_json::Value::Bool(true) => {
+                kinds.push(TaskActivityKind::Completed)
+            }
+            "completed" => kinds.push(TaskActivityKind::Reopened),
+            // Follow `completed`; not separate actions
+            "completed_at" | "completed_by" => {}
+            "title" => kinds.push(TaskActivityKind::Retitled {
+                from: change.before.as_str().unwrap_or_default().to_string(),
+                to: change.after.as_str().unwrap_or_default().to_string(),
+            }),
+            _ => edited.push(change.field),
+        }
+    }
+    if !edited.is_empty() {
+        kinds.push(TaskActivityKind::Edited { fields: edited });
+    }
+    kinds
 }
 
 impl Default for RealmDoc {
@@ -464,6 +1320,23 @@ mod tests {
         assert_eq!(tasks1.len(), 3);
     }
 
+    #[test]
+    fn test_metadata_merges_with_document() {
+        let mut doc = RealmDoc::new();
+        assert_eq!(doc.metadata().unwrap(), RealmMetadata::default());
+
+        let mut peer = doc.fork();
+        let meta = RealmMetadata {
+            description: Some("Garden crew".to_string()),
+            emoji: Some("🌱".to_string()),
+            ..Default::default()
+        };
+        peer.set_metadata(&meta).unwrap();
+
+        doc.merge(&mut peer).unwrap();
+        assert_eq!(doc.metadata().unwrap(), meta);
+    }
+
     #[test]
     fn test_get_nonexistent_task() {
         let doc = RealmDoc::new();
@@ -508,6 +1381,26 @@ mod tests {
         assert_eq!(tasks2.len(), 2);
     }
 
+    #[test]
+    fn test_incoming_authors_lists_only_new_changes() {
+        let mut love = RealmDoc::new();
+        love.add_task("Plant beans").unwrap();
+        love.commit(Some("did:sync:love"));
+        let mut joy = RealmDoc::load(&love.save()).unwrap();
+        joy.add_task("Pick tomatoes").unwrap();
+        joy.commit(Some("did:sync:joy"));
+        joy.add_task("Water squash").unwrap();
+        joy.commit(None);
+
+        let changes = joy.generate_sync_message();
+        assert_eq!(love.incoming_authors(&changes, false).unwrap().len(), 2);
+        assert_eq!(love.list_tasks().unwrap().len(), 1);
+
+        let full = joy.save();
+        let authors = love.incoming_authors(&full, true).unwrap();
+        assert_eq!(authors, vec![Some("did:sync:joy".to_string()), None]);
+    }
+
     #[test]
     fn test_heads_change_on_edit() {
         let mut doc = RealmDoc::new();
@@ -525,4 +1418,308 @@ mod tests {
         let doc: RealmDoc = Default::default();
         assert!(doc.list_tasks().unwrap().is_empty());
     }
+
+    #[test]
+    fn test_diff_between_heads() {
+        let mut doc = RealmDoc::new();
+        let kept = doc.add_task("Kept").unwrap();
+        let removed = doc.add_task("Removed").unwrap();
+        let before = doc.heads();
+
+        doc.toggle_task(&kept).unwrap();
+        doc.delete_task(&removed).unwrap();
+        let added = doc.add_task("Added").unwrap();
+        let after = doc.heads();
+
+        let diff = doc.diff(&before, &after).unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, added);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].id, removed);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].id, kept);
+        let fields: Vec<&str> = diff.modified[0].fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, vec!["completed", "completed_at"]);
+
+        assert!(doc.diff(&after, &after).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_import_independent_document() {
+        let mut ours = RealmDoc::new();
+        ours.add_task("Ours").unwrap();
+
+        let mut theirs = RealmDoc::new();
+        theirs.add_task("Theirs 1").unwrap();
+        theirs.add_task("Theirs 2").unwrap();
+        theirs.validate_schema().unwrap();
+
+        // A plain merge would hide one side's tasks map; import copies tasks over
+        assert_eq!(ours.import(&mut theirs).unwrap(), 2);
+        assert_eq!(ours.list_tasks().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_import_shared_history_merges() {
+        let mut ours = RealmDoc::new();
+        let id = ours.add_task("Shared").unwrap();
+        let mut copy = ours.fork();
+        copy.toggle_task(&id).unwrap();
+        copy.add_task("From copy").unwrap();
+
+        assert_eq!(ours.import(&mut copy).unwrap(), 1);
+        assert!(ours.get_task(&id).unwrap().unwrap().completed);
+        assert_eq!(ours.list_tasks().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_compacted_keeps_state_and_drops_history() {
+        let mut doc = RealmDoc::new();
+        let id = doc.add_task("Water seedlings").unwrap();
+        doc.toggle_task(&id).unwrap();
+        doc.add_task("Turn compost").unwrap();
+        doc.set_metadata(&RealmMetadata {
+            description: Some("Garden".into()),
+            ..Default::default()
+        })
+        .unwrap();
+        doc.commit(None);
+
+        let mut compacted = doc.compacted().unwrap();
+        assert_eq!(compacted.change_count(), 1);
+        assert_eq!(compacted.list_tasks().unwrap(), doc.list_tasks().unwrap());
+        assert_eq!(compacted.metadata().unwrap(), doc.metadata().unwrap());
+        assert_eq!(compacted.compacted_from().unwrap(), doc.heads());
+        assert!(compacted.is_compacted());
+        assert!(doc.compacted_from().unwrap().is_empty());
+        assert!(!doc.is_compacted());
+        compacted.validate_schema().unwrap();
+    }
+
+    #[test]
+    fn test_absorb_rebases_full_history_onto_compaction() {
+        let mut original = RealmDoc::new();
+        let kept = original.add_task("Kept").unwrap();
+        let removed = original.add_task("Removed later").unwrap();
+        original.commit(None);
+
+        let mut behind = original.fork();
+        let mut compacted = original.compacted().unwrap();
+        let after_compaction = compacted.add_task("Added after compaction").unwrap();
+
+        // A peer with full history that kept editing after the compaction point
+        behind.toggle_task(&kept).unwrap();
+        behind.delete_task(&removed).unwrap();
+        let from_behind = behind.add_task("Added by peer").unwrap();
+        behind.commit(None);
+
+        let mut rebased = behind.fork();
+        rebased.absorb(compacted.fork()).unwrap();
+        compacted.absorb(behind.fork()).unwrap();
+
+        for doc in [&rebased, &compacted] {
+            assert!(doc.get_task(&kept).unwrap().unwrap().completed);
+            assert!(doc.get_task(&removed).unwrap().is_none());
+            assert!(doc.get_task(&after_compaction).unwrap().is_some());
+            assert!(doc.get_task(&from_behind).unwrap().is_some());
+            assert_eq!(doc.list_tasks().unwrap().len(), 3);
+        }
+        assert!(!rebased.compacted_from().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_absorb_keeps_concurrent_edits_to_one_task() {
+        let mut original = RealmDoc::new();
+        let id = original.add_task("Water seedlings").unwrap();
+        original.commit(None);
+
+        let mut behind = original.fork();
+        let mut compacted = original.compacted().unwrap();
+        compacted.set_task_title(&id, "Water the seedlings").unwrap();
+        compacted.commit(None);
+        behind.toggle_task(&id).unwrap();
+        behind.commit(None);
+
+        let mut rebased = behind.fork();
+        rebased.absorb(compacted.fork()).unwrap();
+        compacted.absorb(rebased.fork()).unwrap();
+
+        assert_eq!(rebased.heads(), compacted.heads());
+        for doc in [&rebased, &compacted] {
+            let task = doc.get_task(&id).unwrap().unwrap();
+            assert_eq!(task.title, "Water the seedlings");
+            assert!(task.completed);
+        }
+
+        // When both sides change the same field, the compacted side wins
+        let mut behind = original.fork();
+        let mut compacted = original.compacted().unwrap();
+        compacted.set_task_title(&id, "From compacted").unwrap();
+        behind.set_task_title(&id, "From history").unwrap();
+        behind.absorb(compacted.fork()).unwrap();
+        assert_eq!(behind.get_task(&id).unwrap().unwrap().title, "From compacted");
+    }
+
+    #[test]
+    fn test_absorb_refuses_compaction_of_unknown_history() {
+        let mut original = RealmDoc::new();
+        let kept = original.add_task("Kept").unwrap();
+        original.commit(None);
+
+        // A copy restored from a backup taken before the latest edits
+        let mut restored = original.fork();
+        restored.add_task("Only in backup").unwrap();
+        restored.commit(None);
+        original.add_task("After backup").unwrap();
+        original.commit(None);
+        let mut compacted = original.compacted().unwrap();
+
+        assert!(matches!(
+            restored.absorb(compacted.fork()),
+            Err(SyncError::IncompatibleDocument(_))
+        ));
+        assert_eq!(restored.list_tasks().unwrap().len(), 2);
+        assert!(restored.get_task(&kept).unwrap().is_some());
+
+        let mut compacted_copy = compacted.fork();
+        assert!(matches!(
+            compacted_copy.absorb(restored.fork()),
+            Err(SyncError::IncompatibleDocument(_))
+        ));
+        assert_eq!(compacted_copy.list_tasks().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_absorb_adopts_compaction_that_rebased_it() {
+        let mut original = RealmDoc::new();
+        original.add_task("Kept").unwrap();
+        original.commit(None);
+
+        // A replica that missed the compaction and kept editing
+        let mut lagging = original.fork();
+        let offline = lagging.add_task("Added offline").unwrap();
+        lagging.commit(None);
+        original.add_task("After lagging went quiet").unwrap();
+        original.commit(None);
+        let mut compacted = original.compacted().unwrap();
+        assert!(matches!(
+            lagging.fork().absorb(compacted.fork()),
+            Err(SyncError::IncompatibleDocument(_))
+        ));
+
+        // Its edits are carried over through the pre-compaction history
+        let lagging_heads = lagging.heads();
+        let mut bridge = original.fork();
+        bridge.absorb(lagging.fork()).unwrap();
+        compacted.absorb(bridge).unwrap();
+        compacted.record_rebased(&lagging_heads).unwrap();
+
+        lagging.absorb(compacted.fork()).unwrap();
+        assert!(lagging.is_compacted());
+        assert_eq!(lagging.list_tasks().unwrap(), compacted.list_tasks().unwrap());
+        assert!(lagging.get_task(&offline).unwrap().is_some());
+        assert_eq!(lagging.list_tasks().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_absorb_settles_competing_compactions() {
+        let mut original = RealmDoc::new();
+        original.add_task("Shared").unwrap();
+        original.commit(None);
+
+        let mut a = original.compacted().unwrap();
+        let mut b = original.compacted().unwrap();
+        let from_a = a.add_task("From a").unwrap();
+        let from_b = b.add_task("From b").unwrap();
+
+        let mut a_view = a.fork();
+        a_view.absorb(b.fork()).unwrap();
+        let mut b_view = b.fork();
+        b_view.absorb(a.fork()).unwrap();
+
+        assert_eq!(a_view.list_tasks().unwrap(), b_view.list_tasks().unwrap());
+        for doc in [&a_view, &b_view] {
+            assert!(doc.get_task(&from_a).unwrap().is_some());
+            assert!(doc.get_task(&from_b).unwrap().is_some());
+            assert_eq!(doc.list_tasks().unwrap().len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_validate_schema_rejects_foreign_shape() {
+        let mut doc = AutoCommit::new();
+        doc.put(ROOT, "todos", "not ours").unwrap();
+        let foreign = RealmDoc::load(&doc.save()).unwrap();
+        assert!(matches!(
+            foreign.validate_schema(),
+            Err(SyncError::IncompatibleDocument(_))
+        ));
+
+        let mut doc = AutoCommit::new();
+        let tasks = doc.put_object(ROOT, "tasks", ObjType::Map).unwrap();
+        doc.put(&tasks, "task_1", "{\"nope\": true}").unwrap();
+        let malformed = RealmDoc::load(&doc.save()).unwrap();
+        assert!(matches!(
+            malformed.validate_schema(),
+            Err(SyncError::IncompatibleDocument(_))
+        ));
+    }
+
+    #[test]
+    fn test_task_history_attributes_changes_to_authors() {
+        let mut love = RealmDoc::new();
+        let id = love.add_task("Mend fence").unwrap();
+        love.commit(Some("did:sync:love"));
+        love.add_task("Unrelated").unwrap();
+        love.commit(Some("did:sync:love"));
+
+        let mut joy = love.fork();
+        joy.toggle_task(&id).unwrap();
+        joy.commit(Some("did:sync:joy"));
+        love.merge(&mut joy).unwrap();
+
+        love.toggle_task(&id).unwrap();
+        love.commit(None);
+        love.delete_task(&id).unwrap();
+        love.commit(Some("did:sync:love"));
+
+        let history = love.task_history(&id).unwrap();
+        let entries: Vec<_> = history
+            .iter()
+            .map(|a| (a.claimed_author.as_deref(), a.kind.clone()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    Some("did:sync:love"),
+                    TaskActivityKind::Created {
+                        title: "Mend fence".to_string()
+                    }
+                ),
+                (Some("did:sync:joy"), TaskActivityKind::Completed),
+                (None, TaskActivityKind::Reopened),
+                (Some("did:sync:love"), TaskActivityKind::Deleted),
+            ]
+        );
+        assert!(history.iter().all(|a| a.timestamp.is_some()));
+    }
+
+    #[test]
+    fn test_activity_kinds_classifies_field_changes() {
+        let old = serde_json::json!({"title": "Old", "completed": false, "description": ""});
+        let new = serde_json::json!({"title": "New", "completed": false, "description": "Details"});
+        assert_eq!(
+            activity_kinds(&old, &new),
+            vec![
+                TaskActivityKind::Retitled {
+                    from: "Old".to_string(),
+                    to: "New".to_string()
+                },
+                TaskActivityKind::Edited {
+                    fields: vec!["description".to_string()]
+                },
+            ]
+        );
+    }
 }
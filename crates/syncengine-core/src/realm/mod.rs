@@ -5,5 +5,9 @@
 //! conflict resolution for concurrent edits.
 
 pub mod doc;
+pub mod snapshot;
+pub mod template;
 
 pub use doc::RealmDoc;
+pub use snapshot::RealmSnapshotView;
+pub use template::{RealmTemplate, TemplateTask};
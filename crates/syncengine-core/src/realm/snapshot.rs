@@ -0,0 +1,42 @@
+//! Read-only view over a realm snapshot
+//!
+//! A snapshot view wraps the frozen Automerge document taken by
+//! `SyncEngine::snapshot_realm`. It only exposes read operations, so a
+//! snapshot can be inspected without any risk of diverging from what was saved.
+
+use crate::{RealmSnapshot, SyncError, Task, TaskId};
+
+use super::RealmDoc;
+
+/// Immutable view of a realm's tasks at the time a snapshot was taken
+pub struct RealmSnapshotView {
+    snapshot: RealmSnapshot,
+    doc: RealmDoc,
+}
+
+impl RealmSnapshotView {
+    /// Build a view from snapshot metadata and the saved document bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Serialization` if the bytes are not a valid Automerge document.
+    pub fn load(snapshot: RealmSnapshot, doc_bytes: &[u8]) -> Result<Self, SyncError> {
+        let doc = RealmDoc::load(doc_bytes)?;
+        Ok(Self { snapshot, doc })
+    }
+
+    /// Metadata of the snapshot this view was opened from
+    pub fn snapshot(&self) -> &RealmSnapshot {
+        &self.snapshot
+    }
+
+    /// List all tasks as they were when the snapshot was taken
+    pub fn list_tasks(&self) -> Result<Vec<Task>, SyncError> {
+        self.doc.list_tasks()
+    }
+
+    /// Get a task as it was when the snapshot was taken
+    pub fn get_task(&self, id: &TaskId) -> Result<Option<Task>, SyncError> {
+        self.doc.get_task(id)
+    }
+}
@@ -0,0 +1,190 @@
+//! Realm templates - named sets of starter tasks
+//!
+//! A template is applied once, when a realm is created, so the new realm
+//! starts with the same tasks every time. A few templates are built in;
+//! more can be dropped into a `templates/` directory as JSON files:
+//!
+//! ```json
+//! {
+//!   "name": "weekly-review",
+//!   "description": "End-of-week reflection",
+//!   "tasks": [
+//!     { "title": "Clear the inbox", "description": "Process every open note" },
+//!     { "title": "Review the calendar", "category": "planning" }
+//!   ]
+//! }
+//! ```
+//!
+//! Tasks are listed most important first and are created in that order.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::SyncError;
+
+/// A starter task in a [`RealmTemplate`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateTask {
+    /// Task title
+    pub title: String,
+    /// Optional subtitle shown on quest cards
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    /// Markdown description
+    #[serde(default)]
+    pub description: String,
+    /// Optional category for grouping/filtering
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl TemplateTask {
+    /// A task with just a title and description
+    pub fn new(title: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            subtitle: None,
+            description: description.into(),
+            category: None,
+        }
+    }
+}
+
+/// A named set of tasks to seed a new realm with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RealmTemplate {
+    /// Name used to pick the template, e.g. "weekly-review"
+    ///
+    /// Defaults to the file stem when loaded from a file without one.
+    #[serde(default)]
+    pub name: String,
+    /// What the template is for
+    #[serde(default)]
+    pub description: String,
+    /// Starter tasks, in creation order
+    pub tasks: Vec<TemplateTask>,
+}
+
+impl RealmTemplate {
+    /// Templates that ship with the engine
+    pub fn builtin() -> Vec<RealmTemplate> {
+        vec![
+            RealmTemplate {
+                name: "weekly-review".to_string(),
+                description: "End-of-week reflection and planning".to_string(),
+                tasks: vec![
+                    TemplateTask::new("Clear the inbox", "Process every loose note, message and scrap of paper."),
+                    TemplateTask::new("Review last week", "What moved forward? What stalled, and why?"),
+                    TemplateTask::new("Review the calendar", "Look two weeks ahead for commitments that need preparation."),
+                    TemplateTask::new("Choose three intentions", "Pick the three things that would make next week feel complete."),
+                ],
+            },
+            RealmTemplate {
+                name: "project-kickoff".to_string(),
+                description: "First steps for a new shared project".to_string(),
+                tasks: vec![
+                    TemplateTask::new("Write down the goal", "One or two sentences everyone can agree on."),
+                    TemplateTask::new("Invite collaborators", "Share this realm with everyone who should take part."),
+                    TemplateTask::new("List what is needed", "Materials, skills and time the project will draw on."),
+                    TemplateTask::new("Set a first gathering", "Agree on when and where to meet next."),
+                ],
+            },
+        ]
+    }
+
+    /// Load a template from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::Io` if the file cannot be read, or
+    /// `SyncError::InvalidConfig` if it is not a valid template.
+    pub fn load(path: &Path) -> Result<Self, SyncError> {
+        let json = std::fs::read_to_string(path)?;
+        let mut template: RealmTemplate = serde_json::from_str(&json).map_err(|e| {
+            SyncError::InvalidConfig(format!("Invalid template {}: {}", path.display(), e))
+        })?;
+        if template.name.is_empty() {
+            template.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        Ok(template)
+    }
+
+    /// Load every `*.json` template in a directory, sorted by name
+    ///
+    /// A missing directory simply has no templates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or any template in it cannot be read.
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>, SyncError> {
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut templates = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                templates.push(Self::load(&path)?);
+            }
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// Find a template by name, preferring ones in `dir` over the built-ins
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` contains a template that cannot be read.
+    pub fn find(name: &str, dir: Option<&Path>) -> Result<Option<Self>, SyncError> {
+        let from_dir = match dir {
+            Some(dir) => Self::load_dir(dir)?,
+            None => Vec::new(),
+        };
+        Ok(from_dir
+            .into_iter()
+            .chain(Self::builtin())
+            .find(|t| t.name == name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_prefers_directory_templates_over_builtins() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("weekly-review.json"),
+            r#"{ "tasks": [{ "title": "Water the seedlings" }] }"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a template").unwrap();
+
+        let template = RealmTemplate::find("weekly-review", Some(dir.path()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(template.name, "weekly-review");
+        assert_eq!(template.tasks, [TemplateTask::new("Water the seedlings", "")]);
+
+        let builtin = RealmTemplate::find("project-kickoff", Some(dir.path())).unwrap();
+        assert!(builtin.is_some());
+        assert!(RealmTemplate::find("no-such-template", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_invalid_template_file_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("broken.json"), r#"{ "name": "broken" }"#).unwrap();
+
+        let err = RealmTemplate::load_dir(dir.path()).unwrap_err();
+        assert!(matches!(err, SyncError::InvalidConfig(_)));
+    }
+}
@@ -1,34 +1,40 @@
-//! Persistent storage using redb (replaces sled).
+//! Persistent storage on a pluggable key/value backend (replaces sled).
 //!
 //! This module provides ACID-compliant storage for:
 //! - Realms (workspaces/projects)
 //! - Documents (Automerge CRDT blobs)
 //! - Identity and device credentials
+//! - Signed device lists of identities used on several devices
 //! - Realm encryption keys
 //! - User profiles
+//! - Realm membership rosters
+//! - Realm role tables
+//! - Realm snapshots (local only)
 //! - Image blobs (content-addressed)
+//!
+//! Data normally lives in a single redb file ([`RedbBackend`]).
+//! [`Storage::with_backend`] runs on any other [`StorageBackend`] instead,
+//! such as [`InMemoryBackend`].
 
 use crate::error::SyncError;
 use crate::types::{RealmId, RealmInfo};
-use parking_lot::RwLock;
-use redb::{Database, ReadableTable, TableDefinition};
 use std::path::Path;
 use std::sync::Arc;
 
 // Submodules
+mod avatar_thumbs;
+mod backend;
 mod blobs;
 mod contacts;
+mod devices;
 mod peers;
 mod pinned_profiles;
 mod profile_pinners;
 mod profiles;
-
-// Re-export initialization helpers (used in Storage::new)
-use blobs::BLOBS_TABLE;
-use contacts::{CONTACTS_TABLE, PENDING_CONTACTS_TABLE, REVOKED_INVITES_TABLE};
-use peers::{MIGRATION_FLAGS_TABLE, PEER_DID_INDEX, UNIFIED_PEERS_TABLE};
-use pinned_profiles::PINNED_PROFILES_TABLE;
-use profiles::PROFILES_TABLE;
+mod realm_history;
+mod realm_members;
+mod realm_roles;
+mod snapshots;
 
 // Re-export pinning configuration
 pub use pinned_profiles::PinningConfig;
@@ -36,29 +42,36 @@ pub use pinned_profiles::PinningConfig;
 // Re-export pinner info for network page
 pub use profile_pinners::PinnerInfo;
 
-// Table definitions
-const REALMS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("realms");
-const DOCUMENTS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("documents");
-const IDENTITY_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("identity");
-const REALM_KEYS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("realm_keys");
-const ENDPOINT_SECRET_KEY_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("endpoint_secret_key");
-const PROFILE_KEYS_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("profile_keys");
-
-/// Storage layer using redb for ACID-compliant persistence
+// Backends for `Storage::with_backend`
+pub use backend::{InMemoryBackend, ReadTxn, RedbBackend, StorageBackend, WriteTxn};
+
+// Table names
+const REALMS_TABLE: &str = "realms";
+const DOCUMENTS_TABLE: &str = "documents";
+const IDENTITY_TABLE: &str = "identity";
+const REALM_KEYS_TABLE: &str = "realm_keys";
+const ENDPOINT_SECRET_KEY_TABLE: &str = "endpoint_secret_key";
+const PROFILE_KEYS_TABLE: &str = "profile_keys";
+const HEALTH_TABLE: &str = "health";
+
+/// Decode a value stored as a UTF-8 string
+fn string_value(bytes: Vec<u8>) -> Result<String, SyncError> {
+    String::from_utf8(bytes).map_err(|e| SyncError::Serialization(e.to_string()))
+}
+
+/// Storage layer over a transactional key/value backend
 #[derive(Clone)]
 pub struct Storage {
-    db: Arc<RwLock<Database>>,
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl Storage {
-    /// Get a reference to the shared database handle
+    /// Get a reference to the shared backend
     ///
     /// This allows other components (like PeerRegistry) to share the same
-    /// database connection instead of opening multiple instances of the same file.
-    pub fn db_handle(&self) -> Arc<RwLock<Database>> {
-        self.db.clone()
+    /// backend instead of opening multiple instances of the same file.
+    pub fn backend(&self) -> Arc<dyn StorageBackend> {
+        self.backend.clone()
     }
 }
 
@@ -67,8 +80,7 @@ impl Storage {
     ///
     /// This will:
     /// - Create the database directory if it doesn't exist
-    /// - Initialize the database file
-    /// - Create all required tables
+    /// - Open or create the redb database file
     pub fn new(path: impl AsRef<Path>) -> Result<Self, SyncError> {
         let path = path.as_ref();
 
@@ -77,35 +89,35 @@ impl Storage {
             std::fs::create_dir_all(parent)?;
         }
 
-        // Open/create database
-        let db = Database::create(path)?;
+        Ok(Self::with_backend(RedbBackend::create(path)?))
+    }
 
-        // Initialize all tables
-        let write_txn = db.begin_write()?;
-        {
-            let _ = write_txn.open_table(REALMS_TABLE)?;
-            let _ = write_txn.open_table(DOCUMENTS_TABLE)?;
-            let _ = write_txn.open_table(IDENTITY_TABLE)?;
-            let _ = write_txn.open_table(REALM_KEYS_TABLE)?;
-            let _ = write_txn.open_table(ENDPOINT_SECRET_KEY_TABLE)?;
-            let _ = write_txn.open_table(PROFILES_TABLE)?;
-            let _ = write_txn.open_table(BLOBS_TABLE)?;
-            let _ = write_txn.open_table(CONTACTS_TABLE)?;
-            let _ = write_txn.open_table(PENDING_CONTACTS_TABLE)?;
-            let _ = write_txn.open_table(REVOKED_INVITES_TABLE)?;
-            let _ = write_txn.open_table(PINNED_PROFILES_TABLE)?;
-            // Note: PROFILE_PINNERS_TABLE removed - Indra's Net derives pinners from contacts
-            // Unified peers tables
-            let _ = write_txn.open_table(UNIFIED_PEERS_TABLE)?;
-            let _ = write_txn.open_table(PEER_DID_INDEX)?;
-            let _ = write_txn.open_table(MIGRATION_FLAGS_TABLE)?;
-            let _ = write_txn.open_table(PROFILE_KEYS_TABLE)?;
+    /// Create a storage instance over a custom backend.
+    ///
+    /// Use [`InMemoryBackend`] for storage that disappears on drop, or
+    /// implement [`StorageBackend`] to keep the tables somewhere else.
+    pub fn with_backend(backend: impl StorageBackend) -> Self {
+        Self {
+            backend: Arc::new(backend),
         }
-        write_txn.commit()?;
+    }
 
-        Ok(Self {
-            db: Arc::new(RwLock::new(db)),
-        })
+    /// Create a storage instance that lives only in memory.
+    pub fn in_memory() -> Self {
+        Self::with_backend(InMemoryBackend::new())
+    }
+
+    /// Check that the database still accepts writes.
+    ///
+    /// Commits a one-row write (the current time) to a dedicated table, so a
+    /// full disk, a read-only filesystem or a failing device shows up here
+    /// rather than on the next real save.
+    pub fn check_writable(&self) -> Result<(), SyncError> {
+        let now = chrono::Utc::now().timestamp();
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(HEALTH_TABLE, "last_write_check", &now.to_le_bytes())?;
+        write_txn.commit()?;
+        Ok(())
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -116,15 +128,12 @@ impl Storage {
     ///
     /// If a realm with the same ID already exists, it will be overwritten.
     pub fn save_realm(&self, info: &RealmInfo) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(REALMS_TABLE)?;
-            let data =
-                serde_json::to_vec(info).map_err(|e| SyncError::Serialization(e.to_string()))?;
-            let key = info.id.to_base58();
-            table.insert(key.as_str(), data.as_slice())?;
-        }
+        let data =
+            serde_json::to_vec(info).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let key = info.id.to_base58();
+
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(REALMS_TABLE, &key, &data)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -133,14 +142,12 @@ impl Storage {
     ///
     /// Returns `None` if no realm with the given ID exists.
     pub fn load_realm(&self, realm_id: &RealmId) -> Result<Option<RealmInfo>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(REALMS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
         let key = realm_id.to_base58();
 
-        match table.get(key.as_str())? {
+        match read_txn.get(REALMS_TABLE, &key)? {
             Some(v) => {
-                let info: RealmInfo = serde_json::from_slice(v.value())
+                let info: RealmInfo = serde_json::from_slice(&v)
                     .map_err(|e| SyncError::Serialization(e.to_string()))?;
                 Ok(Some(info))
             }
@@ -150,14 +157,11 @@ impl Storage {
 
     /// Load all realms from the database.
     pub fn list_realms(&self) -> Result<Vec<RealmInfo>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(REALMS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut realms = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let info: RealmInfo = serde_json::from_slice(value.value())
+        for (_, value) in read_txn.iter(REALMS_TABLE)? {
+            let info: RealmInfo = serde_json::from_slice(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             realms.push(info);
         }
@@ -166,19 +170,12 @@ impl Storage {
 
     /// Delete a realm and all associated data (documents, keys).
     pub fn delete_realm(&self, realm_id: &RealmId) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let key = realm_id.to_base58();
-            // Delete from all related tables
-            let mut realms = write_txn.open_table(REALMS_TABLE)?;
-            let mut documents = write_txn.open_table(DOCUMENTS_TABLE)?;
-            let mut keys = write_txn.open_table(REALM_KEYS_TABLE)?;
-
-            realms.remove(key.as_str())?;
-            documents.remove(key.as_str())?;
-            keys.remove(key.as_str())?;
-        }
+        let key = realm_id.to_base58();
+        let mut write_txn = self.backend.begin_write()?;
+        // Delete from all related tables
+        write_txn.remove(REALMS_TABLE, &key)?;
+        write_txn.remove(DOCUMENTS_TABLE, &key)?;
+        write_txn.remove(REALM_KEYS_TABLE, &key)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -191,13 +188,8 @@ impl Storage {
     ///
     /// Documents are stored as raw bytes and can be any size.
     pub fn save_document(&self, realm_id: &RealmId, data: &[u8]) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(DOCUMENTS_TABLE)?;
-            let key = realm_id.to_base58();
-            table.insert(key.as_str(), data)?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(DOCUMENTS_TABLE, &realm_id.to_base58(), data)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -206,12 +198,8 @@ impl Storage {
     ///
     /// Returns `None` if no document exists for the given realm.
     pub fn load_document(&self, realm_id: &RealmId) -> Result<Option<Vec<u8>>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(DOCUMENTS_TABLE)?;
-        let key = realm_id.to_base58();
-
-        Ok(table.get(key.as_str())?.map(|v| v.value().to_vec()))
+        let read_txn = self.backend.begin_read()?;
+        read_txn.get(DOCUMENTS_TABLE, &realm_id.to_base58())
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -222,13 +210,8 @@ impl Storage {
     ///
     /// Each shared realm has its own symmetric encryption key.
     pub fn save_realm_key(&self, realm_id: &RealmId, key: &[u8; 32]) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(REALM_KEYS_TABLE)?;
-            let realm_key = realm_id.to_base58();
-            table.insert(realm_key.as_str(), key.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(REALM_KEYS_TABLE, &realm_id.to_base58(), key)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -237,18 +220,33 @@ impl Storage {
     ///
     /// Returns `None` if the realm is not shared or has no key.
     pub fn load_realm_key(&self, realm_id: &RealmId) -> Result<Option<[u8; 32]>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(REALM_KEYS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
         let key = realm_id.to_base58();
 
-        Ok(table.get(key.as_str())?.map(|v| {
+        Ok(read_txn.get(REALM_KEYS_TABLE, &key)?.map(|v| {
             let mut arr = [0u8; 32];
-            arr.copy_from_slice(v.value());
+            arr.copy_from_slice(&v);
             arr
         }))
     }
 
+    /// Load the keys of every realm that has one.
+    pub fn list_realm_keys(&self) -> Result<Vec<(RealmId, [u8; 32])>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+
+        let mut keys = Vec::new();
+        for (realm_id, value) in read_txn.iter(REALM_KEYS_TABLE)? {
+            let realm_id = RealmId::from_base58(&realm_id)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            let key: [u8; 32] = value
+                .as_slice()
+                .try_into()
+                .map_err(|_| SyncError::Storage("Invalid realm key length".to_string()))?;
+            keys.push((realm_id, key));
+        }
+        Ok(keys)
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // Identity Operations
     // ═══════════════════════════════════════════════════════════════════════
@@ -260,13 +258,8 @@ impl Storage {
     ///
     /// There is only one identity per node, stored with a fixed key.
     pub fn save_identity(&self, keypair: &crate::identity::HybridKeypair) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(IDENTITY_TABLE)?;
-            let data = keypair.to_bytes();
-            table.insert(Self::IDENTITY_KEY, data.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(IDENTITY_TABLE, Self::IDENTITY_KEY, &keypair.to_bytes())?;
         write_txn.commit()?;
         Ok(())
     }
@@ -275,13 +268,11 @@ impl Storage {
     ///
     /// Returns `None` if no identity has been created yet.
     pub fn load_identity(&self) -> Result<Option<crate::identity::HybridKeypair>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(IDENTITY_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        match table.get(Self::IDENTITY_KEY)? {
+        match read_txn.get(IDENTITY_TABLE, Self::IDENTITY_KEY)? {
             Some(v) => {
-                let keypair = crate::identity::HybridKeypair::from_bytes(v.value())?;
+                let keypair = crate::identity::HybridKeypair::from_bytes(&v)?;
                 Ok(Some(keypair))
             }
             None => Ok(None),
@@ -290,11 +281,8 @@ impl Storage {
 
     /// Check if an identity exists in storage.
     pub fn has_identity(&self) -> Result<bool, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(IDENTITY_TABLE)?;
-
-        Ok(table.get(Self::IDENTITY_KEY)?.is_some())
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(IDENTITY_TABLE, Self::IDENTITY_KEY)?.is_some())
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -310,13 +298,8 @@ impl Storage {
     /// - Signing keys (hybrid ML-DSA-65 + Ed25519) - derived from identity
     /// - Key exchange keys (X25519 + ML-KEM-768) for sealed boxes
     pub fn save_profile_keys(&self, keys: &crate::profile::ProfileKeys) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PROFILE_KEYS_TABLE)?;
-            let data = keys.to_bytes();
-            table.insert(Self::PROFILE_KEYS_KEY, data.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(PROFILE_KEYS_TABLE, Self::PROFILE_KEYS_KEY, &keys.to_bytes())?;
         write_txn.commit()?;
         Ok(())
     }
@@ -325,13 +308,11 @@ impl Storage {
     ///
     /// Returns `None` if no profile keys have been created yet.
     pub fn load_profile_keys(&self) -> Result<Option<crate::profile::ProfileKeys>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PROFILE_KEYS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        match table.get(Self::PROFILE_KEYS_KEY)? {
+        match read_txn.get(PROFILE_KEYS_TABLE, Self::PROFILE_KEYS_KEY)? {
             Some(v) => {
-                let keys = crate::profile::ProfileKeys::from_bytes(v.value())?;
+                let keys = crate::profile::ProfileKeys::from_bytes(&v)?;
                 Ok(Some(keys))
             }
             None => Ok(None),
@@ -340,11 +321,8 @@ impl Storage {
 
     /// Check if profile keys exist in storage.
     pub fn has_profile_keys(&self) -> Result<bool, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(PROFILE_KEYS_TABLE)?;
-
-        Ok(table.get(Self::PROFILE_KEYS_KEY)?.is_some())
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(PROFILE_KEYS_TABLE, Self::PROFILE_KEYS_KEY)?.is_some())
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -359,12 +337,8 @@ impl Storage {
     /// There is only one endpoint per node, stored with a fixed key.
     /// This ensures stable node identity across restarts.
     pub fn save_endpoint_secret_key(&self, secret_key: &[u8; 32]) -> Result<(), SyncError> {
-        let db = self.db.read();
-        let write_txn = db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(ENDPOINT_SECRET_KEY_TABLE)?;
-            table.insert(Self::ENDPOINT_SECRET_KEY, secret_key.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(ENDPOINT_SECRET_KEY_TABLE, Self::ENDPOINT_SECRET_KEY, secret_key)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -373,14 +347,12 @@ impl Storage {
     ///
     /// Returns `None` if no endpoint secret key has been created yet.
     pub fn load_endpoint_secret_key(&self) -> Result<Option<[u8; 32]>, SyncError> {
-        let db = self.db.read();
-        let read_txn = db.begin_read()?;
-        let table = read_txn.open_table(ENDPOINT_SECRET_KEY_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        match table.get(Self::ENDPOINT_SECRET_KEY)? {
+        match read_txn.get(ENDPOINT_SECRET_KEY_TABLE, Self::ENDPOINT_SECRET_KEY)? {
             Some(v) => {
                 let mut arr = [0u8; 32];
-                arr.copy_from_slice(v.value());
+                arr.copy_from_slice(&v);
                 Ok(Some(arr))
             }
             None => Ok(None),
@@ -417,6 +389,26 @@ mod tests {
         assert!(db_path.exists());
     }
 
+    #[test]
+    fn test_in_memory_storage_round_trips_realms() {
+        let storage = Storage::in_memory();
+
+        let realm = RealmInfo::new("Scratch");
+        storage.save_realm(&realm).unwrap();
+        storage.save_document(&realm.id, b"doc").unwrap();
+
+        assert_eq!(storage.load_realm(&realm.id).unwrap().unwrap().name, "Scratch");
+        assert_eq!(storage.load_document(&realm.id).unwrap().unwrap(), b"doc");
+        storage.check_writable().unwrap();
+    }
+
+    #[test]
+    fn test_fresh_storage_is_writable() {
+        let (storage, _temp) = create_test_storage();
+        storage.check_writable().unwrap();
+        storage.check_writable().unwrap();
+    }
+
     #[test]
     fn test_save_and_load_realm() {
         let (storage, _temp) = create_test_storage();
@@ -0,0 +1,56 @@
+//! Avatar Thumbnail Storage - maps avatar blob IDs to their thumbnails
+//!
+//! Kept beside the profile tables rather than inside `UserProfile` or
+//! `ProfileSnapshot`, whose postcard layout is both stored on disk and
+//! signed, so adding a field there would break older records and
+//! signatures from peers that don't know about thumbnails.
+
+use crate::error::SyncError;
+
+use super::{string_value, Storage};
+
+/// Table for avatar thumbnails (key: avatar blob ID, value: thumbnail blob ID)
+pub(crate) const AVATAR_THUMBS_TABLE: &str = "avatar_thumbs";
+
+impl Storage {
+    /// Record the thumbnail stored for an avatar, replacing any earlier one.
+    pub fn save_avatar_thumbnail(
+        &self,
+        avatar_blob_id: &str,
+        thumb_blob_id: &str,
+    ) -> Result<(), SyncError> {
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(AVATAR_THUMBS_TABLE, avatar_blob_id, thumb_blob_id.as_bytes())?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Look up the thumbnail recorded for an avatar.
+    ///
+    /// Returns `None` if no thumbnail has been made for it here.
+    pub fn load_avatar_thumbnail(&self, avatar_blob_id: &str) -> Result<Option<String>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        read_txn.get(AVATAR_THUMBS_TABLE, avatar_blob_id)?.map(string_value).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_and_load_avatar_thumbnail() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("test.db")).unwrap();
+
+        assert_eq!(storage.load_avatar_thumbnail("avatar").unwrap(), None);
+
+        storage.save_avatar_thumbnail("avatar", "thumb-1").unwrap();
+        storage.save_avatar_thumbnail("avatar", "thumb-2").unwrap();
+        assert_eq!(
+            storage.load_avatar_thumbnail("avatar").unwrap(),
+            Some("thumb-2".to_string())
+        );
+    }
+}
@@ -0,0 +1,469 @@
+//! Storage Backends - the key/value layer under [`Storage`](super::Storage)
+//!
+//! Everything `Storage` keeps lives in named tables of string keys and byte
+//! values. A [`StorageBackend`] provides those tables through read and write
+//! transactions:
+//!
+//! - [`RedbBackend`] keeps them in a redb database file (the default)
+//! - [`InMemoryBackend`] keeps them in memory, gone once dropped
+//!
+//! A table that has never been written reads as empty, so backends create
+//! tables on first insert rather than up front.
+
+use crate::error::SyncError;
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use redb::{Database, ReadableTable, TableDefinition, TableError, TableHandle};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A key/value store that [`Storage`](super::Storage) can run on.
+pub trait StorageBackend: Send + Sync + 'static {
+    /// Start a read transaction over a consistent snapshot of every table.
+    fn begin_read(&self) -> Result<Box<dyn ReadTxn + '_>, SyncError>;
+
+    /// Start a write transaction.
+    ///
+    /// Only one write transaction runs at a time; others wait for it. Its
+    /// changes become visible together on [`WriteTxn::commit`] and are
+    /// discarded if it is dropped uncommitted.
+    fn begin_write(&self) -> Result<Box<dyn WriteTxn + '_>, SyncError>;
+}
+
+/// Reads available in any transaction.
+pub trait ReadTxn {
+    /// Get the value stored under `key`.
+    fn get(&self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError>;
+
+    /// List entries with `start <= key < end` in key order. `None` leaves
+    /// that side of the range open.
+    fn range(
+        &self,
+        table: &str,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<Vec<(String, Vec<u8>)>, SyncError>;
+
+    /// List every entry of a table in key order.
+    fn iter(&self, table: &str) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+        self.range(table, None, None)
+    }
+}
+
+/// A write transaction. Reads see its own uncommitted writes.
+pub trait WriteTxn: ReadTxn {
+    /// Store `value` under `key`, replacing any previous value.
+    fn insert(&mut self, table: &str, key: &str, value: &[u8]) -> Result<(), SyncError>;
+
+    /// Remove `key`, returning the value it had.
+    fn remove(&mut self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError>;
+
+    /// Remove every entry with `start <= key < end`.
+    fn remove_range(
+        &mut self,
+        table: &str,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<(), SyncError> {
+        for (key, _) in self.range(table, start, end)? {
+            self.remove(table, &key)?;
+        }
+        Ok(())
+    }
+
+    /// Make the transaction's changes durable and visible.
+    fn commit(self: Box<Self>) -> Result<(), SyncError>;
+}
+
+/// True when `start..end` cannot hold any key
+fn empty_range(start: Option<&str>, end: Option<&str>) -> bool {
+    matches!((start, end), (Some(start), Some(end)) if start >= end)
+}
+
+fn bounds<'a>(start: Option<&'a str>, end: Option<&'a str>) -> (Bound<&'a str>, Bound<&'a str>) {
+    (
+        start.map_or(Bound::Unbounded, Bound::Included),
+        end.map_or(Bound::Unbounded, Bound::Excluded),
+    )
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// redb
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn redb_table(name: &str) -> TableDefinition<'_, &'static str, &'static [u8]> {
+    TableDefinition::new(name)
+}
+
+/// Backend keeping every table in a redb database.
+pub struct RedbBackend {
+    db: Database,
+}
+
+impl RedbBackend {
+    /// Open the database at `path`, creating it if needed.
+    ///
+    /// Tables written by older versions with other key or value types are
+    /// converted to string keys and byte values on the way in.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, SyncError> {
+        let db = Database::create(path)?;
+        migrate_legacy_tables(&db)?;
+        Ok(Self { db })
+    }
+}
+
+/// Rewrite tables from before the key/value layer into `&str -> &[u8]`.
+///
+/// String values keep their UTF-8 bytes, `i64` values become little-endian
+/// bytes and byte keys (peer endpoint IDs) become lowercase hex.
+fn migrate_legacy_tables(db: &Database) -> Result<(), SyncError> {
+    let write_txn = db.begin_write()?;
+    let names: Vec<String> = write_txn
+        .list_tables()?
+        .map(|table| table.name().to_string())
+        .collect();
+
+    let mut migrated = false;
+    for name in names {
+        match write_txn.open_table(redb_table(&name)) {
+            Ok(_) => continue,
+            Err(TableError::TableTypeMismatch { .. }) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+        if let Ok(table) = write_txn.open_table(TableDefinition::<&str, &str>::new(&name)) {
+            for entry in table.iter()? {
+                let (key, value) = entry?;
+                entries.push((key.value().to_string(), value.value().as_bytes().to_vec()));
+            }
+        } else if let Ok(table) = write_txn.open_table(TableDefinition::<&str, i64>::new(&name)) {
+            for entry in table.iter()? {
+                let (key, value) = entry?;
+                entries.push((key.value().to_string(), value.value().to_le_bytes().to_vec()));
+            }
+        } else {
+            let table = write_txn.open_table(TableDefinition::<&[u8], &[u8]>::new(&name))?;
+            for entry in table.iter()? {
+                let (key, value) = entry?;
+                entries.push((hex::encode(key.value()), value.value().to_vec()));
+            }
+        }
+
+        write_txn.delete_table(redb_table(&name))?;
+        let mut table = write_txn.open_table(redb_table(&name))?;
+        for (key, value) in &entries {
+            table.insert(key.as_str(), value.as_slice())?;
+        }
+        tracing::info!(table = %name, entries = entries.len(), "Migrated legacy storage table");
+        migrated = true;
+    }
+
+    if migrated {
+        write_txn.commit()?;
+    }
+    Ok(())
+}
+
+impl StorageBackend for RedbBackend {
+    fn begin_read(&self) -> Result<Box<dyn ReadTxn + '_>, SyncError> {
+        Ok(Box::new(RedbRead(self.db.begin_read()?)))
+    }
+
+    fn begin_write(&self) -> Result<Box<dyn WriteTxn + '_>, SyncError> {
+        Ok(Box::new(RedbWrite(self.db.begin_write()?)))
+    }
+}
+
+struct RedbRead(redb::ReadTransaction);
+
+impl ReadTxn for RedbRead {
+    fn get(&self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        match self.0.open_table(redb_table(table)) {
+            Ok(table) => Ok(table.get(key)?.map(|v| v.value().to_vec())),
+            Err(TableError::TableDoesNotExist(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn range(
+        &self,
+        table: &str,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+        match self.0.open_table(redb_table(table)) {
+            Ok(table) => collect_range(&table, start, end),
+            Err(TableError::TableDoesNotExist(_)) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+struct RedbWrite(redb::WriteTransaction);
+
+impl ReadTxn for RedbWrite {
+    fn get(&self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        let table = self.0.open_table(redb_table(table))?;
+        let value = table.get(key)?.map(|v| v.value().to_vec());
+        Ok(value)
+    }
+
+    fn range(
+        &self,
+        table: &str,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+        let table = self.0.open_table(redb_table(table))?;
+        collect_range(&table, start, end)
+    }
+}
+
+impl WriteTxn for RedbWrite {
+    fn insert(&mut self, table: &str, key: &str, value: &[u8]) -> Result<(), SyncError> {
+        let mut table = self.0.open_table(redb_table(table))?;
+        table.insert(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&mut self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        let mut table = self.0.open_table(redb_table(table))?;
+        let removed = table.remove(key)?.map(|v| v.value().to_vec());
+        Ok(removed)
+    }
+
+    fn commit(self: Box<Self>) -> Result<(), SyncError> {
+        self.0.commit()?;
+        Ok(())
+    }
+}
+
+fn collect_range(
+    table: &impl ReadableTable<&'static str, &'static [u8]>,
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+    if empty_range(start, end) {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for entry in table.range::<&str>(bounds(start, end))? {
+        let (key, value) = entry?;
+        entries.push((key.value().to_string(), value.value().to_vec()));
+    }
+    Ok(entries)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// In memory
+// ═══════════════════════════════════════════════════════════════════════════
+
+type MemoryTable = BTreeMap<String, Arc<[u8]>>;
+type MemoryTables = BTreeMap<String, Arc<MemoryTable>>;
+
+/// Backend keeping every table in memory.
+///
+/// Transactions work on cheap copy-on-write snapshots, so readers never
+/// wait for a writer and a dropped write transaction leaves nothing behind.
+#[derive(Default)]
+pub struct InMemoryBackend {
+    tables: RwLock<MemoryTables>,
+    writer: Mutex<()>,
+}
+
+impl InMemoryBackend {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for InMemoryBackend {
+    fn begin_read(&self) -> Result<Box<dyn ReadTxn + '_>, SyncError> {
+        Ok(Box::new(MemoryRead(self.tables.read().clone())))
+    }
+
+    fn begin_write(&self) -> Result<Box<dyn WriteTxn + '_>, SyncError> {
+        let writer = self.writer.lock();
+        Ok(Box::new(MemoryWrite {
+            backend: self,
+            tables: self.tables.read().clone(),
+            _writer: writer,
+        }))
+    }
+}
+
+fn memory_get(tables: &MemoryTables, table: &str, key: &str) -> Option<Vec<u8>> {
+    tables.get(table)?.get(key).map(|v| v.to_vec())
+}
+
+fn memory_range(
+    tables: &MemoryTables,
+    table: &str,
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Vec<(String, Vec<u8>)> {
+    match tables.get(table) {
+        Some(table) if !empty_range(start, end) => table
+            .range::<str, _>(bounds(start, end))
+            .map(|(key, value)| (key.clone(), value.to_vec()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+struct MemoryRead(MemoryTables);
+
+impl ReadTxn for MemoryRead {
+    fn get(&self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        Ok(memory_get(&self.0, table, key))
+    }
+
+    fn range(
+        &self,
+        table: &str,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+        Ok(memory_range(&self.0, table, start, end))
+    }
+}
+
+struct MemoryWrite<'a> {
+    backend: &'a InMemoryBackend,
+    tables: MemoryTables,
+    _writer: MutexGuard<'a, ()>,
+}
+
+impl ReadTxn for MemoryWrite<'_> {
+    fn get(&self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        Ok(memory_get(&self.tables, table, key))
+    }
+
+    fn range(
+        &self,
+        table: &str,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+        Ok(memory_range(&self.tables, table, start, end))
+    }
+}
+
+impl WriteTxn for MemoryWrite<'_> {
+    fn insert(&mut self, table: &str, key: &str, value: &[u8]) -> Result<(), SyncError> {
+        let table = self.tables.entry(table.to_string()).or_default();
+        Arc::make_mut(table).insert(key.to_string(), Arc::from(value));
+        Ok(())
+    }
+
+    fn remove(&mut self, table: &str, key: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        match self.tables.get_mut(table) {
+            Some(table) if table.contains_key(key) => {
+                Ok(Arc::make_mut(table).remove(key).map(|v| v.to_vec()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn commit(self: Box<Self>) -> Result<(), SyncError> {
+        let MemoryWrite { backend, tables, _writer } = *self;
+        *backend.tables.write() = tables;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn exercise(backend: &dyn StorageBackend) {
+        let read_txn = backend.begin_read().unwrap();
+        assert_eq!(read_txn.get("fruit", "apple").unwrap(), None);
+        assert!(read_txn.iter("fruit").unwrap().is_empty());
+        drop(read_txn);
+
+        let mut write_txn = backend.begin_write().unwrap();
+        for key in ["apple", "banana", "cherry", "damson"] {
+            write_txn.insert("fruit", key, key.as_bytes()).unwrap();
+        }
+        write_txn.insert("veg", "leek", b"leek").unwrap();
+        assert_eq!(write_txn.get("fruit", "banana").unwrap(), Some(b"banana".to_vec()));
+        write_txn.commit().unwrap();
+
+        // A snapshot taken now doesn't see later writes
+        let snapshot = backend.begin_read().unwrap();
+
+        let mut write_txn = backend.begin_write().unwrap();
+        assert_eq!(write_txn.remove("fruit", "cherry").unwrap(), Some(b"cherry".to_vec()));
+        assert_eq!(write_txn.remove("fruit", "cherry").unwrap(), None);
+        write_txn.commit().unwrap();
+
+        // Dropping a write transaction discards it
+        let mut write_txn = backend.begin_write().unwrap();
+        write_txn.insert("fruit", "elder", b"elder").unwrap();
+        drop(write_txn);
+
+        let read_txn = backend.begin_read().unwrap();
+        let keys = |entries: Vec<(String, Vec<u8>)>| -> Vec<String> {
+            entries.into_iter().map(|(key, _)| key).collect()
+        };
+        assert_eq!(keys(read_txn.iter("fruit").unwrap()), ["apple", "banana", "damson"]);
+        assert_eq!(keys(read_txn.range("fruit", Some("b"), Some("d")).unwrap()), ["banana"]);
+        assert_eq!(keys(read_txn.range("fruit", Some("banana"), None).unwrap()), ["banana", "damson"]);
+        assert!(read_txn.range("fruit", Some("d"), Some("b")).unwrap().is_empty());
+        assert_eq!(read_txn.get("veg", "leek").unwrap(), Some(b"leek".to_vec()));
+        assert_eq!(keys(snapshot.iter("fruit").unwrap()).len(), 4);
+    }
+
+    #[test]
+    fn test_in_memory_backend() {
+        exercise(&InMemoryBackend::new());
+    }
+
+    #[test]
+    fn test_redb_backend() {
+        let temp_dir = tempdir().unwrap();
+        exercise(&RedbBackend::create(temp_dir.path().join("test.redb")).unwrap());
+    }
+
+    #[test]
+    fn test_redb_backend_migrates_legacy_tables() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("test.redb");
+        {
+            let db = Database::create(&path).unwrap();
+            let write_txn = db.begin_write().unwrap();
+            {
+                let mut table = write_txn
+                    .open_table(TableDefinition::<&str, &str>::new("peer_did_index"))
+                    .unwrap();
+                table.insert("did:sync:abc", "00ff").unwrap();
+                let mut table = write_txn
+                    .open_table(TableDefinition::<&str, i64>::new("health"))
+                    .unwrap();
+                table.insert("last_write_check", 42).unwrap();
+                let mut table = write_txn
+                    .open_table(TableDefinition::<&[u8], &[u8]>::new("peers"))
+                    .unwrap();
+                table.insert(&[0u8, 255][..], &b"peer"[..]).unwrap();
+                let mut table = write_txn.open_table(redb_table("realms")).unwrap();
+                table.insert("realm", &b"info"[..]).unwrap();
+            }
+            write_txn.commit().unwrap();
+        }
+
+        let backend = RedbBackend::create(&path).unwrap();
+        let read_txn = backend.begin_read().unwrap();
+        assert_eq!(read_txn.get("peer_did_index", "did:sync:abc").unwrap(), Some(b"00ff".to_vec()));
+        assert_eq!(
+            read_txn.get("health", "last_write_check").unwrap(),
+            Some(42i64.to_le_bytes().to_vec())
+        );
+        assert_eq!(read_txn.get("peers", "00ff").unwrap(), Some(b"peer".to_vec()));
+        assert_eq!(read_txn.get("realms", "realm").unwrap(), Some(b"info".to_vec()));
+    }
+}
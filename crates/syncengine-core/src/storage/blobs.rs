@@ -10,19 +10,18 @@
 //! - `storage.blob_size(hash)` → `blob_manager.blob_size(&hash).await`
 //! - `storage.delete_image_blob(hash)` → `blob_manager.delete_blob(&hash).await`
 //!
-//! The redb table remains for backward compatibility and data migration.
+//! The blobs table remains for backward compatibility and data migration.
 //!
 //! Original description:
-//! Stores image blobs in redb with content hashes as keys.
+//! Stores image blobs in storage with content hashes as keys.
 //! Uses BLAKE3 for content addressing (same as Iroh).
 
 use crate::error::SyncError;
-use redb::TableDefinition;
 
 use super::Storage;
 
 /// Table for storing image blobs (key: BLAKE3 hash hex string, value: raw bytes)
-pub(crate) const BLOBS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("blobs");
+pub(crate) const BLOBS_TABLE: &str = "blobs";
 
 impl Storage {
     /// Save image blob and return its content hash
@@ -37,25 +36,18 @@ impl Storage {
         let hash = blake3::hash(&data);
         let hash_hex = hash.to_hex().to_string();
 
-        let db = self.db_handle();
-        let db_guard = db.read();
-
         // Check if blob already exists (content-addressed deduplication)
         {
-            let read_txn = db_guard.begin_read()?;
-            let table = read_txn.open_table(BLOBS_TABLE)?;
-            if table.get(hash_hex.as_str())?.is_some() {
+            let read_txn = self.backend.begin_read()?;
+            if read_txn.get(BLOBS_TABLE, &hash_hex)?.is_some() {
                 // Already exists, return hash
                 return Ok(hash_hex);
             }
         }
 
         // Store new blob
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(BLOBS_TABLE)?;
-            table.insert(hash_hex.as_str(), data.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(BLOBS_TABLE, &hash_hex, &data)?;
         write_txn.commit()?;
 
         Ok(hash_hex)
@@ -68,16 +60,8 @@ impl Storage {
     /// **DEPRECATED**: Use `BlobManager::get_bytes()` instead for P2P capability.
     #[deprecated(since = "0.2.0", note = "Use BlobManager::get_bytes() instead")]
     pub fn load_image_blob(&self, hash_hex: &str) -> Result<Option<Vec<u8>>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(BLOBS_TABLE)?;
-
-        if let Some(data) = table.get(hash_hex)? {
-            Ok(Some(data.value().to_vec()))
-        } else {
-            Ok(None)
-        }
+        let read_txn = self.backend.begin_read()?;
+        read_txn.get(BLOBS_TABLE, hash_hex)
     }
 
     /// Delete a blob by hash
@@ -89,13 +73,8 @@ impl Storage {
     /// **DEPRECATED**: Use `BlobManager::delete_blob()` instead.
     #[deprecated(since = "0.2.0", note = "Use BlobManager::delete_blob() instead")]
     pub fn delete_image_blob(&self, hash_hex: &str) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(BLOBS_TABLE)?;
-            table.remove(hash_hex)?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(BLOBS_TABLE, hash_hex)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -105,12 +84,8 @@ impl Storage {
     /// **DEPRECATED**: Use `BlobManager::has_blob()` instead.
     #[deprecated(since = "0.2.0", note = "Use BlobManager::has_blob() instead")]
     pub fn blob_exists(&self, hash_hex: &str) -> Result<bool, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(BLOBS_TABLE)?;
-
-        Ok(table.get(hash_hex)?.is_some())
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(BLOBS_TABLE, hash_hex)?.is_some())
     }
 
     /// Get the size of a blob in bytes
@@ -118,16 +93,8 @@ impl Storage {
     /// **DEPRECATED**: Use `BlobManager::blob_size()` instead.
     #[deprecated(since = "0.2.0", note = "Use BlobManager::blob_size() instead")]
     pub fn blob_size(&self, hash_hex: &str) -> Result<Option<usize>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(BLOBS_TABLE)?;
-
-        if let Some(data) = table.get(hash_hex)? {
-            Ok(Some(data.value().len()))
-        } else {
-            Ok(None)
-        }
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(BLOBS_TABLE, hash_hex)?.map(|data| data.len()))
     }
 }
 
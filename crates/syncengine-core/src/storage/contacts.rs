@@ -7,25 +7,31 @@
 
 use crate::error::SyncError;
 use crate::types::contact::{ContactInfo, ContactState, ContactStatus, PendingContact};
-use redb::{ReadableTable, TableDefinition};
 
-use super::Storage;
+use super::{string_value, Storage};
 
 /// Table for accepted contacts (key: peer_did string, value: serialized ContactInfo)
-pub(crate) const CONTACTS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("contacts");
+pub(crate) const CONTACTS_TABLE: &str = "contacts";
 
 /// Table for pending contacts (key: hex invite_id, value: serialized PendingContact)
-pub(crate) const PENDING_CONTACTS_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("pending_contacts");
+pub(crate) const PENDING_CONTACTS_TABLE: &str = "pending_contacts";
+
+/// Table for greetings on pending contacts (key: hex invite_id, value: greeting)
+///
+/// Kept apart from `PENDING_CONTACTS_TABLE` so pending records written
+/// before greetings existed still decode.
+pub(crate) const PENDING_GREETINGS_TABLE: &str = "pending_greetings";
 
 /// Table for revoked invites (key: hex invite_id, value: timestamp bytes)
-pub(crate) const REVOKED_INVITES_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("revoked_invites");
+pub(crate) const REVOKED_INVITES_TABLE: &str = "revoked_invites";
+
+/// Table for DIDs whose contact requests are accepted automatically
+/// (key: DID string, value: timestamp bytes)
+pub(crate) const CONTACT_AUTOACCEPT_TABLE: &str = "contact_autoaccept";
 
 /// Table for invites we generated (key: hex invite_id, value: timestamp bytes)
 /// Used to auto-accept incoming requests that use our invites
-pub(crate) const GENERATED_INVITES_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("generated_invites");
+pub(crate) const GENERATED_INVITES_TABLE: &str = "generated_invites";
 
 impl Storage {
     // ═══════════════════════════════════════════════════════════════════════
@@ -36,15 +42,10 @@ impl Storage {
     ///
     /// If a contact with the same peer_did exists, it will be overwritten.
     pub fn save_contact(&self, contact: &ContactInfo) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(CONTACTS_TABLE)?;
-            let serialized = postcard::to_allocvec(contact)
-                .map_err(|e| SyncError::Serialization(e.to_string()))?;
-            table.insert(contact.peer_did.as_str(), serialized.as_slice())?;
-        }
+        let serialized = postcard::to_allocvec(contact)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(CONTACTS_TABLE, &contact.peer_did, &serialized)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -53,13 +54,10 @@ impl Storage {
     ///
     /// Returns `None` if no contact exists for the given DID.
     pub fn load_contact(&self, did: &str) -> Result<Option<ContactInfo>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(CONTACTS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        if let Some(data) = table.get(did)? {
-            let contact: ContactInfo = postcard::from_bytes(data.value())
+        if let Some(data) = read_txn.get(CONTACTS_TABLE, did)? {
+            let contact: ContactInfo = postcard::from_bytes(&data)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             Ok(Some(contact))
         } else {
@@ -71,13 +69,8 @@ impl Storage {
     ///
     /// Returns `Ok(())` even if the contact doesn't exist.
     pub fn delete_contact(&self, did: &str) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(CONTACTS_TABLE)?;
-            table.remove(did)?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(CONTACTS_TABLE, did)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -86,15 +79,11 @@ impl Storage {
     ///
     /// Returns a vector of all stored contacts.
     pub fn list_contacts(&self) -> Result<Vec<ContactInfo>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(CONTACTS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut contacts = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let contact: ContactInfo = postcard::from_bytes(value.value())
+        for (_, value) in read_txn.iter(CONTACTS_TABLE)? {
+            let contact: ContactInfo = postcard::from_bytes(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             contacts.push(contact);
         }
@@ -188,15 +177,19 @@ impl Storage {
     ///
     /// If a pending contact with the same invite_id exists, it will be overwritten.
     pub fn save_pending(&self, pending: &PendingContact) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PENDING_CONTACTS_TABLE)?;
-            let serialized = postcard::to_allocvec(pending)
-                .map_err(|e| SyncError::Serialization(e.to_string()))?;
-            let key = hex::encode(&pending.invite_id);
-            table.insert(key.as_str(), serialized.as_slice())?;
+        let serialized = postcard::to_allocvec(pending)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let key = hex::encode(&pending.invite_id);
+
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(PENDING_CONTACTS_TABLE, &key, &serialized)?;
+        match &pending.greeting {
+            Some(greeting) => {
+                write_txn.insert(PENDING_GREETINGS_TABLE, &key, greeting.as_bytes())?;
+            }
+            None => {
+                write_txn.remove(PENDING_GREETINGS_TABLE, &key)?;
+            }
         }
         write_txn.commit()?;
         Ok(())
@@ -206,15 +199,16 @@ impl Storage {
     ///
     /// Returns `None` if no pending contact exists for the given invite ID.
     pub fn load_pending(&self, invite_id: &[u8; 16]) -> Result<Option<PendingContact>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(PENDING_CONTACTS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
         let key = hex::encode(invite_id);
 
-        if let Some(data) = table.get(key.as_str())? {
-            let pending: PendingContact = postcard::from_bytes(data.value())
+        if let Some(data) = read_txn.get(PENDING_CONTACTS_TABLE, &key)? {
+            let mut pending: PendingContact = postcard::from_bytes(&data)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            pending.greeting = read_txn
+                .get(PENDING_GREETINGS_TABLE, &key)?
+                .map(string_value)
+                .transpose()?;
             Ok(Some(pending))
         } else {
             Ok(None)
@@ -225,14 +219,10 @@ impl Storage {
     ///
     /// Returns `Ok(())` even if the pending contact doesn't exist.
     pub fn delete_pending(&self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PENDING_CONTACTS_TABLE)?;
-            let key = hex::encode(invite_id);
-            table.remove(key.as_str())?;
-        }
+        let key = hex::encode(invite_id);
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(PENDING_CONTACTS_TABLE, &key)?;
+        write_txn.remove(PENDING_GREETINGS_TABLE, &key)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -259,18 +249,41 @@ impl Storage {
             .collect())
     }
 
+    /// Delete pending contacts that have expired as of `now` (Unix timestamp)
+    ///
+    /// Returns the removed requests. Their invites are left unrevoked.
+    pub fn delete_expired_pending(&self, now: i64) -> Result<Vec<PendingContact>, SyncError> {
+        let expired: Vec<PendingContact> = self
+            .list_all_pending()?
+            .into_iter()
+            .filter(|p| p.is_expired_at(now))
+            .collect();
+        if expired.is_empty() {
+            return Ok(expired);
+        }
+
+        let mut write_txn = self.backend.begin_write()?;
+        for pending in &expired {
+            let key = hex::encode(pending.invite_id);
+            write_txn.remove(PENDING_CONTACTS_TABLE, &key)?;
+            write_txn.remove(PENDING_GREETINGS_TABLE, &key)?;
+        }
+        write_txn.commit()?;
+        Ok(expired)
+    }
+
     /// List all pending contacts (internal helper)
     fn list_all_pending(&self) -> Result<Vec<PendingContact>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(PENDING_CONTACTS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut pending = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let contact: PendingContact = postcard::from_bytes(value.value())
+        for (key, value) in read_txn.iter(PENDING_CONTACTS_TABLE)? {
+            let mut contact: PendingContact = postcard::from_bytes(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            contact.greeting = read_txn
+                .get(PENDING_GREETINGS_TABLE, &key)?
+                .map(string_value)
+                .transpose()?;
             pending.push(contact);
         }
 
@@ -285,15 +298,10 @@ impl Storage {
     ///
     /// Stores the current timestamp as the revocation time.
     pub fn revoke_invite(&self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(REVOKED_INVITES_TABLE)?;
-            let key = hex::encode(invite_id);
-            let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
-            table.insert(key.as_str(), timestamp.as_slice())?;
-        }
+        let key = hex::encode(invite_id);
+        let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(REVOKED_INVITES_TABLE, &key, &timestamp)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -302,13 +310,10 @@ impl Storage {
     ///
     /// Returns `true` if the invite is in the revocation list.
     pub fn is_invite_revoked(&self, invite_id: &[u8; 16]) -> Result<bool, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(REVOKED_INVITES_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
         let key = hex::encode(invite_id);
 
-        Ok(table.get(key.as_str())?.is_some())
+        Ok(read_txn.get(REVOKED_INVITES_TABLE, &key)?.is_some())
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -319,15 +324,10 @@ impl Storage {
     ///
     /// Used to auto-accept incoming requests that use our invites.
     pub fn save_generated_invite(&self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(GENERATED_INVITES_TABLE)?;
-            let key = hex::encode(invite_id);
-            let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
-            table.insert(key.as_str(), timestamp.as_slice())?;
-        }
+        let key = hex::encode(invite_id);
+        let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(GENERATED_INVITES_TABLE, &key, &timestamp)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -336,35 +336,55 @@ impl Storage {
     ///
     /// Returns `true` if this invite_id was created by us.
     pub fn is_our_generated_invite(&self, invite_id: &[u8; 16]) -> Result<bool, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(GENERATED_INVITES_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
         let key = hex::encode(invite_id);
 
-        Ok(table.get(key.as_str())?.is_some())
+        Ok(read_txn.get(GENERATED_INVITES_TABLE, &key)?.is_some())
     }
 
     /// Remove a generated invite record (after it's been used or cancelled)
     pub fn delete_generated_invite(&self, invite_id: &[u8; 16]) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(GENERATED_INVITES_TABLE)?;
-            let key = hex::encode(invite_id);
-            table.remove(key.as_str())?;
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(GENERATED_INVITES_TABLE, &hex::encode(invite_id))?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // Contact Auto-Accept Allowlist
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Replace the DIDs whose contact requests are accepted automatically
+    pub fn set_contact_autoaccept(&self, dids: &[String]) -> Result<(), SyncError> {
+        let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove_range(CONTACT_AUTOACCEPT_TABLE, None, None)?;
+        for did in dids {
+            write_txn.insert(CONTACT_AUTOACCEPT_TABLE, did, &timestamp)?;
         }
         write_txn.commit()?;
         Ok(())
     }
+
+    /// List the DIDs whose contact requests are accepted automatically
+    pub fn list_contact_autoaccept(&self) -> Result<Vec<String>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        let entries = read_txn.iter(CONTACT_AUTOACCEPT_TABLE)?;
+        Ok(entries.into_iter().map(|(did, _)| did).collect())
+    }
+
+    /// Check if contact requests from this DID are accepted automatically
+    pub fn is_contact_autoaccepted(&self, did: &str) -> Result<bool, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(CONTACT_AUTOACCEPT_TABLE, did)?.is_some())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::invite::NodeAddrBytes;
-    use crate::types::contact::ProfileSnapshot;
+    use crate::types::contact::{ProfileSnapshot, PENDING_CONTACT_TTL_SECS};
     use tempfile::tempdir;
 
     fn create_test_contact(did: &str, name: &str) -> ContactInfo {
@@ -386,6 +406,7 @@ mod tests {
             is_favorite: false,
             encryption_keys: None,
             mutual_peers: vec![],
+            nickname: None,
         }
     }
 
@@ -405,6 +426,7 @@ mod tests {
             created_at: chrono::Utc::now().timestamp(),
             encryption_keys: None,
             peer_contact_dids: vec![],
+            greeting: None,
         }
     }
 
@@ -619,6 +641,43 @@ mod tests {
         assert!(storage.is_invite_revoked(&invite_id).unwrap());
     }
 
+    #[test]
+    fn test_delete_expired_pending_keeps_fresh_requests() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        let mut expired = create_test_pending([1u8; 16], "did:sync:old", ContactState::OutgoingPending);
+        expired.created_at = now - PENDING_CONTACT_TTL_SECS - 1;
+        let fresh = create_test_pending([2u8; 16], "did:sync:new", ContactState::IncomingPending);
+        storage.save_pending(&expired).unwrap();
+        storage.save_pending(&fresh).unwrap();
+
+        let removed = storage.delete_expired_pending(now).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].invite_id, [1u8; 16]);
+
+        assert!(storage.load_pending(&[1u8; 16]).unwrap().is_none());
+        assert!(storage.load_pending(&[2u8; 16]).unwrap().is_some());
+        // Expired outgoing requests can be sent again
+        assert!(!storage.is_invite_revoked(&[1u8; 16]).unwrap());
+    }
+
+    #[test]
+    fn test_contact_autoaccept_list_is_replaced() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+
+        storage
+            .set_contact_autoaccept(&["did:sync:love".to_string(), "did:sync:joy".to_string()])
+            .unwrap();
+        assert!(storage.is_contact_autoaccepted("did:sync:joy").unwrap());
+
+        storage.set_contact_autoaccept(&["did:sync:peace".to_string()]).unwrap();
+        assert!(!storage.is_contact_autoaccepted("did:sync:joy").unwrap());
+        assert_eq!(storage.list_contact_autoaccept().unwrap(), vec!["did:sync:peace"]);
+    }
+
     #[test]
     fn test_overwrite_contact() {
         let temp_dir = tempdir().unwrap();
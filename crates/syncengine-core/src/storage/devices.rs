@@ -0,0 +1,118 @@
+//! Device Storage - this node's device ID and identities' device lists
+//!
+//! A node whose identity was imported from another device has its own
+//! device ID, stored next to the identity. Signed device lists are stored
+//! per DID, for our own identity and for contacts who use several devices.
+
+use crate::error::SyncError;
+use crate::identity::{DeviceId, SignedDeviceList, DEVICE_ID_SIZE};
+
+use super::{Storage, IDENTITY_TABLE};
+
+/// Table for storing signed device lists (key: DID string, value: serialized SignedDeviceList)
+pub(crate) const DEVICE_LISTS_TABLE: &str = "device_lists";
+
+impl Storage {
+    /// Device ID storage key, in the identity table
+    const DEVICE_ID_KEY: &'static str = "device_id";
+
+    /// Save which of the identity's devices this node is.
+    pub fn save_device_id(&self, device: &DeviceId) -> Result<(), SyncError> {
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(IDENTITY_TABLE, Self::DEVICE_ID_KEY, device.as_bytes())?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load which of the identity's devices this node is.
+    ///
+    /// Returns [`DeviceId::PRIMARY`] if the identity was created here.
+    pub fn load_device_id(&self) -> Result<DeviceId, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        match read_txn.get(IDENTITY_TABLE, Self::DEVICE_ID_KEY)? {
+            Some(v) => {
+                let bytes: [u8; DEVICE_ID_SIZE] = v
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| SyncError::Storage("Invalid device ID length".to_string()))?;
+                Ok(DeviceId::from_bytes(bytes))
+            }
+            None => Ok(DeviceId::PRIMARY),
+        }
+    }
+
+    /// Save an identity's signed device list, replacing any stored one.
+    ///
+    /// Callers decide whether the list is newer; see
+    /// [`SignedDeviceList::supersedes`].
+    pub fn save_device_list(&self, list: &SignedDeviceList) -> Result<(), SyncError> {
+        let serialized =
+            postcard::to_allocvec(list).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(DEVICE_LISTS_TABLE, list.list.did.as_str(), &serialized)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load an identity's signed device list.
+    ///
+    /// Returns `None` if the identity never published one, which means it
+    /// is used on a single device.
+    pub fn load_device_list(&self, did: &str) -> Result<Option<SignedDeviceList>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        match read_txn.get(DEVICE_LISTS_TABLE, did)? {
+            Some(data) => postcard::from_bytes(&data)
+                .map(Some)
+                .map_err(|e| SyncError::Serialization(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Delete an identity's device list.
+    pub fn delete_device_list(&self, did: &str) -> Result<(), SyncError> {
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(DEVICE_LISTS_TABLE, did)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::{DeviceEntry, DeviceList, Did, HybridKeypair};
+
+    #[test]
+    fn test_device_id_defaults_to_primary() {
+        let storage = Storage::in_memory();
+        assert_eq!(storage.load_device_id().unwrap(), DeviceId::PRIMARY);
+
+        let device = DeviceId::generate();
+        storage.save_device_id(&device).unwrap();
+        assert_eq!(storage.load_device_id().unwrap(), device);
+    }
+
+    #[test]
+    fn test_device_list_roundtrip() {
+        let storage = Storage::in_memory();
+        let keypair = HybridKeypair::generate();
+        let did = Did::from_public_key(&keypair.public_key());
+        assert!(storage.load_device_list(did.as_str()).unwrap().is_none());
+
+        let mut list = DeviceList::new(did.clone());
+        list.upsert(DeviceEntry {
+            device: DeviceId::PRIMARY,
+            endpoint_id: Some([7; 32]),
+            name: "desktop".to_string(),
+            added_at: 1,
+        });
+        storage.save_device_list(&SignedDeviceList::sign(list.clone(), &keypair)).unwrap();
+
+        let loaded = storage.load_device_list(did.as_str()).unwrap().unwrap();
+        assert!(loaded.verify());
+        assert_eq!(loaded.list, list);
+
+        storage.delete_device_list(did.as_str()).unwrap();
+        assert!(storage.load_device_list(did.as_str()).unwrap().is_none());
+    }
+}
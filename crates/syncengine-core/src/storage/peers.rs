@@ -15,23 +15,22 @@
 
 use crate::error::SyncError;
 use crate::types::contact::ContactInfo;
-use crate::types::peer::{ContactDetails, Peer, PeerSource, PeerStatus};
+use crate::types::peer::{ContactDetails, LinkStats, Peer, PeerSource, PeerStatus};
 use iroh::PublicKey;
-use redb::{ReadableTable, TableDefinition};
 
-use super::Storage;
+use super::{string_value, Storage};
 
 /// Table for unified peers (key: hex endpoint_id, value: serialized Peer)
-pub(crate) const UNIFIED_PEERS_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("unified_peers");
+pub(crate) const UNIFIED_PEERS_TABLE: &str = "unified_peers";
 
 /// Index for DID → endpoint_id lookup (key: DID string, value: hex endpoint_id)
-pub(crate) const PEER_DID_INDEX: TableDefinition<&str, &str> =
-    TableDefinition::new("peer_did_index");
+pub(crate) const PEER_DID_INDEX: &str = "peer_did_index";
 
 /// Flag table to track migration status
-pub(crate) const MIGRATION_FLAGS_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("migration_flags");
+pub(crate) const MIGRATION_FLAGS_TABLE: &str = "migration_flags";
+
+/// Table written by `PeerRegistry` before peers were unified
+const OLD_PEERS_TABLE: &str = "peers";
 
 const MIGRATION_FLAG_KEY: &str = "peers_unified_v1";
 
@@ -45,23 +44,16 @@ impl Storage {
     /// If a peer with the same endpoint_id exists, it will be overwritten.
     /// This also updates the DID index if the peer has a DID.
     pub fn save_peer(&self, peer: &Peer) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut peers_table = write_txn.open_table(UNIFIED_PEERS_TABLE)?;
-            let mut did_index = write_txn.open_table(PEER_DID_INDEX)?;
-
-            let key = hex::encode(&peer.endpoint_id);
-            let serialized = postcard::to_allocvec(peer)
-                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let key = hex::encode(peer.endpoint_id);
+        let serialized = postcard::to_allocvec(peer)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
 
-            peers_table.insert(key.as_str(), serialized.as_slice())?;
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(UNIFIED_PEERS_TABLE, &key, &serialized)?;
 
-            // Update DID index if peer has a DID
-            if let Some(ref did) = peer.did {
-                did_index.insert(did.as_str(), key.as_str())?;
-            }
+        // Update DID index if peer has a DID
+        if let Some(ref did) = peer.did {
+            write_txn.insert(PEER_DID_INDEX, did, key.as_bytes())?;
         }
         write_txn.commit()?;
         Ok(())
@@ -71,19 +63,7 @@ impl Storage {
     ///
     /// Returns `None` if no peer exists with the given endpoint ID.
     pub fn load_peer(&self, endpoint_id: &PublicKey) -> Result<Option<Peer>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(UNIFIED_PEERS_TABLE)?;
-
-        let key = hex::encode(endpoint_id.as_bytes());
-        if let Some(data) = table.get(key.as_str())? {
-            let peer: Peer = postcard::from_bytes(data.value())
-                .map_err(|e| SyncError::Serialization(e.to_string()))?;
-            Ok(Some(peer))
-        } else {
-            Ok(None)
-        }
+        self.load_peer_by_bytes(endpoint_id.as_bytes())
     }
 
     /// Load a peer by endpoint ID bytes
@@ -91,13 +71,10 @@ impl Storage {
     /// Convenience method when you have raw bytes instead of a PublicKey.
     pub fn load_peer_by_bytes(&self, endpoint_id: &[u8; 32]) -> Result<Option<Peer>, SyncError> {
         let key = hex::encode(endpoint_id);
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(UNIFIED_PEERS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
-        if let Some(data) = table.get(key.as_str())? {
-            let peer: Peer = postcard::from_bytes(data.value())
+        if let Some(data) = read_txn.get(UNIFIED_PEERS_TABLE, &key)? {
+            let peer: Peer = postcard::from_bytes(&data)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             Ok(Some(peer))
         } else {
@@ -110,21 +87,17 @@ impl Storage {
     /// Uses the DID index for fast lookup. Returns `None` if no peer
     /// with the given DID exists.
     pub fn load_peer_by_did(&self, did: &str) -> Result<Option<Peer>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
+        let read_txn = self.backend.begin_read()?;
 
         // First, look up the endpoint_id in the DID index
-        let did_index = read_txn.open_table(PEER_DID_INDEX)?;
-        let endpoint_key = match did_index.get(did)? {
-            Some(v) => v.value().to_string(),
+        let endpoint_key = match read_txn.get(PEER_DID_INDEX, did)? {
+            Some(v) => string_value(v)?,
             None => return Ok(None),
         };
 
         // Then look up the peer by endpoint_id
-        let peers_table = read_txn.open_table(UNIFIED_PEERS_TABLE)?;
-        if let Some(data) = peers_table.get(endpoint_key.as_str())? {
-            let peer: Peer = postcard::from_bytes(data.value())
+        if let Some(data) = read_txn.get(UNIFIED_PEERS_TABLE, &endpoint_key)? {
+            let peer: Peer = postcard::from_bytes(&data)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             Ok(Some(peer))
         } else {
@@ -141,19 +114,12 @@ impl Storage {
         // First load the peer to get the DID (if any) for index cleanup
         let peer_did = self.load_peer(endpoint_id)?.and_then(|p| p.did);
 
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut peers_table = write_txn.open_table(UNIFIED_PEERS_TABLE)?;
-            let mut did_index = write_txn.open_table(PEER_DID_INDEX)?;
-
-            peers_table.remove(key.as_str())?;
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(UNIFIED_PEERS_TABLE, &key)?;
 
-            // Remove from DID index if the peer had a DID
-            if let Some(did) = peer_did {
-                did_index.remove(did.as_str())?;
-            }
+        // Remove from DID index if the peer had a DID
+        if let Some(did) = peer_did {
+            write_txn.remove(PEER_DID_INDEX, &did)?;
         }
         write_txn.commit()?;
         Ok(())
@@ -161,15 +127,11 @@ impl Storage {
 
     /// List all unified peers
     pub fn list_peers(&self) -> Result<Vec<Peer>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(UNIFIED_PEERS_TABLE)?;
+        let read_txn = self.backend.begin_read()?;
 
         let mut peers = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let peer: Peer = postcard::from_bytes(value.value())
+        for (_, value) in read_txn.iter(UNIFIED_PEERS_TABLE)? {
+            let peer: Peer = postcard::from_bytes(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             peers.push(peer);
         }
@@ -229,27 +191,15 @@ impl Storage {
 
     /// Check if migration to unified peers has been completed
     pub fn is_peers_migrated(&self) -> Result<bool, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-
-        // Try to open the migration flags table
-        match read_txn.open_table(MIGRATION_FLAGS_TABLE) {
-            Ok(table) => Ok(table.get(MIGRATION_FLAG_KEY)?.is_some()),
-            Err(_) => Ok(false), // Table doesn't exist, not migrated
-        }
+        let read_txn = self.backend.begin_read()?;
+        Ok(read_txn.get(MIGRATION_FLAGS_TABLE, MIGRATION_FLAG_KEY)?.is_some())
     }
 
     /// Mark migration as complete
     fn mark_peers_migrated(&self) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(MIGRATION_FLAGS_TABLE)?;
-            let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
-            table.insert(MIGRATION_FLAG_KEY, timestamp.as_slice())?;
-        }
+        let timestamp = chrono::Utc::now().timestamp().to_le_bytes();
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(MIGRATION_FLAGS_TABLE, MIGRATION_FLAG_KEY, &timestamp)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -280,37 +230,22 @@ impl Storage {
         // 2. Migrate discovered peers from PeerRegistry
         // Note: This reads from the old PEERS_TABLE via PeerRegistry
         // We'll migrate any that weren't already migrated as contacts
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-
-        // Try to open the old peers table
-        use redb::TableDefinition;
-        const OLD_PEERS_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("peers");
-
-        if let Ok(old_peers_table) = read_txn.open_table(OLD_PEERS_TABLE) {
-            for entry in old_peers_table.iter()? {
-                let (_, value) = entry?;
-
-                // Try to deserialize as old PeerInfo
-                if let Ok(old_peer) =
-                    postcard::from_bytes::<crate::peers::PeerInfo>(value.value())
-                {
-                    // Skip if already migrated via contact
-                    if migrated_endpoints.contains(&old_peer.endpoint_id) {
-                        continue;
-                    }
-
-                    let peer = Self::peer_info_to_peer(&old_peer);
-                    self.save_peer(&peer)?;
-                    migrated_endpoints.insert(peer.endpoint_id);
-                    migrated_count += 1;
+        let old_peers = self.backend.begin_read()?.iter(OLD_PEERS_TABLE)?;
+        for (_, value) in old_peers {
+            // Try to deserialize as old PeerInfo
+            if let Ok(old_peer) = postcard::from_bytes::<crate::peers::PeerInfo>(&value) {
+                // Skip if already migrated via contact
+                if migrated_endpoints.contains(&old_peer.endpoint_id) {
+                    continue;
                 }
+
+                let peer = Self::peer_info_to_peer(&old_peer);
+                self.save_peer(&peer)?;
+                migrated_endpoints.insert(peer.endpoint_id);
+                migrated_count += 1;
             }
         }
 
-        drop(read_txn);
-
         // Mark migration as complete
         self.mark_peers_migrated()?;
 
@@ -341,6 +276,9 @@ impl Storage {
             connection_attempts: 0,
             successful_connections: 0,
             last_attempt: 0,
+            link: LinkStats::default(),
+            connection_quality: 0,
+            clock_skew_ms: None,
         }
     }
 
@@ -354,12 +292,15 @@ impl Storage {
             contact_info: None,
             source: old.source.clone(),
             shared_realms: old.shared_realms.clone(),
-            node_addr: None,
+            node_addr: old.node_addr.clone(),
             status: old.status,
             last_seen: old.last_seen,
             connection_attempts: old.connection_attempts,
             successful_connections: old.successful_connections,
             last_attempt: old.last_attempt,
+            link: old.link,
+            connection_quality: old.connection_quality,
+            clock_skew_ms: None,
         }
     }
 
@@ -591,6 +532,7 @@ mod tests {
             is_favorite: true,
             encryption_keys: None,
             mutual_peers: vec!["did:sync:joy".to_string()],
+            nickname: None,
         };
 
         let peer = Storage::contact_info_to_peer(&contact);
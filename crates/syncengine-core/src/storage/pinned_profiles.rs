@@ -4,15 +4,15 @@
 //! Profile pins provide P2P redundancy by allowing nodes to serve
 //! profiles for peers who may be offline.
 
+use std::collections::HashMap;
+
 use crate::error::SyncError;
 use crate::types::{PinRelationship, ProfilePin};
-use redb::{ReadableTable, TableDefinition};
 
 use super::Storage;
 
 /// Table for storing pinned profiles (key: DID string, value: serialized ProfilePin)
-pub(crate) const PINNED_PROFILES_TABLE: TableDefinition<&str, &[u8]> =
-    TableDefinition::new("pinned_profiles");
+pub(crate) const PINNED_PROFILES_TABLE: &str = "pinned_profiles";
 
 /// Configuration for profile pinning storage limits
 #[derive(Debug, Clone)]
@@ -39,15 +39,10 @@ impl Storage {
     /// This does NOT enforce storage limits - use `save_pinned_profile_with_limits`
     /// for automatic eviction.
     pub fn save_pinned_profile(&self, pin: &ProfilePin) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PINNED_PROFILES_TABLE)?;
-            let serialized =
-                postcard::to_allocvec(pin).map_err(|e| SyncError::Serialization(e.to_string()))?;
-            table.insert(pin.did.as_str(), serialized.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        let serialized =
+            postcard::to_allocvec(pin).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        write_txn.insert(PINNED_PROFILES_TABLE, pin.did.as_str(), serialized.as_slice())?;
         write_txn.commit()?;
         Ok(())
     }
@@ -56,13 +51,9 @@ impl Storage {
     ///
     /// Returns `None` if no pin exists for the given DID.
     pub fn load_pinned_profile(&self, did: &str) -> Result<Option<ProfilePin>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(PINNED_PROFILES_TABLE)?;
-
-        if let Some(data) = table.get(did)? {
-            let pin: ProfilePin = postcard::from_bytes(data.value())
+        let read_txn = self.backend.begin_read()?;
+        if let Some(data) = read_txn.get(PINNED_PROFILES_TABLE, did)? {
+            let pin: ProfilePin = postcard::from_bytes(&data)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             Ok(Some(pin))
         } else {
@@ -70,17 +61,29 @@ impl Storage {
         }
     }
 
+    /// Load the pinned profiles for many DIDs in one read transaction.
+    ///
+    /// DIDs without a pin are left out of the returned map.
+    pub fn load_pinned_profiles(&self, dids: &[&str]) -> Result<HashMap<String, ProfilePin>, SyncError> {
+        let read_txn = self.backend.begin_read()?;
+        let mut pins = HashMap::with_capacity(dids.len());
+        for did in dids {
+            if let Some(data) = read_txn.get(PINNED_PROFILES_TABLE, did)? {
+                let pin: ProfilePin = postcard::from_bytes(&data)
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?;
+                pins.insert(pin.did.clone(), pin);
+            }
+        }
+
+        Ok(pins)
+    }
+
     /// Delete a pinned profile by DID.
     ///
     /// Returns `Ok(())` even if the pin doesn't exist.
     pub fn delete_pinned_profile(&self, did: &str) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PINNED_PROFILES_TABLE)?;
-            table.remove(did)?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(PINNED_PROFILES_TABLE, did)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -89,15 +92,10 @@ impl Storage {
     ///
     /// Returns a vector of all stored profile pins.
     pub fn list_pinned_profiles(&self) -> Result<Vec<ProfilePin>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(PINNED_PROFILES_TABLE)?;
-
+        let read_txn = self.backend.begin_read()?;
         let mut pins = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let pin: ProfilePin = postcard::from_bytes(value.value())
+        for (_, value) in read_txn.iter(PINNED_PROFILES_TABLE)? {
+            let pin: ProfilePin = postcard::from_bytes(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             pins.push(pin);
         }
@@ -139,6 +139,7 @@ mod tests {
             is_favorite: false,
             encryption_keys: None,
             mutual_peers: vec![],
+            nickname: None,
         }
     }
 
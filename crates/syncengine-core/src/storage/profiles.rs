@@ -4,27 +4,21 @@
 
 use crate::error::SyncError;
 use crate::types::UserProfile;
-use redb::{ReadableTable, TableDefinition};
 
 use super::Storage;
 
 /// Table for storing user profiles (key: peer_id string, value: serialized UserProfile)
-pub(crate) const PROFILES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("profiles");
+pub(crate) const PROFILES_TABLE: &str = "profiles";
 
 impl Storage {
     /// Save a profile to the database
     ///
     /// If a profile with the same peer_id exists, it will be overwritten.
     pub fn save_profile(&self, profile: &UserProfile) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PROFILES_TABLE)?;
-            let serialized = postcard::to_allocvec(profile)
-                .map_err(|e| SyncError::Serialization(e.to_string()))?;
-            table.insert(profile.peer_id.as_str(), serialized.as_slice())?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        let serialized = postcard::to_allocvec(profile)
+            .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        write_txn.insert(PROFILES_TABLE, profile.peer_id.as_str(), serialized.as_slice())?;
         write_txn.commit()?;
         Ok(())
     }
@@ -33,13 +27,9 @@ impl Storage {
     ///
     /// Returns `None` if no profile exists for the given peer.
     pub fn load_profile(&self, peer_id: &str) -> Result<Option<UserProfile>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(PROFILES_TABLE)?;
-
-        if let Some(data) = table.get(peer_id)? {
-            let profile: UserProfile = postcard::from_bytes(data.value())
+        let read_txn = self.backend.begin_read()?;
+        if let Some(data) = read_txn.get(PROFILES_TABLE, peer_id)? {
+            let profile: UserProfile = postcard::from_bytes(&data)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             Ok(Some(profile))
         } else {
@@ -51,13 +41,8 @@ impl Storage {
     ///
     /// Returns `Ok(())` even if the profile doesn't exist.
     pub fn delete_profile(&self, peer_id: &str) -> Result<(), SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let write_txn = db_guard.begin_write()?;
-        {
-            let mut table = write_txn.open_table(PROFILES_TABLE)?;
-            table.remove(peer_id)?;
-        }
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(PROFILES_TABLE, peer_id)?;
         write_txn.commit()?;
         Ok(())
     }
@@ -66,15 +51,10 @@ impl Storage {
     ///
     /// Returns a vector of all stored profiles.
     pub fn list_profiles(&self) -> Result<Vec<UserProfile>, SyncError> {
-        let db = self.db_handle();
-        let db_guard = db.read();
-        let read_txn = db_guard.begin_read()?;
-        let table = read_txn.open_table(PROFILES_TABLE)?;
-
+        let read_txn = self.backend.begin_read()?;
         let mut profiles = Vec::new();
-        for entry in table.iter()? {
-            let (_, value) = entry?;
-            let profile: UserProfile = postcard::from_bytes(value.value())
+        for (_, value) in read_txn.iter(PROFILES_TABLE)? {
+            let profile: UserProfile = postcard::from_bytes(&value)
                 .map_err(|e| SyncError::Serialization(e.to_string()))?;
             profiles.push(profile);
         }
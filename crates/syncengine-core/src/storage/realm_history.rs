@@ -0,0 +1,79 @@
+//! Realm History Storage - change history dropped from realm documents
+//!
+//! When a realm's history is compacted away (here or by a peer), the
+//! document as it was just before is archived so task activity can still be
+//! read and replicas that missed the compaction can be brought across it.
+//! Archives are keyed by `"{realm_base58}/{sequence}"` with a zero-padded
+//! sequence, so a realm's archives read back oldest first. They are local
+//! only and never synced.
+
+use crate::error::SyncError;
+use crate::types::RealmId;
+
+use super::Storage;
+
+/// Table for archived documents (key: "realm/sequence", value: Automerge bytes)
+pub(crate) const REALM_HISTORY_TABLE: &str = "realm_history";
+
+/// Key range covering every archive of a realm (`'0'` sorts right after `'/'`)
+fn realm_range(realm_id: &RealmId) -> (String, String) {
+    let prefix = realm_id.to_base58();
+    (format!("{}/", prefix), format!("{}0", prefix))
+}
+
+impl Storage {
+    /// Archive a realm document whose history is about to be dropped.
+    pub fn archive_realm_history(&self, realm_id: &RealmId, doc_bytes: &[u8]) -> Result<(), SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let mut write_txn = self.backend.begin_write()?;
+        let sequence = write_txn.range(REALM_HISTORY_TABLE, Some(&start), Some(&end))?.len();
+        let key = format!("{}{:010}", start, sequence);
+        write_txn.insert(REALM_HISTORY_TABLE, key.as_str(), doc_bytes)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// List the archived documents of a realm, oldest first.
+    pub fn list_realm_history(&self, realm_id: &RealmId) -> Result<Vec<Vec<u8>>, SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let read_txn = self.backend.begin_read()?;
+        let entries = read_txn.range(REALM_HISTORY_TABLE, Some(&start), Some(&end))?;
+        Ok(entries.into_iter().map(|(_, doc_bytes)| doc_bytes).collect())
+    }
+
+    /// Remove every archive of a realm.
+    pub fn delete_realm_history(&self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove_range(REALM_HISTORY_TABLE, Some(&start), Some(&end))?;
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_archives_list_in_order_per_realm() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm_a = RealmId::new();
+        let realm_b = RealmId::new();
+        assert!(storage.list_realm_history(&realm_a).unwrap().is_empty());
+
+        for n in 0..12u8 {
+            storage.archive_realm_history(&realm_a, &[n]).unwrap();
+        }
+        storage.archive_realm_history(&realm_b, b"other").unwrap();
+
+        let archives = storage.list_realm_history(&realm_a).unwrap();
+        assert_eq!(archives, (0..12u8).map(|n| vec![n]).collect::<Vec<_>>());
+
+        storage.delete_realm_history(&realm_a).unwrap();
+        assert!(storage.list_realm_history(&realm_a).unwrap().is_empty());
+        assert_eq!(storage.list_realm_history(&realm_b).unwrap(), vec![b"other".to_vec()]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Realm Member Storage - roster of DIDs observed in shared realms
+//!
+//! Members are keyed by `"{realm_base58}/{did}"` so that all members of a
+//! realm can be read with a single prefix range scan. The roster is local
+//! bookkeeping only and is never synced.
+
+use crate::error::SyncError;
+use crate::types::{RealmId, RealmMember};
+
+use super::Storage;
+
+/// Table for storing realm members (key: "realm/did", value: serialized RealmMember)
+pub(crate) const REALM_MEMBERS_TABLE: &str = "realm_members";
+
+/// Seconds after a leave during which sightings of that member are ignored.
+///
+/// Gossip does not preserve ordering across broadcasts, so messages a member
+/// sent just before leaving can arrive after their `Leave`. Without this
+/// window those stragglers would immediately re-add the departed member.
+pub(crate) const REJOIN_GRACE_SECS: i64 = 10;
+
+/// Smallest change in `last_seen` worth writing to disk.
+///
+/// Every incoming sync message is a sighting, so without this each one
+/// would cost a write transaction. The roster only needs minute-level
+/// precision to show who has gone quiet.
+pub(crate) const LAST_SEEN_RESOLUTION_SECS: i64 = 60;
+
+/// Build the storage key for a member of a realm
+fn member_key(realm_id: &RealmId, did: &str) -> String {
+    format!("{}/{}", realm_id.to_base58(), did)
+}
+
+/// Key range covering every member of a realm (`'0'` sorts right after `'/'`)
+fn realm_range(realm_id: &RealmId) -> (String, String) {
+    let prefix = realm_id.to_base58();
+    (format!("{}/", prefix), format!("{}0", prefix))
+}
+
+impl Storage {
+    /// Record that a DID was seen participating in a realm.
+    ///
+    /// The first sighting sets `joined_at`; later sightings bump `last_seen`,
+    /// but only once it has moved by [`LAST_SEEN_RESOLUTION_SECS`], so
+    /// frequent sightings don't each write to disk. A member who left is
+    /// re-admitted (with a fresh `joined_at`) only once [`REJOIN_GRACE_SECS`]
+    /// have passed since their leave.
+    ///
+    /// # Returns
+    ///
+    /// `true` if this DID (re)joined the realm with this sighting.
+    pub fn record_realm_member(
+        &self,
+        realm_id: &RealmId,
+        did: &str,
+        seen_at: i64,
+    ) -> Result<bool, SyncError> {
+        let key = member_key(realm_id, did);
+        {
+            let read_txn = self.backend.begin_read()?;
+            if let Some(data) = read_txn.get(REALM_MEMBERS_TABLE, &key)? {
+                let member: RealmMember = postcard::from_bytes(&data)
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?;
+                if member.left_at.is_none()
+                    && seen_at < member.last_seen + LAST_SEEN_RESOLUTION_SECS
+                {
+                    return Ok(false);
+                }
+            }
+        }
+        let mut write_txn = self.backend.begin_write()?;
+        let existing: Option<RealmMember> = match write_txn.get(REALM_MEMBERS_TABLE, &key)? {
+            Some(data) => Some(
+                postcard::from_bytes(&data).map_err(|e| SyncError::Serialization(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let (member, joined) = match existing {
+            Some(member)
+                if member
+                    .left_at
+                    .is_some_and(|left| seen_at <= left + REJOIN_GRACE_SECS) =>
+            {
+                // Straggler from before the leave - keep the tombstone as is
+                return Ok(false);
+            }
+            Some(member) if member.left_at.is_some() => (RealmMember::new(did, seen_at), true),
+            Some(mut member) => {
+                member.last_seen = member.last_seen.max(seen_at);
+                (member, false)
+            }
+            None => (RealmMember::new(did, seen_at), true),
+        };
+
+        let serialized =
+            postcard::to_allocvec(&member).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        write_txn.insert(REALM_MEMBERS_TABLE, &key, &serialized)?;
+        write_txn.commit()?;
+        Ok(joined)
+    }
+
+    /// Mark a member as having left a realm.
+    ///
+    /// The entry is kept as a tombstone so late messages cannot re-add them.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the member was active before this call.
+    pub fn mark_realm_member_left(
+        &self,
+        realm_id: &RealmId,
+        did: &str,
+        left_at: i64,
+    ) -> Result<bool, SyncError> {
+        let key = member_key(realm_id, did);
+        let mut write_txn = self.backend.begin_write()?;
+        let mut member: RealmMember = match write_txn.get(REALM_MEMBERS_TABLE, &key)? {
+            Some(data) => {
+                postcard::from_bytes(&data).map_err(|e| SyncError::Serialization(e.to_string()))?
+            }
+            None => RealmMember::new(did, left_at),
+        };
+
+        let was_active = member.left_at.is_none();
+        member.left_at = Some(left_at);
+        member.last_seen = member.last_seen.max(left_at);
+
+        let serialized =
+            postcard::to_allocvec(&member).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        write_txn.insert(REALM_MEMBERS_TABLE, &key, &serialized)?;
+        write_txn.commit()?;
+        Ok(was_active)
+    }
+
+    /// List the active members of a realm, ordered by join time.
+    ///
+    /// Members who have left are not included.
+    pub fn list_realm_members(&self, realm_id: &RealmId) -> Result<Vec<RealmMember>, SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let read_txn = self.backend.begin_read()?;
+        let mut members = Vec::new();
+        for (_, value) in read_txn.range(REALM_MEMBERS_TABLE, Some(&start), Some(&end))? {
+            let member: RealmMember = postcard::from_bytes(&value)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            if member.left_at.is_none() {
+                members.push(member);
+            }
+        }
+
+        members.sort_by(|a, b| a.joined_at.cmp(&b.joined_at).then_with(|| a.did.cmp(&b.did)));
+        Ok(members)
+    }
+
+    /// Remove the entire roster for a realm, including tombstones.
+    pub fn delete_realm_members(&self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove_range(REALM_MEMBERS_TABLE, Some(&start), Some(&end))?;
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_and_list_members() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm = RealmId::new();
+
+        assert!(storage.record_realm_member(&realm, "did:sync:love", 100).unwrap());
+        assert!(storage.record_realm_member(&realm, "did:sync:joy", 200).unwrap());
+        // Seeing an existing member again is not a new join
+        assert!(!storage.record_realm_member(&realm, "did:sync:love", 300).unwrap());
+
+        let members = storage.list_realm_members(&realm).unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].did, "did:sync:love");
+        assert_eq!(members[0].joined_at, 100);
+        assert_eq!(members[0].last_seen, 300);
+        assert_eq!(members[1].did, "did:sync:joy");
+    }
+
+    #[test]
+    fn test_frequent_sightings_are_not_written() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm = RealmId::new();
+
+        storage.record_realm_member(&realm, "did:sync:love", 100).unwrap();
+        storage.record_realm_member(&realm, "did:sync:love", 100 + LAST_SEEN_RESOLUTION_SECS - 1).unwrap();
+        assert_eq!(storage.list_realm_members(&realm).unwrap()[0].last_seen, 100);
+
+        storage.record_realm_member(&realm, "did:sync:love", 100 + LAST_SEEN_RESOLUTION_SECS).unwrap();
+        assert_eq!(
+            storage.list_realm_members(&realm).unwrap()[0].last_seen,
+            100 + LAST_SEEN_RESOLUTION_SECS
+        );
+    }
+
+    #[test]
+    fn test_members_isolated_per_realm() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm_a = RealmId::new();
+        let realm_b = RealmId::new();
+
+        storage.record_realm_member(&realm_a, "did:sync:love", 1).unwrap();
+        storage.record_realm_member(&realm_b, "did:sync:joy", 1).unwrap();
+
+        assert_eq!(storage.list_realm_members(&realm_a).unwrap().len(), 1);
+
+        storage.delete_realm_members(&realm_a).unwrap();
+        assert!(storage.list_realm_members(&realm_a).unwrap().is_empty());
+        assert_eq!(storage.list_realm_members(&realm_b).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_left_member_ignores_stragglers_then_rejoins() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm = RealmId::new();
+
+        storage.record_realm_member(&realm, "did:sync:joy", 100).unwrap();
+        assert!(storage.mark_realm_member_left(&realm, "did:sync:joy", 200).unwrap());
+        assert!(!storage.mark_realm_member_left(&realm, "did:sync:joy", 201).unwrap());
+        assert!(storage.list_realm_members(&realm).unwrap().is_empty());
+
+        // A message that was in flight when Joy left must not re-add her
+        assert!(!storage.record_realm_member(&realm, "did:sync:joy", 205).unwrap());
+        assert!(storage.list_realm_members(&realm).unwrap().is_empty());
+
+        // Activity well after the leave is a genuine rejoin
+        let rejoin_at = 201 + REJOIN_GRACE_SECS + 1;
+        assert!(storage.record_realm_member(&realm, "did:sync:joy", rejoin_at).unwrap());
+        let members = storage.list_realm_members(&realm).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].joined_at, rejoin_at);
+    }
+}
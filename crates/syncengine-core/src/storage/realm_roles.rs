@@ -0,0 +1,89 @@
+//! Realm Role Storage - the signed role table currently accepted per realm
+//!
+//! Only the latest accepted revision is kept, keyed by realm ID. Older
+//! revisions are overwritten as soon as a newer one is accepted.
+
+use crate::error::SyncError;
+use crate::types::{RealmId, RealmRoles};
+
+use super::Storage;
+
+/// Table for storing realm role tables (key: realm_base58, value: serialized RealmRoles)
+pub(crate) const REALM_ROLES_TABLE: &str = "realm_roles";
+
+impl Storage {
+    /// Save the role table for a realm, replacing any previous revision.
+    pub fn save_realm_roles(&self, roles: &RealmRoles) -> Result<(), SyncError> {
+        let key = roles.realm_id.to_base58();
+        let serialized =
+            postcard::to_allocvec(roles).map_err(|e| SyncError::Serialization(e.to_string()))?;
+
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(REALM_ROLES_TABLE, key.as_str(), serialized.as_slice())?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load the role table for a realm.
+    ///
+    /// Returns `None` if no roles have been assigned in the realm.
+    pub fn load_realm_roles(&self, realm_id: &RealmId) -> Result<Option<RealmRoles>, SyncError> {
+        let key = realm_id.to_base58();
+        let read_txn = self.backend.begin_read()?;
+        match read_txn.get(REALM_ROLES_TABLE, key.as_str())? {
+            Some(data) => Ok(Some(
+                postcard::from_bytes(&data)
+                    .map_err(|e| SyncError::Serialization(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove the role table for a realm.
+    pub fn delete_realm_roles(&self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let key = realm_id.to_base58();
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove(REALM_ROLES_TABLE, key.as_str())?;
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RealmRole;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_load_and_delete_roles() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm = RealmId::new();
+        assert!(storage.load_realm_roles(&realm).unwrap().is_none());
+
+        let roles = RealmRoles {
+            realm_id: realm.clone(),
+            version: 1,
+            roles: BTreeMap::from([
+                ("did:sync:love".to_string(), RealmRole::Owner),
+                ("did:sync:joy".to_string(), RealmRole::Viewer),
+            ]),
+            signer: "did:sync:love".to_string(),
+            signature: vec![7u8; 4],
+        };
+        storage.save_realm_roles(&roles).unwrap();
+        assert_eq!(storage.load_realm_roles(&realm).unwrap(), Some(roles.clone()));
+
+        let newer = RealmRoles {
+            version: 2,
+            ..roles
+        };
+        storage.save_realm_roles(&newer).unwrap();
+        assert_eq!(storage.load_realm_roles(&realm).unwrap().unwrap().version, 2);
+
+        storage.delete_realm_roles(&realm).unwrap();
+        assert!(storage.load_realm_roles(&realm).unwrap().is_none());
+    }
+}
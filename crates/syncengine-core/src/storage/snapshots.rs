@@ -0,0 +1,134 @@
+//! Realm Snapshot Storage - frozen copies of realm documents
+//!
+//! Snapshot metadata and document bytes live in separate tables so that
+//! listing snapshots never has to read the (potentially large) documents.
+//! Both are keyed by `"{realm_base58}/{snapshot_ulid}"`, which keeps a realm's
+//! snapshots together and in creation order.
+
+use crate::error::SyncError;
+use crate::types::{RealmId, RealmSnapshot, SnapshotId};
+
+use super::Storage;
+
+/// Table for snapshot metadata (key: "realm/snapshot", value: serialized RealmSnapshot)
+pub(crate) const REALM_SNAPSHOTS_TABLE: &str = "realm_snapshots";
+
+/// Table for snapshot documents (key: "realm/snapshot", value: Automerge bytes)
+pub(crate) const SNAPSHOT_DOCUMENTS_TABLE: &str = "snapshot_documents";
+
+/// Build the storage key for a snapshot of a realm
+fn snapshot_key(realm_id: &RealmId, snapshot_id: &SnapshotId) -> String {
+    format!("{}/{}", realm_id.to_base58(), snapshot_id.to_string_repr())
+}
+
+/// Key range covering every snapshot of a realm (`'0'` sorts right after `'/'`)
+fn realm_range(realm_id: &RealmId) -> (String, String) {
+    let prefix = realm_id.to_base58();
+    (format!("{}/", prefix), format!("{}0", prefix))
+}
+
+impl Storage {
+    /// Save a snapshot and its document bytes.
+    pub fn save_realm_snapshot(
+        &self,
+        snapshot: &RealmSnapshot,
+        doc_bytes: &[u8],
+    ) -> Result<(), SyncError> {
+        let key = snapshot_key(&snapshot.realm_id, &snapshot.id);
+        let serialized =
+            postcard::to_allocvec(snapshot).map_err(|e| SyncError::Serialization(e.to_string()))?;
+
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.insert(REALM_SNAPSHOTS_TABLE, &key, &serialized)?;
+        write_txn.insert(SNAPSHOT_DOCUMENTS_TABLE, &key, doc_bytes)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// List all snapshots of a realm, oldest first.
+    pub fn list_realm_snapshots(&self, realm_id: &RealmId) -> Result<Vec<RealmSnapshot>, SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let read_txn = self.backend.begin_read()?;
+        let mut snapshots = Vec::new();
+        for (_, value) in read_txn.range(REALM_SNAPSHOTS_TABLE, Some(&start), Some(&end))? {
+            let snapshot: RealmSnapshot = postcard::from_bytes(&value)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            snapshots.push(snapshot);
+        }
+        Ok(snapshots)
+    }
+
+    /// Load a snapshot's metadata together with its document bytes.
+    pub fn load_realm_snapshot(
+        &self,
+        realm_id: &RealmId,
+        snapshot_id: &SnapshotId,
+    ) -> Result<Option<(RealmSnapshot, Vec<u8>)>, SyncError> {
+        let key = snapshot_key(realm_id, snapshot_id);
+        let read_txn = self.backend.begin_read()?;
+        let snapshot: RealmSnapshot = match read_txn.get(REALM_SNAPSHOTS_TABLE, &key)? {
+            Some(data) => postcard::from_bytes(&data)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?,
+            None => return Ok(None),
+        };
+        let doc_bytes = read_txn
+            .get(SNAPSHOT_DOCUMENTS_TABLE, &key)?
+            .ok_or_else(|| {
+                SyncError::Storage(format!("Snapshot {} has no document", snapshot_id))
+            })?;
+
+        Ok(Some((snapshot, doc_bytes)))
+    }
+
+    /// Remove every snapshot of a realm.
+    pub fn delete_realm_snapshots(&self, realm_id: &RealmId) -> Result<(), SyncError> {
+        let (start, end) = realm_range(realm_id);
+        let mut write_txn = self.backend.begin_write()?;
+        write_txn.remove_range(REALM_SNAPSHOTS_TABLE, Some(&start), Some(&end))?;
+        write_txn.remove_range(SNAPSHOT_DOCUMENTS_TABLE, Some(&start), Some(&end))?;
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use ulid::Ulid;
+
+    fn snapshot(realm_id: &RealmId, label: &str, created_ms: u64) -> RealmSnapshot {
+        RealmSnapshot {
+            id: SnapshotId(Ulid::from_parts(created_ms, 0)),
+            realm_id: realm_id.clone(),
+            label: label.to_string(),
+            created_at: (created_ms / 1000) as i64,
+        }
+    }
+
+    #[test]
+    fn test_save_list_and_load_snapshots() {
+        let temp_dir = tempdir().unwrap();
+        let storage = Storage::new(temp_dir.path().join("test.db")).unwrap();
+        let realm = RealmId::new();
+        let other = RealmId::new();
+
+        let first = snapshot(&realm, "before cleanup", 1_000);
+        let second = snapshot(&realm, "after cleanup", 2_000);
+        storage.save_realm_snapshot(&first, b"doc-1").unwrap();
+        storage.save_realm_snapshot(&second, b"doc-2").unwrap();
+        storage.save_realm_snapshot(&snapshot(&other, "elsewhere", 1_500), b"doc-3").unwrap();
+
+        let listed = storage.list_realm_snapshots(&realm).unwrap();
+        assert_eq!(listed, vec![first.clone(), second]);
+
+        let (loaded, bytes) = storage.load_realm_snapshot(&realm, &first.id).unwrap().unwrap();
+        assert_eq!(loaded, first);
+        assert_eq!(bytes, b"doc-1");
+
+        storage.delete_realm_snapshots(&realm).unwrap();
+        assert!(storage.list_realm_snapshots(&realm).unwrap().is_empty());
+        assert!(storage.load_realm_snapshot(&realm, &first.id).unwrap().is_none());
+        assert_eq!(storage.list_realm_snapshots(&other).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+//! Capture of a node's realm gossip traffic for later replay
+//!
+//! A [`MessageCapture`] records every realm envelope a node sends or
+//! receives while capturing, together with the realms and pinned profiles it
+//! started from. Replaying the inbound half into a fresh node re-drives it
+//! through the same states, which makes sync bugs reproducible from a report.
+//!
+//! Captures include realm keys, so they are only for test networks.
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ProfilePin, RealmId, RealmInfo};
+
+/// Whether a captured envelope arrived at or left the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureDirection {
+    /// Received from the realm topic
+    Inbound,
+    /// Broadcast to the realm topic
+    Outbound,
+}
+
+/// One sealed realm envelope seen during a capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedMessage {
+    /// Milliseconds since the capture started
+    pub offset_ms: u64,
+    /// Whether the envelope was received or sent
+    pub direction: CaptureDirection,
+    /// Realm topic the envelope travelled on
+    pub realm_id: RealmId,
+    /// The envelope exactly as it went over the wire
+    pub envelope_bytes: Vec<u8>,
+}
+
+/// A realm as it was when the capture started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedRealm {
+    /// Realm metadata
+    pub info: RealmInfo,
+    /// Key needed to open the realm's envelopes
+    pub realm_key: [u8; 32],
+    /// Saved Automerge document
+    pub document: Vec<u8>,
+}
+
+/// Recorded gossip traffic of one node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageCapture {
+    /// Unix timestamp (milliseconds) when the capture started
+    pub started_at_ms: i64,
+    /// Open realms at the start of the capture
+    pub realms: Vec<CapturedRealm>,
+    /// Pinned profiles at the start, needed to verify senders on replay
+    pub profiles: Vec<ProfilePin>,
+    /// Envelopes in the order they were seen
+    pub messages: Vec<CapturedMessage>,
+}
+
+impl MessageCapture {
+    /// Envelopes the node received, in order
+    pub fn inbound(&self) -> impl Iterator<Item = &CapturedMessage> {
+        self.messages
+            .iter()
+            .filter(|m| m.direction == CaptureDirection::Inbound)
+    }
+}
+
+/// A capture in progress
+#[derive(Debug)]
+pub(crate) struct CaptureRecorder {
+    started: Instant,
+    capture: MessageCapture,
+}
+
+impl CaptureRecorder {
+    #[cfg(feature = "debug-server")]
+    pub(crate) fn new(realms: Vec<CapturedRealm>, profiles: Vec<ProfilePin>) -> Self {
+        Self {
+            started: Instant::now(),
+            capture: MessageCapture {
+                started_at_ms: chrono::Utc::now().timestamp_millis(),
+                realms,
+                profiles,
+                messages: Vec::new(),
+            },
+        }
+    }
+
+    pub(crate) fn record(&mut self, direction: CaptureDirection, realm_id: &RealmId, bytes: &[u8]) {
+        self.capture.messages.push(CapturedMessage {
+            offset_ms: self.started.elapsed().as_millis() as u64,
+            direction,
+            realm_id: realm_id.clone(),
+            envelope_bytes: bytes.to_vec(),
+        });
+    }
+
+    #[cfg(feature = "debug-server")]
+    pub(crate) fn finish(self) -> MessageCapture {
+        self.capture
+    }
+}
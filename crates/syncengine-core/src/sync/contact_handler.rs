@@ -212,6 +212,17 @@ impl ContactProtocolHandler {
                     );
                 }
 
+                // Requests from DIDs on the allowlist are accepted like our own invites
+                let is_allowlisted = storage.is_contact_autoaccepted(&requester_did).unwrap_or(false);
+                if is_allowlisted && !is_our_invite {
+                    info!(
+                        invite_id = ?invite_id,
+                        requester_did = %requester_did,
+                        "Received request from an allowlisted DID - will auto-accept"
+                    );
+                }
+                let auto_accept = is_our_invite || is_allowlisted;
+
                 // Extract ProfileSnapshot from SignedProfile for PendingContact
                 let profile_snapshot = ProfileSnapshot {
                     display_name: requester_signed_profile.profile.display_name.clone(),
@@ -231,6 +242,7 @@ impl ContactProtocolHandler {
                     created_at: chrono::Utc::now().timestamp(),
                     encryption_keys: requester_encryption_keys,
                     peer_contact_dids: requester_contact_dids,
+                    greeting: None,
                 };
 
                 storage.save_pending(&pending)?;
@@ -238,15 +250,15 @@ impl ContactProtocolHandler {
                 info!(
                     invite_id = ?invite_id,
                     requester_did = %requester_did,
-                    auto_accept = is_our_invite,
+                    auto_accept,
                     "Received contact request, saved as IncomingPending"
                 );
 
-                // Emit event (with auto_accept flag if it's our invite)
+                // Emit event (with auto_accept flag if it's our invite or allowlisted)
                 let _ = event_tx.send(ContactEvent::ContactRequestReceived {
                     invite_id,
                     from: profile_snapshot,
-                    auto_accept: is_our_invite,
+                    auto_accept,
                 });
             }
 
@@ -381,10 +393,12 @@ impl ContactProtocolHandler {
                             is_favorite: false,
                             encryption_keys: accepter_encryption_keys.clone(),
                             mutual_peers,
+                            nickname: None,
                         };
 
                         // Save to legacy contacts table
                         storage.save_contact(&contact)?;
+                        crate::sync::packet_gate::reverify_sender(&storage, &contact.peer_did);
 
                         // Also save to unified peers table (new system)
                         let unified_peer = Peer {
@@ -406,6 +420,9 @@ impl ContactProtocolHandler {
                             connection_attempts: 0,
                             successful_connections: 1, // Just succeeded
                             last_attempt: contact.last_seen,
+                            link: Default::default(),
+                            connection_quality: 0,
+                            clock_skew_ms: None,
                         };
                         storage.save_peer(&unified_peer)?;
 
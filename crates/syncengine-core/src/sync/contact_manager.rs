@@ -26,6 +26,7 @@ use std::sync::Arc;
 
 use base64::Engine as _;
 use iroh_gossip::proto::TopicId;
+use serde::{Serialize, Serializer};
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 
@@ -37,14 +38,18 @@ use crate::sync::contact_protocol::{ContactMessage, CONTACT_ALPN};
 use crate::sync::{ActiveContactTopics, GossipSync};
 use crate::types::contact::{
     ContactInfo, ContactState, ContactStatus, HybridContactInvite, PeerContactInvite,
-    PendingContact, ProfileSnapshot,
+    PendingContact, ProfileSnapshot, MAX_INVITE_GREETING_CHARS,
 };
 use crate::types::peer::{ContactDetails, Peer, PeerSource, PeerStatus};
 
 type SyncResult<T> = Result<T, SyncError>;
 
 /// Event emitted by ContactManager for UI notifications
-#[derive(Debug, Clone)]
+///
+/// Serializes with a snake_case `type` tag. Accepted contacts are written
+/// without their gossip topic or key material.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ContactEvent {
     /// A new invite was generated
     InviteGenerated { invite_code: String },
@@ -52,15 +57,23 @@ pub enum ContactEvent {
     ContactRequestReceived {
         invite_id: [u8; 16],
         from: ProfileSnapshot,
-        /// True if this was our own invite (should auto-accept)
+        /// True if this was our own invite or the sender is allowlisted
+        /// (should auto-accept)
         auto_accept: bool,
     },
+    /// A request from a DID on the auto-accept allowlist was accepted
+    AutoAccepted { invite_id: [u8; 16], did: String },
     /// A contact request was successfully sent
     ContactRequestSent { invite_id: [u8; 16], to: String },
     /// A contact was mutually accepted and finalized
-    ContactAccepted { contact: ContactInfo },
+    ContactAccepted {
+        #[serde(serialize_with = "public_contact")]
+        contact: ContactInfo,
+    },
     /// A contact request was declined
     ContactDeclined { invite_id: [u8; 16] },
+    /// An accepted contact was removed
+    ContactRemoved { did: String },
     /// A contact came online
     ContactOnline { did: String },
     /// A contact went offline
@@ -71,6 +84,26 @@ pub enum ContactEvent {
     ContactError { message: String },
 }
 
+fn public_contact<S: Serializer>(contact: &ContactInfo, s: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct PublicContact<'a> {
+        peer_did: &'a str,
+        profile: &'a ProfileSnapshot,
+        accepted_at: i64,
+        status: &'a ContactStatus,
+        is_favorite: bool,
+    }
+
+    PublicContact {
+        peer_did: &contact.peer_did,
+        profile: &contact.profile,
+        accepted_at: contact.accepted_at,
+        status: &contact.status,
+        is_favorite: contact.is_favorite,
+    }
+    .serialize(s)
+}
+
 /// Manages peer contact exchange and auto-reconnection
 ///
 /// ContactManager orchestrates the full lifecycle of peer connections,
@@ -96,6 +129,8 @@ pub struct ContactManager {
     active_topics: ActiveContactTopics,
     /// Packet event buffer for UI visualization (Indra's Network)
     packet_event_buffer: Option<Arc<crate::sync::PacketEventBuffer>>,
+    /// Limits applied to packets received on contact topics, shared with the engine
+    packet_gate: Arc<crate::sync::PacketGate>,
 }
 
 impl ContactManager {
@@ -110,6 +145,7 @@ impl ContactManager {
     /// * `event_tx` - Event broadcast channel (shared with ContactProtocolHandler)
     /// * `active_topics` - Shared map of contact topic senders (shared with ContactProtocolHandler)
     /// * `packet_event_buffer` - Optional buffer for packet event visualization
+    /// * `packet_gate` - Size and rate limits for packets received on contact topics
     pub fn new(
         gossip_sync: Arc<GossipSync>,
         keypair: Arc<HybridKeypair>,
@@ -118,6 +154,7 @@ impl ContactManager {
         event_tx: broadcast::Sender<ContactEvent>,
         active_topics: ActiveContactTopics,
         packet_event_buffer: Option<Arc<crate::sync::PacketEventBuffer>>,
+        packet_gate: Arc<crate::sync::PacketGate>,
     ) -> Self {
         // Note: Incoming contact messages are handled by ContactProtocolHandler
         // registered with the Router in GossipSync. No listener task needed here.
@@ -132,14 +169,17 @@ impl ContactManager {
             event_tx,
             active_topics,
             packet_event_buffer,
+            packet_gate,
         }
     }
 
     /// Start a background task that auto-accepts contact requests for our own invites
     ///
     /// When we generate an invite and someone uses it, we should automatically accept
-    /// instead of requiring manual confirmation. This task listens for ContactRequestReceived
-    /// events with `auto_accept: true` and triggers acceptance.
+    /// instead of requiring manual confirmation. The same goes for requests from DIDs
+    /// on the auto-accept allowlist, which also emit [`ContactEvent::AutoAccepted`].
+    /// This task listens for ContactRequestReceived events with `auto_accept: true`
+    /// and triggers acceptance.
     pub fn start_auto_accept_task(self: Arc<Self>) {
         let mut event_rx = self.event_tx.subscribe();
 
@@ -160,6 +200,13 @@ impl ContactManager {
                         // Small delay to ensure pending contact is saved
                         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
+                        let peer_did = self
+                            .storage
+                            .load_pending(&invite_id)
+                            .ok()
+                            .flatten()
+                            .map(|pending| pending.peer_did);
+
                         // Auto-accept the contact
                         if let Err(e) = self.accept_contact_request(&invite_id).await {
                             error!(
@@ -172,6 +219,13 @@ impl ContactManager {
                                 invite_id = ?invite_id,
                                 "Successfully auto-accepted contact request"
                             );
+                            if let Some(did) = peer_did.filter(|did| {
+                                self.storage.is_contact_autoaccepted(did).unwrap_or(false)
+                            }) {
+                                let _ = self
+                                    .event_tx
+                                    .send(ContactEvent::AutoAccepted { invite_id, did });
+                            }
                         }
                     }
                     Ok(_) => {
@@ -319,6 +373,56 @@ impl ContactManager {
         &self,
         profile: ProfileSnapshot,
         expiry_hours: u8,
+    ) -> SyncResult<String> {
+        self.encode_new_invite(profile, expiry_hours, None, None)
+    }
+
+    /// Generate a contact invite carrying a greeting for the recipient
+    ///
+    /// Produces a version 4 invite; the greeting is shown before the
+    /// recipient decides whether to connect. `realm_ticket` is bundled as
+    /// in [`generate_invite_with_realm`](Self::generate_invite_with_realm).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidInvite` if the greeting is longer than
+    /// [`MAX_INVITE_GREETING_CHARS`] characters.
+    pub fn generate_invite_with_greeting(
+        &self,
+        profile: ProfileSnapshot,
+        expiry_hours: u8,
+        realm_ticket: Option<String>,
+        greeting: String,
+    ) -> SyncResult<String> {
+        if greeting.chars().count() > MAX_INVITE_GREETING_CHARS {
+            return Err(SyncError::InvalidInvite(format!(
+                "Greeting is over {} characters",
+                MAX_INVITE_GREETING_CHARS
+            )));
+        }
+        self.encode_new_invite(profile, expiry_hours, realm_ticket, Some(greeting))
+    }
+
+    /// Generate a contact invite that also carries a realm invite ticket
+    ///
+    /// Produces a version 3 invite: whoever accepts it can become a contact
+    /// and join the realm in one step.
+    pub fn generate_invite_with_realm(
+        &self,
+        profile: ProfileSnapshot,
+        expiry_hours: u8,
+        realm_ticket: String,
+    ) -> SyncResult<String> {
+        self.encode_new_invite(profile, expiry_hours, Some(realm_ticket), None)
+    }
+
+    /// Build, sign, record and encode a new invite
+    fn encode_new_invite(
+        &self,
+        profile: ProfileSnapshot,
+        expiry_hours: u8,
+        realm_ticket: Option<String>,
+        greeting: Option<String>,
     ) -> SyncResult<String> {
         // Cap expiry at 7 days (168 hours)
         let expiry_hours = expiry_hours.min(168);
@@ -333,9 +437,14 @@ impl ContactManager {
         let now = chrono::Utc::now().timestamp();
         let expires_at = now + (expiry_hours as i64 * 3600);
 
-        // Create unsigned hybrid invite (v2)
+        // Create unsigned hybrid invite (v2, v3 with a bundled realm, v4 with a greeting)
+        let version = match (&realm_ticket, &greeting) {
+            (_, Some(_)) => 4,
+            (Some(_), None) => 3,
+            (None, None) => 2,
+        };
         let mut invite = HybridContactInvite {
-            version: 2,
+            version,
             invite_id,
             inviter_did: self.did.to_string(),
             node_addr,
@@ -343,6 +452,8 @@ impl ContactManager {
             created_at: now,
             expires_at,
             signature: vec![], // Filled after signing
+            realm_ticket,
+            greeting,
         };
 
         // Sign the invite (Ed25519-only for compact size)
@@ -350,8 +461,15 @@ impl ContactManager {
         invite.signature = signature;
 
         // Serialize, compress, and encode
-        let serialized =
+        let mut serialized =
             postcard::to_allocvec(&invite).map_err(|e| SyncError::Serialization(e.to_string()))?;
+        let trailer = match (&invite.greeting, &invite.realm_ticket) {
+            (Some(greeting), ticket) => postcard::to_allocvec(&(ticket, greeting)),
+            (None, Some(ticket)) => postcard::to_allocvec(ticket),
+            (None, None) => Ok(Vec::new()),
+        }
+        .map_err(|e| SyncError::Serialization(e.to_string()))?;
+        serialized.extend_from_slice(&trailer);
 
         // Compress with zstd (level 3 = fast with good compression)
         let compressed = zstd::encode_all(&serialized[..], 3)
@@ -364,7 +482,7 @@ impl ContactManager {
         let compression_ratio = (serialized.len() as f64 / compressed.len() as f64 * 100.0) as u32;
         info!(
             invite_id = ?invite_id,
-            version = 2,
+            version = invite.version,
             expiry_hours,
             original_size = serialized.len(),
             compressed_size = compressed.len(),
@@ -426,10 +544,32 @@ impl ContactManager {
         // Try to deserialize based on version
         let version = bytes[0];
         match version {
-            2 => {
-                // V2 HybridContactInvite
-                let invite: HybridContactInvite = postcard::from_bytes(&bytes)
-                    .map_err(|e| SyncError::InvalidInvite(format!("Invalid v2 invite data: {}", e)))?;
+            2..=4 => {
+                // V2 HybridContactInvite; v3 appends a realm ticket after it,
+                // v4 an optional realm ticket and a greeting
+                let (mut invite, rest): (HybridContactInvite, _) = postcard::take_from_bytes(&bytes)
+                    .map_err(|e| {
+                        SyncError::InvalidInvite(format!("Invalid v{} invite data: {}", version, e))
+                    })?;
+                if version == 3 {
+                    let ticket: String = postcard::from_bytes(rest).map_err(|e| {
+                        SyncError::InvalidInvite(format!("Invalid bundled realm ticket: {}", e))
+                    })?;
+                    invite.realm_ticket = Some(ticket);
+                } else if version == 4 {
+                    let (ticket, greeting): (Option<String>, String) = postcard::from_bytes(rest)
+                        .map_err(|e| {
+                            SyncError::InvalidInvite(format!("Invalid invite greeting: {}", e))
+                        })?;
+                    if greeting.chars().count() > MAX_INVITE_GREETING_CHARS {
+                        return Err(SyncError::InvalidInvite(format!(
+                            "Greeting is over {} characters",
+                            MAX_INVITE_GREETING_CHARS
+                        )));
+                    }
+                    invite.realm_ticket = ticket;
+                    invite.greeting = Some(greeting);
+                }
 
                 // Check expiry
                 if invite.is_expired() {
@@ -449,8 +589,9 @@ impl ContactManager {
                 debug!(
                     invite_id = ?invite.invite_id,
                     inviter_did = %invite.inviter_did,
-                    version = 2,
-                    "Decoded and validated hybrid invite (v2)"
+                    version,
+                    has_realm = invite.realm_ticket.is_some(),
+                    "Decoded and validated hybrid invite"
                 );
 
                 Ok(invite)
@@ -492,6 +633,8 @@ impl ContactManager {
                     created_at: v1_invite.created_at,
                     expires_at: v1_invite.expires_at,
                     signature: v1_invite.signature,
+                    realm_ticket: None,
+                    greeting: None,
                 })
             }
             _ => Err(SyncError::InvalidInvite(format!(
@@ -538,6 +681,7 @@ impl ContactManager {
             created_at: chrono::Utc::now().timestamp(),
             encryption_keys: None, // Will be populated from ContactAccept
             peer_contact_dids: vec![], // Will be populated from ContactAccept
+            greeting: invite.greeting.clone(),
         };
 
         self.storage.save_pending(&pending)?;
@@ -692,6 +836,52 @@ impl ContactManager {
         Ok(())
     }
 
+    /// Remove an accepted contact
+    ///
+    /// Drops our sender for the 1:1 contact topic and deletes the contact
+    /// along with any pending requests from the same DID. The contact's topic
+    /// listener notices the contact is gone on its next event and exits,
+    /// which leaves the topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `did` - DID of the contact to remove
+    pub async fn remove_contact(&self, did: &str) -> SyncResult<ContactInfo> {
+        let contact = self
+            .storage
+            .load_contact(did)?
+            .ok_or_else(|| SyncError::ContactNotFound(did.to_string()))?;
+
+        self.active_topics
+            .write()
+            .await
+            .remove(&contact.contact_topic);
+
+        self.storage.delete_contact(did)?;
+        for other in self.storage.list_contacts()? {
+            if other.mutual_peers.iter().any(|peer| peer == did) {
+                self.storage.remove_mutual_peer(&other.peer_did, did)?;
+            }
+        }
+
+        let pending = self
+            .storage
+            .list_incoming_pending()?
+            .into_iter()
+            .chain(self.storage.list_outgoing_pending()?);
+        for stale in pending.filter(|p| p.peer_did == did) {
+            self.storage.delete_pending(&stale.invite_id)?;
+        }
+
+        info!(peer_did = %did, "Removed contact");
+
+        let _ = self.event_tx.send(ContactEvent::ContactRemoved {
+            did: did.to_string(),
+        });
+
+        Ok(contact)
+    }
+
     /// Finalize a mutually accepted contact
     ///
     /// Derives shared keys, subscribes to contact topic, saves to contacts table.
@@ -744,10 +934,12 @@ impl ContactManager {
             is_favorite: false,
             encryption_keys: pending.encryption_keys.clone(),
             mutual_peers,
+            nickname: None,
         };
 
         // Save to contacts table (legacy)
         self.storage.save_contact(&contact)?;
+        crate::sync::packet_gate::reverify_sender(&self.storage, &contact.peer_did);
 
         // Also save as unified Peer (new system)
         let unified_peer = Peer {
@@ -769,6 +961,9 @@ impl ContactManager {
             connection_attempts: 0,
             successful_connections: 1, // Just succeeded
             last_attempt: contact.last_seen,
+            link: Default::default(),
+            connection_quality: 0,
+            clock_skew_ms: None,
         };
         self.storage.save_peer(&unified_peer)?;
 
@@ -1069,6 +1264,7 @@ impl ContactManager {
             receiver,
             self.event_tx.clone(),
             self.packet_event_buffer.clone(),
+            self.packet_gate.clone(),
         );
 
         info!(
@@ -1135,6 +1331,7 @@ impl ContactManager {
         mut receiver: crate::sync::TopicReceiver,
         event_tx: broadcast::Sender<ContactEvent>,
         packet_event_buffer: Option<Arc<crate::sync::PacketEventBuffer>>,
+        packet_gate: Arc<crate::sync::PacketGate>,
     ) {
         tokio::spawn(async move {
             use crate::sync::TopicEvent;
@@ -1151,6 +1348,16 @@ impl ContactManager {
             );
 
             while let Some(event) = receiver.recv_event().await {
+                // Leave the topic once the contact has been removed. Dropping our
+                // sender and receiver here is what unsubscribes us.
+                if let Ok(None) = storage.load_contact(&peer_did) {
+                    info!(
+                        peer_did = %peer_did,
+                        "Contact removed - stopping contact topic listener"
+                    );
+                    break;
+                }
+
                 // Log EVERY event type to diagnose mesh formation and message flow
                 match &event {
                     TopicEvent::Message(msg) => {
@@ -1171,65 +1378,67 @@ impl ContactManager {
                         // RELAY FORWARDING: Check if we have packets addressed to this peer
                         // that we've been storing as a relay. Forward them now that peer is online.
                         if let Ok(peer_did_parsed) = crate::identity::Did::parse(&peer_did) {
-                            match crate::profile::MirrorStore::new(storage.db_handle()) {
-                                Ok(mirror) => {
-                                    match mirror.get_packets_for_recipient(&peer_did_parsed) {
-                                        Ok(packets) if !packets.is_empty() => {
-                                            info!(
-                                                peer_did = %peer_did,
-                                                packet_count = packets.len(),
-                                                "Found relayed packets for peer - forwarding now"
-                                            );
-                                            for envelope in packets {
-                                                // Create packet sync message and forward
-                                                let msg = crate::sync::ProfileGossipMessage::Packet {
-                                                    envelope: envelope.clone(),
-                                                };
-                                                match msg.to_bytes() {
-                                                    Ok(bytes) => {
-                                                        // Broadcast the relayed packet
-                                                        if let Err(e) = topic_sender.broadcast(bytes).await {
-                                                            warn!(
-                                                                peer_did = %peer_did,
-                                                                error = %e,
-                                                                "Failed to forward relayed packet"
-                                                            );
-                                                        } else {
-                                                            info!(
-                                                                peer_did = %peer_did,
-                                                                sender = %envelope.sender,
-                                                                sequence = envelope.sequence,
-                                                                "Forwarded relayed packet to peer"
-                                                            );
-                                                            // Mark as delivered (remove from relay index)
-                                                            let packet_hash = envelope.hash();
-                                                            if let Err(e) = mirror.mark_delivered(&peer_did_parsed, &packet_hash) {
-                                                                warn!(
-                                                                    error = %e,
-                                                                    "Failed to mark packet as delivered"
-                                                                );
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(e) => {
-                                                        warn!(error = %e, "Failed to encode packet for relay");
+                            let mirror = crate::profile::MirrorStore::new(storage.backend());
+                            match mirror.get_packets_for_recipient(&peer_did_parsed) {
+                                Ok(packets) if !packets.is_empty() => {
+                                    info!(
+                                        peer_did = %peer_did,
+                                        packet_count = packets.len(),
+                                        "Found relayed packets for peer - forwarding now"
+                                    );
+                                    for envelope in packets {
+                                        // Create packet sync message and forward
+                                        let msg = crate::sync::ProfileGossipMessage::Packet {
+                                            envelope: envelope.clone(),
+                                        };
+                                        match msg.to_bytes() {
+                                            Ok(bytes) => {
+                                                // Broadcast the relayed packet
+                                                if let Err(e) = topic_sender.broadcast(bytes).await {
+                                                    warn!(
+                                                        peer_did = %peer_did,
+                                                        error = %e,
+                                                        "Failed to forward relayed packet"
+                                                    );
+                                                } else {
+                                                    info!(
+                                                        peer_did = %peer_did,
+                                                        sender = %envelope.sender,
+                                                        sequence = envelope.sequence,
+                                                        "Forwarded relayed packet to peer"
+                                                    );
+                                                    // Mark as delivered (remove from relay index)
+                                                    let packet_hash = envelope.hash();
+                                                    if let Err(e) = mirror.mark_delivered(&peer_did_parsed, &packet_hash) {
+                                                        warn!(
+                                                            error = %e,
+                                                            "Failed to mark packet as delivered"
+                                                        );
                                                     }
                                                 }
                                             }
-                                        }
-                                        Ok(_) => {
-                                            debug!(peer_did = %peer_did, "No relayed packets for peer");
-                                        }
-                                        Err(e) => {
-                                            warn!(error = %e, "Failed to query relayed packets");
+                                            Err(e) => {
+                                                warn!(error = %e, "Failed to encode packet for relay");
+                                            }
                                         }
                                     }
                                 }
+                                Ok(_) => {
+                                    debug!(peer_did = %peer_did, "No relayed packets for peer");
+                                }
                                 Err(e) => {
-                                    warn!(error = %e, "Failed to create MirrorStore for relay check");
+                                    warn!(error = %e, "Failed to query relayed packets");
                                 }
                             }
                         }
+
+                        // LOG CATCH-UP: ask the contact for any of their packets we
+                        // missed while one of us was offline
+                        Self::request_log_catch_up(&storage, &peer_did, &topic_sender).await;
+
+                        // DEVICES: tell the contact every device we use, so they know
+                        // each of those endpoints is us
+                        Self::announce_device_list(&storage, &peer_did, &topic_sender).await;
                     }
                     TopicEvent::NeighborDown(neighbor) => {
                         warn!(
@@ -1270,27 +1479,28 @@ impl ContactManager {
                                     Ok(envelope) => {
                                         // Store in MirrorStore - this auto-indexes by recipient
                                         // for later forwarding via the NeighborUp handler
-                                        match crate::profile::MirrorStore::new(storage.db_handle()) {
-                                            Ok(mirror) => {
-                                                match mirror.store_packet(&envelope) {
-                                                    Ok(_) => {
-                                                        info!(
-                                                            original_sender = %relay.original_sender,
-                                                            final_recipient = %relay.final_recipient,
-                                                            sequence = envelope.sequence,
-                                                            "Stored relayed packet for forwarding when recipient comes online"
-                                                        );
-                                                    }
-                                                    Err(e) => {
-                                                        warn!(
-                                                            error = %e,
-                                                            "Failed to store relayed packet"
-                                                        );
-                                                    }
-                                                }
+                                        let mirror = crate::profile::MirrorStore::new(storage.backend());
+                                        let own_keys = storage.load_profile_keys().ok().flatten();
+                                        match packet_gate.admit(
+                                            &storage,
+                                            &mirror,
+                                            own_keys.as_ref(),
+                                            &envelope,
+                                        ) {
+                                            Ok(None) => {}
+                                            Ok(Some(_)) => {
+                                                info!(
+                                                    original_sender = %relay.original_sender,
+                                                    final_recipient = %relay.final_recipient,
+                                                    sequence = envelope.sequence,
+                                                    "Stored relayed packet for forwarding when recipient comes online"
+                                                );
                                             }
                                             Err(e) => {
-                                                warn!(error = %e, "Failed to create MirrorStore for relay storage");
+                                                warn!(
+                                                    error = %e,
+                                                    "Failed to store relayed packet"
+                                                );
                                             }
                                         }
                                     }
@@ -1402,6 +1612,15 @@ impl ContactManager {
                                 "PARSED PACKET - checking DID match"
                             );
 
+                            // A packet authored by someone other than this contact was
+                            // relayed by them (store-and-forward). Keep it only if it is
+                            // sealed for us.
+                            let keys = storage.load_profile_keys().ok().flatten();
+                            let relayed = sender_did != peer_did;
+                            let relayed_to_us = relayed
+                                && !envelope.is_global()
+                                && keys.as_ref().is_some_and(|k| envelope.is_addressed_to(&k.did()));
+
                             // Record packet event for UI visualization (Indra's Network)
                             if let Some(ref buffer) = packet_event_buffer {
                                 // Get peer display name from storage
@@ -1427,50 +1646,27 @@ impl ContactManager {
                                 };
 
                                 // Determine decryption status based on packet type
-                                let (decryption_status, content_preview) = if envelope.is_global() {
-                                    // Global packet - not encrypted
-                                    let preview = match envelope.decode_global_payload() {
-                                        Ok(crate::profile::PacketPayload::DirectMessage { content, .. }) => {
-                                            crate::sync::PacketEvent::preview_content(&content)
-                                        }
-                                        Ok(_) => "[profile update]".to_string(),
-                                        Err(_) => "[global]".to_string(),
-                                    };
-                                    (crate::sync::DecryptionStatus::Global, preview)
-                                } else {
-                                    // Sealed packet - attempt to decrypt if we have keys
-                                    if let Ok(Some(keys)) = storage.load_profile_keys() {
-                                        match envelope.decrypt_for_recipient(&keys) {
-                                            Ok(payload) => {
-                                                let preview = match payload {
-                                                    crate::profile::PacketPayload::DirectMessage { content, .. } => {
-                                                        crate::sync::PacketEvent::preview_content(&content)
-                                                    }
-                                                    _ => "[packet]".to_string(),
-                                                };
-                                                (crate::sync::DecryptionStatus::Decrypted, preview)
-                                            }
-                                            Err(_) => {
-                                                // Cannot decrypt - not intended for us
-                                                (crate::sync::DecryptionStatus::CannotDecrypt { reason: "not recipient".to_string() }, "[encrypted]".to_string())
-                                            }
-                                        }
-                                    } else {
-                                        // No keys available
-                                        (crate::sync::DecryptionStatus::NotAttempted, "[encrypted]".to_string())
-                                    }
-                                };
+                                let realm_key = storage
+                                    .list_realm_keys()
+                                    .ok()
+                                    .and_then(|realm_keys| envelope.group_realm(&realm_keys).map(|(_, key)| *key));
+                                let (decryption_status, payload) = crate::sync::DecryptionStatus::classify(
+                                    &envelope,
+                                    keys.as_ref(),
+                                    realm_key.as_ref(),
+                                );
+                                let content_preview =
+                                    crate::sync::PacketEvent::preview_for(&decryption_status, payload.as_ref());
 
-                                // Detect relay: if gossip sender (delivered_from) differs from
-                                // packet author (sender_did), the packet was relayed
-                                let (relay_did, relay_name) = if delivered_from != sender_did {
-                                    // Packet was relayed through another peer
-                                    let relay_name = storage.load_peer_by_did(&delivered_from)
+                                // Detect relay: the contact owning this topic delivered a
+                                // packet authored by someone else
+                                let (relay_did, relay_name) = if relayed {
+                                    let relay_name = storage.load_peer_by_did(&peer_did)
                                         .ok()
                                         .flatten()
                                         .map(|p| p.display_name())
-                                        .unwrap_or_else(|| format!("{}...", &delivered_from[..16.min(delivered_from.len())]));
-                                    (Some(delivered_from.clone()), Some(relay_name))
+                                        .unwrap_or_else(|| format!("{}...", &peer_did[..16.min(peer_did.len())]));
+                                    (Some(peer_did.clone()), Some(relay_name))
                                 } else {
                                     (None, None)
                                 };
@@ -1494,34 +1690,37 @@ impl ContactManager {
                                 buffer.record(event);
                             }
 
-                            // Only process packets from the expected peer (the contact)
-                            if sender_did == peer_did {
+                            // Only process packets from the expected peer (the contact),
+                            // or packets it relayed to us
+                            if !relayed || relayed_to_us {
                                 // Store packet in MirrorStore
-                                match crate::profile::MirrorStore::new(storage.db_handle()) {
-                                    Ok(mirror) => {
-                                        match mirror.store_packet(&envelope) {
-                                            Ok(_) => {
-                                                info!(
-                                                    sender = %sender_did,
-                                                    seq = envelope.sequence,
-                                                    "Stored packet from contact via 1:1 topic"
-                                                );
-                                                // Notify UI of new message
-                                                let _ = event_tx.send(ContactEvent::ProfileUpdated {
-                                                    did: sender_did,
-                                                });
-                                            }
-                                            Err(e) => {
-                                                warn!(
-                                                    sender = %sender_did,
-                                                    error = %e,
-                                                    "Failed to store packet from contact topic"
-                                                );
-                                            }
-                                        }
+                                let mirror = crate::profile::MirrorStore::new(storage.backend());
+                                match packet_gate.admit(
+                                    &storage,
+                                    &mirror,
+                                    keys.as_ref(),
+                                    &envelope,
+                                ) {
+                                    Ok(None) => {}
+                                    Ok(Some(trust)) => {
+                                        info!(
+                                            sender = %sender_did,
+                                            seq = envelope.sequence,
+                                            relayed,
+                                            ?trust,
+                                            "Stored packet from contact via 1:1 topic"
+                                        );
+                                        // Notify UI of new message
+                                        let _ = event_tx.send(ContactEvent::ProfileUpdated {
+                                            did: sender_did,
+                                        });
                                     }
                                     Err(e) => {
-                                        warn!(error = %e, "Failed to create MirrorStore for contact topic");
+                                        warn!(
+                                            sender = %sender_did,
+                                            error = %e,
+                                            "Failed to store packet from contact topic"
+                                        );
                                     }
                                 }
                             } else {
@@ -1705,6 +1904,12 @@ impl ContactManager {
                                 did: sender_did.clone(),
                             });
                         }
+                        Ok(crate::sync::ProfileGossipMessage::LogSync { message }) => {
+                            Self::handle_log_sync(&storage, &packet_gate, &peer_did, &topic_sender, message, &event_tx).await;
+                        }
+                        Ok(crate::sync::ProfileGossipMessage::Devices { list }) => {
+                            Self::handle_device_list(&storage, &peer_did, &topic_sender, list, &event_tx).await;
+                        }
                         Ok(_) => {
                             // Ignore Request/Response messages on contact topics
                         }
@@ -1729,6 +1934,198 @@ impl ContactManager {
         });
     }
 
+    /// Send a `LogRequest` for the contact's packets after our mirror head.
+    async fn request_log_catch_up(storage: &Storage, peer_did: &str, sender: &crate::sync::TopicSender) {
+        let Ok(did) = Did::parse(peer_did) else {
+            return;
+        };
+        let head = crate::profile::MirrorStore::new(storage.backend())
+            .get_head(&did)
+            .ok()
+            .flatten();
+
+        let request = crate::sync::PacketSyncMessage::log_request(did, head);
+        match crate::sync::ProfileGossipMessage::log_sync(request).to_bytes() {
+            Ok(bytes) => {
+                if let Err(e) = sender.broadcast(bytes).await {
+                    debug!(peer_did = %peer_did, error = %e, "Failed to send log catch-up request");
+                } else {
+                    info!(peer_did = %peer_did, since = ?head, "Requested missed packets from contact");
+                }
+            }
+            Err(e) => warn!(error = %e, "Failed to encode log catch-up request"),
+        }
+    }
+
+    /// Send our signed device list on a contact topic, if we have one.
+    ///
+    /// Identities used on a single device never publish a list.
+    async fn announce_device_list(storage: &Storage, peer_did: &str, sender: &crate::sync::TopicSender) {
+        let Some(keys) = storage.load_profile_keys().ok().flatten() else {
+            return;
+        };
+        let Some(list) = storage.load_device_list(keys.did().as_str()).ok().flatten() else {
+            return;
+        };
+        match crate::sync::ProfileGossipMessage::devices(list).to_bytes() {
+            Ok(bytes) => {
+                if let Err(e) = sender.broadcast(bytes).await {
+                    debug!(peer_did = %peer_did, error = %e, "Failed to send our device list");
+                } else {
+                    debug!(peer_did = %peer_did, "Sent our device list to contact");
+                }
+            }
+            Err(e) => warn!(error = %e, "Failed to encode device list"),
+        }
+    }
+
+    /// Handle a `Devices` message on a contact topic.
+    ///
+    /// Only the contact's list and our own are accepted: our own arrives when
+    /// another of our devices shares the topic. If our list had to be merged,
+    /// the result goes back out so all our devices agree on it.
+    async fn handle_device_list(
+        storage: &Storage,
+        peer_did: &str,
+        sender: &crate::sync::TopicSender,
+        list: crate::identity::SignedDeviceList,
+        event_tx: &broadcast::Sender<ContactEvent>,
+    ) {
+        let did = list.list.did.to_string();
+        let is_ours = storage
+            .load_profile_keys()
+            .ok()
+            .flatten()
+            .is_some_and(|keys| keys.did() == list.list.did);
+        if did != peer_did && !is_ours {
+            warn!(peer_did = %peer_did, list_did = %did, "Ignoring device list for a third party");
+            return;
+        }
+
+        match crate::sync::receive_device_list(storage, &list) {
+            Ok(crate::sync::DeviceListUpdate::Unchanged) => {}
+            Ok(crate::sync::DeviceListUpdate::Stored) => {
+                if !is_ours {
+                    let _ = event_tx.send(ContactEvent::ProfileUpdated { did });
+                }
+            }
+            Ok(crate::sync::DeviceListUpdate::Merged(merged)) => {
+                match crate::sync::ProfileGossipMessage::devices(*merged).to_bytes() {
+                    Ok(bytes) => {
+                        if let Err(e) = sender.broadcast(bytes).await {
+                            debug!(peer_did = %peer_did, error = %e, "Failed to send merged device list");
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "Failed to encode device list"),
+                }
+            }
+            Err(e) => warn!(peer_did = %peer_did, error = %e, "Rejected device list"),
+        }
+    }
+
+    /// Handle a `LogSync` message on a contact topic.
+    ///
+    /// Requests are answered from our own log only; responses are accepted
+    /// only for the contact's own log, and each entry must carry the
+    /// contact's signature before it is stored in the mirror.
+    async fn handle_log_sync(
+        storage: &Storage,
+        packet_gate: &crate::sync::PacketGate,
+        peer_did: &str,
+        sender: &crate::sync::TopicSender,
+        message: crate::sync::PacketSyncMessage,
+        event_tx: &broadcast::Sender<ContactEvent>,
+    ) {
+        use crate::sync::PacketSyncMessage;
+
+        let mirror = crate::profile::MirrorStore::new(storage.backend());
+
+        match &message {
+            PacketSyncMessage::LogRequest { did, since_sequence } => {
+                let is_ours = storage
+                    .load_profile_keys()
+                    .ok()
+                    .flatten()
+                    .is_some_and(|keys| keys.did() == *did);
+                if !is_ours {
+                    debug!(peer_did = %peer_did, log = %did, "Ignoring log request for a log we don't own");
+                    return;
+                }
+
+                let packets = match mirror.get_after(did, *since_sequence) {
+                    Ok(packets) if !packets.is_empty() => packets,
+                    Ok(_) => {
+                        debug!(peer_did = %peer_did, "Contact is up to date with our log");
+                        return;
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Failed to read our log for catch-up");
+                        return;
+                    }
+                };
+                let response = match PacketSyncMessage::log_response(did.clone(), &packets) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        warn!(error = %e, "Failed to build log response");
+                        return;
+                    }
+                };
+                match crate::sync::ProfileGossipMessage::log_sync(response).to_bytes() {
+                    Ok(bytes) => {
+                        if let Err(e) = sender.broadcast(bytes).await {
+                            warn!(peer_did = %peer_did, error = %e, "Failed to send log response");
+                        } else {
+                            info!(peer_did = %peer_did, count = packets.len(), "Sent missed packets to contact");
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "Failed to encode log response"),
+                }
+            }
+            PacketSyncMessage::LogResponse { did, .. } => {
+                if did.as_str() != peer_did {
+                    warn!(expected = %peer_did, actual = %did, "Log response for another profile on contact topic");
+                    return;
+                }
+                let entries = match message.extract_entries() {
+                    Some(Ok(entries)) => entries,
+                    Some(Err(e)) => {
+                        warn!(peer_did = %peer_did, error = %e, "Failed to decode log response");
+                        return;
+                    }
+                    None => return,
+                };
+
+                let own_keys = storage.load_profile_keys().ok().flatten();
+                let mut stored = 0;
+                for envelope in entries {
+                    let head = mirror.get_head(did).ok().flatten();
+                    if envelope.sender != *did || head.is_some_and(|h| envelope.sequence <= h) {
+                        continue;
+                    }
+                    match packet_gate.admit(storage, &mirror, own_keys.as_ref(), &envelope) {
+                        Ok(None) => break,
+                        Ok(Some(_)) => stored += 1,
+                        Err(SyncError::SignatureInvalid(e)) => {
+                            // Later entries chain from a forged one; drop the rest
+                            warn!(sender = %did, sequence = envelope.sequence, error = %e, "Forged packet in log response");
+                            break;
+                        }
+                        Err(e) => {
+                            warn!(sender = %did, sequence = envelope.sequence, error = %e, "Failed to store caught-up packet");
+                        }
+                    }
+                }
+                if stored > 0 {
+                    info!(peer_did = %peer_did, stored, "Caught up on missed packets from contact");
+                    let _ = event_tx.send(ContactEvent::ProfileUpdated {
+                        did: peer_did.to_string(),
+                    });
+                }
+            }
+            _ => debug!(peer_did = %peer_did, "Ignoring unexpected log sync message"),
+        }
+    }
+
     /// Derive deterministic 1:1 contact topic from two DIDs
     ///
     /// Uses BLAKE3 hash of sorted DIDs to ensure both peers derive the same topic.
@@ -2110,6 +2507,15 @@ impl ContactManager {
         data.extend_from_slice(invite.display_name.as_bytes());
         data.extend_from_slice(&invite.created_at.to_le_bytes());
         data.extend_from_slice(&invite.expires_at.to_le_bytes());
+        if let Some(ticket) = &invite.realm_ticket {
+            data.extend_from_slice(ticket.as_bytes());
+        }
+        if let Some(greeting) = &invite.greeting {
+            // Length-prefixed so it can't be confused with the ticket's tail
+            let greeting_bytes = postcard::to_allocvec(greeting)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            data.extend_from_slice(&greeting_bytes);
+        }
 
         // Sign with Ed25519 only (lightweight 64 bytes for QR codes)
         // Invites are ephemeral (expire in hours), so quantum resistance is less critical
@@ -2174,6 +2580,15 @@ impl ContactManager {
         data.extend_from_slice(invite.display_name.as_bytes());
         data.extend_from_slice(&invite.created_at.to_le_bytes());
         data.extend_from_slice(&invite.expires_at.to_le_bytes());
+        if let Some(ticket) = &invite.realm_ticket {
+            data.extend_from_slice(ticket.as_bytes());
+        }
+        if let Some(greeting) = &invite.greeting {
+            // Length-prefixed so it can't be confused with the ticket's tail
+            let greeting_bytes = postcard::to_allocvec(greeting)
+                .map_err(|e| SyncError::Serialization(e.to_string()))?;
+            data.extend_from_slice(&greeting_bytes);
+        }
 
         // Validate Ed25519 signature format (64 bytes)
         if invite.signature.len() != 64 {
@@ -2602,7 +3017,12 @@ mod tests {
         // Create empty active_topics for testing (normally shared with ContactProtocolHandler)
         let active_topics = Arc::new(RwLock::new(HashMap::new()));
 
-        let manager = ContactManager::new(gossip_sync, keypair, did, storage, event_tx, active_topics, None);
+        let packet_gate = Arc::new(crate::sync::PacketGate::new(
+            &crate::sync::GossipConfig::default(),
+            broadcast::channel(16).0,
+        ));
+
+        let manager = ContactManager::new(gossip_sync, keypair, did, storage, event_tx, active_topics, None, packet_gate);
 
         (manager, temp_dir)
     }
@@ -2632,6 +3052,81 @@ mod tests {
         assert!(!decoded.is_expired());
     }
 
+    #[tokio::test]
+    async fn test_invite_with_bundled_realm_roundtrip() {
+        let (manager, _temp) = create_test_manager().await;
+        let profile = create_test_profile("Love");
+
+        let invite_code = manager
+            .generate_invite_with_realm(profile, 24, "sync-realm:example".to_string())
+            .unwrap();
+
+        let decoded = manager.decode_invite(&invite_code).unwrap();
+        assert_eq!(decoded.version, 3);
+        assert_eq!(decoded.display_name, "Love");
+        assert_eq!(decoded.realm_ticket.as_deref(), Some("sync-realm:example"));
+    }
+
+    #[tokio::test]
+    async fn test_invite_greeting_reaches_pending_request() {
+        let (manager, _temp) = create_test_manager().await;
+        let greeting = "Hi Joy, it's Love from the garden meetup!".to_string();
+
+        let invite_code = manager
+            .generate_invite_with_greeting(create_test_profile("Love"), 24, None, greeting.clone())
+            .unwrap();
+        let invite = manager.decode_invite(&invite_code).unwrap();
+        assert_eq!(invite.version, 4);
+        assert_eq!(invite.greeting.as_deref(), Some(greeting.as_str()));
+        assert_eq!(invite.realm_ticket, None);
+
+        // What send_contact_request saves, without the network step
+        let pending = PendingContact {
+            invite_id: invite.invite_id,
+            peer_did: invite.inviter_did.clone(),
+            profile: create_test_profile("Love"),
+            signed_profile: None,
+            node_addr: invite.node_addr.clone(),
+            state: ContactState::OutgoingPending,
+            created_at: chrono::Utc::now().timestamp(),
+            encryption_keys: None,
+            peer_contact_dids: vec![],
+            greeting: invite.greeting.clone(),
+        };
+        manager.storage.save_pending(&pending).unwrap();
+
+        let outgoing = manager.storage.list_outgoing_pending().unwrap();
+        assert_eq!(outgoing[0].greeting.as_deref(), Some(greeting.as_str()));
+
+        // Greetings travel alongside a bundled realm too
+        let with_realm = manager
+            .generate_invite_with_greeting(
+                create_test_profile("Love"),
+                24,
+                Some("sync-realm:example".to_string()),
+                greeting.clone(),
+            )
+            .unwrap();
+        let decoded = manager.decode_invite(&with_realm).unwrap();
+        assert_eq!(decoded.realm_ticket.as_deref(), Some("sync-realm:example"));
+        assert_eq!(decoded.greeting.as_deref(), Some(greeting.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_invite_greeting_length_limit() {
+        let (manager, _temp) = create_test_manager().await;
+
+        let longest = "✨".repeat(MAX_INVITE_GREETING_CHARS);
+        assert!(manager
+            .generate_invite_with_greeting(create_test_profile("Love"), 24, None, longest.clone())
+            .is_ok());
+
+        let too_long = format!("{}!", longest);
+        let result =
+            manager.generate_invite_with_greeting(create_test_profile("Love"), 24, None, too_long);
+        assert!(matches!(result, Err(SyncError::InvalidInvite(_))));
+    }
+
     #[tokio::test]
     async fn test_hybrid_invite_size_reduction() {
         let (manager, _temp) = create_test_manager().await;
@@ -2729,6 +3224,7 @@ mod tests {
             created_at: chrono::Utc::now().timestamp(),
             encryption_keys: None,
             peer_contact_dids: vec![],
+            greeting: None,
         };
 
         // Save to storage (what send_contact_request does, without network operation)
@@ -2761,6 +3257,7 @@ mod tests {
             created_at: chrono::Utc::now().timestamp(),
             encryption_keys: None,
             peer_contact_dids: vec![],
+            greeting: None,
         };
 
         manager.storage.save_pending(&pending).unwrap();
@@ -2790,6 +3287,7 @@ mod tests {
             is_favorite: false,
             encryption_keys: pending.encryption_keys.clone(),
             mutual_peers: vec![],
+            nickname: None,
         };
 
         // 3. Save contact to storage
@@ -2825,6 +3323,7 @@ mod tests {
             created_at: chrono::Utc::now().timestamp(),
             encryption_keys: None,
             peer_contact_dids: vec![],
+            greeting: None,
         };
 
         manager.storage.save_pending(&pending).unwrap();
@@ -2941,4 +3440,31 @@ mod tests {
 
         assert!(matches!(event, ContactEvent::InviteGenerated { .. }));
     }
+
+    #[test]
+    fn test_contact_accepted_event_serializes_without_key_material() {
+        let contact = ContactInfo {
+            peer_did: "did:sync:test".to_string(),
+            peer_endpoint_id: [1u8; 32],
+            profile: create_test_profile("Grace"),
+            node_addr: NodeAddrBytes::new([1u8; 32]),
+            contact_topic: [2u8; 32],
+            contact_key: [3u8; 32],
+            accepted_at: 1_700_000_000,
+            last_seen: 1_700_000_000,
+            status: ContactStatus::Online,
+            is_favorite: true,
+            encryption_keys: Some(vec![4u8; 8]),
+            mutual_peers: vec![],
+            nickname: None,
+        };
+
+        let json = serde_json::to_value(ContactEvent::ContactAccepted { contact }).unwrap();
+        assert_eq!(json["type"], "contact_accepted");
+        assert_eq!(json["contact"]["peer_did"], "did:sync:test");
+        assert_eq!(json["contact"]["profile"]["display_name"], "Grace");
+        assert!(json["contact"].get("contact_key").is_none());
+        assert!(json["contact"].get("contact_topic").is_none());
+        assert!(json["contact"].get("encryption_keys").is_none());
+    }
 }
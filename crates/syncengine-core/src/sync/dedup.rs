@@ -0,0 +1,112 @@
+//! Bounded seen-set for incoming realm changes
+//!
+//! In a dense mesh the same change reaches us over several gossip paths, and
+//! peers re-send their full document whenever a neighbor comes up. Every copy
+//! would otherwise be verified, decrypted and merged again only to change
+//! nothing. The engine remembers the BLAKE3 hashes of envelopes and change
+//! payloads it has applied and drops repeats before doing that work.
+//!
+//! The set holds at most `capacity` hashes; once full, the least recently
+//! seen hash is evicted. A capacity of zero disables deduplication.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Hash identifying an envelope or change payload
+pub type ContentHash = [u8; 32];
+
+/// Hash `bytes` for a [`SeenCache`] lookup.
+pub fn content_hash(bytes: &[u8]) -> ContentHash {
+    *blake3::hash(bytes).as_bytes()
+}
+
+/// Least-recently-used set of content hashes.
+#[derive(Debug, Clone)]
+pub struct SeenCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<ContentHash, u64>,
+    order: BTreeMap<u64, ContentHash>,
+}
+
+impl SeenCache {
+    /// Create a cache holding up to `capacity` hashes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Check whether `hash` has been seen, marking it recently used if so.
+    pub fn check(&mut self, hash: &ContentHash) -> bool {
+        let Some(last) = self.entries.get(hash).copied() else {
+            return false;
+        };
+        self.order.remove(&last);
+        self.touch(*hash);
+        true
+    }
+
+    /// Remember `hash`, evicting the least recently seen one when full.
+    pub fn insert(&mut self, hash: ContentHash) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(last) = self.entries.get(&hash).copied() {
+            self.order.remove(&last);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.touch(hash);
+    }
+
+    /// Number of hashes held.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no hashes are held.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn touch(&mut self, hash: ContentHash) {
+        self.tick += 1;
+        self.entries.insert(hash, self.tick);
+        self.order.insert(self.tick, hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_seen() {
+        let mut cache = SeenCache::new(2);
+        let (a, b, c) = (content_hash(b"a"), content_hash(b"b"), content_hash(b"c"));
+
+        cache.insert(a);
+        cache.insert(b);
+        // Seeing `a` again makes `b` the oldest
+        assert!(cache.check(&a));
+        cache.insert(c);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.check(&a));
+        assert!(!cache.check(&b));
+        assert!(cache.check(&c));
+    }
+
+    #[test]
+    fn test_zero_capacity_remembers_nothing() {
+        let mut cache = SeenCache::new(0);
+        cache.insert(content_hash(b"a"));
+        assert!(cache.is_empty());
+        assert!(!cache.check(&content_hash(b"a")));
+    }
+}
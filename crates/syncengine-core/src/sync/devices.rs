@@ -0,0 +1,137 @@
+//! Device lists received from contacts and from our own other devices
+//!
+//! Every device of an identity signs the device list with the same identity
+//! key, so any of them can publish it. Two devices may each add an entry at
+//! the same time; [`receive_device_list`] keeps the higher-ranked list for
+//! other identities, and for our own merges both and signs the union, so no
+//! device ever drops another from the list.
+
+use tracing::{debug, info, warn};
+
+use crate::error::SyncError;
+use crate::identity::{Did, SignedDeviceList};
+use crate::storage::Storage;
+
+/// What [`receive_device_list`] did with a list
+#[derive(Debug, Clone)]
+pub enum DeviceListUpdate {
+    /// The list was no newer than the one we hold
+    Unchanged,
+    /// The list replaced the one we held
+    Stored,
+    /// The list was ours and lacked devices we know of. The merged list
+    /// was stored and should be sent back so every device converges.
+    Merged(Box<SignedDeviceList>),
+}
+
+/// Store a device list received from the network if it is newer than ours.
+///
+/// # Errors
+///
+/// Returns [`SyncError::SignatureInvalid`] if the list isn't signed by the
+/// identity it names.
+pub fn receive_device_list(
+    storage: &Storage,
+    incoming: &SignedDeviceList,
+) -> Result<DeviceListUpdate, SyncError> {
+    let did = &incoming.list.did;
+    if !incoming.verify() {
+        warn!(%did, "Rejected device list with invalid signature");
+        return Err(SyncError::SignatureInvalid(format!(
+            "Device list version {} for {}",
+            incoming.list.version, did
+        )));
+    }
+
+    let stored = storage.load_device_list(did.as_str())?;
+    let identity = storage
+        .load_identity()?
+        .filter(|keypair| Did::from_public_key(&keypair.public_key()) == *did);
+
+    if let (Some(keypair), Some(stored)) = (identity, stored.as_ref()) {
+        // Whichever list wins, it must not lose a device the other one has
+        let winner = if incoming.supersedes(stored) { incoming } else { stored };
+        let mut merged = winner.list.clone();
+        if merged.merge(&incoming.list) | merged.merge(&stored.list) {
+            merged.version = incoming.list.version.max(stored.list.version) + 1;
+            let signed = SignedDeviceList::sign(merged, &keypair);
+            storage.save_device_list(&signed)?;
+            info!(%did, version = signed.list.version, devices = signed.list.devices.len(), "Merged our device list");
+            return Ok(DeviceListUpdate::Merged(Box::new(signed)));
+        }
+    }
+
+    match stored {
+        Some(stored) if !incoming.supersedes(&stored) => {
+            debug!(%did, version = incoming.list.version, "Device list is not newer than ours");
+            Ok(DeviceListUpdate::Unchanged)
+        }
+        _ => {
+            storage.save_device_list(incoming)?;
+            info!(%did, version = incoming.list.version, devices = incoming.list.devices.len(), "Stored device list");
+            Ok(DeviceListUpdate::Stored)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::{DeviceEntry, DeviceId, DeviceList, HybridKeypair};
+
+    fn list_with(keypair: &HybridKeypair, version: u64, devices: &[DeviceId]) -> SignedDeviceList {
+        let mut list = DeviceList::new(Did::from_public_key(&keypair.public_key()));
+        list.version = version;
+        for device in devices {
+            list.upsert(DeviceEntry {
+                device: *device,
+                endpoint_id: None,
+                name: device.to_string(),
+                added_at: 0,
+            });
+        }
+        SignedDeviceList::sign(list, keypair)
+    }
+
+    #[test]
+    fn test_contact_device_list_keeps_newest() {
+        let storage = Storage::in_memory();
+        let contact = HybridKeypair::generate();
+        let linked = DeviceId::generate();
+        let old = list_with(&contact, 1, &[DeviceId::PRIMARY]);
+        let new = list_with(&contact, 2, &[DeviceId::PRIMARY, linked]);
+
+        assert!(matches!(receive_device_list(&storage, &new).unwrap(), DeviceListUpdate::Stored));
+        assert!(matches!(receive_device_list(&storage, &old).unwrap(), DeviceListUpdate::Unchanged));
+        let stored = storage.load_device_list(new.list.did.as_str()).unwrap().unwrap();
+        assert_eq!(stored.list, new.list);
+
+        // A list signed by someone else is rejected
+        let mut forged = list_with(&HybridKeypair::generate(), 3, &[]);
+        forged.list.did = new.list.did.clone();
+        assert!(receive_device_list(&storage, &forged).is_err());
+    }
+
+    #[test]
+    fn test_own_device_list_merges_concurrent_links() {
+        let storage = Storage::in_memory();
+        let keypair = HybridKeypair::generate();
+        storage.save_identity(&keypair).unwrap();
+        let (laptop, phone) = (DeviceId::generate(), DeviceId::generate());
+        storage
+            .save_device_list(&list_with(&keypair, 2, &[DeviceId::PRIMARY, laptop]))
+            .unwrap();
+
+        // Another device linked the phone from the same version
+        let theirs = list_with(&keypair, 2, &[DeviceId::PRIMARY, phone]);
+        let DeviceListUpdate::Merged(merged) = receive_device_list(&storage, &theirs).unwrap() else {
+            panic!("Expected the lists to merge");
+        };
+        assert!(merged.verify());
+        assert_eq!(merged.list.version, 3);
+        assert_eq!(merged.list.devices.len(), 3);
+
+        // The merged list comes back from the other device unchanged
+        assert!(matches!(receive_device_list(&storage, &merged).unwrap(), DeviceListUpdate::Unchanged));
+    }
+}
@@ -259,6 +259,15 @@ impl SyncEnvelope {
     pub fn version(&self) -> u8 {
         self.version
     }
+
+    /// Short id for following this message through the logs of every instance.
+    ///
+    /// Derived from the random nonce chosen when the message was sealed, so
+    /// the sender and every receiver compute the same id without any extra
+    /// bytes on the wire.
+    pub fn correlation_id(&self) -> String {
+        hex::encode(&blake3::hash(&self.nonce).as_bytes()[..8])
+    }
 }
 
 #[cfg(test)]
@@ -337,6 +346,7 @@ mod tests {
             realm_id,
             heads: vec![vec![0u8; 32]],
             sender_addr: None,
+            sent_at_ms: 0,
         };
 
         // Seal with correct key
@@ -394,6 +404,7 @@ mod tests {
             realm_id,
             heads: vec![],
             sender_addr: None,
+            sent_at_ms: 0,
         };
 
         // Seal the envelope
@@ -434,6 +445,7 @@ mod tests {
         assert_eq!(restored.ciphertext, envelope.ciphertext);
         assert_eq!(restored.nonce, envelope.nonce);
         assert_eq!(restored.signature, envelope.signature);
+        assert_eq!(restored.correlation_id(), envelope.correlation_id());
 
         // Should be able to open the restored envelope
         let opened = restored.open(&realm_key, mock_verify).unwrap();
@@ -480,8 +492,9 @@ mod tests {
         let envelope1 = SyncEnvelope::seal(&message, sender, &realm_key, mock_sign).unwrap();
         let envelope2 = SyncEnvelope::seal(&message, sender, &realm_key, mock_sign).unwrap();
 
-        // Nonces should be different (random)
+        // Nonces should be different (random), and with them the correlation ids
         assert_ne!(envelope1.nonce, envelope2.nonce);
+        assert_ne!(envelope1.correlation_id(), envelope2.correlation_id());
 
         // Ciphertexts should also be different due to different nonces
         assert_ne!(envelope1.ciphertext, envelope2.ciphertext);
@@ -549,6 +562,7 @@ mod tests {
             realm_id: realm_id.clone(),
             heads: vec![],
             sender_addr: None,
+            sent_at_ms: 0,
         };
 
         let envelope = SyncEnvelope::seal(&message, sender, &realm_key, mock_sign).unwrap();
@@ -17,16 +17,28 @@
 //! │  ├── RealmChanged: Remote peer made changes                     │
 //! │  ├── PeerConnected: New peer joined realm                       │
 //! │  ├── PeerDisconnected: Peer left realm                          │
+//! │  ├── TaskChanged: Local task added, toggled or deleted          │
+//! │  ├── TaskReminder: A task's due date is coming up               │
+//! │  ├── ClockSkewWarning: A peer's clock is far from ours          │
+//! │  ├── PeerRateLimited: A peer is sending packets too fast        │
+//! │  ├── Mentioned: A contact @-mentioned us in a message           │
 //! │  └── SyncError: Error occurred during sync                      │
 //! └─────────────────────────────────────────────────────────────────┘
 //! ```
+//!
+//! Both types serialize to JSON for external consumers (e.g. the CLI's
+//! `/events` WebSocket). `SyncEvent` is tagged with a snake_case `type` field
+//! and realm IDs are written in base58.
 
 use std::fmt;
 
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::profile::{PacketEnvelope, PacketPayload, ProfileKeys};
 use crate::types::RealmId;
 
 /// Debug information about a single peer connection.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PeerDebugInfo {
     /// Short peer ID (first 8 chars for display)
     pub peer_id: String,
@@ -38,9 +50,41 @@ pub struct PeerDebugInfo {
     pub connection_duration_secs: Option<u64>,
 }
 
+/// Debug information about a realm topic we are subscribed to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopicDebugInfo {
+    /// The realm whose topic this is
+    #[serde(serialize_with = "base58_realm_id")]
+    pub realm_id: RealmId,
+    /// Realm name, for reading bug reports
+    pub realm_name: String,
+    /// Sync status of the realm
+    pub status: SyncStatus,
+    /// Current gossip neighbors on the topic
+    pub neighbor_count: usize,
+    /// Whether sync is paused for the realm
+    pub paused: bool,
+}
+
+/// A sync error kept for debug snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NetworkErrorRecord {
+    /// The realm the error occurred in (if known)
+    #[serde(serialize_with = "base58_realm_id_opt")]
+    pub realm_id: Option<RealmId>,
+    /// Error message
+    pub message: String,
+    /// When it occurred (Unix seconds)
+    pub at: i64,
+}
+
 /// Debug information about the network state for a realm.
 /// Used by UI to show detailed sync status in a debug dropdown.
-#[derive(Debug, Clone, Default, PartialEq)]
+///
+/// `SyncEngine::network_debug_snapshot` fills the same struct for the whole
+/// node, including the topic, relay, pending and error fields below, and it
+/// serializes to JSON for bug reports.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct NetworkDebugInfo {
     /// Our node's public key (hex string, first 8 chars for display)
     pub node_id: String,
@@ -60,10 +104,23 @@ pub struct NetworkDebugInfo {
     pub connected_peers: Vec<String>,
     /// Detailed peer information
     pub peers: Vec<PeerDebugInfo>,
+    /// Realm topics we are subscribed to (node snapshot only)
+    pub topics: Vec<TopicDebugInfo>,
+    /// Home relay URL, or the configured one before it connects
+    pub relay_url: Option<String>,
+    /// Whether the home relay is connected (`None` if relays are disabled
+    /// or networking is off)
+    pub relay_connected: Option<bool>,
+    /// Direct messages not yet acknowledged by their recipient
+    pub pending_outbox_messages: usize,
+    /// Shared realms with local changes not yet broadcast
+    pub pending_unsynced_realms: usize,
+    /// Most recent sync errors, oldest first
+    pub recent_errors: Vec<NetworkErrorRecord>,
 }
 
 /// Status of synchronization for a realm
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum SyncStatus {
     /// Realm is not currently syncing
     Idle,
@@ -96,11 +153,13 @@ impl fmt::Display for SyncStatus {
 }
 
 /// Events emitted during synchronization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum SyncEvent {
     /// Remote peer made changes to a realm's document
     RealmChanged {
         /// The realm that was changed
+        #[serde(serialize_with = "base58_realm_id")]
         realm_id: RealmId,
         /// Number of changes applied
         changes_applied: usize,
@@ -108,6 +167,7 @@ pub enum SyncEvent {
     /// A new peer connected to a realm's sync topic
     PeerConnected {
         /// The realm the peer connected to
+        #[serde(serialize_with = "base58_realm_id")]
         realm_id: RealmId,
         /// The peer's public key (as hex string for now)
         peer_id: String,
@@ -115,6 +175,7 @@ pub enum SyncEvent {
     /// A peer disconnected from a realm's sync topic
     PeerDisconnected {
         /// The realm the peer disconnected from
+        #[serde(serialize_with = "base58_realm_id")]
         realm_id: RealmId,
         /// The peer's public key (as hex string for now)
         peer_id: String,
@@ -122,13 +183,59 @@ pub enum SyncEvent {
     /// Sync status changed for a realm
     StatusChanged {
         /// The realm whose status changed
+        #[serde(serialize_with = "base58_realm_id")]
         realm_id: RealmId,
         /// The new sync status
         status: SyncStatus,
     },
+    /// A task was added, toggled or deleted on this node
+    TaskChanged {
+        /// The realm containing the task
+        #[serde(serialize_with = "base58_realm_id")]
+        realm_id: RealmId,
+        /// The task's ID
+        task_id: String,
+    },
+    /// A task's reminder time has arrived; sent once per due date
+    TaskReminder {
+        /// The realm containing the task
+        #[serde(serialize_with = "base58_realm_id")]
+        realm_id: RealmId,
+        /// The task's ID
+        task_id: String,
+    },
+    /// A peer's estimated clock skew went over the warning threshold
+    ///
+    /// Sent once each time the estimate crosses the threshold, not on every
+    /// announce while it stays skewed.
+    ClockSkewWarning {
+        /// The realm the skewed announce arrived on
+        #[serde(serialize_with = "base58_realm_id")]
+        realm_id: RealmId,
+        /// The peer's DID
+        peer_did: String,
+        /// Estimated offset of the peer's clock from ours (positive = ahead)
+        skew_ms: i64,
+    },
+    /// A peer went over its packet rate limit and its packets are being
+    /// dropped
+    ///
+    /// Sent once per flood, not for every dropped packet.
+    PeerRateLimited {
+        /// The sender's DID
+        did: String,
+    },
+    /// A contact mentioned us with `@` in a direct message
+    Mentioned {
+        /// The sender's DID
+        sender_did: String,
+        /// Sequence of the message in the sender's log
+        sequence: u64,
+    },
     /// An error occurred during sync
     SyncError {
         /// The realm where the error occurred (if known)
+        #[serde(serialize_with = "base58_realm_id_opt")]
         realm_id: Option<RealmId>,
         /// Error message
         message: String,
@@ -143,11 +250,27 @@ impl SyncEvent {
             SyncEvent::PeerConnected { realm_id, .. } => Some(realm_id),
             SyncEvent::PeerDisconnected { realm_id, .. } => Some(realm_id),
             SyncEvent::StatusChanged { realm_id, .. } => Some(realm_id),
+            SyncEvent::TaskChanged { realm_id, .. } => Some(realm_id),
+            SyncEvent::TaskReminder { realm_id, .. } => Some(realm_id),
+            SyncEvent::ClockSkewWarning { realm_id, .. } => Some(realm_id),
+            SyncEvent::PeerRateLimited { .. } => None,
+            SyncEvent::Mentioned { .. } => None,
             SyncEvent::SyncError { realm_id, .. } => realm_id.as_ref(),
         }
     }
 }
 
+fn base58_realm_id<S: Serializer>(id: &RealmId, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&id.to_base58())
+}
+
+fn base58_realm_id_opt<S: Serializer>(id: &Option<RealmId>, s: S) -> Result<S::Ok, S::Error> {
+    match id {
+        Some(id) => base58_realm_id(id, s),
+        None => s.serialize_none(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,6 +325,27 @@ mod tests {
         };
         assert_eq!(event.realm_id(), None);
     }
+
+    #[test]
+    fn test_sync_event_serializes_with_type_tag_and_base58_realm() {
+        let realm_id = RealmId::new();
+
+        let event = SyncEvent::TaskChanged {
+            realm_id: realm_id.clone(),
+            task_id: "task_1".to_string(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "task_changed");
+        assert_eq!(json["realm_id"], realm_id.to_base58());
+        assert_eq!(json["task_id"], "task_1");
+
+        let event = SyncEvent::StatusChanged {
+            realm_id,
+            status: SyncStatus::Syncing { peer_count: 2 },
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["status"]["Syncing"]["peer_count"], 2);
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -209,7 +353,7 @@ mod tests {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 /// Direction of a packet relative to our node.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PacketDirection {
     /// We received this packet from the network
     Incoming,
@@ -218,21 +362,61 @@ pub enum PacketDirection {
 }
 
 /// Status of our ability to decrypt a packet's payload.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DecryptionStatus {
     /// Successfully decrypted - we are a recipient
     Decrypted,
     /// Global packet - not encrypted (public broadcast)
     Global,
-    /// Cannot decrypt - encrypted for someone else (we're relaying)
-    CannotDecrypt {
-        /// Why we can't decrypt (e.g., "Not addressed to us")
+    /// Not a recipient - encrypted for someone else (we're relaying)
+    NotForMe,
+    /// Addressed to us, but opening failed (tampered packet or unknown key)
+    Failed {
+        /// Why decryption failed
         reason: String,
     },
     /// Decryption not attempted (e.g., profile keys not loaded)
     NotAttempted,
 }
 
+impl DecryptionStatus {
+    /// Try to open a packet with our profile keys and classify the outcome.
+    ///
+    /// `realm_key` is the key of the realm a group packet is addressed to,
+    /// if we are a member; without it a group packet is `NotForMe`.
+    ///
+    /// Returns the payload alongside the status whenever it could be read
+    /// (global packets and packets we decrypted).
+    pub fn classify(
+        envelope: &PacketEnvelope,
+        keys: Option<&ProfileKeys>,
+        realm_key: Option<&[u8; 32]>,
+    ) -> (Self, Option<PacketPayload>) {
+        if envelope.is_global() {
+            return (Self::Global, envelope.decode_global_payload().ok());
+        }
+        if envelope.is_group() {
+            let Some(realm_key) = realm_key else {
+                return (Self::NotForMe, None);
+            };
+            return match envelope.open_group(realm_key) {
+                Ok(payload) => (Self::Decrypted, Some(payload)),
+                Err(e) => (Self::Failed { reason: e.to_string() }, None),
+            };
+        }
+        let Some(keys) = keys else {
+            return (Self::NotAttempted, None);
+        };
+        if !envelope.is_addressed_to(&keys.did()) {
+            return (Self::NotForMe, None);
+        }
+        match envelope.decrypt_for_recipient(keys) {
+            Ok(payload) => (Self::Decrypted, Some(payload)),
+            Err(e) => (Self::Failed { reason: e.to_string() }, None),
+        }
+    }
+}
+
 /// A packet event for visualization in Indra's Network.
 ///
 /// Tracks the path of a packet: Author → (optional Relay) → Destination
@@ -240,7 +424,7 @@ pub enum DecryptionStatus {
 /// This is used to visualize the relay behavior:
 /// - Direct messages: Author → Destination
 /// - Relayed messages: Author → Relay → Destination (relay can't decrypt)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PacketEvent {
     /// Unique identifier: "{sender_did}:{sequence}"
     pub id: String,
@@ -293,6 +477,26 @@ impl PacketEvent {
         format!("{}:{}", sender_did, sequence)
     }
 
+    /// Get the content preview for a packet opened with [`DecryptionStatus::classify`].
+    ///
+    /// Only readable packets get a text preview; everything else shows a
+    /// placeholder.
+    pub fn preview_for(status: &DecryptionStatus, payload: Option<&PacketPayload>) -> String {
+        match (status, payload) {
+            (_, Some(PacketPayload::DirectMessage { content, .. })) => Self::preview_content(content),
+            (_, Some(PacketPayload::ReputationReport { violation, .. })) => {
+                format!("[report: {}]", Self::preview_content(violation))
+            }
+            (_, Some(PacketPayload::Gratitude { message, .. })) => {
+                format!("[gratitude: {}]", Self::preview_content(message))
+            }
+            (DecryptionStatus::Global, Some(_)) => "[profile update]".to_string(),
+            (DecryptionStatus::Global, None) => "[global]".to_string(),
+            (_, Some(_)) => "[packet]".to_string(),
+            (_, None) => "[encrypted]".to_string(),
+        }
+    }
+
     /// Get a content preview from decrypted text.
     pub fn preview_content(content: &str) -> String {
         if content.len() > 20 {
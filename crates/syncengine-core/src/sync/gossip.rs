@@ -4,12 +4,15 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use iroh::discovery::mdns::{DiscoveryEvent, MdnsDiscovery};
 use iroh::discovery::static_provider::StaticProvider;
 use iroh::protocol::Router;
-use iroh::{Endpoint, EndpointAddr, PublicKey, SecretKey};
+use iroh::{Endpoint, EndpointAddr, PublicKey, RelayMode, RelayUrl, SecretKey};
 use iroh_gossip::net::{Gossip, GOSSIP_ALPN};
 use iroh_gossip::proto::TopicId;
+use n0_future::Stream;
 use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, info, warn};
 
@@ -94,6 +97,16 @@ impl TopicSender {
         Ok(())
     }
 
+    /// Connect to additional peers on this topic
+    pub async fn join_peers(&self, peers: Vec<PublicKey>) -> SyncResult<()> {
+        self.sender
+            .lock()
+            .await
+            .join_peers(peers)
+            .await
+            .map_err(|e| SyncError::Gossip(format!("Failed to join peers: {}", e)))
+    }
+
     /// Get the topic ID
     pub fn topic_id(&self) -> TopicId {
         self.topic_id
@@ -326,6 +339,182 @@ impl TopicHandle {
     }
 }
 
+/// Options for the iroh endpoint behind [`GossipSync`]
+#[derive(Debug, Clone, Default)]
+pub struct GossipConfig {
+    /// Find peers on the same LAN via mDNS, without relays or invites
+    pub local_discovery: bool,
+    /// Relay server to use instead of the default public relays
+    pub relay_url: Option<RelayUrl>,
+    /// Never use a relay; peers must reach each other directly
+    pub disable_relay: bool,
+    /// How often offline peers are retried (`None` uses
+    /// [`GossipConfig::DEFAULT_RECONNECT_INTERVAL`])
+    pub reconnect_interval: Option<Duration>,
+    /// How often open realm documents with unsaved changes are written to
+    /// storage (`None` uses [`GossipConfig::DEFAULT_AUTOSAVE_INTERVAL`])
+    pub autosave_interval: Option<Duration>,
+    /// Sustained profile packets per second accepted from one sender
+    /// (`None` uses [`GossipConfig::DEFAULT_PACKET_RATE`])
+    pub packet_rate: Option<u32>,
+    /// Profile packets one sender may send in a burst
+    /// (`None` uses [`GossipConfig::DEFAULT_PACKET_BURST`])
+    pub packet_burst: Option<u32>,
+    /// Largest incoming profile packet ciphertext accepted, in bytes
+    /// (`None` uses [`GossipConfig::DEFAULT_MAX_PACKET_BYTES`])
+    pub max_packet_bytes: Option<usize>,
+    /// Largest incoming realm change or full document accepted, in bytes
+    /// (`None` uses [`GossipConfig::DEFAULT_MAX_CHANGE_BYTES`])
+    pub max_change_bytes: Option<usize>,
+    /// Longest random delay before startup sync dials peers, in milliseconds;
+    /// `Some(0)` skips the delay (`None` uses
+    /// [`GossipConfig::DEFAULT_STARTUP_JITTER_MAX_MS`])
+    pub startup_jitter_max_ms: Option<u64>,
+    /// Applied realm changes remembered to drop repeats, by hash;
+    /// `Some(0)` turns deduplication off (`None` uses
+    /// [`GossipConfig::DEFAULT_DEDUP_CACHE_SIZE`])
+    pub dedup_cache_size: Option<usize>,
+}
+
+impl GossipConfig {
+    /// Default period of the background reconnection scheduler
+    pub const DEFAULT_RECONNECT_INTERVAL: Duration = Duration::from_secs(300);
+
+    /// Default period between autosaves of dirty realm documents
+    pub const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// Default sustained packet rate per sender
+    pub const DEFAULT_PACKET_RATE: u32 = 20;
+
+    /// Default packet burst per sender; large enough for a log catch-up
+    pub const DEFAULT_PACKET_BURST: u32 = 200;
+
+    /// Default limit on an incoming packet's ciphertext
+    pub const DEFAULT_MAX_PACKET_BYTES: usize = 256 * 1024;
+
+    /// Default limit on incoming realm changes; matches the gossip message size
+    pub const DEFAULT_MAX_CHANGE_BYTES: usize = 1024 * 1024;
+
+    /// Default startup jitter window; short enough not to delay the app noticeably
+    pub const DEFAULT_STARTUP_JITTER_MAX_MS: u64 = 2_000;
+
+    /// Default number of change hashes remembered for deduplication
+    pub const DEFAULT_DEDUP_CACHE_SIZE: usize = 1024;
+
+    /// Reconnection period to use, falling back to the default
+    pub fn effective_reconnect_interval(&self) -> Duration {
+        self.reconnect_interval
+            .unwrap_or(Self::DEFAULT_RECONNECT_INTERVAL)
+    }
+
+    /// Autosave period to use, falling back to the default
+    pub fn effective_autosave_interval(&self) -> Duration {
+        self.autosave_interval
+            .unwrap_or(Self::DEFAULT_AUTOSAVE_INTERVAL)
+    }
+
+    /// Packet size limit to use, falling back to the default
+    pub fn effective_max_packet_bytes(&self) -> usize {
+        self.max_packet_bytes
+            .unwrap_or(Self::DEFAULT_MAX_PACKET_BYTES)
+    }
+
+    /// Realm change size limit to use, falling back to the default
+    pub fn effective_max_change_bytes(&self) -> usize {
+        self.max_change_bytes
+            .unwrap_or(Self::DEFAULT_MAX_CHANGE_BYTES)
+    }
+
+    /// Startup jitter window to use, falling back to the default
+    pub fn effective_startup_jitter_max_ms(&self) -> u64 {
+        self.startup_jitter_max_ms
+            .unwrap_or(Self::DEFAULT_STARTUP_JITTER_MAX_MS)
+    }
+
+    /// Deduplication cache size to use, falling back to the default
+    pub fn effective_dedup_cache_size(&self) -> usize {
+        self.dedup_cache_size
+            .unwrap_or(Self::DEFAULT_DEDUP_CACHE_SIZE)
+    }
+
+    /// Per-sender packet rate limiter for these settings
+    pub fn packet_rate_limiter(&self) -> crate::sync::PacketRateLimiter {
+        crate::sync::PacketRateLimiter::new(
+            self.packet_rate.unwrap_or(Self::DEFAULT_PACKET_RATE),
+            self.packet_burst.unwrap_or(Self::DEFAULT_PACKET_BURST),
+        )
+    }
+
+    /// Parse a relay URL, accepting only `http` and `https` schemes
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidConfig` if the URL is malformed or uses another scheme.
+    pub fn parse_relay_url(url: &str) -> SyncResult<RelayUrl> {
+        let relay_url: RelayUrl = url
+            .parse()
+            .map_err(|e| SyncError::InvalidConfig(format!("Malformed relay URL '{}': {}", url, e)))?;
+        Self::check_relay_scheme(&relay_url)?;
+        Ok(relay_url)
+    }
+
+    fn check_relay_scheme(relay_url: &RelayUrl) -> SyncResult<()> {
+        match relay_url.scheme() {
+            "http" | "https" => Ok(()),
+            scheme => Err(SyncError::InvalidConfig(format!(
+                "Relay URL must use http or https, got '{}'",
+                scheme
+            ))),
+        }
+    }
+
+    /// Check the relay settings without building an endpoint
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::InvalidConfig` for a non-http(s) relay URL, if a
+    /// relay URL is set while relays are disabled, for a zero reconnect
+    /// or autosave interval, or for a zero packet rate, burst or size limit.
+    pub fn validate(&self) -> SyncResult<()> {
+        if self.max_packet_bytes == Some(0) || self.max_change_bytes == Some(0) {
+            return Err(SyncError::InvalidConfig(
+                "Payload size limits must be greater than zero".to_string(),
+            ));
+        }
+        if self.packet_rate == Some(0) || self.packet_burst == Some(0) {
+            return Err(SyncError::InvalidConfig(
+                "Packet rate and burst must be greater than zero".to_string(),
+            ));
+        }
+        if self.reconnect_interval == Some(Duration::ZERO) {
+            return Err(SyncError::InvalidConfig(
+                "Reconnect interval must be greater than zero".to_string(),
+            ));
+        }
+        if self.autosave_interval == Some(Duration::ZERO) {
+            return Err(SyncError::InvalidConfig(
+                "Autosave interval must be greater than zero".to_string(),
+            ));
+        }
+        self.relay_mode().map(|_| ())
+    }
+
+    /// Relay mode for the endpoint builder
+    fn relay_mode(&self) -> SyncResult<RelayMode> {
+        match (&self.relay_url, self.disable_relay) {
+            (Some(_), true) => Err(SyncError::InvalidConfig(
+                "A relay URL cannot be combined with disabling relays".to_string(),
+            )),
+            (Some(url), false) => {
+                Self::check_relay_scheme(url)?;
+                Ok(RelayMode::Custom(url.clone().into()))
+            }
+            (None, true) => Ok(RelayMode::Disabled),
+            (None, false) => Ok(RelayMode::Default),
+        }
+    }
+}
+
 /// Main gossip synchronization engine
 ///
 /// Manages an iroh endpoint with gossip protocol support,
@@ -337,6 +526,10 @@ pub struct GossipSync {
     router: Router,
     /// Static discovery provider for adding out-of-band peer addresses
     static_provider: StaticProvider,
+    /// mDNS discovery service, when local discovery is enabled
+    local_discovery: Option<MdnsDiscovery>,
+    /// Relay server set in the config, if any
+    relay_url: Option<RelayUrl>,
     #[allow(dead_code)]
     secret_key: SecretKey,
 }
@@ -369,6 +562,28 @@ impl GossipSync {
         profile_handler_deps: Option<(Arc<crate::storage::Storage>, Arc<HybridKeypair>, Did)>,
         blob_manager: Option<&BlobManager>,
     ) -> SyncResult<(Self, Option<ActiveContactTopics>)> {
+        Self::with_config(
+            GossipConfig::default(),
+            secret_key,
+            contact_handler_deps,
+            profile_handler_deps,
+            blob_manager,
+        )
+        .await
+    }
+
+    /// Create a new gossip sync instance with explicit endpoint options
+    ///
+    /// Same as [`with_secret_key`](Self::with_secret_key), but applies `config`
+    /// when building the endpoint.
+    pub async fn with_config(
+        config: GossipConfig,
+        secret_key: Option<SecretKey>,
+        contact_handler_deps: Option<(Arc<crate::storage::Storage>, tokio::sync::broadcast::Sender<crate::sync::ContactEvent>, String)>,
+        profile_handler_deps: Option<(Arc<crate::storage::Storage>, Arc<HybridKeypair>, Did)>,
+        blob_manager: Option<&BlobManager>,
+    ) -> SyncResult<(Self, Option<ActiveContactTopics>)> {
+        let relay_mode = config.relay_mode()?;
         let secret_key = secret_key.unwrap_or_else(|| SecretKey::generate(&mut rand::rng()));
 
         // Create static provider for out-of-band peer addresses
@@ -389,6 +604,7 @@ impl GossipSync {
         let endpoint = Endpoint::builder()
             .secret_key(secret_key.clone())
             .alpns(alpns)
+            .relay_mode(relay_mode)
             .discovery(static_provider.clone())
             .bind()
             .await
@@ -397,6 +613,18 @@ impl GossipSync {
         let endpoint_id = endpoint.id();
         info!(%endpoint_id, "Endpoint bound");
 
+        // mDNS is added after binding so we keep a handle for subscribing to its events
+        let local_discovery = if config.local_discovery {
+            let mdns = MdnsDiscovery::builder().build(endpoint_id).map_err(|e| {
+                SyncError::Network(format!("Failed to start local discovery: {}", e))
+            })?;
+            endpoint.discovery().add(mdns.clone());
+            info!("Local network discovery enabled");
+            Some(mdns)
+        } else {
+            None
+        };
+
         // Spawn gossip protocol handler with increased message size limit
         // Default is 4KB, but Automerge documents + envelope overhead can exceed this.
         // Use 1MB to support larger documents with many tasks.
@@ -451,10 +679,29 @@ impl GossipSync {
             gossip,
             router,
             static_provider,
+            local_discovery,
+            relay_url: config.relay_url,
             secret_key,
         }, active_topics))
     }
 
+    /// Relay server this endpoint was configured with, if any
+    pub fn configured_relay_url(&self) -> Option<&RelayUrl> {
+        self.relay_url.as_ref()
+    }
+
+    /// Subscribe to peers appearing on and leaving the local network
+    ///
+    /// Returns `None` if local discovery was not enabled in the [`GossipConfig`].
+    pub async fn local_discovery_events(
+        &self,
+    ) -> Option<impl Stream<Item = DiscoveryEvent> + Unpin + Send + 'static> {
+        match &self.local_discovery {
+            Some(mdns) => Some(mdns.subscribe().await),
+            None => None,
+        }
+    }
+
     /// Get this node's endpoint ID
     ///
     /// This is the public identifier other peers use to connect.
@@ -562,6 +809,21 @@ impl GossipSync {
         self.endpoint.addr()
     }
 
+    /// Whether the current path to `peer` is direct
+    ///
+    /// Returns `Some(false)` for relayed (or relay-assisted) paths and `None`
+    /// when there is no verified path yet.
+    pub fn path_is_direct(&self, peer: PublicKey) -> Option<bool> {
+        use iroh::endpoint::ConnectionType;
+        use iroh::Watcher;
+
+        match self.endpoint.conn_type(peer)?.get() {
+            ConnectionType::Direct(_) => Some(true),
+            ConnectionType::Relay(_) | ConnectionType::Mixed(..) => Some(false),
+            ConnectionType::None => None,
+        }
+    }
+
     /// Join a gossip topic using an invite ticket
     ///
     /// This extracts bootstrap peer information from the invite and subscribes
@@ -765,7 +1027,7 @@ impl GossipSync {
     }
 
     /// Gracefully shutdown the gossip sync engine
-    pub async fn shutdown(self) -> SyncResult<()> {
+    pub async fn shutdown(&self) -> SyncResult<()> {
         info!("Shutting down gossip sync");
 
         // Shutdown the router first
@@ -1050,4 +1312,50 @@ mod tests {
 
         gossip.shutdown().await.unwrap();
     }
+
+    #[test]
+    fn test_relay_url_validation() {
+        let url = GossipConfig::parse_relay_url("https://relay.example.org").unwrap();
+        assert_eq!(url.scheme(), "https");
+        assert!(GossipConfig::parse_relay_url("http://localhost:3340").is_ok());
+
+        for bad in ["ftp://relay.example.org", "not a url", "relay.example.org"] {
+            assert!(
+                matches!(GossipConfig::parse_relay_url(bad), Err(SyncError::InvalidConfig(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+
+        let conflicting = GossipConfig {
+            relay_url: Some(url),
+            disable_relay: true,
+            ..Default::default()
+        };
+        assert!(matches!(conflicting.validate(), Err(SyncError::InvalidConfig(_))));
+
+        let zero_interval = GossipConfig {
+            reconnect_interval: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert!(matches!(zero_interval.validate(), Err(SyncError::InvalidConfig(_))));
+
+        let zero_autosave = GossipConfig {
+            autosave_interval: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert!(matches!(zero_autosave.validate(), Err(SyncError::InvalidConfig(_))));
+
+        let zero_packet_rate = GossipConfig {
+            packet_rate: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(zero_packet_rate.validate(), Err(SyncError::InvalidConfig(_))));
+
+        let zero_change_limit = GossipConfig {
+            max_change_bytes: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(zero_change_limit.validate(), Err(SyncError::InvalidConfig(_))));
+    }
 }
@@ -62,37 +62,52 @@
 //! }
 //! ```
 
+pub mod capture;
 pub mod contact_handler;
 pub mod contact_manager;
 pub mod contact_protocol;
+pub mod dedup;
+pub mod devices;
 pub mod envelope;
 pub mod events;
 pub mod gossip;
 pub mod manager;
 pub mod packet_events;
+pub mod packet_gate;
 pub mod packet_protocol;
 pub mod profile_pinning;
 pub mod profile_protocol;
 pub mod protocol;
+pub mod rate_limit;
 pub mod relay;
+pub mod reputation;
 
+pub use capture::{CaptureDirection, CapturedMessage, CapturedRealm, MessageCapture};
 pub use contact_handler::ContactProtocolHandler;
 pub use contact_manager::{ContactEvent, ContactManager};
 pub use contact_protocol::{
     derive_contact_key, derive_contact_topic, ContactMessage, CONTACT_ALPN,
 };
+pub use dedup::{content_hash, ContentHash, SeenCache};
+pub use devices::{receive_device_list, DeviceListUpdate};
 pub use envelope::{SyncEnvelope, ENVELOPE_VERSION};
 pub use events::{
-    DecryptionStatus, NetworkDebugInfo, PacketDirection, PacketEvent, SyncEvent, SyncStatus,
+    DecryptionStatus, NetworkDebugInfo, NetworkErrorRecord, PacketDirection, PacketEvent,
+    SyncEvent, SyncStatus, TopicDebugInfo,
+};
+pub use gossip::{ActiveContactTopics, GossipConfig, GossipMessage, GossipSync, TopicEvent, TopicHandle, TopicReceiver, TopicSender};
+pub use packet_events::{
+    PacketEventBuffer, PacketEventBufferConfig, PacketEventExport, PacketEventFilter,
 };
-pub use gossip::{ActiveContactTopics, GossipMessage, GossipSync, TopicEvent, TopicHandle, TopicReceiver, TopicSender};
-pub use packet_events::{PacketEventBuffer, PacketEventBufferConfig};
 pub use manager::SyncManager;
+pub use packet_gate::{PacketGate, PacketTrust};
 pub use profile_pinning::{
-    derive_profile_topic, global_profile_topic, ProfileAction, ProfileGossipMessage,
-    ProfileMessageHandler,
+    derive_profile_topic, global_profile_topic, AnnounceDebouncer, ProfileAction,
+    ProfileGossipMessage, ProfileMessageHandler, PROFILE_REANNOUNCE_WINDOW,
 };
 pub use profile_protocol::{ProfileMessage, ProfileProtocolHandler, PublicProfile, PROFILE_ALPN};
 pub use packet_protocol::{CombinedMessage, MessageType, PacketSyncMessage, PacketWireMessage};
 pub use protocol::{SyncMessage, WireMessage};
+pub use rate_limit::{PacketRateLimiter, RateDecision};
 pub use relay::{RelayStore, RelayWrapper, StoredRelay, RELAY_MAGIC};
+pub use reputation::ReputationLedger;
@@ -14,6 +14,9 @@
 //! │  ├── broadcast_tx: broadcast::Sender<PacketEvent>              │
 //! │  │   └── Real-time updates for UI subscriptions                │
 //! │  │                                                              │
+//! │  ├── dropped: u64                                              │
+//! │  │   └── Events evicted because a peer's buffer was full       │
+//! │  │                                                              │
 //! │  └── config                                                    │
 //! │      └── max_events_per_peer: usize                            │
 //! └─────────────────────────────────────────────────────────────────┘
@@ -23,9 +26,10 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 use parking_lot::RwLock;
+use serde::Serialize;
 use tokio::sync::broadcast;
 
-use super::events::PacketEvent;
+use super::events::{PacketDirection, PacketEvent};
 
 /// Default maximum events to keep per peer.
 const DEFAULT_MAX_EVENTS_PER_PEER: usize = 50;
@@ -48,10 +52,84 @@ impl Default for PacketEventBufferConfig {
     }
 }
 
+/// Criteria for selecting packet events.
+///
+/// Every field left as `None` matches all events; set fields must all match.
+#[derive(Debug, Clone, Default)]
+pub struct PacketEventFilter {
+    /// Only events in this direction
+    pub direction: Option<PacketDirection>,
+
+    /// Only events shown in this peer's log (matches `PacketEvent::peer_did`)
+    pub peer_did: Option<String>,
+
+    /// Only events recorded at or after this time (Unix millis, inclusive)
+    pub since: Option<i64>,
+
+    /// Only events recorded strictly before this time (Unix millis, exclusive)
+    pub until: Option<i64>,
+}
+
+impl PacketEventFilter {
+    /// Create a filter that matches every event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to one direction.
+    pub fn direction(mut self, direction: PacketDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Restrict to one peer.
+    pub fn peer(mut self, peer_did: impl Into<String>) -> Self {
+        self.peer_did = Some(peer_did.into());
+        self
+    }
+
+    /// Restrict to the half-open time range `[since, until)` in Unix millis.
+    pub fn between(mut self, since: i64, until: i64) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    /// Check whether an event satisfies the filter.
+    pub fn matches(&self, event: &PacketEvent) -> bool {
+        if self.direction.is_some_and(|d| event.direction != d) {
+            return false;
+        }
+        if let Some(peer_did) = &self.peer_did {
+            if event.peer_did != *peer_did {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| event.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| event.timestamp >= until) {
+            return false;
+        }
+        true
+    }
+}
+
+/// A point-in-time export of the buffer, for external tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct PacketEventExport {
+    /// Events evicted before the export because a peer's buffer was full
+    pub dropped: u64,
+    /// Buffered events, oldest first
+    pub events: Vec<PacketEvent>,
+}
+
 /// Inner state for the packet event buffer.
 struct PacketEventBufferInner {
     /// Events organized by peer DID → circular buffer of events.
     events: HashMap<String, VecDeque<PacketEvent>>,
+    /// Events evicted since the buffer was created.
+    dropped: u64,
     /// Configuration.
     config: PacketEventBufferConfig,
 }
@@ -93,6 +171,7 @@ impl PacketEventBuffer {
         Arc::new(Self {
             inner: RwLock::new(PacketEventBufferInner {
                 events: HashMap::new(),
+                dropped: 0,
                 config,
             }),
             broadcast_tx,
@@ -118,11 +197,12 @@ impl PacketEventBuffer {
             let peer_events = inner.events.entry(peer_did).or_insert_with(VecDeque::new);
 
             // Evict oldest if at capacity
-            if peer_events.len() >= max_events {
-                peer_events.pop_front();
-            }
+            let evicted = peer_events.len() >= max_events && peer_events.pop_front().is_some();
 
             peer_events.push_back(event.clone());
+            if evicted {
+                inner.dropped += 1;
+            }
         }
 
         // Broadcast to subscribers (ignore errors if no subscribers)
@@ -153,6 +233,36 @@ impl PacketEventBuffer {
             .collect()
     }
 
+    /// Get the events matching `filter`, across peers, oldest first.
+    pub fn query(&self, filter: &PacketEventFilter) -> Vec<PacketEvent> {
+        let inner = self.inner.read();
+        let mut events: Vec<PacketEvent> = inner
+            .events
+            .values()
+            .flatten()
+            .filter(|event| filter.matches(event))
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| event.timestamp);
+        events
+    }
+
+    /// Number of events evicted because a peer's buffer was full.
+    ///
+    /// Not reset by [`clear_peer`](Self::clear_peer) or
+    /// [`clear_all`](Self::clear_all), which discard events on purpose.
+    pub fn dropped_count(&self) -> u64 {
+        self.inner.read().dropped
+    }
+
+    /// Snapshot every buffered event together with the dropped count.
+    pub fn export(&self) -> PacketEventExport {
+        PacketEventExport {
+            dropped: self.dropped_count(),
+            events: self.query(&PacketEventFilter::default()),
+        }
+    }
+
     /// Subscribe to real-time packet events.
     ///
     /// Returns a receiver that will receive all new events as they are recorded.
@@ -274,6 +384,64 @@ mod tests {
         assert_eq!(events[2].sequence, 5);
     }
 
+    #[test]
+    fn test_packet_event_buffer_counts_dropped_events() {
+        let buffer = PacketEventBuffer::new(PacketEventBufferConfig {
+            max_events_per_peer: 2,
+        });
+
+        for seq in 1..=5 {
+            buffer.record(make_test_event("peer1", seq));
+        }
+        buffer.record(make_test_event("peer2", 1));
+        assert_eq!(buffer.dropped_count(), 3);
+
+        // Deliberate clearing is not counted as loss
+        buffer.clear_all();
+        assert_eq!(buffer.dropped_count(), 3);
+    }
+
+    #[test]
+    fn test_packet_event_buffer_query_by_direction_peer_and_time() {
+        let buffer = PacketEventBuffer::with_defaults();
+        let at = |peer: &str, seq: u64, direction: PacketDirection, timestamp: i64| PacketEvent {
+            direction,
+            timestamp,
+            ..make_test_event(peer, seq)
+        };
+        buffer.record(at("peer1", 1, PacketDirection::Outgoing, 1_000));
+        buffer.record(at("peer2", 1, PacketDirection::Incoming, 2_000));
+        buffer.record(at("peer1", 2, PacketDirection::Incoming, 3_000));
+        buffer.record(at("peer2", 2, PacketDirection::Outgoing, 4_000));
+
+        let sequences = |filter: PacketEventFilter| -> Vec<(String, u64)> {
+            buffer
+                .query(&filter)
+                .into_iter()
+                .map(|e| (e.peer_did, e.sequence))
+                .collect()
+        };
+        let pair = |peer: &str, seq: u64| (peer.to_string(), seq);
+
+        // Across peers, oldest first
+        assert_eq!(
+            sequences(PacketEventFilter::new()),
+            [pair("peer1", 1), pair("peer2", 1), pair("peer1", 2), pair("peer2", 2)]
+        );
+        assert_eq!(
+            sequences(PacketEventFilter::new().direction(PacketDirection::Incoming)),
+            [pair("peer2", 1), pair("peer1", 2)]
+        );
+        assert_eq!(
+            sequences(PacketEventFilter::new().peer("peer2").direction(PacketDirection::Outgoing)),
+            [pair("peer2", 2)]
+        );
+        assert_eq!(
+            sequences(PacketEventFilter::new().between(2_000, 4_000)),
+            [pair("peer2", 1), pair("peer1", 2)]
+        );
+    }
+
     #[test]
     fn test_packet_event_buffer_per_peer_limits() {
         let config = PacketEventBufferConfig {
@@ -0,0 +1,350 @@
+//! Checks incoming profile packets pass before they are stored
+//!
+//! Packets reach us on several paths: handed to the engine directly, on a
+//! contact's 1:1 topic, wrapped in a relay request, on the global profile
+//! topic, and in log catch-up responses. All of them store through one
+//! shared [`PacketGate`], so a packet from a sender whose keys we know is
+//! kept only if it carries their signature. Packets from senders we hold no
+//! keys for are kept but flagged provisional in the [`MirrorStore`], and
+//! [`reverify_provisional`] settles them once the keys arrive: confirmed if
+//! the signature checks out, removed if it was forged.
+//!
+//! The gate also enforces the packet size limit and per-sender rate limit.
+//! Anyone can put any DID in a packet's sender field, so violations only
+//! count against a sender, in the rate limiter and the [`ReputationLedger`],
+//! once the packet's signature proves it came from them. Unverifiable
+//! packets share a single rate limit bucket and cost no one trust.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use crate::error::SyncError;
+use crate::identity::Did;
+use crate::profile::{MirrorStore, PacketEnvelope, ProfileKeys, ProfilePublicKeys};
+use crate::storage::Storage;
+use crate::sync::rate_limit::MAX_TRACKED_SENDERS;
+use crate::sync::reputation::{self, ReputationLedger};
+use crate::sync::{GossipConfig, PacketRateLimiter, RateDecision, SyncEvent};
+
+/// Rate limit bucket shared by all packets we can't verify yet
+const UNVERIFIED_SENDERS: &str = "unverified";
+
+/// How a received packet was stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketTrust {
+    /// Its signature matched the sender's known keys
+    Verified,
+    /// We hold no keys for the sender yet, so it is flagged until we do
+    Provisional,
+}
+
+/// Check a packet's signature against its sender's known keys.
+///
+/// The sender's signing key comes from our own profile keys, the contact's
+/// exchanged encryption keys, or their pinned profile, in that order.
+///
+/// # Returns
+///
+/// `Ok(true)` if the signature is valid, `Ok(false)` if it is not.
+///
+/// # Errors
+///
+/// Returns `SyncError::ContactKeyExchangeIncomplete` if we know no key for
+/// the sender, so the packet can't be checked yet.
+pub fn verify_packet(
+    storage: &Storage,
+    own_keys: Option<&ProfileKeys>,
+    envelope: &PacketEnvelope,
+) -> Result<bool, SyncError> {
+    let sender = &envelope.sender;
+    if let Some(keys) = own_keys.filter(|k| &k.did() == sender) {
+        return Ok(envelope.verify_signing_key(&keys.signing_public_key()));
+    }
+    if let Some(bytes) = storage
+        .load_contact(sender.as_str())?
+        .and_then(|contact| contact.encryption_keys)
+    {
+        let keys = ProfilePublicKeys::from_bytes(&bytes).map_err(|e| {
+            SyncError::Identity(format!("Failed to parse encryption keys for {}: {}", sender, e))
+        })?;
+        return Ok(envelope.verify(&keys));
+    }
+    if let Some(pin) = storage.load_pinned_profile(sender.as_str())? {
+        if &pin.signed_profile.did() == sender {
+            return Ok(envelope.verify_signing_key(&pin.signed_profile.public_key));
+        }
+    }
+    Err(SyncError::ContactKeyExchangeIncomplete {
+        did: sender.to_string(),
+    })
+}
+
+/// Store a packet already checked by [`verify_packet`].
+///
+/// Once a sender verifies, any of their packets still flagged provisional
+/// are checked again.
+fn store_checked(
+    storage: &Storage,
+    mirror: &MirrorStore,
+    own_keys: Option<&ProfileKeys>,
+    envelope: &PacketEnvelope,
+    trust: PacketTrust,
+) -> Result<(), SyncError> {
+    match trust {
+        PacketTrust::Verified => {
+            mirror.store_packet(envelope)?;
+            reverify_provisional(storage, mirror, own_keys, Some(&envelope.sender))?;
+        }
+        PacketTrust::Provisional => {
+            mirror.store_provisional_packet(envelope)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct GateState {
+    max_packet_bytes: usize,
+    rate_limiter: PacketRateLimiter,
+    reputation: ReputationLedger,
+    oversized_payloads: HashMap<String, u64>,
+}
+
+/// Size limit, rate limit and trust ledger for incoming packets, shared by
+/// the engine and its background listeners.
+#[derive(Debug)]
+pub struct PacketGate {
+    state: Mutex<GateState>,
+    events: broadcast::Sender<SyncEvent>,
+}
+
+impl PacketGate {
+    /// Create a gate with the limits in `config`, announcing rate-limited
+    /// peers on `events`.
+    pub fn new(config: &GossipConfig, events: broadcast::Sender<SyncEvent>) -> Self {
+        Self {
+            state: Mutex::new(GateState {
+                max_packet_bytes: config.effective_max_packet_bytes(),
+                rate_limiter: config.packet_rate_limiter(),
+                reputation: ReputationLedger::new(),
+                oversized_payloads: HashMap::new(),
+            }),
+            events,
+        }
+    }
+
+    /// Apply the limits in `config`. Rate limit buckets start over.
+    pub fn configure(&self, config: &GossipConfig) {
+        let mut state = self.state.lock();
+        state.max_packet_bytes = config.effective_max_packet_bytes();
+        state.rate_limiter = config.packet_rate_limiter();
+    }
+
+    /// Check a received packet and store it in `mirror`.
+    ///
+    /// The signature is checked first, so that only a sender who really
+    /// sent an oversized packet or a flood is held to account for it.
+    ///
+    /// # Returns
+    ///
+    /// How the packet was stored, or `None` if it was dropped by the rate
+    /// limiter.
+    ///
+    /// # Errors
+    ///
+    /// `SyncError::SignatureInvalid` if the sender's keys are known and the
+    /// signature doesn't match them, or `SyncError::PayloadTooLarge` if the
+    /// packet is over the configured size limit.
+    pub fn admit(
+        &self,
+        storage: &Storage,
+        mirror: &MirrorStore,
+        own_keys: Option<&ProfileKeys>,
+        envelope: &PacketEnvelope,
+    ) -> Result<Option<PacketTrust>, SyncError> {
+        let trust = match verify_packet(storage, own_keys, envelope) {
+            Ok(true) => PacketTrust::Verified,
+            Ok(false) => {
+                // Anyone can claim any sender, so this counts against no one
+                warn!(
+                    sender = %envelope.sender,
+                    sequence = envelope.sequence,
+                    "Rejected packet with invalid signature"
+                );
+                return Err(SyncError::SignatureInvalid(format!(
+                    "Packet {} from {} failed signature verification",
+                    envelope.sequence, envelope.sender
+                )));
+            }
+            Err(SyncError::ContactKeyExchangeIncomplete { .. }) => PacketTrust::Provisional,
+            Err(e) => return Err(e),
+        };
+        let verified = trust == PacketTrust::Verified;
+        let sender = envelope.sender.as_str();
+
+        {
+            let mut state = self.state.lock();
+            let (size, limit) = (envelope.ciphertext.len(), state.max_packet_bytes);
+            if size > limit {
+                if verified {
+                    state.count_oversized(sender);
+                }
+                warn!(sender, size, limit, verified, "Rejected oversized packet");
+                return Err(SyncError::PayloadTooLarge { size, limit });
+            }
+
+            let bucket = if verified { sender } else { UNVERIFIED_SENDERS };
+            if let RateDecision::Dropped { first } = state.rate_limiter.check(bucket, Instant::now()) {
+                debug!(sender, sequence = envelope.sequence, verified, "Dropped packet from rate-limited sender");
+                if first {
+                    warn!(sender = bucket, "Peer exceeded packet rate limit");
+                    if verified {
+                        state
+                            .reputation
+                            .record_violation(sender, reputation::SEVERITY_RATE_LIMITED);
+                        let _ = self.events.send(SyncEvent::PeerRateLimited {
+                            did: sender.to_string(),
+                        });
+                    }
+                }
+                return Ok(None);
+            }
+        }
+
+        store_checked(storage, mirror, own_keys, envelope, trust)?;
+        Ok(Some(trust))
+    }
+
+    /// Reject a payload over `limit` bytes from `sender_did`, counting it
+    /// against them.
+    ///
+    /// Only for payloads whose sender is already authenticated, such as
+    /// realm changes opened from a signed envelope.
+    pub fn check_authenticated_size(
+        &self,
+        sender_did: &str,
+        size: usize,
+        limit: usize,
+    ) -> Result<(), SyncError> {
+        if size <= limit {
+            return Ok(());
+        }
+        self.state.lock().count_oversized(sender_did);
+        warn!(sender = %sender_did, size, limit, "Rejected oversized payload");
+        Err(SyncError::PayloadTooLarge { size, limit })
+    }
+
+    /// Number of oversized payloads rejected from `sender_did`.
+    pub fn oversized_payloads_from(&self, sender_did: &str) -> u64 {
+        self.state
+            .lock()
+            .oversized_payloads
+            .get(sender_did)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Number of packets from `sender_did` dropped by the rate limiter.
+    pub fn rate_violations(&self, sender_did: &str) -> u64 {
+        self.state.lock().rate_limiter.violations(sender_did)
+    }
+
+    /// Our trust in `did`, from 0.0 to 1.0.
+    pub fn trust(&self, did: &str) -> f64 {
+        self.state.lock().reputation.trust(did)
+    }
+
+    /// Count a violation we observed from `subject`.
+    pub fn record_violation(&self, subject: &str, severity: u8) {
+        self.state.lock().reputation.record_violation(subject, severity);
+    }
+
+    /// Fold in a violation `reporter` says it observed from `subject`,
+    /// returning the trust taken from the subject.
+    pub fn apply_report(&self, reporter: &str, subject: &str, severity: u8) -> f64 {
+        self.state.lock().reputation.apply_report(reporter, subject, severity)
+    }
+}
+
+impl GateState {
+    fn count_oversized(&mut self, sender_did: &str) {
+        if self.oversized_payloads.len() >= MAX_TRACKED_SENDERS
+            && !self.oversized_payloads.contains_key(sender_did)
+        {
+            let fewest = self
+                .oversized_payloads
+                .iter()
+                .min_by_key(|(_, count)| **count)
+                .map(|(sender, _)| sender.clone());
+            if let Some(sender) = fewest {
+                self.oversized_payloads.remove(&sender);
+            }
+        }
+        *self
+            .oversized_payloads
+            .entry(sender_did.to_string())
+            .or_default() += 1;
+        self.reputation
+            .record_violation(sender_did, reputation::SEVERITY_OVERSIZED_PAYLOAD);
+    }
+}
+
+/// Check provisional packets again, for one sender or all of them.
+///
+/// Packets whose sender we now hold keys for are confirmed if their
+/// signature matches and removed if it doesn't. Packets from senders we
+/// still can't check stay flagged.
+///
+/// # Returns
+///
+/// The number of packets (confirmed, removed).
+pub fn reverify_provisional(
+    storage: &Storage,
+    mirror: &MirrorStore,
+    own_keys: Option<&ProfileKeys>,
+    sender: Option<&Did>,
+) -> Result<(usize, usize), SyncError> {
+    let (mut confirmed, mut removed) = (0, 0);
+    for (did, device, sequence) in mirror.provisional_packets()? {
+        if sender.is_some_and(|sender| sender != &did) {
+            continue;
+        }
+        let Some(envelope) = mirror.get_device_packet(&did, &device, sequence)? else {
+            mirror.confirm_packet(&did, &device, sequence)?;
+            continue;
+        };
+        match verify_packet(storage, own_keys, &envelope) {
+            Ok(true) => {
+                mirror.confirm_packet(&did, &device, sequence)?;
+                confirmed += 1;
+                debug!(sender = %did, sequence, "Confirmed provisional packet");
+            }
+            Ok(false) => {
+                mirror.remove_packet(&did, &device, sequence)?;
+                removed += 1;
+                warn!(sender = %did, sequence, "Removed provisional packet with forged signature");
+            }
+            Err(SyncError::ContactKeyExchangeIncomplete { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((confirmed, removed))
+}
+
+/// Settle `did`'s provisional packets after their keys were saved, e.g. on
+/// accepting them as a contact. Failures are logged, not returned, since the
+/// packets are checked again when the next one from `did` verifies.
+pub fn reverify_sender(storage: &Storage, did: &str) {
+    let result = Did::parse(did).and_then(|did| {
+        let mirror = MirrorStore::new(storage.backend());
+        let own_keys = storage.load_profile_keys()?;
+        reverify_provisional(storage, &mirror, own_keys.as_ref(), Some(&did))
+    });
+    if let Err(e) = result {
+        warn!(sender = %did, error = %e, "Failed to re-verify provisional packets");
+    }
+}
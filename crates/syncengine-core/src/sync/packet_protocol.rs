@@ -13,8 +13,8 @@
 //!   |                               |
 //!   |    (if B is behind A)         |
 //!   |                               |
-//!   |<-- LogRequest {did, from} ----|  (request missing packets)
-//!   |--- LogEntries {did, packets}->|  (send requested packets)
+//!   |<-- LogRequest {did, since} ---|  (request missing packets)
+//!   |--- LogResponse {did, packets}>|  (send requested packets)
 //!   |                               |
 //!   |    (single packet broadcast)  |
 //!   |                               |
@@ -26,9 +26,13 @@
 //! | Message | Purpose |
 //! |---------|---------|
 //! | LogHead | Announce current log state (sequence + hash) |
-//! | LogRequest | Request packets from a specific sequence |
-//! | LogEntries | Response with multiple packets |
+//! | LogRequest | Request packets after a specific sequence |
+//! | LogResponse | Response with multiple packets |
 //! | Packet | Single packet broadcast |
+//!
+//! Contacts run the `LogRequest`/`LogResponse` exchange whenever their 1:1
+//! topic comes back up, so packets sent while one side was offline are
+//! fetched from the other's log (carried as `ProfileGossipMessage::LogSync`).
 
 use crate::identity::Did;
 use crate::profile::PacketEnvelope;
@@ -51,20 +55,21 @@ pub enum PacketSyncMessage {
         hash: [u8; 32],
     },
 
-    /// Request packets from a specific sequence.
+    /// Request packets after a specific sequence.
     ///
     /// Sent when a peer is behind and needs to catch up.
     LogRequest {
         /// Profile DID to request packets for
         did: Did,
-        /// Starting sequence (exclusive - request packets AFTER this)
-        from_sequence: u64,
+        /// Highest sequence the requester already has (exclusive - request
+        /// packets AFTER this), or `None` to request the whole log
+        since_sequence: Option<u64>,
     },
 
     /// Response with multiple packets.
     ///
-    /// Response to LogRequest containing the requested packets.
-    LogEntries {
+    /// Response to LogRequest containing the requested packets, in sequence order.
+    LogResponse {
         /// Profile DID these packets belong to
         did: Did,
         /// Serialized PacketEnvelopes
@@ -124,6 +129,20 @@ impl PacketSyncMessage {
         }
     }
 
+    /// Create a LogRequest for packets after `since_sequence`.
+    pub fn log_request(did: Did, since_sequence: Option<u64>) -> Self {
+        PacketSyncMessage::LogRequest { did, since_sequence }
+    }
+
+    /// Create a LogResponse carrying the given envelopes.
+    pub fn log_response(did: Did, envelopes: &[PacketEnvelope]) -> Result<Self, postcard::Error> {
+        let entries = envelopes
+            .iter()
+            .map(postcard::to_allocvec)
+            .collect::<Result<_, _>>()?;
+        Ok(PacketSyncMessage::LogResponse { did, entries })
+    }
+
     /// Create a Packet message from an envelope.
     pub fn packet_from_envelope(envelope: &PacketEnvelope) -> Result<Self, postcard::Error> {
         Ok(PacketSyncMessage::Packet {
@@ -146,14 +165,27 @@ impl PacketSyncMessage {
         matches!(self, PacketSyncMessage::LogHead { .. })
     }
 
+    /// Extract envelopes from a LogResponse message.
+    pub fn extract_entries(&self) -> Option<Result<Vec<PacketEnvelope>, postcard::Error>> {
+        match self {
+            PacketSyncMessage::LogResponse { entries, .. } => Some(
+                entries
+                    .iter()
+                    .map(|entry| postcard::from_bytes(entry))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
     /// Check if this is a LogRequest message.
     pub fn is_log_request(&self) -> bool {
         matches!(self, PacketSyncMessage::LogRequest { .. })
     }
 
-    /// Check if this is a LogEntries message.
-    pub fn is_log_entries(&self) -> bool {
-        matches!(self, PacketSyncMessage::LogEntries { .. })
+    /// Check if this is a LogResponse message.
+    pub fn is_log_response(&self) -> bool {
+        matches!(self, PacketSyncMessage::LogResponse { .. })
     }
 
     /// Check if this is a Packet message.
@@ -176,7 +208,7 @@ impl PacketSyncMessage {
         match self {
             PacketSyncMessage::LogHead { did, .. } => Some(did),
             PacketSyncMessage::LogRequest { did, .. } => Some(did),
-            PacketSyncMessage::LogEntries { did, .. } => Some(did),
+            PacketSyncMessage::LogResponse { did, .. } => Some(did),
             PacketSyncMessage::Receipt { sender, .. } => Some(sender),
             PacketSyncMessage::Depin { did, .. } => Some(did),
             PacketSyncMessage::Packet { envelope } => {
@@ -338,7 +370,7 @@ mod tests {
         let keys = ProfileKeys::generate();
         let msg = PacketSyncMessage::LogRequest {
             did: keys.did(),
-            from_sequence: 10,
+            since_sequence: Some(10),
         };
 
         let encoded = msg.encode().expect("Should encode");
@@ -348,12 +380,12 @@ mod tests {
     }
 
     #[test]
-    fn test_log_entries_encode_decode() {
+    fn test_log_response_encode_decode() {
         let keys = ProfileKeys::generate();
         let envelope1 = create_test_envelope(&keys, 0);
         let envelope2 = create_test_envelope(&keys, 1);
 
-        let msg = PacketSyncMessage::LogEntries {
+        let msg = PacketSyncMessage::LogResponse {
             did: keys.did(),
             entries: vec![
                 postcard::to_allocvec(&envelope1).unwrap(),
@@ -365,7 +397,7 @@ mod tests {
         let decoded = PacketSyncMessage::decode(&encoded).expect("Should decode");
 
         match decoded {
-            PacketSyncMessage::LogEntries { did, entries } => {
+            PacketSyncMessage::LogResponse { did, entries } => {
                 assert_eq!(did, keys.did());
                 assert_eq!(entries.len(), 2);
             }
@@ -373,6 +405,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_log_response_roundtrips_envelopes_in_order() {
+        let keys = ProfileKeys::generate();
+        let envelopes: Vec<_> = (3..6).map(|seq| create_test_envelope(&keys, seq)).collect();
+
+        let msg = PacketSyncMessage::log_response(keys.did(), &envelopes).expect("Should build response");
+        let decoded = PacketSyncMessage::decode(&msg.encode().unwrap()).unwrap();
+
+        let entries = decoded.extract_entries().expect("Should be a response").expect("Should decode");
+        let sequences: Vec<_> = entries.iter().map(|e| e.sequence).collect();
+        assert_eq!(sequences, [3, 4, 5]);
+        assert!(PacketSyncMessage::log_request(keys.did(), Some(2)).extract_entries().is_none());
+    }
+
     #[test]
     fn test_packet_from_envelope() {
         let keys = ProfileKeys::generate();
@@ -495,16 +541,16 @@ mod tests {
 
         let log_request = PacketSyncMessage::LogRequest {
             did: keys.did(),
-            from_sequence: 0,
+            since_sequence: None,
         };
         assert!(log_request.is_log_request());
         assert!(!log_request.is_log_head());
 
-        let log_entries = PacketSyncMessage::LogEntries {
+        let log_response = PacketSyncMessage::LogResponse {
             did: keys.did(),
             entries: vec![],
         };
-        assert!(log_entries.is_log_entries());
+        assert!(log_response.is_log_response());
 
         let packet = PacketSyncMessage::Packet {
             envelope: vec![],
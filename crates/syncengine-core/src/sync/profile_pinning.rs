@@ -10,17 +10,21 @@
 //! - `Announce`: Broadcast when profile is updated (includes avatar ticket)
 //! - `Request`: Ask for a specific profile by DID
 //! - `Response`: Reply to a request with the signed profile
+//! - `Devices`: The signed list of devices an identity is used on
 //!
 //! # Topic Structure
 //!
 //! All profile messages use a single global topic derived from a fixed seed.
 //! This allows any node to discover profile announcements from the network.
 
+use std::time::{Duration, Instant};
+
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
-use crate::identity::Did;
+use crate::identity::{Did, SignedDeviceList};
 use crate::profile::PacketEnvelope;
+use crate::sync::PacketSyncMessage;
 use crate::types::SignedProfile;
 
 /// The seed used to derive the global profile topic ID
@@ -29,6 +33,12 @@ const PROFILE_TOPIC_SEED: &[u8] = b"syncengine:profiles:v1";
 /// The domain separator for per-peer profile topics
 const PROFILE_TOPIC_DOMAIN: &[u8] = b"sync-profile";
 
+/// Minimum time between automatic profile re-announcements.
+///
+/// New neighbors on the profile topic trigger a re-announce of our profile;
+/// a burst of joins inside this window is answered with a single trailing one.
+pub const PROFILE_REANNOUNCE_WINDOW: Duration = Duration::from_secs(5);
+
 /// Get the global profile topic ID.
 ///
 /// All profile announcements and requests use this single topic.
@@ -144,6 +154,24 @@ pub enum ProfileGossipMessage {
         /// Signature over (sender_did || contact_dids || timestamp) for authenticity
         signature: Vec<u8>,
     },
+
+    /// Packet log catch-up between contacts
+    ///
+    /// Carries a `PacketSyncMessage::LogRequest` sent when a contact topic
+    /// comes up, and the `LogResponse` with the packets the requester missed.
+    LogSync {
+        /// The log sync request or response
+        message: PacketSyncMessage,
+    },
+
+    /// The devices an identity is used on
+    ///
+    /// Sent to contacts when a device is linked and when a contact topic
+    /// comes up, so they accept packets from, and deliver to, every device.
+    Devices {
+        /// The device list, signed by the identity it belongs to
+        list: SignedDeviceList,
+    },
 }
 
 impl ProfileGossipMessage {
@@ -198,6 +226,16 @@ impl ProfileGossipMessage {
         }
     }
 
+    /// Create a log sync message wrapping a packet log request or response.
+    pub fn log_sync(message: PacketSyncMessage) -> Self {
+        Self::LogSync { message }
+    }
+
+    /// Create a message publishing an identity's device list.
+    pub fn devices(list: SignedDeviceList) -> Self {
+        Self::Devices { list }
+    }
+
     /// Serialize the message to bytes.
     pub fn to_bytes(&self) -> Result<Vec<u8>, crate::SyncError> {
         postcard::to_allocvec(self).map_err(|e| crate::SyncError::Serialization(e.to_string()))
@@ -208,12 +246,13 @@ impl ProfileGossipMessage {
         postcard::from_bytes(bytes).map_err(|e| crate::SyncError::Serialization(e.to_string()))
     }
 
-    /// Get the DID of the signer if this is an Announce, Packet, or MeshUpdate message.
+    /// Get the DID of the signer if this is an Announce, Packet, MeshUpdate, or Devices message.
     pub fn signer_did(&self) -> Option<Did> {
         match self {
             Self::Announce { signed_profile, .. } => Some(signed_profile.did()),
             Self::Packet { envelope } => Some(envelope.sender.clone()),
             Self::MeshUpdate { sender_did, .. } => Did::parse(sender_did).ok(),
+            Self::Devices { list } => Some(list.list.did.clone()),
             _ => None,
         }
     }
@@ -225,6 +264,8 @@ impl ProfileGossipMessage {
     /// - Response: Only if we're the requester
     /// - Packet: Always relevant (we might mirror the sender's log)
     /// - MeshUpdate: Always relevant (we update our mutual_peers if applicable)
+    /// - LogSync: Always relevant (contact topics are 1:1)
+    /// - Devices: Always relevant (we deliver to every device of a contact)
     pub fn is_relevant_to(&self, our_did: &str) -> bool {
         match self {
             Self::Announce { .. } => true,  // Always process announcements
@@ -232,6 +273,8 @@ impl ProfileGossipMessage {
             Self::Response { requester_did, .. } => requester_did == our_did,
             Self::Packet { .. } => true,    // Always process packets (mirror if from contact)
             Self::MeshUpdate { .. } => true, // Always process mesh updates (update mutual_peers)
+            Self::LogSync { .. } => true,   // Contact topics only carry our own catch-up
+            Self::Devices { .. } => true,   // A contact, or ourselves, on another device
         }
     }
 }
@@ -368,6 +411,18 @@ impl ProfileMessageHandler {
                 );
                 ProfileAction::Ignore
             }
+
+            ProfileGossipMessage::LogSync { .. } => {
+                // Log catch-up runs on contact topics (see contact_manager.rs)
+                debug!("LogSync message (handled by contact_manager)");
+                ProfileAction::Ignore
+            }
+
+            ProfileGossipMessage::Devices { list } => {
+                // Device lists are exchanged on contact topics (see contact_manager.rs)
+                debug!(did = %list.list.did, "Devices message (handled by contact_manager)");
+                ProfileAction::Ignore
+            }
         }
     }
 }
@@ -397,6 +452,68 @@ pub enum ProfileAction {
     },
 }
 
+/// Debounces profile re-announcements triggered by neighbor joins.
+///
+/// The first join after a quiet period announces immediately. Joins that
+/// arrive while the window is still open are coalesced into one announcement
+/// when it closes, so the newest neighbors are never left without our profile.
+#[derive(Debug, Clone)]
+pub struct AnnounceDebouncer {
+    window: Duration,
+    last_announce: Option<Instant>,
+    pending: bool,
+}
+
+impl AnnounceDebouncer {
+    /// Create a debouncer allowing at most one announcement per `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_announce: None,
+            pending: false,
+        }
+    }
+
+    /// Record a neighbor joining at `now`.
+    ///
+    /// Returns `true` if we should announce right away; otherwise the
+    /// announcement is deferred until [`deadline`](Self::deadline).
+    pub fn neighbor_up(&mut self, now: Instant) -> bool {
+        match self.last_announce {
+            Some(last) if now.duration_since(last) < self.window => {
+                self.pending = true;
+                false
+            }
+            _ => {
+                self.last_announce = Some(now);
+                self.pending = false;
+                true
+            }
+        }
+    }
+
+    /// When the deferred announcement is due, if one is pending.
+    pub fn deadline(&self) -> Option<Instant> {
+        match (self.pending, self.last_announce) {
+            (true, Some(last)) => Some(last + self.window),
+            _ => None,
+        }
+    }
+
+    /// Check the deferred announcement at `now`.
+    ///
+    /// Returns `true` (and starts a new window) if it is due.
+    pub fn poll_deadline(&mut self, now: Instant) -> bool {
+        if self.deadline().is_some_and(|deadline| now >= deadline) {
+            self.last_announce = Some(now);
+            self.pending = false;
+            true
+        } else {
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -633,4 +750,29 @@ mod tests {
         assert!(matches!(action, ProfileAction::Ignore));
     }
 
+
+    #[test]
+    fn test_announce_debouncer_coalesces_neighbor_bursts() {
+        let window = Duration::from_secs(5);
+        let mut debouncer = AnnounceDebouncer::new(window);
+        let start = Instant::now();
+
+        // First join announces immediately, nothing pending
+        assert!(debouncer.neighbor_up(start));
+        assert_eq!(debouncer.deadline(), None);
+
+        // A burst inside the window is deferred to a single trailing announce
+        for ms in [100, 200, 3_000] {
+            assert!(!debouncer.neighbor_up(start + Duration::from_millis(ms)));
+        }
+        assert_eq!(debouncer.deadline(), Some(start + window));
+        assert!(!debouncer.poll_deadline(start + Duration::from_secs(4)));
+        assert!(debouncer.poll_deadline(start + window));
+        assert!(!debouncer.poll_deadline(start + window));
+        assert_eq!(debouncer.deadline(), None);
+
+        // The trailing announce opens a new window
+        assert!(!debouncer.neighbor_up(start + window + Duration::from_secs(1)));
+        assert!(debouncer.neighbor_up(start + window * 3));
+    }
 }
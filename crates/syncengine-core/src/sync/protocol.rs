@@ -6,10 +6,12 @@
 //!
 //! The sync protocol enables Automerge document synchronization over gossip:
 //!
-//! 1. **Announce**: Nodes periodically announce their document heads
+//! 1. **Announce**: Nodes periodically announce their document heads and clock
 //! 2. **SyncRequest**: When heads differ, request full document sync
 //! 3. **SyncResponse**: Return full document state
 //! 4. **Changes**: Broadcast incremental changes as they happen
+//! 5. **Leave**: Tell peers we are leaving the realm
+//! 6. **Roles**: Share the owner-signed role table
 //!
 //! ## Message Flow
 //!
@@ -33,7 +35,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::invite::NodeAddrBytes;
-use crate::RealmId;
+use crate::{RealmId, RealmRoles};
 
 /// Messages sent over gossip for document sync
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +54,10 @@ pub enum SyncMessage {
         /// This allows receivers to add the sender to their address book
         /// for bidirectional communication.
         sender_addr: Option<NodeAddrBytes>,
+        /// Sender's wall clock when the announce was sent (Unix milliseconds)
+        ///
+        /// Receivers compare this with their own clock to estimate skew.
+        sent_at_ms: i64,
     },
 
     /// Request full document sync
@@ -82,6 +88,41 @@ pub enum SyncMessage {
         /// Automerge incremental save data (via `doc.save_after(&heads)`)
         data: Vec<u8>,
     },
+
+    /// Sender is leaving the realm
+    ///
+    /// Broadcast once before a node unsubscribes so peers can drop it from
+    /// their membership roster. The leaving member is identified by the
+    /// envelope's authenticated sender, not by a field in the message.
+    Leave {
+        /// The realm being left
+        realm_id: RealmId,
+    },
+
+    /// Current role table for the realm
+    ///
+    /// Sent by owners when they change a role, and by any member when a peer
+    /// connects. Receivers verify the owner's signature inside the table, so
+    /// it doesn't matter which member forwards it.
+    Roles {
+        /// The realm the roles apply to
+        realm_id: RealmId,
+        /// The signed role table
+        roles: RealmRoles,
+    },
+
+    /// Full document state after a history compaction
+    ///
+    /// Sent in place of `SyncResponse` once the realm has been compacted.
+    /// A compacted document shares no history with older copies of the
+    /// realm, so merging it as-is would conflict with them. Nodes that
+    /// predate compaction can't decode this variant and drop it instead.
+    CompactedDocument {
+        /// The realm this document belongs to
+        realm_id: RealmId,
+        /// Full Automerge document bytes (via `doc.save()`)
+        document: Vec<u8>,
+    },
 }
 
 impl SyncMessage {
@@ -102,6 +143,25 @@ impl SyncMessage {
             SyncMessage::SyncRequest { realm_id } => realm_id,
             SyncMessage::SyncResponse { realm_id, .. } => realm_id,
             SyncMessage::Changes { realm_id, .. } => realm_id,
+            SyncMessage::Leave { realm_id } => realm_id,
+            SyncMessage::Roles { realm_id, .. } => realm_id,
+            SyncMessage::CompactedDocument { realm_id, .. } => realm_id,
+        }
+    }
+
+    /// Short name of the message variant (e.g., "announce", "sync_request")
+    ///
+    /// Logged as the `sync_event` field so log tooling can reconstruct the
+    /// sync exchange without decoding payloads.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SyncMessage::Announce { .. } => "announce",
+            SyncMessage::SyncRequest { .. } => "sync_request",
+            SyncMessage::SyncResponse { .. } => "sync_response",
+            SyncMessage::Changes { .. } => "changes",
+            SyncMessage::Leave { .. } => "leave",
+            SyncMessage::Roles { .. } => "roles",
+            SyncMessage::CompactedDocument { .. } => "compacted_document",
         }
     }
 
@@ -120,10 +180,23 @@ impl SyncMessage {
         matches!(self, SyncMessage::SyncResponse { .. })
     }
 
+    /// Check if this carries a full document, compacted or not
+    pub fn is_full_document(&self) -> bool {
+        matches!(
+            self,
+            SyncMessage::SyncResponse { .. } | SyncMessage::CompactedDocument { .. }
+        )
+    }
+
     /// Check if this is a changes message
     pub fn is_changes(&self) -> bool {
         matches!(self, SyncMessage::Changes { .. })
     }
+
+    /// Check if this is a leave message
+    pub fn is_leave(&self) -> bool {
+        matches!(self, SyncMessage::Leave { .. })
+    }
 }
 
 /// Wrapper for versioned messages (future-proofing)
@@ -228,6 +301,7 @@ mod tests {
             realm_id,
             heads: heads.clone(),
             sender_addr: None,
+            sent_at_ms: 0,
         };
 
         let encoded = msg.encode().unwrap();
@@ -280,6 +354,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_compacted_document_is_refused_by_older_decoders() {
+        /// `SyncMessage` as it was before compaction existed
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        enum PreCompactionMessage {
+            Announce {
+                realm_id: RealmId,
+                heads: Vec<Vec<u8>>,
+                sender_addr: Option<NodeAddrBytes>,
+                sent_at_ms: i64,
+            },
+            SyncRequest {
+                realm_id: RealmId,
+            },
+            SyncResponse {
+                realm_id: RealmId,
+                document: Vec<u8>,
+            },
+            Changes {
+                realm_id: RealmId,
+                data: Vec<u8>,
+            },
+            Leave {
+                realm_id: RealmId,
+            },
+            Roles {
+                realm_id: RealmId,
+                roles: RealmRoles,
+            },
+        }
+
+        let realm_id = RealmId::new();
+        let response = SyncMessage::SyncResponse {
+            realm_id: realm_id.clone(),
+            document: vec![1, 2, 3],
+        };
+        let compacted = SyncMessage::CompactedDocument {
+            realm_id: realm_id.clone(),
+            document: vec![1, 2, 3],
+        };
+
+        let encoded = compacted.encode().unwrap();
+        match SyncMessage::decode(&encoded).unwrap() {
+            SyncMessage::CompactedDocument {
+                realm_id: rid,
+                document,
+            } => {
+                assert_eq!(rid.0, realm_id.0);
+                assert_eq!(document, vec![1, 2, 3]);
+            }
+            _ => panic!("Wrong message type"),
+        }
+        assert!(compacted.is_full_document());
+        assert!(!compacted.is_sync_response());
+        assert_eq!(compacted.kind(), "compacted_document");
+
+        assert!(postcard::from_bytes::<PreCompactionMessage>(&response.encode().unwrap()).is_ok());
+        assert!(postcard::from_bytes::<PreCompactionMessage>(&encoded).is_err());
+    }
+
     #[test]
     fn test_message_type_checks() {
         let realm_id = RealmId::new();
@@ -288,6 +423,7 @@ mod tests {
             realm_id: realm_id.clone(),
             heads: vec![],
             sender_addr: None,
+            sent_at_ms: 0,
         };
         assert!(announce.is_announce());
         assert!(!announce.is_sync_request());
@@ -307,10 +443,20 @@ mod tests {
         assert!(response.is_sync_response());
 
         let changes = SyncMessage::Changes {
-            realm_id,
+            realm_id: realm_id.clone(),
             data: vec![],
         };
         assert!(changes.is_changes());
+
+        let leave = SyncMessage::Leave { realm_id };
+        assert!(leave.is_leave());
+        assert!(!leave.is_changes());
+
+        assert_eq!(announce.kind(), "announce");
+        assert_eq!(request.kind(), "sync_request");
+        assert_eq!(response.kind(), "sync_response");
+        assert_eq!(changes.kind(), "changes");
+        assert_eq!(leave.kind(), "leave");
     }
 
     #[test]
@@ -320,6 +466,7 @@ mod tests {
             realm_id,
             heads: vec![],
             sender_addr: None,
+            sent_at_ms: 0,
         };
         let wire = WireMessage::new(msg);
 
@@ -333,6 +480,7 @@ mod tests {
             realm_id,
             heads: vec![],
             sender_addr: None,
+            sent_at_ms: 0,
         };
 
         let encoded = msg.encode().unwrap();
@@ -0,0 +1,201 @@
+//! Per-sender rate limiting for incoming profile packets
+//!
+//! Each sender DID gets a token bucket: it holds up to `burst` tokens,
+//! refills at `rate_per_sec`, and every accepted packet spends one. A sender
+//! that runs dry has its packets dropped until the bucket refills, so one
+//! spamming peer can't flood the mirror store while everyone else is
+//! unaffected.
+//!
+//! At most [`MAX_TRACKED_SENDERS`] buckets are kept; past that, the bucket
+//! refilled longest ago is dropped, and that sender starts over with a full
+//! bucket.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Most senders the limiter keeps a bucket for
+pub const MAX_TRACKED_SENDERS: usize = 4096;
+
+/// Outcome of offering a packet to the [`PacketRateLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    /// Within the sender's budget
+    Allowed,
+    /// Over budget; `first` is set for the first drop since the sender was
+    /// last allowed through, so callers can report each flood once
+    Dropped {
+        /// Whether this drop starts a new run of drops
+        first: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+    limited: bool,
+    violations: u64,
+}
+
+/// Token buckets for incoming packets, keyed by sender DID.
+#[derive(Debug, Clone)]
+pub struct PacketRateLimiter {
+    rate_per_sec: f64,
+    burst: f64,
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl PacketRateLimiter {
+    /// Create a limiter allowing `burst` packets at once and `rate_per_sec`
+    /// sustained, per sender.
+    pub fn new(rate_per_sec: u32, burst: u32) -> Self {
+        Self {
+            rate_per_sec: f64::from(rate_per_sec),
+            burst: f64::from(burst),
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Offer a packet from `sender` arriving at `now`.
+    pub fn check(&mut self, sender: &str, now: Instant) -> RateDecision {
+        if self.buckets.len() >= MAX_TRACKED_SENDERS && !self.buckets.contains_key(sender) {
+            self.evict_stalest();
+        }
+        let bucket = self
+            .buckets
+            .entry(sender.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: self.burst,
+                last_refill: now,
+                limited: false,
+                violations: 0,
+            });
+
+        let elapsed = now
+            .saturating_duration_since(bucket.last_refill)
+            .as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate_per_sec).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.limited = false;
+            RateDecision::Allowed
+        } else {
+            bucket.violations += 1;
+            let first = !bucket.limited;
+            bucket.limited = true;
+            RateDecision::Dropped { first }
+        }
+    }
+
+    /// Number of packets dropped from `sender` since its bucket was created.
+    pub fn violations(&self, sender: &str) -> u64 {
+        self.buckets
+            .get(sender)
+            .map_or(0, |bucket| bucket.violations)
+    }
+
+    /// Number of senders with a bucket.
+    pub fn tracked_senders(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn evict_stalest(&mut self) {
+        let stalest = self
+            .buckets
+            .iter()
+            .min_by_key(|(_, bucket)| bucket.last_refill)
+            .map(|(sender, _)| sender.clone());
+        if let Some(sender) = stalest {
+            self.buckets.remove(&sender);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let mut limiter = PacketRateLimiter::new(2, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(
+                limiter.check("did:sync:zSpam", start),
+                RateDecision::Allowed
+            );
+        }
+        assert_eq!(
+            limiter.check("did:sync:zSpam", start),
+            RateDecision::Dropped { first: true }
+        );
+        assert_eq!(
+            limiter.check("did:sync:zSpam", start),
+            RateDecision::Dropped { first: false }
+        );
+        assert_eq!(limiter.violations("did:sync:zSpam"), 2);
+
+        // Other senders have their own bucket
+        assert_eq!(
+            limiter.check("did:sync:zCalm", start),
+            RateDecision::Allowed
+        );
+
+        // Half a second at 2/s buys one more packet, then the next flood is reported anew
+        let later = start + Duration::from_millis(500);
+        assert_eq!(
+            limiter.check("did:sync:zSpam", later),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check("did:sync:zSpam", later),
+            RateDecision::Dropped { first: true }
+        );
+
+        // Refill never exceeds the burst
+        let much_later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(
+                limiter.check("did:sync:zSpam", much_later),
+                RateDecision::Allowed
+            );
+        }
+        assert!(matches!(
+            limiter.check("did:sync:zSpam", much_later),
+            RateDecision::Dropped { .. }
+        ));
+        assert_eq!(limiter.violations("did:sync:zCalm"), 0);
+    }
+
+    #[test]
+    fn test_stalest_bucket_evicted_when_full() {
+        let mut limiter = PacketRateLimiter::new(1, 1);
+        let start = Instant::now();
+        let at = |i: usize| start + Duration::from_millis(i as u64);
+
+        // Every sender spends its one token, then sends one more
+        for i in 0..MAX_TRACKED_SENDERS {
+            limiter.check(&format!("did:sync:z{i}"), at(i));
+        }
+        for i in 0..2 {
+            assert_eq!(
+                limiter.check(&format!("did:sync:z{i}"), at(i)),
+                RateDecision::Dropped { first: true }
+            );
+        }
+        assert_eq!(limiter.tracked_senders(), MAX_TRACKED_SENDERS);
+
+        // A new sender pushes out the one refilled longest ago
+        assert_eq!(
+            limiter.check("did:sync:zNew", at(MAX_TRACKED_SENDERS)),
+            RateDecision::Allowed
+        );
+        assert_eq!(limiter.tracked_senders(), MAX_TRACKED_SENDERS);
+        assert_eq!(limiter.violations("did:sync:z0"), 0);
+        assert_eq!(limiter.violations("did:sync:z1"), 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! Trust scores for peers, from our own observations and their reports
+//!
+//! Every peer starts at a neutral trust of 0.5. A violation we observe
+//! ourselves costs `severity * TRUST_PENALTY_PER_SEVERITY`. Violations other
+//! peers report in a `PacketPayload::ReputationReport` cost the same, scaled
+//! by how much we trust the reporter and discounted by [`REPORT_DISCOUNT`].
+//!
+//! Reports are cheap to send, so they are bounded to stop a hostile peer
+//! from talking others out of the network: reporters we distrust are
+//! ignored, each reporter can cost a subject at most
+//! [`MAX_PENALTY_PER_REPORTER`], and reports together never push a subject
+//! below [`REPORTED_TRUST_FLOOR`]. Only our own observations can.
+//!
+//! The ledger keeps standings for at most [`MAX_TRACKED_PEERS`] peers,
+//! dropping the one with the least trust lost when full, and listens to at
+//! most [`MAX_REPORTERS_PER_PEER`] reporters about any one peer.
+
+use std::collections::HashMap;
+
+/// Trust every peer starts with
+pub const NEUTRAL_TRUST: f64 = 0.5;
+
+/// Trust lost per point of violation severity (1-10)
+pub const TRUST_PENALTY_PER_SEVERITY: f64 = 0.02;
+
+/// Weight of a report from a fully trusted reporter, relative to our own observation
+pub const REPORT_DISCOUNT: f64 = 0.5;
+
+/// Reporters below this trust are not listened to
+pub const MIN_REPORTER_TRUST: f64 = 0.3;
+
+/// Most trust a single reporter can cost a subject
+pub const MAX_PENALTY_PER_REPORTER: f64 = 0.1;
+
+/// Lowest trust reports alone can bring a subject to
+pub const REPORTED_TRUST_FLOOR: f64 = 0.25;
+
+/// Most peers the ledger keeps a standing for
+pub const MAX_TRACKED_PEERS: usize = 4096;
+
+/// Most reporters counted against a single peer
+pub const MAX_REPORTERS_PER_PEER: usize = 64;
+
+/// Severity of a sender exceeding the packet rate limit
+pub const SEVERITY_RATE_LIMITED: u8 = 4;
+
+/// Severity of a packet that fails signature verification
+pub const SEVERITY_INVALID_SIGNATURE: u8 = 7;
+
+/// Severity of a payload over the size limit
+pub const SEVERITY_OVERSIZED_PAYLOAD: u8 = 9;
+
+#[derive(Debug, Clone, Default)]
+struct PeerStanding {
+    /// Trust lost to violations we saw ourselves
+    observed_penalty: f64,
+    /// Trust lost to reports, per reporter DID
+    reported_penalty: HashMap<String, f64>,
+}
+
+impl PeerStanding {
+    fn total_penalty(&self) -> f64 {
+        self.observed_penalty + self.reported_penalty.values().sum::<f64>()
+    }
+}
+
+/// Trust scores for peers, keyed by DID.
+#[derive(Debug, Clone, Default)]
+pub struct ReputationLedger {
+    peers: HashMap<String, PeerStanding>,
+}
+
+impl ReputationLedger {
+    /// Create an empty ledger where every peer has neutral trust.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a violation we observed from `subject`.
+    pub fn record_violation(&mut self, subject: &str, severity: u8) {
+        let standing = self.standing_mut(subject);
+        standing.observed_penalty += penalty_for(severity);
+    }
+
+    /// Fold in a violation `reporter` says it observed from `subject`.
+    ///
+    /// Returns the trust actually taken from the subject, which is zero when
+    /// the report is ignored or the reporter has used up its allowance.
+    pub fn apply_report(&mut self, reporter: &str, subject: &str, severity: u8) -> f64 {
+        if reporter == subject {
+            return 0.0;
+        }
+        let reporter_trust = self.trust(reporter);
+        if reporter_trust < MIN_REPORTER_TRUST {
+            return 0.0;
+        }
+
+        let standing = self.standing_mut(subject);
+        if standing.reported_penalty.len() >= MAX_REPORTERS_PER_PEER
+            && !standing.reported_penalty.contains_key(reporter)
+        {
+            return 0.0;
+        }
+        let so_far = standing.reported_penalty.entry(reporter.to_string()).or_default();
+        let penalty = (penalty_for(severity) * reporter_trust * REPORT_DISCOUNT)
+            .min(MAX_PENALTY_PER_REPORTER - *so_far)
+            .max(0.0);
+        *so_far += penalty;
+        penalty
+    }
+
+    /// Current trust in `did`, from 0.0 to 1.0.
+    pub fn trust(&self, did: &str) -> f64 {
+        let Some(standing) = self.peers.get(did) else {
+            return NEUTRAL_TRUST;
+        };
+        let reported: f64 = standing.reported_penalty.values().sum();
+        let reported = reported.min(NEUTRAL_TRUST - REPORTED_TRUST_FLOOR);
+        (NEUTRAL_TRUST - reported - standing.observed_penalty).clamp(0.0, 1.0)
+    }
+
+    /// Number of peers with a standing.
+    pub fn tracked_peers(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Standing of `did`, making room for it if the ledger is full.
+    fn standing_mut(&mut self, did: &str) -> &mut PeerStanding {
+        if self.peers.len() >= MAX_TRACKED_PEERS && !self.peers.contains_key(did) {
+            let mildest = self
+                .peers
+                .iter()
+                .min_by(|(_, a), (_, b)| a.total_penalty().total_cmp(&b.total_penalty()))
+                .map(|(peer, _)| peer.clone());
+            if let Some(peer) = mildest {
+                self.peers.remove(&peer);
+            }
+        }
+        self.peers.entry(did.to_string()).or_default()
+    }
+}
+
+fn penalty_for(severity: u8) -> f64 {
+    f64::from(severity.clamp(1, 10)) * TRUST_PENALTY_PER_SEVERITY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_are_discounted_and_bounded() {
+        let mut ledger = ReputationLedger::new();
+        assert_eq!(ledger.trust("did:sync:zMallory"), NEUTRAL_TRUST);
+
+        // Our own observation counts in full
+        ledger.record_violation("did:sync:zMallory", 5);
+        assert!((ledger.trust("did:sync:zMallory") - 0.4).abs() < 1e-9);
+
+        // A neutral reporter's word counts for a quarter as much
+        let taken = ledger.apply_report("did:sync:zLove", "did:sync:zSpam", 10);
+        assert!((taken - 0.05).abs() < 1e-9);
+        assert!((ledger.trust("did:sync:zSpam") - 0.45).abs() < 1e-9);
+
+        // One reporter repeating itself runs out of allowance
+        for _ in 0..10 {
+            ledger.apply_report("did:sync:zLove", "did:sync:zSpam", 10);
+        }
+        assert!((ledger.trust("did:sync:zSpam") - 0.4).abs() < 1e-9);
+
+        // Many reporters together can't push below the floor
+        for i in 0..20 {
+            ledger.apply_report(&format!("did:sync:zSybil{i}"), "did:sync:zSpam", 10);
+        }
+        assert!((ledger.trust("did:sync:zSpam") - REPORTED_TRUST_FLOOR).abs() < 1e-9);
+
+        // Distrusted reporters and self-reports are ignored
+        ledger.record_violation("did:sync:zMallory", 10);
+        assert_eq!(ledger.apply_report("did:sync:zMallory", "did:sync:zJoy", 10), 0.0);
+        assert_eq!(ledger.apply_report("did:sync:zJoy", "did:sync:zJoy", 10), 0.0);
+        assert_eq!(ledger.trust("did:sync:zJoy"), NEUTRAL_TRUST);
+    }
+
+    #[test]
+    fn test_ledger_size_is_bounded() {
+        let mut ledger = ReputationLedger::new();
+        ledger.record_violation("did:sync:zMallory", 10);
+        for i in 0..MAX_TRACKED_PEERS {
+            ledger.record_violation(&format!("did:sync:z{i}"), 1);
+        }
+
+        // The mildest standings make room; the worst offender is remembered
+        assert_eq!(ledger.tracked_peers(), MAX_TRACKED_PEERS);
+        assert!((ledger.trust("did:sync:zMallory") - 0.3).abs() < 1e-9);
+
+        // Past the reporter cap, new reporters about a peer are not heard
+        for i in 0..MAX_REPORTERS_PER_PEER {
+            assert!(ledger.apply_report(&format!("did:sync:zReporter{i}"), "did:sync:zSpam", 1) > 0.0);
+        }
+        assert_eq!(ledger.apply_report("did:sync:zLate", "did:sync:zSpam", 1), 0.0);
+    }
+}
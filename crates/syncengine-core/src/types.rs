@@ -1,9 +1,12 @@
 //! Core types for Synchronicity Engine
 
+use std::collections::BTreeMap;
+
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
+use crate::identity::Did;
 use crate::invite::NodeAddrBytes;
 
 // Submodules for rich card types
@@ -19,12 +22,16 @@ pub use profile::{PinRelationship, ProfilePin, SignedProfile, UserProfile};
 
 // Re-export contact types for convenience
 pub use contact::{
-    ContactInfo, ContactState, ContactStatus, PeerContactInvite, PendingContact, ProfileSnapshot,
+    BundledContact, ContactImportReport, ContactInfo, ContactState, ContactStatus, ContactsBundle,
+    PeerContactInvite, PendingContact, ProfileSnapshot, CONTACTS_BUNDLE_VERSION,
 };
 
 // Re-export unified peer types
 pub use peer::{ContactDetails, Peer, PeerSource, PeerStatus};
 
+/// Domain separation for [`RealmId::derive_from_secret`]
+const REALM_ID_SECRET_CONTEXT: &str = "syncengine 2025 realm id from shared secret";
+
 /// Unique identifier for a realm (gossip topic)
 ///
 /// A realm represents a shared space where tasks are synchronized
@@ -40,6 +47,18 @@ impl RealmId {
         Self(bytes)
     }
 
+    /// Derive a RealmId from a secret shared out-of-band and a realm name
+    ///
+    /// Everyone holding the same secret computes the same ID for a name, so
+    /// they can meet on the realm's topic without exchanging an invite
+    /// ticket. The ID is a keyed hash of the name, so it can't be guessed
+    /// without the secret; the secret should therefore carry real entropy
+    /// (a passphrase or random bytes, not just the realm name).
+    pub fn derive_from_secret(shared_secret: &[u8], name: &str) -> Self {
+        let key = blake3::derive_key(REALM_ID_SECRET_CONTEXT, shared_secret);
+        Self(*blake3::keyed_hash(&key, name.as_bytes()).as_bytes())
+    }
+
     /// Create a RealmId from raw bytes
     pub fn from_bytes(bytes: [u8; 32]) -> Self {
         Self(bytes)
@@ -96,6 +115,14 @@ impl TaskId {
         Self(ulid)
     }
 
+    /// Create a TaskId from a Unix timestamp in milliseconds and 80 bits of entropy
+    ///
+    /// Entropy beyond 80 bits is discarded. IDs sort by timestamp first, then
+    /// entropy, so tests can build a known, ordered sequence.
+    pub fn from_parts(timestamp_ms: u64, entropy: u128) -> Self {
+        Self(Ulid::from_parts(timestamp_ms, entropy))
+    }
+
     /// Get the underlying ULID
     pub fn as_ulid(&self) -> &Ulid {
         &self.0
@@ -125,6 +152,72 @@ impl std::fmt::Display for TaskId {
     }
 }
 
+/// Source of IDs for tasks created by the engine
+///
+/// See `SyncEngine::set_task_id_generator`.
+pub trait TaskIdGenerator: Send + Sync {
+    /// Produce the ID for the next new task
+    fn next_id(&mut self) -> TaskId;
+}
+
+/// Random ULIDs from the current time (the engine's default)
+///
+/// IDs made within the same millisecond are monotonic, so tasks created
+/// in a burst keep their creation order.
+#[derive(Default)]
+pub struct RandomTaskIds(ulid::Generator);
+
+impl TaskIdGenerator for RandomTaskIds {
+    fn next_id(&mut self) -> TaskId {
+        // Only fails if the random part overflows within one millisecond
+        self.0.generate().map(TaskId::from_ulid).unwrap_or_default()
+    }
+}
+
+/// Deterministic, strictly increasing IDs for tests
+///
+/// The n-th ID (counting from 0) is `TaskId::from_parts(start_ms + n, n)`.
+#[derive(Debug, Clone)]
+pub struct SequentialTaskIds {
+    start_ms: u64,
+    issued: u64,
+}
+
+impl SequentialTaskIds {
+    /// Start a sequence at the given Unix timestamp in milliseconds
+    pub fn new(start_ms: u64) -> Self {
+        Self { start_ms, issued: 0 }
+    }
+}
+
+impl TaskIdGenerator for SequentialTaskIds {
+    fn next_id(&mut self) -> TaskId {
+        let id = TaskId::from_parts(self.start_ms + self.issued, self.issued as u128);
+        self.issued += 1;
+        id
+    }
+}
+
+/// Shared branding for a realm
+///
+/// Kept in the realm document, so every member sees the same description,
+/// cover and color once they have synced.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RealmMetadata {
+    /// What the realm is for
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Iroh blob hash for the cover image
+    #[serde(default)]
+    pub cover_blob_id: Option<String>,
+    /// Accent color, as a CSS color such as `#7c3aed`
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Emoji shown next to the realm name
+    #[serde(default)]
+    pub emoji: Option<String>,
+}
+
 /// Basic realm information
 ///
 /// Contains metadata about a realm without the full task list.
@@ -141,6 +234,24 @@ pub struct RealmInfo {
     /// Bootstrap peers for reconnecting after restart (only for shared realms)
     #[serde(default)]
     pub bootstrap_peers: Vec<NodeAddrBytes>,
+    /// Local copy kept after leaving a shared realm; tasks can be read but not changed
+    #[serde(default)]
+    pub read_only: bool,
+    /// Notifications for this realm are held back on this node (never synced)
+    #[serde(default)]
+    pub muted: bool,
+    /// Number of times the realm key has been replaced; 0 for the original key
+    #[serde(default)]
+    pub key_epoch: u64,
+    /// Whether this node created the realm (and is its first owner)
+    #[serde(default)]
+    pub is_creator: bool,
+    /// DID of the realm's first owner, from creation or the invite we joined with
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Description, cover and color shared by all members
+    #[serde(default)]
+    pub metadata: RealmMetadata,
 }
 
 impl RealmInfo {
@@ -152,10 +263,273 @@ impl RealmInfo {
             is_shared: false,
             created_at: chrono::Utc::now().timestamp(),
             bootstrap_peers: Vec::new(),
+            read_only: false,
+            muted: false,
+            key_epoch: 0,
+            is_creator: false,
+            owner: None,
+            metadata: RealmMetadata::default(),
+        }
+    }
+}
+
+/// A member of a shared realm
+///
+/// Members are observed locally from signed sync traffic, so the roster
+/// reflects who this node has actually seen participating. Members that go
+/// offline remain listed with a stale `last_seen`; members that explicitly
+/// leave are kept as a tombstone with `left_at` set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RealmMember {
+    /// DID of the member
+    pub did: String,
+    /// Unix timestamp when the member was first observed in the realm
+    pub joined_at: i64,
+    /// Unix timestamp of the most recent activity from the member
+    pub last_seen: i64,
+    /// Unix timestamp when the member announced leaving (None while active)
+    pub left_at: Option<i64>,
+}
+
+impl RealmMember {
+    /// Create a member first seen at the given timestamp
+    pub fn new(did: impl Into<String>, seen_at: i64) -> Self {
+        Self {
+            did: did.into(),
+            joined_at: seen_at,
+            last_seen: seen_at,
+            left_at: None,
+        }
+    }
+}
+
+/// A member's permissions in a shared realm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RealmRole {
+    /// Can edit tasks and change other members' roles
+    Owner,
+    /// Can edit tasks
+    Editor,
+    /// Can read tasks; their changes are rejected by peers
+    Viewer,
+}
+
+impl RealmRole {
+    /// Whether changes authored with this role are accepted
+    pub fn can_edit(self) -> bool {
+        !matches!(self, RealmRole::Viewer)
+    }
+}
+
+impl std::fmt::Display for RealmRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RealmRole::Owner => write!(f, "owner"),
+            RealmRole::Editor => write!(f, "editor"),
+            RealmRole::Viewer => write!(f, "viewer"),
+        }
+    }
+}
+
+/// Role table for a shared realm, signed by one of its owners
+///
+/// There is no server to hold the table, so owners sign each revision and
+/// gossip it on the realm topic. Peers keep the highest `version` whose
+/// signer was an owner in the revision they held before. Members without an
+/// entry are editors, so a realm behaves as before until roles are assigned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RealmRoles {
+    /// Realm the table applies to
+    pub realm_id: RealmId,
+    /// Revision number, incremented on every change
+    pub version: u64,
+    /// Role by member DID
+    pub roles: BTreeMap<String, RealmRole>,
+    /// DID of the owner who signed this revision
+    pub signer: String,
+    /// Signature by `signer` over [`signing_bytes`](Self::signing_bytes)
+    pub signature: Vec<u8>,
+}
+
+impl RealmRoles {
+    /// Role of a member, defaulting to `Editor` for members without an entry
+    pub fn role_of(&self, did: &str) -> RealmRole {
+        self.roles.get(did).copied().unwrap_or(RealmRole::Editor)
+    }
+
+    /// Whether a member is an owner in this revision
+    pub fn is_owner(&self, did: &str) -> bool {
+        self.role_of(did) == RealmRole::Owner
+    }
+
+    /// Bytes covered by the signature (everything except the signature)
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(&(&self.realm_id, self.version, &self.roles, &self.signer))
+            .expect("Role table serialization should never fail")
+    }
+}
+
+/// Unique identifier for a realm snapshot
+///
+/// Uses ULID so snapshots of a realm list in the order they were taken.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SnapshotId(pub Ulid);
+
+impl SnapshotId {
+    /// Create a new SnapshotId with current timestamp
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Convert to string representation
+    pub fn to_string_repr(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Parse from string representation
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        let ulid = Ulid::from_string(s)?;
+        Ok(Self(ulid))
+    }
+}
+
+impl Default for SnapshotId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for SnapshotId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "snapshot_{}", self.0)
+    }
+}
+
+/// Metadata for a frozen copy of a realm's document
+///
+/// Snapshots are local only: they are never synced to peers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RealmSnapshot {
+    /// Unique identifier for the snapshot
+    pub id: SnapshotId,
+    /// Realm the snapshot was taken from
+    pub realm_id: RealmId,
+    /// User-supplied label
+    pub label: String,
+    /// Unix timestamp when the snapshot was taken
+    pub created_at: i64,
+}
+
+/// Differences between two states of a realm's tasks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RealmDiff {
+    /// Tasks present only in the later state
+    pub added: Vec<Task>,
+    /// Tasks present only in the earlier state
+    pub removed: Vec<Task>,
+    /// Tasks present in both states whose fields differ
+    pub modified: Vec<TaskChange>,
+}
+
+impl RealmDiff {
+    /// Whether the two states hold identical tasks
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Field-level changes to a single task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskChange {
+    /// The task that changed
+    pub id: TaskId,
+    /// Title in the later state
+    pub title: String,
+    /// Changed fields, in field name order
+    pub fields: Vec<FieldChange>,
+}
+
+/// A single task field that differs between two states
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Field name as serialized (e.g. `completed`)
+    pub field: String,
+    /// Value in the earlier state (`null` if absent)
+    pub before: serde_json::Value,
+    /// Value in the later state (`null` if absent)
+    pub after: serde_json::Value,
+}
+
+/// One entry in a task's history, derived from the realm's change graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskActivity {
+    /// DID the change claims as its author (`None` for changes made without
+    /// an identity, or before authors were recorded)
+    ///
+    /// This is whatever the committing node wrote into the change, and is
+    /// not verified: changes are relayed between peers, so the signed sender
+    /// of the sync message that carried a change need not be its author.
+    pub claimed_author: Option<String>,
+    /// Unix timestamp recorded with the change, if any
+    pub timestamp: Option<i64>,
+    /// What happened to the task
+    pub kind: TaskActivityKind,
+}
+
+/// What a change did to a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TaskActivityKind {
+    /// The task was added
+    Created {
+        /// Title at creation
+        title: String,
+    },
+    /// The task was marked completed
+    Completed,
+    /// A completed task was marked incomplete again
+    Reopened,
+    /// The task's title changed
+    Retitled {
+        /// Previous title
+        from: String,
+        /// New title
+        to: String,
+    },
+    /// Other fields changed (e.g. description or category)
+    Edited {
+        /// Names of the changed fields, as serialized
+        fields: Vec<String>,
+    },
+    /// The task was deleted
+    Deleted,
+}
+
+/// How often a recurring task comes back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Every day
+    Daily,
+    /// Every seven days
+    Weekly,
+    /// Every `interval_secs` seconds
+    Custom { interval_secs: i64 },
+}
+
+impl Recurrence {
+    /// Length of one period in seconds
+    pub fn interval_secs(&self) -> i64 {
+        match self {
+            Recurrence::Daily => 24 * 60 * 60,
+            Recurrence::Weekly => 7 * 24 * 60 * 60,
+            Recurrence::Custom { interval_secs } => *interval_secs,
         }
     }
 }
 
+/// Lead time for a due task's reminder when the task doesn't set one (15 minutes)
+pub const DEFAULT_REMINDER_LEAD_SECS: i64 = 15 * 60;
+
 /// Task in a realm
 ///
 /// Represents a single task item that can be synchronized between peers.
@@ -172,6 +546,9 @@ pub struct Task {
     pub created_at: i64,
     /// Unix timestamp of completion (if completed)
     pub completed_at: Option<i64>,
+    /// Who marked the task done (if completed and known)
+    #[serde(default)]
+    pub completed_by: Option<Did>,
 
     // ─────────────────────────────────────────────────────────────────
     // Rich quest fields (optional, backwards compatible)
@@ -203,6 +580,42 @@ pub struct Task {
     /// Peer ID of who created this quest
     #[serde(default)]
     pub created_by: Option<String>,
+
+    /// Unix timestamp the task is due by
+    #[serde(default)]
+    pub due_at: Option<i64>,
+
+    /// Makes the task come back as a fresh instance when completed
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+
+    /// How many seconds before `due_at` to remind about the task
+    ///
+    /// Falls back to [`DEFAULT_REMINDER_LEAD_SECS`] when unset.
+    #[serde(default)]
+    pub remind_before_secs: Option<i64>,
+
+    /// ID shared by every instance of a recurring task
+    ///
+    /// The first instance's own ID, assigned when it is first completed.
+    #[serde(default)]
+    pub series_id: Option<TaskId>,
+
+    /// Realm member the task is assigned to
+    #[serde(default)]
+    pub assignee: Option<Did>,
+
+    /// Display name of `assignee`, from their pinned profile
+    ///
+    /// Only filled in by `SyncEngine` task reads; never stored in the document.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub assignee_name: Option<String>,
+
+    /// Titles from concurrent renames that lost to `title`, with their authors
+    ///
+    /// Only filled in by `SyncEngine::get_task`; never stored in the document.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    pub conflicting_values: Vec<(Did, String)>,
 }
 
 impl Task {
@@ -214,6 +627,7 @@ impl Task {
             completed: false,
             created_at: chrono::Utc::now().timestamp(),
             completed_at: None,
+            completed_by: None,
             subtitle: None,
             quest_link: None,
             description: String::new(),
@@ -221,6 +635,13 @@ impl Task {
             involved_peers: Vec::new(),
             category: None,
             created_by: None,
+            due_at: None,
+            recurrence: None,
+            remind_before_secs: None,
+            series_id: None,
+            assignee: None,
+            assignee_name: None,
+            conflicting_values: Vec::new(),
         }
     }
 
@@ -236,6 +657,7 @@ impl Task {
             completed: false,
             created_at: chrono::Utc::now().timestamp(),
             completed_at: None,
+            completed_by: None,
             subtitle,
             quest_link: None,
             description: description.into(),
@@ -243,6 +665,13 @@ impl Task {
             involved_peers: Vec::new(),
             category: None,
             created_by: None,
+            due_at: None,
+            recurrence: None,
+            remind_before_secs: None,
+            series_id: None,
+            assignee: None,
+            assignee_name: None,
+            conflicting_values: Vec::new(),
         }
     }
 
@@ -263,15 +692,58 @@ impl Task {
     pub fn uncomplete(&mut self) {
         self.completed = false;
         self.completed_at = None;
+        self.completed_by = None;
     }
 
     /// Toggle the completion state
     pub fn toggle(&mut self) {
+        self.toggle_by(None);
+    }
+
+    /// Toggle the completion state, crediting `by` if this completes it
+    pub fn toggle_by(&mut self, by: Option<&Did>) {
         if self.completed {
             self.uncomplete();
         } else {
             self.complete();
+            self.completed_by = by.cloned();
+        }
+    }
+
+    /// Unix timestamp at which a reminder for this task is due
+    ///
+    /// `None` if the task has no due date or is already completed.
+    pub fn reminder_at(&self) -> Option<i64> {
+        if self.completed {
+            return None;
         }
+        let lead = self.remind_before_secs.unwrap_or(DEFAULT_REMINDER_LEAD_SECS);
+        self.due_at.map(|due| due - lead)
+    }
+
+    /// The open instance that follows this one in its recurring series
+    ///
+    /// Returns `None` for non-recurring tasks. The next due date is one
+    /// interval after the current due date, or after completion if the task
+    /// had none. Links this task into the series if it wasn't already.
+    pub fn next_occurrence(&mut self) -> Option<Task> {
+        let recurrence = self.recurrence?;
+        let series_id = self.series_id.get_or_insert_with(|| self.id.clone()).clone();
+        let from = self
+            .due_at
+            .or(self.completed_at)
+            .unwrap_or_else(|| chrono::Utc::now().timestamp());
+
+        let mut next = Task::new_quest(self.title.clone(), self.subtitle.clone(), self.description.clone());
+        next.category = self.category.clone();
+        next.image_blob_id = self.image_blob_id.clone();
+        next.involved_peers = self.involved_peers.clone();
+        next.created_by = self.created_by.clone();
+        next.due_at = Some(from + recurrence.interval_secs());
+        next.recurrence = Some(recurrence);
+        next.remind_before_secs = self.remind_before_secs;
+        next.series_id = Some(series_id);
+        Some(next)
     }
 }
 
@@ -302,6 +774,16 @@ mod tests {
         assert_eq!(realm, decoded);
     }
 
+    #[test]
+    fn test_realm_id_derived_from_secret() {
+        let kitchen = RealmId::derive_from_secret(b"blue heron at dawn", "Kitchen");
+        assert_eq!(kitchen, RealmId::derive_from_secret(b"blue heron at dawn", "Kitchen"));
+        assert_ne!(kitchen, RealmId::derive_from_secret(b"grey heron at dusk", "Kitchen"));
+        assert_ne!(kitchen, RealmId::derive_from_secret(b"blue heron at dawn", "Garden"));
+        // The name alone doesn't determine the ID
+        assert_ne!(kitchen, RealmId::derive_from_secret(b"", "Kitchen"));
+    }
+
     #[test]
     fn test_task_id_new() {
         let task1 = TaskId::new();
@@ -310,6 +792,21 @@ mod tests {
         assert_ne!(task1, task2);
     }
 
+    #[test]
+    fn test_sequential_task_ids_are_reproducible_and_ordered() {
+        let first: Vec<_> = {
+            let mut ids = SequentialTaskIds::new(1_700_000_000_000);
+            (0..3).map(|_| ids.next_id()).collect()
+        };
+        let mut ids = SequentialTaskIds::new(1_700_000_000_000);
+        let second: Vec<_> = (0..3).map(|_| ids.next_id()).collect();
+
+        assert_eq!(first, second);
+        assert_eq!(first[0], TaskId::from_parts(1_700_000_000_000, 0));
+        assert_eq!(first[2].as_ulid().timestamp_ms(), 1_700_000_000_002);
+        assert!(first.windows(2).all(|w| w[0].as_ulid() < w[1].as_ulid()));
+    }
+
     #[test]
     fn test_task_id_display() {
         let task = TaskId::new();
@@ -73,7 +73,7 @@ pub struct PeerContactInvite {
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HybridContactInvite {
-    /// Protocol version (should be 2)
+    /// Protocol version (2, 3 when a realm ticket is bundled, 4 with a greeting)
     pub version: u8,
     /// Unique random ID (nonce) for this invite
     pub invite_id: [u8; 16],
@@ -89,8 +89,24 @@ pub struct HybridContactInvite {
     pub expires_at: i64,
     /// HybridSignature over all fields
     pub signature: Vec<u8>,
+    /// Realm invite ticket bundled with the contact invite (version 3 and 4)
+    ///
+    /// Not part of the struct encoding so version 2 invites keep decoding;
+    /// version 3 invites append the ticket after the struct bytes.
+    #[serde(skip)]
+    pub realm_ticket: Option<String>,
+    /// Greeting from the inviter, at most [`MAX_INVITE_GREETING_CHARS`]
+    /// (version 4 only)
+    ///
+    /// Version 4 invites append the optional realm ticket and then the
+    /// greeting after the struct bytes.
+    #[serde(skip)]
+    pub greeting: Option<String>,
 }
 
+/// Longest greeting an invite may carry, in characters
+pub const MAX_INVITE_GREETING_CHARS: usize = 280;
+
 impl PeerContactInvite {
     /// Create a new invite ID (random 16 bytes)
     pub fn generate_invite_id() -> [u8; 16] {
@@ -207,14 +223,45 @@ pub struct ContactInfo {
     /// These can be used as relay fallbacks when direct connection fails.
     #[serde(default)]
     pub mutual_peers: Vec<String>,
+    /// Local alias set by us; never shared with the contact
+    #[serde(default)]
+    pub nickname: Option<String>,
 }
 
 impl ContactInfo {
+    /// Name to show for this contact: our nickname if set, else their profile name
+    pub fn display_name(&self) -> &str {
+        self.nickname.as_deref().unwrap_or(&self.profile.display_name)
+    }
+
     /// Update last seen timestamp to now
     pub fn mark_seen(&mut self) {
         self.last_seen = chrono::Utc::now().timestamp() as u64;
     }
 
+    /// How well `query` matches this contact, higher is better
+    ///
+    /// Display names match case-insensitively, from an exact match down to
+    /// the query's characters merely appearing in order; DIDs match by
+    /// prefix, with or without the leading `did:sync:` or `did:sync:z`. Returns `None` if the
+    /// contact doesn't match at all.
+    pub fn match_score(&self, query: &str) -> Option<u32> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        let did_score = ["", "did:sync:", "did:sync:z"]
+            .iter()
+            .any(|prefix| {
+                self.peer_did
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with(query))
+            })
+            .then_some(700);
+        let name_score = name_match_score(&self.profile.display_name, query);
+        did_score.max(name_score)
+    }
+
     /// Check if contact was recently online (within 5 minutes)
     pub fn is_recently_active(&self) -> bool {
         let now = chrono::Utc::now().timestamp() as u64;
@@ -222,6 +269,48 @@ impl ContactInfo {
     }
 }
 
+/// Score a case-insensitive match of `query` against a display name
+///
+/// Tiers, best first: exact, prefix, start of a later word, substring, and
+/// finally subsequence, where tighter spans score higher.
+fn name_match_score(name: &str, query: &str) -> Option<u32> {
+    let name: String = name.trim().to_lowercase();
+    let query: String = query.to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    if name == query {
+        return Some(1000);
+    }
+    if name.starts_with(&query) {
+        return Some(800);
+    }
+    if name
+        .split_whitespace()
+        .skip(1)
+        .any(|word| word.starts_with(&query))
+    {
+        return Some(600);
+    }
+    if name.contains(&query) {
+        return Some(400);
+    }
+
+    // Subsequence: every query char appears in order
+    let name: Vec<char> = name.chars().collect();
+    let mut first = None;
+    let mut pos = 0;
+    for q in query.chars() {
+        let offset = name[pos..].iter().position(|&c| c == q)?;
+        first.get_or_insert(pos + offset);
+        pos += offset + 1;
+    }
+    let span = pos - first.unwrap_or(0);
+    let query_len = query.chars().count();
+    Some(100 + (100 * query_len / span.max(1)) as u32)
+}
+
 /// Online/offline status of a contact
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ContactStatus {
@@ -246,6 +335,79 @@ impl std::fmt::Display for ContactStatus {
     }
 }
 
+/// Current format version of [`ContactsBundle`]
+pub const CONTACTS_BUNDLE_VERSION: u32 = 1;
+
+/// Portable copy of a contact list, for moving to another device
+///
+/// Holds what is needed to re-create each contact without running the
+/// contact exchange again. The 1:1 topic and message key are left out: both
+/// are derived from the two DIDs, so the importing engine derives its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactsBundle {
+    /// Format version ([`CONTACTS_BUNDLE_VERSION`] when written)
+    pub version: u32,
+    /// Identity DID of the engine that exported the contacts
+    pub exported_by: String,
+    /// Profile DID of the exporter, whose public keys the contacts hold
+    pub exported_by_profile: Option<String>,
+    /// Unix timestamp of the export
+    pub exported_at: i64,
+    /// The exported contacts
+    pub contacts: Vec<BundledContact>,
+}
+
+impl ContactsBundle {
+    /// Serialize the bundle to pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, crate::SyncError> {
+        serde_json::to_string_pretty(self).map_err(|e| crate::SyncError::Serialization(e.to_string()))
+    }
+
+    /// Parse a bundle from JSON
+    pub fn from_json(json: &str) -> Result<Self, crate::SyncError> {
+        serde_json::from_str(json).map_err(|e| crate::SyncError::Serialization(e.to_string()))
+    }
+}
+
+/// One contact in a [`ContactsBundle`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledContact {
+    /// DID of the contact
+    pub did: String,
+    /// Iroh endpoint ID (32-byte public key)
+    pub endpoint_id: [u8; 32],
+    /// Last known network address
+    pub node_addr: NodeAddrBytes,
+    /// Cached profile information
+    pub profile: ProfileSnapshot,
+    /// The contact's pinned signed profile, if we had one
+    pub signed_profile: Option<crate::types::SignedProfile>,
+    /// User-set nickname
+    pub nickname: Option<String>,
+    /// Why the contact's profile was pinned
+    pub relationship: crate::types::PinRelationship,
+    /// Unix timestamp of mutual acceptance
+    pub accepted_at: i64,
+    /// Priority for auto-connect on startup
+    pub is_favorite: bool,
+    /// The contact's serialized ProfilePublicKeys, if the exchange included them
+    pub encryption_keys: Option<Vec<u8>>,
+}
+
+/// Outcome of importing a [`ContactsBundle`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContactImportReport {
+    /// DIDs of contacts that were created
+    pub imported: Vec<String>,
+    /// DIDs skipped because they were already contacts
+    pub skipped: Vec<String>,
+    /// Imported DIDs that need a new contact exchange before E2E messaging works
+    ///
+    /// Either the bundle has no encryption keys for them, or it was exported
+    /// by a different identity or profile, so they hold keys we don't have.
+    pub needs_key_exchange: Vec<String>,
+}
+
 /// Pending contact request state
 ///
 /// Tracks the lifecycle of a contact request from initial invite
@@ -276,13 +438,36 @@ pub struct PendingContact {
     /// Received during contact exchange and used to compute mutual_peers.
     #[serde(default)]
     pub peer_contact_dids: Vec<String>,
+    /// Greeting the inviter attached to the invite, if any
+    ///
+    /// Stored beside the record rather than in it, so pending contacts
+    /// saved before greetings existed keep decoding.
+    #[serde(skip)]
+    pub greeting: Option<String>,
 }
 
+/// How long a pending contact request stays open (7 days)
+pub const PENDING_CONTACT_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
 impl PendingContact {
+    /// Unix timestamp after which this request expires
+    pub fn expires_at(&self) -> i64 {
+        self.created_at + PENDING_CONTACT_TTL_SECS
+    }
+
+    /// Check if this request has expired as of `now` (Unix timestamp)
+    pub fn is_expired_at(&self, now: i64) -> bool {
+        now > self.expires_at()
+    }
+
+    /// Seconds left before this request expires as of `now`, zero once expired
+    pub fn remaining_secs(&self, now: i64) -> i64 {
+        (self.expires_at() - now).max(0)
+    }
+
     /// Check if this pending contact has been waiting for more than 7 days
     pub fn is_stale(&self) -> bool {
-        let now = chrono::Utc::now().timestamp();
-        now - self.created_at > 7 * 24 * 60 * 60 // 7 days
+        self.is_expired_at(chrono::Utc::now().timestamp())
     }
 }
 
@@ -328,6 +513,17 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_name_match_tiers_ignore_case_across_unicode() {
+        assert_eq!(name_match_score("Zoë Ødegaard", "ZOË ØDEGAARD"), Some(1000));
+        assert_eq!(name_match_score("Zoë Ødegaard", "zoë"), Some(800));
+        assert_eq!(name_match_score("Zoë Ødegaard", "øde"), Some(600));
+        assert_eq!(name_match_score("Zoë Ødegaard", "gaa"), Some(400));
+        assert_eq!(name_match_score("Zoë Ødegaard", "zød"), Some(100 + 300 / 6));
+        assert_eq!(name_match_score("Zoë Ødegaard", "dz"), None);
+        assert_eq!(name_match_score("Zoë Ødegaard", ""), None);
+    }
+
     #[test]
     fn test_bio_truncation() {
         let short_bio = "This is a short bio.";
@@ -342,6 +538,28 @@ mod tests {
         assert!(truncated.ends_with("..."));
     }
 
+    #[test]
+    fn test_decodes_stored_profile_snapshot() {
+        // Snapshot bytes as stored inside contacts, peers and pending contacts
+        let stored: &[u8] = &[
+            4, b'L', b'o', b'v', b'e', // display_name
+            1, 3, b'J', b'o', b'y', // subtitle
+            1, 2, b'a', b'b', // avatar_blob_id
+            2, b'h', b'i', // bio
+        ];
+        let snapshot: ProfileSnapshot = postcard::from_bytes(stored).unwrap();
+        assert_eq!(
+            snapshot,
+            ProfileSnapshot {
+                display_name: "Love".to_string(),
+                subtitle: Some("Joy".to_string()),
+                avatar_blob_id: Some("ab".to_string()),
+                bio: "hi".to_string(),
+            }
+        );
+        assert_eq!(postcard::to_allocvec(&snapshot).unwrap(), stored);
+    }
+
     #[test]
     fn test_invite_expiry() {
         let mut invite = PeerContactInvite {
@@ -417,6 +635,7 @@ mod tests {
             is_favorite: false,
             encryption_keys: None,
             mutual_peers: vec![],
+            nickname: None,
         };
 
         assert!(contact.is_recently_active());
@@ -443,6 +662,7 @@ mod tests {
             created_at: chrono::Utc::now().timestamp(),
             encryption_keys: None,
             peer_contact_dids: vec![],
+            greeting: None,
         };
 
         assert!(!pending.is_stale());
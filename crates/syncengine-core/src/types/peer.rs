@@ -26,7 +26,7 @@ use crate::types::contact::ProfileSnapshot;
 use crate::types::RealmId;
 use iroh::PublicKey;
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// How a peer was discovered
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -37,6 +37,8 @@ pub enum PeerSource {
     FromInvite,
     /// Became a mutually accepted contact
     FromContact,
+    /// Found on the local network via mDNS
+    LocalDiscovery,
 }
 
 impl Default for PeerSource {
@@ -67,6 +69,79 @@ impl std::fmt::Display for PeerStatus {
     }
 }
 
+/// Smoothed link measurements used to score a peer's connection quality
+///
+/// Each keepalive either yields a round-trip sample or counts as a lost probe.
+/// Both RTT and loss are exponentially weighted so a single outlier does not
+/// swing the score, while a sustained change shows up within a few samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LinkStats {
+    /// Smoothed round-trip time in milliseconds (`None` until the first sample)
+    pub rtt_ms: Option<u32>,
+    /// Smoothed probe loss in permille (0 = no loss, 1000 = every probe lost)
+    pub loss_permille: u16,
+    /// Whether the most recent sample went over a direct (non-relayed) path
+    pub direct: bool,
+    /// Number of probes recorded, answered or lost
+    pub samples: u32,
+}
+
+impl LinkStats {
+    /// RTT at or below which latency costs nothing
+    const GOOD_RTT_MS: u32 = 50;
+    /// RTT at or above which the latency component bottoms out
+    const BAD_RTT_MS: u32 = 1000;
+    /// Points deducted when traffic is going through a relay
+    const RELAY_PENALTY: u32 = 15;
+
+    /// Record an answered probe
+    pub fn record_rtt(&mut self, rtt: Duration, direct: bool) {
+        let sample = rtt.as_millis().min(u32::MAX as u128) as u32;
+        self.rtt_ms = Some(match self.rtt_ms {
+            // Weight new samples at 1/4, as a compromise between TCP's 1/8 and
+            // the short sample history of a freshly connected peer
+            Some(prev) => ((prev as u64 * 3 + sample as u64) / 4) as u32,
+            None => sample,
+        });
+        self.loss_permille = Self::smooth_loss(self.loss_permille, 0);
+        self.direct = direct;
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    /// Record a probe that went unanswered
+    pub fn record_loss(&mut self) {
+        self.loss_permille = Self::smooth_loss(self.loss_permille, 1000);
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    fn smooth_loss(prev: u16, sample: u16) -> u16 {
+        ((prev as u32 * 3 + sample as u32) / 4) as u16
+    }
+
+    /// Connection quality score from 0 (unusable or unmeasured) to 100
+    ///
+    /// Latency maps linearly from 100 at 50ms to 0 at 1s, is scaled down by
+    /// the loss ratio, and loses a fixed penalty when the path is relayed.
+    pub fn quality(&self) -> u8 {
+        let Some(rtt) = self.rtt_ms else {
+            return 0;
+        };
+        let latency_score = if rtt <= Self::GOOD_RTT_MS {
+            100
+        } else if rtt >= Self::BAD_RTT_MS {
+            0
+        } else {
+            100 - (rtt - Self::GOOD_RTT_MS) * 100 / (Self::BAD_RTT_MS - Self::GOOD_RTT_MS)
+        };
+        let delivered = 1000 - self.loss_permille.min(1000) as u32;
+        let mut score = latency_score * delivered / 1000;
+        if !self.direct {
+            score = score.saturating_sub(Self::RELAY_PENALTY);
+        }
+        score as u8
+    }
+}
+
 /// Contact-specific details (only for mutually accepted contacts)
 ///
 /// When two peers mutually accept each other as contacts, they exchange
@@ -163,6 +238,16 @@ pub struct Peer {
     /// Unix timestamp of last connection attempt
     #[serde(default)]
     pub last_attempt: u64,
+    /// Smoothed RTT, loss and path measurements from keepalives
+    #[serde(default)]
+    pub link: LinkStats,
+    /// Connection quality score (0-100) derived from `link`
+    #[serde(default)]
+    pub connection_quality: u8,
+    /// Estimated offset of the peer's clock from ours in milliseconds
+    /// (positive = peer is ahead; `None` until the first announce)
+    #[serde(default)]
+    pub clock_skew_ms: Option<i64>,
 }
 
 impl Peer {
@@ -182,6 +267,9 @@ impl Peer {
             connection_attempts: 0,
             successful_connections: 0,
             last_attempt: 0,
+            link: LinkStats::default(),
+            connection_quality: 0,
+            clock_skew_ms: None,
         }
     }
 
@@ -390,6 +478,37 @@ impl Peer {
         elapsed >= required_delay
     }
 
+    /// Record a keepalive round trip and rescore the connection
+    pub fn record_rtt_sample(&mut self, rtt: Duration, direct: bool) {
+        self.link.record_rtt(rtt, direct);
+        self.connection_quality = self.link.quality();
+    }
+
+    /// Record an unanswered keepalive and rescore the connection
+    pub fn record_probe_loss(&mut self) {
+        self.link.record_loss();
+        self.connection_quality = self.link.quality();
+    }
+
+    /// Record a peer timestamp and update the clock skew estimate
+    ///
+    /// `sent_at_ms` is the peer's clock when it sent a message and
+    /// `received_at_ms` ours when it arrived. Half the smoothed RTT is added
+    /// for the time in flight, and samples are weighted like RTT samples so
+    /// one delayed message does not swing the estimate.
+    ///
+    /// Returns the updated estimate.
+    pub fn record_clock_sample(&mut self, sent_at_ms: i64, received_at_ms: i64) -> i64 {
+        let in_flight = self.link.rtt_ms.map_or(0, |rtt| rtt as i64 / 2);
+        let sample = sent_at_ms + in_flight - received_at_ms;
+        let skew = match self.clock_skew_ms {
+            Some(prev) => (prev * 3 + sample) / 4,
+            None => sample,
+        };
+        self.clock_skew_ms = Some(skew);
+        skew
+    }
+
     /// Check if contact was recently online (within 5 minutes)
     pub fn is_recently_active(&self) -> bool {
         let now = Self::current_timestamp();
@@ -610,4 +729,18 @@ mod tests {
     fn test_peer_source_default() {
         assert_eq!(PeerSource::default(), PeerSource::FromInvite);
     }
+
+    #[test]
+    fn test_clock_skew_accounts_for_flight_time_and_smooths() {
+        let mut peer = Peer::new(create_test_public_key(), PeerSource::FromInvite);
+        assert_eq!(peer.clock_skew_ms, None);
+
+        // Sent at their 10_000, arrived at our 5_100 after ~100ms in flight
+        peer.record_rtt_sample(Duration::from_millis(200), true);
+        assert_eq!(peer.record_clock_sample(10_000, 5_100), 5_000);
+
+        // A single late message only moves the estimate a quarter of the way
+        assert_eq!(peer.record_clock_sample(20_000, 19_100), 4_000);
+        assert_eq!(peer.clock_skew_ms, Some(4_000));
+    }
 }
@@ -181,7 +181,11 @@ impl SignedProfile {
     /// Verify that the signature is valid for this profile.
     ///
     /// Returns `true` if both the Ed25519 and ML-DSA-65 signatures verify.
+    /// This is expensive; for pinned profiles prefer [`ProfilePin::check_signature`].
     pub fn verify(&self) -> bool {
+        #[cfg(test)]
+        VERIFY_CALLS.with(|calls| calls.set(calls.get() + 1));
+
         let profile_bytes = match postcard::to_allocvec(&self.profile) {
             Ok(bytes) => bytes,
             Err(_) => return false,
@@ -196,6 +200,31 @@ impl SignedProfile {
     pub fn did(&self) -> crate::identity::Did {
         crate::identity::Did::from_public_key(&self.public_key)
     }
+
+    /// BLAKE3 hash over the profile, signature and public key.
+    ///
+    /// Any change to what [`verify`](Self::verify) checks changes the hash.
+    pub fn content_hash(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        if let Ok(profile_bytes) = postcard::to_allocvec(&self.profile) {
+            hasher.update(&profile_bytes);
+        }
+        hasher.update(&self.signature.to_bytes());
+        hasher.update(&self.public_key.to_bytes());
+        *hasher.finalize().as_bytes()
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Number of `SignedProfile::verify` calls made on this thread
+    static VERIFY_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Number of signature verifications run so far on the current thread.
+#[cfg(test)]
+pub(crate) fn verify_calls() -> usize {
+    VERIFY_CALLS.with(|calls| calls.get())
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -279,6 +308,13 @@ pub struct ProfilePin {
     pub avatar_hash: Option<[u8; 32]>,
     /// Unix timestamp of last profile update we received
     pub last_updated: i64,
+    /// Cached result of verifying `signed_profile`, valid while
+    /// `verified_hash` matches its content hash
+    #[serde(default)]
+    pub verified: bool,
+    /// [`SignedProfile::content_hash`] that `verified` was computed for
+    #[serde(default)]
+    pub verified_hash: Option<[u8; 32]>,
 }
 
 impl ProfilePin {
@@ -303,9 +339,31 @@ impl ProfilePin {
             relationship,
             avatar_hash,
             last_updated: now,
+            verified: false,
+            verified_hash: None,
         }
     }
 
+    /// Whether the pinned profile's signature is valid.
+    ///
+    /// Verifies at most once per profile content: the result is cached in
+    /// `verified` and reused until `signed_profile` changes. Save the pin
+    /// afterwards to keep the cached result.
+    pub fn check_signature(&mut self) -> bool {
+        let hash = self.signed_profile.content_hash();
+        if self.verified_hash != Some(hash) {
+            self.verified = self.signed_profile.verify();
+            self.verified_hash = Some(hash);
+        }
+        self.verified
+    }
+
+    /// Record that `signed_profile` has just been verified by the caller.
+    pub(crate) fn mark_verified(&mut self) {
+        self.verified = true;
+        self.verified_hash = Some(self.signed_profile.content_hash());
+    }
+
     /// Update the signed profile while preserving pin metadata.
     ///
     /// Returns `false` if the new profile's signature is invalid.
@@ -326,6 +384,7 @@ impl ProfilePin {
             });
 
         self.signed_profile = new_signed_profile;
+        self.mark_verified();
         self.last_updated = chrono::Utc::now().timestamp();
         true
     }
@@ -394,6 +453,35 @@ mod signed_profile_tests {
         assert!(!signed.verify());
     }
 
+    #[test]
+    fn test_decodes_and_verifies_stored_profile_bytes() {
+        // A profile as stored and signed by earlier releases. The layout is
+        // signed, so it must decode and re-encode to exactly these bytes.
+        let stored: &[u8] = &[
+            2, b'p', b'1', // peer_id
+            4, b'L', b'o', b'v', b'e', // display_name
+            0, // subtitle
+            0, // profile_link
+            1, 2, b'a', b'b', // avatar_blob_id
+            0, // bio
+            0, // top_quests
+            2, // created_at = 1
+            4, // updated_at = 2
+        ];
+        let profile: UserProfile = postcard::from_bytes(stored).expect("Deserialization failed");
+        assert_eq!(profile.display_name, "Love");
+        assert_eq!(profile.avatar_blob_id.as_deref(), Some("ab"));
+        assert_eq!(postcard::to_allocvec(&profile).unwrap(), stored);
+
+        let keypair = HybridKeypair::generate();
+        let signed = SignedProfile {
+            profile,
+            signature: keypair.sign(stored),
+            public_key: keypair.public_key(),
+        };
+        assert!(signed.verify());
+    }
+
     #[test]
     fn test_signed_profile_serialization_roundtrip() {
         let keypair = HybridKeypair::generate();
@@ -280,8 +280,8 @@ async fn test_blob_sharing_between_contacts() {
     }
 
     // Verify they're contacts
-    let love_contacts = love.list_contacts().unwrap();
-    let joy_contacts = joy.list_contacts().unwrap();
+    let love_contacts = love.list_contacts(false).unwrap();
+    let joy_contacts = joy.list_contacts(false).unwrap();
 
     if love_contacts.is_empty() || joy_contacts.is_empty() {
         println!("⚠️ Contact exchange didn't complete - skipping blob test");
@@ -292,7 +292,7 @@ async fn test_blob_sharing_between_contacts() {
 
     // Love uploads avatar
     let avatar_data = vec![0xAA; 50 * 1024]; // 50KB avatar
-    let avatar_id = love.upload_avatar(avatar_data.clone()).await.unwrap();
+    let avatar_id = love.upload_avatar(avatar_data.clone()).await.unwrap().blob_id;
     let avatar_ticket = love.create_image_ticket(&avatar_id).await.unwrap();
 
     // Joy downloads Love's avatar
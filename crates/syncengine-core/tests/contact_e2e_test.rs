@@ -33,7 +33,11 @@
 //! - QUIC connection problems
 
 use syncengine_core::engine::SyncEngine;
-use syncengine_core::types::ContactStatus;
+use syncengine_core::profile::MirrorStore;
+use syncengine_core::types::{ContactStatus, PinRelationship};
+use syncengine_core::sync::{GossipConfig, SyncEvent};
+use syncengine_core::ContactEvent;
+use syncengine_core::{PacketAddress, PacketDirection, PacketEventFilter, PacketPayload};
 use tempfile::tempdir;
 use tokio::time::{sleep, Duration};
 
@@ -85,7 +89,7 @@ async fn test_two_engines_exchange_contacts_over_quic() {
     sleep(Duration::from_millis(1500)).await;
 
     // Verify Love has Joy as a contact (auto-accepted)
-    let love_contacts = love.list_contacts().unwrap();
+    let love_contacts = love.list_contacts(false).unwrap();
     println!("Love has {} contacts", love_contacts.len());
     assert_eq!(love_contacts.len(), 1, "Love should have 1 contact");
     assert_eq!(love_contacts[0].profile.display_name, "Anonymous User");
@@ -97,7 +101,7 @@ async fn test_two_engines_exchange_contacts_over_quic() {
     );
 
     // Verify Joy has Love as a contact
-    let joy_contacts = joy.list_contacts().unwrap();
+    let joy_contacts = joy.list_contacts(false).unwrap();
     println!("Joy has {} contacts", joy_contacts.len());
     assert_eq!(joy_contacts.len(), 1, "Joy should have 1 contact");
     assert_eq!(joy_contacts[0].profile.display_name, "Anonymous User");
@@ -165,8 +169,8 @@ async fn test_contact_exchange_requires_both_parties() {
     sleep(Duration::from_millis(500)).await;
 
     // Verify neither party has contacts (no exchange happened)
-    assert_eq!(love.list_contacts().unwrap().len(), 0, "Love should have no contacts");
-    assert_eq!(joy.list_contacts().unwrap().len(), 0, "Joy should have no contacts");
+    assert_eq!(love.list_contacts(false).unwrap().len(), 0, "Love should have no contacts");
+    assert_eq!(joy.list_contacts(false).unwrap().len(), 0, "Joy should have no contacts");
 
     // Verify no pending requests exist
     let (love_incoming, love_outgoing) = love.list_pending_contacts().unwrap();
@@ -216,8 +220,8 @@ async fn test_contact_topic_and_key_derivation_match() {
     sleep(Duration::from_millis(1500)).await;
 
     // Get contacts
-    let love_contacts = love.list_contacts().unwrap();
-    let joy_contacts = joy.list_contacts().unwrap();
+    let love_contacts = love.list_contacts(false).unwrap();
+    let joy_contacts = joy.list_contacts(false).unwrap();
 
     assert_eq!(love_contacts.len(), 1, "Love should have 1 contact");
     assert_eq!(joy_contacts.len(), 1, "Joy should have 1 contact");
@@ -246,3 +250,776 @@ async fn test_contact_topic_and_key_derivation_match() {
 
     println!("✅ Contact topic/key derivation is deterministic (simplified protocol)!");
 }
+
+/// Test that a contact invite bundling a realm makes the invitee a contact
+/// and a member of the realm in one step
+#[tokio::test]
+async fn test_contact_invite_with_realm_joins_both() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    // Love bundles a realm with her contact invite
+    let realm_id = love.create_realm("Garden Plans").await.unwrap();
+    let invite_code = love
+        .generate_contact_invite_with_realm(24, &realm_id)
+        .await
+        .unwrap();
+
+    // Joy accepts: contact request and realm join happen together
+    let invite = joy.decode_contact_invite(&invite_code).await.unwrap();
+    assert!(invite.realm_ticket.is_some(), "Invite should carry a realm ticket");
+    let acceptance = joy.accept_contact_invite(invite).await.unwrap();
+
+    assert_eq!(acceptance.inviter_did, love.did().unwrap().to_string());
+    assert_eq!(acceptance.joined_realm, Some(realm_id.clone()));
+    assert!(acceptance.realm_skipped.is_none());
+
+    let joined = joy.get_realm(&realm_id).await.unwrap().expect("Joy should have the realm");
+    assert_eq!(joined.name, "Garden Plans");
+
+    // Wait for auto-accept to finalize the contact on both sides
+    sleep(Duration::from_millis(1500)).await;
+    assert_eq!(love.list_contacts(false).unwrap().len(), 1, "Love should have 1 contact");
+    assert_eq!(joy.list_contacts(false).unwrap().len(), 1, "Joy should have 1 contact");
+}
+
+/// Test that an expired bundled realm ticket still lets the contact through
+#[tokio::test]
+async fn test_contact_invite_with_expired_realm_still_adds_contact() {
+    use syncengine_core::{InviteTicket, RealmId};
+
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let realm_id = love.create_realm("Expired Share").await.unwrap();
+    let invite_code = love
+        .generate_contact_invite_with_realm(24, &realm_id)
+        .await
+        .unwrap();
+
+    // Swap in a realm ticket that expired an hour ago
+    let mut invite = joy.decode_contact_invite(&invite_code).await.unwrap();
+    let expired = InviteTicket::new(&RealmId::new(), [7u8; 32], vec![])
+        .with_expiry(chrono::Utc::now().timestamp() - 3600);
+    invite.realm_ticket = Some(expired.encode().unwrap());
+
+    let acceptance = joy.accept_contact_invite(invite).await.unwrap();
+    assert!(acceptance.joined_realm.is_none());
+    assert!(
+        acceptance.realm_skipped.as_deref().unwrap_or_default().contains("expired"),
+        "Skip reason should mention expiry: {:?}",
+        acceptance.realm_skipped
+    );
+
+    sleep(Duration::from_millis(1500)).await;
+    assert_eq!(joy.list_contacts(false).unwrap().len(), 1, "Contact should still be added");
+}
+
+/// Test that rekeying a realm keeps contacts in the realm and locks out
+/// a member who never receives the new key
+#[tokio::test]
+async fn test_rekey_realm_excludes_member_without_new_key() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    joy.start_networking().await.unwrap();
+
+    let peace_dir = tempdir().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    // Joy becomes a contact and a member. Peace only joins the realm: Love and
+    // Peace pin each other's profiles so their sync traffic verifies, but
+    // there is no contact topic to carry a new key to Peace.
+    let love_signed = love.sign_and_pin_own_profile().unwrap();
+    let peace_signed = peace.sign_and_pin_own_profile().unwrap();
+    love.pin_profile(peace_signed, PinRelationship::Contact).unwrap();
+    peace.pin_profile(love_signed, PinRelationship::Contact).unwrap();
+
+    let realm_id = love.create_realm("Rekeyed Garden").await.unwrap();
+    let invite_code = love
+        .generate_contact_invite_with_realm(24, &realm_id)
+        .await
+        .unwrap();
+    let invite = joy.decode_contact_invite(&invite_code).await.unwrap();
+    joy.accept_contact_invite(invite).await.unwrap();
+    let ticket = love.create_invite(&realm_id).await.unwrap();
+    peace.join_realm(&ticket).await.unwrap();
+
+    let love_did = love.did().unwrap().to_string();
+    let joy_did = joy.did().unwrap().to_string();
+    let peace_did = peace.did().unwrap().to_string();
+    let on_roster = |engine: &SyncEngine, did: &str| {
+        engine
+            .realm_members(&realm_id)
+            .unwrap()
+            .iter()
+            .any(|m| m.did == did)
+    };
+
+    // Wait for the contact to finalize and every roster to fill in
+    let mut ready = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        love.process_pending_sync();
+        joy.process_pending_sync();
+        peace.process_pending_sync();
+        if love.list_contacts(false).unwrap().len() == 1
+            && joy.list_contacts(false).unwrap().len() == 1
+            && on_roster(&love, &joy_did)
+            && on_roster(&love, &peace_did)
+            && on_roster(&joy, &love_did)
+        {
+            ready = true;
+            break;
+        }
+    }
+    assert!(ready, "Contact and realm rosters should converge");
+
+    let outcome = love.rekey_realm(&realm_id, &[]).await.unwrap();
+    assert_eq!(outcome.epoch, 1);
+    assert_eq!(outcome.delivered, vec![joy_did.clone()]);
+    assert_eq!(outcome.undelivered, vec![peace_did.clone()]);
+
+    // Joy picks up the new key from her contact topic
+    let mut rekeyed = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100)).await;
+        joy.process_pending_sync();
+        if joy.get_realm(&realm_id).await.unwrap().unwrap().key_epoch == 1 {
+            rekeyed = true;
+            break;
+        }
+    }
+    assert!(rekeyed, "Joy should receive the new realm key");
+
+    love.add_task(&realm_id, "Sealed with the new key").await.unwrap();
+
+    let mut joy_has_task = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        love.process_pending_sync();
+        joy.process_pending_sync();
+        peace.process_pending_sync();
+        if joy
+            .list_tasks(&realm_id)
+            .unwrap()
+            .iter()
+            .any(|t| t.title == "Sealed with the new key")
+        {
+            joy_has_task = true;
+            break;
+        }
+    }
+    assert!(joy_has_task, "Current member should apply changes after rekey");
+
+    // Give Peace the same window plus a little more; it still can't decrypt
+    sleep(Duration::from_millis(1000)).await;
+    peace.process_pending_sync();
+    assert!(
+        !peace
+            .list_tasks(&realm_id)
+            .unwrap()
+            .iter()
+            .any(|t| t.title == "Sealed with the new key"),
+        "Member without the new key must not apply new changes"
+    );
+}
+
+/// Exchange contacts so that `invitee` becomes a contact of `inviter`
+async fn befriend(inviter: &mut SyncEngine, invitee: &mut SyncEngine) {
+    let invite_code = inviter.generate_contact_invite(24).await.unwrap();
+    let invite = invitee.decode_contact_invite(&invite_code).await.unwrap();
+    invitee.send_contact_request(invite).await.unwrap();
+    sleep(Duration::from_millis(1500)).await;
+}
+
+/// Test store-and-forward through a mutual peer that cannot reach the recipient yet
+///
+/// Love → Joy (relay) → Peace: Love hands a sealed message to Joy before Joy
+/// has a link to Peace. Once Joy and Peace connect, Joy forwards it over
+/// their contact topic.
+#[tokio::test]
+async fn test_packet_relayed_through_mutual_peer_reaches_recipient_on_connect() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    joy.start_networking().await.unwrap();
+
+    let peace_dir = tempdir().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.init_profile_keys().unwrap();
+    peace.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    // Love knows both; Joy and Peace have never met
+    befriend(&mut love, &mut joy).await;
+    befriend(&mut love, &mut peace).await;
+    assert_eq!(love.list_contacts(false).unwrap().len(), 2);
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+    let peace_did = peace.profile_did().unwrap();
+
+    // Love seals a message for Peace and hands it to the mutual peer only
+    let seq = love
+        .create_packet(
+            PacketPayload::DirectMessage {
+                content: "Held for you by Joy".to_string(),
+                recipient: peace_did.clone(),
+            },
+            PacketAddress::Individual(peace_did.clone()),
+        )
+        .unwrap();
+    let envelope = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    let relays = love.relay_packet_to(&peace_did, &envelope).await.unwrap();
+    assert_eq!(relays, vec![joy_did.to_string()]);
+
+    let sent = love.packet_events(PacketEventFilter::new().direction(PacketDirection::Outgoing));
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].relay_did.as_deref(), Some(joy_did.as_str()));
+    assert_eq!(sent[0].destination_did, peace_did.to_string());
+
+    // Joy holds the packet for Peace without being able to read it
+    let mut held = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100)).await;
+        if joy.mirror_head(&love_did) == Some(seq) {
+            held = true;
+            break;
+        }
+    }
+    assert!(held, "Relay should store the packet for the unreachable recipient");
+    assert!(peace.get_conversation(love_did.as_str()).unwrap().messages().is_empty());
+
+    // Peace and Joy connect; Joy forwards the held packet
+    befriend(&mut joy, &mut peace).await;
+
+    let mut delivered = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        let convo = peace.get_conversation(love_did.as_str()).unwrap();
+        if convo.messages().iter().any(|m| m.content == "Held for you by Joy") {
+            delivered = true;
+            break;
+        }
+    }
+    assert!(delivered, "Peace should receive the relayed message once connected to Joy");
+}
+
+/// Test that requests from allowlisted DIDs are accepted without a manual
+/// step, while requests from anyone else stay pending
+#[tokio::test]
+async fn test_allowlisted_contact_request_is_auto_accepted() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+
+    let peace_dir = tempdir().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let joy_did = joy.did().unwrap();
+    let peace_did = peace.did().unwrap().to_string();
+    love.set_contact_autoaccept(vec![joy_did.clone()]).unwrap();
+    assert_eq!(love.contact_autoaccept().unwrap(), vec![joy_did.clone()]);
+    let mut love_events = love.subscribe_contact_events().await.unwrap();
+
+    // Forget that Love made the invites, so only the allowlist can auto-accept
+    let invite_code = love.generate_contact_invite(24).await.unwrap();
+    let invite = joy.decode_contact_invite(&invite_code).await.unwrap();
+    love.storage().delete_generated_invite(&invite.invite_id).unwrap();
+    let peace_code = love.generate_contact_invite(24).await.unwrap();
+    let peace_invite = peace.decode_contact_invite(&peace_code).await.unwrap();
+    love.storage().delete_generated_invite(&peace_invite.invite_id).unwrap();
+
+    joy.send_contact_request(invite).await.unwrap();
+    peace.send_contact_request(peace_invite).await.unwrap();
+    sleep(Duration::from_millis(1500)).await;
+
+    let love_contacts = love.list_contacts(false).unwrap();
+    assert_eq!(love_contacts.len(), 1, "Only the allowlisted DID becomes a contact");
+    assert_eq!(love_contacts[0].peer_did, joy_did.to_string());
+    assert_eq!(joy.list_contacts(false).unwrap().len(), 1);
+
+    let (love_incoming, _) = love.list_pending_contacts().unwrap();
+    assert_eq!(love_incoming.len(), 1, "Peace's request waits for Love");
+    assert_eq!(love_incoming[0].peer_did, peace_did);
+
+    let mut auto_accepted = Vec::new();
+    while let Ok(event) = love_events.try_recv() {
+        if let ContactEvent::AutoAccepted { did, .. } = event {
+            auto_accepted.push(did);
+        }
+    }
+    assert_eq!(auto_accepted, vec![joy_did.to_string()]);
+}
+
+/// Test that removing a contact stops their packets from arriving and drops
+/// them from the contact list
+#[tokio::test]
+async fn test_removed_contact_packets_are_no_longer_received() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    befriend(&mut love, &mut joy).await;
+    assert_eq!(love.list_contacts(false).unwrap().len(), 1);
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+
+    // Messages flow while they are contacts
+    joy.send_message(love_did.as_str(), "Before removal").await.unwrap();
+    let mut delivered = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100)).await;
+        let convo = love.get_conversation(joy_did.as_str()).unwrap();
+        if convo.messages().iter().any(|m| m.content == "Before removal") {
+            delivered = true;
+            break;
+        }
+    }
+    assert!(delivered, "Love should receive Joy's message before removal");
+
+    love.remove_contact(joy_did.as_str(), false).await.unwrap();
+    assert!(love.list_contacts(false).unwrap().is_empty());
+    assert!(love.list_peer_contacts().unwrap().is_empty());
+    assert!(love.get_pinned_profile(joy_did.as_str()).unwrap().is_none());
+    assert!(love.mirror_packets_all(&joy_did).unwrap().is_empty());
+
+    // Joy still has Love as a contact, but nothing reaches Love anymore
+    joy.send_message(love_did.as_str(), "After removal").await.unwrap();
+    sleep(Duration::from_secs(3)).await;
+    assert!(
+        love.mirror_packets_all(&joy_did).unwrap().is_empty(),
+        "Packets from a removed contact must not be stored"
+    );
+}
+
+/// Test that edits made while a realm's sync is paused go out on resume
+/// and both sides converge
+#[tokio::test]
+async fn test_paused_realm_edits_are_broadcast_on_resume() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    // Pin each other's profiles so realm traffic verifies
+    let love_signed = love.sign_and_pin_own_profile().unwrap();
+    let joy_signed = joy.sign_and_pin_own_profile().unwrap();
+    love.pin_profile(joy_signed, PinRelationship::Contact).unwrap();
+    joy.pin_profile(love_signed, PinRelationship::Contact).unwrap();
+
+    let realm_id = love.create_realm("Paused Garden").await.unwrap();
+    let ticket = love.create_invite(&realm_id).await.unwrap();
+    joy.join_realm(&ticket).await.unwrap();
+
+    let has_task = |engine: &SyncEngine, title: &str| {
+        engine
+            .list_tasks(&realm_id)
+            .unwrap()
+            .iter()
+            .any(|t| t.title == title)
+    };
+
+    love.add_task(&realm_id, "Before the pause").await.unwrap();
+    let mut synced = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        love.process_pending_sync();
+        joy.process_pending_sync();
+        if has_task(&joy, "Before the pause") {
+            synced = true;
+            break;
+        }
+    }
+    assert!(synced, "Realm should sync before pausing");
+
+    love.pause_sync(&realm_id).unwrap();
+    assert!(love.is_sync_paused(&realm_id));
+    assert!(love.is_realm_syncing(&realm_id), "Pausing keeps the subscription");
+
+    love.add_task(&realm_id, "Written while paused").await.unwrap();
+    joy.add_task(&realm_id, "Sent to a paused peer").await.unwrap();
+    for _ in 0..20 {
+        sleep(Duration::from_millis(100)).await;
+        love.process_pending_sync();
+        joy.process_pending_sync();
+    }
+    assert!(!has_task(&joy, "Written while paused"), "Paused realm must not broadcast");
+    assert!(!has_task(&love, "Sent to a paused peer"), "Paused realm must not apply changes");
+
+    love.resume_sync(&realm_id).await.unwrap();
+    assert!(!love.is_sync_paused(&realm_id));
+
+    let mut converged = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        love.process_pending_sync();
+        joy.process_pending_sync();
+        if has_task(&joy, "Written while paused") && has_task(&love, "Sent to a paused peer") {
+            converged = true;
+            break;
+        }
+    }
+    assert!(converged, "Both sides should converge after resume");
+    assert_eq!(love.list_tasks(&realm_id).unwrap().len(), 3);
+    assert_eq!(joy.list_tasks(&realm_id).unwrap().len(), 3);
+}
+
+/// Test that two nodes syncing the same realm see each other as gossip
+/// neighbors on its topic
+#[tokio::test]
+async fn test_realm_members_are_topic_neighbors() {
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let realm_id = love.create_realm("Neighborly Garden").await.unwrap();
+    assert!(love.topic_neighbors(&realm_id).is_empty());
+
+    let ticket = love.create_invite(&realm_id).await.unwrap();
+    joy.join_realm(&ticket).await.unwrap();
+
+    let love_id = love.endpoint_id().unwrap();
+    let joy_id = joy.endpoint_id().unwrap();
+    let mut connected = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        if love.topic_neighbors(&realm_id) == vec![joy_id]
+            && joy.topic_neighbors(&realm_id) == vec![love_id]
+        {
+            connected = true;
+            break;
+        }
+    }
+    assert!(connected, "Both nodes should list each other as topic neighbors");
+
+    love.stop_sync(&realm_id).await.unwrap();
+    assert!(love.topic_neighbors(&realm_id).is_empty());
+}
+
+/// The network snapshot lists a syncing realm's topic with its neighbor count
+#[tokio::test]
+async fn test_network_debug_snapshot_lists_topic_neighbors() {
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let realm_id = love.create_realm("Debug Orchard").await.unwrap();
+    assert!(love.network_debug_snapshot().topics.is_empty());
+
+    let ticket = love.create_invite(&realm_id).await.unwrap();
+    joy.join_realm(&ticket).await.unwrap();
+
+    let mut snapshot = love.network_debug_snapshot();
+    for _ in 0..100 {
+        if snapshot.topics.first().is_some_and(|t| t.neighbor_count == 1) {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+        snapshot = love.network_debug_snapshot();
+    }
+    assert_eq!(snapshot.topics.len(), 1);
+    let topic = &snapshot.topics[0];
+    assert_eq!(topic.realm_id, realm_id);
+    assert_eq!(topic.realm_name, "Debug Orchard");
+    assert_eq!(topic.neighbor_count, 1);
+    assert!(snapshot.sync_active);
+
+    let json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(json["topics"][0]["realm_id"], realm_id.to_base58());
+    assert_eq!(json["topics"][0]["neighbor_count"], 1);
+}
+
+/// Test that a relay on a contact topic rejects a tampered packet from a
+/// sender whose keys it knows, and stores the genuine one
+///
+/// Love → Joy (relay) → Peace: Joy holds Love's keys from their contact
+/// exchange, so a packet claiming to be Love's must carry her signature.
+#[tokio::test]
+async fn test_contact_topic_rejects_tampered_packet() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    joy.start_networking().await.unwrap();
+
+    let peace_dir = tempdir().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.init_profile_keys().unwrap();
+    peace.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    befriend(&mut love, &mut joy).await;
+    befriend(&mut love, &mut peace).await;
+
+    let love_did = love.profile_did().unwrap();
+    let peace_did = peace.profile_did().unwrap();
+    let seq = love
+        .create_packet(
+            PacketPayload::DirectMessage {
+                content: "Signed by Love".to_string(),
+                recipient: peace_did.clone(),
+            },
+            PacketAddress::Individual(peace_did.clone()),
+        )
+        .unwrap();
+    let envelope = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+
+    let mut tampered = envelope.clone();
+    tampered.timestamp += 1;
+    love.relay_packet_to(&peace_did, &tampered).await.unwrap();
+    sleep(Duration::from_secs(2)).await;
+    assert_eq!(joy.mirror_head(&love_did), None, "Tampered packet must not be stored");
+
+    love.relay_packet_to(&peace_did, &envelope).await.unwrap();
+    let mut stored = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100)).await;
+        if joy.mirror_head(&love_did) == Some(seq) {
+            stored = true;
+            break;
+        }
+    }
+    assert!(stored, "Genuine packet should be stored by the relay");
+    let held = joy.mirror_packets_all(&love_did).unwrap();
+    assert_eq!(held.last().unwrap().hash(), envelope.hash());
+    assert!(!joy.is_provisional_packet(&love_did, seq));
+}
+
+/// Test that log catch-up on a contact topic stops at a forged entry:
+/// packets before it are stored, it and everything after are not
+#[tokio::test]
+async fn test_log_catch_up_stops_at_forged_entry() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    // Love posts before Joy knows her, then her stored packet 1 is altered
+    let love_did = love.profile_did().unwrap();
+    for _ in 0..3 {
+        love.create_packet(
+            PacketPayload::Heartbeat {
+                timestamp: chrono::Utc::now().timestamp_millis(),
+            },
+            PacketAddress::Global,
+        )
+        .unwrap();
+    }
+    let store = MirrorStore::new(love.storage().backend());
+    let mut forged = store.get_packet(&love_did, 1).unwrap().unwrap();
+    forged.ciphertext[0] ^= 1;
+    store.overwrite_packets(&[forged]).unwrap();
+
+    // Becoming contacts makes Joy ask Love for her log
+    befriend(&mut love, &mut joy).await;
+
+    let mut caught_up = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100)).await;
+        if joy.mirror_head(&love_did) == Some(0) {
+            caught_up = true;
+            break;
+        }
+    }
+    assert!(caught_up, "Joy should store Love's packet before the forged one");
+    sleep(Duration::from_secs(1)).await;
+    assert_eq!(joy.mirror_head(&love_did), Some(0), "Forged entry and later ones must not be stored");
+    assert!(!joy.is_provisional_packet(&love_did, 0));
+}
+
+/// Test that packets flooding in on a contact topic are rate limited by the
+/// background listener, not only when handed to the engine directly
+#[tokio::test]
+async fn test_contact_topic_flood_is_rate_limited() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    love.start_networking().await.unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    joy.set_gossip_config(GossipConfig {
+        packet_rate: Some(1),
+        packet_burst: Some(3),
+        ..Default::default()
+    })
+    .unwrap();
+    let mut events = joy.subscribe_events();
+    joy.start_networking().await.unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    befriend(&mut love, &mut joy).await;
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+    for i in 0..10 {
+        love.send_message(joy_did.as_str(), &format!("Message {}", i))
+            .await
+            .unwrap();
+    }
+
+    let mut limited = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100)).await;
+        if joy.packet_rate_violations(love_did.as_str()) > 0 {
+            limited = true;
+            break;
+        }
+    }
+    assert!(limited, "Flood on the contact topic should hit the rate limit");
+    assert!(joy.mirror_packets_all(&love_did).unwrap().len() < 10);
+
+    let mut announced = false;
+    while let Ok(event) = events.try_recv() {
+        if matches!(&event, SyncEvent::PeerRateLimited { did } if did == love_did.as_str()) {
+            announced = true;
+        }
+    }
+    assert!(announced, "Rate-limited contact should be announced");
+}
@@ -27,6 +27,7 @@
 //! - No flaky timing issues
 
 use syncengine_core::engine::SyncEngine;
+use syncengine_core::{InviteKind, SyncError};
 use syncengine_core::types::{ContactState, ContactStatus};
 use tempfile::tempdir;
 use tokio;
@@ -81,7 +82,7 @@ async fn test_send_contact_request() {
 
     // With auto-accept, the contact should be finalized
     // Check both possibilities: either pending (if exchange not complete) or finalized
-    let joy_contacts = joy.list_contacts().unwrap();
+    let joy_contacts = joy.list_contacts(false).unwrap();
     let (_, joy_outgoing) = joy.list_pending_contacts().unwrap();
 
     // Either we have a finalized contact OR a pending outgoing request
@@ -123,6 +124,7 @@ async fn test_accept_contact_request_storage() {
         created_at: chrono::Utc::now().timestamp(),
         encryption_keys: None,
         peer_contact_dids: vec![],
+        greeting: None,
     };
     storage.save_pending(&pending).unwrap();
 
@@ -157,6 +159,7 @@ async fn test_accept_contact_request_storage() {
         is_favorite: false,
         encryption_keys: None,
         mutual_peers: vec![],
+        nickname: None,
     };
 
     // 3. Save contact to storage
@@ -166,7 +169,7 @@ async fn test_accept_contact_request_storage() {
     storage.delete_pending(&fake_invite_id).unwrap();
 
     // Verify contact was finalized
-    let contacts = love.list_contacts().unwrap();
+    let contacts = love.list_contacts(false).unwrap();
     assert_eq!(contacts.len(), 1);
     assert_eq!(contacts[0].peer_did, "did:sync:fake_peer");
     assert_eq!(contacts[0].profile.display_name, "Joy");
@@ -202,6 +205,7 @@ async fn test_decline_contact_request() {
         created_at: chrono::Utc::now().timestamp(),
         encryption_keys: None,
         peer_contact_dids: vec![],
+        greeting: None,
     };
     storage.save_pending(&pending).unwrap();
 
@@ -217,7 +221,7 @@ async fn test_decline_contact_request() {
     assert_eq!(incoming.len(), 0);
 
     // Verify no contact was created
-    let contacts = love.list_contacts().unwrap();
+    let contacts = love.list_contacts(false).unwrap();
     assert_eq!(contacts.len(), 0);
 }
 
@@ -229,7 +233,7 @@ async fn test_list_contacts() {
     engine.init_identity().unwrap();
 
     // Initially no contacts
-    let contacts = engine.list_contacts().unwrap();
+    let contacts = engine.list_contacts(false).unwrap();
     assert_eq!(contacts.len(), 0);
 
     // Add a contact directly via storage (simulating accepted connection)
@@ -252,11 +256,12 @@ async fn test_list_contacts() {
         is_favorite: false,
         encryption_keys: None,
         mutual_peers: vec![],
+        nickname: None,
     };
     storage.save_contact(&contact).unwrap();
 
     // List contacts
-    let contacts = engine.list_contacts().unwrap();
+    let contacts = engine.list_contacts(false).unwrap();
     assert_eq!(contacts.len(), 1);
     assert_eq!(contacts[0].peer_did, "did:sync:peer1");
     assert_eq!(contacts[0].profile.display_name, "Love");
@@ -331,3 +336,46 @@ async fn test_revoked_invite_rejected() {
         .to_string()
         .contains("revoked"));
 }
+
+#[tokio::test]
+async fn test_parse_invite_uri_distinguishes_realm_and_contact_invites() {
+    let love_dir = tempdir().unwrap();
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+
+    let contact_uri = love.generate_contact_invite(24).await.unwrap();
+    let realm_id = love.create_realm("Orchard").await.unwrap();
+    let realm_uri = love.generate_invite(&realm_id).await.unwrap().encode().unwrap();
+
+    let joy_dir = tempdir().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+
+    match joy.parse_invite_uri(&realm_uri).await.unwrap() {
+        InviteKind::Realm(ticket) => assert_eq!(ticket.realm_id(), realm_id),
+        other => panic!("expected a realm invite, got {:?}", other),
+    }
+    // Deep links often arrive with a trailing newline
+    match joy.parse_invite_uri(&format!("{}\n", contact_uri)).await.unwrap() {
+        InviteKind::Contact(invite) => {
+            assert_eq!(invite.inviter_did, love.did().unwrap().to_string())
+        }
+        other => panic!("expected a contact invite, got {:?}", other),
+    }
+
+    for bad in [
+        "sync-realm:3mJr7AoU",
+        "https://example.org/invite",
+        "no scheme at all",
+        "sync-contact:invalid!base58",
+    ] {
+        assert!(
+            matches!(joy.parse_invite_uri(bad).await, Err(SyncError::InvalidInvite(_))),
+            "{} should be rejected",
+            bad
+        );
+    }
+
+    love.shutdown().await.unwrap();
+    joy.shutdown().await.unwrap();
+}
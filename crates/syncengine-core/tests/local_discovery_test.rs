@@ -0,0 +1,107 @@
+//! Local network discovery tests
+//!
+//! Two engines with `GossipConfig::local_discovery` enabled find each other
+//! over mDNS and sync a realm without exchanging an invite. Neither node is
+//! given the other's address: the only bootstrap source is the peer registry
+//! entry created by local discovery.
+//!
+//! These tests need multicast on the loopback/LAN interface. If they fail in
+//! a sandboxed environment, check that mDNS traffic is allowed.
+
+use std::time::Duration;
+
+use syncengine_core::types::PinRelationship;
+use syncengine_core::{GossipConfig, PeerSource, PeerStatus, RealmId, SyncEngine};
+use tempfile::{tempdir, TempDir};
+use tokio::time::sleep;
+
+/// Create an engine with local discovery enabled and networking started
+async fn lan_engine() -> (SyncEngine, TempDir) {
+    let dir = tempdir().unwrap();
+    let mut engine = SyncEngine::new(dir.path()).await.unwrap();
+    engine.init_identity().unwrap();
+    engine
+        .set_gossip_config(GossipConfig {
+            local_discovery: true,
+            ..Default::default()
+        })
+        .unwrap();
+    engine.start_networking().await.unwrap();
+    (engine, dir)
+}
+
+/// Check whether `engine` has found `other` on the local network
+async fn discovered(engine: &SyncEngine, other: &SyncEngine) -> bool {
+    let other_id = other.node_info().await.unwrap().node_id.unwrap();
+    engine
+        .peer_registry()
+        .list_by_status(PeerStatus::Online)
+        .unwrap()
+        .iter()
+        .any(|p| p.source == PeerSource::LocalDiscovery && hex::encode(p.endpoint_id) == other_id)
+}
+
+#[tokio::test]
+async fn test_lan_peers_discover_each_other_and_sync_realm() {
+    tracing_subscriber::fmt()
+        .with_env_filter("debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    let (mut love, _love_dir) = lan_engine().await;
+    let (mut joy, _joy_dir) = lan_engine().await;
+
+    // Both nodes must recognise each other's signatures on the realm topic
+    let love_signed = love.sign_and_pin_own_profile().unwrap();
+    let joy_signed = joy.sign_and_pin_own_profile().unwrap();
+    love.pin_profile(joy_signed, PinRelationship::Contact).unwrap();
+    joy.pin_profile(love_signed, PinRelationship::Contact).unwrap();
+
+    // Joy holds a copy of Love's realm (e.g. restored on a second device),
+    // but no bootstrap peers to reach Love with
+    let realm_id: RealmId = love.create_realm("Kitchen Garden").await.unwrap();
+    love.add_task(&realm_id, "Sow basil").await.unwrap();
+    let mut info = love.storage().load_realm(&realm_id).unwrap().unwrap();
+    info.bootstrap_peers.clear();
+    let key = love.storage().load_realm_key(&realm_id).unwrap().unwrap();
+    let doc = love.storage().load_document(&realm_id).unwrap().unwrap();
+    joy.storage().save_realm(&info).unwrap();
+    joy.storage().save_realm_key(&realm_id, &key).unwrap();
+    joy.storage().save_document(&realm_id, &doc).unwrap();
+
+    let mut found = false;
+    for _ in 0..100 {
+        if discovered(&joy, &love).await && discovered(&love, &joy).await {
+            found = true;
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(found, "Engines should find each other via local discovery");
+
+    love.start_sync(&realm_id).await.unwrap();
+    joy.open_realm(&realm_id).await.unwrap();
+    joy.start_sync(&realm_id).await.unwrap();
+
+    love.add_task(&realm_id, "Water tomatoes").await.unwrap();
+
+    let mut synced = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(100)).await;
+        love.process_pending_sync();
+        joy.process_pending_sync();
+        if joy
+            .list_tasks(&realm_id)
+            .unwrap()
+            .iter()
+            .any(|t| t.title == "Water tomatoes")
+        {
+            synced = true;
+            break;
+        }
+    }
+    assert!(synced, "Task should sync between LAN peers without an invite");
+
+    love.shutdown().await.unwrap();
+    joy.shutdown().await.unwrap();
+}
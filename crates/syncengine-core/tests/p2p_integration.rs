@@ -2145,6 +2145,12 @@ async fn test_persistence_rejoin_gossip() {
         is_shared: true,
         created_at: chrono::Utc::now().timestamp(),
         bootstrap_peers: Vec::new(),
+        read_only: false,
+        muted: false,
+        key_epoch: 0,
+        is_creator: false,
+        owner: None,
+        metadata: Default::default(),
     };
 
     storage_a.save_realm(&realm_info_a).unwrap();
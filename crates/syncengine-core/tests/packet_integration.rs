@@ -23,22 +23,25 @@
 //! 6. test_automatic_receipt — p2 auto-sends Receipt after receiving packet
 //! 7. test_depin_after_all_receipts — p1 broadcasts Depin when all receipts arrive
 //! 8. test_relay_deletes_on_depin — p3 removes packet from mirror on Depin
+//! 9. test_log_catch_up_after_offline — p2 requests what it missed from p1's log
+//! 10. test_log_integrity_break_repaired_from_mirror — p1 restores a damaged packet from p2's mirror
 
-use parking_lot::RwLock;
-use redb::Database;
 use std::sync::Arc;
 use syncengine_core::engine::SyncEngine;
 use syncengine_core::profile::{
-    MirrorStore, PacketAddress, PacketBuilder, PacketPayload, ProfileKeys, ProfileLog,
+    LogBreak, LogBreakKind, MirrorStore, PacketAddress, PacketBuilder, PacketPayload, ProfileKeys,
+    ProfileLog,
 };
-use syncengine_core::Did;
+use syncengine_core::sync::{GossipConfig, PacketSyncMessage, ProfileGossipMessage, SyncEvent};
+use syncengine_core::types::contact::{ContactInfo, ContactStatus, ProfileSnapshot};
+use syncengine_core::storage::RedbBackend;
+use syncengine_core::{Did, MessageSegment, NodeAddrBytes, SyncError, TaskId, TranscriptFormat};
 use tempfile::tempdir;
 
 /// Helper to create a database for testing
-fn create_test_db(path: &std::path::Path) -> Arc<RwLock<Database>> {
+fn create_test_db(path: &std::path::Path) -> Arc<RedbBackend> {
     let db_path = path.join("test.redb");
-    let db = Database::create(&db_path).expect("Failed to create database");
-    Arc::new(RwLock::new(db))
+    Arc::new(RedbBackend::create(&db_path).expect("Failed to create database"))
 }
 
 /// Helper to create a heartbeat payload
@@ -196,7 +199,7 @@ async fn test_create_packet_requires_keys() {
 fn test_mirror_store_basic() {
     let temp_dir = tempdir().unwrap();
     let db = create_test_db(temp_dir.path());
-    let mirror_store = MirrorStore::new(db).unwrap();
+    let mirror_store = MirrorStore::new(db);
 
     // Create a test packet manually
     let keys = ProfileKeys::generate();
@@ -230,7 +233,7 @@ fn test_mirror_store_basic() {
 fn test_mirror_store_multiple_profiles() {
     let temp_dir = tempdir().unwrap();
     let db = create_test_db(temp_dir.path());
-    let mirror_store = MirrorStore::new(db).unwrap();
+    let mirror_store = MirrorStore::new(db);
 
     // Create packets from two different profiles
     let love_keys = ProfileKeys::generate();
@@ -483,7 +486,7 @@ fn test_fork_detection_in_log() {
 fn test_mirror_detects_fork() {
     let temp_dir = tempdir().unwrap();
     let db = create_test_db(temp_dir.path());
-    let mirror_store = MirrorStore::new(db).unwrap();
+    let mirror_store = MirrorStore::new(db);
 
     let keys = ProfileKeys::generate();
     let did = keys.did();
@@ -563,10 +566,921 @@ async fn test_global_packet_roundtrip() {
     }
 }
 
+/// Test log catch-up when a contact reconnects.
+///
+/// ```text
+///     Love ──message 1──→ Joy
+///          (Joy goes offline; Love sends 5 more)
+///     Love ←─LogRequest{since: Joy's head}── Joy   (on reconnect)
+///     Love ──LogResponse{5 packets}─────────→ Joy
+/// ```
+///
+/// Both messages go through `ProfileGossipMessage` bytes, as on a contact topic.
+#[tokio::test]
+async fn test_log_catch_up_after_offline() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    save_contact_keys(&love, &joy);
+    save_contact_keys(&joy, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+    let direct_message = |content: &str| PacketPayload::DirectMessage {
+        content: content.to_string(),
+        recipient: joy_did.clone(),
+    };
+
+    // Joy receives the first message while online
+    let first = love
+        .create_packet(direct_message("Are you around?"), PacketAddress::Individual(joy_did.clone()))
+        .unwrap();
+    let envelope = love.my_log().unwrap().get(first).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(envelope).unwrap());
+    assert_eq!(joy.mirror_head(&love_did), Some(first));
+
+    // Joy goes offline; Love keeps writing
+    for i in 1..=5 {
+        love.create_packet(
+            direct_message(&format!("Offline message {}", i)),
+            PacketAddress::Individual(joy_did.clone()),
+        )
+        .unwrap();
+    }
+
+    // Joy reconnects and asks for everything after her head
+    let request = joy.log_catch_up_request(&love_did);
+    assert!(matches!(
+        &request,
+        PacketSyncMessage::LogRequest { since_sequence: Some(seq), .. } if *seq == first
+    ));
+    let request = over_the_wire(request);
+
+    let response = love.answer_log_request(&request).unwrap().expect("Love has newer packets");
+    let response = over_the_wire(response);
+    assert_eq!(joy.apply_log_response(&response).unwrap(), 5);
+
+    assert_eq!(joy.mirror_head(&love_did), Some(first + 5));
+    let convo = joy.get_conversation(love_did.as_str()).unwrap();
+    let contents: Vec<_> = convo.messages().iter().filter(|m| !m.is_mine).map(|m| m.content.clone()).collect();
+    assert_eq!(
+        contents,
+        [
+            "Are you around?",
+            "Offline message 1",
+            "Offline message 2",
+            "Offline message 3",
+            "Offline message 4",
+            "Offline message 5",
+        ]
+    );
+
+    // Once caught up, there is nothing more to send
+    let request = joy.log_catch_up_request(&love_did);
+    assert!(love.answer_log_request(&request).unwrap().is_none());
+    assert_eq!(joy.apply_log_response(&response).unwrap(), 0, "Replayed response is ignored");
+}
+
+/// Test that a corrupted packet in our own log is found and repaired from a mirror.
+///
+/// Love's stored packet 2 is damaged; the chain check stops there, and Joy's
+/// mirror of Love's log supplies the original.
+#[tokio::test]
+async fn test_log_integrity_break_repaired_from_mirror() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    save_contact_keys(&love, &joy);
+    save_contact_keys(&joy, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+    for i in 0..5 {
+        let seq = love
+            .create_packet(
+                PacketPayload::DirectMessage {
+                    content: format!("Message {}", i),
+                    recipient: joy_did.clone(),
+                },
+                PacketAddress::Individual(joy_did.clone()),
+            )
+            .unwrap();
+        let envelope = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+        assert!(joy.handle_incoming_packet(envelope).unwrap());
+    }
+
+    let report = love.verify_log_integrity().unwrap();
+    assert!(report.is_intact());
+    assert_eq!((report.head, report.checked), (Some(4), 5));
+
+    // Flip a bit in the stored copy of packet 2
+    let store = MirrorStore::new(love.storage().backend());
+    let mut damaged = store.get_packet(&love_did, 2).unwrap().unwrap();
+    damaged.ciphertext[0] ^= 1;
+    store.overwrite_packets(&[damaged]).unwrap();
+
+    let report = love.verify_log_integrity().unwrap();
+    assert_eq!(
+        report.first_break,
+        Some(LogBreak {
+            sequence: 2,
+            kind: LogBreakKind::BadSignature,
+        })
+    );
+    assert_eq!(report.checked, 2);
+    assert_eq!(report.last_intact(), Some(1));
+
+    // Only a response for our own log is accepted
+    let foreign = PacketSyncMessage::log_response(joy_did.clone(), &[]).unwrap();
+    assert!(matches!(
+        love.repair_log_from_mirror(&foreign),
+        Err(SyncError::InvalidOperation(_))
+    ));
+
+    // Joy's mirror has the original packets from the break onward
+    let request = over_the_wire(PacketSyncMessage::log_request(love_did.clone(), report.last_intact()));
+    let response = joy.answer_log_request(&request).unwrap().expect("Joy mirrors Love's log");
+    let repaired = love.repair_log_from_mirror(&over_the_wire(response)).unwrap();
+    assert!(repaired.is_intact());
+    assert_eq!(repaired.checked, 5);
+    assert_eq!(love.my_log().unwrap().len(), 5);
+}
+
+/// Send a log sync message through the contact-topic wire format
+fn over_the_wire(message: PacketSyncMessage) -> PacketSyncMessage {
+    let bytes = ProfileGossipMessage::log_sync(message).to_bytes().unwrap();
+    match ProfileGossipMessage::from_bytes(&bytes).unwrap() {
+        ProfileGossipMessage::LogSync { message } => message,
+        other => panic!("Expected LogSync, got {:?}", other),
+    }
+}
+
 // ============================================================================
-// Receipt and Depin Tests (Stubbed for Future)
+// Receipt and Depin Tests
 // ============================================================================
 
+/// Helper to save `other` as a contact of `engine`, with encryption keys,
+/// as a completed contact exchange would
+fn save_contact_keys(engine: &SyncEngine, other: &SyncEngine) {
+    let keys = other.storage().load_profile_keys().unwrap().unwrap();
+    let contact = ContactInfo {
+        peer_did: keys.did().to_string(),
+        peer_endpoint_id: [0u8; 32],
+        profile: ProfileSnapshot {
+            display_name: "Contact".to_string(),
+            subtitle: None,
+            avatar_blob_id: None,
+            bio: String::new(),
+        },
+        node_addr: NodeAddrBytes::new([0u8; 32]),
+        contact_topic: [1u8; 32],
+        contact_key: [2u8; 32],
+        accepted_at: 0,
+        last_seen: 0,
+        status: ContactStatus::Offline,
+        is_favorite: false,
+        encryption_keys: Some(keys.public_bundle().to_bytes()),
+        mutual_peers: vec![],
+        nickname: None,
+    };
+    engine.storage().save_contact(&contact).unwrap();
+}
+
+/// Test that storing a direct message sends a receipt that marks it delivered.
+///
+/// ```text
+///     Love ──DirectMessage──→ Peace
+///     Love ←────Receipt────── Peace
+/// ```
+#[tokio::test]
+async fn test_automatic_receipt_marks_message_delivered() {
+    let love_dir = tempdir().unwrap();
+    let peace_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.init_profile_keys().unwrap();
+    save_contact_keys(&love, &peace);
+    save_contact_keys(&peace, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let peace_did = peace.profile_did().unwrap();
+
+    let seq = love
+        .create_packet(
+            PacketPayload::DirectMessage {
+                content: "Did this reach you?".to_string(),
+                recipient: peace_did.clone(),
+            },
+            PacketAddress::Individual(peace_did.clone()),
+        )
+        .unwrap();
+    let message = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    let convo = love.get_conversation(peace_did.as_str()).unwrap();
+    assert!(!convo.messages()[0].delivered, "Not delivered before a receipt");
+    assert_eq!(love.gc_eligible_before(), seq, "Unacknowledged message blocks GC");
+
+    // Peace stores the message and acknowledges it in her own log
+    assert!(peace.handle_incoming_packet(message.clone()).unwrap());
+    let peace_log = peace.my_log().unwrap().entries_ordered();
+    assert_eq!(peace_log.len(), 1, "Peace should have created one receipt");
+    let receipt = peace_log[0].envelope.clone();
+    assert_eq!(
+        receipt.open(&love.storage().load_profile_keys().unwrap().unwrap()).unwrap(),
+        PacketPayload::Receipt {
+            original_sender: love_did.clone(),
+            packet_seq: seq,
+        }
+    );
+
+    // A duplicate of the message is not acknowledged again
+    assert!(!peace.handle_incoming_packet(message).unwrap());
+    assert_eq!(peace.my_log().unwrap().len(), 1);
+
+    // Love processes the receipt; the message flips to delivered
+    assert!(love.handle_incoming_packet(receipt.clone()).unwrap());
+    assert!(!love.handle_incoming_packet(receipt).unwrap());
+    let convo = love.get_conversation(peace_did.as_str()).unwrap();
+    assert_eq!(convo.messages().len(), 1);
+    assert!(convo.messages()[0].is_mine);
+    assert!(convo.messages()[0].delivered, "Message should be marked delivered");
+    assert_eq!(love.gc_eligible_before(), seq + 1, "Acknowledged message is collectable");
+
+    // Receipts are not themselves acknowledged
+    assert_eq!(love.my_log().unwrap().len(), 1);
+}
+
+/// Test that a direct message mentioning us is highlighted and notifies.
+///
+/// ```text
+///     Love ──"@joy ..."──→ Joy  (Mentioned event)
+/// ```
+#[tokio::test]
+async fn test_mention_notifies_and_highlights() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    save_contact_keys(&love, &joy);
+    save_contact_keys(&joy, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+    let mut events = joy.subscribe_events();
+
+    // A message without a mention doesn't notify
+    let seq = love
+        .create_packet(
+            PacketPayload::DirectMessage {
+                content: "Ask @nobody about it".to_string(),
+                recipient: joy_did.clone(),
+            },
+            PacketAddress::Individual(joy_did.clone()),
+        )
+        .unwrap();
+    let plain = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(plain).unwrap());
+
+    let content = format!("@{} can you take this one?", joy_did);
+    let seq = love
+        .create_packet(
+            PacketPayload::DirectMessage {
+                content: content.clone(),
+                recipient: joy_did.clone(),
+            },
+            PacketAddress::Individual(joy_did.clone()),
+        )
+        .unwrap();
+    let mention = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(mention).unwrap());
+
+    let mut mentioned = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let SyncEvent::Mentioned { sender_did, sequence } = event {
+            mentioned.push((sender_did, sequence));
+        }
+    }
+    assert_eq!(mentioned, vec![(love_did.to_string(), seq)]);
+
+    let convo = joy.get_conversation(love_did.as_str()).unwrap();
+    let messages = convo.messages();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].mentions.is_empty(), "Unknown handles stay text");
+    assert_eq!(
+        messages[0].segments(),
+        vec![MessageSegment::Text("Ask @nobody about it")]
+    );
+    assert!(messages[1].is_mentioned(&joy_did));
+    let handle = format!("@{}", joy_did);
+    assert_eq!(
+        messages[1].segments()[0],
+        MessageSegment::Mention(handle.as_str())
+    );
+}
+
+/// Test that node info reports the profile log, mirrors, contacts and outbox.
+#[tokio::test]
+async fn test_node_info_reports_packet_state() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+
+    let info = love.node_info().await.unwrap();
+    assert_eq!(info.profile_did, love.profile_did().map(|d| d.to_string()));
+    assert_eq!(info.log_head, None);
+    assert_eq!(
+        (info.mirror_count, info.contact_count, info.outbox_size),
+        (0, 0, 0)
+    );
+
+    save_contact_keys(&love, &joy);
+    save_contact_keys(&joy, &love);
+    let joy_did = joy.profile_did().unwrap();
+    let mut sent = Vec::new();
+    for content in ["One", "Two"] {
+        let seq = love
+            .create_packet(
+                PacketPayload::DirectMessage {
+                    content: content.to_string(),
+                    recipient: joy_did.clone(),
+                },
+                PacketAddress::Individual(joy_did.clone()),
+            )
+            .unwrap();
+        sent.push(love.my_log().unwrap().get(seq).unwrap().envelope.clone());
+    }
+
+    // Joy stores the first message only, so only one receipt comes back
+    assert!(joy.handle_incoming_packet(sent[0].clone()).unwrap());
+    let receipt = joy.my_log().unwrap().entries_ordered()[0].envelope.clone();
+    assert!(love.handle_incoming_packet(receipt).unwrap());
+
+    let info = love.node_info().await.unwrap();
+    assert_eq!(info.log_head, Some(1));
+    assert_eq!(info.contact_count, 1);
+    assert_eq!(info.outbox_size, 1, "Second message is still unacknowledged");
+    assert_eq!(info.mirror_count, 1, "Joy's log, from her receipt");
+
+    let info = joy.node_info().await.unwrap();
+    assert_eq!(info.log_head, Some(0));
+    assert_eq!(info.contact_count, 1);
+    assert_eq!(info.outbox_size, 0, "Receipts aren't waiting on anyone");
+    assert_eq!(info.mirror_count, 1);
+}
+
+/// Test that an exported transcript keeps message order and who said what.
+#[tokio::test]
+async fn test_export_conversation_transcript() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    save_contact_keys(&love, &joy);
+    save_contact_keys(&joy, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let joy_did = joy.profile_did().unwrap();
+
+    let dm = |content: &str, recipient: &Did| {
+        (
+            PacketPayload::DirectMessage {
+                content: content.to_string(),
+                recipient: recipient.clone(),
+            },
+            PacketAddress::Individual(recipient.clone()),
+        )
+    };
+    let (payload, address) = dm("Shall we plant the garden?", &joy_did);
+    let seq = love.create_packet(payload, address).unwrap();
+    let question = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(question).unwrap());
+
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let (payload, address) = dm("Yes, Saturday\nI'll bring seeds", &love_did);
+    let seq = joy.create_packet(payload, address).unwrap();
+    let answer = joy.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(love.handle_incoming_packet(answer).unwrap());
+
+    let joy_name = love.get_conversation(joy_did.as_str()).unwrap().display_name();
+    let markdown = love
+        .export_conversation(joy_did.as_str(), TranscriptFormat::Markdown)
+        .unwrap();
+    assert!(markdown.starts_with(&format!("# Conversation with {}\n", joy_name)));
+    assert!(markdown.contains("- Messages: 2"));
+    let question_at = markdown.find("### You · ").expect("our message is labelled");
+    let answer_at = markdown
+        .find(&format!("### {} · ", joy_name))
+        .expect("theirs is labelled");
+    assert!(question_at < answer_at, "Oldest message comes first");
+    assert!(markdown[question_at..answer_at].contains("Shall we plant the garden?"));
+    assert!(markdown[answer_at..].contains("Yes, Saturday\nI'll bring seeds"));
+
+    let mut file = Vec::new();
+    let written = love
+        .export_conversation_to(joy_did.as_str(), TranscriptFormat::PlainText, &mut file)
+        .unwrap();
+    assert_eq!(written, 2);
+    let text = String::from_utf8(file).unwrap();
+    let lines: Vec<_> = text.lines().filter(|l| l.starts_with('[')).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("] You: Shall we plant the garden?"));
+    assert!(lines[1].ends_with(&format!("] {}: Yes, Saturday", joy_name)));
+    assert!(text.contains("\n    I'll bring seeds\n"), "Continuation lines are indented");
+}
+
+/// Test that a reputation report from a trusted contact lowers our trust in its subject.
+///
+/// ```text
+///     Mallory ──misbehaves──→ Love ──ReputationReport──→ Joy
+/// ```
+#[tokio::test]
+async fn test_reputation_report_lowers_trust_elsewhere() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+    let stranger_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    let mut stranger = SyncEngine::new(stranger_dir.path()).await.unwrap();
+    stranger.init_identity().unwrap();
+    stranger.init_profile_keys().unwrap();
+    save_contact_keys(&joy, &love);
+
+    let mallory = ProfileKeys::generate().did();
+    let neutral = joy.peer_trust(mallory.as_str());
+
+    // A stranger's report can't be verified, so Joy stores it but doesn't act on it
+    let seq = stranger
+        .report_peer(&mallory, "invalid_signature", 7)
+        .await
+        .unwrap();
+    let report = stranger.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(report).unwrap());
+    assert_eq!(joy.peer_trust(mallory.as_str()), neutral);
+
+    // Love saw Mallory misbehave and warns everyone
+    let seq = love
+        .report_peer(&mallory, "invalid_signature", 7)
+        .await
+        .unwrap();
+    let love_trust = love.peer_trust(mallory.as_str());
+    assert!(love_trust < neutral);
+
+    let report = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert_eq!(
+        joy.decrypt_packet(&report),
+        Some(PacketPayload::ReputationReport {
+            subject_did: mallory.clone(),
+            violation: "invalid_signature".to_string(),
+            severity: 7,
+        })
+    );
+    assert!(joy.handle_incoming_packet(report).unwrap());
+
+    // Joy lowers Mallory's score, but by less than Love's first-hand account
+    let joy_trust = joy.peer_trust(mallory.as_str());
+    assert!(joy_trust < neutral, "Report should lower trust");
+    assert!(joy_trust > love_trust, "Second-hand reports count for less");
+
+    // Reports about ourselves are ignored
+    let joy_did = joy.profile_did().unwrap();
+    let seq = love.report_peer(&joy_did, "spam", 10).await.unwrap();
+    let report = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(report).unwrap());
+    assert_eq!(joy.peer_trust(joy_did.as_str()), neutral);
+}
+
+/// Test that gratitude is counted on both sides once it is processed.
+///
+/// ```text
+///     Love ──Gratitude──→ Peace  (given +1 / received +1)
+/// ```
+#[tokio::test]
+async fn test_gratitude_counts_for_giver_and_receiver() {
+    let love_dir = tempdir().unwrap();
+    let peace_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.init_profile_keys().unwrap();
+    save_contact_keys(&love, &peace);
+    save_contact_keys(&peace, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let peace_did = peace.profile_did().unwrap();
+    assert_eq!(love.gratitude_given(&peace_did), 0);
+    assert_eq!(peace.gratitude_received(&love_did), 0);
+
+    let task_id = TaskId::new();
+    let mut sent = Vec::new();
+    let thanks = [
+        (Some(task_id.clone()), "Thank you for mending the fence"),
+        (None, "And for the bread"),
+    ];
+    for (for_task, message) in thanks {
+        let seq = love.send_gratitude(&peace_did, for_task, message).await.unwrap();
+        sent.push(love.my_log().unwrap().get(seq).unwrap().envelope.clone());
+    }
+    assert_eq!(love.gratitude_given(&peace_did), 2);
+    assert_eq!(love.gratitude_received(&peace_did), 0);
+
+    assert_eq!(
+        peace.decrypt_packet(&sent[0]),
+        Some(PacketPayload::Gratitude {
+            for_task: Some(task_id),
+            message: "Thank you for mending the fence".to_string(),
+            recipient: peace_did.clone(),
+        })
+    );
+    for envelope in sent {
+        assert!(peace.handle_incoming_packet(envelope).unwrap());
+    }
+    assert_eq!(peace.gratitude_received(&love_did), 2);
+    assert_eq!(peace.gratitude_given(&love_did), 0);
+
+    // Thanks flow the other way too
+    let seq = peace.send_gratitude(&love_did, None, "Likewise").await.unwrap();
+    let envelope = peace.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(love.handle_incoming_packet(envelope).unwrap());
+    assert_eq!(love.gratitude_received(&peace_did), 1);
+    assert_eq!(peace.gratitude_given(&love_did), 1);
+}
+
+/// Test that work done offline is counted as pending until it syncs.
+///
+/// An unacknowledged direct message stays in the outbox until its receipt
+/// arrives; a shared realm changed while not syncing stays unsynced until
+/// sync starts and the document is broadcast.
+#[tokio::test]
+async fn test_offline_work_is_pending_until_synced() {
+    let love_dir = tempdir().unwrap();
+    let peace_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut peace = SyncEngine::new(peace_dir.path()).await.unwrap();
+    peace.init_identity().unwrap();
+    peace.init_profile_keys().unwrap();
+    save_contact_keys(&love, &peace);
+    save_contact_keys(&peace, &love);
+    let peace_did = peace.profile_did().unwrap();
+
+    let shared = love.create_realm("Orchard Crew").await.unwrap();
+    let mut info = love.storage().load_realm(&shared).unwrap().unwrap();
+    info.is_shared = true;
+    love.storage().save_realm(&info).unwrap();
+    let local = love.create_realm("Notebook").await.unwrap();
+    assert!(love.pending_sync_summary().is_empty());
+
+    // Offline: both realms change, but only the shared one has peers to reach
+    love.add_task(&shared, "Prune the plum trees").await.unwrap();
+    love.add_task(&shared, "Mulch the rows").await.unwrap();
+    love.add_task(&local, "Sketch the irrigation").await.unwrap();
+    let seq = love
+        .create_packet(
+            PacketPayload::DirectMessage {
+                content: "Bring the ladder".to_string(),
+                recipient: peace_did.clone(),
+            },
+            PacketAddress::Individual(peace_did.clone()),
+        )
+        .unwrap();
+
+    let pending = love.pending_sync_summary();
+    assert_eq!(pending.outbox_messages, 1);
+    assert_eq!(pending.unsynced_realms, 1);
+    assert_eq!(pending.total(), 2);
+
+    // Peace receives the message; her receipt empties the outbox
+    let message = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(peace.handle_incoming_packet(message).unwrap());
+    let receipt = peace.my_log().unwrap().entries_ordered()[0].envelope.clone();
+    assert!(love.handle_incoming_packet(receipt).unwrap());
+    assert_eq!(love.pending_sync_summary().outbox_messages, 0);
+
+    // Starting sync broadcasts the full document, clearing the realm
+    love.start_sync(&shared).await.unwrap();
+    assert!(love.pending_sync_summary().is_empty());
+}
+
+/// Test that a known contact's packets must carry their signature.
+///
+/// A tampered packet from a contact is rejected and not stored; a packet
+/// from a sender we hold no keys for is stored but flagged provisional.
+#[tokio::test]
+async fn test_tampered_packet_from_known_contact_is_rejected() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+    let stranger_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    let mut stranger = SyncEngine::new(stranger_dir.path()).await.unwrap();
+    stranger.init_identity().unwrap();
+    stranger.init_profile_keys().unwrap();
+    save_contact_keys(&joy, &love);
+
+    let love_did = love.profile_did().unwrap();
+    let seq = love.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+    let packet = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.verify_incoming_packet(&packet).unwrap());
+
+    let mut tampered = packet.clone();
+    tampered.timestamp += 1;
+    assert!(!joy.verify_incoming_packet(&tampered).unwrap());
+    assert!(matches!(
+        joy.handle_incoming_packet(tampered),
+        Err(SyncError::SignatureInvalid(_))
+    ));
+    assert!(joy.mirror_packets_all(&love_did).unwrap().is_empty(), "Tampered packet must not be stored");
+
+    assert!(joy.handle_incoming_packet(packet).unwrap());
+    assert!(!joy.is_provisional_packet(&love_did, seq));
+
+    // No keys for the stranger: stored, but flagged
+    let stranger_did = stranger.profile_did().unwrap();
+    let seq = stranger.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+    let packet = stranger.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(matches!(
+        joy.verify_incoming_packet(&packet),
+        Err(SyncError::ContactKeyExchangeIncomplete { .. })
+    ));
+    assert!(joy.handle_incoming_packet(packet).unwrap());
+    assert!(joy.is_provisional_packet(&stranger_did, seq));
+}
+
+/// Test that provisional packets are settled once the sender's keys arrive:
+/// genuine ones are confirmed and forged ones removed.
+#[tokio::test]
+async fn test_provisional_packets_settled_when_keys_arrive() {
+    let joy_dir = tempdir().unwrap();
+    let stranger_dir = tempdir().unwrap();
+
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    let mut stranger = SyncEngine::new(stranger_dir.path()).await.unwrap();
+    stranger.init_identity().unwrap();
+    stranger.init_profile_keys().unwrap();
+
+    let stranger_did = stranger.profile_did().unwrap();
+    let mut packets = Vec::new();
+    for _ in 0..3 {
+        let seq = stranger.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+        packets.push(stranger.my_log().unwrap().get(seq).unwrap().envelope.clone());
+    }
+    let seqs: Vec<u64> = packets.iter().map(|p| p.sequence).collect();
+    let mut forged = packets[1].clone();
+    forged.timestamp += 1;
+
+    assert!(joy.handle_incoming_packet(packets[0].clone()).unwrap());
+    assert!(joy.handle_incoming_packet(forged).unwrap());
+    assert!(joy.is_provisional_packet(&stranger_did, seqs[0]));
+    assert!(joy.is_provisional_packet(&stranger_did, seqs[1]));
+
+    // Once the stranger's keys are known, their next packet settles the rest
+    save_contact_keys(&joy, &stranger);
+    assert!(joy.handle_incoming_packet(packets[2].clone()).unwrap());
+
+    let stored: Vec<u64> = joy
+        .mirror_packets_all(&stranger_did)
+        .unwrap()
+        .iter()
+        .map(|p| p.sequence)
+        .collect();
+    assert_eq!(stored, vec![seqs[0], seqs[2]], "Forged packet must be removed");
+    assert!(!joy.is_provisional_packet(&stranger_did, seqs[0]));
+    assert!(!joy.is_provisional_packet(&stranger_did, seqs[2]));
+}
+
+#[tokio::test]
+async fn test_flooding_sender_is_rate_limited() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+    let spammer_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    let mut spammer = SyncEngine::new(spammer_dir.path()).await.unwrap();
+    spammer.init_identity().unwrap();
+    spammer.init_profile_keys().unwrap();
+    save_contact_keys(&joy, &love);
+    save_contact_keys(&joy, &spammer);
+
+    joy.set_gossip_config(GossipConfig {
+        packet_rate: Some(1),
+        packet_burst: Some(5),
+        ..Default::default()
+    })
+    .unwrap();
+    let mut events = joy.subscribe_events();
+
+    let flood: Vec<_> = (0..20)
+        .map(|_| {
+            let seq = spammer.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+            spammer.my_log().unwrap().get(seq).unwrap().envelope.clone()
+        })
+        .collect();
+    let accepted = flood
+        .into_iter()
+        .filter(|packet| joy.handle_incoming_packet(packet.clone()).unwrap())
+        .count();
+
+    // The burst gets through; at 1/s at most one more token refills meanwhile
+    assert!((5..=6).contains(&accepted), "accepted {} of 20", accepted);
+    let spammer_did = spammer.profile_did().unwrap();
+    assert_eq!(joy.packet_rate_violations(spammer_did.as_str()), 20 - accepted as u64);
+    assert_eq!(joy.mirror_packets_all(&spammer_did).unwrap().len(), accepted);
+
+    let mut rate_limited = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let SyncEvent::PeerRateLimited { did } = event {
+            rate_limited.push(did);
+        }
+    }
+    assert!(!rate_limited.is_empty());
+    assert!(rate_limited.iter().all(|did| did == spammer_did.as_str()));
+
+    // A well-behaved sender has its own budget
+    let love_did = love.profile_did().unwrap();
+    for _ in 0..3 {
+        let seq = love.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+        let packet = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+        assert!(joy.handle_incoming_packet(packet).unwrap());
+    }
+    assert_eq!(joy.packet_rate_violations(love_did.as_str()), 0);
+    assert_eq!(joy.mirror_packets_all(&love_did).unwrap().len(), 3);
+}
+
+/// Test that packets forged in a contact's name cost the contact nothing:
+/// only verified senders are rate limited or lose trust, and unverifiable
+/// packets share one budget.
+#[tokio::test]
+async fn test_forged_packets_do_not_count_against_claimed_sender() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+    let stranger_dir = tempdir().unwrap();
+    let other_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    let mut stranger = SyncEngine::new(stranger_dir.path()).await.unwrap();
+    stranger.init_identity().unwrap();
+    stranger.init_profile_keys().unwrap();
+    let mut other = SyncEngine::new(other_dir.path()).await.unwrap();
+    other.init_identity().unwrap();
+    other.init_profile_keys().unwrap();
+    save_contact_keys(&joy, &love);
+
+    joy.set_gossip_config(GossipConfig {
+        packet_rate: Some(1),
+        packet_burst: Some(2),
+        max_packet_bytes: Some(1024),
+        ..Default::default()
+    })
+    .unwrap();
+    let love_did = love.profile_did().unwrap();
+    let neutral = joy.peer_trust(love_did.as_str());
+
+    // A flood of forgeries claiming to be Love, some of them oversized
+    let seq = love.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+    let genuine = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    for i in 0..20 {
+        let mut forged = genuine.clone();
+        forged.timestamp += i + 1;
+        if i % 2 == 0 {
+            forged.ciphertext = vec![0; 2048];
+        }
+        assert!(matches!(
+            joy.handle_incoming_packet(forged),
+            Err(SyncError::SignatureInvalid(_))
+        ));
+    }
+    assert_eq!(joy.packet_rate_violations(love_did.as_str()), 0);
+    assert_eq!(joy.oversized_payloads_from(love_did.as_str()), 0);
+    assert_eq!(joy.peer_trust(love_did.as_str()), neutral);
+    assert!(joy.handle_incoming_packet(genuine).unwrap());
+
+    // Oversized packets from senders we can't verify are rejected unattributed
+    let stranger_did = stranger.profile_did().unwrap();
+    let bio = PacketPayload::ProfileUpdate {
+        display_name: None,
+        bio: Some("🌿".repeat(1024)),
+        avatar_blob_id: None,
+    };
+    let seq = stranger.create_packet(bio, PacketAddress::Global).unwrap();
+    let packet = stranger.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(matches!(
+        joy.handle_incoming_packet(packet),
+        Err(SyncError::PayloadTooLarge { limit: 1024, .. })
+    ));
+    assert_eq!(joy.oversized_payloads_from(stranger_did.as_str()), 0);
+    assert_eq!(joy.peer_trust(stranger_did.as_str()), neutral);
+
+    // Unverifiable senders draw on one shared budget
+    let mut accepted = 0;
+    for _ in 0..5 {
+        let seq = stranger.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+        let packet = stranger.my_log().unwrap().get(seq).unwrap().envelope.clone();
+        accepted += usize::from(joy.handle_incoming_packet(packet).unwrap());
+    }
+    assert!((2..=3).contains(&accepted), "accepted {} of 5", accepted);
+    let seq = other.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+    let packet = other.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(!joy.handle_incoming_packet(packet).unwrap(), "Shared budget is spent");
+    assert_eq!(joy.peer_trust(stranger_did.as_str()), neutral);
+}
+
+#[tokio::test]
+async fn test_oversized_packet_is_rejected() {
+    let love_dir = tempdir().unwrap();
+    let joy_dir = tempdir().unwrap();
+
+    let mut love = SyncEngine::new(love_dir.path()).await.unwrap();
+    love.init_identity().unwrap();
+    love.init_profile_keys().unwrap();
+    let mut joy = SyncEngine::new(joy_dir.path()).await.unwrap();
+    joy.init_identity().unwrap();
+    joy.init_profile_keys().unwrap();
+    save_contact_keys(&joy, &love);
+    joy.set_gossip_config(GossipConfig {
+        max_packet_bytes: Some(1024),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let love_did = love.profile_did().unwrap();
+    let bio = PacketPayload::ProfileUpdate {
+        display_name: None,
+        bio: Some("🌿".repeat(1024)),
+        avatar_blob_id: None,
+    };
+    let seq = love.create_packet(bio, PacketAddress::Global).unwrap();
+    let packet = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(matches!(
+        joy.handle_incoming_packet(packet),
+        Err(SyncError::PayloadTooLarge { limit: 1024, .. })
+    ));
+    assert!(joy.mirror_packets_all(&love_did).unwrap().is_empty());
+    assert_eq!(joy.oversized_payloads_from(love_did.as_str()), 1);
+
+    let seq = love.create_packet(heartbeat(), PacketAddress::Global).unwrap();
+    let packet = love.my_log().unwrap().get(seq).unwrap().envelope.clone();
+    assert!(joy.handle_incoming_packet(packet).unwrap());
+    assert_eq!(joy.oversized_payloads_from(love_did.as_str()), 1);
+}
+
 /// Test automatic receipt generation (stub - full implementation requires network).
 #[test]
 fn test_receipt_payload_creation() {
@@ -20,6 +20,7 @@
 //! 1. **Initial propagation**: Love changes name -> Joy sees it
 //! 2. **Post-restart propagation**: After restart, Love changes name -> Joy sees it
 //! 3. **Bidirectional**: Both Love and Joy update profiles, both see changes
+//! 4. **Re-announce on join**: Joy joins the profile topic -> sees Love's profile
 //!
 //! ## Key Assertions
 //!
@@ -173,8 +174,8 @@ async fn test_profile_update_propagates_to_contact() {
     sleep(Duration::from_millis(3000)).await;
 
     // Verify contacts were created
-    let love_contacts = love.list_contacts().unwrap();
-    let joy_contacts = joy.list_contacts().unwrap();
+    let love_contacts = love.list_contacts(false).unwrap();
+    let joy_contacts = joy.list_contacts(false).unwrap();
     assert_eq!(love_contacts.len(), 1, "Love should have 1 contact");
     assert_eq!(joy_contacts.len(), 1, "Joy should have 1 contact");
     println!("Contact exchange complete");
@@ -282,8 +283,8 @@ async fn test_profile_update_works_after_restart() {
         joy.send_contact_request(invite).await.unwrap();
         sleep(Duration::from_millis(2000)).await;
 
-        assert_eq!(love.list_contacts().unwrap().len(), 1);
-        assert_eq!(joy.list_contacts().unwrap().len(), 1);
+        assert_eq!(love.list_contacts(false).unwrap().len(), 1);
+        assert_eq!(joy.list_contacts(false).unwrap().len(), 1);
         println!("Contacts exchanged");
 
         // Love sets initial name and broadcasts
@@ -334,12 +335,12 @@ async fn test_profile_update_works_after_restart() {
 
     // Verify contacts persisted
     assert_eq!(
-        love.list_contacts().unwrap().len(),
+        love.list_contacts(false).unwrap().len(),
         1,
         "Love should still have contact after restart"
     );
     assert_eq!(
-        joy.list_contacts().unwrap().len(),
+        joy.list_contacts(false).unwrap().len(),
         1,
         "Joy should still have contact after restart"
     );
@@ -441,8 +442,8 @@ async fn test_manual_sync_broadcasts_profile() {
     sleep(Duration::from_millis(2000)).await;
 
     // Verify contacts
-    assert_eq!(love.list_contacts().unwrap().len(), 1);
-    assert_eq!(joy.list_contacts().unwrap().len(), 1);
+    assert_eq!(love.list_contacts(false).unwrap().len(), 1);
+    assert_eq!(joy.list_contacts(false).unwrap().len(), 1);
     println!("Contacts established");
 
     // Love updates profile
@@ -566,3 +567,65 @@ async fn test_bidirectional_profile_updates() {
 
     println!("\n=== PASSED: Bidirectional profile updates work ===\n");
 }
+
+// ============================================================================
+// Test 5: Profile Re-announced When Neighbors Join
+// ============================================================================
+
+/// Test that a node joining the profile topic gets an existing node's profile
+/// without anyone announcing by hand
+///
+/// ## Test Flow:
+/// 1. Love starts profile sync; Joy only starts networking
+/// 2. Love and Joy exchange contacts
+/// 3. Love renames herself and signs the profile, but does NOT announce it
+/// 4. Joy joins the profile topic with Love as bootstrap
+/// 5. Love sees Joy as a new neighbor and re-announces automatically
+/// 6. Verify Joy's Peer table shows "Love"
+#[tokio::test]
+async fn test_profile_reannounced_when_neighbor_joins() {
+    tracing_subscriber::fmt()
+        .with_env_filter("info,syncengine_core=debug,quinn=warn,iroh=warn")
+        .try_init()
+        .ok();
+
+    println!("\n=== Test: Profile Re-announced When Neighbor Joins ===\n");
+
+    let ctx_love = TestContext::new().expect("Failed to create Love context");
+    let mut love = ctx_love.create_engine().await.expect("Failed to create Love engine");
+    love.init_identity().unwrap();
+    love.start_networking().await.unwrap();
+    love.startup_sync().await.ok();
+
+    let ctx_joy = TestContext::new().expect("Failed to create Joy context");
+    let mut joy = ctx_joy.create_engine().await.expect("Failed to create Joy engine");
+    joy.init_identity().unwrap();
+    joy.start_networking().await.unwrap();
+
+    let love_did = love.did().unwrap().to_string();
+
+    // Exchange contacts so Joy is willing to pin Love's profile
+    let invite_code = love.generate_contact_invite(24).await.unwrap();
+    let invite = joy.decode_contact_invite(&invite_code).await.unwrap();
+    joy.send_contact_request(invite).await.unwrap();
+    sleep(Duration::from_millis(3000)).await;
+    assert_eq!(joy.list_contacts(false).unwrap().len(), 1, "Joy should have 1 contact");
+
+    // Love's new name is signed but never broadcast by hand
+    update_display_name(&mut love, "Love").await.unwrap();
+    love.sign_and_pin_own_profile().unwrap();
+    assert_ne!(get_peer_display_name(&joy, &love_did), Some("Love".to_string()));
+
+    // Joy joining the profile topic is enough for Love to re-announce
+    let love_node = love.endpoint_id().expect("Love should have an endpoint");
+    joy.start_profile_sync(vec![love_node]).await.unwrap();
+
+    wait_for_profile_update(&joy, &love_did, "Love", Duration::from_secs(15))
+        .await
+        .expect("Joy should receive Love's profile on joining the topic");
+
+    love.shutdown().await.ok();
+    joy.shutdown().await.ok();
+
+    println!("\n=== PASSED: Profile re-announced to new neighbor ===\n");
+}
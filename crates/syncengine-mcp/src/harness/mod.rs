@@ -5,7 +5,7 @@
 mod node;
 mod mesh;
 
-pub use node::{TestNode, NodeInfo, RealmState};
+pub use node::{InjectionOutcome, InjectionResult, TestNode, NodeInfo, RealmState};
 pub use mesh::{TestMesh, MeshTopology};
 
 use crate::error::{McpError, McpResult};
@@ -134,6 +134,35 @@ impl TestHarness {
         Ok(())
     }
 
+    /// Restore every edge of every stored mesh
+    ///
+    /// Undoes earlier [`disconnect_nodes`](Self::disconnect_nodes) calls
+    /// within meshes. Edges to nodes that have since been removed are
+    /// skipped. Returns the number of edges that were reconnected.
+    pub async fn reconnect_all(&self) -> McpResult<usize> {
+        let edges: Vec<(String, String)> = self
+            .meshes
+            .read()
+            .values()
+            .flat_map(|mesh| mesh.edges.clone())
+            .collect();
+
+        let mut restored = 0;
+        for (node_a, node_b) in edges {
+            let (Ok(a), Ok(b)) = (self.get_node(&node_a), self.get_node(&node_b)) else {
+                tracing::warn!(from = %node_a, to = %node_b, "Skipping mesh edge to removed node");
+                continue;
+            };
+            if !a.is_connected_to(&node_b) {
+                a.connect_to(&b).await?;
+                restored += 1;
+            }
+        }
+
+        tracing::info!(restored, "Healed mesh connections");
+        Ok(restored)
+    }
+
     /// Create a shared realm visible to multiple nodes
     pub async fn create_shared_realm(
         &self,
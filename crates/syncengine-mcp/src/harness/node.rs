@@ -4,9 +4,9 @@ use crate::error::{McpError, McpResult};
 use parking_lot::RwLock as SyncRwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use syncengine_core::{RealmId, SyncEngine, SyncEvent};
-use tempfile::TempDir;
+use std::time::Instant;
+use syncengine_core::sync::MessageCapture;
+use syncengine_core::{PeerInfo, RealmId, RealmMember, SyncEngine, SyncEvent};
 use tokio::sync::{broadcast, RwLock};
 
 /// Information about a test node
@@ -24,6 +24,14 @@ pub struct NodeInfo {
     pub peers: Vec<String>,
     /// Whether sync is running
     pub sync_active: bool,
+    /// Milliseconds since the node was created
+    pub uptime_ms: u64,
+    /// Realm messages broadcast by the node
+    pub messages_sent: u64,
+    /// Realm messages received by the node
+    pub messages_received: u64,
+    /// Number of realm topics the node is subscribed to
+    pub subscription_count: usize,
 }
 
 /// State of a realm on a test node
@@ -41,30 +49,54 @@ pub struct RealmState {
     pub peers: Vec<String>,
 }
 
+/// How a node handled raw bytes injected into its receive path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionOutcome {
+    /// The envelope opened and was processed as a sync message
+    Accepted,
+    /// The bytes were dropped as malformed, forged or undecryptable
+    Rejected,
+    /// The node failed to handle the bytes (e.g. realm not open)
+    Error,
+}
+
+/// Result of injecting raw bytes into a node's gossip receive path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InjectionResult {
+    /// Node the bytes were injected into
+    pub node_id: String,
+    /// Realm ID (hex)
+    pub realm_id: String,
+    /// Number of bytes injected
+    pub byte_count: usize,
+    /// How the node handled them
+    pub outcome: InjectionOutcome,
+    /// Kind of sync message, if accepted
+    pub message_kind: Option<String>,
+    /// Error message, if handling failed
+    pub error: Option<String>,
+}
+
 /// A test node with ephemeral storage
 pub struct TestNode {
     /// Node name
     name: String,
-    /// Temporary directory for storage
-    _temp_dir: TempDir,
-    /// Data directory path
-    data_dir: PathBuf,
     /// The sync engine (async RwLock for async operations)
     engine: RwLock<SyncEngine>,
     /// Event receiver for sync events
     _event_rx: broadcast::Receiver<SyncEvent>,
     /// Connected peer node IDs (sync RwLock for quick access)
     connected_peers: SyncRwLock<HashMap<String, String>>, // node_name -> iroh_node_id
+    /// When the node was created
+    created_at: Instant,
 }
 
 impl TestNode {
     /// Create a new test node with ephemeral storage
     pub async fn new(name: String) -> McpResult<Self> {
-        let temp_dir = TempDir::new().map_err(|e| McpError::NodeCreation(e.to_string()))?;
-        let data_dir = temp_dir.path().to_path_buf();
-
-        // Create engine with ephemeral storage
-        let mut engine = SyncEngine::new(&data_dir)
+        // Everything lives in memory, so nothing needs cleaning up afterwards
+        let mut engine = SyncEngine::new_ephemeral()
             .await
             .map_err(|e| McpError::NodeCreation(format!("Engine: {}", e)))?;
 
@@ -76,15 +108,14 @@ impl TestNode {
         // Subscribe to events
         let event_rx = engine.subscribe_events();
 
-        tracing::debug!(name = %name, path = ?data_dir, "Created test node");
+        tracing::debug!(name = %name, "Created test node");
 
         Ok(Self {
             name,
-            _temp_dir: temp_dir,
-            data_dir,
             engine: RwLock::new(engine),
             _event_rx: event_rx,
             connected_peers: SyncRwLock::new(HashMap::new()),
+            created_at: Instant::now(),
         })
     }
 
@@ -93,11 +124,6 @@ impl TestNode {
         &self.name
     }
 
-    /// Get data directory
-    pub fn data_dir(&self) -> &PathBuf {
-        &self.data_dir
-    }
-
     /// Get node info
     pub async fn info(&self) -> NodeInfo {
         let engine = self.engine.read().await;
@@ -108,6 +134,7 @@ impl TestNode {
             .iter()
             .map(|r| hex::encode(r.id.as_bytes()))
             .collect();
+        let counts = engine.message_counts();
 
         NodeInfo {
             name: self.name.clone(),
@@ -116,6 +143,10 @@ impl TestNode {
             realms,
             peers: self.connected_peers.read().keys().cloned().collect(),
             sync_active: engine.is_networking_active(),
+            uptime_ms: self.created_at.elapsed().as_millis() as u64,
+            messages_sent: counts.sent,
+            messages_received: counts.received,
+            subscription_count: engine.syncing_realms().len(),
         }
     }
 
@@ -220,7 +251,7 @@ impl TestNode {
 
     /// Get realm state
     pub async fn realm_state(&self, realm_id: &RealmId) -> McpResult<RealmState> {
-        let engine = self.engine.read().await;
+        let mut engine = self.engine.write().await;
 
         let realm_info = engine
             .storage()
@@ -232,8 +263,12 @@ impl TestNode {
 
         let tasks = engine.list_tasks(realm_id).unwrap_or_default();
 
-        // For now, we don't have direct access to heads
-        let heads = vec![];
+        let heads = engine
+            .realm_heads(realm_id)
+            .await?
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
 
         Ok(RealmState {
             realm_id: hex::encode(realm_id.as_bytes()),
@@ -251,6 +286,26 @@ impl TestNode {
         Ok(task_id)
     }
 
+    /// Get the hashes of every change in a realm, hex encoded and sorted
+    pub async fn change_hashes(&self, realm_id: &RealmId) -> McpResult<Vec<String>> {
+        let mut engine = self.engine.write().await;
+        let hashes = engine
+            .realm_change_hashes(realm_id)
+            .await?
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        Ok(hashes)
+    }
+
+    /// Get a realm's membership roster, after applying pending sync messages
+    pub async fn realm_members(&self, realm_id: &RealmId) -> McpResult<Vec<RealmMember>> {
+        let mut engine = self.engine.write().await;
+        engine.process_pending_sync();
+        let members = engine.realm_members(realm_id)?;
+        Ok(members)
+    }
+
     /// Get all tasks in a realm
     pub async fn list_tasks(&self, realm_id: &RealmId) -> McpResult<Vec<syncengine_core::Task>> {
         let engine = self.engine.read().await;
@@ -260,14 +315,55 @@ impl TestNode {
 
     /// Shutdown the node
     /// Note: This doesn't call engine.shutdown() since that consumes the engine.
-    /// The temp directory cleanup will happen when TestNode is dropped.
+    /// The in-memory storage is freed when TestNode is dropped.
     pub async fn shutdown(&self) -> McpResult<()> {
         // Stop networking if active
-        // The temp directory will be cleaned up when the node is dropped
         tracing::debug!(name = %self.name, "Shutting down test node");
         Ok(())
     }
 
+    /// Feed raw bytes into the node's gossip receive path for a realm
+    pub async fn inject_raw_message(&self, realm_id: &RealmId, bytes: Vec<u8>) -> InjectionResult {
+        let byte_count = bytes.len();
+        let handled = self.engine.write().await.inject_incoming(realm_id, bytes);
+        let (outcome, message_kind, error) = match handled {
+            Ok(Some(kind)) => (InjectionOutcome::Accepted, Some(kind.to_string()), None),
+            Ok(None) => (InjectionOutcome::Rejected, None, None),
+            Err(e) => (InjectionOutcome::Error, None, Some(e.to_string())),
+        };
+
+        InjectionResult {
+            node_id: self.name.clone(),
+            realm_id: hex::encode(realm_id.as_bytes()),
+            byte_count,
+            outcome,
+            message_kind,
+            error,
+        }
+    }
+
+    /// Start recording the node's realm gossip traffic
+    pub async fn start_capture(&self) -> McpResult<()> {
+        self.engine.write().await.start_capture()?;
+        Ok(())
+    }
+
+    /// Stop recording and return the captured traffic
+    pub async fn stop_capture(&self) -> McpResult<MessageCapture> {
+        self.engine
+            .write()
+            .await
+            .stop_capture()
+            .ok_or_else(|| McpError::InvalidOperation(format!("No capture running on {}", self.name)))
+    }
+
+    /// Re-drive the node with a capture taken on another node
+    ///
+    /// Returns the number of inbound envelopes replayed.
+    pub async fn replay_capture(&self, capture: &MessageCapture) -> McpResult<usize> {
+        Ok(self.engine.write().await.replay_capture(capture).await?)
+    }
+
     /// Subscribe to sync events
     pub async fn subscribe(&self) -> broadcast::Receiver<SyncEvent> {
         self.engine.read().await.subscribe_events()
@@ -282,6 +378,21 @@ impl TestNode {
     pub fn is_connected_to(&self, peer_name: &str) -> bool {
         self.connected_peers.read().contains_key(peer_name)
     }
+
+    /// Get this node's peer registry entry for a connected peer
+    ///
+    /// Returns `None` if the peer is not connected, has no real endpoint ID,
+    /// or has not been recorded in the registry yet.
+    pub async fn peer_link(&self, peer_name: &str) -> Option<PeerInfo> {
+        let node_id = self.connected_peers.read().get(peer_name).cloned()?;
+        let engine = self.engine.read().await;
+        engine
+            .peer_registry()
+            .list_all()
+            .ok()?
+            .into_iter()
+            .find(|p| p.public_key().to_string() == node_id)
+    }
 }
 
 #[cfg(test)]
@@ -297,6 +408,26 @@ mod tests {
         assert!(info.did.is_some());
     }
 
+    #[tokio::test]
+    async fn test_info_reports_runtime_stats() {
+        let node = TestNode::new("love".to_string()).await.unwrap();
+        let realm_id = node.create_realm("Garden").await.unwrap();
+        let info = node.info().await;
+        assert_eq!(info.subscription_count, 0);
+        assert_eq!(info.messages_sent, 0);
+
+        node.start_sync(&realm_id).await.unwrap();
+        let before = node.info().await;
+        assert_eq!(before.subscription_count, 1);
+
+        node.add_task(&realm_id, "Water the beans").await.unwrap();
+        let after = node.info().await;
+        assert!(after.messages_sent > before.messages_sent);
+        assert_eq!(after.messages_received, 0);
+        assert!(after.uptime_ms > 0);
+        assert!(after.uptime_ms >= before.uptime_ms);
+    }
+
     #[tokio::test]
     async fn test_create_realm() {
         let node = TestNode::new("test_node".to_string()).await.unwrap();
@@ -307,6 +438,254 @@ mod tests {
         assert_eq!(state.task_count, 0);
     }
 
+    #[tokio::test]
+    async fn test_injected_malformed_envelopes_are_rejected() {
+        use syncengine_core::sync::{SyncEnvelope, SyncMessage};
+
+        let node = TestNode::new("love".to_string()).await.unwrap();
+        let realm_id = node.create_realm("Test Realm").await.unwrap();
+        let realm_key = node.engine().await.storage().load_realm_key(&realm_id).unwrap().unwrap();
+
+        // A well-formed envelope from a signer the node has never heard of
+        let stranger = syncengine_core::HybridKeypair::generate();
+        let stranger_did = syncengine_core::Did::from_public_key(&stranger.public_key()).to_string();
+        let message = SyncMessage::SyncRequest {
+            realm_id: realm_id.clone(),
+        };
+        let envelope = SyncEnvelope::seal(&message, &stranger_did, &realm_key, |data| {
+            stranger.sign(data).to_bytes()
+        })
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+        let inputs = vec![
+            envelope[..envelope.len() / 2].to_vec(),
+            envelope[..1].to_vec(),
+            Vec::new(),
+            vec![0xFF; 512],
+            envelope,
+        ];
+        for bytes in inputs {
+            let len = bytes.len();
+            let result = node.inject_raw_message(&realm_id, bytes).await;
+            assert_eq!(result.outcome, InjectionOutcome::Rejected, "{} bytes", len);
+            assert_eq!(result.byte_count, len);
+        }
+        assert_eq!(node.realm_state(&realm_id).await.unwrap().task_count, 0);
+
+        let unknown_realm = RealmId::new();
+        let result = node.inject_raw_message(&unknown_realm, vec![0; 8]).await;
+        assert_eq!(result.outcome, InjectionOutcome::Error);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_capture_reaches_same_heads() {
+        use syncengine_core::sync::{SyncEnvelope, SyncMessage};
+        use syncengine_core::types::{PinRelationship, SignedProfile, UserProfile};
+
+        let love = TestNode::new("love".to_string()).await.unwrap();
+        let realm_id = love.create_realm("Garden").await.unwrap();
+        let realm_key = love.engine().await.storage().load_realm_key(&realm_id).unwrap().unwrap();
+        let base_doc = love.engine().await.storage().load_document(&realm_id).unwrap().unwrap();
+
+        // Joy is a known peer who edits the realm elsewhere
+        let joy = syncengine_core::HybridKeypair::generate();
+        let joy_did = syncengine_core::Did::from_public_key(&joy.public_key()).to_string();
+        let profile = UserProfile::new(joy_did.clone(), "Joy".to_string());
+        love.engine()
+            .await
+            .pin_profile(SignedProfile::sign(&profile, &joy), PinRelationship::Contact)
+            .unwrap();
+        let editor = TestNode::new("editor".to_string()).await.unwrap();
+        {
+            let mut engine = editor.engine_mut().await;
+            let info = love.engine().await.storage().load_realm(&realm_id).unwrap().unwrap();
+            engine.storage().save_realm(&info).unwrap();
+            engine.storage().save_realm_key(&realm_id, &realm_key).unwrap();
+            engine.storage().save_document(&realm_id, &base_doc).unwrap();
+            engine.open_realm(&realm_id).await.unwrap();
+        }
+
+        love.start_capture().await.unwrap();
+        for title in ["Water the beans", "Weed the carrots"] {
+            editor.add_task(&realm_id, title).await.unwrap();
+            let document = editor.engine().await.storage().load_document(&realm_id).unwrap().unwrap();
+            let message = SyncMessage::SyncResponse {
+                realm_id: realm_id.clone(),
+                document,
+            };
+            let envelope = SyncEnvelope::seal(&message, &joy_did, &realm_key, |data| {
+                joy.sign(data).to_bytes()
+            })
+            .unwrap();
+            let result = love.inject_raw_message(&realm_id, envelope.to_bytes().unwrap()).await;
+            assert_eq!(result.outcome, InjectionOutcome::Accepted);
+        }
+        let capture = love.stop_capture().await.unwrap();
+        assert_eq!(capture.inbound().count(), 2);
+        assert!(love.stop_capture().await.is_err());
+
+        // The capture survives a trip through a report
+        let json = serde_json::to_string(&capture).unwrap();
+        let capture: MessageCapture = serde_json::from_str(&json).unwrap();
+
+        let fresh = TestNode::new("fresh".to_string()).await.unwrap();
+        assert_eq!(fresh.replay_capture(&capture).await.unwrap(), 2);
+
+        let original = love.realm_state(&realm_id).await.unwrap();
+        let replayed = fresh.realm_state(&realm_id).await.unwrap();
+        assert_eq!(original.task_count, 2);
+        assert_eq!(replayed.task_count, 2);
+        assert_eq!(replayed.heads, original.heads);
+    }
+
+    #[tokio::test]
+    async fn test_invited_nodes_appear_in_each_others_roster() {
+        use syncengine_core::types::PinRelationship;
+
+        let love = TestNode::new("love".to_string()).await.unwrap();
+        let joy = TestNode::new("joy".to_string()).await.unwrap();
+        let love_did = love.info().await.did.unwrap();
+        let joy_did = joy.info().await.did.unwrap();
+
+        // Each knows the other's keys, so their envelopes verify
+        let love_profile = love.engine_mut().await.sign_and_pin_own_profile().unwrap();
+        let joy_profile = joy.engine_mut().await.sign_and_pin_own_profile().unwrap();
+        love.engine().await.pin_profile(joy_profile, PinRelationship::Contact).unwrap();
+        joy.engine().await.pin_profile(love_profile, PinRelationship::Contact).unwrap();
+        love.engine_mut().await.start_networking().await.unwrap();
+        joy.engine_mut().await.start_networking().await.unwrap();
+        let love_addr = love.engine().await.node_addr().unwrap();
+        let joy_addr = joy.engine().await.node_addr().unwrap();
+        love.engine().await.add_peer_node_addr(&joy_addr).unwrap();
+        joy.engine().await.add_peer_node_addr(&love_addr).unwrap();
+
+        let realm_id = love.create_realm("Shared Roster").await.unwrap();
+        let invite = love.generate_invite(&realm_id).await.unwrap();
+        joy.join_via_invite(&invite).await.unwrap();
+
+        let lists = |members: &[RealmMember], did: &str| members.iter().any(|m| m.did == did);
+        let mut rosters = (vec![], vec![]);
+        for _ in 0..100 {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            rosters = (
+                love.realm_members(&realm_id).await.unwrap(),
+                joy.realm_members(&realm_id).await.unwrap(),
+            );
+            if lists(&rosters.0, &joy_did) && lists(&rosters.1, &love_did) {
+                break;
+            }
+        }
+        let (love_roster, joy_roster) = rosters;
+        assert!(lists(&love_roster, &joy_did), "Love should list Joy as a member");
+        assert!(lists(&joy_roster, &love_did), "Joy should list Love as a member");
+
+        // Each roster holds exactly the two participants
+        for members in [&love_roster, &joy_roster] {
+            assert_eq!(members.len(), 2);
+            assert!(members.iter().all(|m| m.last_seen >= m.joined_at));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_linked_devices_share_one_identity() {
+        use std::time::Duration;
+        use syncengine_core::Did;
+
+        async fn wait_for(mut done: impl AsyncFnMut() -> bool) -> bool {
+            for _ in 0..150 {
+                if done().await {
+                    return true;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            false
+        }
+        async fn has_message(node: &TestNode, with: &Did, content: &str) -> bool {
+            let engine = node.engine().await;
+            engine
+                .get_conversation(with.as_str())
+                .is_ok_and(|c| c.messages().iter().any(|m| m.content == content))
+        }
+
+        // Peace befriends Love
+        let love = TestNode::new("love".to_string()).await.unwrap();
+        let peace = TestNode::new("peace".to_string()).await.unwrap();
+        for node in [&love, &peace] {
+            let mut engine = node.engine_mut().await;
+            engine.init_profile_keys().unwrap();
+            engine.start_networking().await.unwrap();
+        }
+        let love_did = love.engine().await.did().unwrap();
+        let peace_did = peace.engine().await.did().unwrap();
+        let invite = love.engine_mut().await.generate_contact_invite(24).await.unwrap();
+        {
+            let mut engine = peace.engine_mut().await;
+            let invite = engine.decode_contact_invite(&invite).await.unwrap();
+            engine.send_contact_request(invite).await.unwrap();
+        }
+        assert!(
+            wait_for(async || !love.engine().await.list_contacts(false).unwrap().is_empty()).await,
+            "Love and Peace should become contacts"
+        );
+
+        // Love links a laptop to the same identity
+        let link = love.engine().await.export_identity_encrypted().unwrap();
+        let laptop = TestNode::new("love-laptop".to_string()).await.unwrap();
+        {
+            let mut engine = laptop.engine_mut().await;
+            let device = engine
+                .import_identity_encrypted(&link.bundle, &link.link_key, "laptop")
+                .unwrap();
+            assert!(!device.is_primary());
+            assert_eq!(engine.did().unwrap(), love_did);
+            engine.startup_sync().await.unwrap();
+        }
+
+        // Peace learns from the signed device list that both endpoints are Love
+        let love_endpoint = love.engine().await.endpoint_id().unwrap();
+        let laptop_endpoint = laptop.engine().await.endpoint_id().unwrap();
+        assert!(
+            wait_for(async || {
+                peace.engine().await.is_device_of(&love_did, &laptop_endpoint).unwrap()
+            })
+            .await,
+            "Peace should learn the laptop is one of Love's devices"
+        );
+        {
+            let engine = peace.engine().await;
+            assert!(engine.is_device_of(&love_did, &love_endpoint).unwrap());
+            assert!(!engine.is_device_of(&peace_did, &laptop_endpoint).unwrap());
+            assert_eq!(engine.device_list(&love_did).unwrap().unwrap().list.devices.len(), 2);
+        }
+
+        // A message to Love's DID reaches both devices
+        peace.engine_mut().await.send_message(love_did.as_str(), "Hello Love").await.unwrap();
+        assert!(wait_for(async || has_message(&love, &peace_did, "Hello Love").await).await);
+        assert!(
+            wait_for(async || has_message(&laptop, &peace_did, "Hello Love").await).await,
+            "The linked device should receive messages sent to the DID"
+        );
+
+        // Both devices are valid senders for Love's DID
+        love.engine_mut().await.send_message(peace_did.as_str(), "From the desktop").await.unwrap();
+        laptop.engine_mut().await.send_message(peace_did.as_str(), "From the laptop").await.unwrap();
+        for content in ["From the desktop", "From the laptop"] {
+            assert!(
+                wait_for(async || has_message(&peace, &love_did, content).await).await,
+                "Peace should accept {:?} as from Love",
+                content
+            );
+        }
+        let conversation = peace.engine().await.get_conversation(love_did.as_str()).unwrap();
+        assert!(conversation
+            .messages()
+            .iter()
+            .filter(|m| m.content.starts_with("From the"))
+            .all(|m| m.sender_did == love_did.as_str()));
+    }
+
     #[tokio::test]
     async fn test_connect_nodes() {
         let node_a = TestNode::new("love".to_string()).await.unwrap();
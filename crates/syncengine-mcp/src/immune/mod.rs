@@ -6,6 +6,7 @@ use crate::error::{McpError, McpResult};
 use crate::harness::TestHarness;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Bad behavior types that can be simulated
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -48,8 +49,30 @@ impl BadBehavior {
             ))),
         }
     }
+
+    /// How serious the behavior is (1-10)
+    ///
+    /// Scales the trust penalty a peer takes when the behavior is detected.
+    pub fn severity(&self) -> u8 {
+        match self {
+            BadBehavior::MalformedMessages => 2,
+            BadBehavior::ConnectionChurn => 3,
+            BadBehavior::MessageSpam => 4,
+            BadBehavior::InviteSpam => 4,
+            BadBehavior::ReplayAttack => 6,
+            BadBehavior::FakePeerAnnouncement => 6,
+            BadBehavior::InvalidSignatures => 7,
+            BadBehavior::DocumentBomb => 9,
+        }
+    }
 }
 
+/// Trust lost per point of violation severity
+const TRUST_PENALTY_PER_SEVERITY: f64 = 0.02;
+
+/// Trust score below which a peer is quarantined
+const QUARANTINE_TRUST_THRESHOLD: f64 = 0.2;
+
 /// Result of rate limit check
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitResult {
@@ -148,6 +171,61 @@ pub struct AnomalyDetectionResult {
     pub features: HashMap<String, f64>,
 }
 
+/// Reputations and quarantine entries saved between debugger sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImmuneState {
+    /// Reputation per peer
+    pub reputations: HashMap<String, PeerReputation>,
+    /// Quarantined peers
+    pub quarantine: Vec<QuarantineEntry>,
+}
+
+/// Tuning for [`ImmuneTester::test_anomaly_detection`]
+///
+/// A pattern's anomaly score is the weighted sum of its features; it is
+/// flagged when the score exceeds `detection_threshold`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Score above which a pattern is anomalous
+    pub detection_threshold: f64,
+    /// Confidence above which the peer should be quarantined rather than rate limited
+    pub quarantine_threshold: f64,
+    /// Weight of the message rate feature (normalised to 0.0 - 1.0)
+    pub message_rate_weight: f64,
+    /// Weight of the repetitive content feature
+    pub repetitiveness_weight: f64,
+    /// Weight of the suspicious keyword feature
+    pub suspicious_keywords_weight: f64,
+    /// Words that mark a pattern as suspicious
+    pub suspicious_keywords: Vec<String>,
+}
+
+impl AnomalyConfig {
+    /// Add words to the suspicious keyword list
+    pub fn with_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.suspicious_keywords
+            .extend(keywords.into_iter().map(Into::into));
+        self
+    }
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            detection_threshold: 0.5,
+            quarantine_threshold: 0.8,
+            message_rate_weight: 1.0,
+            repetitiveness_weight: 0.3,
+            suspicious_keywords_weight: 0.4,
+            suspicious_keywords: vec!["attack".into(), "flood".into()],
+        }
+    }
+}
+
 /// Immune system tester
 pub struct ImmuneTester {
     /// Simulated rate limiters per node-peer pair
@@ -156,6 +234,8 @@ pub struct ImmuneTester {
     reputations: HashMap<String, PeerReputation>,
     /// Quarantine list
     quarantine: Vec<QuarantineEntry>,
+    /// Anomaly detection tuning
+    anomaly_config: AnomalyConfig,
 }
 
 struct RateLimitState {
@@ -168,13 +248,40 @@ struct RateLimitState {
 impl ImmuneTester {
     /// Create a new immune system tester
     pub fn new() -> Self {
+        Self::new_with_anomaly_config(AnomalyConfig::default())
+    }
+
+    /// Create a tester with custom anomaly detection tuning
+    pub fn new_with_anomaly_config(anomaly_config: AnomalyConfig) -> Self {
         Self {
             rate_limits: HashMap::new(),
             reputations: HashMap::new(),
             quarantine: Vec::new(),
+            anomaly_config,
         }
     }
 
+    /// Current anomaly detection tuning
+    pub fn anomaly_config(&self) -> &AnomalyConfig {
+        &self.anomaly_config
+    }
+
+    /// Replace the anomaly detection tuning
+    pub fn set_anomaly_config(&mut self, anomaly_config: AnomalyConfig) {
+        self.anomaly_config = anomaly_config;
+    }
+
+    /// Add words to the suspicious keyword list
+    pub fn register_keywords<I, S>(&mut self, keywords: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.anomaly_config
+            .suspicious_keywords
+            .extend(keywords.into_iter().map(Into::into));
+    }
+
     /// Trigger rate limit for testing
     pub fn trigger_rate_limit(
         &mut self,
@@ -344,21 +451,26 @@ impl ImmuneTester {
                 }
             });
 
+            let severity = behavior.severity();
             rep.violation_count += 1;
-            rep.trust_score = (rep.trust_score - 0.1).max(0.0);
+            rep.trust_score =
+                (rep.trust_score - f64::from(severity) * TRUST_PENALTY_PER_SEVERITY).max(0.0);
             rep.violations.push(ViolationRecord {
                 violation_type: format!("{:?}", behavior),
                 timestamp: chrono::Utc::now().to_rfc3339(),
-                severity: 5,
+                severity,
                 details: Some(response.clone()),
             });
 
             // Quarantine if trust score too low
-            if rep.trust_score < 0.2 {
+            if rep.trust_score < QUARANTINE_TRUST_THRESHOLD && !rep.quarantined {
                 rep.quarantined = true;
                 self.quarantine.push(QuarantineEntry {
                     peer_id: peer_id.to_string(),
-                    reason: format!("Trust score below threshold after {:?}", behavior),
+                    reason: format!(
+                        "Trust score below threshold after {:?} (severity {})",
+                        behavior, severity
+                    ),
                     started_at: chrono::Utc::now().to_rfc3339(),
                     expires_at: None,
                     permanent: false,
@@ -393,21 +505,27 @@ impl ImmuneTester {
         // Simple pattern matching for demonstration
         let mut features: HashMap<String, f64> = HashMap::new();
 
+        let config = &self.anomaly_config;
+
         // Extract features from pattern
         let message_rate = pattern.matches("spam").count() as f64 * 100.0;
         let is_repetitive = pattern.len() < 10 && pattern.chars().collect::<std::collections::HashSet<_>>().len() < 3;
-        let has_suspicious_keywords = pattern.contains("attack") || pattern.contains("flood");
+        let lowered = pattern.to_lowercase();
+        let has_suspicious_keywords = config
+            .suspicious_keywords
+            .iter()
+            .any(|k| !k.is_empty() && lowered.contains(&k.to_lowercase()));
 
         features.insert("message_rate".into(), message_rate);
         features.insert("repetitiveness".into(), if is_repetitive { 1.0 } else { 0.0 });
         features.insert("suspicious_keywords".into(), if has_suspicious_keywords { 1.0 } else { 0.0 });
 
         // Calculate anomaly score
-        let anomaly_score = (message_rate / 100.0).min(1.0)
-            + if is_repetitive { 0.3 } else { 0.0 }
-            + if has_suspicious_keywords { 0.4 } else { 0.0 };
+        let anomaly_score = (message_rate / 100.0).min(1.0) * config.message_rate_weight
+            + if is_repetitive { config.repetitiveness_weight } else { 0.0 }
+            + if has_suspicious_keywords { config.suspicious_keywords_weight } else { 0.0 };
 
-        let detected = anomaly_score > 0.5;
+        let detected = anomaly_score > config.detection_threshold;
         let confidence = anomaly_score.min(1.0);
 
         let anomaly_type = if detected {
@@ -423,9 +541,9 @@ impl ImmuneTester {
         };
 
         let recommended_response = if detected {
-            if confidence > 0.8 {
+            if confidence > config.quarantine_threshold {
                 "quarantine_peer".into()
-            } else if confidence > 0.5 {
+            } else if confidence > config.detection_threshold {
                 "rate_limit_peer".into()
             } else {
                 "monitor_peer".into()
@@ -444,6 +562,26 @@ impl ImmuneTester {
         }
     }
 
+    /// Save reputations and quarantine entries to a JSON file
+    ///
+    /// Rate limiter windows are short-lived and aren't saved.
+    pub fn save(&self, path: impl AsRef<Path>) -> McpResult<ImmuneState> {
+        let state = ImmuneState {
+            reputations: self.reputations.clone(),
+            quarantine: self.quarantine.clone(),
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&state)?)?;
+        Ok(state)
+    }
+
+    /// Replace reputations and quarantine entries with those saved at `path`
+    pub fn load(&mut self, path: impl AsRef<Path>) -> McpResult<ImmuneState> {
+        let state: ImmuneState = serde_json::from_slice(&std::fs::read(path)?)?;
+        self.reputations = state.reputations.clone();
+        self.quarantine = state.quarantine.clone();
+        Ok(state)
+    }
+
     /// Clear all test state
     pub fn reset(&mut self) {
         self.rate_limits.clear();
@@ -502,6 +640,137 @@ mod tests {
         assert!(result.confidence > 0.5);
     }
 
+    /// Repeat `behavior` from `peer` until it is quarantined
+    async fn attempts_until_quarantined(
+        tester: &mut ImmuneTester,
+        harness: &TestHarness,
+        peer: &str,
+        behavior: BadBehavior,
+    ) -> u32 {
+        let mut attempts = 0;
+        while !tester.get_peer_reputation("love", peer).quarantined {
+            tester
+                .simulate_bad_behavior(harness, peer, behavior.clone())
+                .await
+                .unwrap();
+            attempts += 1;
+        }
+        attempts
+    }
+
+    #[tokio::test]
+    async fn test_severe_violations_cost_more_trust() {
+        let harness = TestHarness::new();
+
+        // One violation each: the severe one costs more trust
+        let mut tester = ImmuneTester::new();
+        tester
+            .simulate_bad_behavior(&harness, "bomber", BadBehavior::DocumentBomb)
+            .await
+            .unwrap();
+        tester
+            .simulate_bad_behavior(&harness, "sloppy", BadBehavior::MalformedMessages)
+            .await
+            .unwrap();
+        let bomber = tester.get_peer_reputation("love", "bomber");
+        let sloppy = tester.get_peer_reputation("love", "sloppy");
+        assert_eq!(bomber.violations[0].severity, 9);
+        assert_eq!(sloppy.violations[0].severity, 2);
+        assert!(bomber.trust_score < sloppy.trust_score);
+        assert!(sloppy.trust_score < 0.5);
+
+        // And crosses the quarantine threshold in far fewer attempts
+        let mut tester = ImmuneTester::new();
+        let bomb_attempts =
+            attempts_until_quarantined(&mut tester, &harness, "bomber", BadBehavior::DocumentBomb)
+                .await;
+        let malformed_attempts = attempts_until_quarantined(
+            &mut tester,
+            &harness,
+            "sloppy",
+            BadBehavior::MalformedMessages,
+        )
+        .await;
+        assert_eq!(bomb_attempts, 2);
+        assert_eq!(malformed_attempts, 8);
+        assert_eq!(tester.get_quarantine_list("love").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_saved_state_survives_reset() {
+        let harness = TestHarness::new();
+        let mut tester = ImmuneTester::new();
+        for _ in 0..4 {
+            tester
+                .simulate_bad_behavior(&harness, "mallory", BadBehavior::InvalidSignatures)
+                .await
+                .unwrap();
+        }
+        assert!(tester.get_peer_reputation("love", "mallory").quarantined);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("immune.json");
+        tester.save(&path).unwrap();
+
+        tester.reset();
+        assert!(tester.get_quarantine_list("love").is_empty());
+        assert!(!tester.get_peer_reputation("love", "mallory").quarantined);
+
+        let state = tester.load(&path).unwrap();
+        assert_eq!(state.quarantine.len(), 1);
+        let quarantine = tester.get_quarantine_list("love");
+        assert_eq!(quarantine.len(), 1);
+        assert_eq!(quarantine[0].peer_id, "mallory");
+        let rep = tester.get_peer_reputation("love", "mallory");
+        assert!(rep.quarantined);
+        assert_eq!(rep.violation_count, 4);
+        assert_eq!(rep.violations.len(), 4);
+        assert!(rep.trust_score < 0.2);
+    }
+
+    #[test]
+    fn test_raised_threshold_suppresses_borderline_detection() {
+        let config = AnomalyConfig {
+            suspicious_keywords_weight: 0.6,
+            ..AnomalyConfig::default()
+        };
+        let pattern = "attack at dawn";
+
+        let tester = ImmuneTester::new_with_anomaly_config(config.clone());
+        let result = tester.test_anomaly_detection(pattern);
+        assert!(result.detected);
+        assert_eq!(result.recommended_response, "rate_limit_peer");
+
+        let strict = ImmuneTester::new_with_anomaly_config(AnomalyConfig {
+            detection_threshold: 0.7,
+            ..config
+        });
+        let result = strict.test_anomaly_detection(pattern);
+        assert!(!result.detected);
+        assert_eq!(result.recommended_response, "none");
+    }
+
+    #[test]
+    fn test_custom_keywords_trigger_detection() {
+        let config = AnomalyConfig {
+            suspicious_keywords_weight: 0.6,
+            ..AnomalyConfig::default()
+        };
+        let pattern = "free crypto giveaway, click here";
+
+        let mut tester = ImmuneTester::new_with_anomaly_config(config.clone());
+        assert!(!tester.test_anomaly_detection(pattern).detected);
+
+        tester.register_keywords(["Giveaway"]);
+        let result = tester.test_anomaly_detection(pattern);
+        assert!(result.detected);
+        assert_eq!(result.features["suspicious_keywords"], 1.0);
+        assert_eq!(result.anomaly_type.as_deref(), Some("suspicious_pattern"));
+
+        let tester = ImmuneTester::new_with_anomaly_config(config.with_keywords(["crypto"]));
+        assert!(tester.test_anomaly_detection(pattern).detected);
+    }
+
     #[test]
     fn test_bad_behavior_parsing() {
         assert_eq!(BadBehavior::from_str("spam").unwrap(), BadBehavior::MessageSpam);
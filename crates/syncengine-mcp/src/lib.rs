@@ -130,6 +130,11 @@ impl NetworkDebugger {
         self.harness.disconnect_nodes(node_a, node_b).await
     }
 
+    /// Restore every connection of the stored meshes, undoing partitions
+    pub async fn reconnect_all(&self) -> McpResult<usize> {
+        self.harness.reconnect_all().await
+    }
+
     /// Create a shared realm across nodes
     pub async fn create_shared_realm(
         &self,
@@ -237,6 +242,13 @@ impl NetworkDebugger {
         TopologyInspector::get_node_view(&self.harness, node_id).await
     }
 
+    /// Estimate how far a gossip message from a node would spread
+    pub async fn estimate_reach(&self, from_node: &str) -> McpResult<topology::ReachEstimate> {
+        self.harness.get_node(from_node)?;
+        let graph = TopologyInspector::get_connection_graph(&self.harness).await;
+        Ok(TopologyInspector::estimate_reach(&graph, from_node))
+    }
+
     /// Ping between nodes
     pub async fn ping_peer(
         &self,
@@ -262,7 +274,13 @@ impl NetworkDebugger {
             .try_into()
             .map_err(|_| error::McpError::InvalidOperation("Invalid trace ID length".into()))?;
 
-        DeliveryVerifier::verify_delivery(&*self.tracer.read().await, &trace_id, expected_nodes)
+        DeliveryVerifier::verify_delivery(
+            &self.harness,
+            &*self.tracer.read().await,
+            &trace_id,
+            expected_nodes,
+        )
+        .await
     }
 
     /// Compare realm state across nodes
@@ -282,6 +300,32 @@ impl NetworkDebugger {
         DeliveryVerifier::compare_realm_state(&self.harness, &realm_id, node_ids).await
     }
 
+    /// Wait until nodes converge on a realm or the timeout passes
+    pub async fn wait_for_sync(
+        &self,
+        realm_id: &str,
+        node_ids: &[&str],
+        timeout_ms: u64,
+    ) -> McpResult<verification::SyncWaitResult> {
+        let realm_bytes = hex::decode(realm_id)
+            .map_err(|e| error::McpError::InvalidOperation(format!("Invalid realm ID: {}", e)))?;
+        let realm_id = syncengine_core::RealmId::from_bytes(
+            realm_bytes
+                .try_into()
+                .map_err(|_| error::McpError::InvalidOperation("Invalid realm ID length".into()))?,
+        );
+
+        DeliveryVerifier::wait_for_sync(&self.harness, &realm_id, node_ids, timeout_ms).await
+    }
+
+    /// Compare every realm the nodes share
+    pub async fn compare_all_realms(
+        &self,
+        node_ids: &[&str],
+    ) -> McpResult<Vec<verification::RealmStateComparison>> {
+        DeliveryVerifier::compare_all_realms(&self.harness, node_ids).await
+    }
+
     /// Find message gaps on a node
     pub async fn find_message_gaps(
         &self,
@@ -299,6 +343,62 @@ impl NetworkDebugger {
         DeliveryVerifier::find_message_gaps(&self.harness, &realm_id, node_id).await
     }
 
+    /// Feed raw bytes into a node's gossip receive path (test mode only)
+    pub async fn inject_raw_message(
+        &self,
+        node_id: &str,
+        realm_id: &str,
+        bytes: Vec<u8>,
+    ) -> McpResult<harness::InjectionResult> {
+        self.require_test_mode("Message injection")?;
+
+        let realm_bytes = hex::decode(realm_id)
+            .map_err(|e| error::McpError::InvalidOperation(format!("Invalid realm ID: {}", e)))?;
+        let realm_id = syncengine_core::RealmId::from_bytes(
+            realm_bytes
+                .try_into()
+                .map_err(|_| error::McpError::InvalidOperation("Invalid realm ID length".into()))?,
+        );
+
+        let node = self.harness.get_node(node_id)?;
+        Ok(node.inject_raw_message(&realm_id, bytes).await)
+    }
+
+    /// Start recording a node's realm gossip traffic (test mode only)
+    pub async fn start_capture(&self, node_id: &str) -> McpResult<()> {
+        self.require_test_mode("Message capture")?;
+        self.harness.get_node(node_id)?.start_capture().await
+    }
+
+    /// Stop recording and return a node's captured traffic
+    pub async fn stop_capture(
+        &self,
+        node_id: &str,
+    ) -> McpResult<syncengine_core::sync::MessageCapture> {
+        self.harness.get_node(node_id)?.stop_capture().await
+    }
+
+    /// Replay a capture into a node (test mode only)
+    pub async fn replay_capture(
+        &self,
+        node_id: &str,
+        capture: &syncengine_core::sync::MessageCapture,
+    ) -> McpResult<usize> {
+        self.require_test_mode("Capture replay")?;
+        self.harness.get_node(node_id)?.replay_capture(capture).await
+    }
+
+    /// Refuse an operation that injects traffic or exposes realm keys
+    fn require_test_mode(&self, operation: &str) -> McpResult<()> {
+        if self.live_mode {
+            return Err(error::McpError::InvalidOperation(format!(
+                "{} is disabled in live mode",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
     // =========================================================================
     // Immune System Tools
     // =========================================================================
@@ -345,6 +445,16 @@ impl NetworkDebugger {
         self.immune.read().await.test_anomaly_detection(pattern)
     }
 
+    /// Save peer reputations and quarantine entries to a JSON file
+    pub async fn save_immune_state(&self, path: &str) -> McpResult<immune::ImmuneState> {
+        self.immune.read().await.save(path)
+    }
+
+    /// Restore peer reputations and quarantine entries from a JSON file
+    pub async fn load_immune_state(&self, path: &str) -> McpResult<immune::ImmuneState> {
+        self.immune.write().await.load(path)
+    }
+
     // =========================================================================
     // Profile Pinning Tools (for offline peer scenario testing)
     // =========================================================================
@@ -693,6 +803,14 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
                 "required": ["node_a", "node_b"]
             }),
         },
+        ToolDefinition {
+            name: "heal_mesh".into(),
+            description: "Reconnect every edge of the configured meshes after partition tests".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }),
+        },
         ToolDefinition {
             name: "create_shared_realm".into(),
             description: "Create a realm visible to multiple nodes".into(),
@@ -784,6 +902,17 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
                 "required": ["node_id"]
             }),
         },
+        ToolDefinition {
+            name: "estimate_reach".into(),
+            description: "Predict how many nodes a gossip message from a node can reach, and in how many hops".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "from_node": { "type": "string" }
+                },
+                "required": ["from_node"]
+            }),
+        },
         ToolDefinition {
             name: "ping_peer".into(),
             description: "Test connectivity between nodes".into(),
@@ -827,6 +956,36 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
                 "required": ["realm_id", "node_ids"]
             }),
         },
+        ToolDefinition {
+            name: "wait_for_sync".into(),
+            description: "Poll until nodes converge on a realm or the timeout passes".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "realm_id": { "type": "string" },
+                    "node_ids": {
+                        "type": "array",
+                        "items": { "type": "string" }
+                    },
+                    "timeout_ms": { "type": "integer" }
+                },
+                "required": ["realm_id", "node_ids", "timeout_ms"]
+            }),
+        },
+        ToolDefinition {
+            name: "compare_all_realms".into(),
+            description: "Check which realms shared by the nodes are in sync".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "node_ids": {
+                        "type": "array",
+                        "items": { "type": "string" }
+                    }
+                },
+                "required": ["node_ids"]
+            }),
+        },
         ToolDefinition {
             name: "find_message_gaps".into(),
             description: "Find messages present on peers but not on a node".into(),
@@ -839,6 +998,53 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
                 "required": ["realm_id", "node_id"]
             }),
         },
+        ToolDefinition {
+            name: "inject_raw_message".into(),
+            description: "Feed raw hex-encoded bytes into a node's gossip receive path (test mode only)".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "node_id": { "type": "string" },
+                    "realm_id": { "type": "string" },
+                    "bytes_hex": { "type": "string" }
+                },
+                "required": ["node_id", "realm_id", "bytes_hex"]
+            }),
+        },
+        ToolDefinition {
+            name: "start_capture".into(),
+            description: "Start recording a node's inbound and outbound realm gossip (test mode only)".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "node_id": { "type": "string" }
+                },
+                "required": ["node_id"]
+            }),
+        },
+        ToolDefinition {
+            name: "stop_capture".into(),
+            description: "Stop recording and return the node's message capture as JSON".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "node_id": { "type": "string" }
+                },
+                "required": ["node_id"]
+            }),
+        },
+        ToolDefinition {
+            name: "replay_capture".into(),
+            description: "Re-drive a node with a message capture from stop_capture (test mode only)".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "node_id": { "type": "string" },
+                    "capture": { "type": "string" }
+                },
+                "required": ["node_id", "capture"]
+            }),
+        },
         // Immune System Tools
         ToolDefinition {
             name: "trigger_rate_limit".into(),
@@ -904,5 +1110,74 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
                 "required": ["pattern"]
             }),
         },
+        ToolDefinition {
+            name: "save_immune_state".into(),
+            description: "Save peer reputations and quarantine list to a JSON file".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" }
+                },
+                "required": ["path"]
+            }),
+        },
+        ToolDefinition {
+            name: "load_immune_state".into(),
+            description: "Restore peer reputations and quarantine list from a JSON file".into(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" }
+                },
+                "required": ["path"]
+            }),
+        },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_message_injection_disabled_in_live_mode() {
+        let debugger = NetworkDebugger::with_live_mode();
+        let realm_id = hex::encode([0u8; 32]);
+        let result = debugger.inject_raw_message("love", &realm_id, vec![0xFF; 16]).await;
+        assert!(matches!(result, Err(error::McpError::InvalidOperation(_))));
+        let result = debugger.start_capture("love").await;
+        assert!(matches!(result, Err(error::McpError::InvalidOperation(_))));
+    }
+
+    fn edge_set(graph: &topology::ConnectionGraph) -> std::collections::HashSet<(String, String)> {
+        graph
+            .edges
+            .iter()
+            .map(|e| (e.from.clone(), e.to.clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_heal_restores_partitioned_ring() {
+        let debugger = NetworkDebugger::new();
+        debugger
+            .create_mesh(4, "ring", Some("ring".into()))
+            .await
+            .unwrap();
+        let ring = edge_set(&debugger.get_connection_graph().await);
+        assert_eq!(ring.len(), 4);
+
+        // Split the ring into two halves
+        debugger.disconnect_nodes("mesh_ring_0", "mesh_ring_1").await.unwrap();
+        debugger.disconnect_nodes("mesh_ring_2", "mesh_ring_3").await.unwrap();
+        assert_eq!(debugger.get_connection_graph().await.edge_count, 2);
+
+        assert_eq!(debugger.reconnect_all().await.unwrap(), 2);
+        assert_eq!(edge_set(&debugger.get_connection_graph().await), ring);
+
+        // Nothing left to heal
+        assert_eq!(debugger.reconnect_all().await.unwrap(), 0);
+
+        debugger.harness.cleanup().await.unwrap();
+    }
+}
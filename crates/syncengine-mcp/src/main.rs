@@ -94,6 +94,14 @@ impl NetworkDebuggerServer {
         }
     }
 
+    #[tool(description = "Reconnect every edge of the configured meshes after partition tests")]
+    async fn heal_mesh(&self) -> String {
+        match self.debugger.reconnect_all().await {
+            Ok(restored) => serde_json::json!({ "success": true, "restored_edges": restored }).to_string(),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
     #[tool(description = "Create a realm visible to multiple nodes")]
     async fn create_shared_realm(
         &self,
@@ -164,6 +172,14 @@ impl NetworkDebuggerServer {
         }
     }
 
+    #[tool(description = "Predict how many nodes a gossip message from a node can reach, and in how many hops")]
+    async fn estimate_reach(&self, #[tool(param)] from_node: String) -> String {
+        match self.debugger.estimate_reach(&from_node).await {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
     #[tool(description = "Test connectivity between nodes")]
     async fn ping_peer(
         &self,
@@ -202,6 +218,29 @@ impl NetworkDebuggerServer {
         }
     }
 
+    #[tool(description = "Poll until nodes converge on a realm or the timeout passes")]
+    async fn wait_for_sync(
+        &self,
+        #[tool(param)] realm_id: String,
+        #[tool(param)] node_ids: Vec<String>,
+        #[tool(param)] timeout_ms: i64,
+    ) -> String {
+        let node_refs: Vec<&str> = node_ids.iter().map(|s| s.as_str()).collect();
+        match self.debugger.wait_for_sync(&realm_id, &node_refs, timeout_ms.max(0) as u64).await {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
+    #[tool(description = "Check which realms shared by the nodes are in sync")]
+    async fn compare_all_realms(&self, #[tool(param)] node_ids: Vec<String>) -> String {
+        let node_refs: Vec<&str> = node_ids.iter().map(|s| s.as_str()).collect();
+        match self.debugger.compare_all_realms(&node_refs).await {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
     #[tool(description = "Find messages present on peers but not on a node")]
     async fn find_message_gaps(
         &self,
@@ -214,6 +253,55 @@ impl NetworkDebuggerServer {
         }
     }
 
+    #[tool(description = "Feed raw hex-encoded bytes into a node's gossip receive path (test mode only)")]
+    async fn inject_raw_message(
+        &self,
+        #[tool(param)] node_id: String,
+        #[tool(param)] realm_id: String,
+        #[tool(param)] bytes_hex: String,
+    ) -> String {
+        let bytes = match hex::decode(bytes_hex.trim()) {
+            Ok(bytes) => bytes,
+            Err(e) => return format!("{{\"error\": \"Invalid hex bytes: {}\"}}", e),
+        };
+        match self.debugger.inject_raw_message(&node_id, &realm_id, bytes).await {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
+    #[tool(description = "Start recording a node's inbound and outbound realm gossip (test mode only)")]
+    async fn start_capture(&self, #[tool(param)] node_id: String) -> String {
+        match self.debugger.start_capture(&node_id).await {
+            Ok(()) => serde_json::json!({ "capturing": true, "node_id": node_id }).to_string(),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
+    #[tool(description = "Stop recording and return the node's message capture as JSON")]
+    async fn stop_capture(&self, #[tool(param)] node_id: String) -> String {
+        match self.debugger.stop_capture(&node_id).await {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
+    #[tool(description = "Re-drive a node with a message capture from stop_capture (test mode only)")]
+    async fn replay_capture(
+        &self,
+        #[tool(param)] node_id: String,
+        #[tool(param)] capture: String,
+    ) -> String {
+        let capture = match serde_json::from_str(&capture) {
+            Ok(capture) => capture,
+            Err(e) => return format!("{{\"error\": \"Invalid capture: {}\"}}", e),
+        };
+        match self.debugger.replay_capture(&node_id, &capture).await {
+            Ok(replayed) => serde_json::json!({ "node_id": node_id, "replayed": replayed }).to_string(),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
     #[tool(description = "Force rate limit to test defensive mechanisms")]
     async fn trigger_rate_limit(
         &self,
@@ -259,6 +347,32 @@ impl NetworkDebuggerServer {
         serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
     }
 
+    #[tool(description = "Save peer reputations and quarantine list to a JSON file")]
+    async fn save_immune_state(&self, #[tool(param)] path: String) -> String {
+        match self.debugger.save_immune_state(&path).await {
+            Ok(state) => serde_json::json!({
+                "saved": path,
+                "reputations": state.reputations.len(),
+                "quarantined": state.quarantine.len(),
+            })
+            .to_string(),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
+    #[tool(description = "Restore peer reputations and quarantine list from a JSON file")]
+    async fn load_immune_state(&self, #[tool(param)] path: String) -> String {
+        match self.debugger.load_immune_state(&path).await {
+            Ok(state) => serde_json::json!({
+                "loaded": path,
+                "reputations": state.reputations.len(),
+                "quarantined": state.quarantine.len(),
+            })
+            .to_string(),
+            Err(e) => format!("{{\"error\": \"{}\"}}", e),
+        }
+    }
+
     // =========================================================================
     // Profile Pinning Tools
     // =========================================================================
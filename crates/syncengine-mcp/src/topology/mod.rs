@@ -5,7 +5,7 @@
 use crate::error::McpResult;
 use crate::harness::TestHarness;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Node in the connection graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,12 +84,21 @@ impl ConnectionGraph {
 
                 if !seen_edges.contains(&(a.clone(), b.clone())) {
                     seen_edges.insert((a.clone(), b.clone()));
+                    let link = match harness.get_node(&info.name) {
+                        Ok(node) => node.peer_link(peer).await,
+                        Err(_) => None,
+                    };
+                    // Unmeasured links report zero quality rather than a guess
+                    let (quality, is_direct, latency_ms) = match link {
+                        Some(p) => (p.connection_quality, p.link.direct, p.link.rtt_ms),
+                        None => (0, false, None),
+                    };
                     edges.push(GraphEdge {
                         from: a,
                         to: b,
-                        quality: 100, // Assume perfect quality in test harness
-                        is_direct: true,
-                        latency_ms: None,
+                        quality,
+                        is_direct,
+                        latency_ms,
                     });
                 }
             }
@@ -217,6 +226,23 @@ pub struct PingResult {
     pub relay_url: Option<String>,
 }
 
+/// How far a gossip message from one node can spread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReachEstimate {
+    /// Node the message starts from
+    pub from_node: String,
+    /// Size of the connected component holding the source, including it
+    pub component_size: usize,
+    /// Nodes in the graph the message can't reach
+    pub unreachable: Vec<String>,
+    /// Hops to the farthest reachable node
+    pub max_hops: usize,
+    /// Average hops to the other reachable nodes
+    pub average_hops: f64,
+    /// Whether every node in the graph can be reached
+    pub reaches_all: bool,
+}
+
 /// Network topology inspector
 pub struct TopologyInspector;
 
@@ -328,6 +354,59 @@ impl TopologyInspector {
         })
     }
 
+    /// Hop count from `from` to every node it can reach, including itself
+    pub fn hop_distances(graph: &ConnectionGraph, from: &str) -> HashMap<String, usize> {
+        let mut distances: HashMap<String, usize> = HashMap::new();
+        let mut queue: VecDeque<String> = VecDeque::new();
+        distances.insert(from.to_string(), 0);
+        queue.push_back(from.to_string());
+
+        while let Some(current) = queue.pop_front() {
+            let hops = distances[&current];
+            for neighbor in graph.neighbors(&current) {
+                if !distances.contains_key(&neighbor) {
+                    distances.insert(neighbor.clone(), hops + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Estimate how far a gossip message from `from_node` would spread
+    ///
+    /// Any node outside the source's connected component won't receive the
+    /// message until the partition heals.
+    pub fn estimate_reach(graph: &ConnectionGraph, from_node: &str) -> ReachEstimate {
+        let distances = Self::hop_distances(graph, from_node);
+
+        let mut unreachable: Vec<String> = graph
+            .nodes
+            .iter()
+            .filter(|n| !distances.contains_key(&n.id))
+            .map(|n| n.id.clone())
+            .collect();
+        unreachable.sort();
+
+        let others = distances.len() - 1;
+        let max_hops = distances.values().copied().max().unwrap_or(0);
+        let average_hops = if others == 0 {
+            0.0
+        } else {
+            distances.values().sum::<usize>() as f64 / others as f64
+        };
+
+        ReachEstimate {
+            from_node: from_node.to_string(),
+            component_size: distances.len(),
+            reaches_all: unreachable.is_empty(),
+            unreachable,
+            max_hops,
+            average_hops,
+        }
+    }
+
     /// Find path between two nodes
     pub fn find_path(graph: &ConnectionGraph, from: &str, to: &str) -> Option<Vec<String>> {
         if from == to {
@@ -426,6 +505,37 @@ mod tests {
         assert_eq!(path, vec!["a", "b", "c"]);
     }
 
+    #[test]
+    fn test_reach_on_chain() {
+        let ids = ["a", "b", "c", "d", "e"];
+        let node = |id: &str| GraphNode { id: id.into(), node_key: None, did: None, realms: vec![], connection_count: 0 };
+        let edge = |from: &str, to: &str| GraphEdge { from: from.into(), to: to.into(), quality: 100, is_direct: true, latency_ms: None };
+        let mut graph = ConnectionGraph {
+            nodes: ids.iter().map(|id| node(id)).collect(),
+            edges: ids.windows(2).map(|w| edge(w[0], w[1])).collect(),
+            node_count: 5,
+            edge_count: 4,
+            density: 0.4,
+        };
+
+        let reach = TopologyInspector::estimate_reach(&graph, "a");
+        assert_eq!(reach.component_size, ids.len());
+        assert_eq!(reach.max_hops, ids.len() - 1);
+        assert_eq!(reach.average_hops, 2.5);
+        assert!(reach.reaches_all);
+
+        // From the middle the far ends are two hops away
+        assert_eq!(TopologyInspector::estimate_reach(&graph, "c").max_hops, 2);
+
+        // Cutting c-d strands d and e
+        graph.edges.retain(|e| !(e.from == "c" && e.to == "d"));
+        let reach = TopologyInspector::estimate_reach(&graph, "a");
+        assert_eq!(reach.component_size, 3);
+        assert_eq!(reach.max_hops, 2);
+        assert_eq!(reach.unreachable, vec!["d", "e"]);
+        assert!(!reach.reaches_all);
+    }
+
     #[test]
     fn test_neighbors() {
         let graph = ConnectionGraph {
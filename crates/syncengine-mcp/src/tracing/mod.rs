@@ -56,13 +56,24 @@ impl MessageTracer {
         // Get the source node
         let node = harness.get_node(from_node)?;
 
+        // The message is a task; its change hash is what peers must end up with
+        let change_hash = {
+            let mut engine = node.engine_mut().await;
+            engine.add_task(realm_id, content).await?;
+            let heads = engine.realm_heads(realm_id).await?;
+            heads.first().map(|h| h.to_string())
+        };
+
         // Create the trace record
-        let trace = MessageTrace::new(
+        let mut trace = MessageTrace::new(
             trace_id,
             from_node.to_string(),
             realm_id.clone(),
             content.to_string(),
         );
+        if let Some(hash) = change_hash {
+            trace = trace.with_change_hash(hash);
+        }
         self.traces.write().insert(trace_id, trace);
 
         // Record the send event
@@ -75,11 +86,6 @@ impl MessageTracer {
             details: Some(format!("Content: {}", content)),
         });
 
-        // Add a task with the trace ID embedded in the title
-        // This is a simple way to send a traceable message
-        let trace_marker = format!("[trace:{}] {}", hex::encode(trace_id), content);
-        node.add_task(realm_id, &trace_marker).await?;
-
         tracing::info!(
             trace_id = %hex::encode(trace_id),
             from = %from_node,
@@ -90,6 +96,15 @@ impl MessageTracer {
         Ok(trace_id)
     }
 
+    /// Get a copy of a trace
+    pub fn get_trace(&self, trace_id: &TraceId) -> McpResult<MessageTrace> {
+        self.traces
+            .read()
+            .get(trace_id)
+            .cloned()
+            .ok_or_else(|| McpError::TraceNotFound(hex::encode(trace_id)))
+    }
+
     /// Get trace results for a message
     pub fn get_trace_results(&self, trace_id: &TraceId) -> McpResult<TraceResult> {
         let traces = self.traces.read();
@@ -45,6 +45,8 @@ pub struct MessageTrace {
     pub realm_id: RealmId,
     /// Message content (for debugging)
     pub content: String,
+    /// Hash of the realm change that carries the message (hex)
+    pub change_hash: Option<String>,
     /// When the trace started
     pub started_at: DateTime<Utc>,
     /// All hops in the trace
@@ -68,6 +70,7 @@ impl MessageTrace {
             source_node,
             realm_id,
             content,
+            change_hash: None,
             started_at: Utc::now(),
             hops: Vec::new(),
             status: TraceStatus::InProgress,
@@ -75,6 +78,12 @@ impl MessageTrace {
         }
     }
 
+    /// Set the hash of the realm change that carries the message
+    pub fn with_change_hash(mut self, change_hash: String) -> Self {
+        self.change_hash = Some(change_hash);
+        self
+    }
+
     /// Add a hop to the trace
     pub fn add_hop(&mut self, hop: TraceHop) {
         self.hops.push(hop);
@@ -146,6 +155,8 @@ pub struct TraceResult {
     pub realm_id: String,
     /// Message content
     pub content: String,
+    /// Hash of the realm change that carries the message (hex)
+    pub change_hash: Option<String>,
     /// Current status
     pub status: TraceStatus,
     /// When trace started
@@ -219,6 +230,7 @@ impl TraceResult {
             source_node: trace.source_node.clone(),
             realm_id: hex::encode(trace.realm_id.as_bytes()),
             content: trace.content.clone(),
+            change_hash: trace.change_hash.clone(),
             status: trace.status,
             started_at: trace.started_at.to_rfc3339(),
             hops,
@@ -78,6 +78,17 @@ pub struct StateGroup {
     pub task_count: usize,
 }
 
+/// Outcome of waiting for nodes to converge on a realm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncWaitResult {
+    /// Whether the nodes converged before the timeout
+    pub synced: bool,
+    /// How long the wait took in milliseconds
+    pub elapsed_ms: u64,
+    /// Comparison from the last poll
+    pub comparison: RealmStateComparison,
+}
+
 /// Missing messages on a node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageGaps {
@@ -85,9 +96,9 @@ pub struct MessageGaps {
     pub node_id: String,
     /// Realm ID (hex)
     pub realm_id: String,
-    /// Message IDs present on peers but not here
+    /// Change hashes present on peers but not here
     pub missing_messages: Vec<String>,
-    /// Peers that have these messages
+    /// Peers that have each missing change
     pub available_from: HashMap<String, Vec<String>>,
     /// Total missing count
     pub missing_count: usize,
@@ -98,35 +109,66 @@ pub struct DeliveryVerifier;
 
 impl DeliveryVerifier {
     /// Verify that a message was delivered to expected nodes
-    pub fn verify_delivery(
+    ///
+    /// A node has the message when its copy of the realm contains the change
+    /// that carried it. Traces without a change hash fall back to the
+    /// recorded hops. Nodes whose realm can't be read are reported as
+    /// partial receives and counted as missing.
+    pub async fn verify_delivery(
+        harness: &TestHarness,
         tracer: &MessageTracer,
         trace_id: &TraceId,
         expected_nodes: &[&str],
     ) -> McpResult<DeliveryReport> {
-        let trace_result = tracer.get_trace_results(trace_id)?;
-
-        let expected: HashSet<&str> = expected_nodes.iter().copied().collect();
-        let delivered: HashSet<String> = trace_result.delivered_to.iter().cloned().collect();
+        let trace = tracer.get_trace(trace_id)?;
+
+        let mut delivered_to: Vec<String> = Vec::new();
+        let mut missing_from: Vec<String> = Vec::new();
+        let mut partial_receives: Vec<PartialReceive> = Vec::new();
+
+        let received_by = trace.received_by();
+        for node_id in expected_nodes {
+            let delivered = match &trace.change_hash {
+                Some(hash) => {
+                    let hashes = match harness.get_node(node_id) {
+                        Ok(node) => node.change_hashes(&trace.realm_id).await,
+                        Err(e) => Err(e),
+                    };
+                    match hashes {
+                        Ok(hashes) => hashes.contains(hash),
+                        Err(e) => {
+                            partial_receives.push(PartialReceive {
+                                node_id: node_id.to_string(),
+                                error: e.to_string(),
+                                received_portion: None,
+                            });
+                            false
+                        }
+                    }
+                }
+                None => received_by.iter().any(|n| n == node_id),
+            };
 
-        let missing_from: Vec<String> = expected
-            .iter()
-            .filter(|n| !delivered.contains(**n))
-            .map(|n| n.to_string())
-            .collect();
+            if delivered {
+                delivered_to.push(node_id.to_string());
+            } else {
+                missing_from.push(node_id.to_string());
+            }
+        }
 
-        let success_rate = if expected.is_empty() {
+        let success_rate = if expected_nodes.is_empty() {
             1.0
         } else {
-            delivered.len() as f64 / expected.len() as f64
+            delivered_to.len() as f64 / expected_nodes.len() as f64
         };
 
         Ok(DeliveryReport {
-            message_id: trace_result.trace_id,
-            delivered_to: trace_result.delivered_to,
-            missing_from: missing_from.clone(),
-            partial_receives: vec![], // Would be populated from trace errors
-            success_rate,
+            message_id: hex::encode(trace_id),
+            delivered_to,
             complete: missing_from.is_empty(),
+            missing_from,
+            partial_receives,
+            success_rate,
         })
     }
 
@@ -175,7 +217,45 @@ impl DeliveryVerifier {
         })
     }
 
-    /// Find messages present on peers but missing from a node
+    /// Compare every realm the nodes have in common
+    ///
+    /// Realms missing from any of the nodes are skipped. Results are ordered
+    /// by realm ID.
+    pub async fn compare_all_realms(
+        harness: &TestHarness,
+        node_ids: &[&str],
+    ) -> McpResult<Vec<RealmStateComparison>> {
+        let mut common: Option<HashSet<RealmId>> = None;
+        for node_id in node_ids {
+            let node = harness.get_node(node_id)?;
+            let realms: HashSet<RealmId> = node
+                .engine()
+                .await
+                .storage()
+                .list_realms()?
+                .into_iter()
+                .map(|r| r.id)
+                .collect();
+            common = Some(match common {
+                Some(shared) => shared.intersection(&realms).cloned().collect(),
+                None => realms,
+            });
+        }
+
+        let mut realm_ids: Vec<RealmId> = common.unwrap_or_default().into_iter().collect();
+        realm_ids.sort_by_key(|id| *id.as_bytes());
+
+        let mut comparisons = Vec::with_capacity(realm_ids.len());
+        for realm_id in &realm_ids {
+            comparisons.push(Self::compare_realm_state(harness, realm_id, node_ids).await?);
+        }
+        Ok(comparisons)
+    }
+
+    /// Find changes present on peers but missing from a node
+    ///
+    /// Compares the full change history of the realm on the node against
+    /// each connected peer's copy.
     pub async fn find_message_gaps(
         harness: &TestHarness,
         realm_id: &RealmId,
@@ -183,11 +263,8 @@ impl DeliveryVerifier {
     ) -> McpResult<MessageGaps> {
         let realm_hex = hex::encode(realm_id.as_bytes());
         let node = harness.get_node(node_id)?;
-        let node_tasks = node.list_tasks(realm_id).await?;
-        let node_task_ids: HashSet<String> = node_tasks
-            .iter()
-            .map(|t| t.id.to_string())
-            .collect();
+        let node_changes: HashSet<String> =
+            node.change_hashes(realm_id).await?.into_iter().collect();
 
         let mut missing_messages: Vec<String> = Vec::new();
         let mut available_from: HashMap<String, Vec<String>> = HashMap::new();
@@ -196,15 +273,14 @@ impl DeliveryVerifier {
         let info = node.info().await;
         for peer_name in &info.peers {
             if let Ok(peer_node) = harness.get_node(peer_name) {
-                if let Ok(peer_tasks) = peer_node.list_tasks(realm_id).await {
-                    for task in peer_tasks {
-                        let task_id = task.id.to_string();
-                        if !node_task_ids.contains(&task_id) {
-                            if !missing_messages.contains(&task_id) {
-                                missing_messages.push(task_id.clone());
+                if let Ok(peer_changes) = peer_node.change_hashes(realm_id).await {
+                    for hash in peer_changes {
+                        if !node_changes.contains(&hash) {
+                            if !missing_messages.contains(&hash) {
+                                missing_messages.push(hash.clone());
                             }
                             available_from
-                                .entry(task_id)
+                                .entry(hash)
                                 .or_insert_with(Vec::new)
                                 .push(peer_name.clone());
                         }
@@ -288,24 +364,28 @@ impl DeliveryVerifier {
     }
 
     /// Wait for sync to complete between nodes
+    ///
+    /// Polls the realm state until the nodes agree or `timeout_ms` passes,
+    /// and returns the last comparison either way.
     pub async fn wait_for_sync(
         harness: &TestHarness,
         realm_id: &RealmId,
         node_ids: &[&str],
         timeout_ms: u64,
-    ) -> McpResult<bool> {
+    ) -> McpResult<SyncWaitResult> {
         let start = std::time::Instant::now();
         let timeout = std::time::Duration::from_millis(timeout_ms);
 
         loop {
             let comparison = Self::compare_realm_state(harness, realm_id, node_ids).await?;
-
-            if comparison.in_sync {
-                return Ok(true);
-            }
-
-            if start.elapsed() >= timeout {
-                return Ok(false);
+            let timed_out = start.elapsed() >= timeout;
+
+            if comparison.in_sync || timed_out {
+                return Ok(SyncWaitResult {
+                    synced: comparison.in_sync,
+                    elapsed_ms: start.elapsed().as_millis() as u64,
+                    comparison,
+                });
             }
 
             // Poll every 50ms
@@ -317,6 +397,7 @@ impl DeliveryVerifier {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::harness::TestNode;
 
     #[test]
     fn test_divergence_detection() {
@@ -339,6 +420,150 @@ mod tests {
         assert!(divergence.common_ancestor.contains(&"head1".to_string()));
     }
 
+    /// Give `to` an exact copy of `from`'s realm, as if they had fully synced
+    async fn replicate_realm(from: &TestNode, to: &TestNode, realm_id: &RealmId) {
+        let (info, key, doc) = {
+            let engine = from.engine().await;
+            let storage = engine.storage();
+            (
+                storage.load_realm(realm_id).unwrap().unwrap(),
+                storage.load_realm_key(realm_id).unwrap().unwrap(),
+                storage.load_document(realm_id).unwrap().unwrap(),
+            )
+        };
+        let mut engine = to.engine_mut().await;
+        engine.storage().save_realm(&info).unwrap();
+        engine.storage().save_realm_key(realm_id, &key).unwrap();
+        engine.storage().save_document(realm_id, &doc).unwrap();
+        engine.open_realm(realm_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compare_all_realms_flags_divergent_realm() {
+        let harness = TestHarness::new();
+        let love = harness.create_node(Some("love".into())).await.unwrap();
+        let joy = harness.create_node(Some("joy".into())).await.unwrap();
+
+        let garden = love.create_realm("Garden").await.unwrap();
+        love.add_task(&garden, "Water the beans").await.unwrap();
+        replicate_realm(&love, &joy, &garden).await;
+
+        let kitchen = love.create_realm("Kitchen").await.unwrap();
+        replicate_realm(&love, &joy, &kitchen).await;
+        joy.add_task(&kitchen, "Bake bread").await.unwrap();
+
+        // Only Love has this one, so it isn't compared
+        love.create_realm("Workshop").await.unwrap();
+
+        let comparisons = DeliveryVerifier::compare_all_realms(&harness, &["love", "joy"])
+            .await
+            .unwrap();
+        let in_sync: HashMap<String, bool> = comparisons
+            .iter()
+            .map(|c| (c.realm_id.clone(), c.in_sync))
+            .collect();
+        assert_eq!(
+            in_sync,
+            HashMap::from([
+                (hex::encode(garden.as_bytes()), true),
+                (hex::encode(kitchen.as_bytes()), false),
+            ])
+        );
+        let kitchen_comparison = comparisons.iter().find(|c| !c.in_sync).unwrap();
+        assert_eq!(kitchen_comparison.task_counts["joy"], 1);
+        assert!(kitchen_comparison.divergence.is_some());
+
+        harness.cleanup().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sync_until_converged_or_timeout() {
+        let harness = TestHarness::new();
+        let love = harness.create_node(Some("love".into())).await.unwrap();
+        let joy = harness.create_node(Some("joy".into())).await.unwrap();
+
+        let garden = love.create_realm("Garden").await.unwrap();
+        replicate_realm(&love, &joy, &garden).await;
+        love.add_task(&garden, "Water the beans").await.unwrap();
+
+        // Partitioned: Joy never hears about the task
+        let result = DeliveryVerifier::wait_for_sync(&harness, &garden, &["love", "joy"], 200)
+            .await
+            .unwrap();
+        assert!(!result.synced);
+        assert!(result.elapsed_ms >= 200);
+        assert!(result.comparison.divergence.is_some());
+
+        // Joy catches up while we wait
+        let catch_up = async {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            let doc = love.engine().await.storage().load_document(&garden).unwrap().unwrap();
+            joy.engine_mut().await.import_automerge(&garden, &doc).await.unwrap();
+        };
+        let (result, ()) = tokio::join!(
+            DeliveryVerifier::wait_for_sync(&harness, &garden, &["love", "joy"], 5000),
+            catch_up
+        );
+        let result = result.unwrap();
+        assert!(result.synced);
+        assert_eq!(result.comparison.task_counts["joy"], 1);
+
+        harness.cleanup().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delivery_follows_the_traced_change() {
+        let harness = TestHarness::new();
+        let love = harness.create_node(Some("love".into())).await.unwrap();
+        let joy = harness.create_node(Some("joy".into())).await.unwrap();
+        let peace = harness.create_node(Some("peace".into())).await.unwrap();
+        peace.connect_to(&love).await.unwrap();
+
+        let garden = love.create_realm("Garden").await.unwrap();
+        replicate_realm(&love, &joy, &garden).await;
+        replicate_realm(&love, &peace, &garden).await;
+
+        let tracer = MessageTracer::new();
+        let trace_id = tracer
+            .send_traced_message(&harness, "love", &garden, "Water the beans")
+            .await
+            .unwrap();
+        let change_hash = tracer.get_trace(&trace_id).unwrap().change_hash.unwrap();
+
+        // Only Joy receives the change; Peace stays partitioned
+        let doc = love.engine().await.storage().load_document(&garden).unwrap().unwrap();
+        joy.engine_mut().await.import_automerge(&garden, &doc).await.unwrap();
+
+        let report = DeliveryVerifier::verify_delivery(
+            &harness,
+            &tracer,
+            &trace_id,
+            &["love", "joy", "peace"],
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.delivered_to, vec!["love", "joy"]);
+        assert_eq!(report.missing_from, vec!["peace"]);
+        assert!(!report.complete);
+
+        // Peace is missing exactly the traced change, which Love can supply
+        let gaps = DeliveryVerifier::find_message_gaps(&harness, &garden, "peace")
+            .await
+            .unwrap();
+        assert_eq!(gaps.missing_messages, vec![change_hash.clone()]);
+        assert_eq!(gaps.available_from[&change_hash], vec!["love"]);
+
+        peace.engine_mut().await.import_automerge(&garden, &doc).await.unwrap();
+        let report =
+            DeliveryVerifier::verify_delivery(&harness, &tracer, &trace_id, &["joy", "peace"])
+                .await
+                .unwrap();
+        assert!(report.complete);
+        assert_eq!(report.success_rate, 1.0);
+
+        harness.cleanup().await.unwrap();
+    }
+
     #[test]
     fn test_in_sync_detection() {
         let mut heads_per_node: HashMap<String, Vec<String>> = HashMap::new();
@@ -2,9 +2,13 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use dioxus::prelude::*;
+use syncengine_core::{ContactEvent, PeerStatus};
 use tokio::sync::RwLock;
 
-use crate::context::{get_data_dir, get_init_connect, get_init_profile_name, PendingChatContact, SharedEngine};
+use crate::context::{
+    get_data_dir, get_init_connect, get_init_profile_name, ContactPresence, PendingChatContact,
+    SharedEngine,
+};
 use crate::pages::{Field, Landing, Network, Profile, RealmView};
 use crate::theme::GLOBAL_STYLES;
 
@@ -38,11 +42,13 @@ pub fn App() -> Element {
     let engine: Signal<SharedEngine> = use_signal(|| Arc::new(RwLock::new(None)));
     let mut engine_ready: Signal<bool> = use_signal(|| false);
     let pending_chat_contact: Signal<Option<PendingChatContact>> = use_signal(|| None);
+    let mut contact_presence: Signal<ContactPresence> = use_signal(ContactPresence::default);
 
     // Provide engine context to all child components
     use_context_provider(|| engine);
     use_context_provider(|| engine_ready);
     use_context_provider(|| pending_chat_contact);
+    use_context_provider(|| contact_presence);
 
     // Initialize engine on mount
     use_effect(move || {
@@ -330,6 +336,51 @@ pub fn App() -> Element {
         });
     });
 
+    // Roll up contact presence once the engine is ready, then follow
+    // online/offline events so every status display shares one count
+    use_effect(move || {
+        if !engine_ready() {
+            return;
+        }
+        spawn(async move {
+            let shared = engine();
+            let mut guard = shared.write().await;
+            let Some(ref mut eng) = *guard else { return };
+            if let Ok(contacts) = eng.list_peer_contacts() {
+                contact_presence.set(ContactPresence::from_contacts(&contacts));
+            }
+            let mut event_rx = match eng.subscribe_contact_events().await {
+                Ok(rx) => rx,
+                Err(e) => {
+                    tracing::error!("Failed to subscribe to contact events: {:?}", e);
+                    return;
+                }
+            };
+            drop(guard);
+
+            while let Ok(event) = event_rx.recv().await {
+                match event {
+                    ContactEvent::ContactOnline { did } => {
+                        contact_presence.write().set_status(&did, PeerStatus::Online);
+                    }
+                    ContactEvent::ContactOffline { did } => {
+                        contact_presence.write().set_status(&did, PeerStatus::Offline);
+                    }
+                    ContactEvent::ContactAccepted { .. } | ContactEvent::ContactRemoved { .. } => {
+                        let shared = engine();
+                        let guard = shared.read().await;
+                        if let Some(ref eng) = *guard {
+                            if let Ok(contacts) = eng.list_peer_contacts() {
+                                contact_presence.set(ContactPresence::from_contacts(&contacts));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+    });
+
     rsx! {
         style { {GLOBAL_STYLES} }
         Router::<Route> {}
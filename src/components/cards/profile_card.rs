@@ -9,6 +9,7 @@ use super::{CardGallery, CardHeader, CardOrientation, GoldenCard};
 use super::card_gallery::GalleryItem;
 use crate::components::images::{AsyncImage, ImageOrientation, ImageUpload};
 use crate::components::profile::QRSignature;
+use crate::context::use_engine;
 
 // Embed default profile image as base64 data URI
 const PROFILE_DEFAULT_BYTES: &[u8] = include_bytes!("../../../assets/profile-default.png");
@@ -83,6 +84,7 @@ pub fn ProfileCard(
     // Start in edit mode if profile has default/empty name
     let should_start_editing = profile.display_name.is_empty();
     let mut editing = use_signal(move || should_start_editing);
+    let engine = use_engine();
     let mut draft = use_signal(|| profile.clone());
 
     // Track profile prop as a signal for reactive memos (display mode)
@@ -165,8 +167,17 @@ pub fn ProfileCard(
                         ImageUpload {
                             orientation: ImageOrientation::Portrait,
                             icon_only: true,
-                            on_upload: move |blob_id| {
-                                draft.write().avatar_blob_id = Some(blob_id);
+                            on_upload: move |blob_id: String| {
+                                draft.write().avatar_blob_id = Some(blob_id.clone());
+                                // Thumbnail for list views, recorded against the avatar
+                                spawn(async move {
+                                    let shared = engine();
+                                    let guard = shared.read().await;
+                                    let Some(eng) = guard.as_ref() else { return };
+                                    if let Err(e) = eng.create_avatar_thumbnail(&blob_id).await {
+                                        tracing::warn!("Failed to make avatar thumbnail: {:?}", e);
+                                    }
+                                });
                             },
                         }
                     }
@@ -1,10 +1,12 @@
 //! Contact Card Component
 //!
-//! Individual contact card showing avatar, name, and online status.
+//! Individual contact card showing avatar, name, online status, and favorite star.
 
 use dioxus::prelude::*;
 
-use crate::components::images::AsyncImage;
+use syncengine_core::Did;
+
+use crate::components::images::{AsyncImage, Identicon};
 
 // Embed default profile image as base64 data URI
 const PROFILE_DEFAULT_BYTES: &[u8] = include_bytes!("../../../assets/profile-default.png");
@@ -18,6 +20,9 @@ fn profile_default_uri() -> String {
 /// Contact Card
 ///
 /// Displays a single contact with avatar, name, and online/offline status indicator.
+/// The star toggles the favorite flag; the new value goes to `on_toggle_favorite`.
+/// `name_collision` adds a warning badge suggesting a nickname.
+/// Contacts without an avatar get an identicon generated from their DID.
 ///
 /// # Example
 ///
@@ -26,9 +31,12 @@ fn profile_default_uri() -> String {
 ///     ContactCard {
 ///         contact_name: "Alice Smith".to_string(),
 ///         contact_avatar: Some("blob_id_here".to_string()),
+///         contact_did: Some("did:sync:z...".to_string()),
 ///         is_online: true,
+///         is_favorite: false,
 ///         index: 0,
 ///         on_click: move |_| { /* Handle click */ },
+///         on_toggle_favorite: move |favorite: bool| { /* Persist the star */ },
 ///     }
 /// }
 /// ```
@@ -39,21 +47,35 @@ pub fn ContactCard(
     /// Optional avatar blob ID
     #[props(default = None)]
     contact_avatar: Option<String>,
+    /// Contact's DID, used to draw an identicon when there is no avatar
+    #[props(default = None)]
+    contact_did: Option<String>,
     /// Whether contact is currently online
     #[props(default = false)]
     is_online: bool,
     /// Whether this contact has recent packet activity
     #[props(default = false)]
     has_activity: bool,
+    /// Whether the contact is starred
+    #[props(default = false)]
+    is_favorite: bool,
+    /// Whether another contact uses the same display name
+    #[props(default = false)]
+    name_collision: bool,
     /// Index for staggered animation
     #[props(default = 0)]
     index: usize,
     /// Optional click handler
     #[props(default = None)]
     on_click: Option<EventHandler<()>>,
+    /// Called with the new favorite state when the star is clicked
+    #[props(default = None)]
+    on_toggle_favorite: Option<EventHandler<bool>>,
 ) -> Element {
     let status_class = if is_online { "online" } else { "offline" };
+    let favorite_class = if is_favorite { "favorite" } else { "" };
     let activity_class = if has_activity { "packet-activity" } else { "" };
+    let identicon_did = contact_did.as_deref().and_then(|did| Did::parse(did).ok());
 
     let handle_click = move |_| {
         if let Some(handler) = &on_click {
@@ -63,7 +85,7 @@ pub fn ContactCard(
 
     rsx! {
         div {
-            class: "contact-card {status_class} {activity_class}",
+            class: "contact-card {status_class} {activity_class} {favorite_class}",
             style: "--index: {index}",
             onclick: handle_click,
 
@@ -74,6 +96,13 @@ pub fn ContactCard(
                         blob_id: blob_id,
                         alt: contact_name.clone(),
                         class: Some("avatar-image".to_string()),
+                        thumbnail: true,
+                    }
+                } else if let Some(did) = identicon_did {
+                    Identicon {
+                        did: did,
+                        alt: contact_name.clone(),
+                        class: Some("avatar-image".to_string()),
                     }
                 } else {
                     // Default profile image
@@ -84,6 +113,18 @@ pub fn ContactCard(
                     }
                 }
 
+                if let Some(handler) = on_toggle_favorite {
+                    button {
+                        class: "favorite-star",
+                        title: if is_favorite { "Remove from favorites" } else { "Add to favorites" },
+                        onclick: move |evt| {
+                            evt.stop_propagation();
+                            handler.call(!is_favorite);
+                        },
+                        if is_favorite { "★" } else { "☆" }
+                    }
+                }
+
                 // Status dot indicator
                 div {
                     class: "status-dot",
@@ -94,6 +135,13 @@ pub fn ContactCard(
             // Name below avatar
             div { class: "contact-name",
                 "{contact_name}"
+                if name_collision {
+                    span {
+                        class: "name-collision-badge",
+                        title: "Another contact uses this name. Set a nickname to tell them apart.",
+                        "⚠"
+                    }
+                }
             }
         }
     }
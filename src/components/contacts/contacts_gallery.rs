@@ -4,11 +4,12 @@
 //! Now uses the unified Peer type for consistency with the rest of the system.
 //! Also shows packet activity visualization when messages are sent/received.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use dioxus::prelude::*;
 use syncengine_core::sync::ContactEvent;
 use syncengine_core::types::contact::ContactStatus;
+use syncengine_core::types::ProfilePin;
 use syncengine_core::{Peer, PeerStatus};
 
 use super::ContactCard;
@@ -36,6 +37,13 @@ pub fn ContactsGallery() -> Element {
     let mut loading = use_signal(|| true);
     // Track which contacts have recent packet activity (by DID)
     let mut active_contacts = use_signal(|| HashSet::<String>::new());
+    // Search box text, and the DIDs it matched in rank order (None = no search)
+    let mut search_query = use_signal(String::new);
+    let mut search_results = use_signal(|| Option::<Vec<String>>::None);
+    // Latest pinned profiles by DID; fresher than the snapshot taken at contact exchange
+    let mut pinned_profiles = use_signal(HashMap::<String, ProfilePin>::new);
+    // DIDs whose profile name is shared with another contact (possible impersonation)
+    let mut colliding_dids = use_signal(HashSet::<String>::new);
 
     // Load contacts on mount and poll for updates
     use_effect(move || {
@@ -48,12 +56,27 @@ pub fn ContactsGallery() -> Element {
                     // Use the new unified peer list, filtered to contacts only
                     match eng.list_peer_contacts() {
                         Ok(loaded_contacts) => {
+                            // One storage pass for every contact's pinned profile
+                            let dids: Vec<&str> = loaded_contacts.iter().filter_map(|c| c.did.as_deref()).collect();
+                            match eng.get_pinned_profiles_bulk(&dids) {
+                                Ok(pins) => pinned_profiles.set(pins),
+                                Err(e) => tracing::warn!("Failed to load pinned profiles: {:?}", e),
+                            }
                             contacts.set(loaded_contacts);
                         }
                         Err(e) => {
                             tracing::error!("Failed to load contacts: {:?}", e);
                         }
                     }
+                    match eng.find_name_collisions() {
+                        Ok(collisions) => colliding_dids.set(
+                            collisions
+                                .into_iter()
+                                .flat_map(|(_, dids)| dids.into_iter().map(|did| did.to_string()))
+                                .collect(),
+                        ),
+                        Err(e) => tracing::warn!("Failed to check contact names: {:?}", e),
+                    }
                 }
                 loading.set(false);
 
@@ -153,13 +176,13 @@ pub fn ContactsGallery() -> Element {
         };
     }
 
-    let contact_list = contacts();
-    let online_count = contact_list
+    let all_contacts = contacts();
+    let online_count = all_contacts
         .iter()
         .filter(|c| matches!(c.status, PeerStatus::Online))
         .count();
 
-    if contact_list.is_empty() {
+    if all_contacts.is_empty() {
         return rsx! {
             div { class: "contacts-gallery-empty",
                 h3 { class: "section-title", "Contacts" }
@@ -175,20 +198,84 @@ pub fn ContactsGallery() -> Element {
         };
     }
 
+    // Show search matches in rank order, or everyone (favorites first, then by name)
+    let contact_list: Vec<Peer> = match search_results() {
+        Some(dids) => dids
+            .iter()
+            .filter_map(|did| all_contacts.iter().find(|c| c.did.as_deref() == Some(did.as_str())))
+            .cloned()
+            .collect(),
+        None => {
+            let mut sorted = all_contacts;
+            sorted.sort_by_cached_key(|c| {
+                let name = c.nickname.clone().unwrap_or_else(|| c.display_name());
+                (!is_favorite(c), name.to_lowercase())
+            });
+            sorted
+        }
+    };
+
+    let handle_search = move |evt: FormEvent| {
+        let query = evt.value();
+        search_query.set(query.clone());
+        if query.trim().is_empty() {
+            search_results.set(None);
+            return;
+        }
+        spawn(async move {
+            let shared = engine();
+            let guard = shared.read().await;
+            if let Some(ref eng) = *guard {
+                match eng.search_contacts(&query) {
+                    // Ignore results for a query the user has already typed past
+                    Ok(found) if search_query() == query => {
+                        search_results.set(Some(found.into_iter().map(|c| c.peer_did).collect()));
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to search contacts: {:?}", e),
+                }
+            }
+        });
+    };
+
     rsx! {
         div { class: "contacts-gallery",
             h3 { class: "section-title",
                 "Contacts ({online_count} online)"
             }
 
+            input {
+                class: "contact-search",
+                r#type: "search",
+                placeholder: "Search by name or DID…",
+                value: "{search_query}",
+                oninput: handle_search,
+            }
+
+            if contact_list.is_empty() {
+                p { class: "empty-hint", "No contacts match \"{search_query}\"." }
+            }
+
             div { class: "contact-grid",
                 {contact_list.iter().enumerate().map(|(index, contact)| {
                     let contact_did = contact.did.clone().unwrap_or_else(|| format!("peer_{}", hex::encode(&contact.endpoint_id[..4])));
                     let contact_did_for_click = contact_did.clone();
                     let contact_did_for_activity = contact_did.clone();
-                    let contact_name_display = contact.display_name();
-                    let contact_avatar_display = contact.profile.as_ref().and_then(|p| p.avatar_blob_id.clone());
+                    let pinned = contact.did.as_ref().and_then(|did| pinned_profiles.read().get(did).map(|pin| pin.signed_profile.profile.clone()));
+                    // Our local nickname wins over whatever name they gave themselves
+                    let contact_name_display = contact.nickname.clone()
+                        .or_else(|| pinned.as_ref().map(|p| p.display_name.clone()))
+                        .unwrap_or_else(|| contact.display_name());
+                    let contact_avatar_display = match pinned {
+                        Some(profile) => profile.avatar_blob_id,
+                        None => contact.profile.as_ref().and_then(|p| p.avatar_blob_id.clone()),
+                    };
                     let is_online_display = matches!(contact.status, PeerStatus::Online);
+                    let is_favorite_display = is_favorite(contact);
+                    // A nickname already tells same-named contacts apart
+                    let name_collision_display = contact.nickname.is_none()
+                        && contact.did.as_ref().is_some_and(|did| colliding_dids.read().contains(did));
+                    let contact_did_for_star = contact.did.clone();
                     // Check if this contact has recent packet activity
                     let has_activity_display = active_contacts().contains(&contact_did_for_activity);
 
@@ -197,12 +284,37 @@ pub fn ContactsGallery() -> Element {
                             key: "{contact_did}",
                             contact_name: contact_name_display,
                             contact_avatar: contact_avatar_display,
+                            contact_did: contact.did.clone(),
                             is_online: is_online_display,
+                            is_favorite: is_favorite_display,
+                            name_collision: name_collision_display,
                             has_activity: has_activity_display,
                             index: index,
                             on_click: move |_| {
                                 tracing::info!("Clicked contact: {}", contact_did_for_click);
                             },
+                            on_toggle_favorite: move |favorite: bool| {
+                                let Some(did) = contact_did_for_star.clone() else { return };
+                                spawn(async move {
+                                    let shared = engine();
+                                    let guard = shared.read().await;
+                                    if let Some(ref eng) = *guard {
+                                        match eng.set_contact_favorite(&did, favorite) {
+                                            Ok(()) => {
+                                                if let Some(info) = contacts
+                                                    .write()
+                                                    .iter_mut()
+                                                    .find(|c| c.did.as_deref() == Some(did.as_str()))
+                                                    .and_then(|c| c.contact_info.as_mut())
+                                                {
+                                                    info.is_favorite = favorite;
+                                                }
+                                            }
+                                            Err(e) => tracing::error!("Failed to update favorite: {:?}", e),
+                                        }
+                                    }
+                                });
+                            },
                         }
                     }
                 })}
@@ -210,3 +322,7 @@ pub fn ContactsGallery() -> Element {
         }
     }
 }
+
+fn is_favorite(contact: &Peer) -> bool {
+    contact.contact_info.as_ref().is_some_and(|info| info.is_favorite)
+}
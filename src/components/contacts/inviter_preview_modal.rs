@@ -20,6 +20,7 @@ use crate::components::images::AsyncImage;
 ///         inviter_subtitle: Some("Developer".to_string()),
 ///         inviter_bio: "Loves building cool things...".to_string(),
 ///         inviter_avatar: Some("blob_id_here".to_string()),
+///         greeting: Some("Hi, we met at the garden!".to_string()),
 ///         on_close: move |_| show_preview.set(false),
 ///         on_accept: move |_| { /* Accept contact */ },
 ///         on_decline: move |_| { /* Decline contact */ },
@@ -38,6 +39,9 @@ pub fn InviterPreviewModal(
     /// Optional avatar blob ID
     #[props(default = None)]
     inviter_avatar: Option<String>,
+    /// Greeting the inviter attached to the invite
+    #[props(default = None)]
+    greeting: Option<String>,
     /// Show/hide modal
     #[props(default = true)]
     show: bool,
@@ -95,6 +99,10 @@ pub fn InviterPreviewModal(
                     p { class: "inviter-bio", "{inviter_bio}" }
                 }
 
+                if let Some(greeting) = greeting {
+                    blockquote { class: "inviter-greeting", "{greeting}" }
+                }
+
                 p { class: "request-message",
                     "This user wants to connect with you"
                 }
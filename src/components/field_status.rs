@@ -8,15 +8,21 @@
 //! | State | Sacred Term | Meaning |
 //! |-------|-------------|---------|
 //! | Idle | "field dormant" | Not connected, not syncing |
+//! | Idle, work queued | "field dormant — N intentions held" | Offline with local work waiting to sync |
 //! | Connecting | "seeking resonance..." | Establishing peer connections |
 //! | Syncing(0) | "field listening" | Connected but no peers yet |
-//! | Syncing(1+) | "field resonating • N souls" | Actively syncing with peers |
+//! | Syncing(1+), listening | "field listening • N souls" | Peers online, weak links or no recent sync |
+//! | Syncing(1+), resonating | "field resonating • N souls" | Healthy links and recent sync activity |
 //! | Error | "dissonance" | Connection error |
 //!
+//! Listening vs. resonating with peers online comes from the engine's
+//! [`ResonanceLevel`], which is derived from peer count, connection quality
+//! and sync recency.
+//!
 //! Clicking the indicator shows a debug dropdown with network details.
 
 use dioxus::prelude::*;
-use syncengine_core::{NetworkDebugInfo, SyncStatus};
+use syncengine_core::{NetworkDebugInfo, PendingSync, ResonanceLevel, SyncStatus};
 
 /// Format a duration in seconds into a human-readable string
 fn format_duration(seconds: u64) -> String {
@@ -31,6 +37,15 @@ fn format_duration(seconds: u64) -> String {
     }
 }
 
+/// Label for an offline field, mentioning queued work if there is any
+fn dormant_label(held: usize) -> String {
+    match held {
+        0 => "field dormant".to_string(),
+        1 => "field dormant — 1 intention held".to_string(),
+        n => format!("field dormant — {} intentions held", n),
+    }
+}
+
 /// Legacy field state enum for backwards compatibility.
 /// New code should use NetworkState with actual SyncStatus.
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
@@ -67,6 +82,10 @@ pub struct NetworkState {
     pub status: SyncStatus,
     /// Whether data was recently synced (for activity indicator)
     pub recently_active: bool,
+    /// Resonance derived from network metrics
+    pub resonance: ResonanceLevel,
+    /// Local work waiting to reach peers
+    pub pending: PendingSync,
 }
 
 impl NetworkState {
@@ -75,18 +94,37 @@ impl NetworkState {
         Self {
             status,
             recently_active: false,
+            resonance: ResonanceLevel::default(),
+            pending: PendingSync::default(),
         }
     }
 
+    /// Set the queued work shown while offline
+    pub fn with_pending(mut self, pending: PendingSync) -> Self {
+        self.pending = pending;
+        self
+    }
+
+    /// Set the metric-driven resonance level
+    pub fn with_resonance(mut self, resonance: ResonanceLevel) -> Self {
+        self.resonance = resonance;
+        self
+    }
+
     /// Get the sacred label for this state
     pub fn label(&self) -> String {
         match &self.status {
-            SyncStatus::Idle => "field dormant".to_string(),
+            SyncStatus::Idle => dormant_label(self.pending.total()),
             SyncStatus::Connecting => "seeking resonance".to_string(),
             SyncStatus::Syncing { peer_count: 0 } => "field listening".to_string(),
             SyncStatus::Syncing { peer_count } => {
                 let souls = if *peer_count == 1 { "soul" } else { "souls" };
-                format!("field resonating · {} {}", peer_count, souls)
+                let field = if self.is_resonating() {
+                    "field resonating"
+                } else {
+                    "field listening"
+                };
+                format!("{} · {} {}", field, peer_count, souls)
             }
             SyncStatus::Error(_) => "dissonance".to_string(),
         }
@@ -97,8 +135,8 @@ impl NetworkState {
         match &self.status {
             SyncStatus::Idle => "resonance-dot dormant",
             SyncStatus::Connecting => "resonance-dot seeking",
-            SyncStatus::Syncing { peer_count: 0 } => "resonance-dot listening",
-            SyncStatus::Syncing { .. } => "resonance-dot resonating",
+            SyncStatus::Syncing { .. } if self.is_resonating() => "resonance-dot resonating",
+            SyncStatus::Syncing { .. } => "resonance-dot listening",
             SyncStatus::Error(_) => "resonance-dot dissonance",
         }
     }
@@ -108,15 +146,16 @@ impl NetworkState {
         match &self.status {
             SyncStatus::Idle => "resonance-label dormant",
             SyncStatus::Connecting => "resonance-label seeking",
-            SyncStatus::Syncing { peer_count: 0 } => "resonance-label listening",
-            SyncStatus::Syncing { .. } => "resonance-label resonating",
+            SyncStatus::Syncing { .. } if self.is_resonating() => "resonance-label resonating",
+            SyncStatus::Syncing { .. } => "resonance-label listening",
             SyncStatus::Error(_) => "resonance-label dissonance",
         }
     }
 
-    /// Check if actively syncing with peers
+    /// Check if actively syncing with peers over healthy links
     pub fn is_resonating(&self) -> bool {
         matches!(&self.status, SyncStatus::Syncing { peer_count } if *peer_count > 0)
+            && self.resonance == ResonanceLevel::Resonating
     }
 
     /// Get peer count if syncing
@@ -129,18 +168,24 @@ impl NetworkState {
 }
 
 /// Legacy status indicator (for backwards compatibility)
+///
+/// `held` is the number of queued actions, shown while dormant.
 #[component]
-pub fn FieldStatus(status: FieldState) -> Element {
+pub fn FieldStatus(status: FieldState, #[props(default)] held: usize) -> Element {
     let dot_class = if status.is_active() {
         "status-dot active"
     } else {
         "status-dot"
     };
+    let label = match status {
+        FieldState::Dormant => dormant_label(held),
+        _ => status.label().to_string(),
+    };
 
     rsx! {
         div { class: "field-status",
             span { class: "{dot_class}" }
-            span { class: "status-label", "{status.label()}" }
+            span { class: "status-label", "{label}" }
         }
     }
 }
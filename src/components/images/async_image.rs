@@ -26,6 +26,9 @@ pub fn AsyncImage(
     /// Optional CSS class
     #[props(default = None)]
     class: Option<String>,
+    /// Show the avatar's thumbnail instead, making one if it's missing
+    #[props(default = false)]
+    thumbnail: bool,
 ) -> Element {
     let engine = use_engine();
     let mut image_data = use_signal(|| Option::<String>::None);
@@ -43,6 +46,15 @@ pub fn AsyncImage(
             let guard = shared.read().await;
 
             if let Some(ref eng) = *guard {
+                let blob_id = if thumbnail {
+                    match eng.avatar_thumbnail(&blob_id) {
+                        Ok(Some(thumb)) => thumb,
+                        // Falls back to the full avatar if it can't be made yet
+                        _ => eng.create_avatar_thumbnail(&blob_id).await.unwrap_or(blob_id),
+                    }
+                } else {
+                    blob_id
+                };
                 match eng.load_image(&blob_id).await {
                     Ok(Some(data)) => {
                         // Convert to base64 data URI
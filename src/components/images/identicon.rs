@@ -0,0 +1,46 @@
+//! Identicon
+//!
+//! Generated avatar for identities that haven't uploaded one.
+
+use dioxus::prelude::*;
+use syncengine_core::Did;
+
+/// Deterministic avatar drawn from a DID
+///
+/// The same DID always renders the same pattern and color.
+///
+/// # Examples
+///
+/// ```rust
+/// rsx! {
+///     Identicon {
+///         did: did.clone(),
+///         alt: "Love".to_string(),
+///     }
+/// }
+/// ```
+#[component]
+pub fn Identicon(
+    /// Identity to draw
+    did: Did,
+    /// Alt text for accessibility
+    alt: String,
+    /// Optional CSS class
+    #[props(default = None)]
+    class: Option<String>,
+) -> Element {
+    use base64::Engine;
+    let svg = did.to_identicon_svg();
+    let src = format!(
+        "data:image/svg+xml;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(svg)
+    );
+
+    rsx! {
+        img {
+            class: class.unwrap_or_default(),
+            src: "{src}",
+            alt: "{alt}",
+        }
+    }
+}
@@ -3,10 +3,13 @@
 //! File picker with automatic golden ratio cropping.
 
 use dioxus::prelude::*;
-use image::{DynamicImage, GenericImageView, ImageFormat};
+use image::ImageFormat;
 use rfd::FileDialog;
+use syncengine_core::blobs::crop_to_golden_ratio;
 use crate::context::use_engine;
 
+pub use syncengine_core::ImageOrientation;
+
 /// Image upload button with golden ratio cropping
 ///
 /// # Examples
@@ -22,14 +25,6 @@ use crate::context::use_engine;
 ///     }
 /// }
 /// ```
-#[derive(Clone, Copy, PartialEq)]
-pub enum ImageOrientation {
-    /// Portrait: 1:1.618 (height > width)
-    Portrait,
-    /// Landscape: 1.618:1 (width > height)
-    Landscape,
-}
-
 #[component]
 pub fn ImageUpload(
     /// Desired image orientation (crops to golden ratio)
@@ -68,7 +63,7 @@ pub fn ImageUpload(
                     match image::open(&path) {
                         Ok(img) => {
                             // Crop to golden ratio
-                            let cropped = crop_to_golden_ratio(img, orientation);
+                            let cropped = crop_to_golden_ratio(&img, orientation);
 
                             // Encode as PNG (lossless)
                             let mut buffer = Vec::new();
@@ -150,40 +145,3 @@ pub fn ImageUpload(
         }
     }
 }
-
-/// Crop image to golden ratio (1:1.618)
-///
-/// Centers the crop and maintains the golden proportion based on orientation.
-fn crop_to_golden_ratio(img: DynamicImage, orientation: ImageOrientation) -> DynamicImage {
-    let (width, height) = img.dimensions();
-    let phi = 1.618;
-
-    let (target_w, target_h) = match orientation {
-        ImageOrientation::Portrait => {
-            // Portrait: 1:1.618 (taller)
-            let new_h = (width as f64 * phi) as u32;
-            if new_h <= height {
-                (width, new_h)
-            } else {
-                let new_w = (height as f64 / phi) as u32;
-                (new_w, height)
-            }
-        }
-        ImageOrientation::Landscape => {
-            // Landscape: 1.618:1 (wider)
-            let new_w = (height as f64 * phi) as u32;
-            if new_w <= width {
-                (new_w, height)
-            } else {
-                let new_h = (width as f64 / phi) as u32;
-                (width, new_h)
-            }
-        }
-    };
-
-    // Center crop
-    let x = (width.saturating_sub(target_w)) / 2;
-    let y = (height.saturating_sub(target_h)) / 2;
-
-    img.crop_imm(x, y, target_w, target_h)
-}
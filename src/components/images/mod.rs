@@ -3,7 +3,9 @@
 //! Upload, display, and manage images with golden ratio cropping.
 
 mod async_image;
+mod identicon;
 pub mod image_upload;
 
 pub use async_image::AsyncImage;
+pub use identicon::Identicon;
 pub use image_upload::{ImageOrientation, ImageUpload};
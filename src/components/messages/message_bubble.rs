@@ -4,6 +4,7 @@
 //! Follows DESIGN_SYSTEM.md cyber-mystical terminal aesthetic.
 
 use dioxus::prelude::*;
+use syncengine_core::chat::{split_mentions, MessageSegment};
 
 /// A chat message for display in a bubble
 #[derive(Clone, Debug, PartialEq)]
@@ -18,6 +19,8 @@ pub struct ChatBubbleMessage {
     pub timestamp: i64,
     /// Whether this message was sent by us
     pub is_mine: bool,
+    /// Byte ranges of `@` mentions in `content`, for highlighting
+    pub mention_spans: Vec<std::ops::Range<usize>>,
 }
 
 /// Format timestamp as relative time
@@ -62,8 +65,17 @@ pub fn MessageBubble(message: ChatBubbleMessage) -> Element {
                     }
                 }
 
-                // Message content
-                div { class: "message-bubble-content", "{message.content}" }
+                // Message content, with mentions highlighted
+                div { class: "message-bubble-content",
+                    for segment in split_mentions(&message.content, &message.mention_spans) {
+                        match segment {
+                            MessageSegment::Text(text) => rsx! { "{text}" },
+                            MessageSegment::Mention(handle) => rsx! {
+                                span { class: "message-mention", "{handle}" }
+                            },
+                        }
+                    }
+                }
 
                 // Timestamp
                 div { class: "message-bubble-time", "{format_time(message.timestamp)}" }
@@ -4,12 +4,15 @@
 //! Mobile: Hidden (replaced by MobileNav)
 
 use dioxus::prelude::*;
-use syncengine_core::{PacketEvent, Peer, PeerStatus};
+use syncengine_core::{PacketEvent, Peer};
 
 use crate::app::Route;
 use crate::components::mobile_nav::MobileNav;
 use crate::components::PeerStatusDropdown;
-use crate::context::{use_engine, use_engine_ready, use_pending_chat_contact, PendingChatContact};
+use crate::context::{
+    use_contact_presence, use_engine, use_engine_ready, use_pending_chat_contact,
+    PendingChatContact,
+};
 
 /// Navigation location within the application
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -55,6 +58,7 @@ pub struct NavHeaderProps {
 pub fn NavHeader(props: NavHeaderProps) -> Element {
     let engine = use_engine();
     let engine_ready = use_engine_ready();
+    let contact_presence = use_contact_presence();
 
     // State
     let mut show_dropdown = use_signal(|| false);
@@ -155,9 +159,8 @@ pub fn NavHeader(props: NavHeaderProps) -> Element {
     };
 
     let peer_count = peers().len();
-    let online_count = peers().iter()
-        .filter(|p| matches!(p.status, PeerStatus::Online))
-        .count();
+    let online_count = contact_presence().online_contact_count();
+    let contact_count = contact_presence().contact_count();
 
     rsx! {
         header { class: "nav-header-v2",
@@ -191,7 +194,8 @@ pub fn NavHeader(props: NavHeaderProps) -> Element {
                         r#type: "button",
                         class: if syncing() { "status-orb-btn syncing" } else { "status-orb-btn" },
                         onclick: move |_| show_dropdown.set(!show_dropdown()),
-                        "aria-label": "Connection status - {online_count} peers online",
+                        "aria-label": "Connection status - {online_count} of {contact_count} contacts online",
+                        title: "{online_count} of {contact_count} resonating",
                         "aria-expanded": "{show_dropdown()}",
 
                         span { class: if syncing() { "status-orb syncing" } else { "status-orb" } }
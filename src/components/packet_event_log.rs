@@ -28,7 +28,8 @@ fn decrypt_indicator(status: &DecryptionStatus) -> (&'static str, &'static str)
     match status {
         DecryptionStatus::Decrypted => ("\u{2713}", "decrypt-success"),     // ✓
         DecryptionStatus::Global => ("\u{25CB}", "decrypt-global"),          // ○
-        DecryptionStatus::CannotDecrypt { .. } => ("\u{2717}", "decrypt-fail"), // ✗
+        DecryptionStatus::NotForMe => ("\u{2717}", "decrypt-fail"),         // ✗
+        DecryptionStatus::Failed { .. } => ("\u{26A0}", "decrypt-fail"),    // ⚠
         DecryptionStatus::NotAttempted => ("\u{2014}", "decrypt-pending"),   // —
     }
 }
@@ -1,13 +1,18 @@
 //! Peer Card - Individual peer display with status and metrics.
 
 use dioxus::prelude::*;
-use syncengine_core::{PeerInfo, PeerStatus};
+use syncengine_core::{Did, PeerInfo, PeerStatus};
+
+use crate::components::images::{AsyncImage, Identicon};
 
 /// Props for the peer card component.
 #[derive(Props, Clone, PartialEq)]
 pub struct PeerCardProps {
     /// Peer information
     pub peer: PeerInfo,
+    /// Peer's DID, if known, shown as an identicon
+    #[props(default)]
+    pub did: Option<Did>,
 }
 
 /// Format timestamp as relative time string.
@@ -40,6 +45,13 @@ pub fn PeerCard(props: PeerCardProps) -> Element {
     // Format peer ID (first 4 bytes as hex)
     let peer_id_short = hex::encode(&props.peer.endpoint_id[..4]);
 
+    // Only the thumbnail; the full avatar loads when the profile is opened
+    let avatar = props
+        .peer
+        .profile
+        .as_ref()
+        .and_then(|profile| profile.avatar_blob_id.clone());
+
     // Format last seen
     let last_seen = if is_online {
         "Now".to_string()
@@ -51,6 +63,21 @@ pub fn PeerCard(props: PeerCardProps) -> Element {
         div { class: "peer-card",
             // Status and name row
             div { class: "peer-status",
+                if let Some(blob_id) = avatar {
+                    AsyncImage {
+                        blob_id: blob_id,
+                        alt: peer_id_short.clone(),
+                        class: Some("peer-identicon".to_string()),
+                        thumbnail: true,
+                    }
+                } else if let Some(did) = props.did.clone() {
+                    Identicon {
+                        did: did,
+                        alt: peer_id_short.clone(),
+                        class: Some("peer-identicon".to_string()),
+                    }
+                }
+
                 span {
                     class: if is_online {
                         "peer-status-dot online"
@@ -11,6 +11,12 @@ pub struct StewardshipStatsProps {
     pub intention_count: u32,
     /// Number of intentions manifested (completed)
     pub manifested_count: u32,
+    /// Gratitude packets received from contacts
+    #[props(default)]
+    pub gratitude_received: u32,
+    /// Gratitude packets sent to contacts
+    #[props(default)]
+    pub gratitude_given: u32,
 }
 
 /// Stewardship stats card showing activity metrics.
@@ -35,6 +41,14 @@ pub fn StewardshipStats(props: StewardshipStatsProps) -> Element {
                     div { class: "stat-value", "{props.manifested_count}" }
                     div { class: "stat-label", "Manifested" }
                 }
+                div { class: "stat-box",
+                    div { class: "stat-value", "{props.gratitude_received}" }
+                    div { class: "stat-label", "Thanked" }
+                }
+                div { class: "stat-box",
+                    div { class: "stat-value", "{props.gratitude_given}" }
+                    div { class: "stat-label", "Thanks Given" }
+                }
             }
 
             // Future: Recent activity feed
@@ -76,6 +76,8 @@ pub fn RealmSelector(props: RealmSelectorProps) -> Element {
                             RealmItem {
                                 key: "{realm_id_key}",
                                 name: realm.name.clone(),
+                                emoji: realm.metadata.emoji.clone(),
+                                color: realm.metadata.color.clone(),
                                 is_shared: realm.is_shared,
                                 is_selected: is_selected,
                                 on_click: move |_| on_select.call(realm_id.clone()),
@@ -120,6 +122,10 @@ pub fn RealmSelector(props: RealmSelectorProps) -> Element {
 struct RealmItemProps {
     /// Realm name to display
     name: String,
+    /// Emoji shown before the name, from the realm's metadata
+    emoji: Option<String>,
+    /// Accent color from the realm's metadata
+    color: Option<String>,
     /// Whether this realm is shared with peers
     is_shared: bool,
     /// Whether this realm is currently selected
@@ -136,11 +142,19 @@ fn RealmItem(props: RealmItemProps) -> Element {
     } else {
         "realm-item"
     };
+    let (item_class, item_style) = match &props.color {
+        Some(color) => (format!("{item_class} branded"), format!("--realm-color: {color}")),
+        None => (item_class.to_string(), String::new()),
+    };
 
     rsx! {
         button {
             class: "{item_class}",
+            style: "{item_style}",
             onclick: move |_| props.on_click.call(()),
+            if let Some(emoji) = &props.emoji {
+                span { class: "realm-emoji", "{emoji}" }
+            }
             span { class: "realm-name", "{props.name}" }
             if props.is_shared {
                 span { class: "realm-shared-badge", "shared" }
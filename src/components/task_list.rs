@@ -23,20 +23,30 @@ use syncengine_core::{Task, TaskId};
 /// - Toggle checkbox (circle when incomplete, checkmark when complete)
 /// - Title with strikethrough when completed
 /// - Delete button that appears on hover
+/// - "also edited as …" choices when peers renamed it concurrently
+/// - The member it is assigned to, if any
 ///
 /// # Props
 ///
 /// * `task` - The task data to display
 /// * `on_toggle` - Called when the checkbox is clicked
 /// * `on_delete` - Called when the delete button is clicked
+/// * `on_choose_title` - Called with the title picked from a conflict; the
+///   choices are only shown when this is set
 #[component]
 pub fn TaskItem(
     task: Task,
     on_toggle: EventHandler<TaskId>,
     on_delete: EventHandler<TaskId>,
+    #[props(default)] on_choose_title: Option<EventHandler<(TaskId, String)>>,
 ) -> Element {
     let task_id = task.id.clone();
     let task_id_for_delete = task.id.clone();
+    let assignee = assignee_label(&task);
+    let alternatives: Vec<String> = match on_choose_title {
+        Some(_) => task.conflicting_values.iter().map(|(_, title)| title.clone()).collect(),
+        None => Vec::new(),
+    };
 
     let check_class = if task.completed {
         "check completed"
@@ -66,6 +76,30 @@ pub fn TaskItem(
                 span { class: "{check_class}", "{check_symbol}" }
             }
             span { class: "{title_class}", "{task.title}" }
+            if let Some(assignee) = assignee {
+                span { class: "intention-assignee", title: "assigned to", "\u{2192} {assignee}" }
+            }
+            if !alternatives.is_empty() {
+                span { class: "intention-conflicts",
+                    "also edited as "
+                    for title in alternatives {
+                        button {
+                            class: "intention-conflict-choice",
+                            title: "keep this title",
+                            onclick: {
+                                let task_id = task.id.clone();
+                                let title = title.clone();
+                                move |_| {
+                                    if let Some(handler) = on_choose_title {
+                                        handler.call((task_id.clone(), title.clone()));
+                                    }
+                                }
+                            },
+                            "\u{201C}{title}\u{201D}"
+                        }
+                    }
+                }
+            }
             button {
                 class: "intention-delete",
                 onclick: move |_| on_delete.call(task_id_for_delete.clone()),
@@ -77,6 +111,19 @@ pub fn TaskItem(
     }
 }
 
+/// Who a task is assigned to: their profile name, or a shortened DID
+fn assignee_label(task: &Task) -> Option<String> {
+    if let Some(name) = &task.assignee_name {
+        return Some(name.clone());
+    }
+    let did = task.assignee.as_ref()?.to_string();
+    if did.len() > 20 {
+        Some(format!("{}…", &did[..20]))
+    } else {
+        Some(did)
+    }
+}
+
 /// Input field for manifesting new intentions.
 ///
 /// Features:
@@ -140,6 +187,7 @@ pub fn ManifestInput(on_add: EventHandler<String>) -> Element {
 /// * `on_toggle` - Called when a task's completion is toggled
 /// * `on_delete` - Called when a task should be deleted
 /// * `on_add` - Called with the title when a new task is added
+/// * `on_choose_title` - Called when a title is picked from a rename conflict
 ///
 /// # Example
 ///
@@ -157,6 +205,7 @@ pub fn TaskList(
     on_toggle: EventHandler<TaskId>,
     on_delete: EventHandler<TaskId>,
     on_add: EventHandler<String>,
+    #[props(default)] on_choose_title: Option<EventHandler<(TaskId, String)>>,
 ) -> Element {
     rsx! {
         div { class: "task-list-container",
@@ -176,6 +225,7 @@ pub fn TaskList(
                             task: task.clone(),
                             on_toggle: on_toggle,
                             on_delete: on_delete,
+                            on_choose_title: on_choose_title,
                         }
                     }
                 }
@@ -12,11 +12,12 @@
 //! let engine = use_engine();
 //! ```
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use dioxus::prelude::*;
-use syncengine_core::SyncEngine;
+use syncengine_core::{Peer, PeerStatus, SyncEngine};
 use tokio::sync::RwLock;
 
 /// Shared engine type for context.
@@ -89,3 +90,117 @@ pub struct PendingChatContact {
 pub fn use_pending_chat_contact() -> Signal<Option<PendingChatContact>> {
     use_context::<Signal<Option<PendingChatContact>>>()
 }
+
+/// Presence of every contact, rolled up once for the whole app.
+///
+/// Kept up to date by `App` from the contact list and the engine's
+/// online/offline events (which presence packets and peer status drive),
+/// so headers and status displays don't each query the peer registry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ContactPresence {
+    /// Presence of each contact, keyed by DID
+    presence: HashMap<String, PeerStatus>,
+    /// Number of contacts currently online
+    online_contact_count: usize,
+}
+
+impl ContactPresence {
+    /// Build the roll-up from the contact list.
+    ///
+    /// Contacts without a DID are skipped.
+    pub fn from_contacts(contacts: &[Peer]) -> Self {
+        let mut rollup = Self {
+            presence: contacts
+                .iter()
+                .filter_map(|peer| Some((peer.did.clone()?, peer.status)))
+                .collect(),
+            online_contact_count: 0,
+        };
+        rollup.recount();
+        rollup
+    }
+
+    /// Record a contact's new status and recompute the online count.
+    ///
+    /// DIDs that aren't contacts are ignored.
+    pub fn set_status(&mut self, did: &str, status: PeerStatus) {
+        let Some(current) = self.presence.get_mut(did) else {
+            return;
+        };
+        *current = status;
+        self.recount();
+    }
+
+    /// Number of contacts currently online
+    pub fn online_contact_count(&self) -> usize {
+        self.online_contact_count
+    }
+
+    /// Number of contacts known, online or not
+    pub fn contact_count(&self) -> usize {
+        self.presence.len()
+    }
+
+    /// Presence of each contact, keyed by DID
+    pub fn presence(&self) -> &HashMap<String, PeerStatus> {
+        &self.presence
+    }
+
+    /// Presence of one contact; `Unknown` if it isn't a contact
+    pub fn status(&self, did: &str) -> PeerStatus {
+        self.presence.get(did).copied().unwrap_or_default()
+    }
+
+    fn recount(&mut self) {
+        self.online_contact_count = self
+            .presence
+            .values()
+            .filter(|status| matches!(status, PeerStatus::Online))
+            .count();
+    }
+}
+
+/// Hook to access the contact presence roll-up from context.
+///
+/// Returns a Signal that updates whenever a contact comes online or goes offline.
+pub fn use_contact_presence() -> Signal<ContactPresence> {
+    use_context::<Signal<ContactPresence>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(did: &str, status: PeerStatus) -> Peer {
+        let endpoint = iroh::SecretKey::from_bytes(&[7u8; 32]).public();
+        let mut peer = Peer::new(endpoint, syncengine_core::PeerSource::FromContact);
+        peer.did = Some(did.to_string());
+        peer.status = status;
+        peer
+    }
+
+    #[test]
+    fn test_status_update_recomputes_online_count() {
+        let mut presence = ContactPresence::from_contacts(&[
+            contact("did:sync:love", PeerStatus::Online),
+            contact("did:sync:joy", PeerStatus::Offline),
+            contact("did:sync:peace", PeerStatus::Unknown),
+        ]);
+        assert_eq!(presence.online_contact_count(), 1);
+        assert_eq!(presence.contact_count(), 3);
+
+        presence.set_status("did:sync:joy", PeerStatus::Online);
+        assert_eq!(presence.online_contact_count(), 2);
+        assert_eq!(presence.status("did:sync:joy"), PeerStatus::Online);
+
+        presence.set_status("did:sync:love", PeerStatus::Offline);
+        assert_eq!(presence.online_contact_count(), 1);
+        assert_eq!(presence.presence().len(), 3);
+
+        // A peer that isn't a contact doesn't count
+        presence.set_status("did:sync:stranger", PeerStatus::Online);
+        assert_eq!(presence.online_contact_count(), 1);
+        assert_eq!(presence.contact_count(), 3);
+        assert_eq!(presence.status("did:sync:stranger"), PeerStatus::Unknown);
+    }
+}
@@ -4,7 +4,7 @@
 
 use dioxus::prelude::*;
 use std::collections::HashMap;
-use syncengine_core::{NetworkDebugInfo, RealmId, RealmInfo, SyncEvent, Task};
+use syncengine_core::{NetworkDebugInfo, RealmId, RealmInfo, SyncEngine, SyncEvent, SyncStatus, Task};
 use crate::components::IntentionData;
 
 use crate::app::Route;
@@ -14,6 +14,13 @@ use crate::components::{
 };
 use crate::context::{use_engine, use_engine_ready};
 
+/// Network indicator state for `status`, with the engine's live metrics
+fn network_state_for(eng: &SyncEngine, status: SyncStatus) -> NetworkState {
+    NetworkState::from_status(status)
+        .with_resonance(eng.network_resonance())
+        .with_pending(eng.pending_sync_summary())
+}
+
 /// Main application view component with unified realm-task interface.
 #[component]
 pub fn Field() -> Element {
@@ -73,7 +80,7 @@ pub fn Field() -> Element {
 
                                 // Update network status for first realm
                                 let status = eng.sync_status(&first_realm.id);
-                                network_state.set(NetworkState::from_status(status));
+                                network_state.set(network_state_for(eng, status));
                                 network_debug.set(Some(eng.network_debug_info(&first_realm.id)));
                                 opened_realm.set(Some(first_realm.id.clone()));
                             }
@@ -120,6 +127,10 @@ pub fn Field() -> Element {
                                     // Process any pending sync messages first
                                     let _ = eng.process_pending_sync();
 
+                                    // Applied changes count as sync activity
+                                    let status = network_state.peek().status.clone();
+                                    network_state.set(network_state_for(eng, status));
+
                                     // Update tasks for this realm
                                     if let Ok(task_list) = eng.list_tasks(&realm_id) {
                                         let mut map = tasks_by_realm.read().clone();
@@ -133,7 +144,12 @@ pub fn Field() -> Element {
                             Ok(SyncEvent::StatusChanged { realm_id, status }) => {
                                 // Update network state if this is the opened realm
                                 if opened_realm() == Some(realm_id) {
-                                    network_state.set(NetworkState::from_status(status));
+                                    let previous = network_state.peek().clone();
+                                    network_state.set(
+                                        NetworkState::from_status(status)
+                                            .with_resonance(previous.resonance)
+                                            .with_pending(previous.pending),
+                                    );
                                 }
                             }
                             Ok(
@@ -146,7 +162,7 @@ pub fn Field() -> Element {
                                     let guard = shared.read().await;
                                     if let Some(ref eng) = *guard {
                                         let status = eng.sync_status(&realm_id);
-                                        network_state.set(NetworkState::from_status(status));
+                                        network_state.set(network_state_for(eng, status));
                                         network_debug.set(Some(eng.network_debug_info(&realm_id)));
                                     }
                                 }
@@ -189,7 +205,7 @@ pub fn Field() -> Element {
 
                         // Update network status
                         let status = eng.sync_status(&realm_id);
-                        network_state.set(NetworkState::from_status(status));
+                        network_state.set(network_state_for(eng, status));
                         network_debug.set(Some(eng.network_debug_info(&realm_id)));
                     }
                     Err(e) => {
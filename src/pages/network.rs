@@ -123,6 +123,7 @@ pub fn Network() -> Element {
                                     event,
                                     ContactEvent::ProfileUpdated { .. } |
                                     ContactEvent::ContactAccepted { .. } |
+                                    ContactEvent::ContactRemoved { .. } |
                                     ContactEvent::ContactOnline { .. } |
                                     ContactEvent::ContactOffline { .. }
                                 );
@@ -205,6 +206,8 @@ pub fn Network() -> Element {
                                         sender_name: msg.sender_name.clone(),
                                         timestamp: msg.timestamp,
                                         is_mine: msg.is_mine,
+                                    mention_spans: msg.mention_spans.iter().map(|m| m.range.clone()).collect(),
+                                        mention_spans: msg.mention_spans.iter().map(|m| m.range.clone()).collect(),
                                     })
                                     .collect();
 
@@ -241,6 +244,7 @@ pub fn Network() -> Element {
                                     sender_name: msg.sender_name.clone(),
                                     timestamp: msg.timestamp,
                                     is_mine: msg.is_mine,
+                                    mention_spans: msg.mention_spans.iter().map(|m| m.range.clone()).collect(),
                                 })
                                 .collect();
 
@@ -296,6 +300,7 @@ pub fn Network() -> Element {
                                 sender_name: None,
                                 timestamp: chrono::Utc::now().timestamp_millis(),
                                 is_mine: true,
+                                mention_spans: Vec::new(),
                             };
 
                             let mut msgs = conversation_messages();
@@ -421,6 +421,7 @@ pub fn Profile() -> Element {
                         inviter_subtitle: None,
                         inviter_bio: "Full profile will be available when you connect.".to_string(),
                         inviter_avatar: None,
+                        greeting: invite.greeting.clone(),
                         show: true,
                         on_close: move |_| decoded_invite.set(None),
                         on_accept: move |_| {
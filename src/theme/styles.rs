@@ -844,6 +844,14 @@ body {
   white-space: nowrap;
 }
 
+.realm-item.branded {
+  border-left: 3px solid var(--realm-color);
+}
+
+.realm-emoji {
+  margin-right: 0.5rem;
+}
+
 .realm-shared-badge {
   font-size: var(--text-xs);
   color: var(--cyan);
@@ -960,6 +968,32 @@ body {
   color: var(--danger);
 }
 
+.intention-assignee {
+  font-size: var(--text-sm);
+  color: var(--cyan);
+  margin-right: 0.5rem;
+}
+
+.intention-conflicts {
+  font-family: var(--font-mono);
+  font-size: var(--text-sm);
+  color: var(--text-muted);
+}
+
+.intention-conflict-choice {
+  background: transparent;
+  border: none;
+  color: var(--moss);
+  cursor: pointer;
+  font-family: inherit;
+  font-size: inherit;
+  padding: 0 0.25rem;
+}
+
+.intention-conflict-choice:hover {
+  color: var(--moss-glow);
+}
+
 /* === Empty & Loading States === */
 .no-realm-selected {
   display: flex;
@@ -2407,6 +2441,13 @@ body {
   margin-bottom: 0.5rem;
 }
 
+.peer-identicon {
+  width: 24px;
+  height: 24px;
+  border-radius: 4px;
+  flex-shrink: 0;
+}
+
 .peer-status-dot {
   width: 8px;
   height: 8px;
@@ -4487,6 +4528,17 @@ body {
   line-height: 1.6;
 }
 
+.inviter-greeting {
+  font-family: var(--font-serif);
+  font-size: 16px;
+  font-style: italic;
+  color: var(--text-primary);
+  border-left: 2px solid var(--gold);
+  padding: 8px 16px;
+  margin: 20px 0 0;
+  white-space: pre-wrap;
+}
+
 .request-message {
   font-family: var(--font-mono);
   font-size: 14px;
@@ -4651,6 +4703,23 @@ body {
   font-style: italic;
 }
 
+.contact-search {
+  width: 100%;
+  max-width: 320px;
+  padding: 0.5rem 0.75rem;
+  font-family: var(--font-mono);
+  font-size: 14px;
+  color: var(--text-primary);
+  background: var(--void-black);
+  border: 1px solid var(--void-border);
+  border-radius: 4px;
+}
+
+.contact-search:focus {
+  outline: none;
+  border-color: var(--gold);
+}
+
 .contact-grid {
   display: grid;
   grid-template-columns: repeat(auto-fill, minmax(120px, 1fr));
@@ -4720,6 +4789,37 @@ body {
   background: rgba(255, 255, 255, 0.3);
 }
 
+/* Favorite star - top right of portrait avatar */
+.contact-avatar .favorite-star {
+  position: absolute;
+  top: 4px;
+  right: 6px;
+  background: none;
+  border: none;
+  padding: 0;
+  font-size: 16px;
+  line-height: 1;
+  color: rgba(255, 255, 255, 0.3);
+  cursor: pointer;
+  opacity: 0;
+  transition: opacity var(--transition-fast), color var(--transition-fast);
+}
+
+.contact-card:hover .favorite-star,
+.contact-card.favorite .favorite-star {
+  opacity: 1;
+}
+
+.contact-card.favorite .favorite-star {
+  color: var(--gold);
+}
+
+.contact-name .name-collision-badge {
+  color: var(--danger);
+  margin-left: 4px;
+  cursor: help;
+}
+
 .contact-name {
   font-family: var(--font-mono);
   font-size: 14px;
@@ -7696,6 +7796,11 @@ body {
   line-height: 1.5;
 }
 
+.message-mention {
+  color: var(--gold);
+  font-weight: 600;
+}
+
 .message-bubble-time {
   font-size: 0.75rem;
   opacity: 0.7;